@@ -0,0 +1,28 @@
+/* Generated by napi-rs from src/lib.rs; keep in sync when the options change */
+
+export interface GenerateOptions {
+  /** Seed for deterministic generation, as a decimal string so all 64 bits survive */
+  seed?: string
+  /** UUID for deterministic generation (overrides seed) */
+  uuid?: string
+  /** Theme name, or a blend such as `mesos:blues:0.4` */
+  theme?: string
+  /** Number of shapes (1-25) */
+  shapes?: number
+  /** Grid density (2-8) */
+  gridSize?: number
+  /** Shape opacity (0.0-1.0) */
+  opacity?: number
+  /** Allow shapes to overlap with blended colors */
+  overlap?: boolean
+  /** Output width in pixels */
+  width?: number
+  /** Output height in pixels */
+  height?: number
+  /** Background color as hex, a CSS name, rgb() or hsl(); transparent when omitted */
+  background?: string
+}
+/** Generates a logo as an SVG document */
+export function generateSvg(options?: GenerateOptions | undefined | null): Promise<string>
+/** Generates a logo as PNG data */
+export function generatePng(options?: GenerateOptions | undefined | null): Promise<Buffer>
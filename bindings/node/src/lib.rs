@@ -0,0 +1,147 @@
+use hexlogogen::generator::color::parse_color;
+use hexlogogen::generator::{GeneratorConfig, Theme};
+use hexlogogen::{png, svg, utils};
+use napi::bindgen_prelude::{AsyncTask, Buffer};
+use napi::{Env, Error, Result, Task};
+use napi_derive::napi;
+
+/// Options shared by `generateSvg` and `generatePng`, all optional
+#[napi(object)]
+#[derive(Debug, Default)]
+pub struct GenerateOptions {
+    /// Seed for deterministic generation, as a decimal string so all 64 bits survive
+    pub seed: Option<String>,
+    /// UUID for deterministic generation (overrides seed)
+    pub uuid: Option<String>,
+    /// Theme name, or a blend such as `mesos:blues:0.4`
+    pub theme: Option<String>,
+    /// Number of shapes (1-25)
+    pub shapes: Option<u32>,
+    /// Grid density (2-8)
+    pub grid_size: Option<u32>,
+    /// Shape opacity (0.0-1.0)
+    pub opacity: Option<f64>,
+    /// Allow shapes to overlap with blended colors
+    pub overlap: Option<bool>,
+    /// Output width in pixels
+    pub width: Option<u32>,
+    /// Output height in pixels
+    pub height: Option<u32>,
+    /// Background color as hex, a CSS name, rgb() or hsl(); transparent when omitted
+    pub background: Option<String>,
+}
+
+/// A validated request, ready to render off the JavaScript thread
+struct Request {
+    config: GeneratorConfig,
+    width: u32,
+    height: u32,
+    options: svg::SvgOptions,
+}
+
+impl Request {
+    /// Applies the CLI's defaults and rejects out-of-range values instead of clamping them
+    fn new(options: GenerateOptions) -> Result<Self> {
+        let reason = |e: Box<dyn std::error::Error>| Error::from_reason(e.to_string());
+        let small = |name: &str, value: Option<u32>, default: u8| -> Result<u8> {
+            let value = value.unwrap_or(default as u32);
+            u8::try_from(value).map_err(|_| Error::from_reason(format!("{} {} is out of range", name, value)))
+        };
+
+        let seed = match (&options.uuid, &options.seed) {
+            (Some(uuid), _) => Some(utils::uuid_to_seed(uuid).map_err(reason)?),
+            (None, Some(seed)) => Some(
+                seed.trim()
+                    .parse::<u64>()
+                    .map_err(|_| Error::from_reason(format!("Invalid seed '{}' (expected a non-negative integer)", seed)))?,
+            ),
+            (None, None) => None,
+        };
+        let theme = options
+            .theme
+            .as_deref()
+            .unwrap_or("mesos")
+            .parse::<Theme>()
+            .map_err(Error::from_reason)?;
+        let background = match &options.background {
+            Some(color) => Some(parse_color(color).map_err(reason)?),
+            None => None,
+        };
+
+        let config = GeneratorConfig::try_new(
+            small("gridSize", options.grid_size, 4)?,
+            small("shapes", options.shapes, 4)?,
+            options.opacity.unwrap_or(0.8) as f32,
+            seed,
+        )
+        .map_err(reason)?
+        .with_theme(theme)
+        .with_allow_overlap(options.overlap.unwrap_or(true));
+
+        Ok(Self {
+            config,
+            width: options.width.unwrap_or(512),
+            height: options.height.unwrap_or(512),
+            options: svg::SvgOptions {
+                background,
+                ..svg::SvgOptions::default()
+            },
+        })
+    }
+
+    fn svg(&self) -> Result<String> {
+        let design = self.config.generate().map_err(|e| Error::from_reason(e.to_string()))?;
+        svg::design_to_svg(&design, self.width, self.height, &self.options)
+            .map_err(|e| Error::from_reason(e.to_string()))
+    }
+
+    fn png(&self) -> Result<Vec<u8>> {
+        let design = self.config.generate().map_err(|e| Error::from_reason(e.to_string()))?;
+        png::design_to_png(&design, self.width, self.height, &self.options)
+            .map_err(|e| Error::from_reason(e.to_string()))
+    }
+}
+
+/// Generates an SVG on the libuv thread pool
+pub struct GenerateSvg(Option<GenerateOptions>);
+
+impl Task for GenerateSvg {
+    type Output = String;
+    type JsValue = String;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        Request::new(self.0.take().unwrap_or_default())?.svg()
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+/// Generates a PNG on the libuv thread pool
+pub struct GeneratePng(Option<GenerateOptions>);
+
+impl Task for GeneratePng {
+    type Output = Vec<u8>;
+    type JsValue = Buffer;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        Request::new(self.0.take().unwrap_or_default())?.png()
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output.into())
+    }
+}
+
+/// Generates a logo as an SVG document
+#[napi]
+pub fn generate_svg(options: Option<GenerateOptions>) -> AsyncTask<GenerateSvg> {
+    AsyncTask::new(GenerateSvg(options))
+}
+
+/// Generates a logo as PNG data
+#[napi]
+pub fn generate_png(options: Option<GenerateOptions>) -> AsyncTask<GeneratePng> {
+    AsyncTask::new(GeneratePng(options))
+}
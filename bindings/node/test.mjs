@@ -0,0 +1,23 @@
+import assert from 'node:assert/strict'
+import { test } from 'node:test'
+import { createRequire } from 'node:module'
+
+const { generateSvg, generatePng } = createRequire(import.meta.url)('./index.js')
+
+test('the same seed gives the same SVG', async () => {
+  const first = await generateSvg({ seed: '42', theme: 'blues' })
+  assert.match(first, /^<svg/)
+  assert.equal(first, await generateSvg({ seed: '42', theme: 'blues' }))
+})
+
+test('PNG output is a PNG of the requested size', async () => {
+  const png = await generatePng({ seed: '7', width: 128, height: 64 })
+  assert.deepEqual([...png.subarray(1, 4)], [0x50, 0x4e, 0x47])
+  assert.equal(png.readUInt32BE(16), 128)
+  assert.equal(png.readUInt32BE(20), 64)
+})
+
+test('invalid options reject the promise', async () => {
+  await assert.rejects(generateSvg({ theme: 'plaid' }), /Unknown theme 'plaid'/)
+  await assert.rejects(generateSvg({ seed: 'abc' }), /Invalid seed/)
+})
@@ -0,0 +1,193 @@
+use super::named;
+use super::ColorManager;
+use crate::Result;
+
+/// Parses a CSS color and normalizes it to an uppercase hex string
+///
+/// Accepts hex (`#RGB`, `#RGBA`, `#RRGGBB`, `#RRGGBBAA`), CSS color names,
+/// `rgb()`/`rgba()` and `hsl()`/`hsla()` in both the comma and space separated
+/// syntaxes. Opaque colors become `#RRGGBB` and translucent ones `#RRGGBBAA`.
+pub fn parse_color(input: &str) -> Result<String> {
+    let color = input.trim().to_ascii_lowercase();
+    let invalid = |reason: &str| format!("Invalid color '{}': {}", input.trim(), reason);
+
+    let rgba = if color.starts_with('#') {
+        let rgb = parse_hex(&color).map_err(|_| invalid(HEX_FORMS))?;
+        (rgb.r, rgb.g, rgb.b, rgb.a)
+    } else if let Some(args) = function_args(&color, &["rgb", "rgba"]) {
+        parse_rgb_function(args).map_err(|reason| invalid(&reason))?
+    } else if let Some(args) = function_args(&color, &["hsl", "hsla"]) {
+        parse_hsl_function(args).map_err(|reason| invalid(&reason))?
+    } else {
+        let (r, g, b) = named::lookup(&color).ok_or_else(|| invalid("unknown color name"))?;
+        (r, g, b, 255)
+    };
+
+    Ok(ColorManager::rgba_to_hex(rgba.0, rgba.1, rgba.2, rgba.3))
+}
+
+const HEX_FORMS: &str = "expected #RGB, #RGBA, #RRGGBB or #RRGGBBAA";
+
+/// A color's channels, each 0-255; `a` is 255 for opaque colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+/// Parses a `#RGB`, `#RGBA`, `#RRGGBB` or `#RRGGBBAA` color; the `#` is optional
+pub fn parse_hex(hex: &str) -> Result<Rgb> {
+    let digits = hex.trim();
+    let digits = digits.strip_prefix('#').unwrap_or(digits);
+    if !digits.bytes().all(|c| c.is_ascii_hexdigit()) || ![3, 4, 6, 8].contains(&digits.len()) {
+        return Err(format!("Invalid hex color '{}': {}", hex.trim(), HEX_FORMS).into());
+    }
+
+    // Short forms repeat each digit, so #F80 is #FF8800
+    let width = if digits.len() <= 4 { 1 } else { 2 };
+    let mut channels = digits.as_bytes().chunks(width).map(|chunk| {
+        let value = chunk
+            .iter()
+            .fold(0, |value, &digit| value * 16 + (digit as char).to_digit(16).unwrap_or(0) as u8);
+        if width == 1 {
+            value * 17
+        } else {
+            value
+        }
+    });
+    Ok(Rgb {
+        r: channels.next().unwrap_or(0),
+        g: channels.next().unwrap_or(0),
+        b: channels.next().unwrap_or(0),
+        a: channels.next().unwrap_or(255),
+    })
+}
+
+/// Returns the argument text of `name(...)` for any of the given function names
+fn function_args<'a>(color: &'a str, names: &[&str]) -> Option<&'a str> {
+    let open = color.find('(')?;
+    if !names.contains(&color[..open].trim_end()) {
+        return None;
+    }
+    color[open + 1..].strip_suffix(')')
+}
+
+/// Splits function arguments into the color components and an optional alpha
+///
+/// Handles both `a, b, c[, alpha]` and `a b c[ / alpha]`.
+fn split_args(args: &str) -> std::result::Result<(Vec<&str>, Option<&str>), String> {
+    let (components, alpha) = match args.split_once('/') {
+        Some((components, alpha)) => (components, Some(alpha.trim())),
+        None => (args, None),
+    };
+
+    let mut parts: Vec<&str> = if components.contains(',') {
+        components.split(',').map(str::trim).collect()
+    } else {
+        components.split_whitespace().collect()
+    };
+
+    let alpha = match (alpha, parts.len()) {
+        (Some(alpha), 3) => Some(alpha),
+        (None, 4) => parts.pop(),
+        (None, 3) => None,
+        _ => return Err("expected three components and an optional alpha".to_string()),
+    };
+
+    Ok((parts, alpha))
+}
+
+/// Converts an optional alpha component (number or percentage) to 0-255
+fn parse_alpha(alpha: Option<&str>) -> std::result::Result<u8, String> {
+    match alpha {
+        None => Ok(255),
+        Some(alpha) => {
+            let value = parse_number_or_percent(alpha, 1.0)?.clamp(0.0, 1.0);
+            Ok((value * 255.0).round() as u8)
+        }
+    }
+}
+
+/// Parses a number, or a percentage of `scale`
+fn parse_number_or_percent(value: &str, scale: f64) -> std::result::Result<f64, String> {
+    let parsed = match value.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().map(|p| p / 100.0 * scale),
+        None => value.parse::<f64>(),
+    };
+
+    match parsed {
+        Ok(number) if number.is_finite() => Ok(number),
+        _ => Err(format!("'{}' is not a number", value)),
+    }
+}
+
+fn parse_rgb_function(args: &str) -> std::result::Result<(u8, u8, u8, u8), String> {
+    let (parts, alpha) = split_args(args)?;
+    let alpha = parse_alpha(alpha)?;
+
+    let mut channels = [0u8; 3];
+    for (channel, part) in channels.iter_mut().zip(&parts) {
+        *channel = parse_number_or_percent(part, 255.0)?.round().clamp(0.0, 255.0) as u8;
+    }
+
+    Ok((channels[0], channels[1], channels[2], alpha))
+}
+
+fn parse_hsl_function(args: &str) -> std::result::Result<(u8, u8, u8, u8), String> {
+    let (parts, alpha) = split_args(args)?;
+    let alpha = parse_alpha(alpha)?;
+
+    let hue = parse_hue(parts[0])?;
+    let saturation = parse_percent(parts[1])?;
+    let lightness = parse_percent(parts[2])?;
+
+    let (r, g, b) = hsl_to_rgb(hue, saturation, lightness);
+    Ok((r, g, b, alpha))
+}
+
+/// Parses a hue in degrees, accepting the `deg`, `rad` and `turn` units
+fn parse_hue(value: &str) -> std::result::Result<f64, String> {
+    let (number, scale) = if let Some(n) = value.strip_suffix("deg") {
+        (n, 1.0)
+    } else if let Some(n) = value.strip_suffix("rad") {
+        (n, 180.0 / std::f64::consts::PI)
+    } else if let Some(n) = value.strip_suffix("turn") {
+        (n, 360.0)
+    } else {
+        (value, 1.0)
+    };
+
+    match number.trim().parse::<f64>() {
+        Ok(hue) if hue.is_finite() => Ok((hue * scale).rem_euclid(360.0)),
+        _ => Err(format!("'{}' is not a valid hue", value)),
+    }
+}
+
+fn parse_percent(value: &str) -> std::result::Result<f64, String> {
+    if !value.ends_with('%') {
+        return Err(format!("'{}' must be a percentage", value));
+    }
+    Ok(parse_number_or_percent(value, 1.0)?.clamp(0.0, 1.0))
+}
+
+/// Converts HSL (hue in degrees, saturation and lightness in 0-1) to RGB
+pub(super) fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> (u8, u8, u8) {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let sector = hue / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    let m = lightness - chroma / 2.0;
+    let to_u8 = |v: f64| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    (to_u8(r), to_u8(g), to_u8(b))
+}
@@ -0,0 +1,235 @@
+use super::ColorManager;
+use std::fmt::Write;
+
+/// File formats a palette can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteFormat {
+    /// Adobe Swatch Exchange
+    Ase,
+    /// GIMP palette, also read by Inkscape and Krita
+    Gpl,
+    /// A `tailwind.config.js` theme extension
+    Tailwind,
+    /// CSS custom properties on `:root`
+    Css,
+}
+
+impl PaletteFormat {
+    /// Names accepted by [`PaletteFormat::from_str`](std::str::FromStr)
+    pub fn names() -> Vec<&'static str> {
+        vec!["ase", "gpl", "tailwind", "css"]
+    }
+
+    /// Suffix for the exported file, after the palette name
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PaletteFormat::Ase => "ase",
+            PaletteFormat::Gpl => "gpl",
+            PaletteFormat::Tailwind => "tailwind.js",
+            PaletteFormat::Css => "css",
+        }
+    }
+}
+
+impl std::fmt::Display for PaletteFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaletteFormat::Ase => write!(f, "ase"),
+            PaletteFormat::Gpl => write!(f, "gpl"),
+            PaletteFormat::Tailwind => write!(f, "tailwind"),
+            PaletteFormat::Css => write!(f, "css"),
+        }
+    }
+}
+
+impl std::str::FromStr for PaletteFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ase" => Ok(PaletteFormat::Ase),
+            "gpl" | "gimp" => Ok(PaletteFormat::Gpl),
+            "tailwind" => Ok(PaletteFormat::Tailwind),
+            "css" => Ok(PaletteFormat::Css),
+            _ => Err(format!(
+                "Unknown palette format '{}' (expected one of: {})",
+                s,
+                Self::names().join(", ")
+            )),
+        }
+    }
+}
+
+/// A palette color with a name usable as a CSS identifier
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedColor {
+    pub name: String,
+    pub hex: String,
+}
+
+/// Names each color after its nearest CSS color, numbering repeats
+///
+/// Two blues that are both closest to `steelblue` become `steelblue` and
+/// `steelblue-2`, so every name is unique and stable for a given palette.
+pub fn name_colors(colors: &[String]) -> Vec<NamedColor> {
+    let mut named: Vec<NamedColor> = Vec::with_capacity(colors.len());
+    for hex in colors {
+        let base = ColorManager::nearest_named_color(hex);
+        let repeats = named
+            .iter()
+            .filter(|color| color.name == base || color.name.starts_with(&format!("{}-", base)))
+            .count();
+        let name = match repeats {
+            0 => base.to_string(),
+            n => format!("{}-{}", base, n + 1),
+        };
+        named.push(NamedColor {
+            name,
+            hex: hex.to_uppercase(),
+        });
+    }
+    named
+}
+
+/// Lowercases a palette name and replaces anything but letters and digits with dashes
+fn slug(name: &str) -> String {
+    let slug: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let slug = slug.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-");
+    if slug.is_empty() {
+        "palette".to_string()
+    } else {
+        slug
+    }
+}
+
+/// Writes `colors` as a palette called `name` in the given format
+///
+/// ASE and GPL files hold opaque colors, so any alpha is dropped there; the
+/// Tailwind and CSS output keeps the full hex value.
+pub fn export(colors: &[NamedColor], name: &str, format: PaletteFormat) -> Vec<u8> {
+    match format {
+        PaletteFormat::Ase => to_ase(colors, name),
+        PaletteFormat::Gpl => to_gpl(colors, name).into_bytes(),
+        PaletteFormat::Tailwind => to_tailwind(colors, name).into_bytes(),
+        PaletteFormat::Css => to_css(colors, name).into_bytes(),
+    }
+}
+
+fn to_gpl(colors: &[NamedColor], name: &str) -> String {
+    let mut out = format!("GIMP Palette\nName: {}\nColumns: {}\n#\n", name, colors.len().min(16));
+    for color in colors {
+        let (r, g, b) = ColorManager::hex_to_rgb(&color.hex);
+        let _ = writeln!(out, "{:3} {:3} {:3}\t{}", r, g, b, color.name);
+    }
+    out
+}
+
+fn to_tailwind(colors: &[NamedColor], name: &str) -> String {
+    let mut out = String::from("module.exports = {\n  theme: {\n    extend: {\n      colors: {\n");
+    let _ = writeln!(out, "        '{}': {{", slug(name));
+    for color in colors {
+        let _ = writeln!(out, "          '{}': '{}',", color.name, color.hex);
+    }
+    out.push_str("        },\n      },\n    },\n  },\n};\n");
+    out
+}
+
+fn to_css(colors: &[NamedColor], name: &str) -> String {
+    let prefix = slug(name);
+    let mut out = String::from(":root {\n");
+    for color in colors {
+        let _ = writeln!(out, "  --{}-{}: {};", prefix, color.name, color.hex);
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Builds an ASE file with the colors in one group named after the palette
+fn to_ase(colors: &[NamedColor], name: &str) -> Vec<u8> {
+    // Names are null-terminated UTF-16, prefixed by their length in code units
+    let utf16 = |text: &str| {
+        let units: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut bytes = (units.len() as u16).to_be_bytes().to_vec();
+        bytes.extend(units.iter().flat_map(|unit| unit.to_be_bytes()));
+        bytes
+    };
+    let block = |out: &mut Vec<u8>, kind: u16, body: &[u8]| {
+        out.extend(kind.to_be_bytes());
+        out.extend((body.len() as u32).to_be_bytes());
+        out.extend(body);
+    };
+
+    let mut out = b"ASEF".to_vec();
+    out.extend([0, 1, 0, 0]);
+    out.extend((colors.len() as u32 + 2).to_be_bytes());
+
+    block(&mut out, 0xC001, &utf16(name));
+    for color in colors {
+        let (r, g, b) = ColorManager::hex_to_rgb(&color.hex);
+        let mut body = utf16(&color.name);
+        body.extend(b"RGB ");
+        for channel in [r, g, b] {
+            body.extend((channel as f32 / 255.0).to_be_bytes());
+        }
+        // Color type 2 is a normal, non-spot color
+        body.extend(2u16.to_be_bytes());
+        block(&mut out, 0x0001, &body);
+    }
+    block(&mut out, 0xC002, &[]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::import;
+    use super::*;
+
+    fn sample() -> Vec<NamedColor> {
+        name_colors(&["#3960A9".to_string(), "#3A62AB".to_string(), "#FFCC09".to_string()])
+    }
+
+    #[test]
+    fn test_palette_format_names() {
+        assert_eq!("Tailwind".parse::<PaletteFormat>().unwrap(), PaletteFormat::Tailwind);
+        assert_eq!(PaletteFormat::Css.to_string(), "css");
+        assert!("sketch".parse::<PaletteFormat>().is_err());
+    }
+
+    #[test]
+    fn test_name_colors() {
+        let colors = sample();
+        assert_ne!(colors[0].name, colors[1].name);
+        assert_eq!(colors[1].name, format!("{}-2", colors[0].name));
+        assert_eq!(slug("My Brand!"), "my-brand");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let colors = sample();
+        let hexes: Vec<String> = colors.iter().map(|color| color.hex.clone()).collect();
+
+        let gpl = String::from_utf8(export(&colors, "Brand", PaletteFormat::Gpl)).unwrap();
+        let imported = import::from_gpl(&gpl).unwrap();
+        assert_eq!(imported.name, "Brand");
+        assert_eq!(imported.colors, hexes);
+
+        let ase = export(&colors, "Brand", PaletteFormat::Ase);
+        assert_eq!(import::from_ase(&ase, "Brand").unwrap().colors, hexes);
+    }
+
+    #[test]
+    fn test_code_snippets() {
+        let colors = sample();
+        let css = String::from_utf8(export(&colors, "My Brand", PaletteFormat::Css)).unwrap();
+        assert!(css.starts_with(":root {"));
+        assert!(css.contains(&format!("--my-brand-{}: #FFCC09;", colors[2].name)));
+
+        let tailwind = String::from_utf8(export(&colors, "My Brand", PaletteFormat::Tailwind)).unwrap();
+        assert!(tailwind.contains("'my-brand': {"));
+        assert!(tailwind.contains("'#3960A9'"));
+    }
+}
@@ -0,0 +1,112 @@
+use std::cmp::Reverse;
+
+/// Colors tried before the search gives up, bounding its time on graphs it can't settle quickly
+pub const MAX_COLORING_STEPS: usize = 50_000;
+
+/// Colors a graph with at most `colors` colors so that no two neighbors share one
+///
+/// `adjacency[node]` lists each node's neighbors, in both directions. Nodes are
+/// colored most constrained first (fewest colors left, then most neighbors) and
+/// the search backtracks when a node has no color left, so unlike a greedy pass
+/// it finds a coloring whenever one exists. Returns the color index of every
+/// node, or `None` when there is no such coloring or none turned up within
+/// [`MAX_COLORING_STEPS`].
+pub fn color_graph(adjacency: &[Vec<usize>], colors: usize) -> Option<Vec<usize>> {
+    let mut assignment = vec![None; adjacency.len()];
+    let mut steps = 0;
+    if !assign_next(adjacency, colors, &mut assignment, &mut steps) {
+        return None;
+    }
+    assignment.into_iter().collect()
+}
+
+/// Colors the most constrained uncolored node and recurses; false when every choice fails
+fn assign_next(adjacency: &[Vec<usize>], colors: usize, assignment: &mut [Option<usize>], steps: &mut usize) -> bool {
+    // Colors are interchangeable, so a node never needs more than one color past those in use
+    let in_use = assignment.iter().flatten().max().map_or(0, |&max| max + 1);
+    let free = |node: usize| -> Vec<usize> {
+        (0..colors.min(in_use + 1))
+            .filter(|&color| adjacency[node].iter().all(|&neighbor| assignment[neighbor] != Some(color)))
+            .collect()
+    };
+
+    let next = (0..adjacency.len())
+        .filter(|&node| assignment[node].is_none())
+        .map(|node| (node, free(node)))
+        .min_by_key(|(node, free)| (free.len(), Reverse(adjacency[*node].len())));
+    let Some((node, choices)) = next else {
+        return true;
+    };
+
+    for color in choices {
+        *steps += 1;
+        if *steps > MAX_COLORING_STEPS {
+            break;
+        }
+        assignment[node] = Some(color);
+        if assign_next(adjacency, colors, assignment, steps) {
+            return true;
+        }
+    }
+    assignment[node] = None;
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Undirected adjacency lists from an edge list
+    fn graph(nodes: usize, edges: &[(usize, usize)]) -> Vec<Vec<usize>> {
+        let mut adjacency = vec![Vec::new(); nodes];
+        for &(a, b) in edges {
+            adjacency[a].push(b);
+            adjacency[b].push(a);
+        }
+        adjacency
+    }
+
+    fn assert_proper(adjacency: &[Vec<usize>], colors: usize) {
+        let coloring = color_graph(adjacency, colors).expect("a coloring exists");
+        for (node, neighbors) in adjacency.iter().enumerate() {
+            assert!(coloring[node] < colors);
+            assert!(neighbors.iter().all(|&neighbor| coloring[neighbor] != coloring[node]));
+        }
+    }
+
+    #[test]
+    fn test_color_graph_adversarial() {
+        // A crown graph: greedy coloring in node order uses a color per pair,
+        // but it's bipartite
+        let n = 8;
+        let crown: Vec<(usize, usize)> = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (2 * i, 2 * j + 1)))
+            .collect();
+        assert_proper(&graph(2 * n, &crown), 2);
+
+        let complete: Vec<(usize, usize)> = (0..4).flat_map(|a| (a + 1..4).map(move |b| (a, b))).collect();
+        assert_eq!(color_graph(&graph(4, &complete), 3), None);
+        assert_proper(&graph(4, &complete), 4);
+
+        let cycle: Vec<(usize, usize)> = (0..5).map(|i| (i, (i + 1) % 5)).collect();
+        assert_eq!(color_graph(&graph(5, &cycle), 2), None);
+        assert_proper(&graph(5, &cycle), 3);
+
+        // An odd wheel needs four colors without containing four mutual neighbors
+        let wheel: Vec<(usize, usize)> = cycle.iter().copied().chain((0..5).map(|i| (i, 5))).collect();
+        assert_eq!(color_graph(&graph(6, &wheel), 3), None);
+        assert_proper(&graph(6, &wheel), 4);
+
+        // The Grötzsch graph has no triangles at all and still needs four
+        let grotzsch: Vec<(usize, usize)> = cycle
+            .iter()
+            .copied()
+            .chain((0..5).flat_map(|i| [(i + 5, (i + 1) % 5), (i + 5, (i + 4) % 5), (i + 5, 10)]))
+            .collect();
+        assert_eq!(color_graph(&graph(11, &grotzsch), 3), None);
+        assert_proper(&graph(11, &grotzsch), 4);
+
+        assert_eq!(color_graph(&[], 0), Some(Vec::new()));
+        assert_eq!(color_graph(&graph(1, &[]), 0), None);
+    }
+}
@@ -0,0 +1,377 @@
+use super::{parse_color, ColorManager, Theme};
+use crate::Result;
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+
+/// Most colors kept from one imported palette
+pub const MAX_COLORS: usize = 64;
+
+/// Colors read from a palette file or URL
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedPalette {
+    pub name: String,
+    /// Normalized uppercase hex colors, in file order, without duplicates
+    pub colors: Vec<String>,
+    /// Relative frequency of each color; empty when all are equally likely
+    pub weights: Vec<u32>,
+}
+
+impl ImportedPalette {
+    fn new(name: &str, colors: Vec<String>, source: &str) -> Result<Self> {
+        Self::weighted(name, colors.into_iter().map(|color| (color, 1)).collect(), source)
+    }
+
+    /// Builds a palette whose repeated colors add up their weights
+    fn weighted(name: &str, entries: Vec<(String, u32)>, source: &str) -> Result<Self> {
+        let mut unique: Vec<(String, u32)> = Vec::new();
+        for (color, weight) in entries {
+            match unique.iter_mut().find(|(existing, _)| *existing == color) {
+                Some((_, total)) => *total = total.saturating_add(weight),
+                None => unique.push((color, weight)),
+            }
+        }
+        if unique.len() < 2 {
+            return Err(format!("{} has {} color(s); a palette needs at least 2", source, unique.len()).into());
+        }
+        unique.truncate(MAX_COLORS);
+
+        let (colors, mut weights): (Vec<String>, Vec<u32>) = unique.into_iter().unzip();
+        if weights.iter().all(|&weight| weight == weights[0]) {
+            weights.clear();
+        }
+
+        let name = name.trim();
+        Ok(Self {
+            name: if name.is_empty() { "custom".to_string() } else { name.to_string() },
+            colors,
+            weights,
+        })
+    }
+
+    /// A theme that picks from these colors
+    pub fn into_theme(self) -> Theme {
+        Theme::Custom {
+            name: self.name,
+            colors: self.colors,
+            weights: self.weights,
+        }
+    }
+}
+
+/// Parses a GIMP palette: a `GIMP Palette` header, then one `R G B name` line per color
+pub fn from_gpl(text: &str) -> Result<ImportedPalette> {
+    let mut lines = text.lines();
+    if lines.next().map(str::trim) != Some("GIMP Palette") {
+        return Err("Invalid GIMP palette: missing 'GIMP Palette' header".into());
+    }
+
+    let mut name = "";
+    let mut colors = Vec::new();
+    for (number, line) in lines.enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(value) = line.strip_prefix("Name:") {
+            name = value;
+            continue;
+        }
+        if line.starts_with("Columns:") {
+            continue;
+        }
+
+        let channels: Vec<u8> = line
+            .split_whitespace()
+            .take(3)
+            .map(|channel| channel.parse::<u8>())
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|_| format!("Invalid GIMP palette: bad color on line {}: '{}'", number + 2, line))?;
+        if channels.len() < 3 {
+            return Err(format!("Invalid GIMP palette: bad color on line {}: '{}'", number + 2, line).into());
+        }
+        colors.push(ColorManager::rgba_to_hex(channels[0], channels[1], channels[2], 255));
+    }
+
+    ImportedPalette::new(name, colors, "GIMP palette")
+}
+
+/// Parses an Adobe Swatch Exchange file
+///
+/// RGB, CMYK and gray swatches are read, in or out of groups; LAB swatches
+/// are rejected since converting them needs the document's white point.
+pub fn from_ase(data: &[u8], name: &str) -> Result<ImportedPalette> {
+    let invalid = |reason: &str| format!("Invalid ASE file: {}", reason);
+    let mut reader = Reader { data, position: 0 };
+
+    if reader.take(4) != Some(b"ASEF".as_slice()) {
+        return Err(invalid("missing ASEF signature").into());
+    }
+    reader.take(4).ok_or_else(|| invalid("truncated header"))?;
+    let blocks = reader.u32().ok_or_else(|| invalid("truncated header"))?;
+
+    let mut colors = Vec::new();
+    for _ in 0..blocks {
+        let kind = reader.u16().ok_or_else(|| invalid("truncated block"))?;
+        let length = reader.u32().ok_or_else(|| invalid("truncated block"))? as usize;
+        let body = reader.take(length).ok_or_else(|| invalid("truncated block"))?;
+
+        // Only color entries matter; group start and end blocks are skipped
+        if kind != 0x0001 {
+            continue;
+        }
+        let mut entry = Reader { data: body, position: 0 };
+        let name_units = entry.u16().ok_or_else(|| invalid("truncated color name"))? as usize;
+        entry.take(name_units * 2).ok_or_else(|| invalid("truncated color name"))?;
+        let model = entry.take(4).ok_or_else(|| invalid("truncated color model"))?;
+
+        let mut values = |count: usize| -> Result<Vec<f32>> {
+            (0..count)
+                .map(|_| entry.f32().ok_or_else(|| invalid("truncated color values").into()))
+                .collect()
+        };
+        let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let color = match model {
+            b"RGB " => {
+                let v = values(3)?;
+                ColorManager::rgba_to_hex(channel(v[0]), channel(v[1]), channel(v[2]), 255)
+            }
+            b"CMYK" => {
+                let v = values(4)?;
+                let rgb = |c: f32| channel((1.0 - c) * (1.0 - v[3]));
+                ColorManager::rgba_to_hex(rgb(v[0]), rgb(v[1]), rgb(v[2]), 255)
+            }
+            b"Gray" => {
+                let gray = channel(values(1)?[0]);
+                ColorManager::rgba_to_hex(gray, gray, gray, 255)
+            }
+            b"LAB " => return Err(invalid("LAB swatches are not supported; export the swatches as RGB").into()),
+            other => {
+                return Err(invalid(&format!("unknown color model '{}'", String::from_utf8_lossy(other))).into())
+            }
+        };
+        colors.push(color);
+    }
+
+    ImportedPalette::new(name, colors, "ASE file")
+}
+
+/// Reads the colors from a coolors.co palette URL
+///
+/// Both `https://coolors.co/264653-2a9d8f-e9c46a` and the
+/// `https://coolors.co/palette/...` form are accepted. Nothing is fetched;
+/// the colors are part of the URL itself.
+pub fn from_coolors_url(url: &str) -> Result<ImportedPalette> {
+    let invalid = |reason: &str| format!("Invalid coolors URL '{}': {}", url, reason);
+    let rest = url
+        .trim()
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches("www.");
+    let path = rest
+        .strip_prefix("coolors.co/")
+        .ok_or_else(|| invalid("expected a https://coolors.co/... address"))?;
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    let codes = path.trim_start_matches("palette/").trim_end_matches('/');
+
+    let colors = codes
+        .split('-')
+        .map(|code| {
+            if code.len() != 6 {
+                return Err(invalid(&format!("'{}' is not a 6-digit hex color", code)));
+            }
+            parse_color(&format!("#{}", code)).map_err(|_| invalid(&format!("'{}' is not a 6-digit hex color", code)))
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    ImportedPalette::new("coolors", colors, "Coolors URL")
+}
+
+/// Parses a JSON palette: a list of colors, or an object of colors and weights
+///
+/// `{"#4285F4": 6, "#EA4335": 3, "#FBBC05": 1}` picks the first color six
+/// times as often as the last. Weights are positive integers and keep the
+/// order they're written in.
+pub fn from_json(text: &str, name: &str) -> Result<ImportedPalette> {
+    let entries = serde_json::from_str::<WeightedColors>(text)
+        .map_err(|e| format!("Invalid JSON palette: {}", e))?
+        .0;
+    let entries = entries
+        .into_iter()
+        .map(|(color, weight)| {
+            if weight == 0 {
+                return Err(format!("Invalid JSON palette: weight of {} must be at least 1", color));
+            }
+            let color = parse_color(&color).map_err(|e| format!("Invalid JSON palette: {}", e))?;
+            Ok((color, weight))
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    ImportedPalette::weighted(name, entries, "JSON palette")
+}
+
+/// Colors of a JSON palette with their weights, in document order
+struct WeightedColors(Vec<(String, u32)>);
+
+impl<'de> Deserialize<'de> for WeightedColors {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct ColorsVisitor;
+
+        impl<'de> Visitor<'de> for ColorsVisitor {
+            type Value = WeightedColors;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "a list of colors or an object of colors and weights")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error> {
+                let mut entries = Vec::new();
+                while let Some(color) = seq.next_element::<String>()? {
+                    entries.push((color, 1));
+                }
+                Ok(WeightedColors(entries))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<Self::Value, A::Error> {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry::<String, u32>()? {
+                    entries.push(entry);
+                }
+                Ok(WeightedColors(entries))
+            }
+        }
+
+        deserializer.deserialize_any(ColorsVisitor)
+    }
+}
+
+/// Big-endian reader over an ASE file
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.position..self.position.checked_add(count)?)?;
+        self.position += count;
+        Some(bytes)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn f32(&mut self) -> Option<f32> {
+        self.u32().map(f32::from_bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds an ASE file with one color entry per (model, values) pair
+    fn ase(entries: &[(&[u8; 4], &[f32])]) -> Vec<u8> {
+        let mut data = b"ASEF\x00\x01\x00\x00".to_vec();
+        data.extend((entries.len() as u32 + 2).to_be_bytes());
+
+        // Wrap the colors in a group like Adobe's apps do
+        data.extend([0xC0, 0x01, 0, 0, 0, 6, 0, 2, 0, b'g', 0, 0]);
+        for (model, values) in entries {
+            let mut body = vec![0, 2, 0, b'c', 0, 0];
+            body.extend(model.iter());
+            for value in values.iter() {
+                body.extend(value.to_be_bytes());
+            }
+            body.extend([0, 2]);
+            data.extend([0, 1]);
+            data.extend((body.len() as u32).to_be_bytes());
+            data.extend(body);
+        }
+        data.extend([0xC0, 0x02, 0, 0, 0, 0]);
+        data
+    }
+
+    #[test]
+    fn test_gpl() {
+        let text = "GIMP Palette\nName: Sunset\nColumns: 3\n#\n255 204   9\tYellow\n 57  96 169 Blue\n255 204 9 Again\n";
+        let palette = from_gpl(text).unwrap();
+        assert_eq!(palette.name, "Sunset");
+        assert_eq!(palette.colors, vec!["#FFCC09", "#3960A9"]);
+
+        assert!(from_gpl("255 0 0\n0 0 255\n").is_err());
+        assert!(from_gpl("GIMP Palette\n300 0 0 Red\n0 0 0 Black\n").is_err());
+        assert!(from_gpl("GIMP Palette\n255 0 0 Red\n").is_err());
+    }
+
+    #[test]
+    fn test_ase() {
+        let data = ase(&[
+            (b"RGB ", &[1.0, 0.8, 0.0]),
+            (b"CMYK", &[0.0, 1.0, 1.0, 0.0]),
+            (b"Gray", &[0.5]),
+        ]);
+        let palette = from_ase(&data, "brand").unwrap();
+        assert_eq!(palette.name, "brand");
+        assert_eq!(palette.colors, vec!["#FFCC00", "#FF0000", "#808080"]);
+
+        assert!(from_ase(&data[..data.len() - 20], "brand").is_err());
+        assert!(from_ase(b"GIMP", "brand").is_err());
+        let lab = ase(&[(b"LAB ", &[0.5, 10.0, 10.0]), (b"RGB ", &[0.0, 0.0, 0.0])]);
+        assert!(from_ase(&lab, "brand").unwrap_err().to_string().contains("LAB"));
+    }
+
+    #[test]
+    fn test_coolors_url() {
+        let palette = from_coolors_url("https://coolors.co/264653-2a9d8f-e9c46a-f4a261-e76f51").unwrap();
+        assert_eq!(palette.colors.len(), 5);
+        assert_eq!(palette.colors[0], "#264653");
+
+        let palette = from_coolors_url("coolors.co/palette/264653-2a9d8f?ref=x").unwrap();
+        assert_eq!(palette.colors, vec!["#264653", "#2A9D8F"]);
+
+        assert!(from_coolors_url("https://example.com/264653-2a9d8f").is_err());
+        assert!(from_coolors_url("https://coolors.co/264653-zzzzzz").is_err());
+    }
+
+    #[test]
+    fn test_json() {
+        let palette = from_json(r##"{"#4285f4": 6, "red": 3, "#FBBC05": 1}"##, "brand").unwrap();
+        assert_eq!(palette.colors, vec!["#4285F4", "#FF0000", "#FBBC05"]);
+        assert_eq!(palette.weights, vec![6, 3, 1]);
+
+        // Plain lists and equal weights stay unweighted
+        let palette = from_json(r##"["#264653", "#2A9D8F", "#264653"]"##, "list").unwrap();
+        assert_eq!(palette.colors, vec!["#264653", "#2A9D8F"]);
+        assert_eq!(palette.weights, vec![2, 1]);
+        assert!(from_json(r##"{"#264653": 2, "#2A9D8F": 2}"##, "even").unwrap().weights.is_empty());
+
+        assert!(from_json(r##"{"#264653": 0, "#2A9D8F": 1}"##, "zero").is_err());
+        assert!(from_json(r##"{"#264653": 1.5, "#2A9D8F": 1}"##, "fraction").is_err());
+        assert!(from_json(r##"{"nope": 1, "#2A9D8F": 1}"##, "bad").is_err());
+    }
+
+    #[test]
+    fn test_weighted_picking() {
+        let theme = from_json(r##"{"#4285F4": 6, "#EA4335": 3, "#FBBC05": 1}"##, "brand")
+            .unwrap()
+            .into_theme();
+        let mut manager = ColorManager::with_theme(theme, Some(3));
+        let picks = manager.get_random_colors(2000);
+        let share = |color: &str| picks.iter().filter(|&pick| pick == color).count() as f64 / picks.len() as f64;
+        assert!((share("#4285F4") - 0.6).abs() < 0.05);
+        assert!((share("#EA4335") - 0.3).abs() < 0.05);
+        assert!((share("#FBBC05") - 0.1).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_into_theme() {
+        let palette = from_coolors_url("https://coolors.co/264653-2a9d8f").unwrap();
+        let theme = palette.into_theme();
+        assert_eq!(theme.to_string(), "coolors");
+        assert_eq!(ColorManager::theme_palette(&theme), vec!["#264653", "#2A9D8F"]);
+    }
+}
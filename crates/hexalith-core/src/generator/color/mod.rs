@@ -0,0 +1,1583 @@
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashMap;
+
+/// Hue step between synthesized colors; it never repeats a hue, so any count stays spread out
+const GOLDEN_ANGLE: f64 = 137.507_764;
+
+mod css;
+pub mod export;
+mod graph;
+pub mod import;
+pub mod named;
+mod oklab;
+mod registry;
+
+pub use css::{parse_color, parse_hex, Rgb};
+pub use registry::{register_theme, unregister_theme};
+
+/// Manages color selection and blending for logo generation
+pub struct ColorManager {
+    palette: Vec<String>,
+    /// Relative frequency of each palette color; empty when all are equally likely
+    weights: Vec<u32>,
+    rng: ChaCha8Rng,
+}
+
+/// Available color themes for logo generation
+#[derive(Debug, Clone, PartialEq)]
+pub enum Theme {
+    Mesos,   // Original Mesos style colors
+    Google,  // Google brand colors
+    Blues,   // Blue color theme
+    Greens,  // Green color theme
+    Reds,    // Red color theme
+    Purples, // Purple color theme
+    Rainbow, // All colors of the rainbow
+    /// A palette part way between two themes, written `mesos:blues:0.4`
+    Blend(Box<Theme>, Box<Theme>, f32),
+    /// A palette imported from a file or URL, or registered with [`register_theme`]
+    ///
+    /// `weights` gives each color's relative frequency, e.g. 6, 3 and 1 for a
+    /// 60/30/10 split; it's empty when every color is equally likely.
+    Custom {
+        name: String,
+        colors: Vec<String>,
+        weights: Vec<u32>,
+    },
+}
+
+impl std::fmt::Display for Theme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Theme::Mesos => write!(f, "mesos"),
+            Theme::Google => write!(f, "google"),
+            Theme::Blues => write!(f, "blues"),
+            Theme::Greens => write!(f, "greens"),
+            Theme::Reds => write!(f, "reds"),
+            Theme::Purples => write!(f, "purples"),
+            Theme::Rainbow => write!(f, "rainbow"),
+            Theme::Blend(from, to, t) => write!(f, "{}:{}:{}", from, to, t),
+            Theme::Custom { name, .. } => write!(f, "{}", name),
+        }
+    }
+}
+
+impl Theme {
+    /// Checks that the theme has colors to pick from, on both sides of a blend
+    ///
+    /// Themes parsed from names and registered themes always do; this catches
+    /// ones built directly, such as a [`Theme::Custom`] with no colors.
+    pub fn validate(&self) -> crate::Result<()> {
+        match self {
+            Theme::Blend(from, to, t) => {
+                if !(0.0..=1.0).contains(t) {
+                    return Err(format!("Invalid blend amount '{}' in theme '{}' (expected 0.0-1.0)", t, self).into());
+                }
+                from.validate()?;
+                to.validate()
+            }
+            Theme::Custom { name, colors, .. } if colors.is_empty() => {
+                Err(format!("Theme '{}' needs at least one color", name).into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl std::str::FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+        if let [from, to, t] = name.split(':').collect::<Vec<_>>()[..] {
+            let t = t
+                .parse::<f32>()
+                .ok()
+                .filter(|t| (0.0..=1.0).contains(t))
+                .ok_or_else(|| format!("Invalid blend amount '{}' in theme '{}' (expected 0.0-1.0)", t, s))?;
+            let from = from.parse::<Theme>()?;
+            let to = to.parse::<Theme>()?;
+            return Ok(Theme::Blend(Box::new(from), Box::new(to), t));
+        }
+
+        match name.as_str() {
+            "mesos" => Ok(Theme::Mesos),
+            "google" => Ok(Theme::Google),
+            "blues" => Ok(Theme::Blues),
+            "greens" => Ok(Theme::Greens),
+            "reds" => Ok(Theme::Reds),
+            "purples" => Ok(Theme::Purples),
+            "rainbow" => Ok(Theme::Rainbow),
+            _ => registry::lookup(&name)
+                .map(|colors| Theme::Custom {
+                    name,
+                    colors,
+                    weights: Vec::new(),
+                })
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown theme '{}' (expected one of: {}, or a blend like mesos:blues:0.4)",
+                        s,
+                        ColorManager::available_themes().join(", ")
+                    )
+                }),
+        }
+    }
+}
+
+impl From<&str> for Theme {
+    fn from(s: &str) -> Self {
+        // Default to Mesos theme if unknown
+        s.parse().unwrap_or(Theme::Mesos)
+    }
+}
+
+/// The color space overlaps and theme blends are mixed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendSpace {
+    /// OKLab for mixing and OKLCH for interpolation, keeping midpoints bright
+    #[default]
+    Oklab,
+    /// Plain sRGB averaging for mixing and HSL for interpolation
+    Rgb,
+}
+
+impl BlendSpace {
+    /// Names accepted by [`BlendSpace::from_str`](std::str::FromStr)
+    pub fn names() -> Vec<&'static str> {
+        vec!["oklab", "rgb"]
+    }
+
+    /// Mixes two colors, `t` of the way from `color1` to `color2`
+    ///
+    /// Alpha is always mixed linearly.
+    pub fn mix(self, color1: &str, color2: &str, t: f64) -> String {
+        let t = t.clamp(0.0, 1.0);
+        let (r, g, b) = match self {
+            BlendSpace::Oklab => {
+                let (l1, a1, b1) = ColorManager::hex_to_oklab(color1);
+                let (l2, a2, b2) = ColorManager::hex_to_oklab(color2);
+                oklab::oklab_to_rgb(l1 + (l2 - l1) * t, a1 + (a2 - a1) * t, b1 + (b2 - b1) * t)
+            }
+            BlendSpace::Rgb => {
+                let (r1, g1, b1) = ColorManager::hex_to_rgb(color1);
+                let (r2, g2, b2) = ColorManager::hex_to_rgb(color2);
+                let channel = |x: u8, y: u8| (x as f64 + (y as f64 - x as f64) * t).round() as u8;
+                (channel(r1, r2), channel(g1, g2), channel(b1, b2))
+            }
+        };
+
+        let alpha1 = ColorManager::hex_to_rgba(color1).3 as f64;
+        let alpha2 = ColorManager::hex_to_rgba(color2).3 as f64;
+        ColorManager::rgba_to_hex(r, g, b, (alpha1 + (alpha2 - alpha1) * t).round() as u8)
+    }
+
+    /// Interpolates between two colors, taking the shorter way around the hue circle
+    pub fn interpolate(self, from: &str, to: &str, t: f64) -> String {
+        match self {
+            BlendSpace::Oklab => ColorManager::interpolate_oklch(from, to, t),
+            BlendSpace::Rgb => ColorManager::interpolate_hsl(from, to, t),
+        }
+    }
+}
+
+impl std::fmt::Display for BlendSpace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlendSpace::Oklab => write!(f, "oklab"),
+            BlendSpace::Rgb => write!(f, "rgb"),
+        }
+    }
+}
+
+impl std::str::FromStr for BlendSpace {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "oklab" => Ok(BlendSpace::Oklab),
+            "rgb" => Ok(BlendSpace::Rgb),
+            _ => Err(format!(
+                "Unknown blend space '{}' (expected one of: {})",
+                s,
+                Self::names().join(", ")
+            )),
+        }
+    }
+}
+
+impl ColorManager {
+    /// Picks colors from `palette`, or from the Mesos theme's when it's empty
+    pub fn new(palette: Vec<String>, seed: Option<u64>) -> Self {
+        if palette.is_empty() {
+            return Self::mesos_theme(seed);
+        }
+
+        // A given seed always reproduces the same sequence
+        let rng = match seed {
+            Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+            None => ChaCha8Rng::from_entropy(),
+        };
+
+        Self {
+            palette,
+            weights: Vec::new(),
+            rng,
+        }
+    }
+
+    /// Makes each palette color this relatively likely to be picked
+    ///
+    /// Ignored unless there is one positive weight per color.
+    pub fn with_weights(mut self, weights: Vec<u32>) -> Self {
+        if weights.len() == self.palette.len() && weights.iter().any(|&w| w > 0) {
+            self.weights = weights;
+        }
+        self
+    }
+
+    /// Relative frequency of each palette color; empty when all are equally likely
+    pub fn weights(&self) -> &[u32] {
+        &self.weights
+    }
+
+    /// Names of the themes that ship with the generator
+    pub fn builtin_themes() -> Vec<&'static str> {
+        vec!["mesos", "google", "blues", "greens", "reds", "purples", "rainbow"]
+    }
+
+    /// Get a list of available theme names, built-in ones first, then any registered with [`register_theme`]
+    pub fn available_themes() -> Vec<String> {
+        Self::builtin_themes()
+            .into_iter()
+            .map(String::from)
+            .chain(registry::names())
+            .collect()
+    }
+
+    /// Initialize with a specified theme
+    pub fn with_theme(theme: Theme, seed: Option<u64>) -> Self {
+        Self::with_theme_in(theme, seed, BlendSpace::default())
+    }
+
+    /// Initialize with a specified theme, blending themes in `space`
+    pub fn with_theme_in(theme: Theme, seed: Option<u64>, space: BlendSpace) -> Self {
+        match theme {
+            Theme::Mesos => Self::mesos_theme(seed),
+            Theme::Google => Self::google_theme(seed),
+            Theme::Blues => Self::blues_theme(seed),
+            Theme::Greens => Self::greens_theme(seed),
+            Theme::Reds => Self::reds_theme(seed),
+            Theme::Purples => Self::purples_theme(seed),
+            Theme::Rainbow => Self::rainbow_theme(seed),
+            Theme::Blend(from, to, t) => {
+                Self::new(Self::interpolate_themes_in(&from, &to, t as f64, space), seed)
+            }
+            Theme::Custom { colors, weights, .. } => Self::new(colors, seed).with_weights(weights),
+        }
+    }
+
+    /// The colors a theme picks from
+    pub fn theme_palette(theme: &Theme) -> Vec<String> {
+        // The seed only affects picking, not the palette itself
+        Self::with_theme(theme.clone(), Some(0)).palette
+    }
+
+    /// A theme's colors in brand-role order: primary, secondary, accent, then the rest
+    ///
+    /// Built-in themes name their roles; custom themes rank colors by weight,
+    /// keeping file order for equal weights, and blends keep palette order.
+    pub fn theme_roles(theme: &Theme) -> Vec<String> {
+        let manager = Self::with_theme(theme.clone(), Some(0));
+        let mut order: Vec<usize> = (0..manager.palette.len()).collect();
+        if !manager.weights.is_empty() {
+            order.sort_by_key(|&i| std::cmp::Reverse(manager.weights[i]));
+        }
+
+        // Palette indices of each built-in theme's primary, secondary and accent colors
+        let roles: &[usize] = match theme {
+            Theme::Mesos => &[7, 0, 2],
+            Theme::Google => &[0, 1, 2, 3],
+            Theme::Blues | Theme::Greens | Theme::Reds => &[1, 7, 9],
+            Theme::Purples => &[1, 7, 12],
+            Theme::Rainbow => &[0, 8, 4],
+            Theme::Blend(..) | Theme::Custom { .. } => &[],
+        };
+        order.retain(|i| !roles.contains(i));
+        roles
+            .iter()
+            .chain(&order)
+            .map(|&i| manager.palette[i].clone())
+            .collect()
+    }
+
+    /// A palette `t` of the way from theme `a` (0.0) to theme `b` (1.0)
+    ///
+    /// Both palettes are ordered by hue and stretched to the longer one's
+    /// length, then matching entries are interpolated. The result always has
+    /// as many colors as the larger of the two themes.
+    pub fn interpolate_themes(a: &Theme, b: &Theme, t: f64) -> Vec<String> {
+        Self::interpolate_themes_in(a, b, t, BlendSpace::default())
+    }
+
+    /// [`ColorManager::interpolate_themes`], interpolating entries in `space`
+    pub fn interpolate_themes_in(a: &Theme, b: &Theme, t: f64, space: BlendSpace) -> Vec<String> {
+        let by_hue = |theme: &Theme| {
+            let mut palette = Self::theme_palette(theme);
+            palette.sort_by(|x, y| Self::hex_to_hsl(x).0.total_cmp(&Self::hex_to_hsl(y).0));
+            palette
+        };
+        let (from, to) = (by_hue(a), by_hue(b));
+        let count = from.len().max(to.len());
+
+        (0..count)
+            .map(|i| {
+                let x = &from[i * from.len() / count];
+                let y = &to[i * to.len() / count];
+                space.interpolate(x, y, t)
+            })
+            .collect()
+    }
+
+    /// Create a ColorManager with the specified theme by name
+    #[allow(dead_code)]
+    pub fn with_theme_name(theme_name: &str, seed: Option<u64>) -> Self {
+        let theme = Theme::from(theme_name);
+        Self::with_theme(theme, seed)
+    }
+
+    /// Initialize with the default (Mesos) theme
+    #[allow(dead_code)]
+    pub fn default(seed: Option<u64>) -> Self {
+        Self::mesos_theme(seed)
+    }
+
+    /// Mesos theme - original colors from the Mesos logo generator
+    pub fn mesos_theme(seed: Option<u64>) -> Self {
+        Self::new(
+            vec![
+                // Original hexagonal logo generator colors from the JS implementation
+                "#FFCC09".to_string(), // Yellow [255, 204, 9]
+                "#F68A21".to_string(), // Orange [246, 138, 33]
+                "#E42728".to_string(), // Red [228, 39, 43]
+                "#E81F6F".to_string(), // Magenta [232, 31, 111]
+                "#BD3D93".to_string(), // Pink [189, 61, 147]
+                "#71459B".to_string(), // Purple [113, 69, 155]
+                "#4D499C".to_string(), // Dark Blue [77, 73, 156]
+                "#3960A9".to_string(), // Medium Blue [57, 96, 169]
+                "#20B7E8".to_string(), // Light Blue [32, 183, 232]
+                "#46B78C".to_string(), // Teal [70, 183, 140]
+                "#49B650".to_string(), // Green [73, 182, 80]
+                "#78BF44".to_string(), // Light Green [120, 191, 68]
+                // Additional colors for variety
+                "#B3675E".to_string(), // Rust
+                "#3EAF51".to_string(), // Forest Green
+                "#5A4FCF".to_string(), // Royal Purple
+            ],
+            seed,
+        )
+    }
+
+    /// Google theme - based on Google's brand colors
+    pub fn google_theme(seed: Option<u64>) -> Self {
+        Self::new(
+            vec![
+                "#4285F4".to_string(), // Google Blue
+                "#EA4335".to_string(), // Google Red
+                "#FBBC05".to_string(), // Google Yellow
+                "#34A853".to_string(), // Google Green
+                "#1A73E8".to_string(), // Google Blue (alternate)
+                "#D93025".to_string(), // Google Red (darker)
+                "#F9AB00".to_string(), // Google Yellow (darker)
+                "#1E8E3E".to_string(), // Google Green (darker)
+                "#174EA6".to_string(), // Google Blue (darkest)
+                "#A50E0E".to_string(), // Google Red (darkest)
+                "#E37400".to_string(), // Google Yellow (darkest)
+                "#0D652D".to_string(), // Google Green (darkest)
+                "#5BB974".to_string(), // Light Green
+                "#81C995".to_string(), // Very Light Green
+                "#8AB4F8".to_string(), // Light Blue
+            ],
+            seed,
+        )
+    }
+
+    /// Blues theme - various shades of blue
+    pub fn blues_theme(seed: Option<u64>) -> Self {
+        Self::new(
+            vec![
+                "#0D47A1".to_string(), // Deep Blue
+                "#1565C0".to_string(), // Dark Blue
+                "#1976D2".to_string(), // Medium Blue
+                "#1E88E5".to_string(), // Blue
+                "#2196F3".to_string(), // Light Blue
+                "#42A5F5".to_string(), // Lighter Blue
+                "#64B5F6".to_string(), // Very Light Blue
+                "#90CAF9".to_string(), // Pale Blue
+                "#BBDEFB".to_string(), // Extremely Light Blue
+                "#2962FF".to_string(), // Accent Blue
+                "#0277BD".to_string(), // Light Blue (darker)
+                "#01579B".to_string(), // Light Blue (darkest)
+                "#039BE5".to_string(), // Light Blue (medium)
+                "#03A9F4".to_string(), // Light Blue (lighter)
+                "#29B6F6".to_string(), // Light Blue (lightest)
+            ],
+            seed,
+        )
+    }
+
+    /// Greens theme - various shades of green
+    pub fn greens_theme(seed: Option<u64>) -> Self {
+        Self::new(
+            vec![
+                "#1B5E20".to_string(), // Deep Green
+                "#2E7D32".to_string(), // Dark Green
+                "#388E3C".to_string(), // Medium Green
+                "#43A047".to_string(), // Green
+                "#4CAF50".to_string(), // Light Green
+                "#66BB6A".to_string(), // Lighter Green
+                "#81C784".to_string(), // Very Light Green
+                "#A5D6A7".to_string(), // Pale Green
+                "#C8E6C9".to_string(), // Extremely Light Green
+                "#00C853".to_string(), // Accent Green
+                "#00695C".to_string(), // Teal Green
+                "#00796B".to_string(), // Medium Teal
+                "#00897B".to_string(), // Light Teal
+                "#009688".to_string(), // Teal
+                "#26A69A".to_string(), // Light Teal Green
+            ],
+            seed,
+        )
+    }
+
+    /// Reds theme - various shades of red and orange
+    pub fn reds_theme(seed: Option<u64>) -> Self {
+        Self::new(
+            vec![
+                "#B71C1C".to_string(), // Deep Red
+                "#C62828".to_string(), // Dark Red
+                "#D32F2F".to_string(), // Medium Red
+                "#E53935".to_string(), // Red
+                "#F44336".to_string(), // Light Red
+                "#EF5350".to_string(), // Lighter Red
+                "#E57373".to_string(), // Very Light Red
+                "#EF9A9A".to_string(), // Pale Red
+                "#FFCDD2".to_string(), // Extremely Light Red
+                "#DD2C00".to_string(), // Accent Red
+                "#BF360C".to_string(), // Deep Orange
+                "#E64A19".to_string(), // Dark Orange
+                "#F4511E".to_string(), // Medium Orange
+                "#FF5722".to_string(), // Orange
+                "#FF7043".to_string(), // Light Orange
+            ],
+            seed,
+        )
+    }
+
+    /// Purples theme - various shades of purple and pink
+    pub fn purples_theme(seed: Option<u64>) -> Self {
+        Self::new(
+            vec![
+                "#4A148C".to_string(), // Deep Purple
+                "#6A1B9A".to_string(), // Dark Purple
+                "#7B1FA2".to_string(), // Medium Purple
+                "#8E24AA".to_string(), // Purple
+                "#9C27B0".to_string(), // Light Purple
+                "#AB47BC".to_string(), // Lighter Purple
+                "#BA68C8".to_string(), // Very Light Purple
+                "#CE93D8".to_string(), // Pale Purple
+                "#E1BEE7".to_string(), // Extremely Light Purple
+                "#880E4F".to_string(), // Deep Pink
+                "#AD1457".to_string(), // Dark Pink
+                "#C2185B".to_string(), // Medium Pink
+                "#D81B60".to_string(), // Pink
+                "#E91E63".to_string(), // Light Pink
+                "#EC407A".to_string(), // Lighter Pink
+            ],
+            seed,
+        )
+    }
+
+    /// Rainbow theme - full spectrum of colors
+    pub fn rainbow_theme(seed: Option<u64>) -> Self {
+        Self::new(
+            vec![
+                "#FF0000".to_string(), // Red
+                "#FF4500".to_string(), // Orange Red
+                "#FF8C00".to_string(), // Dark Orange
+                "#FFA500".to_string(), // Orange
+                "#FFD700".to_string(), // Gold
+                "#FFFF00".to_string(), // Yellow
+                "#ADFF2F".to_string(), // Green Yellow
+                "#32CD32".to_string(), // Lime Green
+                "#008000".to_string(), // Green
+                "#00FF7F".to_string(), // Spring Green
+                "#00FFFF".to_string(), // Cyan
+                "#1E90FF".to_string(), // Dodger Blue
+                "#0000FF".to_string(), // Blue
+                "#4B0082".to_string(), // Indigo
+                "#8A2BE2".to_string(), // Blue Violet
+                "#FF00FF".to_string(), // Magenta
+                "#C71585".to_string(), // Medium Violet Red
+            ],
+            seed,
+        )
+    }
+
+    // Public methods that are directly used in the application
+
+    /// Get a random color from the palette, honoring any weights
+    pub fn get_random_color(&mut self) -> String {
+        // Unweighted palettes draw exactly as they always have, so seeds keep their colors
+        let idx = match WeightedIndex::new(&self.weights) {
+            Ok(weighted) => weighted.sample(&mut self.rng),
+            Err(_) => self.rng.gen_range(0..self.palette.len()),
+        };
+        self.palette[idx].clone()
+    }
+
+    /// Get a specific number of random colors
+    pub fn get_random_colors(&mut self, count: usize) -> Vec<String> {
+        let mut colors = Vec::with_capacity(count);
+        for _ in 0..count {
+            colors.push(self.get_random_color());
+        }
+        colors
+    }
+
+    /// Get a color that's different from the provided colors
+    pub fn get_different_color(&mut self, existing_colors: &[String]) -> String {
+        if existing_colors.is_empty() {
+            return self.get_random_color();
+        }
+
+        let mut color = self.get_random_color();
+        let max_attempts = 20; // Prevent infinite loop in cases with limited palette
+        let mut attempts = 0;
+
+        while existing_colors.contains(&color) && attempts < max_attempts {
+            color = self.get_random_color();
+            attempts += 1;
+        }
+
+        color
+    }
+
+    /// The palette followed by synthesized colors, `count` distinct colors in all
+    ///
+    /// New colors spread around the hue wheel by the golden angle from the
+    /// palette's first color, at the palette's average lightness and chroma, so
+    /// they sit with the theme while staying apart from each other.
+    pub fn expanded_palette(&self, count: usize) -> Vec<String> {
+        let mut colors: Vec<String> = Vec::with_capacity(count);
+        for color in &self.palette {
+            if !colors.contains(color) {
+                colors.push(color.clone());
+            }
+        }
+        if colors.len() >= count || colors.is_empty() {
+            return colors;
+        }
+
+        let lch: Vec<(f64, f64, f64)> = colors.iter().map(|color| Self::hex_to_oklch(color)).collect();
+        let lightness = lch.iter().map(|&(l, _, _)| l).sum::<f64>() / lch.len() as f64;
+        let chroma = (lch.iter().map(|&(_, c, _)| c).sum::<f64>() / lch.len() as f64).max(0.08);
+        let start = lch[0].2;
+        // Rounding to hex can repeat a color, so allow a few extra steps
+        for step in 1..=count * 4 {
+            if colors.len() >= count {
+                break;
+            }
+            let color = Self::oklch_to_hex(lightness, chroma, start + step as f64 * GOLDEN_ANGLE);
+            if !colors.contains(&color) {
+                colors.push(color);
+            }
+        }
+        colors
+    }
+
+    /// Recolors shapes so that no two share a color, keeping the first of each
+    ///
+    /// A repeated color is replaced by an unused palette color, or once the
+    /// palette runs out, by a lighter or darker variant of itself.
+    pub fn make_colors_unique(&mut self, shapes: &mut [crate::generator::shape::Shape]) {
+        let mut used: Vec<String> = Vec::with_capacity(shapes.len());
+        for shape in shapes.iter_mut() {
+            let current = shape.fill().to_string();
+            if used.contains(&current) {
+                let color = self.get_different_color(&used);
+                shape.color = Some(if used.contains(&color) {
+                    self.palette
+                        .iter()
+                        .find(|color| !used.contains(color))
+                        .cloned()
+                        .unwrap_or_else(|| Self::unused_variant(&current, &used))
+                } else {
+                    color
+                });
+            }
+            used.push(shape.fill().to_string());
+        }
+    }
+
+    /// A lightness, then hue, variation of `color` that isn't in `used`
+    fn unused_variant(color: &str, used: &[String]) -> String {
+        let lightness = (1..=12).flat_map(|step| {
+            let amount = step as f64 * 0.07;
+            [Self::lighten(color, amount), Self::darken(color, amount)]
+        });
+        let (l, c, h) = Self::hex_to_oklch(color);
+        let hue = (1..36).map(|step| Self::oklch_to_hex(l, c.max(0.05), h + step as f64 * 10.0));
+        lightness
+            .chain(hue)
+            .find(|variant| !used.contains(variant))
+            .unwrap_or_else(|| color.to_string())
+    }
+
+    /// Get a color for a shape that's harmonious with the design
+    /// This avoids using the same color for adjacent shapes
+    #[allow(dead_code)]
+    pub fn get_color_avoiding_adjacency(
+        &mut self,
+        grid: &crate::generator::grid::TriangularGrid,
+        shape_cells: &[usize],
+        existing_shapes: &[crate::generator::shape::Shape],
+    ) -> String {
+        // If no existing shapes, just return a random color
+        if existing_shapes.is_empty() {
+            return self.get_random_color();
+        }
+
+        // Build an adjacency list to track which colors to avoid
+        let mut adjacent_colors = Vec::new();
+
+        // For each cell in our shape
+        for &cell_id in shape_cells {
+            // Get all adjacent cells
+            let adjacent_cells = grid.adjacent_cells(cell_id);
+
+            // For each adjacent cell, check if it belongs to an existing shape
+            for &adj_cell in &adjacent_cells {
+                for existing_shape in existing_shapes {
+                    if existing_shape.contains_cell(adj_cell) {
+                        // Add the color of the adjacent shape to our avoid list
+                        adjacent_colors.extend(existing_shape.color.clone());
+                        break; // Once we find a shape that contains this cell, we can stop checking
+                    }
+                }
+            }
+        }
+
+        // Remove duplicates
+        adjacent_colors.sort_unstable();
+        adjacent_colors.dedup();
+
+        // Get a color different from all adjacent colors
+        self.get_different_color(&adjacent_colors)
+    }
+
+    /// Assign optimal colors to a set of shapes to ensure visual harmony
+    ///
+    /// Candidates are drawn with [`ColorManager::get_random_color`], so palette
+    /// weights carry over to which colors dominate. The rotated copies of a
+    /// symmetric shape are colored as one shape and all get its color.
+    ///
+    /// No two touching shapes ever share a color. A greedy pass colors the
+    /// shapes most-neighbors first; where it runs out of palette colors, a
+    /// backtracking search looks for a coloring within the palette's distinct
+    /// colors instead. Four colors always suffice for shapes that are each one
+    /// piece, so only palettes of three or fewer colors, or searches past
+    /// `MAX_COLORING_STEPS`, keep the colors the greedy pass synthesized beyond
+    /// the palette with [`ColorManager::expanded_palette`].
+    pub fn assign_harmonious_colors(
+        &mut self,
+        grid: &crate::generator::grid::TriangularGrid,
+        shapes: &mut [crate::generator::shape::Shape],
+    ) {
+        // Each shape is colored through the first shape of its symmetry group
+        let representative: Vec<usize> = shapes
+            .iter()
+            .enumerate()
+            .map(|(i, shape)| match shape.symmetry {
+                Some(symmetry) => shapes
+                    .iter()
+                    .position(|other| other.symmetry.is_some_and(|o| o.group == symmetry.group))
+                    .unwrap_or(i),
+                None => i,
+            })
+            .collect();
+
+        // Index the shapes covering each cell, in shape order, so finding a
+        // cell's shapes doesn't mean scanning every shape
+        let mut owners: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (j, shape) in shapes.iter().enumerate() {
+            for &cell_id in &shape.cells {
+                owners.entry(cell_id).or_default().push(j);
+            }
+        }
+
+        // Create a map of shape index -> adjacent shape indices
+        let mut adjacency_map: HashMap<usize, Vec<usize>> = HashMap::new();
+
+        // For each shape, find adjacent shapes
+        for i in 0..shapes.len() {
+            let mut adjacent_shapes = adjacency_map.remove(&representative[i]).unwrap_or_default();
+
+            // Check each cell in this shape
+            for &cell_id in &shapes[i].cells {
+                // Get adjacent cells
+                let adjacent_cells = grid.adjacent_cells(cell_id);
+
+                // For each adjacent cell, check if it belongs to another shape
+                for &adj_cell in &adjacent_cells {
+                    for &j in owners.get(&adj_cell).into_iter().flatten() {
+                        let j = representative[j];
+                        if j != representative[i] && !adjacent_shapes.contains(&j) {
+                            adjacent_shapes.push(j);
+                            break;
+                        }
+                    }
+                }
+            }
+
+            adjacency_map.insert(representative[i], adjacent_shapes);
+        }
+
+        // Sort shapes by number of adjacencies (descending)
+        let mut shape_indices: Vec<usize> = (0..shapes.len()).filter(|&i| representative[i] == i).collect();
+
+        // Assign colors using a greedy algorithm (Welsh-Powell)
+        let mut available_colors = self.get_random_colors(self.palette.len().min(shape_indices.len() + 3));
+        let mut assigned_colors: HashMap<usize, String> = HashMap::new();
+        let mut synthesized = false;
+
+        shape_indices.sort_by(|&a, &b| {
+            let a_adj = adjacency_map.get(&a).map_or(0, |v| v.len());
+            let b_adj = adjacency_map.get(&b).map_or(0, |v| v.len());
+            b_adj.cmp(&a_adj) // Descending order
+        });
+
+        // Assign colors to shapes
+        for &shape_idx in &shape_indices {
+            // Get colors of adjacent shapes
+            let adjacent_colors: Vec<String> =
+                if let Some(adj_indices) = adjacency_map.get(&shape_idx) {
+                    adj_indices
+                        .iter()
+                        .filter_map(|&adj_idx| assigned_colors.get(&adj_idx).cloned())
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+
+            // Find first available color not used by adjacent shapes
+            let mut chosen_color = None;
+            for color in &available_colors {
+                if !adjacent_colors.contains(color) {
+                    chosen_color = Some(color.clone());
+                    break;
+                }
+            }
+
+            // If no suitable color found, add a new random one that's different from adjacent,
+            // synthesizing one once the palette is exhausted
+            let color = match chosen_color {
+                Some(color) => color,
+                None => {
+                    let mut new_color = self.get_different_color(&adjacent_colors);
+                    if adjacent_colors.contains(&new_color) {
+                        new_color = self
+                            .expanded_palette(self.palette.len() + adjacent_colors.len() + 1)
+                            .into_iter()
+                            .find(|color| !adjacent_colors.contains(color))
+                            .unwrap_or(new_color);
+                        synthesized |= !self.palette.contains(&new_color);
+                    }
+                    available_colors.push(new_color.clone());
+                    new_color
+                }
+            };
+
+            // Assign the color
+            assigned_colors.insert(shape_idx, color);
+        }
+
+        // The greedy pass can run out of colors where another order wouldn't,
+        // so search for a coloring within the palette before leaving it
+        if synthesized {
+            let mut palette: Vec<String> = Vec::with_capacity(self.palette.len());
+            for color in available_colors.iter().chain(&self.palette) {
+                if self.palette.contains(color) && !palette.contains(color) {
+                    palette.push(color.clone());
+                }
+            }
+
+            let node_of: HashMap<usize, usize> =
+                shape_indices.iter().enumerate().map(|(node, &shape_idx)| (shape_idx, node)).collect();
+            let mut graph = vec![Vec::new(); shape_indices.len()];
+            for (node, shape_idx) in shape_indices.iter().enumerate() {
+                for adj_idx in adjacency_map.get(shape_idx).into_iter().flatten() {
+                    let other = node_of[adj_idx];
+                    if !graph[node].contains(&other) {
+                        graph[node].push(other);
+                        graph[other].push(node);
+                    }
+                }
+            }
+
+            if let Some(coloring) = graph::color_graph(&graph, palette.len()) {
+                for (node, color) in coloring.into_iter().enumerate() {
+                    assigned_colors.insert(shape_indices[node], palette[color].clone());
+                }
+            }
+        }
+
+        // Update the actual shapes with assigned colors
+        for (i, shape) in shapes.iter_mut().enumerate() {
+            if let Some(color) = assigned_colors.get(&representative[i]) {
+                shape.color = Some(color.clone());
+            }
+        }
+    }
+
+    /// Get a pair of colors with a blended color for overlapping regions
+    /// Returns (color1, color2, blend)
+    #[allow(dead_code)]
+    pub fn get_colors_with_blend(&mut self) -> (String, String, String) {
+        // Get two distinct random colors
+        let color1 = self.get_random_color();
+        let mut color2 = self.get_random_color();
+        while color2 == color1 {
+            color2 = self.get_random_color();
+        }
+
+        let blend = Self::mix(&color1, &color2, 0.5);
+
+        (color1, color2, blend)
+    }
+
+    /// Calculate color contrast ratio between two colors
+    pub fn color_contrast(color1: &str, color2: &str) -> f64 {
+        // Convert to RGB
+        let (r1, g1, b1) = Self::hex_to_rgb(color1);
+        let (r2, g2, b2) = Self::hex_to_rgb(color2);
+
+        // Calculate relative luminance
+        let l1 = Self::relative_luminance(r1, g1, b1);
+        let l2 = Self::relative_luminance(r2, g2, b2);
+
+        // Calculate contrast ratio
+        if l1 > l2 {
+            (l1 + 0.05) / (l2 + 0.05)
+        } else {
+            (l2 + 0.05) / (l1 + 0.05)
+        }
+    }
+
+    /// Calculate relative luminance for contrast calculation
+    fn relative_luminance(r: u8, g: u8, b: u8) -> f64 {
+        // Convert RGB to linear values first
+        let r_linear = Self::to_linear(r as f64 / 255.0);
+        let g_linear = Self::to_linear(g as f64 / 255.0);
+        let b_linear = Self::to_linear(b as f64 / 255.0);
+
+        // Calculate luminance (per WCAG formula)
+        0.2126 * r_linear + 0.7152 * g_linear + 0.0722 * b_linear
+    }
+
+    /// Convert sRGB value to linear RGB value
+    fn to_linear(value: f64) -> f64 {
+        if value <= 0.03928 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        }
+    }
+
+    /// The theme colors this manager picks from
+    pub fn palette(&self) -> &[String] {
+        &self.palette
+    }
+
+    /// Name of the CSS named color closest to a hex color
+    pub fn nearest_named_color(hex: &str) -> &'static str {
+        named::nearest(Self::hex_to_rgb(hex))
+    }
+
+    /// Infallible counterpart of [`parse_hex`], reading malformed colors as black
+    pub fn hex_to_rgb(hex: &str) -> (u8, u8, u8) {
+        let (r, g, b, _) = Self::hex_to_rgba(hex);
+        (r, g, b)
+    }
+
+    pub fn rgb_to_hex(r: u8, g: u8, b: u8) -> String {
+        format!("#{:02X}{:02X}{:02X}", r, g, b)
+    }
+
+    /// Alpha-aware counterpart of [`ColorManager::hex_to_rgb`]
+    ///
+    /// Reads `#RRGGBBAA` and `#RGBA` colors; colors without an alpha channel
+    /// are fully opaque, and malformed ones opaque black.
+    pub fn hex_to_rgba(hex: &str) -> (u8, u8, u8, u8) {
+        let rgb = parse_hex(hex).unwrap_or(Rgb { r: 0, g: 0, b: 0, a: 255 });
+        (rgb.r, rgb.g, rgb.b, rgb.a)
+    }
+
+    /// Formats a color as `#RRGGBB`, or `#RRGGBBAA` when it is not fully opaque
+    pub fn rgba_to_hex(r: u8, g: u8, b: u8, a: u8) -> String {
+        if a == 255 {
+            Self::rgb_to_hex(r, g, b)
+        } else {
+            format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a)
+        }
+    }
+
+    /// Alpha channel of a hex color as a fraction from 0.0 to 1.0
+    pub fn alpha(hex: &str) -> f32 {
+        Self::hex_to_rgba(hex).3 as f32 / 255.0
+    }
+
+    /// Converts a hex color to HSL, with hue in degrees and saturation and
+    /// lightness from 0.0 to 1.0
+    pub fn hex_to_hsl(hex: &str) -> (f64, f64, f64) {
+        let (r, g, b) = Self::hex_to_rgb(hex);
+        let (r, g, b) = (r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0);
+
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let lightness = (max + min) / 2.0;
+        let delta = max - min;
+        if delta == 0.0 {
+            return (0.0, 0.0, lightness);
+        }
+
+        let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+        let hue = if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+
+        (hue, saturation, lightness)
+    }
+
+    /// Converts HSL, with hue in degrees and saturation and lightness from 0.0
+    /// to 1.0, to a hex color
+    pub fn hsl_to_hex(hue: f64, saturation: f64, lightness: f64) -> String {
+        let (r, g, b) = css::hsl_to_rgb(
+            hue.rem_euclid(360.0),
+            saturation.clamp(0.0, 1.0),
+            lightness.clamp(0.0, 1.0),
+        );
+        Self::rgb_to_hex(r, g, b)
+    }
+
+    /// Converts a hex color to OKLab (lightness from 0.0 to 1.0, then the a and b axes)
+    pub fn hex_to_oklab(hex: &str) -> (f64, f64, f64) {
+        let (r, g, b) = Self::hex_to_rgb(hex);
+        oklab::rgb_to_oklab(r, g, b)
+    }
+
+    /// Converts OKLab to a hex color, clipping out-of-gamut channels
+    pub fn oklab_to_hex(lightness: f64, a: f64, b: f64) -> String {
+        let (r, g, b) = oklab::oklab_to_rgb(lightness, a, b);
+        Self::rgb_to_hex(r, g, b)
+    }
+
+    /// Converts a hex color to OKLCH (lightness, chroma, hue in degrees)
+    pub fn hex_to_oklch(hex: &str) -> (f64, f64, f64) {
+        let (lightness, a, b) = Self::hex_to_oklab(hex);
+        oklab::oklab_to_oklch(lightness, a, b)
+    }
+
+    /// Converts OKLCH to a hex color, reducing chroma to fit the sRGB gamut
+    pub fn oklch_to_hex(lightness: f64, chroma: f64, hue: f64) -> String {
+        let (r, g, b) = oklab::oklch_to_rgb(lightness, chroma, hue);
+        Self::rgb_to_hex(r, g, b)
+    }
+
+    /// Mixes two colors, `t` of the way from `color1` to `color2`
+    ///
+    /// Mixing happens in OKLab, which keeps midpoints as bright and saturated
+    /// as the eye expects instead of the muddy result of averaging sRGB; see
+    /// [`BlendSpace`] for the alternative. Alpha is always mixed linearly.
+    pub fn mix(color1: &str, color2: &str, t: f64) -> String {
+        BlendSpace::Oklab.mix(color1, color2, t)
+    }
+
+    /// Interpolates between two colors for gradients, theme blends and animation
+    ///
+    /// Uses OKLCH; see [`BlendSpace`] for the alternative.
+    pub fn interpolate(from: &str, to: &str, t: f64) -> String {
+        BlendSpace::Oklab.interpolate(from, to, t)
+    }
+
+    /// Interpolates between two colors in OKLCH, taking the shorter way around
+    /// the hue circle
+    ///
+    /// `t` runs from 0.0 (`from`) to 1.0 (`to`). Alpha is interpolated linearly.
+    pub fn interpolate_oklch(from: &str, to: &str, t: f64) -> String {
+        let t = t.clamp(0.0, 1.0);
+        let (l1, c1, h1) = Self::hex_to_oklch(from);
+        let (l2, c2, h2) = Self::hex_to_oklch(to);
+
+        // Near-grays have no meaningful hue, so borrow the other color's
+        let gray = 1e-3;
+        let (h1, h2) = match (c1 < gray, c2 < gray) {
+            (true, false) => (h2, h2),
+            (false, true) => (h1, h1),
+            _ => (h1, h2),
+        };
+        let delta = (h2 - h1 + 540.0).rem_euclid(360.0) - 180.0;
+
+        let (r, g, b) = oklab::oklch_to_rgb(
+            l1 + (l2 - l1) * t,
+            c1 + (c2 - c1) * t,
+            (h1 + delta * t).rem_euclid(360.0),
+        );
+        let a1 = Self::hex_to_rgba(from).3 as f64;
+        let a2 = Self::hex_to_rgba(to).3 as f64;
+        Self::rgba_to_hex(r, g, b, (a1 + (a2 - a1) * t).round() as u8)
+    }
+
+    /// Raises a color's perceptual lightness by `amount` (0.0-1.0), keeping its hue
+    pub fn lighten(hex: &str, amount: f64) -> String {
+        Self::adjust_lightness(hex, amount)
+    }
+
+    /// Lowers a color's perceptual lightness by `amount` (0.0-1.0), keeping its hue
+    pub fn darken(hex: &str, amount: f64) -> String {
+        Self::adjust_lightness(hex, -amount)
+    }
+
+    fn adjust_lightness(hex: &str, amount: f64) -> String {
+        let (lightness, chroma, hue) = Self::hex_to_oklch(hex);
+        let alpha = Self::hex_to_rgba(hex).3;
+        let (r, g, b) = oklab::oklch_to_rgb((lightness + amount).clamp(0.0, 1.0), chroma, hue);
+        Self::rgba_to_hex(r, g, b, alpha)
+    }
+
+    /// The closest color to `color` that reaches `min_ratio` WCAG contrast against `background`
+    ///
+    /// Only lightness changes, moving away from the background, so the hue and
+    /// colorfulness stay recognizable. Colors that already pass are returned as is.
+    pub fn ensure_contrast(color: &str, background: &str, min_ratio: f64) -> String {
+        if Self::color_contrast(color, background) >= min_ratio {
+            return color.to_string();
+        }
+
+        let (lightness, _, _) = Self::hex_to_oklch(color);
+        let background_lightness = Self::hex_to_oklch(background).0;
+        let step = if lightness >= background_lightness { 0.01 } else { -0.01 };
+
+        let mut candidate = color.to_string();
+        for i in 1..=100 {
+            candidate = Self::adjust_lightness(color, step * i as f64);
+            if Self::color_contrast(&candidate, background) >= min_ratio {
+                break;
+            }
+        }
+        candidate
+    }
+
+    /// Interpolates between two colors in HSL, taking the shorter way around
+    /// the hue circle
+    ///
+    /// `t` runs from 0.0 (`from`) to 1.0 (`to`). Alpha is interpolated linearly.
+    pub fn interpolate_hsl(from: &str, to: &str, t: f64) -> String {
+        let t = t.clamp(0.0, 1.0);
+        let (h1, s1, l1) = Self::hex_to_hsl(from);
+        let (h2, s2, l2) = Self::hex_to_hsl(to);
+
+        // Grays have no meaningful hue, so borrow the other color's
+        let (h1, h2) = match (s1 == 0.0, s2 == 0.0) {
+            (true, false) => (h2, h2),
+            (false, true) => (h1, h1),
+            _ => (h1, h2),
+        };
+        let delta = (h2 - h1 + 540.0).rem_euclid(360.0) - 180.0;
+        let hue = (h1 + delta * t).rem_euclid(360.0);
+
+        let (r, g, b) = css::hsl_to_rgb(hue, s1 + (s2 - s1) * t, l1 + (l2 - l1) * t);
+        let a1 = Self::hex_to_rgba(from).3 as f64;
+        let a2 = Self::hex_to_rgba(to).3 as f64;
+        Self::rgba_to_hex(r, g, b, (a1 + (a2 - a1) * t).round() as u8)
+    }
+
+    /// Blend two colors together with a given opacity
+    ///
+    /// `color2` is painted over `color1`; its own alpha channel, if any,
+    /// scales the opacity. This models how SVG renderers composite, so it
+    /// stays in sRGB; use [`ColorManager::mix`] to pick new colors.
+    pub fn blend_colors(color1: &str, color2: &str, opacity: f32) -> String {
+        let (r1, g1, b1) = Self::hex_to_rgb(color1);
+        let (r2, g2, b2) = Self::hex_to_rgb(color2);
+
+        let alpha = (opacity.clamp(0.0, 1.0) * Self::alpha(color2)) as f64;
+
+        let r = (r1 as f64 * (1.0 - alpha) + r2 as f64 * alpha).round() as u8;
+        let g = (g1 as f64 * (1.0 - alpha) + g2 as f64 * alpha).round() as u8;
+        let b = (b1 as f64 * (1.0 - alpha) + b2 as f64 * alpha).round() as u8;
+
+        Self::rgb_to_hex(r, g, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolate_themes() {
+        let mesos = ColorManager::theme_palette(&Theme::Mesos);
+        let blues = ColorManager::theme_palette(&Theme::Blues);
+
+        let start = ColorManager::interpolate_themes(&Theme::Mesos, &Theme::Blues, 0.0);
+        assert_eq!(start.len(), mesos.len().max(blues.len()));
+        assert!(start.iter().all(|color| mesos.contains(color)));
+
+        let end = ColorManager::interpolate_themes(&Theme::Mesos, &Theme::Blues, 1.0);
+        assert!(end.iter().all(|color| blues.contains(color)));
+
+        let theme: Theme = "Mesos:blues:0.4".parse().unwrap();
+        assert_eq!(theme.to_string(), "mesos:blues:0.4");
+        assert_eq!(
+            ColorManager::theme_palette(&theme),
+            ColorManager::interpolate_themes(&Theme::Mesos, &Theme::Blues, 0.4)
+        );
+
+        assert!("mesos:blues:1.5".parse::<Theme>().is_err());
+        assert!("mesos:plaid:0.5".parse::<Theme>().is_err());
+        assert_eq!(Theme::from("plaid"), Theme::Mesos);
+    }
+
+    #[test]
+    fn test_theme_roles() {
+        let roles = ColorManager::theme_roles(&Theme::Google);
+        assert_eq!(&roles[..4], ["#4285F4", "#EA4335", "#FBBC05", "#34A853"]);
+
+        // Every theme lists each of its colors exactly once
+        for name in ColorManager::builtin_themes() {
+            let theme = name.parse::<Theme>().unwrap();
+            let mut roles = ColorManager::theme_roles(&theme);
+            let mut palette = ColorManager::theme_palette(&theme);
+            roles.sort();
+            palette.sort();
+            assert_eq!(roles, palette, "{}", name);
+        }
+
+        let weighted = Theme::Custom {
+            name: "brand".to_string(),
+            colors: vec!["#111111".to_string(), "#222222".to_string(), "#333333".to_string()],
+            weights: vec![1, 6, 3],
+        };
+        assert_eq!(ColorManager::theme_roles(&weighted), vec!["#222222", "#333333", "#111111"]);
+    }
+
+    #[test]
+    fn test_oklab_helpers() {
+        let (l, a, b) = ColorManager::hex_to_oklab("#FF0000");
+        assert!((l - 0.628).abs() < 1e-3 && (a - 0.225).abs() < 1e-3 && (b - 0.126).abs() < 1e-3);
+        assert_eq!(ColorManager::oklab_to_hex(l, a, b), "#FF0000");
+        let (l, c, h) = ColorManager::hex_to_oklch("#3960A9");
+        assert_eq!(ColorManager::oklch_to_hex(l, c, h), "#3960A9");
+
+        // Lightness changes keep the hue
+        let lighter = ColorManager::lighten("#3960A9", 0.2);
+        let darker = ColorManager::darken("#3960A9", 0.2);
+        assert!(ColorManager::hex_to_oklch(&lighter).0 > l + 0.15);
+        assert!(ColorManager::hex_to_oklch(&darker).0 < l - 0.15);
+        assert!((ColorManager::hex_to_oklch(&darker).2 - h).abs() < 3.0);
+
+        // Yellow on white is unreadable until darkened
+        let text = ColorManager::ensure_contrast("#FFCC09", "#FFFFFF", 4.5);
+        assert!(ColorManager::color_contrast(&text, "#FFFFFF") >= 4.5);
+        assert_eq!(ColorManager::ensure_contrast("#000000", "#FFFFFF", 4.5), "#000000");
+    }
+
+    #[test]
+    fn test_perceptual_mixing() {
+        // Averaging sRGB gives a dark, muddy #800080; OKLab stays lighter
+        let mixed = ColorManager::mix("#FF0000", "#0000FF", 0.5);
+        assert_eq!(mixed, "#8C53A2");
+        assert!(ColorManager::hex_to_oklab(&mixed).0 > ColorManager::hex_to_oklab("#800080").0);
+
+        assert_eq!(ColorManager::mix("#FF000080", "#0000FF", 0.0), "#FF000080");
+        assert_eq!(ColorManager::interpolate("#FF0000", "#0000FF", 1.0), "#0000FF");
+    }
+
+    #[test]
+    fn test_rgb_blend_space() {
+        assert_eq!(BlendSpace::Rgb.mix("#FF0000", "#0000FF", 0.5), "#800080");
+        assert_eq!(BlendSpace::Rgb.interpolate("#FF0000", "#0000FF", 0.5), "#FF00FF");
+        assert_eq!("RGB".parse::<BlendSpace>(), Ok(BlendSpace::Rgb));
+        assert!("lab".parse::<BlendSpace>().is_err());
+
+        let blend = ColorManager::interpolate_themes_in(&Theme::Mesos, &Theme::Blues, 0.5, BlendSpace::Rgb);
+        assert_ne!(blend, ColorManager::interpolate_themes(&Theme::Mesos, &Theme::Blues, 0.5));
+    }
+
+    #[test]
+    fn test_color_conversion() {
+        let hex = "#FF5500";
+        let (r, g, b) = ColorManager::hex_to_rgb(hex);
+        assert_eq!(r, 255);
+        assert_eq!(g, 85);
+        assert_eq!(b, 0);
+
+        let hex2 = ColorManager::rgb_to_hex(r, g, b);
+        assert_eq!(hex2.to_uppercase(), "#FF5500");
+
+        // Short and malformed input reads as black rather than panicking
+        assert_eq!(ColorManager::hex_to_rgb("#F50"), (255, 85, 0));
+        assert_eq!(ColorManager::hex_to_rgb("#F"), (0, 0, 0));
+        assert_eq!(ColorManager::hex_to_rgb(""), (0, 0, 0));
+        assert_eq!(ColorManager::hex_to_rgb("#éé1234"), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_rgba_conversion() {
+        assert_eq!(ColorManager::hex_to_rgba("#FF550080"), (255, 85, 0, 128));
+        assert_eq!(ColorManager::hex_to_rgba("#FF5500"), (255, 85, 0, 255));
+        assert_eq!(ColorManager::hex_to_rgba("#F508"), (255, 85, 0, 136));
+        assert_eq!(ColorManager::hex_to_rgb("#FF550080"), (255, 85, 0));
+
+        assert_eq!(ColorManager::rgba_to_hex(255, 85, 0, 128), "#FF550080");
+        assert_eq!(ColorManager::rgba_to_hex(255, 85, 0, 255), "#FF5500");
+        assert!((ColorManager::alpha("#00000080") - 128.0 / 255.0).abs() < 1e-6);
+        assert_eq!(ColorManager::alpha("#000000"), 1.0);
+
+        // A translucent top color contributes less when blended
+        assert_eq!(ColorManager::blend_colors("#FFFFFF", "#000000", 0.5), "#808080");
+        assert_eq!(ColorManager::blend_colors("#FFFFFF", "#00000080", 1.0), "#7F7F7F");
+    }
+
+    #[test]
+    fn test_interpolate_hsl() {
+        let (h, s, l) = ColorManager::hex_to_hsl("#FF0000");
+        assert_eq!((h, s, l), (0.0, 1.0, 0.5));
+        assert_eq!(ColorManager::hex_to_hsl("#00FF00").0, 120.0);
+
+        assert_eq!(ColorManager::interpolate_hsl("#FF0000", "#0000FF", 0.0), "#FF0000");
+        assert_eq!(ColorManager::interpolate_hsl("#FF0000", "#0000FF", 1.0), "#0000FF");
+
+        // Red to blue goes the short way, through magenta
+        assert_eq!(ColorManager::interpolate_hsl("#FF0000", "#0000FF", 0.5), "#FF00FF");
+        assert_eq!(ColorManager::interpolate_hsl("#FFFFFF", "#FF0000", 0.5), "#DF9F9F");
+    }
+
+    #[test]
+    fn test_color_blending() {
+        let color1 = "#FF0000"; // Red
+        let color2 = "#0000FF"; // Blue
+
+        // 50% blend should give purple
+        let blended = ColorManager::blend_colors(color1, color2, 0.5);
+        assert_eq!(blended.to_uppercase(), "#800080");
+
+        // 0% opacity should give color1
+        let blended = ColorManager::blend_colors(color1, color2, 0.0);
+        assert_eq!(blended.to_uppercase(), "#FF0000");
+
+        // 100% opacity should give color2
+        let blended = ColorManager::blend_colors(color1, color2, 1.0);
+        assert_eq!(blended.to_uppercase(), "#0000FF");
+    }
+
+    #[test]
+    fn test_random_color_selection() {
+        let mut manager = ColorManager::default(Some(42)); // Fixed seed for deterministic testing
+
+        // Get a few random colors and ensure they're from the palette
+        let color1 = manager.get_random_color();
+        let color2 = manager.get_random_color();
+
+        assert!(manager.palette().contains(&color1));
+        assert!(manager.palette().contains(&color2));
+
+        // Get multiple colors at once
+        let colors = manager.get_random_colors(3);
+        assert_eq!(colors.len(), 3);
+
+        for color in colors {
+            assert!(manager.palette().contains(&color));
+        }
+    }
+
+    #[test]
+    fn test_get_different_color() {
+        let mut manager = ColorManager::default(Some(42)); // Fixed seed for deterministic testing
+
+        let existing_colors = vec![
+            "#FFCC09".to_string(),
+            "#F68A21".to_string(),
+            "#E42728".to_string(),
+        ];
+
+        let different_color = manager.get_different_color(&existing_colors);
+        assert!(!existing_colors.contains(&different_color));
+    }
+
+    #[test]
+    fn test_make_colors_unique() {
+        use crate::generator::shape::Shape;
+
+        // Two colors for five shapes forces generated variants
+        let mut manager = ColorManager::new(vec!["#4285F4".to_string(), "#EA4335".to_string()], Some(1));
+        let mut shapes: Vec<Shape> = (0..5).map(|_| Shape::new(1.0).with_color("#4285F4")).collect();
+        manager.make_colors_unique(&mut shapes);
+
+        let mut colors: Vec<String> = shapes.iter().map(|shape| shape.fill().to_string()).collect();
+        assert_eq!(colors[0], "#4285F4");
+        assert!(colors.contains(&"#EA4335".to_string()));
+        colors.sort();
+        colors.dedup();
+        assert_eq!(colors.len(), 5);
+    }
+
+    #[test]
+    fn test_expanded_palette() {
+        let manager = ColorManager::google_theme(Some(1));
+        let palette = manager.palette().to_vec();
+        let expanded = manager.expanded_palette(25);
+        assert_eq!(expanded.len(), 25);
+        assert_eq!(expanded[..palette.len()], palette[..]);
+        let mut distinct = expanded.clone();
+        distinct.sort();
+        distinct.dedup();
+        assert_eq!(distinct.len(), 25);
+        // A palette already large enough is returned as is
+        assert_eq!(manager.expanded_palette(2), palette);
+
+        // Shapes that all touch each other still get distinct colors past the palette
+        use crate::generator::grid::TriangularGrid;
+        use crate::generator::shape::Shape;
+        let grid = TriangularGrid::new(100.0, 2);
+        let mut shapes: Vec<Shape> = (0..6)
+            .map(|sector| {
+                let mut shape = Shape::new(1.0);
+                shape.cells = grid.cells_in_sector(sector);
+                shape
+            })
+            .collect();
+        let mut manager = ColorManager::new(vec!["#4285F4".to_string(), "#EA4335".to_string()], Some(3));
+        manager.assign_harmonious_colors(&grid, &mut shapes);
+        for (i, shape) in shapes.iter().enumerate() {
+            let next = &shapes[(i + 1) % shapes.len()];
+            assert_ne!(shape.fill(), next.fill());
+        }
+    }
+
+    #[test]
+    fn test_harmonious_colors_stay_in_small_palettes() {
+        use crate::generator::GeneratorConfig;
+
+        // Mosaics pack hundreds of touching tiles, where a greedy pass alone
+        // usually runs out of three colors
+        let three = ["#4285F4", "#EA4335", "#FBBC05"];
+        for (colors, grid_size) in [(&three[..], 3), (&three[..], 6), (&three[..], 8), (&three[..2], 5)] {
+            for seed in 0..8 {
+                let theme = Theme::Custom {
+                    name: "small".to_string(),
+                    colors: colors.iter().map(|color| color.to_string()).collect(),
+                    weights: Vec::new(),
+                };
+                let design = GeneratorConfig::new(grid_size, 4, 0.8, Some(seed))
+                    .with_theme(theme)
+                    .with_mosaic(true)
+                    .generate()
+                    .unwrap();
+                let shapes = design.shapes();
+
+                let mut owner = vec![None; design.grid().cell_count()];
+                for (index, shape) in shapes.iter().enumerate() {
+                    for &cell in &shape.cells {
+                        owner[cell] = Some(index);
+                    }
+                }
+                for (a, b) in design.grid().edges() {
+                    if let (Some(a), Some(b)) = (owner[a], owner[b]) {
+                        assert!(a == b || shapes[a].fill() != shapes[b].fill());
+                    }
+                }
+
+                // Two colors can't cover the odd cycles a mosaic has, so only
+                // then do colors come from past the palette
+                let in_palette = shapes.iter().all(|shape| colors.contains(&shape.fill()));
+                assert_eq!(in_palette, colors.len() == 3, "seed {} with {} colors", seed, colors.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_nearest_named_color() {
+        assert_eq!(ColorManager::nearest_named_color("#FF0000"), "red");
+        assert_eq!(ColorManager::nearest_named_color("#FE0102"), "red");
+        assert_eq!(ColorManager::nearest_named_color("#4285F4"), "dodgerblue");
+        assert_eq!(named::lookup("RebeccaPurple"), Some((0x66, 0x33, 0x99)));
+        assert_eq!(named::lookup("notacolor"), None);
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#4285f4").unwrap(), "#4285F4");
+        assert_eq!(parse_color(" #F80 ").unwrap(), "#FF8800");
+        assert_eq!(parse_color("RebeccaPurple").unwrap(), "#663399");
+        assert_eq!(parse_color("rgb(255, 128, 0)").unwrap(), "#FF8000");
+        assert_eq!(parse_color("rgb(100% 50% 0%)").unwrap(), "#FF8000");
+        assert_eq!(parse_color("rgba(0, 0, 255, 1)").unwrap(), "#0000FF");
+        assert_eq!(parse_color("hsl(120, 100%, 25%)").unwrap(), "#008000");
+        assert_eq!(parse_color("hsl(0.5turn 100% 50% / 100%)").unwrap(), "#00FFFF");
+        assert_eq!(parse_color("hsl(-120deg, 100%, 50%)").unwrap(), "#0000FF");
+
+        // Translucent colors keep their alpha as an eight digit hex
+        assert_eq!(parse_color("#FF000080").unwrap(), "#FF000080");
+        assert_eq!(parse_color("#f008").unwrap(), "#FF000088");
+        assert_eq!(parse_color("#FF0000FF").unwrap(), "#FF0000");
+        assert_eq!(parse_color("rgba(0, 0, 0, 0.5)").unwrap(), "#00000080");
+        assert_eq!(parse_color("hsl(0 100% 50% / 25%)").unwrap(), "#FF000040");
+    }
+
+    #[test]
+    fn test_parse_color_errors() {
+        for input in [
+            "",
+            "#12",
+            "#GGGGGG",
+            "notacolor",
+            "rgb(1, 2)",
+            "rgb(1, 2, x)",
+            "hsl(120, 100, 50)",
+            "rgb(1, 2, 3",
+            "rgba(0, 0, 0, x)",
+            "#12345",
+        ] {
+            let err = parse_color(input).unwrap_err().to_string();
+            assert!(err.starts_with("Invalid color"), "{}: {}", input, err);
+        }
+    }
+
+    #[test]
+    fn test_parse_hex() {
+        let rgb = |r, g, b, a| Rgb { r, g, b, a };
+        assert_eq!(parse_hex("#FF5500").unwrap(), rgb(255, 85, 0, 255));
+        assert_eq!(parse_hex("ff5500").unwrap(), rgb(255, 85, 0, 255));
+        assert_eq!(parse_hex("#F50").unwrap(), rgb(255, 85, 0, 255));
+        assert_eq!(parse_hex("#F508").unwrap(), rgb(255, 85, 0, 136));
+        assert_eq!(parse_hex(" #FF550080 ").unwrap(), rgb(255, 85, 0, 128));
+
+        for input in ["", "#", "#F", "#12345", "#GG5500", "#éé1234", "#FF5500FF00"] {
+            let err = parse_hex(input).unwrap_err().to_string();
+            assert!(err.starts_with("Invalid hex color"), "{}: {}", input, err);
+        }
+    }
+
+    #[test]
+    fn test_color_contrast() {
+        // Test high contrast (black/white)
+        let contrast = ColorManager::color_contrast("#FFFFFF", "#000000");
+        assert!(contrast > 20.0); // Should be 21.0
+
+        // Test low contrast (similar colors)
+        let contrast = ColorManager::color_contrast("#FF0000", "#FF0001");
+        assert!(contrast < 1.1); // Should be very close to 1.0
+    }
+
+    #[test]
+    fn test_with_theme_name() {
+        // Test with a valid theme name
+        let manager = ColorManager::with_theme_name("blues", Some(42));
+        let palette = manager.palette();
+
+        // Verify colors are from the blues theme
+        assert!(palette.iter().any(|color| color.to_uppercase() == "#1E88E5"
+            || color.to_uppercase() == "#2196F3"
+            || color.to_uppercase() == "#0D47A1"));
+
+        // Test with another theme
+        let manager = ColorManager::with_theme_name("google", Some(42));
+        let palette = manager.palette();
+
+        // Verify colors are from the google theme
+        assert!(palette
+            .iter()
+            .any(|color| color.to_uppercase() == "#4285F4"));
+    }
+
+    #[test]
+    fn test_default() {
+        // Test default theme (should be Mesos)
+        let manager = ColorManager::default(Some(42));
+        let palette = manager.palette();
+
+        // Verify colors are from the Mesos theme
+        assert!(palette
+            .iter()
+            .any(|color| color.to_uppercase() == "#FFCC09"));
+        assert!(palette
+            .iter()
+            .any(|color| color.to_uppercase() == "#E42728"));
+    }
+
+    #[test]
+    fn test_colors_with_blend() {
+        let mut manager = ColorManager::default(Some(42));
+
+        let (color1, color2, blend) = manager.get_colors_with_blend();
+
+        // Verify the colors are from the palette
+        assert!(manager.palette().contains(&color1));
+        assert!(manager.palette().contains(&color2));
+
+        // Verify that color1 and color2 are different
+        assert_ne!(color1, color2);
+
+        // The blend should be the perceptual midpoint of the two colors
+        assert_eq!(blend, ColorManager::mix(&color1, &color2, 0.5));
+    }
+
+    #[test]
+    fn test_color_avoiding_adjacency() {
+        use crate::generator::grid::triangular::TriangularGrid;
+        use crate::generator::shape::Shape;
+
+        // Create a triangular grid for testing
+        let grid = TriangularGrid::new(100.0, 2);
+
+        // Create a ColorManager
+        let mut manager = ColorManager::default(Some(42));
+
+        // Test with no existing shapes (should just return a random color)
+        let shape_cells = vec![0, 1, 2];
+        let existing_shapes: Vec<Shape> = vec![];
+        let color = manager.get_color_avoiding_adjacency(&grid, &shape_cells, &existing_shapes);
+
+        assert!(manager.palette().contains(&color));
+
+        // Create a shape with a known color
+        let mut shape1 = Shape::new(0.8).with_color("#FF0000");
+        shape1.add_cell(3);
+        shape1.add_cell(4);
+
+        // Create another shape with a different color
+        let mut shape2 = Shape::new(0.8).with_color("#00FF00");
+        shape2.add_cell(6);
+        shape2.add_cell(7);
+
+        let existing_shapes = vec![shape1, shape2];
+
+        // Get a color avoiding adjacency to these shapes
+        let color = manager.get_color_avoiding_adjacency(&grid, &shape_cells, &existing_shapes);
+
+        // The color should be from the palette
+        assert!(manager.palette().contains(&color));
+
+        // The color should be different from the existing shapes' colors
+        assert_ne!(color, "#FF0000");
+        assert_ne!(color, "#00FF00");
+    }
+}
@@ -0,0 +1,138 @@
+/// Converts an sRGB channel from 0.0-1.0 to linear light
+fn to_linear(channel: f64) -> f64 {
+    if channel <= 0.04045 {
+        channel / 12.92
+    } else {
+        ((channel + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a linear light channel back to sRGB's 0.0-1.0
+fn from_linear(channel: f64) -> f64 {
+    if channel <= 0.003_130_8 {
+        channel * 12.92
+    } else {
+        1.055 * channel.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Converts 8-bit sRGB to OKLab (lightness from 0.0 to 1.0, then the a and b axes)
+pub fn rgb_to_oklab(r: u8, g: u8, b: u8) -> (f64, f64, f64) {
+    let r = to_linear(r as f64 / 255.0);
+    let g = to_linear(g as f64 / 255.0);
+    let b = to_linear(b as f64 / 255.0);
+
+    let l = (0.412_221_470_8 * r + 0.536_332_536_3 * g + 0.051_445_992_9 * b).cbrt();
+    let m = (0.211_903_498_2 * r + 0.680_699_545_1 * g + 0.107_396_956_6 * b).cbrt();
+    let s = (0.088_302_461_9 * r + 0.281_718_837_6 * g + 0.629_978_700_5 * b).cbrt();
+
+    (
+        0.210_454_255_3 * l + 0.793_617_785_0 * m - 0.004_072_046_8 * s,
+        1.977_998_495_1 * l - 2.428_592_205_0 * m + 0.450_593_709_9 * s,
+        0.025_904_037_1 * l + 0.782_771_766_2 * m - 0.808_675_766_0 * s,
+    )
+}
+
+/// Converts OKLab to linear sRGB, which may fall outside 0.0-1.0
+fn oklab_to_linear(lightness: f64, a: f64, b: f64) -> (f64, f64, f64) {
+    let l = (lightness + 0.396_337_777_4 * a + 0.215_803_757_3 * b).powi(3);
+    let m = (lightness - 0.105_561_345_8 * a - 0.063_854_172_8 * b).powi(3);
+    let s = (lightness - 0.089_484_177_5 * a - 1.291_485_548_0 * b).powi(3);
+
+    (
+        4.076_741_662_1 * l - 3.307_711_591_3 * m + 0.230_969_929_2 * s,
+        -1.268_438_004_6 * l + 2.609_757_401_1 * m - 0.341_319_396_5 * s,
+        -0.004_196_086_3 * l - 0.703_418_614_7 * m + 1.707_614_701_0 * s,
+    )
+}
+
+/// Converts OKLab to 8-bit sRGB, clipping channels that fall outside the gamut
+pub fn oklab_to_rgb(lightness: f64, a: f64, b: f64) -> (u8, u8, u8) {
+    let (r, g, b) = oklab_to_linear(lightness, a, b);
+    let channel = |c: f64| (from_linear(c.clamp(0.0, 1.0)) * 255.0).round() as u8;
+    (channel(r), channel(g), channel(b))
+}
+
+/// Converts OKLab to OKLCH (lightness, chroma, hue in degrees)
+pub fn oklab_to_oklch(lightness: f64, a: f64, b: f64) -> (f64, f64, f64) {
+    let chroma = (a * a + b * b).sqrt();
+    let hue = b.atan2(a).to_degrees().rem_euclid(360.0);
+    (lightness, chroma, hue)
+}
+
+/// Converts OKLCH to 8-bit sRGB
+///
+/// Colors outside the sRGB gamut keep their lightness and hue and lose just
+/// enough chroma to fit, rather than having channels clipped independently.
+pub fn oklch_to_rgb(lightness: f64, chroma: f64, hue: f64) -> (u8, u8, u8) {
+    let lightness = lightness.clamp(0.0, 1.0);
+    let (sin, cos) = hue.to_radians().sin_cos();
+    let in_gamut = |chroma: f64| {
+        let (r, g, b) = oklab_to_linear(lightness, chroma * cos, chroma * sin);
+        [r, g, b].iter().all(|c| (-1e-4..=1.0 + 1e-4).contains(c))
+    };
+
+    let mut chroma = chroma.max(0.0);
+    if !in_gamut(chroma) {
+        let (mut low, mut high) = (0.0, chroma);
+        for _ in 0..24 {
+            let middle = (low + high) / 2.0;
+            if in_gamut(middle) {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        chroma = low;
+    }
+
+    oklab_to_rgb(lightness, chroma * cos, chroma * sin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: (f64, f64, f64), expected: (f64, f64, f64)) {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-4;
+        assert!(
+            close(actual.0, expected.0) && close(actual.1, expected.1) && close(actual.2, expected.2),
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_reference_values() {
+        // Reference values published with OKLab
+        assert_close(rgb_to_oklab(255, 255, 255), (1.0, 0.0, 0.0));
+        assert_close(rgb_to_oklab(255, 0, 0), (0.627_96, 0.224_86, 0.125_85));
+        assert_close(rgb_to_oklab(0, 255, 0), (0.866_44, -0.233_89, 0.179_50));
+        assert_close(rgb_to_oklab(0, 0, 255), (0.452_01, -0.032_46, -0.311_53));
+        assert_close(rgb_to_oklab(0, 0, 0), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for &(r, g, b) in &[(255, 204, 9), (57, 96, 169), (32, 183, 232), (128, 128, 128)] {
+            let (l, a, bb) = rgb_to_oklab(r, g, b);
+            assert_eq!(oklab_to_rgb(l, a, bb), (r, g, b));
+
+            let (l, c, h) = oklab_to_oklch(l, a, bb);
+            assert_eq!(oklch_to_rgb(l, c, h), (r, g, b));
+        }
+    }
+
+    #[test]
+    fn test_out_of_gamut_chroma_is_reduced() {
+        // A very saturated cyan at this lightness doesn't exist in sRGB
+        let (r, g, b) = oklch_to_rgb(0.9, 0.4, 195.0);
+        let (l, _, h) = {
+            let (l, a, b) = rgb_to_oklab(r, g, b);
+            oklab_to_oklch(l, a, b)
+        };
+        assert!((l - 0.9).abs() < 0.01);
+        assert!((h - 195.0).abs() < 2.0);
+    }
+}
@@ -0,0 +1,126 @@
+use super::{parse_color, ColorManager};
+use crate::Result;
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+/// Themes added at runtime, keyed by lowercase name
+static THEMES: Lazy<RwLock<BTreeMap<String, Vec<String>>>> = Lazy::new(|| RwLock::new(BTreeMap::new()));
+
+/// Adds a named theme that every later lookup by name can use
+///
+/// Meant to be called while an application starts up, before generating.
+/// Names are case-insensitive and can't shadow a built-in or already
+/// registered theme; colors accept any syntax [`parse_color`] does.
+pub fn register_theme<S: AsRef<str>>(name: &str, colors: Vec<S>) -> Result<()> {
+    let key = name.trim().to_lowercase();
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!(
+            "Invalid theme name '{}' (use letters, digits, '-' and '_')",
+            name
+        )
+        .into());
+    }
+    if colors.is_empty() {
+        return Err(format!("Theme '{}' needs at least one color", name).into());
+    }
+    let colors = colors
+        .iter()
+        .map(|color| parse_color(color.as_ref()))
+        .collect::<Result<Vec<_>>>()?;
+
+    if ColorManager::builtin_themes().contains(&key.as_str()) {
+        return Err(format!("Theme '{}' is built in and can't be replaced", name).into());
+    }
+    let mut themes = THEMES.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if themes.contains_key(&key) {
+        return Err(format!("Theme '{}' is already registered", name).into());
+    }
+    themes.insert(key, colors);
+    Ok(())
+}
+
+/// Removes a theme added with [`register_theme`], returning whether it was registered
+///
+/// For applications that reload their themes while running, such as the web
+/// server picking up an edited config file.
+pub fn unregister_theme(name: &str) -> bool {
+    let mut themes = THEMES.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+    themes.remove(&name.trim().to_lowercase()).is_some()
+}
+
+/// The colors of a registered theme
+pub(super) fn lookup(name: &str) -> Option<Vec<String>> {
+    let themes = THEMES.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+    themes.get(&name.to_lowercase()).cloned()
+}
+
+/// Names of all registered themes, sorted
+pub(super) fn names() -> Vec<String> {
+    let themes = THEMES.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+    themes.keys().cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Theme;
+    use super::*;
+
+    #[test]
+    fn test_register_theme() {
+        register_theme("Registry-Corp", vec!["#112233", "teal", "rgb(255 0 0)"]).unwrap();
+
+        let theme = "registry-corp".parse::<Theme>().unwrap();
+        assert_eq!(theme.to_string(), "registry-corp");
+        assert_eq!(
+            ColorManager::theme_palette(&theme),
+            vec!["#112233", "#008080", "#FF0000"]
+        );
+        assert!(ColorManager::available_themes().contains(&"registry-corp".to_string()));
+
+        // Registered themes also blend like built-in ones
+        assert!("mesos:registry-corp:0.5".parse::<Theme>().is_ok());
+
+        let mut generator = crate::Generator::new(3, 2, 0.8, Some(1));
+        generator.set_color_scheme("REGISTRY-CORP").generate().unwrap();
+        assert_eq!(generator.config().theme().to_string(), "registry-corp");
+    }
+
+    #[test]
+    fn test_register_theme_errors() {
+        register_theme("registry-twice", vec!["#000000"]).unwrap();
+        let error = register_theme("Registry-Twice", vec!["#FFFFFF"]).unwrap_err();
+        assert!(error.to_string().contains("already registered"));
+
+        assert!(register_theme("registry-empty", Vec::<String>::new())
+            .unwrap_err()
+            .to_string()
+            .contains("at least one color"));
+        assert!(register_theme("mesos", vec!["#000000"]).is_err());
+        assert!(register_theme("a:b", vec!["#000000"]).is_err());
+        assert!(register_theme("registry-bad", vec!["nope"]).is_err());
+        assert!(lookup("registry-bad").is_none());
+
+        register_theme("registry-replaced", vec!["#000000"]).unwrap();
+        assert!(unregister_theme("Registry-Replaced"));
+        assert!(!unregister_theme("registry-replaced"));
+        register_theme("registry-replaced", vec!["#FFFFFF"]).unwrap();
+        assert_eq!(lookup("registry-replaced"), Some(vec!["#FFFFFF".to_string()]));
+        assert!(!unregister_theme("mesos"));
+    }
+
+    #[test]
+    fn test_concurrent_registration() {
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                std::thread::spawn(move || {
+                    register_theme(&format!("registry-thread-{}", i), vec!["#123456"]).is_ok()
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert!(handle.join().unwrap());
+        }
+        assert_eq!(names().iter().filter(|name| name.starts_with("registry-thread-")).count(), 8);
+    }
+}
@@ -0,0 +1,237 @@
+use super::color::BlendSpace;
+use super::design::ColorBlend;
+use super::shape::Shape;
+use std::collections::BTreeSet;
+
+/// Cells of `a` that are also in `b`, in `a`'s order
+pub fn intersection(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let b: BTreeSet<usize> = b.iter().copied().collect();
+    a.iter().copied().filter(|cell| b.contains(cell)).collect()
+}
+
+/// Cells of `a` that aren't in `b`, in `a`'s order
+pub fn difference(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let b: BTreeSet<usize> = b.iter().copied().collect();
+    a.iter().copied().filter(|cell| !b.contains(cell)).collect()
+}
+
+/// Cells in either `a` or `b`: `a`'s in order, then the rest of `b`'s
+pub fn union(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let mut cells = a.to_vec();
+    cells.extend(difference(b, a));
+    cells
+}
+
+/// Color of the region where two shapes overlap, an even mix of both in `space`
+pub fn blend_color(a: &str, b: &str, space: BlendSpace) -> String {
+    space.mix(a, b, 0.5)
+}
+
+/// How opaque the region where two shapes overlap is drawn
+///
+/// Blends inherit the first shape's opacity by default, which can look washed
+/// out against a light background.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OverlapOpacity {
+    /// The first shape's opacity (the original behavior)
+    #[default]
+    Inherit,
+    /// Fully opaque
+    Full,
+    /// As dense as both shapes stacked: `1 - (1 - a) * (1 - b)`, multiplying their transparency
+    Multiply,
+    /// A fixed opacity from 0.0 to 1.0, whatever the shapes' own
+    Custom(f32),
+}
+
+impl OverlapOpacity {
+    /// Names accepted by [`OverlapOpacity::from_str`](std::str::FromStr), besides an opacity
+    pub fn names() -> Vec<&'static str> {
+        vec!["inherit", "full", "multiply"]
+    }
+
+    /// The overlap's opacity, given the opacities of the shapes it's mixed from
+    pub fn resolve(&self, first: f32, second: f32) -> f32 {
+        match self {
+            OverlapOpacity::Inherit => first,
+            OverlapOpacity::Full => 1.0,
+            OverlapOpacity::Multiply => 1.0 - (1.0 - first) * (1.0 - second),
+            OverlapOpacity::Custom(opacity) => opacity.clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl std::fmt::Display for OverlapOpacity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OverlapOpacity::Inherit => write!(f, "inherit"),
+            OverlapOpacity::Full => write!(f, "full"),
+            OverlapOpacity::Multiply => write!(f, "multiply"),
+            OverlapOpacity::Custom(opacity) => write!(f, "{}", opacity),
+        }
+    }
+}
+
+impl std::str::FromStr for OverlapOpacity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "inherit" => Ok(OverlapOpacity::Inherit),
+            "full" => Ok(OverlapOpacity::Full),
+            "multiply" => Ok(OverlapOpacity::Multiply),
+            opacity => match opacity.parse::<f32>() {
+                Ok(opacity) if (0.0..=1.0).contains(&opacity) => Ok(OverlapOpacity::Custom(opacity)),
+                _ => Err(format!(
+                    "Unknown overlap opacity '{}' (expected one of: {}, or an opacity from 0.0 to 1.0)",
+                    s,
+                    Self::names().join(", ")
+                )),
+            },
+        }
+    }
+}
+
+/// Two overlapping shapes cut into the parts only one covers and the part both do
+#[derive(Debug, Clone)]
+pub struct Split {
+    /// Cells only the first shape covers, with its color
+    pub first: Shape,
+    /// Cells only the second shape covers, with its color
+    pub second: Shape,
+    /// Cells both cover, colored with [`blend_color`]; `None` when they don't overlap
+    ///
+    /// Left uncolored when either shape is, as while a design is still growing.
+    pub overlap: Option<Shape>,
+}
+
+impl Split {
+    /// The parts as design shapes, exclusive parts first, with the blend recording the overlap
+    ///
+    /// `offset` is the index the first part will have in the design.
+    pub fn into_shapes(self, offset: usize) -> (Vec<Shape>, Option<ColorBlend>) {
+        let mut shapes = vec![self.first, self.second];
+        let blend = self.overlap.map(|overlap| {
+            shapes.push(overlap);
+            ColorBlend {
+                shape: offset + 2,
+                sources: [offset, offset + 1],
+            }
+        });
+        (shapes, blend)
+    }
+}
+
+/// Splits two shapes into their exclusive parts and their overlap
+///
+/// Parts keep their shape's cell order; the overlap takes the first shape's
+/// opacity and mixes their colors in `space`.
+pub fn split(first: &Shape, second: &Shape, space: BlendSpace) -> Split {
+    let part = |source: &Shape, cells: Vec<usize>| Shape {
+        id: source.id,
+        cells,
+        color: source.color.clone(),
+        opacity: source.opacity,
+        symmetry: source.symmetry,
+    };
+
+    let shared = intersection(&first.cells, &second.cells);
+    let overlap = if shared.is_empty() {
+        None
+    } else {
+        let mut overlap = Shape::new(first.opacity);
+        if let (Some(a), Some(b)) = (&first.color, &second.color) {
+            overlap.color = Some(blend_color(a, b, space));
+        }
+        overlap.cells = shared.clone();
+        Some(overlap)
+    };
+
+    Split {
+        first: part(first, difference(&first.cells, &shared)),
+        second: part(second, difference(&second.cells, &shared)),
+        overlap,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shape(color: &str, cells: &[usize]) -> Shape {
+        let mut shape = Shape::new(0.8).with_color(color);
+        shape.cells = cells.to_vec();
+        shape
+    }
+
+    #[test]
+    fn test_set_algebra() {
+        let a = [5, 1, 9, 3];
+        let b = [3, 4, 5, 8];
+        assert_eq!(intersection(&a, &b), vec![5, 3]);
+        assert_eq!(difference(&a, &b), vec![1, 9]);
+        assert_eq!(difference(&b, &a), vec![4, 8]);
+        assert_eq!(union(&a, &b), vec![5, 1, 9, 3, 4, 8]);
+
+        assert!(intersection(&a, &[]).is_empty());
+        assert_eq!(difference(&a, &[]), a.to_vec());
+        assert_eq!(union(&[], &b), b.to_vec());
+
+        // The parts partition the union
+        let mut parts = [intersection(&a, &b), difference(&a, &b), difference(&b, &a)].concat();
+        let mut all = union(&a, &b);
+        parts.sort();
+        all.sort();
+        assert_eq!(parts, all);
+    }
+
+    #[test]
+    fn test_split() {
+        let first = shape("#FF0000", &[1, 2, 3, 4]);
+        let second = shape("#0000FF", &[3, 4, 5]);
+        let split = split(&first, &second, BlendSpace::Oklab);
+        assert_eq!(split.first.cells, vec![1, 2]);
+        assert_eq!(split.first.fill(), "#FF0000");
+        assert_eq!(split.second.cells, vec![5]);
+
+        let overlap = split.overlap.clone().unwrap();
+        assert_eq!(overlap.cells, vec![3, 4]);
+        assert_eq!(overlap.color, Some(blend_color("#FF0000", "#0000FF", BlendSpace::Oklab)));
+
+        let (shapes, blend) = split.into_shapes(1);
+        assert_eq!(shapes.len(), 3);
+        assert_eq!(blend, Some(ColorBlend { shape: 3, sources: [1, 2] }));
+
+        let apart = super::split(&first, &shape("#0000FF", &[7, 8]), BlendSpace::Oklab);
+        assert!(apart.overlap.is_none());
+        assert_eq!(apart.into_shapes(0).1, None);
+
+        let uncolored = super::split(&Shape::new(0.8), &shape("#0000FF", &[2, 3]), BlendSpace::Oklab);
+        assert!(uncolored.overlap.is_none());
+        let mut grown = Shape::new(0.8);
+        grown.cells = vec![1, 2];
+        assert_eq!(super::split(&grown, &shape("#0000FF", &[2, 3]), BlendSpace::Oklab).overlap.unwrap().color, None);
+    }
+
+    #[test]
+    fn test_overlap_opacity() {
+        assert_eq!("full".parse::<OverlapOpacity>(), Ok(OverlapOpacity::Full));
+        assert_eq!("0.9".parse::<OverlapOpacity>(), Ok(OverlapOpacity::Custom(0.9)));
+        assert!("1.5".parse::<OverlapOpacity>().unwrap_err().contains("inherit, full, multiply"));
+        assert!("dim".parse::<OverlapOpacity>().is_err());
+
+        assert_eq!(OverlapOpacity::Inherit.resolve(0.6, 0.8), 0.6);
+        assert_eq!(OverlapOpacity::Full.resolve(0.6, 0.8), 1.0);
+        assert!((OverlapOpacity::Multiply.resolve(0.6, 0.5) - 0.8).abs() < 1e-6);
+        assert_eq!(OverlapOpacity::Custom(0.3).resolve(0.6, 0.8), 0.3);
+    }
+
+    #[test]
+    fn test_blend_color() {
+        for space in [BlendSpace::Oklab, BlendSpace::Rgb] {
+            assert_eq!(blend_color("#336699", "#336699", space), "#336699");
+            assert_eq!(blend_color("#FF0000", "#0000FF", space), blend_color("#0000FF", "#FF0000", space));
+        }
+        assert_eq!(blend_color("#FF0000", "#0000FF", BlendSpace::Rgb), "#800080");
+    }
+}
@@ -0,0 +1,470 @@
+use super::color::{parse_color, ColorManager};
+use super::grid::{Point, TriangularGrid};
+use super::shape::{Shape, ShapeGenerator, ShapeMetrics};
+use super::{GeneratorConfig, Theme};
+use crate::Result;
+use std::collections::BTreeSet;
+
+/// A shape whose color was mixed from two other shapes where they overlap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorBlend {
+    /// Index of the blended shape
+    pub shape: usize,
+    /// Indices of the shapes whose colors were mixed
+    pub sources: [usize; 2],
+}
+
+/// The immutable result of running a [`GeneratorConfig`]
+///
+/// A design owns its grid and shapes, so it can be cached, shared between
+/// threads, and rendered any number of times without regenerating.
+#[derive(Debug, Clone)]
+pub struct GeneratedDesign {
+    config: GeneratorConfig,
+    grid: TriangularGrid,
+    shapes: Vec<Shape>,
+    blends: Vec<ColorBlend>,
+    winning_candidates: Vec<usize>,
+}
+
+impl GeneratedDesign {
+    /// Assembles a design from a grid and shapes laid out elsewhere, such as a traced image
+    ///
+    /// This is the color stage: shapes without a color are colored from
+    /// `config`'s theme the way [`GeneratorConfig::generate`] colors them, and
+    /// shapes that have one keep it. Blends naming a shape that isn't in
+    /// `shapes` are dropped.
+    pub fn new(
+        config: GeneratorConfig,
+        grid: TriangularGrid,
+        mut shapes: Vec<Shape>,
+        mut blends: Vec<ColorBlend>,
+    ) -> Self {
+        blends.retain(|blend| [blend.shape, blend.sources[0], blend.sources[1]].iter().all(|&i| i < shapes.len()));
+        if shapes.iter().any(|shape| shape.color.is_none()) {
+            let mut colored = shapes.clone();
+            config.assign_colors(&grid, &mut colored, &blends);
+            for (shape, colored) in shapes.iter_mut().zip(colored) {
+                if shape.color.is_none() {
+                    shape.color = colored.color;
+                }
+            }
+        }
+
+        Self {
+            config,
+            grid,
+            shapes,
+            blends,
+            winning_candidates: Vec::new(),
+        }
+    }
+
+    pub(crate) fn with_winning_candidates(mut self, winning_candidates: Vec<usize>) -> Self {
+        self.winning_candidates = winning_candidates;
+        self
+    }
+
+    /// The configuration that produced this design
+    pub fn config(&self) -> &GeneratorConfig {
+        &self.config
+    }
+
+    pub fn grid(&self) -> &TriangularGrid {
+        &self.grid
+    }
+
+    pub fn shapes(&self) -> &[Shape] {
+        &self.shapes
+    }
+
+    /// Which of the effort's candidates was picked, for each shape grown from candidates
+    ///
+    /// Indices count from 0, in the order the last generation or reroll grew
+    /// shapes. Styles that grow a single candidate leave this empty.
+    pub fn winning_candidates(&self) -> &[usize] {
+        &self.winning_candidates
+    }
+
+    /// Shapes colored by mixing two overlapping shapes
+    pub fn blends(&self) -> &[ColorBlend] {
+        &self.blends
+    }
+
+    /// The index of the shape with the stable id `id`, see [`Shape::id`]
+    pub fn shape_index(&self, id: usize) -> Option<usize> {
+        self.shapes.iter().position(|shape| shape.id == id)
+    }
+
+    /// The id of the grid cell under `point`, in design coordinates
+    pub fn cell_at(&self, point: Point) -> Option<usize> {
+        self.grid.cell_at(point)
+    }
+
+    /// The index of the shape drawn at `point`, in design coordinates
+    ///
+    /// Shapes are painted in order, so where several cover the cell the last
+    /// one, which is on top, is returned. `None` when no shape covers the point.
+    pub fn shape_at(&self, point: Point) -> Option<usize> {
+        let cell = self.cell_at(point)?;
+        self.shapes.iter().rposition(|shape| shape.contains_cell(cell))
+    }
+
+    /// The index of the shape drawn at each pixel of a `width` x `height` image, row by row
+    ///
+    /// Pixels are laid out as on the default SVG canvas, with the hexagon's
+    /// bounding square fit to the image and centered, and each is sampled at its
+    /// center by the rules of [`GeneratedDesign::shape_at`]. Every cell's top
+    /// shape is worked out once and the grid's sector lookup finds the cell under
+    /// each pixel, so no renderer is involved and a map costs a fraction of a
+    /// render, cheap enough for visual diffs, hashing and level-of-detail checks.
+    pub fn rasterize_cells(&self, width: u32, height: u32) -> Vec<Option<usize>> {
+        let mut owners = vec![None; self.grid.cell_count()];
+        for (index, shape) in self.shapes.iter().enumerate() {
+            for &cell in &shape.cells {
+                if let Some(owner) = owners.get_mut(cell) {
+                    *owner = Some(index);
+                }
+            }
+        }
+
+        let hex = self.grid.hex_grid();
+        let scale = 2.0 * hex.size / width.min(height).max(1) as f64;
+        let origin = Point::new(
+            hex.center.x - width as f64 / 2.0 * scale,
+            hex.center.y - height as f64 / 2.0 * scale,
+        );
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let point = Point::new(origin.x + (x as f64 + 0.5) * scale, origin.y + (y as f64 + 0.5) * scale);
+                self.grid.cell_at(point).and_then(|cell| owners[cell])
+            })
+            .collect()
+    }
+
+    /// A copy colored from `theme`, reusing the grown shapes
+    ///
+    /// Gives the same design as generating with the new theme, but only redoes
+    /// the color stage, so live previews can follow a theme picker cheaply.
+    pub fn with_theme(&self, theme: Theme) -> GeneratedDesign {
+        let mut recolored = self.clone();
+        recolored.config = self.config.clone().with_theme(theme);
+        recolored
+            .config
+            .assign_colors(&recolored.grid, &mut recolored.shapes, &recolored.blends);
+        recolored
+    }
+
+    /// A copy with every shape at `opacity`, clamped like [`GeneratorConfig::new`]
+    ///
+    /// Overlaps follow the config's [`OverlapOpacity`](super::OverlapOpacity).
+    pub fn with_opacity(&self, opacity: f32) -> GeneratedDesign {
+        let mut faded = self.clone();
+        faded.config = self.config.clone().with_opacity(opacity);
+        let opacity = faded.config.opacity();
+        for shape in &mut faded.shapes {
+            shape.opacity = opacity;
+        }
+        for blend in &faded.blends {
+            faded.shapes[blend.shape].opacity = faded.config.overlap_opacity().resolve(opacity, opacity);
+        }
+        faded
+    }
+
+    /// A copy with one shape painted `color`, any syntax [`parse_color`] accepts
+    ///
+    /// Only that shape changes; blends mixed from it keep their original color.
+    pub fn with_shape_color(&self, shape: usize, color: &str) -> Result<GeneratedDesign> {
+        if shape >= self.shapes.len() {
+            return Err(format!(
+                "Shape index {} is out of range (the design has {} shapes)",
+                shape,
+                self.shapes.len()
+            )
+            .into());
+        }
+
+        let mut recolored = self.clone();
+        recolored.shapes[shape].color = Some(parse_color(color)?);
+        Ok(recolored)
+    }
+
+    /// A copy where every shape not in `locked` is grown again from `seed`
+    ///
+    /// Locked shapes keep their cells, colors and ids, and new shapes grow
+    /// around them and take colors that differ from theirs and ids no shape
+    /// has had. Every shape keeps its index. Overlap blends survive only when
+    /// all three shapes are locked.
+    pub fn with_unlocked_rerolled(&self, locked: &[usize], seed: u64) -> Result<GeneratedDesign> {
+        if let Some(&index) = locked.iter().find(|&&index| index >= self.shapes.len()) {
+            return Err(format!(
+                "Locked shape index {} is out of range (the design has {} shapes)",
+                index,
+                self.shapes.len()
+            )
+            .into());
+        }
+
+        let is_locked = |index: usize| locked.contains(&index);
+        let mut used_cells: BTreeSet<usize> = locked
+            .iter()
+            .flat_map(|&index| self.shapes[index].cells.iter().copied())
+            .collect();
+        let mut used_colors: Vec<String> = locked.iter().map(|&index| self.shapes[index].fill().to_string()).collect();
+
+        let config = &self.config;
+        let size_range = config.size_range(&self.grid);
+        let algorithm = config.style().algorithm();
+        let mut shape_generator = ShapeGenerator::new(&self.grid, Some(seed));
+        shape_generator
+            .set_anchor(config.anchor())
+            .set_aesthetic(config.aesthetic())
+            .set_effort(config.effort())
+            .set_guide(config.guide().cloned());
+        let mut color_manager = ColorManager::with_theme(config.theme(), Some(seed));
+        let mut next_id = self.shapes.iter().map(|shape| shape.id + 1).max().unwrap_or(0);
+
+        let mut rerolled = self.clone();
+        for (index, shape) in rerolled.shapes.iter_mut().enumerate() {
+            if is_locked(index) {
+                continue;
+            }
+
+            *shape = match &algorithm {
+                Some(algorithm) => shape_generator.generate_with_algorithm(
+                    algorithm.as_ref(),
+                    config.opacity(),
+                    size_range,
+                    &used_cells,
+                ),
+                None => shape_generator.generate_shape_avoiding_cells(config.opacity(), size_range.1, &used_cells),
+            };
+            shape.color = Some(color_manager.get_different_color(&used_colors));
+            shape.id = next_id;
+            next_id += 1;
+            used_cells.extend(shape.cells.iter().copied());
+            used_colors.push(shape.fill().to_string());
+        }
+        rerolled.winning_candidates = shape_generator.winning_candidates().to_vec();
+        rerolled
+            .blends
+            .retain(|blend| is_locked(blend.shape) && blend.sources.iter().all(|&source| is_locked(source)));
+        Ok(rerolled)
+    }
+
+    /// A copy without the shapes covering fewer than `min_cells` cells
+    ///
+    /// Dropped shapes keep their place with no cells, so shape and blend
+    /// indices stay valid. The largest shape is always kept, so a design never
+    /// simplifies to nothing.
+    pub fn without_small_shapes(&self, min_cells: usize) -> GeneratedDesign {
+        let largest = self
+            .shapes
+            .iter()
+            .enumerate()
+            .max_by_key(|(index, shape)| (shape.cell_count(), std::cmp::Reverse(*index)))
+            .map(|(index, _)| index);
+
+        let mut simplified = self.clone();
+        for (index, shape) in simplified.shapes.iter_mut().enumerate() {
+            if shape.cell_count() < min_cells && Some(index) != largest {
+                shape.cells.clear();
+            }
+        }
+        simplified
+    }
+
+    /// A copy where shapes covering fewer than `min_cells` cells join a neighbor
+    ///
+    /// Each small shape, smallest first, gives its cells to the shape it shares
+    /// the most cell edges with; a small shape touching no other is dropped.
+    /// As with [`GeneratedDesign::without_small_shapes`] indices stay valid and
+    /// the largest shape is always kept.
+    pub fn with_small_shapes_merged(&self, min_cells: usize) -> GeneratedDesign {
+        let mut merged = self.clone();
+        let mut order: Vec<usize> = (0..merged.shapes.len()).collect();
+        order.sort_by_key(|&index| (merged.shapes[index].cell_count(), index));
+        // The largest shape sorts last and is never merged away
+        order.pop();
+
+        for index in order {
+            let size = merged.shapes[index].cell_count();
+            if size == 0 || size >= min_cells {
+                continue;
+            }
+
+            let mut shared = vec![0usize; merged.shapes.len()];
+            for &cell in &merged.shapes[index].cells {
+                for neighbor in merged.grid.neighbors(cell) {
+                    for (other, shape) in merged.shapes.iter().enumerate() {
+                        if other != index && shape.contains_cell(neighbor) {
+                            shared[other] += 1;
+                        }
+                    }
+                }
+            }
+
+            let cells = std::mem::take(&mut merged.shapes[index].cells);
+            let target = (0..shared.len())
+                .filter(|&other| shared[other] > 0)
+                .max_by_key(|&other| (shared[other], std::cmp::Reverse(other)));
+            if let Some(target) = target {
+                for cell in cells {
+                    merged.shapes[target].add_cell(cell);
+                }
+            }
+        }
+        merged
+    }
+
+    /// A copy with one-cell spurs trimmed and one-cell holes filled along every outline
+    ///
+    /// A spur is a cell touching at most one other cell of its shape; a hole is
+    /// an empty cell whose three neighbors all belong to the same shape. A shape
+    /// is never trimmed down to nothing.
+    pub fn with_smoothed_outlines(&self) -> GeneratedDesign {
+        let grid = &self.grid;
+        let mut owned = vec![false; grid.cell_count()];
+        for &cell in self.shapes.iter().flat_map(|shape| shape.cells.iter()) {
+            if let Some(slot) = owned.get_mut(cell) {
+                *slot = true;
+            }
+        }
+
+        let mut smoothed = self.clone();
+        for (shape, original) in smoothed.shapes.iter_mut().zip(&self.shapes) {
+            if original.cells.is_empty() {
+                continue;
+            }
+
+            let kept: Vec<usize> = original
+                .cells
+                .iter()
+                .copied()
+                .filter(|&cell| {
+                    grid.neighbors(cell).iter().filter(|&&n| original.contains_cell(n)).count() > 1
+                })
+                .collect();
+            if !kept.is_empty() {
+                shape.cells = kept;
+            }
+
+            let holes = (0..grid.cell_count()).filter(|&cell| {
+                let neighbors = grid.neighbors(cell);
+                !owned[cell] && neighbors.len() == 3 && neighbors.iter().all(|&n| original.contains_cell(n))
+            });
+            for cell in holes {
+                shape.add_cell(cell);
+            }
+        }
+        smoothed
+    }
+
+    /// Fraction of the second shape covered by the first, when they overlap
+    ///
+    /// Measured on the first blend, i.e. the overlap between the two leading
+    /// shapes; `None` when the design has no blended overlap.
+    pub fn overlap_ratio(&self) -> Option<f32> {
+        let blend = self.blends.first()?;
+        let overlap = self.shapes[blend.shape].cell_count();
+        let second = self.shapes[blend.sources[1]].cell_count() + overlap;
+        Some(overlap as f32 / second.max(1) as f32)
+    }
+
+    /// Quality and geometry measurements of each shape, in shape order
+    pub fn shape_metrics(&self) -> Vec<ShapeMetrics> {
+        // Measuring draws no random numbers, so the seed doesn't matter
+        let generator = ShapeGenerator::new(&self.grid, Some(0));
+        self.shapes.iter().map(|shape| generator.evaluate_shape_quality(shape)).collect()
+    }
+
+    /// Stable fingerprint of the configuration and every generated shape
+    ///
+    /// Two designs share a checksum only if they would render identically, so
+    /// it can be embedded in output files and compared after regenerating.
+    pub fn checksum(&self) -> u64 {
+        let config = &self.config;
+        let mut canonical = format!(
+            "grid={};shapes={};opacity={};seed={:?};theme={};overlap={};overlap_ratio={:?};style={};anchor={};pack={}",
+            config.grid_size(),
+            config.shapes_count(),
+            config.opacity(),
+            config.seed(),
+            config.theme(),
+            config.allow_overlap(),
+            config.overlap_ratio(),
+            config.style(),
+            config.anchor(),
+            config.pack()
+        );
+
+        for shape in &self.shapes {
+            canonical.push_str(&format!("|{}:{}:{:?}", shape.fill(), shape.opacity, shape.cells));
+        }
+
+        crate::stable_hash(canonical.as_bytes())
+    }
+
+    /// How alike two designs look, from 0.0 (nothing shared) to 1.0 (identical)
+    ///
+    /// Blends how well each shape's cells match its closest counterpart in the
+    /// other design (Jaccard index) with how close the two palettes are in
+    /// OKLab. Shapes left without cells are ignored since they don't render.
+    /// Designs on grids of different sizes never count as similar.
+    pub fn similarity(&self, other: &GeneratedDesign) -> f64 {
+        let visible = |design: &GeneratedDesign| -> Vec<Shape> {
+            design.shapes.iter().filter(|shape| !shape.cells.is_empty()).cloned().collect()
+        };
+        let (a, b) = (visible(self), visible(other));
+        if self.grid.cell_count() != other.grid.cell_count() || a.is_empty() || b.is_empty() {
+            return 0.0;
+        }
+
+        let shapes = (best_jaccard(&a, &b) + best_jaccard(&b, &a)) / 2.0;
+        let palette = (palette_closeness(&a, &b) + palette_closeness(&b, &a)) / 2.0;
+        SHAPE_WEIGHT * shapes + (1.0 - SHAPE_WEIGHT) * palette
+    }
+}
+
+/// Share of [`GeneratedDesign::similarity`] given to shape overlap rather than color
+const SHAPE_WEIGHT: f64 = 0.7;
+
+/// OKLab distance at which two colors no longer count as alike at all
+const COLOR_DISTANCE_LIMIT: f64 = 0.25;
+
+/// Mean over `a`'s shapes of the best Jaccard index against any shape in `b`
+fn best_jaccard(a: &[Shape], b: &[Shape]) -> f64 {
+    let total: f64 = a
+        .iter()
+        .map(|shape| {
+            b.iter()
+                .map(|other| {
+                    let shared = shape.cells.iter().filter(|cell| other.cells.contains(cell)).count();
+                    let union = shape.cells.len() + other.cells.len() - shared;
+                    shared as f64 / union.max(1) as f64
+                })
+                .fold(0.0, f64::max)
+        })
+        .sum();
+    total / a.len() as f64
+}
+
+/// Mean over `a`'s colors of how close the nearest color in `b` is, from 0.0 to 1.0
+fn palette_closeness(a: &[Shape], b: &[Shape]) -> f64 {
+    let total: f64 = a
+        .iter()
+        .map(|shape| {
+            let (l1, a1, b1) = ColorManager::hex_to_oklab(shape.fill());
+            let nearest = b
+                .iter()
+                .map(|other| {
+                    let (l2, a2, b2) = ColorManager::hex_to_oklab(other.fill());
+                    ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+                })
+                .fold(f64::MAX, f64::min);
+            1.0 - (nearest / COLOR_DISTANCE_LIMIT).min(1.0)
+        })
+        .sum();
+    total / a.len() as f64
+}
@@ -1,4 +1,17 @@
-use std::f64::consts::PI;
+/// Unit vectors from a hexagon's center to its vertices, at 0, 60, ... 300 degrees
+///
+/// These are the values `cos` and `sin` of `i * PI / 3` have always given
+/// here, written out because math libraries round trig functions differently
+/// from platform to platform. Grid coordinates then come only from correctly
+/// rounded arithmetic, so a seed grows the same design everywhere.
+pub(crate) const HEX_DIRECTIONS: [(f64, f64); 6] = [
+    (1.0, 0.0),
+    (0.5000000000000001, 0.8660254037844386),
+    (-0.4999999999999998, 0.8660254037844387),
+    (-1.0, 1.2246467991473532e-16),
+    (-0.5000000000000004, -0.8660254037844384),
+    (0.5000000000000001, -0.8660254037844386),
+];
 
 /// A 2D point using floating point coordinates
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -42,6 +55,12 @@ impl Cell {
         }
     }
 
+    /// Area of the triangle
+    pub fn area(&self) -> f64 {
+        let [a, b, c] = &self.vertices;
+        ((b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y)).abs() / 2.0
+    }
+
     pub fn contains_point(&self, point: &Point) -> bool {
         // Using barycentric coordinates to check if a point is inside a triangle
         let p1 = &self.vertices[0];
@@ -75,7 +94,7 @@ impl Cell {
 }
 
 /// Represents the hexagonal grid structure
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct HexGrid {
     pub size: f64,
     pub grid_density: u8,
@@ -88,19 +107,17 @@ impl HexGrid {
     /// Creates a new hexagonal grid with the specified parameters
     ///
     /// * `size` - The size of the hexagon (distance from center to any vertex)
-    /// * `grid_density` - Controls how finely the hexagon is divided (should be 2-8)
+    /// * `grid_density` - Controls how finely the hexagon is divided, clamped to 2-8
     /// * `center` - The center point of the hexagon
     pub fn new(size: f64, grid_density: u8, center: Point) -> Self {
         // Ensure grid density is within acceptable range
-        let grid_density = grid_density.clamp(2, 8);
+        let range = crate::generator::params::GRID_SIZE_RANGE;
+        let grid_density = grid_density.clamp(*range.start(), *range.end());
 
         // Generate the 6 vertices of the regular hexagon
         let mut vertices = Vec::with_capacity(6);
-        for i in 0..6 {
-            let angle = (i as f64) * PI / 3.0;
-            let x = center.x + size * angle.cos();
-            let y = center.y + size * angle.sin();
-            vertices.push(Point::new(x, y));
+        for (cos, sin) in HEX_DIRECTIONS {
+            vertices.push(Point::new(center.x + size * cos, center.y + size * sin));
         }
 
         // Create an empty cells vector that will be populated by the triangular grid
@@ -0,0 +1,242 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+    use crate::generator::grid::geometry::{HexGrid, Point};
+    use crate::generator::grid::triangular::TriangularGrid;
+
+    #[test]
+    fn test_hexagon_creation() {
+        let center = Point::new(0.0, 0.0);
+        let size = 100.0;
+        let grid_density = 6;
+
+        let hex_grid = HexGrid::new(size, grid_density, center);
+
+        // Verify that the hexagon has 6 vertices
+        assert_eq!(hex_grid.vertices.len(), 6);
+
+        // Verify that all vertices are at the correct distance from the center
+        for vertex in &hex_grid.vertices {
+            let distance = center.distance(vertex);
+            assert!((distance - size).abs() < 1e-6);
+        }
+
+        // Verify expected cell count
+        assert_eq!(
+            hex_grid.expected_cell_count(),
+            6 * (grid_density as usize).pow(2)
+        );
+    }
+
+    #[test]
+    fn test_triangular_grid_creation() {
+        let size = 100.0;
+
+        // Test with different grid densities
+        for grid_density in 3..=8 {
+            let grid = TriangularGrid::new(size, grid_density);
+            let expected_cells = 6 * (grid_density as usize).pow(2);
+
+            // Verify cell count
+            assert_eq!(grid.cell_count(), expected_cells);
+
+            // Each cell should have 3 vertices
+            if let Some(cell) = grid.get_cell(0) {
+                assert_eq!(cell.vertices.len(), 3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_point_in_hexagon() {
+        let center = Point::new(0.0, 0.0);
+        let size = 100.0;
+        let grid_density = 6;
+
+        let hex_grid = HexGrid::new(size, grid_density, center);
+
+        // Points inside the hexagon
+        assert!(hex_grid.contains_point(&center));
+
+        // Points on the boundary
+        for vertex in &hex_grid.vertices {
+            assert!(hex_grid.contains_point(vertex));
+        }
+
+        // Points outside the hexagon
+        let outside = Point::new(size * 2.0, size * 2.0);
+        assert!(!hex_grid.contains_point(&outside));
+    }
+
+    #[test]
+    fn test_cell_adjacency() {
+        let size = 100.0;
+        let grid_density = 3; // Small grid for easier testing
+
+        let grid = TriangularGrid::new(size, grid_density);
+
+        // Each cell should have at least one adjacent cell
+        for i in 0..grid.cell_count() {
+            let adjacent = grid.adjacent_cells(i);
+            assert!(!adjacent.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_original_style_grid() {
+        let size = 100.0;
+        let grid_density = 2; // This should trigger the original style grid
+
+        let grid = TriangularGrid::new(size, grid_density);
+
+        // Original style grid should have exactly 24 cells
+        assert_eq!(grid.cell_count(), 24);
+
+        // Verify centroid functionality
+        for i in 0..grid.cell_count() {
+            let centroid = grid.get_cell_centroid(i);
+            assert!(centroid.is_some());
+        }
+
+        // Non-existent cell should return None
+        assert!(grid.get_cell_centroid(100).is_none());
+        assert!(grid.get_cell(100).is_none());
+
+        // Verify cells access method
+        let cells = grid.cells();
+        assert_eq!(cells.len(), 24);
+
+        // Verify hex_grid and hex_grid_mut methods
+        let hex_grid = grid.hex_grid();
+        assert_eq!(hex_grid.size, size);
+
+        let mut grid = TriangularGrid::new(size, grid_density);
+        let hex_grid_mut = grid.hex_grid_mut();
+        assert_eq!(hex_grid_mut.size, size);
+    }
+
+    #[test]
+    fn test_topology_neighbors_and_edges() {
+        for density in [2, 3, 5] {
+            let grid = TriangularGrid::new(100.0, density);
+
+            // Each edge appears once and adjacency is symmetric
+            let edges = grid.edges();
+            for &(a, b) in &edges {
+                assert!(a < b);
+                assert!(grid.neighbors(b).contains(&a));
+            }
+
+            let degree_sum: usize = grid.cells().iter().map(|c| grid.neighbors(c.id).len()).sum();
+            assert_eq!(degree_sum, edges.len() * 2);
+            assert!(grid.cells().iter().all(|c| grid.neighbors(c.id).len() <= 3));
+        }
+
+        let grid = TriangularGrid::new(100.0, 3);
+        assert!(grid.neighbors(grid.cell_count()).is_empty());
+    }
+
+    #[test]
+    fn test_topology_rings() {
+        let grid = TriangularGrid::new(100.0, 4);
+
+        assert_eq!(grid.ring(10, 0), vec![10]);
+        assert_eq!(grid.ring(10, 1), grid.neighbors(10));
+
+        // Rings partition the grid, since it is connected
+        let mut seen = Vec::new();
+        for k in 0..grid.cell_count() {
+            seen.extend(grid.ring(0, k));
+        }
+        seen.sort_unstable();
+        assert_eq!(seen, (0..grid.cell_count()).collect::<Vec<_>>());
+
+        assert!(grid.ring(0, grid.cell_count()).is_empty());
+        assert!(grid.ring(grid.cell_count(), 0).is_empty());
+    }
+
+    #[test]
+    fn test_topology_sectors() {
+        for density in [2, 4] {
+            let grid = TriangularGrid::new(100.0, density);
+            let per_sector = grid.cell_count() / 6;
+
+            for sector in 0..6 {
+                let cells = grid.cells_in_sector(sector);
+                assert_eq!(cells.len(), per_sector);
+                assert!(cells.iter().all(|&id| grid.sector_of(id) == Some(sector)));
+
+                // Every centroid lies within the sector's 60 degree wedge
+                for &id in &cells {
+                    let c = grid.get_cell_centroid(id).unwrap();
+                    let angle = c.y.atan2(c.x).to_degrees().rem_euclid(360.0);
+                    assert!(angle >= sector as f64 * 60.0 - 1e-6);
+                    assert!(angle <= (sector + 1) as f64 * 60.0 + 1e-6);
+                }
+            }
+
+            assert!(grid.cells_in_sector(6).is_empty());
+            assert_eq!(grid.sector_of(grid.cell_count()), None);
+        }
+    }
+
+    #[test]
+    fn test_cell_at() {
+        for density in 2..=8 {
+            let grid = TriangularGrid::new(100.0, density);
+            for cell in grid.cells() {
+                assert_eq!(grid.cell_at(cell.centroid), Some(cell.id), "density {}", density);
+
+                // Points near a corner still resolve to a cell touching that corner
+                let near = Point::new(
+                    cell.vertices[0].x * 0.99 + cell.centroid.x * 0.01,
+                    cell.vertices[0].y * 0.99 + cell.centroid.y * 0.01,
+                );
+                let found = grid.cell_at(near).unwrap();
+                assert!(grid.cells()[found].contains_point(&near));
+            }
+
+            assert_eq!(grid.cell_at(Point::new(0.0, 99.0)), None);
+            assert_eq!(grid.cell_at(Point::new(500.0, 0.0)), None);
+            assert!(grid.cell_at(Point::new(0.0, 0.0)).is_some());
+        }
+    }
+
+    #[test]
+    fn test_grid_coordinates_are_bit_identical() {
+        use crate::generator::grid::geometry::HEX_DIRECTIONS;
+
+        for (i, (cos, sin)) in HEX_DIRECTIONS.into_iter().enumerate() {
+            let angle = i as f64 * std::f64::consts::PI / 3.0;
+            assert!((cos - angle.cos()).abs() < 1e-15 && (sin - angle.sin()).abs() < 1e-15);
+        }
+
+        // Every coordinate of every grid, down to the last bit; these hashes must
+        // match on every platform, or the same seed grows different designs
+        let fingerprints: Vec<String> = (2..=8)
+            .map(|density| {
+                let grid = TriangularGrid::new(100.0, density);
+                let bits: Vec<u8> = grid
+                    .cells()
+                    .iter()
+                    .flat_map(|cell| cell.vertices.iter().chain([&cell.centroid]))
+                    .flat_map(|point| [point.x.to_bits(), point.y.to_bits()])
+                    .flat_map(u64::to_le_bytes)
+                    .collect();
+                format!("{:016x}", crate::stable_hash(&bits))
+            })
+            .collect();
+        assert_eq!(
+            fingerprints,
+            vec![
+                "fb3783b9b2cb9451",
+                "7cdba7c9df419ffa",
+                "3f1aba4ddffc0b29",
+                "0f8f845be77b3179",
+                "bec1416f04d9e255",
+                "1f6298640e8ed0c9",
+                "c37fa5a0d98c3e9a",
+            ]
+        );
+    }
+}
@@ -1,9 +1,13 @@
-use super::geometry::{Cell, HexGrid, Point};
+use super::geometry::{Cell, HexGrid, Point, HEX_DIRECTIONS};
+use std::collections::VecDeque;
+use std::sync::OnceLock;
 
 /// Represents a triangular grid subdividing a hexagon
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TriangularGrid {
     hex_grid: HexGrid,
+    /// Neighbors of every cell, worked out on first use
+    adjacency: OnceLock<Vec<Vec<usize>>>,
 }
 
 impl TriangularGrid {
@@ -17,7 +21,10 @@ impl TriangularGrid {
         let cells = Self::generate_triangular_cells(&hex_grid);
         hex_grid.cells = cells;
 
-        Self { hex_grid }
+        Self {
+            hex_grid,
+            adjacency: OnceLock::new(),
+        }
     }
 
     /// Generates the triangular cells filling the hexagon
@@ -124,6 +131,8 @@ impl TriangularGrid {
 
     /// Returns a mutable reference to the underlying hexagonal grid
     pub fn hex_grid_mut(&mut self) -> &mut HexGrid {
+        // The cells may change, so their neighbors are worked out again
+        self.adjacency = OnceLock::new();
         &mut self.hex_grid
     }
 
@@ -133,12 +142,10 @@ impl TriangularGrid {
         let center = hex_grid.center;
         let mut cells = Vec::with_capacity(24); // Exactly 24 triangles
 
-        // Helper function to create a point at specific angle and distance
-        let point_at = |angle: f64, distance: f64| -> Point {
-            let rad_angle = angle * std::f64::consts::PI / 180.0;
-            let x = center.x + distance * rad_angle.cos();
-            let y = center.y + distance * rad_angle.sin();
-            Point::new(x, y)
+        // Helper function to create a point toward a hexagon corner at some distance
+        let point_at = |corner: usize, distance: f64| -> Point {
+            let (cos, sin) = HEX_DIRECTIONS[corner];
+            Point::new(center.x + distance * cos, center.y + distance * sin)
         };
 
         // Use 1/3 and 2/3 distances to create equiangular triangles that grow from center
@@ -149,10 +156,9 @@ impl TriangularGrid {
         let mut inner_points1 = Vec::with_capacity(6);
         let mut inner_points2 = Vec::with_capacity(6);
 
-        for i in 0..6 {
-            let angle = i as f64 * 60.0; // 60 degrees per hexagon corner
-            inner_points1.push(point_at(angle, inner_distance1));
-            inner_points2.push(point_at(angle, inner_distance2));
+        for corner in 0..6 {
+            inner_points1.push(point_at(corner, inner_distance1));
+            inner_points2.push(point_at(corner, inner_distance2));
         }
 
         // Create the 24 triangles (4 per sector) that grow from center outward
@@ -201,8 +207,14 @@ impl TriangularGrid {
     }
 
     /// Finds all cells adjacent to the given cell
+    ///
+    /// Comparing every pair of cells is quadratic, so the whole table is built
+    /// once per grid and later lookups are constant time.
     pub fn adjacent_cells(&self, cell_id: usize) -> Vec<usize> {
-        self.hex_grid.adjacent_cells(cell_id)
+        let adjacency = self.adjacency.get_or_init(|| {
+            (0..self.cell_count()).map(|id| self.hex_grid.adjacent_cells(id)).collect()
+        });
+        adjacency.get(cell_id).cloned().unwrap_or_default()
     }
 
     /// Gets the centroid point for the cell with the given ID
@@ -214,4 +226,140 @@ impl TriangularGrid {
     pub fn cells(&self) -> &[Cell] {
         &self.hex_grid.cells
     }
+
+    /// Returns the ids of the cells sharing an edge with the given cell, in ascending order
+    ///
+    /// Interior cells have three neighbors; cells on the hexagon boundary have
+    /// fewer. An unknown cell id has no neighbors.
+    pub fn neighbors(&self, cell_id: usize) -> Vec<usize> {
+        self.adjacent_cells(cell_id)
+    }
+
+    /// Returns every pair of adjacent cells once, as `(a, b)` with `a < b`
+    ///
+    /// Together with [`TriangularGrid::cells`] this is the full adjacency graph
+    /// of the grid.
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        let mut edges = Vec::new();
+
+        for cell in self.cells() {
+            for neighbor in self.neighbors(cell.id) {
+                if cell.id < neighbor {
+                    edges.push((cell.id, neighbor));
+                }
+            }
+        }
+
+        edges
+    }
+
+    /// Returns the cells exactly `k` steps away from `cell_id` in the adjacency graph
+    ///
+    /// Ring 0 is the cell itself and ring 1 its neighbors. The result is sorted
+    /// and empty when `k` exceeds the grid's extent or the cell doesn't exist.
+    pub fn ring(&self, cell_id: usize, k: usize) -> Vec<usize> {
+        if cell_id >= self.cell_count() {
+            return Vec::new();
+        }
+
+        // Breadth-first search, stopping once the requested distance is reached
+        let mut distance = vec![usize::MAX; self.cell_count()];
+        let mut queue = VecDeque::new();
+        distance[cell_id] = 0;
+        queue.push_back(cell_id);
+
+        while let Some(current) = queue.pop_front() {
+            if distance[current] == k {
+                continue;
+            }
+            for neighbor in self.neighbors(current) {
+                if distance[neighbor] == usize::MAX {
+                    distance[neighbor] = distance[current] + 1;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        (0..self.cell_count())
+            .filter(|&id| distance[id] == k)
+            .collect()
+    }
+
+    /// Returns the cells in one of the six triangular sectors of the hexagon
+    ///
+    /// Sector `s` spans from the hexagon's center to vertices `s` and `s + 1`,
+    /// numbered by increasing angle from the vertex on the positive x axis
+    /// (clockwise on screen, since SVG's y axis points down). Sectors outside
+    /// `0..6` are empty.
+    pub fn cells_in_sector(&self, sector: usize) -> Vec<usize> {
+        if sector >= 6 {
+            return Vec::new();
+        }
+
+        // Cells are generated sector by sector, each holding the same number of cells
+        let per_sector = self.cell_count() / 6;
+        (sector * per_sector..(sector + 1) * per_sector).collect()
+    }
+
+    /// Returns the id of the cell containing `point`, or `None` outside the hexagon
+    ///
+    /// The point's angle picks one of the six sectors and its barycentric
+    /// coordinates in that sector pick the cell, so the lookup takes constant
+    /// time whatever the density. Points on an edge shared by two cells may
+    /// resolve to either.
+    pub fn cell_at(&self, point: Point) -> Option<usize> {
+        let hex = &self.hex_grid;
+        if self.cell_count() == 0 || !hex.contains_point(&point) {
+            return None;
+        }
+
+        let (dx, dy) = (point.x - hex.center.x, point.y - hex.center.y);
+        let angle = dy.atan2(dx).rem_euclid(2.0 * std::f64::consts::PI);
+        let sector = ((angle / (std::f64::consts::PI / 3.0)) as usize).min(5);
+        let sector_cells = self.cells_in_sector(sector);
+        let contains = |id: &usize| self.cells()[*id].contains_point(&point);
+
+        self.sector_candidate(sector, point)
+            .filter(contains)
+            .or_else(|| sector_cells.iter().copied().find(contains))
+            // Points on a sector border may fall just outside it through rounding
+            .or_else(|| (0..self.cell_count()).find(contains))
+    }
+
+    /// The cell of `sector` that `point` falls in, computed from the subdivision layout
+    ///
+    /// Only valid for subdivided sectors; the 24-cell layout of density 2 has no
+    /// candidate and is searched instead.
+    fn sector_candidate(&self, sector: usize, point: Point) -> Option<usize> {
+        let hex = &self.hex_grid;
+        let n = hex.grid_density as usize;
+        if n == 2 {
+            return None;
+        }
+
+        // Solve point = center + u (v1 - center) + v (v2 - center)
+        let (c, v1, v2) = (hex.center, hex.vertices[sector], hex.vertices[(sector + 1) % 6]);
+        let (ax, ay, bx, by) = (v1.x - c.x, v1.y - c.y, v2.x - c.x, v2.y - c.y);
+        let determinant = ax * by - ay * bx;
+        let (px, py) = (point.x - c.x, point.y - c.y);
+        let u = (px * by - py * bx) / determinant * n as f64;
+        let v = (ax * py - ay * px) / determinant * n as f64;
+
+        let i = (u.max(0.0) as usize).min(n - 1);
+        let j = (v.max(0.0) as usize).min(n - 1 - i);
+        let upward = (u - i as f64) + (v - j as f64) < 1.0 || j == n - 1 - i;
+
+        // Row k holds n - k upward cells interleaved with n - k - 1 downward ones
+        let row_start: usize = (0..i).map(|k| 2 * (n - k) - 1).sum();
+        let offset = row_start + 2 * j + usize::from(!upward);
+        Some(sector * (self.cell_count() / 6) + offset)
+    }
+
+    /// Returns the sector (0 to 5) containing the given cell
+    pub fn sector_of(&self, cell_id: usize) -> Option<usize> {
+        if cell_id >= self.cell_count() {
+            return None;
+        }
+        Some(cell_id / (self.cell_count() / 6))
+    }
 }
@@ -0,0 +1,136 @@
+use super::GeneratedDesign;
+
+/// How much detail to give up so small renders stay legible
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lod {
+    /// Simplify only when shapes or cells would come out smaller than a few pixels
+    #[default]
+    Auto,
+    /// Always render every cell as generated
+    Off,
+    /// Simplify earlier and harder, for favicons and other very small icons
+    Aggressive,
+}
+
+/// Smallest shape worth keeping, cell size below which outlines are smoothed,
+/// both in square output pixels, and how many smoothing passes to run
+struct Thresholds {
+    min_shape_area: f64,
+    smooth_cell_area: f64,
+    passes: usize,
+}
+
+impl Lod {
+    /// Names accepted by [`Lod::from_str`](std::str::FromStr)
+    pub fn names() -> Vec<&'static str> {
+        vec!["auto", "off", "aggressive"]
+    }
+
+    fn thresholds(&self) -> Option<Thresholds> {
+        match self {
+            Lod::Auto => Some(Thresholds {
+                min_shape_area: 16.0,
+                smooth_cell_area: 4.0,
+                passes: 1,
+            }),
+            Lod::Off => None,
+            Lod::Aggressive => Some(Thresholds {
+                min_shape_area: 64.0,
+                smooth_cell_area: 16.0,
+                passes: 2,
+            }),
+        }
+    }
+
+    /// Simplifies a design for rendering at `width` x `height` pixels with `padding`
+    ///
+    /// Shapes smaller than a few pixels are merged into the neighbor they share
+    /// the longest border with, or dropped when they touch none, and once cells
+    /// are this small the spurs and pinholes along shape outlines are smoothed
+    /// away. Large renders come back unchanged.
+    pub fn apply(&self, design: &GeneratedDesign, width: u32, height: u32, padding: f64) -> GeneratedDesign {
+        let Some(thresholds) = self.thresholds() else {
+            return design.clone();
+        };
+
+        // The hexagon fills the unpadded part of the shorter side
+        let radius = width.min(height) as f64 / 2.0 * (1.0 - 2.0 * padding.clamp(0.0, 0.45));
+        let hexagon_area = 3.0 * 3f64.sqrt() / 2.0 * radius * radius;
+        let cell_area = hexagon_area / design.grid().cell_count().max(1) as f64;
+
+        let min_cells = (thresholds.min_shape_area / cell_area).ceil() as usize;
+        let mut simplified = design.with_small_shapes_merged(min_cells);
+        if cell_area < thresholds.smooth_cell_area {
+            for _ in 0..thresholds.passes {
+                simplified = simplified.with_smoothed_outlines();
+            }
+        }
+        simplified
+    }
+}
+
+impl std::fmt::Display for Lod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Lod::Auto => write!(f, "auto"),
+            Lod::Off => write!(f, "off"),
+            Lod::Aggressive => write!(f, "aggressive"),
+        }
+    }
+}
+
+impl std::str::FromStr for Lod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Lod::Auto),
+            "off" => Ok(Lod::Off),
+            "aggressive" => Ok(Lod::Aggressive),
+            _ => Err(format!(
+                "Unknown level of detail '{}' (expected one of: {})",
+                s,
+                Self::names().join(", ")
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::GeneratorConfig;
+
+    fn visible(design: &GeneratedDesign) -> usize {
+        design.shapes().iter().filter(|shape| shape.cell_count() > 0).count()
+    }
+
+    #[test]
+    fn test_lod_leaves_large_renders_alone() {
+        let design = GeneratorConfig::new(8, 6, 0.8, Some(3)).generate().unwrap();
+        assert_eq!(Lod::Auto.apply(&design, 512, 512, 0.0).checksum(), design.checksum());
+        assert_eq!(Lod::Off.apply(&design, 8, 8, 0.0).checksum(), design.checksum());
+    }
+
+    #[test]
+    fn test_lod_simplifies_small_renders() {
+        let design = GeneratorConfig::new(8, 6, 0.8, Some(3)).generate().unwrap();
+        let auto = Lod::Auto.apply(&design, 16, 16, 0.0);
+        let aggressive = Lod::Aggressive.apply(&design, 16, 16, 0.0);
+
+        assert_ne!(auto.checksum(), design.checksum());
+        assert!(visible(&aggressive) <= visible(&auto));
+        assert!(visible(&auto) <= visible(&design));
+        assert!(visible(&aggressive) >= 1);
+        assert_eq!(aggressive.shapes().len(), design.shapes().len());
+    }
+
+    #[test]
+    fn test_lod_names() {
+        for name in Lod::names() {
+            assert_eq!(name.parse::<Lod>().unwrap().to_string(), name);
+        }
+        let error = "tiny".parse::<Lod>().unwrap_err();
+        assert_eq!(error, "Unknown level of detail 'tiny' (expected one of: auto, off, aggressive)");
+    }
+}
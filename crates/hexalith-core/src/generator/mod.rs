@@ -0,0 +1,1449 @@
+pub mod color;
+pub mod compositor;
+mod design;
+pub mod grid;
+mod lod;
+pub mod params;
+pub mod shape;
+mod version;
+
+use crate::Result;
+use color::ColorManager;
+use grid::TriangularGrid;
+use shape::{Shape, ShapeGenerator};
+use std::collections::BTreeSet;
+
+// Re-export Theme enum for use in other modules
+pub use color::{BlendSpace, Theme};
+pub use compositor::OverlapOpacity;
+pub use design::{ColorBlend, GeneratedDesign};
+pub use lod::Lod;
+pub use params::Params;
+pub use version::AlgoVersion;
+pub use shape::{
+    Aesthetic, Anchor, Effort, GuideMask, ShapeAlgorithm, ShapeMetrics, ShapeStyle, Symmetry, SymmetryColors, SymmetryGroup,
+};
+
+/// Immutable generation settings
+///
+/// A config is cheap to clone and can be shared between threads; every call to
+/// [`GeneratorConfig::generate`] produces a new, independent [`GeneratedDesign`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratorConfig {
+    grid_size: u8,
+    shapes_count: u8,
+    opacity: f32,
+    seed: Option<u64>,
+    theme: Theme,
+    allow_overlap: bool,
+    style: ShapeStyle,
+    anchor: Anchor,
+    aesthetic: Aesthetic,
+    effort: Effort,
+    symmetry: Symmetry,
+    symmetry_colors: SymmetryColors,
+    pack: bool,
+    mosaic: bool,
+    overlap_ratio: Option<f32>,
+    blend_space: BlendSpace,
+    overlap_opacity: OverlapOpacity,
+    guide: Option<GuideMask>,
+    color_by_size: bool,
+    unique_colors: bool,
+    algo_version: AlgoVersion,
+}
+
+impl GeneratorConfig {
+    /// Creates a config, clamping values into the ranges defined in [`params`]
+    pub fn new(grid_size: u8, shapes_count: u8, opacity: f32, seed: Option<u64>) -> Self {
+        Self {
+            grid_size: grid_size.clamp(*params::GRID_SIZE_RANGE.start(), *params::GRID_SIZE_RANGE.end()),
+            shapes_count: shapes_count.clamp(*params::SHAPES_RANGE.start(), *params::SHAPES_RANGE.end()),
+            opacity: opacity.clamp(*params::OPACITY_RANGE.start(), *params::OPACITY_RANGE.end()),
+            seed,
+            theme: Theme::Mesos, // Set Mesos as the default theme
+            allow_overlap: false,
+            style: ShapeStyle::Balanced,
+            anchor: Anchor::Center,
+            aesthetic: Aesthetic::Balanced,
+            effort: Effort::Medium,
+            symmetry: Symmetry::None,
+            symmetry_colors: SymmetryColors::Same,
+            pack: false,
+            mosaic: false,
+            overlap_ratio: None,
+            blend_space: BlendSpace::Oklab,
+            overlap_opacity: OverlapOpacity::Inherit,
+            guide: None,
+            color_by_size: false,
+            unique_colors: false,
+            algo_version: AlgoVersion::LATEST,
+        }
+    }
+
+    /// Creates a config, rejecting out-of-range values instead of clamping them
+    pub fn try_new(grid_size: u8, shapes_count: u8, opacity: f32, seed: Option<u64>) -> Result<Self> {
+        Params::new(grid_size, shapes_count, opacity).validate()?;
+        Ok(Self::new(grid_size, shapes_count, opacity, seed))
+    }
+
+    /// Replace the seed, keeping every other setting
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Set the color theme by theme enum
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Set the shape opacity, clamped like [`GeneratorConfig::new`]
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity.clamp(*params::OPACITY_RANGE.start(), *params::OPACITY_RANGE.end());
+        self
+    }
+
+    /// Set the color theme by name
+    pub fn with_color_scheme(mut self, color_scheme: &str) -> Self {
+        self.theme = Theme::from(color_scheme);
+        self
+    }
+
+    pub fn with_allow_overlap(mut self, allow_overlap: bool) -> Self {
+        self.allow_overlap = allow_overlap;
+        self
+    }
+
+    /// Set the algorithm used to grow shapes
+    pub fn with_style(mut self, style: ShapeStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Set where the first shape starts growing
+    pub fn with_anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Set the scoring profile shapes are grown and picked by
+    pub fn with_aesthetic(mut self, aesthetic: Aesthetic) -> Self {
+        self.aesthetic = aesthetic;
+        self
+    }
+
+    /// Set how many candidate shapes are grown to pick each shape from
+    pub fn with_effort(mut self, effort: Effort) -> Self {
+        self.effort = effort;
+        self
+    }
+
+    /// Grow shapes in one wedge and repeat them around the center
+    ///
+    /// Symmetric shapes are laid side by side, so overlap and the shape style
+    /// don't apply.
+    pub fn with_symmetry(mut self, symmetry: Symmetry) -> Self {
+        self.symmetry = symmetry;
+        self
+    }
+
+    /// Set whether the rotated copies of a symmetric shape share its color or turn through the palette
+    pub fn with_symmetry_colors(mut self, symmetry_colors: SymmetryColors) -> Self {
+        self.symmetry_colors = symmetry_colors;
+        self
+    }
+
+    /// Partition every grid cell among the shapes instead of growing them
+    pub fn with_pack(mut self, pack: bool) -> Self {
+        self.pack = pack;
+        self
+    }
+
+    /// Cover the grid with dozens of one- to three-cell shapes instead of growing `shapes_count` of them
+    pub fn with_mosaic(mut self, mosaic: bool) -> Self {
+        self.mosaic = mosaic;
+        self
+    }
+
+    /// Aim for this fraction of the second shape to overlap the first
+    ///
+    /// Only applies when overlap is allowed; `None` leaves the overlap to chance.
+    pub fn with_overlap_ratio(mut self, ratio: Option<f32>) -> Self {
+        self.overlap_ratio = ratio.map(|r| r.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Set the color space overlaps and theme blends are mixed in
+    pub fn with_blend_space(mut self, blend_space: BlendSpace) -> Self {
+        self.blend_space = blend_space;
+        self
+    }
+
+    /// Set how opaque the region where shapes overlap is, independent of the shapes' own opacity
+    pub fn with_overlap_opacity(mut self, overlap_opacity: OverlapOpacity) -> Self {
+        self.overlap_opacity = overlap_opacity;
+        self
+    }
+
+    /// Grow shapes toward the dark areas of a guide image
+    pub fn with_guide(mut self, guide: Option<GuideMask>) -> Self {
+        self.guide = guide;
+        self
+    }
+
+    /// Color shapes by size: the largest takes the theme's primary color, the next its secondary, and so on
+    ///
+    /// See [`ColorManager::theme_roles`] for each theme's role order.
+    pub fn with_color_by_size(mut self, color_by_size: bool) -> Self {
+        self.color_by_size = color_by_size;
+        self
+    }
+
+    /// Never give two shapes the same color, adding variants of palette colors if it runs out
+    pub fn with_unique_colors(mut self, unique_colors: bool) -> Self {
+        self.unique_colors = unique_colors;
+        self
+    }
+
+    /// Generate with this version of the algorithm instead of the latest
+    pub fn with_algo_version(mut self, algo_version: AlgoVersion) -> Self {
+        self.algo_version = algo_version;
+        self
+    }
+
+    pub fn grid_size(&self) -> u8 {
+        self.grid_size
+    }
+
+    pub fn shapes_count(&self) -> u8 {
+        self.shapes_count
+    }
+
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn theme(&self) -> Theme {
+        self.theme.clone()
+    }
+
+    pub fn allow_overlap(&self) -> bool {
+        self.allow_overlap
+    }
+
+    pub fn style(&self) -> ShapeStyle {
+        self.style
+    }
+
+    pub fn anchor(&self) -> Anchor {
+        self.anchor
+    }
+
+    pub fn aesthetic(&self) -> Aesthetic {
+        self.aesthetic
+    }
+
+    pub fn effort(&self) -> Effort {
+        self.effort
+    }
+
+    pub fn symmetry(&self) -> Symmetry {
+        self.symmetry
+    }
+
+    pub fn symmetry_colors(&self) -> SymmetryColors {
+        self.symmetry_colors
+    }
+
+    pub fn pack(&self) -> bool {
+        self.pack
+    }
+
+    pub fn mosaic(&self) -> bool {
+        self.mosaic
+    }
+
+    pub fn overlap_ratio(&self) -> Option<f32> {
+        self.overlap_ratio
+    }
+
+    pub fn blend_space(&self) -> BlendSpace {
+        self.blend_space
+    }
+
+    pub fn overlap_opacity(&self) -> OverlapOpacity {
+        self.overlap_opacity
+    }
+
+    pub fn guide(&self) -> Option<&GuideMask> {
+        self.guide.as_ref()
+    }
+
+    pub fn color_by_size(&self) -> bool {
+        self.color_by_size
+    }
+
+    pub fn unique_colors(&self) -> bool {
+        self.unique_colors
+    }
+
+    pub fn algo_version(&self) -> AlgoVersion {
+        self.algo_version
+    }
+
+    /// Lazily generates designs for `seed_start`, `seed_start + 1`, and so on
+    ///
+    /// Each design keeps every other setting of this config, so the sequence is
+    /// the same every time. The iterator ends at `u64::MAX` or at the first
+    /// design that fails to generate.
+    pub fn iter_designs(&self, seed_start: u64) -> impl Iterator<Item = GeneratedDesign> {
+        let config = self.clone();
+        (seed_start..=u64::MAX).map_while(move |seed| config.clone().with_seed(Some(seed)).generate().ok())
+    }
+
+    /// Runs the generation pipeline and returns the finished design
+    ///
+    /// The configuration is not modified, so a shared config can be used to
+    /// generate designs from several threads at once. Fails when the theme
+    /// has no colors to pick from, see [`Theme::validate`].
+    pub fn generate(&self) -> Result<GeneratedDesign> {
+        self.theme.validate()?;
+        match self.algo_version {
+            AlgoVersion::V1 => self.generate_v1(),
+        }
+    }
+
+    /// The pipeline of [`AlgoVersion::V1`], frozen by `test_algo_v1_is_frozen`
+    fn generate_v1(&self) -> Result<GeneratedDesign> {
+        // Initialize the triangular grid
+        let grid = TriangularGrid::new(100.0, self.grid_size);
+        let mut shapes = Vec::new();
+        let mut blends = Vec::new();
+
+        let size_range = self.size_range(&grid);
+
+        // Generate the shapes; colors are assigned once every shape is placed,
+        // so a new theme or opacity can reuse the grown cells
+        let mut shape_generator = ShapeGenerator::new(&grid, self.seed);
+        shape_generator
+            .set_anchor(self.anchor)
+            .set_aesthetic(self.aesthetic)
+            .set_effort(self.effort)
+            .set_guide(self.guide.clone());
+
+        if self.mosaic {
+            // Mosaic tiles decide their own count and never overlap
+            shapes = shape_generator.generate_mosaic(self.opacity);
+        } else if self.pack {
+            // Packed shapes tile the whole grid, so neither style nor overlap applies
+            shapes = shape_generator.generate_packed(self.shapes_count as usize, self.opacity);
+        } else if self.symmetry != Symmetry::None {
+            shapes = shape_generator.generate_symmetric(
+                self.symmetry,
+                self.shapes_count as usize,
+                self.opacity,
+                size_range,
+            );
+        } else if let Some(algorithm) = self.style.algorithm() {
+            // Pluggable styles lay shapes side by side; overlap blending only
+            // applies to the balanced style
+            let mut used_cells = BTreeSet::new();
+
+            for _ in 0..self.shapes_count {
+                let shape = shape_generator.generate_with_algorithm(
+                    algorithm.as_ref(),
+                    self.opacity,
+                    size_range,
+                    &used_cells,
+                );
+
+                if shape.cells.is_empty() {
+                    break;
+                }
+                used_cells.extend(shape.cells.iter().copied());
+                shapes.push(shape);
+            }
+        } else if self.allow_overlap && self.shapes_count >= 2 {
+            // Generate two overlapping shapes with improved algorithms
+            let shape1 = shape_generator.generate_balanced_shape(
+                self.opacity,
+                size_range.1, // Use larger size for better overlap chance
+            );
+
+            // A target ratio steers the second shape's growth along the first
+            let shape2 = match self.overlap_ratio {
+                Some(ratio) => shape_generator.generate_overlapping_shape(
+                    self.opacity,
+                    size_range.1,
+                    &shape1.cells,
+                    ratio,
+                ),
+                None => shape_generator.generate_balanced_shape(self.opacity, size_range.1),
+            };
+
+            // Keep the non-overlapping part of each shape, plus the overlap if there is one
+            let mut split = compositor::split(&shape1, &shape2, self.blend_space);
+            if let Some(overlap) = &mut split.overlap {
+                overlap.opacity = self.overlap_opacity.resolve(shape1.opacity, shape2.opacity);
+            }
+            let (parts, blend) = split.into_shapes(shapes.len());
+            shapes.extend(parts);
+            blends.extend(blend);
+
+            // Create a set of cells already used
+            let mut used_cells = BTreeSet::new();
+            for shape in &shapes {
+                for &cell in &shape.cells {
+                    used_cells.insert(cell);
+                }
+            }
+
+            // For harmony, additional shapes are balanced shapes that avoid existing ones
+            for _ in 2..self.shapes_count {
+                let shape = shape_generator.generate_shape_avoiding_cells(
+                    self.opacity,
+                    size_range.1,
+                    &used_cells,
+                );
+
+                // Update the used cells
+                for &cell in &shape.cells {
+                    used_cells.insert(cell);
+                }
+
+                shapes.push(shape);
+            }
+        } else {
+            // Use the improved algorithm without overlap
+            shapes = shape_generator.generate_shapes(
+                self.opacity,
+                self.shapes_count as usize,
+                size_range,
+            );
+        }
+
+        for (id, shape) in shapes.iter_mut().enumerate() {
+            shape.id = id;
+        }
+        // Shapes are grown uncolored; the design colors them
+        let winning_candidates = shape_generator.winning_candidates().to_vec();
+        Ok(GeneratedDesign::new(self.clone(), grid, shapes, blends).with_winning_candidates(winning_candidates))
+    }
+
+    /// The smallest and largest number of cells a grown shape aims for
+    pub(crate) fn size_range(&self, grid: &TriangularGrid) -> (usize, usize) {
+        // Calculate shape size based on grid density
+        // Higher density = smaller shapes
+        let total_cells = grid.cell_count();
+
+        // With grid density of 2, we have exactly 24 cells, like the original logo generator
+        // Let's adjust our size range to work well with both small and large grid densities
+        let min_size = if self.grid_size <= 2 {
+            // For grid_size 2 (24 cells total), use 2-5 cells per shape
+            2
+        } else {
+            (total_cells as f32 * 0.01).round() as usize
+        };
+
+        let max_size = if self.grid_size <= 2 {
+            // For grid_size 2, limit the max size to keep multiple shapes visible
+            5.min(total_cells / self.shapes_count as usize)
+        } else {
+            (total_cells as f32 * 0.05).round() as usize
+        };
+
+        (min_size, max_size.max(min_size + 1))
+    }
+
+    /// Colors grown shapes from the theme, the way [`GeneratorConfig::generate`] does
+    ///
+    /// Colors come from their own seeded random stream, so recoloring the same
+    /// shapes with another theme matches generating with that theme.
+    pub(crate) fn assign_colors(&self, grid: &TriangularGrid, shapes: &mut [Shape], blends: &[ColorBlend]) {
+        let mut color_manager = ColorManager::with_theme_in(self.theme.clone(), self.seed, self.blend_space);
+
+        if shapes.iter().any(|shape| shape.symmetry.is_some()) {
+            // Rotated copies share their shape's color, and with rotating colors
+            // each copy then moves one step along the colors of the shapes
+            color_manager.assign_harmonious_colors(grid, shapes);
+            if self.symmetry_colors == SymmetryColors::Rotate {
+                let mut palette: Vec<String> = shapes
+                    .iter()
+                    .filter(|shape| shape.symmetry.is_some_and(|symmetry| symmetry.copy == 0))
+                    .map(|shape| shape.fill().to_string())
+                    .collect();
+                // A single shape still turns through as many colors as it has copies
+                while palette.len() < self.symmetry.fold() {
+                    palette.push(color_manager.get_different_color(&palette));
+                }
+                for shape in shapes.iter_mut() {
+                    if let Some(symmetry) = shape.symmetry {
+                        shape.color = Some(palette[(symmetry.group + symmetry.copy) % palette.len()].clone());
+                    }
+                }
+            }
+        } else if self.mosaic {
+            // Tiles touch on every side, so keeping neighbors apart matters more than ranking them
+            color_manager.assign_harmonious_colors(grid, shapes);
+        } else if self.color_by_size {
+            // Rank the grown shapes by area; blends are colored from their sources below
+            let mut by_size: Vec<usize> = (0..shapes.len())
+                .filter(|&index| blends.iter().all(|blend| blend.shape != index))
+                .collect();
+            by_size.sort_by_key(|&index| std::cmp::Reverse(shapes[index].cell_count()));
+
+            let roles = ColorManager::theme_roles(&self.theme);
+            for (rank, &index) in by_size.iter().enumerate() {
+                shapes[index].color = Some(roles[rank % roles.len()].clone());
+            }
+            for blend in blends {
+                let [a, b] = blend.sources;
+                let color = compositor::blend_color(shapes[a].fill(), shapes[b].fill(), self.blend_space);
+                shapes[blend.shape].color = Some(color);
+            }
+        } else if self.pack || self.style.algorithm().is_some() {
+            // Packed shapes and shapes from pluggable styles often don't touch,
+            // so give each its own color rather than only keeping neighbors apart,
+            // synthesizing more once the palette runs out
+            let mut used_colors = Vec::with_capacity(shapes.len());
+            for shape in shapes.iter_mut() {
+                let mut color = color_manager.get_different_color(&used_colors);
+                if used_colors.contains(&color) {
+                    color = color_manager
+                        .expanded_palette(used_colors.len() + 1)
+                        .into_iter()
+                        .find(|color| !used_colors.contains(color))
+                        .unwrap_or(color);
+                }
+                used_colors.push(color.clone());
+                shape.color = Some(color);
+            }
+        } else if self.allow_overlap && self.shapes_count >= 2 && shapes.len() >= 2 {
+            // Get colors with high contrast
+            let available_colors = color_manager.get_random_colors(self.palette_size());
+
+            // Take the first color
+            let color1 = available_colors[0].clone();
+
+            // Find the color with highest contrast against the first color
+            let color2 = {
+                let mut best_color = available_colors[1].clone();
+                let mut best_contrast = ColorManager::color_contrast(&color1, &best_color);
+
+                for color in available_colors.iter().skip(2) {
+                    let contrast = ColorManager::color_contrast(&color1, color);
+                    if contrast > best_contrast {
+                        best_contrast = contrast;
+                        best_color = color.clone();
+                    }
+                }
+
+                best_color
+            };
+
+            // The overlap mixes both colors, alpha included
+            let blend = compositor::blend_color(&color1, &color2, self.blend_space);
+            shapes[0].color = Some(color1.clone());
+            shapes[1].color = Some(color2.clone());
+            let mut colored = 2;
+            if let Some(overlap) = blends.first() {
+                shapes[overlap.shape].color = Some(blend);
+                colored = overlap.shape + 1;
+            }
+
+            // Additional shapes use the remaining palette colors first
+            let additional_colors_needed = shapes.len() - colored;
+            let used_colors = [color1, color2];
+            let mut additional_colors = Vec::new();
+            for color in available_colors {
+                if additional_colors.len() >= additional_colors_needed {
+                    break;
+                }
+                if !used_colors.contains(&color) && !additional_colors.contains(&color) {
+                    additional_colors.push(color);
+                }
+            }
+
+            // If we still need more colors, get random ones that are different from existing
+            let current_colors: Vec<String> = shapes[..colored].iter().map(|s| s.fill().to_string()).collect();
+            while additional_colors.len() < additional_colors_needed {
+                additional_colors.push(color_manager.get_different_color(&current_colors));
+            }
+
+            for (shape, color) in shapes[colored..].iter_mut().zip(additional_colors) {
+                shape.color = Some(color);
+            }
+        } else {
+            // Assign harmonious colors to avoid same-colored neighbors
+            color_manager.assign_harmonious_colors(grid, shapes);
+        }
+
+        if self.unique_colors {
+            color_manager.make_colors_unique(shapes);
+        }
+    }
+
+    /// Determine number of colors to use based on grid size and shape count
+    fn palette_size(&self) -> usize {
+        // We want at least as many colors as shapes
+        // but also want enough colors to provide good variety
+        let base_size = self.shapes_count as usize + 2; // Add 2 for some extra variety
+
+        // For larger grids, we may want more colors for variety
+        if self.grid_size >= 4 {
+            base_size + 2
+        } else {
+            base_size
+        }
+    }
+}
+
+/// Stateful convenience wrapper around [`GeneratorConfig`] that keeps the last design
+pub struct Generator {
+    config: GeneratorConfig,
+    design: Option<GeneratedDesign>,
+}
+
+impl Generator {
+    pub fn new(grid_size: u8, shapes_count: u8, opacity: f32, seed: Option<u64>) -> Self {
+        Self {
+            config: GeneratorConfig::new(grid_size, shapes_count, opacity, seed),
+            design: None,
+        }
+    }
+
+    /// Set the color theme by theme enum
+    pub fn set_theme(&mut self, theme: Theme) -> &mut Self {
+        self.config.theme = theme;
+        self
+    }
+
+    /// Set the color theme by name
+    pub fn set_color_scheme(&mut self, color_scheme: &str) -> &mut Self {
+        self.config.theme = Theme::from(color_scheme);
+        self
+    }
+
+    /// Get a list of available theme names
+    pub fn available_themes() -> Vec<String> {
+        ColorManager::available_themes()
+    }
+
+    pub fn set_allow_overlap(&mut self, allow_overlap: bool) -> &mut Self {
+        self.config.allow_overlap = allow_overlap;
+        self
+    }
+
+    pub fn set_style(&mut self, style: ShapeStyle) -> &mut Self {
+        self.config.style = style;
+        self
+    }
+
+    pub fn set_anchor(&mut self, anchor: Anchor) -> &mut Self {
+        self.config.anchor = anchor;
+        self
+    }
+
+    pub fn set_aesthetic(&mut self, aesthetic: Aesthetic) -> &mut Self {
+        self.config.aesthetic = aesthetic;
+        self
+    }
+
+    pub fn set_effort(&mut self, effort: Effort) -> &mut Self {
+        self.config.effort = effort;
+        self
+    }
+
+    pub fn set_algo_version(&mut self, algo_version: AlgoVersion) -> &mut Self {
+        self.config.algo_version = algo_version;
+        self
+    }
+
+    pub fn set_pack(&mut self, pack: bool) -> &mut Self {
+        self.config.pack = pack;
+        self
+    }
+
+    pub fn set_mosaic(&mut self, mosaic: bool) -> &mut Self {
+        self.config.mosaic = mosaic;
+        self
+    }
+
+    pub fn set_color_by_size(&mut self, color_by_size: bool) -> &mut Self {
+        self.config.color_by_size = color_by_size;
+        self
+    }
+
+    pub fn set_unique_colors(&mut self, unique_colors: bool) -> &mut Self {
+        self.config.unique_colors = unique_colors;
+        self
+    }
+
+    pub fn set_overlap_ratio(&mut self, ratio: Option<f32>) -> &mut Self {
+        self.config.overlap_ratio = ratio.map(|r| r.clamp(0.0, 1.0));
+        self
+    }
+
+    pub fn set_blend_space(&mut self, blend_space: BlendSpace) -> &mut Self {
+        self.config.blend_space = blend_space;
+        self
+    }
+
+    pub fn set_overlap_opacity(&mut self, overlap_opacity: OverlapOpacity) -> &mut Self {
+        self.config.overlap_opacity = overlap_opacity;
+        self
+    }
+
+    /// Generates a new design from the current settings, replacing the previous one
+    pub fn generate(&mut self) -> Result<()> {
+        self.design = Some(self.config.generate()?);
+        Ok(())
+    }
+
+    pub fn config(&self) -> &GeneratorConfig {
+        &self.config
+    }
+
+    /// Regrows every shape of the current design except the `locked` ones from `new_seed`
+    ///
+    /// See [`GeneratedDesign::with_unlocked_rerolled`]. Fails if `generate()`
+    /// hasn't been called or a locked index is out of range.
+    pub fn regenerate_unlocked(&mut self, locked: &[usize], new_seed: u64) -> Result<()> {
+        let design = self
+            .design
+            .as_ref()
+            .ok_or("Grid not initialized. Call generate() first.")?;
+        self.design = Some(design.with_unlocked_rerolled(locked, new_seed)?);
+        Ok(())
+    }
+
+    /// Lazily generates successive designs from the current settings, one per seed from `seed_start`
+    ///
+    /// See [`GeneratorConfig::iter_designs`]; the last design kept by this
+    /// generator is left untouched.
+    pub fn iter_designs(&self, seed_start: u64) -> impl Iterator<Item = GeneratedDesign> {
+        self.config.iter_designs(seed_start)
+    }
+
+    /// The most recently generated design, if `generate()` has been called
+    pub fn design(&self) -> Option<&GeneratedDesign> {
+        self.design.as_ref()
+    }
+
+    pub fn grid(&self) -> Option<&TriangularGrid> {
+        self.design.as_ref().map(|design| design.grid())
+    }
+
+    pub fn shapes(&self) -> &[Shape] {
+        self.design.as_ref().map_or(&[], |design| design.shapes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_many_shapes_past_the_palette() {
+        assert_eq!(GeneratorConfig::new(8, 30, 0.8, None).shapes_count(), 25);
+
+        // Packing 25 shapes needs more colors than the theme has
+        let config = GeneratorConfig::try_new(8, 25, 0.8, Some(3)).unwrap().with_theme(Theme::Blues).with_pack(true);
+        let design = config.generate().unwrap();
+        assert_eq!(design.shapes().len(), 25);
+        assert!(ColorManager::theme_palette(&Theme::Blues).len() < 25);
+        let mut colors: Vec<&str> = design.shapes().iter().map(|shape| shape.fill()).collect();
+        colors.sort();
+        colors.dedup();
+        assert_eq!(colors.len(), 25);
+
+        // Even the smallest grid copes, with as many shapes as fit
+        for grid_size in [2, 4] {
+            let design = GeneratorConfig::try_new(grid_size, 25, 0.8, Some(3)).unwrap().generate().unwrap();
+            assert!(design.shapes().len() > 10);
+        }
+    }
+
+    #[test]
+    fn test_algo_v1_is_frozen() {
+        assert_eq!("latest".parse::<AlgoVersion>(), Ok(AlgoVersion::LATEST));
+        assert_eq!("v1".parse::<AlgoVersion>(), Ok(AlgoVersion::V1));
+        assert!("9".parse::<AlgoVersion>().unwrap_err().contains("1, latest"));
+
+        // Version 1 must keep growing exactly these designs; a change to growth
+        // that breaks this belongs in a new version
+        let base = |seed| GeneratorConfig::new(4, 4, 0.8, Some(seed)).with_algo_version(AlgoVersion::V1);
+        let configs = [
+            base(1),
+            base(2).with_allow_overlap(true),
+            base(3).with_style(ShapeStyle::Ribbon),
+            base(4).with_pack(true).with_theme(Theme::Blues),
+            base(5).with_symmetry(Symmetry::Three),
+            base(6).with_effort(Effort::Candidates(shape::MAX_CANDIDATES)),
+            GeneratorConfig::new(2, 3, 0.5, Some(6)).with_algo_version(AlgoVersion::V1),
+        ];
+        let checksums: Vec<String> = configs
+            .iter()
+            .map(|config| format!("{:016x}", config.generate().unwrap().checksum()))
+            .collect();
+        assert_eq!(
+            checksums,
+            vec![
+                "d7048e6ba43875db",
+                "4db8648df8c57b2f",
+                "b4931c181fa093eb",
+                "f973f92d1eb47e65",
+                "b1855f13fff1a38a",
+                "7e9cc5c7e4f9bf6d",
+                "d19fa8bcd8a77840",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extreme_parameters_never_panic() {
+        let one_color = Theme::Custom { name: "ink".into(), colors: vec!["#112233".into()], weights: vec![0] };
+        let styles = [ShapeStyle::Balanced, ShapeStyle::Ribbon, ShapeStyle::OrganicCa];
+        let efforts = [
+            Effort::Low,
+            Effort::Medium,
+            Effort::High,
+            Effort::Candidates(1),
+            Effort::Candidates(shape::MAX_CANDIDATES),
+        ];
+        let mut seed = 0;
+        for grid_size in [2, 8] {
+            // 25 shapes on grid size 2 leaves about one cell per shape
+            for shapes in [1, 2, 25] {
+                for (theme, effort) in [Theme::Mesos, one_color.clone()].iter().flat_map(|t| efforts.map(|e| (t, e))) {
+                    for mode in 0..5 {
+                        seed += 1;
+                        let config = GeneratorConfig::new(grid_size, shapes, f32::NAN, Some(seed))
+                            .with_theme(theme.clone())
+                            .with_effort(effort)
+                            .with_style(styles[seed as usize % 3])
+                            .with_anchor(Anchor::Corner)
+                            .with_unique_colors(seed % 2 == 0)
+                            .with_color_by_size(seed % 4 == 1);
+                        let config = match mode {
+                            0 => config,
+                            1 => config.with_allow_overlap(true).with_overlap_ratio(Some(f32::NAN)),
+                            2 => config.with_pack(true),
+                            3 => config.with_mosaic(true),
+                            _ => config.with_symmetry(Symmetry::Six).with_symmetry_colors(SymmetryColors::Rotate),
+                        };
+
+                        let design = config.generate().unwrap();
+                        let emptied = design.without_small_shapes(usize::MAX);
+                        assert!(emptied.with_unlocked_rerolled(&[0], seed).is_ok());
+                        emptied.with_small_shapes_merged(usize::MAX).with_smoothed_outlines().shape_metrics();
+                        design.with_opacity(f32::NAN).similarity(&emptied);
+                    }
+                }
+            }
+        }
+
+        // Themes without colors are errors, not panics, and recoloring with one falls back to Mesos
+        let empty = Theme::Custom { name: "void".into(), colors: Vec::new(), weights: Vec::new() };
+        let blend = Theme::Blend(Box::new(Theme::Blues), Box::new(empty.clone()), 0.5);
+        let unbounded = Theme::Blend(Box::new(Theme::Blues), Box::new(Theme::Reds), f32::NAN);
+        for theme in [empty.clone(), blend.clone(), unbounded] {
+            let error = GeneratorConfig::new(4, 4, 0.8, Some(1)).with_theme(theme).generate().unwrap_err();
+            assert!(error.to_string().contains("void") || error.to_string().contains("blend amount"));
+        }
+        let design = GeneratorConfig::new(4, 4, 0.8, Some(1)).generate().unwrap();
+        assert!(design.with_theme(empty).shapes().iter().all(|shape| shape.color.is_some()));
+        let mesos = Theme::Blend(Box::new(Theme::Blues), Box::new(Theme::Mesos), 0.5);
+        assert_eq!(ColorManager::theme_palette(&blend), ColorManager::theme_palette(&mesos));
+
+        // So are blends pointing past the shapes
+        let bad_blend = ColorBlend { shape: 5, sources: [0, 9] };
+        let shapes = design.shapes().to_vec();
+        let rebuilt = GeneratedDesign::new(design.config().clone(), design.grid().clone(), shapes, vec![bad_blend]);
+        assert!(rebuilt.blends().is_empty());
+    }
+
+    #[test]
+    fn test_config_generates_independent_designs() {
+        let config = GeneratorConfig::new(4, 3, 0.8, Some(42)).with_allow_overlap(true);
+
+        // Generating twice must not accumulate shapes in either design
+        let first = config.generate().unwrap();
+        let second = config.generate().unwrap();
+
+        assert!(!first.shapes().is_empty());
+        assert!(first.shapes().len() <= 4);
+        assert!(second.shapes().len() <= 4);
+        assert_eq!(first.config(), &config);
+        assert_eq!(first.grid().cell_count(), 96);
+    }
+
+    #[test]
+    fn test_iter_designs() {
+        let mut generator = Generator::new(4, 3, 0.8, None);
+        generator.set_theme(Theme::Blues);
+
+        let designs: Vec<GeneratedDesign> = generator.iter_designs(10).take(3).collect();
+        let seeds: Vec<Option<u64>> = designs.iter().map(|design| design.config().seed()).collect();
+        assert_eq!(seeds, vec![Some(10), Some(11), Some(12)]);
+        assert_eq!(designs[0].config().theme(), Theme::Blues);
+        assert_eq!(
+            designs[1].checksum(),
+            generator.config().clone().with_seed(Some(11)).generate().unwrap().checksum()
+        );
+
+        // Searching lazily stops at the first match
+        let busy = generator
+            .iter_designs(0)
+            .find(|design| design.shapes().len() >= 3)
+            .unwrap();
+        assert!(busy.shapes().len() >= 3);
+        assert!(generator.design().is_none());
+
+        assert_eq!(generator.iter_designs(u64::MAX).count(), 1);
+    }
+
+    #[test]
+    fn test_design_similarity() {
+        let config = GeneratorConfig::new(4, 3, 0.8, Some(5));
+        let design = config.generate().unwrap();
+        assert!((design.similarity(&design) - 1.0).abs() < 1e-9);
+
+        // Same shapes in another theme keep the shape score but lose on color
+        let recolored = config.clone().with_theme(Theme::Greens).generate().unwrap();
+        let other = config.clone().with_seed(Some(6)).generate().unwrap();
+        assert!(design.similarity(&recolored) < 1.0);
+        assert!(design.similarity(&other) < design.similarity(&design));
+        assert!((design.similarity(&other) - other.similarity(&design)).abs() < 1e-9);
+
+        let finer = GeneratorConfig::new(5, 3, 0.8, Some(5)).generate().unwrap();
+        assert_eq!(design.similarity(&finer), 0.0);
+    }
+
+    #[test]
+    fn test_without_small_shapes() {
+        let design = GeneratorConfig::new(4, 5, 0.8, Some(8)).generate().unwrap();
+        let counts: Vec<usize> = design.shapes().iter().map(|shape| shape.cell_count()).collect();
+        let largest = *counts.iter().max().unwrap();
+
+        let simplified = design.without_small_shapes(largest);
+        assert_eq!(simplified.shapes().len(), design.shapes().len());
+        let kept: Vec<usize> = simplified.shapes().iter().map(|shape| shape.cell_count()).collect();
+        assert_eq!(kept.iter().filter(|&&count| count > 0).count(), 1);
+        assert!(kept.contains(&largest));
+
+        // Nothing is dropped below the threshold of one cell
+        assert_eq!(design.without_small_shapes(1).checksum(), design.checksum());
+        assert!(design.without_small_shapes(usize::MAX).shapes().iter().any(|shape| shape.cell_count() > 0));
+    }
+
+    #[test]
+    fn test_small_shapes_merged_and_smoothed() {
+        let design = GeneratorConfig::new(6, 6, 0.8, Some(8)).generate().unwrap();
+        let covered = |design: &GeneratedDesign| -> BTreeSet<usize> {
+            design.shapes().iter().flat_map(|shape| shape.cells.iter().copied()).collect()
+        };
+
+        // Merging moves cells between shapes but never uncovers a cell that touches another shape
+        let merged = design.with_small_shapes_merged(usize::MAX);
+        assert_eq!(merged.shapes().len(), design.shapes().len());
+        assert_eq!(merged.shapes().iter().filter(|shape| shape.cell_count() > 0).count(), 1);
+        assert!(covered(&merged).is_subset(&covered(&design)));
+        assert_eq!(design.with_small_shapes_merged(1).checksum(), design.checksum());
+
+        let smoothed = design.with_smoothed_outlines();
+        for (shape, original) in smoothed.shapes().iter().zip(design.shapes()) {
+            assert_eq!(shape.cells.is_empty(), original.cells.is_empty());
+            // Spurs of the original outline are gone
+            for &cell in &original.cells {
+                let same = design.grid().neighbors(cell).iter().filter(|&&n| original.contains_cell(n)).count();
+                assert!(same > 1 || !shape.contains_cell(cell) || shape.cell_count() == original.cell_count());
+            }
+        }
+    }
+
+    #[test]
+    fn test_shape_at() {
+        let design = GeneratorConfig::new(5, 4, 0.8, Some(31)).with_allow_overlap(true).generate().unwrap();
+        for (index, shape) in design.shapes().iter().enumerate() {
+            for &cell in &shape.cells {
+                let centroid = design.grid().get_cell_centroid(cell).unwrap();
+                assert_eq!(design.cell_at(centroid), Some(cell));
+                // Only a later shape on the same cell can hide this one
+                let hit = design.shape_at(centroid).unwrap();
+                assert!(hit >= index && design.shapes()[hit].contains_cell(cell));
+            }
+        }
+
+        let empty = (0..design.grid().cell_count())
+            .find(|&cell| design.shapes().iter().all(|shape| !shape.contains_cell(cell)))
+            .unwrap();
+        let centroid = design.grid().get_cell_centroid(empty).unwrap();
+        assert_eq!(design.shape_at(centroid), None);
+        assert_eq!(design.shape_at(grid::Point::new(0.0, 150.0)), None);
+    }
+
+    #[test]
+    fn test_rasterize_cells() {
+        let design = GeneratorConfig::new(5, 4, 0.8, Some(32)).with_allow_overlap(true).generate().unwrap();
+        let owners = design.rasterize_cells(40, 40);
+        assert_eq!(owners.len(), 40 * 40);
+        for (pixel, &owner) in owners.iter().enumerate() {
+            let (x, y) = ((pixel % 40) as f64, (pixel / 40) as f64);
+            let point = grid::Point::new((x + 0.5) * 5.0 - 100.0, (y + 0.5) * 5.0 - 100.0);
+            assert_eq!(owner, design.shape_at(point));
+        }
+        for index in 0..design.shapes().len() {
+            assert!(owners.contains(&Some(index)), "shape {} is drawn", index);
+        }
+
+        // A wide image centers the square, leaving its sides bare
+        let wide = design.rasterize_cells(80, 40);
+        for (row, square) in wide.chunks(80).zip(owners.chunks(40)) {
+            assert_eq!(&row[20..60], square);
+            assert!(row[..20].iter().chain(&row[60..]).all(Option::is_none));
+        }
+        assert!(design.rasterize_cells(0, 10).is_empty());
+    }
+
+    #[test]
+    fn test_with_shape_color() {
+        let design = GeneratorConfig::new(3, 3, 0.8, Some(12)).generate().unwrap();
+        let recolored = design.with_shape_color(1, "rgb(255, 0, 0)").unwrap();
+        assert_eq!(recolored.shapes()[1].fill(), "#FF0000");
+        assert_eq!(recolored.shapes()[0].color, design.shapes()[0].color);
+        assert_eq!(recolored.shapes()[1].cells, design.shapes()[1].cells);
+        assert_ne!(recolored.checksum(), design.checksum());
+
+        assert!(design.with_shape_color(3, "red").is_err());
+        assert!(design.with_shape_color(0, "plaid").is_err());
+    }
+
+    #[test]
+    fn test_regenerate_unlocked() {
+        let mut generator = Generator::new(4, 4, 0.8, Some(5));
+        assert!(generator.regenerate_unlocked(&[0], 1).is_err());
+        generator.generate().unwrap();
+        let original = generator.design().unwrap().clone();
+
+        generator.regenerate_unlocked(&[0, 2], 99).unwrap();
+        let rerolled = generator.design().unwrap();
+        assert_eq!(rerolled.shapes().len(), original.shapes().len());
+        for index in [0, 2] {
+            assert_eq!(rerolled.shapes()[index].cells, original.shapes()[index].cells);
+            assert_eq!(rerolled.shapes()[index].color, original.shapes()[index].color);
+        }
+        assert_ne!(rerolled.shapes()[1].cells, original.shapes()[1].cells);
+
+        // Rerolled shapes never cover a locked cell
+        for index in [1, 3] {
+            for cell in &rerolled.shapes()[index].cells {
+                assert!(!original.shapes()[0].contains_cell(*cell));
+                assert!(!original.shapes()[2].contains_cell(*cell));
+            }
+        }
+
+        // Locked shapes keep their ids and rerolled ones get new ids
+        assert_eq!(rerolled.shapes()[2].id, 2);
+        assert_eq!(rerolled.shape_index(2), Some(2));
+        assert!(rerolled.shapes()[1].id >= original.shapes().len());
+        assert_eq!(rerolled.shape_index(1), None);
+
+        // The same seed rerolls the same way
+        let again = original.with_unlocked_rerolled(&[0, 2], 99).unwrap();
+        assert_eq!(again.checksum(), rerolled.checksum());
+        assert!(original.with_unlocked_rerolled(&[4], 99).is_err());
+    }
+
+    #[test]
+    fn test_recolor_matches_regeneration() {
+        let configs = [
+            GeneratorConfig::new(4, 4, 0.8, Some(11)).with_allow_overlap(true),
+            GeneratorConfig::new(4, 5, 0.8, Some(12)),
+            GeneratorConfig::new(5, 3, 0.8, Some(13)).with_pack(true),
+            GeneratorConfig::new(5, 3, 0.8, Some(14)).with_style(ShapeStyle::Ribbon),
+        ];
+
+        for config in configs {
+            let design = config.generate().unwrap();
+            let recolored = design.with_theme(Theme::Rainbow);
+            let regenerated = config.clone().with_theme(Theme::Rainbow).generate().unwrap();
+            assert_eq!(recolored.checksum(), regenerated.checksum());
+            assert_eq!(recolored.config(), regenerated.config());
+
+            let faded = design.with_opacity(0.5);
+            assert_eq!(faded.checksum(), config.clone().with_opacity(0.5).generate().unwrap().checksum());
+            assert!(faded.shapes().iter().all(|shape| shape.opacity == 0.5));
+        }
+
+        let design = GeneratorConfig::new(4, 3, 0.8, Some(1)).generate().unwrap();
+        assert_eq!(design.with_opacity(7.0).config().opacity(), 1.0);
+    }
+
+    #[test]
+    fn test_seeded_generation_is_deterministic() {
+        for overlap in [false, true] {
+            let config = GeneratorConfig::new(5, 6, 0.8, Some(1234)).with_allow_overlap(overlap);
+            let first = config.generate().unwrap();
+            let second = config.generate().unwrap();
+
+            assert_eq!(first.checksum(), second.checksum());
+            for (a, b) in first.shapes().iter().zip(second.shapes()) {
+                assert_eq!(a.id, b.id);
+                assert_eq!(a.cells, b.cells);
+                assert_eq!(a.color, b.color);
+            }
+        }
+
+        let other = GeneratorConfig::new(5, 6, 0.8, Some(1235)).generate().unwrap();
+        let design = GeneratorConfig::new(5, 6, 0.8, Some(1234)).generate().unwrap();
+        assert_ne!(other.checksum(), design.checksum());
+    }
+
+    #[test]
+    fn test_ribbon_style() {
+        let config = GeneratorConfig::new(5, 3, 0.8, Some(9))
+            .with_style(ShapeStyle::Ribbon)
+            .with_allow_overlap(true);
+        let design = config.generate().unwrap();
+
+        assert_eq!(design.shapes().len(), 3);
+        assert!(design.blends().is_empty());
+
+        // Ribbons never share cells and every ribbon is a connected strand
+        let mut seen = BTreeSet::new();
+        for shape in design.shapes() {
+            assert!(shape.color.is_some());
+            for pair in shape.cells.windows(2) {
+                assert!(design.grid().neighbors(pair[0]).contains(&pair[1]));
+            }
+            for &cell in &shape.cells {
+                assert!(seen.insert(cell));
+            }
+        }
+
+        assert_eq!(config.generate().unwrap().checksum(), design.checksum());
+    }
+
+    #[test]
+    fn test_organic_ca_style() {
+        let config = GeneratorConfig::new(6, 4, 0.8, Some(21)).with_style(ShapeStyle::OrganicCa);
+        let design = config.generate().unwrap();
+
+        assert_eq!(design.shapes().len(), 4);
+        let mut seen = BTreeSet::new();
+        for shape in design.shapes() {
+            assert!(!shape.cells.is_empty());
+            assert!(shape.cells.iter().all(|&cell| seen.insert(cell)));
+        }
+        assert_eq!(config.generate().unwrap().checksum(), design.checksum());
+    }
+
+    #[test]
+    fn test_anchor_moves_composition() {
+        // Mean distance of all shape cells from the hexagon center
+        let spread = |anchor: Anchor| {
+            let design = GeneratorConfig::new(6, 1, 0.8, Some(11))
+                .with_anchor(anchor)
+                .generate()
+                .unwrap();
+            let grid = design.grid();
+            let cells: Vec<usize> = design.shapes().iter().flat_map(|s| s.cells.clone()).collect();
+            let total: f64 = cells
+                .iter()
+                .map(|&id| grid.get_cell_centroid(id).unwrap().distance(&grid.hex_grid().center))
+                .sum();
+            total / cells.len() as f64
+        };
+
+        assert!(spread(Anchor::Corner) > spread(Anchor::Center) * 2.0);
+        assert!(spread(Anchor::Edge) > spread(Anchor::Center) * 2.0);
+        assert_eq!("corner".parse::<Anchor>(), Ok(Anchor::Corner));
+        assert!("middle".parse::<Anchor>().is_err());
+    }
+
+    #[test]
+    fn test_aesthetic_changes_shapes() {
+        // Mean convexity of the shapes, over a few seeds
+        let convexity = |aesthetic: Aesthetic| {
+            let metrics: Vec<ShapeMetrics> = (0..8)
+                .flat_map(|seed| {
+                    GeneratorConfig::new(6, 3, 0.8, Some(seed))
+                        .with_aesthetic(aesthetic)
+                        .generate()
+                        .unwrap()
+                        .shape_metrics()
+                })
+                .collect();
+            metrics.iter().map(|m| m.convexity).sum::<f64>() / metrics.len() as f64
+        };
+
+        assert!(convexity(Aesthetic::Compact) > convexity(Aesthetic::Spiky));
+        assert_eq!(
+            GeneratorConfig::new(6, 3, 0.8, Some(5)).generate().unwrap().checksum(),
+            GeneratorConfig::new(6, 3, 0.8, Some(5))
+                .with_aesthetic(Aesthetic::Balanced)
+                .generate()
+                .unwrap()
+                .checksum()
+        );
+    }
+
+    #[test]
+    fn test_effort_records_winners() {
+        let generate = |effort: Effort| GeneratorConfig::new(6, 3, 0.8, Some(11)).with_effort(effort).generate().unwrap();
+
+        let high = generate(Effort::High);
+        assert!(!high.winning_candidates().is_empty());
+        assert!(high.winning_candidates().iter().all(|&index| index < Effort::High.candidates()));
+        assert_eq!(high.checksum(), generate(Effort::High).checksum());
+
+        // A single candidate always wins
+        assert!(generate(Effort::Low).winning_candidates().iter().all(|&index| index == 0));
+        assert_eq!(
+            generate(Effort::Medium).checksum(),
+            GeneratorConfig::new(6, 3, 0.8, Some(11)).generate().unwrap().checksum()
+        );
+    }
+
+    #[test]
+    fn test_design_colors_grown_shapes() {
+        let config = GeneratorConfig::new(4, 4, 0.8, Some(9));
+        let grid = TriangularGrid::new(100.0, 4);
+        let mut shapes = ShapeGenerator::new(&grid, Some(9)).generate_shapes(0.8, 4, (4, 8));
+        assert!(shapes.iter().all(|shape| shape.color.is_none()));
+        shapes[0].color = Some("#123456".to_string());
+
+        // Assembling the design colors what the generator left uncolored
+        let design = GeneratedDesign::new(config, grid, shapes, Vec::new());
+        assert_eq!(design.shapes()[0].fill(), "#123456");
+        for shape in design.shapes() {
+            assert!(color::parse_hex(shape.color.as_deref().unwrap()).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_symmetric_colors() {
+        let config = GeneratorConfig::new(4, 2, 0.8, Some(21))
+            .with_allow_overlap(true)
+            .with_symmetry(Symmetry::Three);
+        let design = config.generate().unwrap();
+        assert_eq!(design.shapes().len(), 6);
+
+        // Copies are rotated a third of a turn and share their shape's color
+        let per_sector = design.grid().cell_count() / 6;
+        for pair in design.shapes().chunks(3) {
+            for (copy, shape) in pair.iter().enumerate() {
+                assert_eq!(shape.symmetry.map(|symmetry| symmetry.copy), Some(copy));
+                assert_eq!(shape.color, pair[0].color);
+                let expected: Vec<usize> = pair[0].cells.iter().map(|&cell| cell + copy * 2 * per_sector).collect();
+                assert_eq!(shape.cells, expected);
+            }
+        }
+
+        // Rotating colors moves each copy one step along the shapes' colors
+        let rotated = config.with_symmetry_colors(SymmetryColors::Rotate).generate().unwrap();
+        let colors: Vec<&str> = rotated.shapes().iter().map(|shape| shape.fill()).collect();
+        assert_eq!(colors[0], design.shapes()[0].fill());
+        assert_eq!(colors[1], colors[3]);
+        assert_eq!(colors[2], colors[4]);
+        assert_eq!(colors[0], colors[5]);
+        assert_ne!(colors[0], colors[1]);
+    }
+
+    #[test]
+    fn test_guide_pulls_shapes_toward_dark_areas() {
+        // Share of shape cells left of center, over a few seeds
+        let left_share = |guide: Option<GuideMask>| {
+            let (mut left, mut total) = (0, 0);
+            for seed in 0..8 {
+                let design = GeneratorConfig::new(6, 2, 0.8, Some(seed))
+                    .with_guide(guide.clone())
+                    .generate()
+                    .unwrap();
+                for &id in design.shapes().iter().flat_map(|s| s.cells.iter()) {
+                    total += 1;
+                    if design.grid().get_cell_centroid(id).unwrap().x < 0.0 {
+                        left += 1;
+                    }
+                }
+            }
+            left as f64 / total as f64
+        };
+
+        let left_half = GuideMask::from_fn(32, |point| if point.x < 0.0 { 1.0 } else { 0.0 });
+        assert!(left_share(Some(left_half)) > left_share(None) + 0.25);
+    }
+
+    #[test]
+    fn test_color_by_size() {
+        let roles = ColorManager::theme_roles(&Theme::Google);
+        for (seed, overlap) in [(3, false), (4, true), (5, true)] {
+            let design = GeneratorConfig::new(5, 4, 0.8, Some(seed))
+                .with_theme(Theme::Google)
+                .with_allow_overlap(overlap)
+                .with_color_by_size(true)
+                .generate()
+                .unwrap();
+
+            let mut grown: Vec<&Shape> = design
+                .shapes()
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| design.blends().iter().all(|blend| blend.shape != *index))
+                .map(|(_, shape)| shape)
+                .collect();
+            grown.sort_by_key(|shape| std::cmp::Reverse(shape.cell_count()));
+            assert_eq!(grown[0].fill(), roles[0]);
+            assert_eq!(grown[1].fill(), roles[1]);
+        }
+    }
+
+    #[test]
+    fn test_unique_colors() {
+        // Three colors can't cover six shapes without variants
+        let theme = Theme::Custom {
+            name: "trio".to_string(),
+            colors: vec!["#4285F4".to_string(), "#EA4335".to_string(), "#FBBC05".to_string()],
+            weights: Vec::new(),
+        };
+        for (seed, color_by_size) in [(0, false), (1, false), (2, true), (3, true)] {
+            let design = GeneratorConfig::new(6, 6, 0.8, Some(seed))
+                .with_theme(theme.clone())
+                .with_pack(true)
+                .with_color_by_size(color_by_size)
+                .with_unique_colors(true)
+                .generate()
+                .unwrap();
+            let mut colors: Vec<&str> = design.shapes().iter().map(|shape| shape.fill()).collect();
+            colors.sort();
+            colors.dedup();
+            assert_eq!(colors.len(), design.shapes().len());
+        }
+    }
+
+    #[test]
+    fn test_pack_mode() {
+        let design = GeneratorConfig::new(4, 5, 0.8, Some(21))
+            .with_pack(true)
+            .with_allow_overlap(true)
+            .generate()
+            .unwrap();
+
+        assert_eq!(design.shapes().len(), 5);
+        assert!(design.blends().is_empty());
+
+        let mut cells: Vec<usize> = design.shapes().iter().flat_map(|s| s.cells.clone()).collect();
+        cells.sort_unstable();
+        assert_eq!(cells, (0..design.grid().cell_count()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_mosaic_mode() {
+        for (grid_size, theme) in [(3, Theme::Mesos), (6, Theme::Blues), (8, Theme::Google)] {
+            let design = GeneratorConfig::new(grid_size, 4, 0.8, Some(grid_size as u64))
+                .with_theme(theme)
+                .with_mosaic(true)
+                .with_allow_overlap(true)
+                .generate()
+                .unwrap();
+            let grid = design.grid();
+            let shapes = design.shapes();
+
+            // Dozens of tiles, whatever the shape count asked for
+            assert!(shapes.len() >= grid.cell_count() / 4, "{} shapes", shapes.len());
+            assert!(shapes.iter().all(|shape| (1..=shape::MAX_TILE_CELLS).contains(&shape.cells.len())));
+            assert!(design.blends().is_empty());
+
+            // No two touching tiles share a color
+            let mut owner = vec![None; grid.cell_count()];
+            for (index, shape) in shapes.iter().enumerate() {
+                for &cell in &shape.cells {
+                    owner[cell] = Some(index);
+                }
+            }
+            for (a, b) in grid.edges() {
+                if let (Some(a), Some(b)) = (owner[a], owner[b]) {
+                    assert!(a == b || shapes[a].fill() != shapes[b].fill(), "shapes {} and {} share a color", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_overlap_ratio() {
+        for ratio in [0.0, 0.25, 0.5] {
+            let design = GeneratorConfig::new(6, 2, 0.8, Some(9))
+                .with_allow_overlap(true)
+                .with_overlap_ratio(Some(ratio))
+                .generate()
+                .unwrap();
+
+            let achieved = design.overlap_ratio().unwrap_or(0.0);
+            assert!((achieved - ratio).abs() <= 0.1, "target {} achieved {}", ratio, achieved);
+        }
+    }
+
+    #[test]
+    fn test_overlap_opacity() {
+        let config = GeneratorConfig::new(6, 2, 0.6, Some(9))
+            .with_allow_overlap(true)
+            .with_overlap_ratio(Some(0.3));
+        let overlap = |config: &GeneratorConfig| {
+            let design = config.generate().unwrap();
+            let blend = design.blends()[0];
+            (design.shapes()[blend.shape].opacity, design.shapes()[blend.sources[0]].opacity)
+        };
+
+        assert_eq!(overlap(&config), (0.6, 0.6));
+        assert_eq!(overlap(&config.clone().with_overlap_opacity(OverlapOpacity::Full)), (1.0, 0.6));
+        let (multiplied, _) = overlap(&config.clone().with_overlap_opacity(OverlapOpacity::Multiply));
+        assert!((multiplied - 0.84).abs() < 1e-6);
+
+        // Fading the design keeps the overlap's own rule
+        let custom = config.with_overlap_opacity(OverlapOpacity::Custom(0.95));
+        let faded = custom.generate().unwrap().with_opacity(0.3);
+        let blend = faded.blends()[0];
+        assert_eq!(faded.shapes()[blend.shape].opacity, 0.95);
+        assert_eq!(faded.checksum(), custom.with_opacity(0.3).generate().unwrap().checksum());
+    }
+
+    #[test]
+    fn test_concurrent_generation() {
+        let config = Arc::new(GeneratorConfig::new(3, 4, 0.8, Some(7)).with_theme(Theme::Blues));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let config = Arc::clone(&config);
+                std::thread::spawn(move || config.generate().unwrap())
+            })
+            .collect();
+
+        for handle in handles {
+            let design = handle.join().unwrap();
+            assert_eq!(design.config().theme(), Theme::Blues);
+            assert!(!design.shapes().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_generator_wraps_config() {
+        let mut generator = Generator::new(4, 2, 0.8, Some(42));
+        assert!(generator.grid().is_none());
+        assert!(generator.shapes().is_empty());
+
+        generator.set_theme(Theme::Reds).set_allow_overlap(true);
+        generator.generate().unwrap();
+
+        assert_eq!(generator.config().theme(), Theme::Reds);
+        assert!(generator.design().is_some());
+        assert!(!generator.shapes().is_empty());
+    }
+}
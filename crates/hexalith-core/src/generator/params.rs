@@ -0,0 +1,109 @@
+use crate::Result;
+use std::ops::RangeInclusive;
+
+/// Supported grid densities; density 2 is the classic 24-cell layout
+pub const GRID_SIZE_RANGE: RangeInclusive<u8> = 2..=8;
+
+/// Supported number of shapes in one design; past the palette's size, colors are synthesized
+pub const SHAPES_RANGE: RangeInclusive<u8> = 1..=25;
+
+/// Supported shape opacity
+pub const OPACITY_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+
+/// The numeric limits every front end shares
+///
+/// [`GeneratorConfig::new`](super::GeneratorConfig::new) clamps out-of-range
+/// values for backwards compatibility; the CLI and web API call
+/// [`Params::validate`] first so users get an explicit error instead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Params {
+    pub grid_size: u8,
+    pub shapes: u8,
+    pub opacity: f32,
+}
+
+impl Params {
+    pub fn new(grid_size: u8, shapes: u8, opacity: f32) -> Self {
+        Self {
+            grid_size,
+            shapes,
+            opacity,
+        }
+    }
+
+    /// Every out-of-range value as a (field, message) pair
+    pub fn problems(&self) -> Vec<(&'static str, String)> {
+        let mut problems = Vec::new();
+
+        if !GRID_SIZE_RANGE.contains(&self.grid_size) {
+            problems.push(("grid_size", range_message(&GRID_SIZE_RANGE)));
+        }
+        if !SHAPES_RANGE.contains(&self.shapes) {
+            problems.push(("shapes", range_message(&SHAPES_RANGE)));
+        }
+        if !OPACITY_RANGE.contains(&self.opacity) {
+            problems.push((
+                "opacity",
+                format!(
+                    "must be between {:.1} and {:.1}",
+                    OPACITY_RANGE.start(),
+                    OPACITY_RANGE.end()
+                ),
+            ));
+        }
+
+        problems
+    }
+
+    /// Checks the values against the supported ranges, naming each one that is out of range
+    pub fn validate(&self) -> Result<()> {
+        let problems = self.problems();
+        if problems.is_empty() {
+            return Ok(());
+        }
+
+        let details: Vec<String> = problems
+            .iter()
+            .map(|(field, message)| format!("{} {}", field, message))
+            .collect();
+        Err(format!("Invalid parameters: {}", details.join("; ")).into())
+    }
+}
+
+fn range_message(range: &RangeInclusive<u8>) -> String {
+    format!("must be from {} to {}", range.start(), range.end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::grid::TriangularGrid;
+    use crate::generator::GeneratorConfig;
+
+    #[test]
+    fn test_validate() {
+        assert!(Params::new(2, 1, 0.0).validate().is_ok());
+        assert!(Params::new(8, 25, 1.0).validate().is_ok());
+
+        let error = Params::new(9, 0, 1.5).validate().unwrap_err().to_string();
+        assert_eq!(
+            error,
+            "Invalid parameters: grid_size must be from 2 to 8; shapes must be from 1 to 25; \
+             opacity must be between 0.0 and 1.0"
+        );
+        assert!(Params::new(4, 4, f32::NAN).validate().is_err());
+    }
+
+    #[test]
+    fn test_classic_grid_size() {
+        // Grid size 2 is the original 24-triangle layout, and every layer agrees on it
+        let design = GeneratorConfig::try_new(2, 3, 0.8, Some(1)).unwrap().generate().unwrap();
+        assert_eq!(design.grid().cell_count(), 24);
+        assert_eq!(TriangularGrid::new(100.0, 2).cell_count(), 24);
+
+        // Smaller densities are rejected when validated and clamped to 2 otherwise
+        assert!(GeneratorConfig::try_new(1, 3, 0.8, Some(1)).is_err());
+        assert_eq!(GeneratorConfig::new(1, 3, 0.8, Some(1)).grid_size(), 2);
+        assert_eq!(TriangularGrid::new(100.0, 1).cell_count(), 24);
+    }
+}
@@ -0,0 +1,117 @@
+/// Scoring profile that decides which grown shapes and cells count as better
+///
+/// Each profile weighs three measurements that range from 0 to 1. A negative
+/// weight prefers low values, scoring `1 - value` instead, so every profile's
+/// scores stay within 0 and 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Aesthetic {
+    /// Even weighting of roundness, smooth outlines and balance
+    #[default]
+    Balanced,
+    /// Tight, round blobs with few concave notches
+    Compact,
+    /// Jagged outlines with tendrils reaching out from the body
+    Spiky,
+    /// Loose shapes spread thinly but smoothly over the grid
+    Sprawling,
+}
+
+impl Aesthetic {
+    /// Names accepted by [`Aesthetic::from_str`](std::str::FromStr)
+    pub fn names() -> Vec<&'static str> {
+        vec!["balanced", "compact", "spiky", "sprawling"]
+    }
+
+    /// Weights of a shape's compactness, smoothness and balance
+    pub fn shape_weights(&self) -> [f64; 3] {
+        match self {
+            Aesthetic::Balanced => [0.4, 0.4, 0.2],
+            Aesthetic::Compact => [0.6, 0.3, 0.1],
+            Aesthetic::Spiky => [-0.5, -0.3, 0.2],
+            Aesthetic::Sprawling => [-0.4, 0.3, 0.3],
+        }
+    }
+
+    /// Weights of a candidate cell's adjacency, distance and balance scores
+    pub fn cell_weights(&self) -> [f64; 3] {
+        match self {
+            Aesthetic::Balanced => [0.4, 0.4, 0.2],
+            Aesthetic::Compact => [0.6, 0.3, 0.1],
+            Aesthetic::Spiky => [-0.4, -0.4, 0.2],
+            Aesthetic::Sprawling => [0.4, -0.3, 0.3],
+        }
+    }
+
+    /// Combines three scores in `0..=1` with `weights`
+    pub fn weigh(weights: [f64; 3], scores: [f64; 3]) -> f64 {
+        weights
+            .iter()
+            .zip(scores)
+            .map(|(&weight, score)| if weight < 0.0 { -weight * (1.0 - score) } else { score * weight })
+            .sum()
+    }
+}
+
+impl std::fmt::Display for Aesthetic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Aesthetic::Balanced => write!(f, "balanced"),
+            Aesthetic::Compact => write!(f, "compact"),
+            Aesthetic::Spiky => write!(f, "spiky"),
+            Aesthetic::Sprawling => write!(f, "sprawling"),
+        }
+    }
+}
+
+impl std::str::FromStr for Aesthetic {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "balanced" => Ok(Aesthetic::Balanced),
+            "compact" => Ok(Aesthetic::Compact),
+            "spiky" => Ok(Aesthetic::Spiky),
+            "sprawling" => Ok(Aesthetic::Sprawling),
+            _ => Err(format!(
+                "Unknown aesthetic '{}' (expected one of: {})",
+                s,
+                Self::names().join(", ")
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aesthetic_names() {
+        for name in Aesthetic::names() {
+            let aesthetic: Aesthetic = name.parse().unwrap();
+            assert_eq!(aesthetic.to_string(), name);
+        }
+        assert!("pointy".parse::<Aesthetic>().unwrap_err().contains("balanced, compact"));
+    }
+
+    #[test]
+    fn test_weigh() {
+        // The balanced profile is the original fixed 0.4/0.4/0.2 weighting
+        let scores = [0.3, 0.9, 0.5];
+        assert_eq!(
+            Aesthetic::weigh(Aesthetic::Balanced.shape_weights(), scores),
+            0.3 * 0.4 + 0.9 * 0.4 + 0.5 * 0.2
+        );
+
+        for aesthetic in Aesthetic::names().iter().map(|name| name.parse::<Aesthetic>().unwrap()) {
+            for weights in [aesthetic.shape_weights(), aesthetic.cell_weights()] {
+                assert!((weights.iter().map(|w| w.abs()).sum::<f64>() - 1.0).abs() < 1e-9);
+                assert!((0.0..=1.0).contains(&Aesthetic::weigh(weights, [0.0, 1.0, 0.5])));
+            }
+        }
+
+        // Spiky shapes score best where balanced ones score worst
+        let rough = [0.1, 0.2, 0.5];
+        assert!(Aesthetic::weigh(Aesthetic::Spiky.shape_weights(), rough) > Aesthetic::weigh(Aesthetic::Balanced.shape_weights(), rough));
+    }
+}
@@ -0,0 +1,73 @@
+use crate::generator::grid::TriangularGrid;
+use rand::RngCore;
+use std::collections::BTreeSet;
+
+/// A strategy for choosing the cells of a single shape
+///
+/// Implementations must return connected cells that avoid `used_cells`, and
+/// should only draw randomness from `rng` so seeded designs stay reproducible.
+pub trait ShapeAlgorithm {
+    /// Picks roughly `target_size` cells for a new shape, or none if the grid has no room
+    fn grow(
+        &self,
+        grid: &TriangularGrid,
+        rng: &mut dyn RngCore,
+        target_size: usize,
+        used_cells: &BTreeSet<usize>,
+    ) -> Vec<usize>;
+}
+
+/// Selects which algorithm grows the shapes of a design
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShapeStyle {
+    /// Compact shapes grown outward from the center (the original behavior)
+    #[default]
+    Balanced,
+    /// Snake-like strands connecting two points on the hexagon's edge
+    Ribbon,
+    /// Irregular, coral-like clusters carved by a cellular automaton
+    OrganicCa,
+}
+
+impl ShapeStyle {
+    /// Names accepted by [`ShapeStyle::from_str`](std::str::FromStr)
+    pub fn names() -> Vec<&'static str> {
+        vec!["balanced", "ribbon", "organic-ca"]
+    }
+
+    /// The algorithm for this style, or `None` for the built-in balanced growth
+    pub fn algorithm(&self) -> Option<Box<dyn ShapeAlgorithm>> {
+        match self {
+            ShapeStyle::Balanced => None,
+            ShapeStyle::Ribbon => Some(Box::new(super::ribbon::RibbonAlgorithm::default())),
+            ShapeStyle::OrganicCa => Some(Box::new(super::organic::OrganicCaAlgorithm::default())),
+        }
+    }
+}
+
+impl std::fmt::Display for ShapeStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShapeStyle::Balanced => write!(f, "balanced"),
+            ShapeStyle::Ribbon => write!(f, "ribbon"),
+            ShapeStyle::OrganicCa => write!(f, "organic-ca"),
+        }
+    }
+}
+
+impl std::str::FromStr for ShapeStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "balanced" => Ok(ShapeStyle::Balanced),
+            "ribbon" => Ok(ShapeStyle::Ribbon),
+            "organic-ca" => Ok(ShapeStyle::OrganicCa),
+            _ => Err(format!(
+                "Unknown shape style '{}' (expected one of: {})",
+                s,
+                Self::names().join(", ")
+            )),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use crate::generator::grid::{Point, TriangularGrid};
+use rand::Rng;
+
+/// Where in the hexagon a composition starts growing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Anchor {
+    /// The middle of the hexagon, giving centered, symmetric-feeling designs
+    #[default]
+    Center,
+    /// One of the six hexagon corners, picked at random
+    Corner,
+    /// The midpoint of one of the six hexagon sides, picked at random
+    Edge,
+    /// The centroid of a random cell anywhere in the grid
+    Random,
+}
+
+impl Anchor {
+    /// Names accepted by [`Anchor::from_str`](std::str::FromStr)
+    pub fn names() -> Vec<&'static str> {
+        vec!["center", "corner", "edge", "random"]
+    }
+
+    /// Picks the concrete anchor point for one design
+    ///
+    /// The centered anchor draws nothing from `rng`, so centered designs are
+    /// unaffected by the other policies.
+    pub fn resolve<R: Rng + ?Sized>(&self, grid: &TriangularGrid, rng: &mut R) -> Point {
+        let hex = grid.hex_grid();
+        match self {
+            Anchor::Center => hex.center,
+            Anchor::Corner => hex.vertices[rng.gen_range(0..hex.vertices.len())],
+            Anchor::Edge => {
+                let side = rng.gen_range(0..hex.vertices.len());
+                let a = hex.vertices[side];
+                let b = hex.vertices[(side + 1) % hex.vertices.len()];
+                Point::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0)
+            }
+            Anchor::Random => {
+                if grid.cell_count() == 0 {
+                    return hex.center;
+                }
+                grid.cells()[rng.gen_range(0..grid.cell_count())].centroid
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Anchor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Anchor::Center => write!(f, "center"),
+            Anchor::Corner => write!(f, "corner"),
+            Anchor::Edge => write!(f, "edge"),
+            Anchor::Random => write!(f, "random"),
+        }
+    }
+}
+
+impl std::str::FromStr for Anchor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "center" => Ok(Anchor::Center),
+            "corner" => Ok(Anchor::Corner),
+            "edge" => Ok(Anchor::Edge),
+            "random" => Ok(Anchor::Random),
+            _ => Err(format!(
+                "Unknown anchor '{}' (expected one of: {})",
+                s,
+                Self::names().join(", ")
+            )),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+/// Most candidates an explicit effort may ask for
+pub const MAX_CANDIDATES: usize = 32;
+
+/// How hard the generator searches for good shapes
+///
+/// More effort grows more candidate shapes to pick the best from, and gives
+/// each one more attempts to reach its target size, at the cost of time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Effort {
+    /// A single candidate, for the fastest generation
+    Low,
+    /// Three candidates (the original behavior)
+    #[default]
+    Medium,
+    /// Eight candidates, each with more growth attempts
+    High,
+    /// An explicit number of candidates, from 1 to [`MAX_CANDIDATES`]
+    Candidates(usize),
+}
+
+impl Effort {
+    /// Names accepted by [`Effort::from_str`](std::str::FromStr), besides a count
+    pub fn names() -> Vec<&'static str> {
+        vec!["low", "medium", "high"]
+    }
+
+    /// Candidate shapes grown for each shape of a design
+    pub fn candidates(&self) -> usize {
+        match self {
+            Effort::Low => 1,
+            Effort::Medium => 3,
+            Effort::High => 8,
+            Effort::Candidates(count) => (*count).clamp(1, MAX_CANDIDATES),
+        }
+    }
+
+    /// Growth attempts a candidate gets per cell of its target size
+    pub fn attempts_per_cell(&self) -> usize {
+        match self {
+            Effort::Low => 2,
+            Effort::High => 5,
+            Effort::Medium | Effort::Candidates(_) => 3,
+        }
+    }
+}
+
+impl std::fmt::Display for Effort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Effort::Low => write!(f, "low"),
+            Effort::Medium => write!(f, "medium"),
+            Effort::High => write!(f, "high"),
+            Effort::Candidates(count) => write!(f, "{}", count),
+        }
+    }
+}
+
+impl std::str::FromStr for Effort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Effort::Low),
+            "medium" => Ok(Effort::Medium),
+            "high" => Ok(Effort::High),
+            count => match count.parse::<usize>() {
+                Ok(count) if (1..=MAX_CANDIDATES).contains(&count) => Ok(Effort::Candidates(count)),
+                _ => Err(format!(
+                    "Unknown effort '{}' (expected one of: {}, or a candidate count from 1 to {})",
+                    s,
+                    Self::names().join(", "),
+                    MAX_CANDIDATES
+                )),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effort_parsing() {
+        assert_eq!("HIGH".parse::<Effort>(), Ok(Effort::High));
+        assert_eq!("5".parse::<Effort>(), Ok(Effort::Candidates(5)));
+        assert_eq!(Effort::Candidates(5).to_string(), "5");
+        assert_eq!(Effort::default().candidates(), 3);
+        assert!(Effort::High.candidates() > Effort::Medium.candidates());
+        assert!("0".parse::<Effort>().is_err());
+        assert!("33".parse::<Effort>().unwrap_err().contains("from 1 to 32"));
+        assert!("max".parse::<Effort>().is_err());
+    }
+}
@@ -0,0 +1,60 @@
+use crate::generator::grid::Point;
+use std::sync::Arc;
+
+/// Side length of the square design area the mask covers
+const GUIDE_EXTENT: f64 = 200.0;
+
+/// How strongly a fully dark cell outweighs the usual shape-quality score
+pub(crate) const GUIDE_WEIGHT: f64 = 0.6;
+
+/// How much nearer, in design units, a fully dark cell seems when picking a start cell
+pub(crate) const GUIDE_PULL: f64 = 30.0;
+
+/// Darkness of a guide image sampled over the design area
+///
+/// Shape growth prefers cells whose centroid falls where the mask is dark,
+/// so designs loosely follow the silhouette. The mask covers the square
+/// around the hexagon at a fixed resolution and is cheap to clone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuideMask {
+    resolution: usize,
+    darkness: Arc<[f32]>,
+}
+
+impl GuideMask {
+    /// Samples `darkness` (0.0 light to 1.0 dark) at the center of each mask pixel
+    pub fn from_fn<F: Fn(Point) -> f64>(resolution: usize, darkness: F) -> Self {
+        let resolution = resolution.max(1);
+        let step = GUIDE_EXTENT / resolution as f64;
+        let values = (0..resolution * resolution)
+            .map(|i| {
+                let point = Point::new(
+                    -GUIDE_EXTENT / 2.0 + (i % resolution) as f64 * step + step / 2.0,
+                    -GUIDE_EXTENT / 2.0 + (i / resolution) as f64 * step + step / 2.0,
+                );
+                darkness(point).clamp(0.0, 1.0) as f32
+            })
+            .collect();
+        Self {
+            resolution,
+            darkness: values,
+        }
+    }
+
+    /// Darkness under a point in design coordinates; 0.0 outside the mask
+    pub fn darkness_at(&self, point: Point) -> f64 {
+        let step = GUIDE_EXTENT / self.resolution as f64;
+        let x = ((point.x + GUIDE_EXTENT / 2.0) / step).floor();
+        let y = ((point.y + GUIDE_EXTENT / 2.0) / step).floor();
+        if x < 0.0 || y < 0.0 || x >= self.resolution as f64 || y >= self.resolution as f64 {
+            return 0.0;
+        }
+        self.darkness[y as usize * self.resolution + x as usize] as f64
+    }
+
+    /// A hash of the mask's contents, for telling guides apart in cache keys
+    pub fn checksum(&self) -> u64 {
+        let bytes: Vec<u8> = self.darkness.iter().flat_map(|value| value.to_le_bytes()).collect();
+        crate::stable_hash(&bytes) ^ self.resolution as u64
+    }
+}
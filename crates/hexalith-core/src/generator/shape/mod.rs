@@ -1,25 +1,71 @@
-use crate::generator::grid::TriangularGrid;
+use crate::generator::grid::{Point, TriangularGrid};
 use rand::prelude::*;
 use rand_chacha::ChaCha8Rng;
-use std::collections::{HashSet, VecDeque};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+mod aesthetic;
+pub mod algorithm;
+mod anchor;
+mod effort;
+mod guide;
+mod mosaic;
+mod organic;
+mod pack;
+mod ribbon;
+mod symmetry;
+
+pub use aesthetic::Aesthetic;
+pub use algorithm::{ShapeAlgorithm, ShapeStyle};
+pub use anchor::Anchor;
+pub use effort::{Effort, MAX_CANDIDATES};
+pub use guide::GuideMask;
+pub use mosaic::MAX_TILE_CELLS;
+pub use organic::OrganicCaAlgorithm;
+pub use ribbon::RibbonAlgorithm;
+pub use symmetry::{rotate_cell, Symmetry, SymmetryColors, SymmetryGroup};
+
+/// Fill of a shape that was never colored: neutral gray, so a skipped color stage shows but breaks nothing
+pub const UNCOLORED: &str = "#808080";
 
 /// Represents a shape made up of connected triangular cells
 #[derive(Debug, Clone)]
 pub struct Shape {
+    /// Stable identifier, assigned in seeded generation order
+    ///
+    /// Unlike the shape's index it survives edits such as rerolling, so it
+    /// can name a shape across regenerations.
+    pub id: usize,
     pub cells: Vec<usize>,
-    pub color: String,
+    /// Fill color; shapes are grown without one and colored in a separate stage
+    pub color: Option<String>,
     pub opacity: f32,
+    /// The symmetric shape this is a rotated copy of, in a symmetric design
+    pub symmetry: Option<SymmetryGroup>,
 }
 
 impl Shape {
-    pub fn new(color: String, opacity: f32) -> Self {
+    /// An empty, uncolored shape
+    pub fn new(opacity: f32) -> Self {
         Self {
+            id: 0,
             cells: Vec::new(),
-            color,
+            color: None,
             opacity,
+            symmetry: None,
         }
     }
 
+    /// The same shape filled with `color`
+    pub fn with_color(mut self, color: impl Into<String>) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+
+    /// The fill color, or [`UNCOLORED`] if no color stage has reached the shape
+    pub fn fill(&self) -> &str {
+        self.color.as_deref().unwrap_or(UNCOLORED)
+    }
+
     pub fn add_cell(&mut self, cell_id: usize) {
         if !self.cells.contains(&cell_id) {
             self.cells.push(cell_id);
@@ -33,6 +79,70 @@ impl Shape {
     pub fn cell_count(&self) -> usize {
         self.cells.len()
     }
+
+    /// A copy grown by every cell within `n` edge steps of the shape
+    ///
+    /// Added cells follow the shape's own, nearest first. Cells outside the
+    /// grid are kept but don't grow.
+    pub fn dilate(&self, grid: &TriangularGrid, n: usize) -> Shape {
+        let mut inside = self.membership(grid);
+        let mut dilated = self.clone();
+        let mut frontier: Vec<usize> = self.cells.iter().copied().filter(|&cell| cell < grid.cell_count()).collect();
+        for _ in 0..n {
+            let mut layer: Vec<usize> = frontier
+                .iter()
+                .flat_map(|&cell| grid.neighbors(cell))
+                .filter(|&neighbor| !inside[neighbor])
+                .collect();
+            layer.sort_unstable();
+            layer.dedup();
+            if layer.is_empty() {
+                break;
+            }
+            for &cell in &layer {
+                inside[cell] = true;
+            }
+            dilated.cells.extend(&layer);
+            frontier = layer;
+        }
+        dilated
+    }
+
+    /// A copy without the cells within `n` edge steps of anything outside the shape
+    ///
+    /// Past the hexagon's edge counts as outside, so cells along it erode too,
+    /// and cells outside the grid are dropped. Eroding a shape thinner than
+    /// `2n` cells leaves it empty.
+    pub fn erode(&self, grid: &TriangularGrid, n: usize) -> Shape {
+        let mut eroded = self.clone();
+        eroded.cells.retain(|&cell| cell < grid.cell_count());
+        for _ in 0..n {
+            let inside = eroded.membership(grid);
+            // A triangle has three edges; a missing neighbor is the hexagon's edge
+            eroded.cells.retain(|&cell| {
+                let neighbors = grid.neighbors(cell);
+                neighbors.len() == 3 && neighbors.iter().all(|&neighbor| inside[neighbor])
+            });
+        }
+        eroded
+    }
+
+    /// The cells outside the shape that share an edge with it, in ascending order
+    pub fn outline_ring(&self, grid: &TriangularGrid) -> Vec<usize> {
+        let dilated = self.dilate(grid, 1);
+        dilated.cells[self.cells.len()..].to_vec()
+    }
+
+    /// Whether each cell of `grid` belongs to the shape, indexed by cell id
+    fn membership(&self, grid: &TriangularGrid) -> Vec<bool> {
+        let mut inside = vec![false; grid.cell_count()];
+        for &cell in &self.cells {
+            if let Some(slot) = inside.get_mut(cell) {
+                *slot = true;
+            }
+        }
+        inside
+    }
 }
 
 /// Shape evaluation metrics for balanced shapes
@@ -41,79 +151,118 @@ pub struct ShapeMetrics {
     pub compactness: f64, // Higher is better (more compact)
     pub smoothness: f64,  // Higher is better (smoother perimeter)
     pub balance: f64,     // Higher is better (more balanced from center)
+    pub area: f64,         // Sum of the cells' triangle areas, in grid units
+    pub perimeter: f64,    // Length of the outline, in grid units
+    pub convexity: f64,    // Area over the convex hull's area, 1 for convex shapes
+    pub aspect_ratio: f64, // Longer over shorter side of the bounding box, at least 1
 }
 
 /// Generates random shapes on the triangular grid
 pub struct ShapeGenerator<'a> {
     grid: &'a TriangularGrid,
     rng: ChaCha8Rng,
+    /// Point the composition grows outward from
+    anchor_point: Point,
+    /// Silhouette that candidate cells are drawn toward
+    guide: Option<GuideMask>,
+    /// Weights used to score candidate cells and shapes
+    aesthetic: Aesthetic,
+    /// Candidates grown per shape and growth attempts per cell
+    effort: Effort,
+    /// Index of the candidate picked for each shape grown from candidates
+    winners: Vec<usize>,
 }
 
 impl<'a> ShapeGenerator<'a> {
     pub fn new(grid: &'a TriangularGrid, seed: Option<u64>) -> Self {
-        // Add extra randomness by combining seed with timestamp nanoseconds
+        // A given seed always reproduces the same sequence
         let rng = match seed {
-            Some(seed) => {
-                // Get the current timestamp's nanoseconds
-                let now = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .subsec_nanos();
-
-                // Combine seed and timestamp for additional randomness
-                // But only use a portion of the nanoseconds to keep some seed determinism
-                let combined_seed = seed.wrapping_add((now % 10000) as u64);
-                ChaCha8Rng::seed_from_u64(combined_seed)
-            }
+            Some(seed) => ChaCha8Rng::seed_from_u64(seed),
             None => ChaCha8Rng::from_entropy(),
         };
 
-        Self { grid, rng }
+        Self {
+            grid,
+            rng,
+            anchor_point: grid.hex_grid().center,
+            guide: None,
+            aesthetic: Aesthetic::default(),
+            effort: Effort::default(),
+            winners: Vec::new(),
+        }
+    }
+
+    /// Sets the start-cell policy, choosing where the first shape begins growing
+    pub fn set_anchor(&mut self, anchor: Anchor) -> &mut Self {
+        self.anchor_point = anchor.resolve(self.grid, &mut self.rng);
+        self
+    }
+
+    /// Biases growth toward the dark areas of a guide image
+    pub fn set_guide(&mut self, guide: Option<GuideMask>) -> &mut Self {
+        self.guide = guide;
+        self
+    }
+
+    /// Sets the scoring profile that candidate cells and shapes are ranked by
+    pub fn set_aesthetic(&mut self, aesthetic: Aesthetic) -> &mut Self {
+        self.aesthetic = aesthetic;
+        self
+    }
+
+    /// Sets how many candidate shapes are grown and how long each may grow
+    pub fn set_effort(&mut self, effort: Effort) -> &mut Self {
+        self.effort = effort;
+        self
+    }
+
+    /// Which candidate won, for each shape grown from candidates so far
+    pub fn winning_candidates(&self) -> &[usize] {
+        &self.winners
+    }
+
+    /// Picks the best of `candidates` by their score, with a little randomness
+    ///
+    /// Each candidate draws one jitter, in candidate order, and the highest
+    /// jittered score wins, the first of equals.
+    fn pick_candidate(&mut self, candidates: Vec<Shape>) -> Option<Shape> {
+        // Higher is better, but add randomness to avoid always picking the same shape
+        let keys: Vec<f64> = candidates
+            .iter()
+            .map(|shape| self.evaluate_shape_quality(shape).score(self.aesthetic) + self.rng.gen_range(-0.1..0.1))
+            .collect();
+
+        let winner = (0..keys.len()).rev().max_by(|&a, &b| keys[a].total_cmp(&keys[b]))?;
+        self.winners.push(winner);
+        candidates.into_iter().nth(winner)
     }
 
     /// Generates a more angular shape with equiangular triangles and connecting edges
     /// that grows from the center outward, but with improved balance
     pub fn generate_angular_shape(
         &mut self,
-        color: String,
         opacity: f32,
         target_size: usize,
     ) -> Shape {
         // Generate multiple candidate shapes and select the best one
-        let candidates = 3;
+        let candidates = self.effort.candidates();
         let mut shapes = Vec::with_capacity(candidates);
 
         for _ in 0..candidates {
-            shapes.push(self.generate_angular_shape_candidate(color.clone(), opacity, target_size));
+            shapes.push(self.generate_angular_shape_candidate(opacity, target_size));
         }
 
-        // Sort shapes by quality metric
-        shapes.sort_by(|a, b| {
-            let score_a = self.evaluate_shape_quality(a);
-            let score_b = self.evaluate_shape_quality(b);
-
-            // Higher is better, but add randomness to avoid always picking the same shape
-            let random_factor = self.rng.gen_range(-0.1..0.1);
-            (score_b.total_score() + random_factor)
-                .partial_cmp(&score_a.total_score())
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-
-        // Return the best shape
-        shapes
-            .into_iter()
-            .next()
-            .unwrap_or_else(|| Shape::new(color, opacity))
+        self.pick_candidate(shapes)
+            .unwrap_or_else(|| Shape::new(opacity))
     }
 
     /// Internal function to generate a candidate shape with angular properties
     fn generate_angular_shape_candidate(
         &mut self,
-        color: String,
         opacity: f32,
         target_size: usize,
     ) -> Shape {
-        let mut shape = Shape::new(color, opacity);
+        let mut shape = Shape::new(opacity);
         let total_cells = self.grid.cell_count();
 
         if total_cells == 0 || target_size == 0 {
@@ -135,7 +284,7 @@ impl<'a> ShapeGenerator<'a> {
         shape.add_cell(start_cell);
 
         // Maximum attempts to reach target size
-        let max_attempts = target_size * 3;
+        let max_attempts = target_size * self.effort.attempts_per_cell();
         let mut attempts = 0;
 
         // Use a modified breadth-first growth approach that creates balanced, angular patterns
@@ -147,7 +296,7 @@ impl<'a> ShapeGenerator<'a> {
         let mut frontier = Vec::new();
 
         // Track boundary cells (cells with at least one non-filled adjacent cell)
-        let mut boundary = HashSet::new();
+        let mut boundary = BTreeSet::new();
         boundary.insert(start_cell);
 
         // Randomness factor for this particular shape
@@ -192,9 +341,7 @@ impl<'a> ShapeGenerator<'a> {
                 let score_a = self.score_candidate_cell(&shape, a);
                 let score_b = self.score_candidate_cell(&shape, b);
                 // Compare scores (higher is better)
-                score_b
-                    .partial_cmp(&score_a)
-                    .unwrap_or(std::cmp::Ordering::Equal)
+                score_b.total_cmp(&score_a)
             });
 
             // Introduce more randomness in selection
@@ -244,9 +391,7 @@ impl<'a> ShapeGenerator<'a> {
                     let score_a = self.score_candidate_cell(&shape, a);
                     let score_b = self.score_candidate_cell(&shape, b);
                     // Compare scores (lower is worse)
-                    score_a
-                        .partial_cmp(&score_b)
-                        .unwrap_or(std::cmp::Ordering::Equal)
+                    score_a.total_cmp(&score_b)
                 });
 
                 // Remove the worst candidate
@@ -265,6 +410,19 @@ impl<'a> ShapeGenerator<'a> {
     /// Score a candidate cell for addition to a shape
     /// Higher scores indicate better candidates for balanced shapes
     fn score_candidate_cell(&self, shape: &Shape, cell_id: usize) -> f64 {
+        self.shape_score(shape, cell_id) + self.guide_score(cell_id)
+    }
+
+    /// Pull toward the guide silhouette: how dark the guide is under the cell
+    fn guide_score(&self, cell_id: usize) -> f64 {
+        match (&self.guide, self.grid.get_cell(cell_id)) {
+            (Some(guide), Some(cell)) => guide.darkness_at(cell.centroid) * guide::GUIDE_WEIGHT,
+            _ => 0.0,
+        }
+    }
+
+    /// Shape-quality part of [`ShapeGenerator::score_candidate_cell`]
+    fn shape_score(&self, shape: &Shape, cell_id: usize) -> f64 {
         if shape.cells.is_empty() {
             return 1.0; // All cells are equally good for empty shapes
         }
@@ -333,14 +491,17 @@ impl<'a> ShapeGenerator<'a> {
             let new_center_y = (center_y * shape.cells.len() as f64 + cell.centroid.y)
                 / (shape.cells.len() + 1) as f64;
 
-            let center_shift =
-                ((new_center_x - center_x).powi(2) + (new_center_y - center_y).powi(2)).sqrt();
+            let (shift_x, shift_y) = (new_center_x - center_x, new_center_y - center_y);
+            let center_shift = (shift_x * shift_x + shift_y * shift_y).sqrt();
 
             // Normalize by the expected radius
             let balance_score = 1.0 - (center_shift / expected_radius).min(1.0);
 
             // Combine scores with appropriate weights
-            score = adjacency_score * 0.4 + distance_score * 0.4 + balance_score * 0.2;
+            score = Aesthetic::weigh(
+                self.aesthetic.cell_weights(),
+                [adjacency_score, distance_score, balance_score],
+            );
         }
 
         score
@@ -385,7 +546,7 @@ impl<'a> ShapeGenerator<'a> {
             if adj_boundary >= 2 {
                 // This cell has at least 2 neighbors on the boundary
                 // Find external cells adjacent to both this and its boundary neighbors
-                let mut external_cells = HashSet::new();
+                let mut external_cells = BTreeSet::new();
 
                 for &adj in &adjacent {
                     if shape.contains_cell(adj) && boundary_cells.contains(&adj) {
@@ -428,9 +589,7 @@ impl<'a> ShapeGenerator<'a> {
             let score_a = self.score_candidate_cell(shape, a);
             let score_b = self.score_candidate_cell(shape, b);
             // Higher score is better
-            score_b
-                .partial_cmp(&score_a)
-                .unwrap_or(std::cmp::Ordering::Equal)
+            score_b.total_cmp(&score_a)
         });
 
         // Add randomness - maybe don't fill all concave areas
@@ -456,6 +615,10 @@ impl<'a> ShapeGenerator<'a> {
                 compactness: 0.0,
                 smoothness: 0.0,
                 balance: 0.0,
+                area: 0.0,
+                perimeter: 0.0,
+                convexity: 0.0,
+                aspect_ratio: 0.0,
             };
         }
 
@@ -562,19 +725,25 @@ impl<'a> ShapeGenerator<'a> {
                 let dy = cell.centroid.y - center_y;
                 let dist = (dx * dx + dy * dy).sqrt();
 
-                variance += (dist - avg_dist).powi(2);
+                variance += (dist - avg_dist) * (dist - avg_dist);
             }
         }
 
         variance /= shape.cells.len() as f64;
 
         // Lower variance means more uniform distribution around center
-        let balance = 1.0 - (variance / max_dist.powi(2)).min(1.0);
+        let balance = 1.0 - (variance / (max_dist * max_dist)).min(1.0);
+
+        let geometry = ShapeGeometry::measure(self.grid, shape);
 
         ShapeMetrics {
             compactness,
             smoothness,
             balance,
+            area: geometry.area,
+            perimeter: geometry.perimeter,
+            convexity: geometry.convexity,
+            aspect_ratio: geometry.aspect_ratio,
         }
     }
 
@@ -582,22 +751,102 @@ impl<'a> ShapeGenerator<'a> {
     /// This replaces the previous random shape generation to ensure all shapes grow from center
     pub fn generate_random_shape(
         &mut self,
-        color: String,
         opacity: f32,
         target_size: usize,
     ) -> Shape {
         // Now we have a chance to do either a center shape or angular shape
         if self.rng.gen::<f32>() < 0.5 {
-            self.generate_center_shape(color, opacity, target_size)
+            self.generate_center_shape(opacity, target_size)
         } else {
-            self.generate_angular_shape(color, opacity, target_size)
+            self.generate_angular_shape(opacity, target_size)
+        }
+    }
+
+    /// Grows a shape with a pluggable [`ShapeAlgorithm`], drawing its size from
+    /// `size_range` and sharing this generator's random stream
+    pub fn generate_with_algorithm(
+        &mut self,
+        algorithm: &dyn ShapeAlgorithm,
+        opacity: f32,
+        size_range: (usize, usize),
+        used_cells: &BTreeSet<usize>,
+    ) -> Shape {
+        let target_size = self.rng.gen_range(size_range.0..=size_range.1.max(size_range.0));
+
+        let mut shape = Shape::new(opacity);
+        for cell_id in algorithm.grow(self.grid, &mut self.rng, target_size, used_cells) {
+            shape.add_cell(cell_id);
+        }
+        shape
+    }
+
+    /// Splits the whole grid into `count` shapes that cover every cell exactly
+    /// once, flooding outward from seeds spread around the anchor point
+    pub fn generate_packed(&mut self, count: usize, opacity: f32) -> Vec<Shape> {
+        let first = self.find_center_cells()[0];
+
+        pack::partition(self.grid, &mut self.rng, first, count)
+            .into_iter()
+            .map(|cells| {
+                let mut shape = Shape::new(opacity);
+                for cell_id in cells {
+                    shape.add_cell(cell_id);
+                }
+                shape
+            })
+            .collect()
+    }
+
+    /// Covers most of the grid with dozens of tiny shapes of one to three cells
+    ///
+    /// The number of shapes follows from the grid size, with single bare cells
+    /// left as gaps between them.
+    pub fn generate_mosaic(&mut self, opacity: f32) -> Vec<Shape> {
+        mosaic::tile(self.grid, &mut self.rng)
+            .into_iter()
+            .map(|cells| {
+                let mut shape = Shape::new(opacity);
+                for cell_id in cells {
+                    shape.add_cell(cell_id);
+                }
+                shape
+            })
+            .collect()
+    }
+
+    /// Grows `count` shapes side by side in one wedge and repeats them around the center
+    ///
+    /// Shape sizes are drawn from `size_range` scaled down to the wedge, so the
+    /// repeated design covers about as much of the grid as an asymmetric one.
+    pub fn generate_symmetric(
+        &mut self,
+        symmetry: Symmetry,
+        count: usize,
+        opacity: f32,
+        size_range: (usize, usize),
+    ) -> Vec<Shape> {
+        let fold = symmetry.fold();
+        let wedge: BTreeSet<usize> = symmetry.wedge(self.grid).into_iter().collect();
+        let mut used_cells: BTreeSet<usize> = (0..self.grid.cell_count()).filter(|cell| !wedge.contains(cell)).collect();
+        let min_size = size_range.0.div_ceil(fold).max(1);
+        let max_size = size_range.1.div_ceil(fold).max(min_size);
+
+        let mut shapes = Vec::with_capacity(count);
+        for _ in 0..count {
+            let size = self.rng.gen_range(min_size..=max_size);
+            let shape = self.generate_shape_avoiding_cells(opacity, size, &used_cells);
+            if shape.cells.is_empty() {
+                break;
+            }
+            used_cells.extend(shape.cells.iter().copied());
+            shapes.push(shape);
         }
+        symmetry.repeat(self.grid, shapes)
     }
 
     /// Generates multiple shapes that grow from the center out with connecting edges
     pub fn generate_shapes(
         &mut self,
-        colors: Vec<String>,
         opacity: f32,
         count: usize,
         size_range: (usize, usize),
@@ -605,7 +854,7 @@ impl<'a> ShapeGenerator<'a> {
         let mut shapes = Vec::with_capacity(count);
 
         // Track which cells are already used
-        let mut used_cells = HashSet::new();
+        let mut used_cells = BTreeSet::new();
 
         // Generate the first shape - always start from the center
         if count > 0 {
@@ -616,25 +865,9 @@ impl<'a> ShapeGenerator<'a> {
 
             // Generate first shape - variety for first shape type
             let first_shape = if self.rng.gen::<f32>() < 0.5 {
-                self.generate_balanced_shape(
-                    if colors.is_empty() {
-                        String::from("#FF0000")
-                    } else {
-                        colors[0].clone()
-                    },
-                    opacity,
-                    size,
-                )
+                self.generate_balanced_shape(opacity, size)
             } else {
-                self.generate_angular_shape(
-                    if colors.is_empty() {
-                        String::from("#FF0000")
-                    } else {
-                        colors[0].clone()
-                    },
-                    opacity,
-                    size,
-                )
+                self.generate_angular_shape(opacity, size)
             };
 
             // Add the shape's cells to used_cells
@@ -646,15 +879,7 @@ impl<'a> ShapeGenerator<'a> {
         }
 
         // Generate remaining shapes, ensuring they connect to existing ones and grow outward
-        for i in 1..count {
-            // Use a color from the provided list if available
-            let color = if !colors.is_empty() && i < colors.len() {
-                colors[i].clone()
-            } else {
-                // Use a placeholder color if no colors were provided (will be replaced later)
-                format!("#PLACEHOLDER{}", i)
-            };
-
+        for _ in 1..count {
             // Randomize size within the range
             let min_size = size_range.0;
             let max_size = size_range.1;
@@ -664,10 +889,10 @@ impl<'a> ShapeGenerator<'a> {
             // Add more variety in shape types
             let shape = if self.rng.gen::<f32>() < 0.3 {
                 // Sometimes create shapes that avoid existing ones
-                self.generate_shape_avoiding_cells(color, opacity, size, &used_cells)
+                self.generate_shape_avoiding_cells(opacity, size, &used_cells)
             } else {
                 // Usually create shapes that connect to existing ones
-                self.generate_connected_shape(color, opacity, size, &used_cells)
+                self.generate_connected_shape(opacity, size, &used_cells)
             };
 
             // Add the shape's cells to used_cells
@@ -684,44 +909,25 @@ impl<'a> ShapeGenerator<'a> {
     /// Generate a balanced, aesthetically pleasing shape
     pub fn generate_balanced_shape(
         &mut self,
-        color: String,
         opacity: f32,
         target_size: usize,
     ) -> Shape {
         // Generate multiple candidates and select the best one
-        let candidates = 3;
+        let candidates = self.effort.candidates();
         let mut shapes = Vec::with_capacity(candidates);
 
         for _ in 0..candidates {
-            shapes.push(self.generate_center_shape(color.clone(), opacity, target_size));
+            shapes.push(self.generate_center_shape(opacity, target_size));
         }
 
-        // Sort shapes by quality metrics
-        shapes.sort_by(|a, b| {
-            let metrics_a = self.evaluate_shape_quality(a);
-            let metrics_b = self.evaluate_shape_quality(b);
-
-            let score_a = metrics_a.total_score();
-            let score_b = metrics_b.total_score();
-
-            // Higher score is better, but add randomness to avoid always picking the same shape
-            let random_factor = self.rng.gen_range(-0.1..0.1);
-            (score_b + random_factor)
-                .partial_cmp(&score_a)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-
-        // Return the best shape
-        shapes
-            .into_iter()
-            .next()
-            .unwrap_or_else(|| Shape::new(color, opacity))
+        self.pick_candidate(shapes)
+            .unwrap_or_else(|| Shape::new(opacity))
     }
 
     /// Generates a shape starting from the center of the hexagon and growing outward
     /// This ensures shapes are connected, not floating isolated, and grow from the center out
-    fn generate_center_shape(&mut self, color: String, opacity: f32, target_size: usize) -> Shape {
-        let mut shape = Shape::new(color, opacity);
+    fn generate_center_shape(&mut self, opacity: f32, target_size: usize) -> Shape {
+        let mut shape = Shape::new(opacity);
         let total_cells = self.grid.cell_count();
 
         if total_cells == 0 || target_size == 0 {
@@ -745,7 +951,7 @@ impl<'a> ShapeGenerator<'a> {
         shape.add_cell(start_cell);
 
         // Maximum attempts to reach target size
-        let max_attempts = target_size * 3;
+        let max_attempts = target_size * self.effort.attempts_per_cell();
         let mut attempts = 0;
 
         // Randomness factor for this shape
@@ -755,7 +961,7 @@ impl<'a> ShapeGenerator<'a> {
         let mut queue = VecDeque::new();
         queue.push_back(start_cell);
 
-        let mut visited = HashSet::new();
+        let mut visited = BTreeSet::new();
         visited.insert(start_cell);
 
         while shape.cell_count() < target_size && attempts < max_attempts && !queue.is_empty() {
@@ -781,9 +987,7 @@ impl<'a> ShapeGenerator<'a> {
                     let score_a = self.score_candidate_cell(&shape, a);
                     let score_b = self.score_candidate_cell(&shape, b);
                     // Higher score is better
-                    score_b
-                        .partial_cmp(&score_a)
-                        .unwrap_or(std::cmp::Ordering::Equal)
+                    score_b.total_cmp(&score_a)
                 });
             }
 
@@ -806,18 +1010,22 @@ impl<'a> ShapeGenerator<'a> {
         shape
     }
 
-    /// Finds cells closest to the center of the hexagon, sorted by distance
+    /// Finds cells closest to the anchor point, sorted by distance
+    ///
+    /// With the default centered anchor these are the cells nearest the middle
+    /// of the hexagon.
     fn find_center_cells(&self) -> Vec<usize> {
-        let center = self.grid.hex_grid().center;
+        let center = self.anchor_point;
         let mut cells_by_distance = Vec::new();
 
         for (i, cell) in self.grid.cells().iter().enumerate() {
-            let distance = cell.centroid.distance(&center);
-            cells_by_distance.push((i, distance));
+            // A guide draws the starting cell into its dark areas
+            let pull = self.guide.as_ref().map_or(0.0, |guide| guide.darkness_at(cell.centroid) * guide::GUIDE_PULL);
+            cells_by_distance.push((i, cell.centroid.distance(&center) - pull));
         }
 
         // Sort by distance to center (closest first)
-        cells_by_distance.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        cells_by_distance.sort_by(|a, b| a.1.total_cmp(&b.1));
 
         // Return all cell IDs sorted by distance from center
         // This is critical for growing from center outward in a structured way
@@ -829,13 +1037,11 @@ impl<'a> ShapeGenerator<'a> {
     /// and maintain the pattern of growing from center outward
     fn generate_connected_shape(
         &mut self,
-        color: String,
         opacity: f32,
         target_size: usize,
-        used_cells: &HashSet<usize>,
+        used_cells: &BTreeSet<usize>,
     ) -> Shape {
-        let color_clone = color.clone(); // Clone color up front for potential use later
-        let mut shape = Shape::new(color, opacity);
+        let mut shape = Shape::new(opacity);
         let total_cells = self.grid.cell_count();
 
         if total_cells == 0 || target_size == 0 {
@@ -847,7 +1053,6 @@ impl<'a> ShapeGenerator<'a> {
         if boundary_cells.is_empty() {
             // Fall back to random placement if no boundary cells found
             return self.generate_shape_avoiding_cells(
-                color_clone,
                 opacity,
                 target_size,
                 used_cells,
@@ -880,7 +1085,7 @@ impl<'a> ShapeGenerator<'a> {
         shape.add_cell(start_cell);
 
         // Maximum attempts to reach target size
-        let max_attempts = target_size * 3;
+        let max_attempts = target_size * self.effort.attempts_per_cell();
         let mut attempts = 0;
 
         // Randomness factor
@@ -890,7 +1095,7 @@ impl<'a> ShapeGenerator<'a> {
         let mut queue = VecDeque::new();
         queue.push_back(start_cell);
 
-        let mut visited = HashSet::new();
+        let mut visited = BTreeSet::new();
         visited.insert(start_cell);
 
         while shape.cell_count() < target_size && attempts < max_attempts && !queue.is_empty() {
@@ -919,9 +1124,7 @@ impl<'a> ShapeGenerator<'a> {
                     let score_a = self.score_candidate_cell(&shape, a);
                     let score_b = self.score_candidate_cell(&shape, b);
                     // Higher score is better
-                    score_b
-                        .partial_cmp(&score_a)
-                        .unwrap_or(std::cmp::Ordering::Equal)
+                    score_b.total_cmp(&score_a)
                 });
             }
 
@@ -945,7 +1148,7 @@ impl<'a> ShapeGenerator<'a> {
     }
 
     /// Finds cells that are adjacent to already used cells
-    fn find_boundary_cells(&self, used_cells: &HashSet<usize>) -> Vec<usize> {
+    fn find_boundary_cells(&self, used_cells: &BTreeSet<usize>) -> Vec<usize> {
         let mut boundary = Vec::new();
 
         for &used_cell in used_cells.iter() {
@@ -960,15 +1163,100 @@ impl<'a> ShapeGenerator<'a> {
         boundary
     }
 
+    /// Grows a shape so that about `ratio` of its cells overlap `other`
+    ///
+    /// Growth starts on the edge of `other` and, at every step, picks a
+    /// neighboring cell inside or outside it depending on how many overlapping
+    /// cells are still needed to reach the target.
+    pub fn generate_overlapping_shape(
+        &mut self,
+        opacity: f32,
+        target_size: usize,
+        other: &[usize],
+        ratio: f32,
+    ) -> Shape {
+        let mut shape = Shape::new(opacity);
+        if self.grid.cell_count() == 0 || target_size == 0 {
+            return shape;
+        }
+
+        let inside: BTreeSet<usize> = other.iter().copied().collect();
+        let wanted_inside = ((ratio.clamp(0.0, 1.0) * target_size as f32).round() as usize).min(inside.len());
+
+        // Start on the border between the two regions so either side is reachable
+        let border: Vec<usize> = self
+            .find_center_cells()
+            .into_iter()
+            .filter(|&cell| {
+                let in_other = inside.contains(&cell);
+                let crosses = self
+                    .grid
+                    .adjacent_cells(cell)
+                    .iter()
+                    .any(|adj| inside.contains(adj) != in_other);
+                crosses && (in_other == (wanted_inside > 0))
+            })
+            .collect();
+        let start = match border.first() {
+            Some(&cell) => cell,
+            None => match self.find_center_cells().first() {
+                Some(&cell) => cell,
+                None => return shape,
+            },
+        };
+        shape.add_cell(start);
+
+        while shape.cell_count() < target_size {
+            let mut frontier: Vec<usize> = shape
+                .cells
+                .iter()
+                .flat_map(|&cell| self.grid.adjacent_cells(cell))
+                .filter(|cell| !shape.contains_cell(*cell))
+                .collect();
+            frontier.sort_unstable();
+            frontier.dedup();
+            if frontier.is_empty() {
+                break;
+            }
+
+            let have_inside = shape.cells.iter().filter(|cell| inside.contains(cell)).count();
+            let need_inside = wanted_inside.saturating_sub(have_inside);
+            let remaining = target_size - shape.cell_count();
+
+            // Spread the overlapping cells over the remaining growth steps
+            let want_inside = need_inside > 0
+                && (need_inside >= remaining
+                    || self.rng.gen::<f64>() < need_inside as f64 / remaining as f64);
+
+            let (preferred, fallback): (Vec<usize>, Vec<usize>) = frontier
+                .into_iter()
+                .partition(|cell| inside.contains(cell) == want_inside);
+            let candidates = if preferred.is_empty() { fallback } else { preferred };
+
+            // Favor compact growth, with a little noise to vary the outline
+            let next = candidates
+                .iter()
+                .copied()
+                .map(|cell| (cell, self.score_candidate_cell(&shape, cell) + self.rng.gen_range(0.0..0.2)))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(cell, _)| cell);
+            match next {
+                Some(cell) => shape.add_cell(cell),
+                None => break,
+            }
+        }
+
+        shape
+    }
+
     /// Generates a shape with connected edges that grows from center outward while avoiding used cells
     pub fn generate_shape_avoiding_cells(
         &mut self,
-        color: String,
         opacity: f32,
         target_size: usize,
-        used_cells: &HashSet<usize>,
+        used_cells: &BTreeSet<usize>,
     ) -> Shape {
-        let mut shape = Shape::new(color, opacity);
+        let mut shape = Shape::new(opacity);
         let total_cells = self.grid.cell_count();
 
         if total_cells == 0 || target_size == 0 {
@@ -996,7 +1284,7 @@ impl<'a> ShapeGenerator<'a> {
         shape.add_cell(start_cell);
 
         // Maximum attempts to reach target size
-        let max_attempts = target_size * 3;
+        let max_attempts = target_size * self.effort.attempts_per_cell();
         let mut attempts = 0;
 
         // Randomness factor
@@ -1006,7 +1294,7 @@ impl<'a> ShapeGenerator<'a> {
         let mut queue = VecDeque::new();
         queue.push_back(start_cell);
 
-        let mut visited = HashSet::new();
+        let mut visited = BTreeSet::new();
         visited.insert(start_cell);
 
         while shape.cell_count() < target_size && attempts < max_attempts && !queue.is_empty() {
@@ -1035,9 +1323,7 @@ impl<'a> ShapeGenerator<'a> {
                     let score_a = self.score_candidate_cell(&shape, a);
                     let score_b = self.score_candidate_cell(&shape, b);
                     // Higher score is better
-                    score_b
-                        .partial_cmp(&score_a)
-                        .unwrap_or(std::cmp::Ordering::Equal)
+                    score_b.total_cmp(&score_a)
                 });
             }
 
@@ -1061,12 +1347,110 @@ impl<'a> ShapeGenerator<'a> {
     }
 }
 
+/// Measurements of a shape's outline, taken from its cells' vertices
+struct ShapeGeometry {
+    area: f64,
+    perimeter: f64,
+    convexity: f64,
+    aspect_ratio: f64,
+}
+
+impl ShapeGeometry {
+    fn measure(grid: &TriangularGrid, shape: &Shape) -> Self {
+        let cells: Vec<_> = shape.cells.iter().filter_map(|&id| grid.get_cell(id)).collect();
+        let area: f64 = cells.iter().map(|cell| cell.area()).sum();
+
+        // Edges shared by two of the shape's cells are inside it; the rest outline it
+        let mut edges: HashMap<(VertexKey, VertexKey), (usize, f64)> = HashMap::new();
+        for cell in &cells {
+            for i in 0..3 {
+                let (a, b) = (&cell.vertices[i], &cell.vertices[(i + 1) % 3]);
+                let (ka, kb) = (vertex_key(a), vertex_key(b));
+                let entry = edges.entry((ka.min(kb), ka.max(kb))).or_insert((0, a.distance(b)));
+                entry.0 += 1;
+            }
+        }
+        let perimeter = edges.values().filter(|(count, _)| *count == 1).map(|(_, length)| length).sum();
+
+        let vertices: Vec<Point> = cells.iter().flat_map(|cell| cell.vertices).collect();
+        let hull_area = polygon_area(&convex_hull(&vertices));
+        let convexity = if hull_area > 0.0 { (area / hull_area).min(1.0) } else { 0.0 };
+
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+        for vertex in &vertices {
+            min_x = min_x.min(vertex.x);
+            min_y = min_y.min(vertex.y);
+            max_x = max_x.max(vertex.x);
+            max_y = max_y.max(vertex.y);
+        }
+        let (width, height) = (max_x - min_x, max_y - min_y);
+        let aspect_ratio = if width > 0.0 && height > 0.0 {
+            width.max(height) / width.min(height)
+        } else {
+            0.0
+        };
+
+        Self {
+            area,
+            perimeter,
+            convexity,
+            aspect_ratio,
+        }
+    }
+}
+
+/// A vertex position rounded so the copies neighboring cells share compare equal
+type VertexKey = (i64, i64);
+
+fn vertex_key(point: &Point) -> VertexKey {
+    ((point.x * 1e4).round() as i64, (point.y * 1e4).round() as i64)
+}
+
+/// The convex hull of `points`, counterclockwise (Andrew's monotone chain)
+fn convex_hull(points: &[Point]) -> Vec<Point> {
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup_by(|a, b| vertex_key(a) == vertex_key(b));
+    if points.len() < 3 {
+        return points;
+    }
+
+    let cross = |o: &Point, a: &Point, b: &Point| (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x);
+    let mut hull: Vec<Point> = Vec::with_capacity(points.len() * 2);
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let start = hull.len();
+        for point in pass {
+            while hull.len() >= start + 2 && cross(&hull[hull.len() - 2], &hull[hull.len() - 1], &point) <= 0.0 {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        // The last point of each half is the first of the other
+        hull.pop();
+    }
+    hull
+}
+
+/// Area enclosed by a polygon (shoelace formula)
+fn polygon_area(polygon: &[Point]) -> f64 {
+    let doubled: f64 = polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum();
+    doubled.abs() / 2.0
+}
+
 // Extension to ShapeMetrics to calculate the total score
 impl ShapeMetrics {
+    /// Score under the default balanced profile, see [`ShapeMetrics::score`]
     pub fn total_score(&self) -> f64 {
-        // Weight the different metrics based on their importance
-        // Higher score means more aesthetically pleasing shape
-        self.compactness * 0.4 + self.smoothness * 0.4 + self.balance * 0.2
+        self.score(Aesthetic::Balanced)
+    }
+
+    /// How well the shape fits `aesthetic`, from 0 to 1; higher is better
+    pub fn score(&self, aesthetic: Aesthetic) -> f64 {
+        Aesthetic::weigh(aesthetic.shape_weights(), [self.compactness, self.smoothness, self.balance])
     }
 }
 
@@ -1077,15 +1461,19 @@ mod tests {
 
     #[test]
     fn test_shape_creation() {
-        let shape = Shape::new("#FF0000".to_string(), 0.8);
+        let shape = Shape::new(0.8).with_color("#FF0000");
         assert_eq!(shape.cell_count(), 0);
-        assert_eq!(shape.color, "#FF0000");
+        assert_eq!(shape.fill(), "#FF0000");
         assert_eq!(shape.opacity, 0.8);
+
+        // Grown shapes have no color until one is applied
+        assert_eq!(Shape::new(0.8).color, None);
+        assert_eq!(Shape::new(0.8).fill(), UNCOLORED);
     }
 
     #[test]
     fn test_shape_add_cell() {
-        let mut shape = Shape::new("#FF0000".to_string(), 0.8);
+        let mut shape = Shape::new(0.8).with_color("#FF0000");
         shape.add_cell(1);
         shape.add_cell(2);
         shape.add_cell(3);
@@ -1097,6 +1485,41 @@ mod tests {
         assert!(!shape.contains_cell(4));
     }
 
+    #[test]
+    fn test_dilate_erode_and_outline_ring() {
+        let grid = TriangularGrid::new(100.0, 4);
+        // A cell away from the hexagon's edge, so it has all three neighbors
+        let cell = (0..grid.cell_count()).find(|&cell| grid.ring(cell, 2).len() == 6).unwrap();
+        let single = Shape::new(0.8).with_color("#FF0000");
+        let single = Shape { cells: vec![cell], ..single };
+
+        let star = single.dilate(&grid, 1);
+        assert_eq!(star.cells[0], cell);
+        assert_eq!(star.cells[1..], grid.neighbors(cell)[..]);
+        assert_eq!(star.fill(), "#FF0000");
+        assert_eq!(single.outline_ring(&grid), grid.neighbors(cell));
+        assert_eq!(star.dilate(&grid, 1).cell_count(), 1 + 3 + 6);
+        assert_eq!(single.dilate(&grid, 0).cells, vec![cell]);
+
+        // Eroding the star strips its points and leaves the middle cell
+        assert_eq!(star.erode(&grid, 1).cells, vec![cell]);
+        assert!(star.erode(&grid, 2).cells.is_empty());
+        assert!(single.erode(&grid, 1).cells.is_empty());
+        assert_eq!(star.erode(&grid, 0).cells, star.cells);
+
+        // The whole grid erodes only along the hexagon's edge and has no ring
+        let all = Shape { cells: (0..grid.cell_count()).collect(), ..Shape::new(0.8) };
+        let edge: Vec<usize> = (0..grid.cell_count()).filter(|&c| grid.neighbors(c).len() < 3).collect();
+        assert_eq!(all.erode(&grid, 1).cell_count(), grid.cell_count() - edge.len());
+        assert!(all.outline_ring(&grid).is_empty());
+        assert_eq!(single.dilate(&grid, grid.cell_count()).cell_count(), grid.cell_count());
+
+        // Cells outside the grid are ignored rather than indexed
+        let stray = Shape { cells: vec![cell, grid.cell_count() + 7], ..Shape::new(0.8) };
+        assert_eq!(stray.outline_ring(&grid), grid.neighbors(cell));
+        assert_eq!(stray.erode(&grid, 1).cell_count(), 0);
+    }
+
     #[test]
     fn test_find_center_cells() {
         let grid = TriangularGrid::new(100.0, 4);
@@ -1113,11 +1536,10 @@ mod tests {
         let grid = TriangularGrid::new(100.0, 4);
         let mut generator = ShapeGenerator::new(&grid, Some(42)); // Fixed seed for deterministic testing
 
-        let color = "#FF0000".to_string();
         let opacity = 0.8;
         let target_size = 10;
 
-        let shape = generator.generate_center_shape(color, opacity, target_size);
+        let shape = generator.generate_center_shape(opacity, target_size);
 
         // Shape should have cells starting from center
         assert!(!shape.cells.is_empty());
@@ -1129,11 +1551,10 @@ mod tests {
         let grid = TriangularGrid::new(100.0, 4);
         let mut generator = ShapeGenerator::new(&grid, Some(42)); // Fixed seed for deterministic testing
 
-        let color = "#FF0000".to_string();
         let opacity = 0.8;
         let target_size = 10;
 
-        let shape = generator.generate_random_shape(color, opacity, target_size);
+        let shape = generator.generate_random_shape(opacity, target_size);
 
         // Shape should have cells (may be less than target if we ran out of adjacent cells)
         assert!(!shape.cells.is_empty());
@@ -1145,19 +1566,15 @@ mod tests {
         let grid = TriangularGrid::new(100.0, 4);
         let mut generator = ShapeGenerator::new(&grid, Some(42)); // Fixed seed for deterministic testing
 
-        let colors = vec![
-            "#FF0000".to_string(),
-            "#00FF00".to_string(),
-            "#0000FF".to_string(),
-        ];
         let opacity = 0.8;
         let count = 3;
         let size_range = (5, 10);
 
-        let shapes = generator.generate_shapes(colors, opacity, count, size_range);
+        let shapes = generator.generate_shapes(opacity, count, size_range);
 
-        // Should have the requested number of shapes
+        // Should have the requested number of shapes, all uncolored
         assert_eq!(shapes.len(), count);
+        assert!(shapes.iter().all(|shape| shape.color.is_none()));
 
         // Each shape should have cells within the size range
         for shape in &shapes {
@@ -1170,11 +1587,10 @@ mod tests {
         let grid = TriangularGrid::new(100.0, 4);
         let mut generator = ShapeGenerator::new(&grid, Some(42)); // Fixed seed for deterministic testing
 
-        let color = "#FF0000".to_string();
         let opacity = 0.8;
         let size = 12;
 
-        let shape = generator.generate_balanced_shape(color, opacity, size);
+        let shape = generator.generate_balanced_shape(opacity, size);
         let metrics = generator.evaluate_shape_quality(&shape);
 
         // Basic sanity checks on the metrics
@@ -1184,7 +1600,37 @@ mod tests {
 
         // Total score should be in valid range
         let total = metrics.total_score();
-        assert!(total >= 0.0 && total <= 1.0);
+        assert!((0.0..=1.0).contains(&total));
+    }
+
+    #[test]
+    fn test_shape_geometry() {
+        let grid = TriangularGrid::new(100.0, 4);
+        let generator = ShapeGenerator::new(&grid, Some(42));
+        let cell = grid.get_cell(0).unwrap();
+        let side = cell.vertices[0].distance(&cell.vertices[1]);
+
+        let mut shape = Shape::new(1.0).with_color("#FF0000");
+        shape.add_cell(0);
+        let single = generator.evaluate_shape_quality(&shape);
+        assert!((single.area - cell.area()).abs() < 1e-9);
+        assert!((single.perimeter - 3.0 * side).abs() < 1e-6);
+        assert!((single.convexity - 1.0).abs() < 1e-9);
+        assert!(single.aspect_ratio >= 1.0);
+
+        // Two neighbors make a rhombus: their shared edge is not outline
+        shape.add_cell(grid.adjacent_cells(0)[0]);
+        let pair = generator.evaluate_shape_quality(&shape);
+        assert!((pair.area - 2.0 * cell.area()).abs() < 1e-6);
+        assert!((pair.perimeter - 4.0 * side).abs() < 1e-6);
+        assert!((pair.convexity - 1.0).abs() < 1e-6);
+
+        let grown = ShapeGenerator::new(&grid, Some(7)).generate_balanced_shape(1.0, 20);
+        let metrics = generator.evaluate_shape_quality(&grown);
+        assert!(metrics.convexity > 0.0 && metrics.convexity <= 1.0);
+        assert!(metrics.perimeter > 0.0 && metrics.aspect_ratio >= 1.0);
+
+        assert_eq!(generator.evaluate_shape_quality(&Shape::new(1.0).with_color("#000000")).area, 0.0);
     }
 
     #[test]
@@ -1192,12 +1638,11 @@ mod tests {
         let grid = TriangularGrid::new(100.0, 4);
         let mut generator = ShapeGenerator::new(&grid, Some(42)); // Fixed seed for deterministic testing
 
-        let color = "#FF0000".to_string();
         let opacity = 0.8;
         let size = 15;
 
         // Create a shape without smoothing first
-        let mut shape = Shape::new(color.clone(), opacity);
+        let mut shape = Shape::new(opacity);
         let start_cell = generator.find_center_cells()[0];
         shape.add_cell(start_cell);
 
@@ -1225,18 +1670,17 @@ mod tests {
         let grid = TriangularGrid::new(100.0, 4);
         let mut generator = ShapeGenerator::new(&grid, Some(42)); // Fixed seed for deterministic testing
 
-        let color = "#FF0000".to_string();
         let opacity = 0.8;
         let target_size = 10;
 
-        let shape = generator.generate_angular_shape(color, opacity, target_size);
+        let shape = generator.generate_angular_shape(opacity, target_size);
 
         // Shape should have cells
         assert!(!shape.cells.is_empty());
         assert!(shape.cell_count() <= target_size);
 
         // Test with extreme case - zero target size
-        let shape = generator.generate_angular_shape("#00FF00".to_string(), 0.5, 0);
+        let shape = generator.generate_angular_shape(0.5, 0);
         assert_eq!(shape.cell_count(), 0);
     }
 
@@ -1246,7 +1690,7 @@ mod tests {
         let generator = ShapeGenerator::new(&grid, Some(42)); // Fixed seed for deterministic testing
 
         // Create a set of used cells
-        let mut used_cells = HashSet::new();
+        let mut used_cells = BTreeSet::new();
         used_cells.insert(0);
         used_cells.insert(1);
         used_cells.insert(2);
@@ -1268,17 +1712,16 @@ mod tests {
         let grid = TriangularGrid::new(100.0, 4);
         let mut generator = ShapeGenerator::new(&grid, Some(42)); // Fixed seed for deterministic testing
 
-        let color = "#FF0000".to_string();
         let opacity = 0.8;
         let target_size = 10;
 
         // Create a set of used cells
-        let mut used_cells = HashSet::new();
+        let mut used_cells = BTreeSet::new();
         used_cells.insert(0); // Use the center cell
 
         // Generate a shape avoiding used cells
         let shape =
-            generator.generate_shape_avoiding_cells(color, opacity, target_size, &used_cells);
+            generator.generate_shape_avoiding_cells(opacity, target_size, &used_cells);
 
         // Shape should have cells
         assert!(!shape.cells.is_empty());
@@ -1290,13 +1733,13 @@ mod tests {
         }
 
         // Test with all cells used
-        let mut all_used = HashSet::new();
+        let mut all_used = BTreeSet::new();
         for i in 0..grid.cell_count() {
             all_used.insert(i);
         }
 
         let shape =
-            generator.generate_shape_avoiding_cells("#00FF00".to_string(), 0.5, 5, &all_used);
+            generator.generate_shape_avoiding_cells(0.5, 5, &all_used);
         // Should be empty since no cells are available
         assert_eq!(shape.cell_count(), 0);
     }
@@ -1306,11 +1749,10 @@ mod tests {
         let grid = TriangularGrid::new(100.0, 4);
         let mut generator = ShapeGenerator::new(&grid, Some(42)); // Fixed seed for deterministic testing
 
-        let color = "#FF0000".to_string();
         let opacity = 0.8;
         let target_size = 10;
 
-        let shape = generator.generate_balanced_shape(color, opacity, target_size);
+        let shape = generator.generate_balanced_shape(opacity, target_size);
 
         // Shape should have cells
         assert!(!shape.cells.is_empty());
@@ -1318,7 +1760,7 @@ mod tests {
 
         // Test with entropy-based RNG
         let mut generator = ShapeGenerator::new(&grid, None); // No seed, use entropy
-        let shape = generator.generate_balanced_shape("#00FF00".to_string(), 0.5, 8);
+        let shape = generator.generate_balanced_shape(0.5, 8);
         assert!(!shape.cells.is_empty());
     }
 }
@@ -0,0 +1,83 @@
+use crate::generator::grid::TriangularGrid;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+
+/// Most cells a mosaic tile spans
+pub const MAX_TILE_CELLS: usize = 3;
+
+/// Chance that a cell starts a tile rather than being left bare
+const COVERAGE: f64 = 0.9;
+
+/// Cuts the grid into many small tiles of one to three connected cells
+///
+/// Cells are visited in random order; each one not yet taken either starts a
+/// tile, which then grows into random free neighbors up to its drawn size, or
+/// stays bare. Bare cells are single gaps between tiles, so tiles cover well
+/// over nine in ten cells.
+pub fn tile(grid: &TriangularGrid, rng: &mut dyn RngCore) -> Vec<Vec<usize>> {
+    let total = grid.cell_count();
+    let mut order: Vec<usize> = (0..total).collect();
+    order.shuffle(rng);
+
+    let mut taken = vec![false; total];
+    let mut tiles = Vec::new();
+    for cell in order {
+        if taken[cell] {
+            continue;
+        }
+        taken[cell] = true;
+        if !rng.gen_bool(COVERAGE) {
+            continue;
+        }
+
+        let size = rng.gen_range(1..=MAX_TILE_CELLS);
+        let mut tile = vec![cell];
+        while tile.len() < size {
+            let frontier: Vec<usize> = tile
+                .iter()
+                .flat_map(|&cell| grid.neighbors(cell))
+                .filter(|&neighbor| !taken[neighbor])
+                .collect();
+            if frontier.is_empty() {
+                break;
+            }
+            let next = frontier[rng.gen_range(0..frontier.len())];
+            taken[next] = true;
+            tile.push(next);
+        }
+
+        tile.sort_unstable();
+        tiles.push(tile);
+    }
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_tiles_are_small_and_cover_most_cells() {
+        for density in [2, 4, 8] {
+            let grid = TriangularGrid::new(100.0, density);
+            let mut rng = ChaCha8Rng::seed_from_u64(density as u64);
+            let tiles = tile(&grid, &mut rng);
+
+            let mut cells: Vec<usize> = tiles.iter().flatten().copied().collect();
+            let covered = cells.len();
+            cells.sort_unstable();
+            cells.dedup();
+            assert_eq!(cells.len(), covered, "tiles never share a cell");
+            assert!(covered * 10 >= grid.cell_count() * 8, "{} of {} cells covered", covered, grid.cell_count());
+
+            for tile in &tiles {
+                assert!((1..=MAX_TILE_CELLS).contains(&tile.len()));
+                // With three cells or fewer, every cell touching another means the tile is connected
+                let touching = |cell: usize| tile.iter().any(|&other| grid.neighbors(cell).contains(&other));
+                assert!(tile.len() == 1 || tile.iter().all(|&cell| touching(cell)));
+            }
+        }
+    }
+}
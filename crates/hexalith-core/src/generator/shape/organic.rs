@@ -0,0 +1,173 @@
+use super::algorithm::ShapeAlgorithm;
+use crate::generator::grid::TriangularGrid;
+use rand::{Rng, RngCore};
+use std::collections::{BTreeSet, VecDeque};
+
+/// Grows organic, coral-like shapes with a cellular automaton
+///
+/// A patch of free cells is seeded at random, then a birth/survival rule on
+/// each cell's three triangular neighbors is applied for a few generations.
+/// The largest connected cluster that survives becomes the shape.
+#[derive(Debug, Clone)]
+pub struct OrganicCaAlgorithm {
+    /// Fraction of the patch's cells that start alive
+    pub fill: f64,
+    /// Number of CA generations to run
+    pub iterations: usize,
+    /// A dead cell comes alive with at least this many live neighbors
+    pub birth: usize,
+    /// A live cell survives with at least this many live neighbors
+    pub survival: usize,
+}
+
+impl Default for OrganicCaAlgorithm {
+    fn default() -> Self {
+        Self {
+            fill: 0.55,
+            iterations: 4,
+            birth: 2,
+            survival: 1,
+        }
+    }
+}
+
+impl ShapeAlgorithm for OrganicCaAlgorithm {
+    fn grow(
+        &self,
+        grid: &TriangularGrid,
+        rng: &mut dyn RngCore,
+        target_size: usize,
+        used_cells: &BTreeSet<usize>,
+    ) -> Vec<usize> {
+        let free: Vec<usize> = (0..grid.cell_count())
+            .filter(|id| !used_cells.contains(id))
+            .collect();
+        if free.is_empty() {
+            return Vec::new();
+        }
+
+        // The automaton runs on a patch around a random free cell, about twice
+        // the target size so the rule has room to carve an irregular outline
+        let origin = free[rng.gen_range(0..free.len())];
+        let patch = nearest_free_cells(grid, origin, target_size.max(1) * 2, used_cells);
+
+        let mut alive = vec![false; grid.cell_count()];
+        for &id in &patch {
+            alive[id] = rng.gen_bool(self.fill.clamp(0.0, 1.0));
+        }
+        alive[origin] = true;
+
+        for _ in 0..self.iterations {
+            let mut next = alive.clone();
+            for &id in &patch {
+                let live_neighbors = grid
+                    .neighbors(id)
+                    .into_iter()
+                    .filter(|&n| alive[n])
+                    .count();
+                next[id] = if alive[id] {
+                    live_neighbors >= self.survival
+                } else {
+                    live_neighbors >= self.birth
+                };
+            }
+            alive = next;
+        }
+
+        let mut cluster = largest_cluster(grid, &patch, &alive);
+        if cluster.is_empty() {
+            cluster.push(origin);
+        }
+
+        // Trim overgrown clusters back to the target in growth order, which keeps them connected
+        cluster.truncate(target_size.max(1));
+        cluster
+    }
+}
+
+/// Collects up to `count` free cells in breadth-first order around `origin`
+fn nearest_free_cells(
+    grid: &TriangularGrid,
+    origin: usize,
+    count: usize,
+    used_cells: &BTreeSet<usize>,
+) -> Vec<usize> {
+    let mut visited = vec![false; grid.cell_count()];
+    let mut queue = VecDeque::from([origin]);
+    let mut cells = Vec::with_capacity(count);
+    visited[origin] = true;
+
+    while let Some(current) = queue.pop_front() {
+        cells.push(current);
+        if cells.len() >= count {
+            break;
+        }
+        for neighbor in grid.neighbors(current) {
+            if !visited[neighbor] && !used_cells.contains(&neighbor) {
+                visited[neighbor] = true;
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    cells
+}
+
+/// Finds the largest connected group of live cells, in breadth-first order
+fn largest_cluster(grid: &TriangularGrid, patch: &[usize], alive: &[bool]) -> Vec<usize> {
+    let mut visited = vec![false; grid.cell_count()];
+    let mut best = Vec::new();
+
+    for &start in patch {
+        if !alive[start] || visited[start] {
+            continue;
+        }
+
+        let mut cluster = Vec::new();
+        let mut queue = VecDeque::from([start]);
+        visited[start] = true;
+
+        while let Some(current) = queue.pop_front() {
+            cluster.push(current);
+            for neighbor in grid.neighbors(current) {
+                if alive[neighbor] && !visited[neighbor] {
+                    visited[neighbor] = true;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        if cluster.len() > best.len() {
+            best = cluster;
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_organic_shape_is_connected() {
+        let grid = TriangularGrid::new(100.0, 6);
+        let used: BTreeSet<usize> = grid.cells_in_sector(2).into_iter().collect();
+
+        for seed in 0..10 {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            let cells = OrganicCaAlgorithm::default().grow(&grid, &mut rng, 15, &used);
+
+            assert!(!cells.is_empty() && cells.len() <= 15);
+            assert!(cells.iter().all(|id| !used.contains(id)));
+
+            // Every cell after the first touches an earlier one
+            for (i, &cell) in cells.iter().enumerate().skip(1) {
+                let neighbors = grid.neighbors(cell);
+                assert!(cells[..i].iter().any(|c| neighbors.contains(c)));
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::generator::grid::TriangularGrid;
+use rand::{Rng, RngCore};
+use std::collections::VecDeque;
+
+/// Partitions every cell of the grid among `count` connected regions
+///
+/// Seeds are spread out by farthest-point sampling starting from `first`, then
+/// all regions flood outward together, each claiming one random frontier cell
+/// per round, until no cell is left unassigned. Regions therefore stay roughly
+/// the same size and always tile the whole hexagon.
+pub fn partition(
+    grid: &TriangularGrid,
+    rng: &mut dyn RngCore,
+    first: usize,
+    count: usize,
+) -> Vec<Vec<usize>> {
+    let total = grid.cell_count();
+    let count = count.clamp(1, total);
+
+    let seeds = spread_seeds(grid, rng, first, count);
+
+    let mut owner: Vec<Option<usize>> = vec![None; total];
+    let mut regions: Vec<Vec<usize>> = vec![Vec::new(); count];
+    let mut frontiers: Vec<Vec<usize>> = vec![Vec::new(); count];
+
+    for (region, &seed) in seeds.iter().enumerate() {
+        owner[seed] = Some(region);
+        regions[region].push(seed);
+        frontiers[region].extend(grid.neighbors(seed));
+    }
+
+    let mut remaining = total - count;
+    while remaining > 0 {
+        let mut claimed_any = false;
+
+        for region in 0..count {
+            // Frontiers hold stale entries for cells claimed by other regions
+            while !frontiers[region].is_empty() {
+                let pick = rng.gen_range(0..frontiers[region].len());
+                let cell = frontiers[region].swap_remove(pick);
+                if owner[cell].is_some() {
+                    continue;
+                }
+
+                owner[cell] = Some(region);
+                regions[region].push(cell);
+                frontiers[region].extend(
+                    grid.neighbors(cell)
+                        .into_iter()
+                        .filter(|&n| owner[n].is_none()),
+                );
+                remaining -= 1;
+                claimed_any = true;
+                break;
+            }
+
+            if remaining == 0 {
+                break;
+            }
+        }
+
+        // The grid is connected, so this only guards against malformed grids
+        if !claimed_any {
+            break;
+        }
+    }
+
+    for region in &mut regions {
+        region.sort_unstable();
+    }
+    regions
+}
+
+/// Picks `count` seed cells, each as far as possible from those already chosen
+fn spread_seeds(
+    grid: &TriangularGrid,
+    rng: &mut dyn RngCore,
+    first: usize,
+    count: usize,
+) -> Vec<usize> {
+    let mut seeds = vec![first];
+    let mut distance = distances_from(grid, first);
+
+    while seeds.len() < count {
+        let farthest = distance.iter().copied().max().unwrap_or(0);
+        let candidates: Vec<usize> = (0..distance.len())
+            .filter(|&id| distance[id] == farthest)
+            .collect();
+        let seed = candidates[rng.gen_range(0..candidates.len())];
+        seeds.push(seed);
+
+        for (current, new) in distance.iter_mut().zip(distances_from(grid, seed)) {
+            *current = (*current).min(new);
+        }
+    }
+
+    seeds
+}
+
+/// Edge-adjacency distance from `start` to every cell
+fn distances_from(grid: &TriangularGrid, start: usize) -> Vec<usize> {
+    let mut distance = vec![usize::MAX; grid.cell_count()];
+    distance[start] = 0;
+
+    let mut queue = VecDeque::from([start]);
+    while let Some(cell) = queue.pop_front() {
+        for neighbor in grid.neighbors(cell) {
+            if distance[neighbor] == usize::MAX {
+                distance[neighbor] = distance[cell] + 1;
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    distance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_partition_covers_grid() {
+        let grid = TriangularGrid::new(100.0, 4);
+        let mut rng = ChaCha8Rng::seed_from_u64(5);
+        let regions = partition(&grid, &mut rng, 0, 6);
+
+        assert_eq!(regions.len(), 6);
+
+        // Every cell belongs to exactly one region
+        let mut cells: Vec<usize> = regions.iter().flatten().copied().collect();
+        cells.sort_unstable();
+        assert_eq!(cells, (0..grid.cell_count()).collect::<Vec<_>>());
+
+        // Each region is a single connected piece
+        for region in &regions {
+            let mut reached = vec![region[0]];
+            let mut queue = VecDeque::from([region[0]]);
+            while let Some(cell) = queue.pop_front() {
+                for neighbor in grid.neighbors(cell) {
+                    if region.contains(&neighbor) && !reached.contains(&neighbor) {
+                        reached.push(neighbor);
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+            assert_eq!(reached.len(), region.len());
+        }
+    }
+}
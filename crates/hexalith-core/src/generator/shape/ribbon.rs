@@ -0,0 +1,209 @@
+use super::algorithm::ShapeAlgorithm;
+use crate::generator::grid::TriangularGrid;
+use rand::{Rng, RngCore};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, VecDeque};
+
+/// Grows ribbon shapes: a winding path of cells between two edge cells
+///
+/// The path is found with A* over the cell adjacency graph, where every step
+/// gets a random extra cost so the route detours instead of running straight.
+#[derive(Debug, Clone)]
+pub struct RibbonAlgorithm {
+    /// Upper bound of the random extra cost per step; higher values wander more
+    pub detour: f64,
+    /// How many endpoint pairs to try before giving up on a crowded grid
+    pub attempts: usize,
+}
+
+impl Default for RibbonAlgorithm {
+    fn default() -> Self {
+        Self {
+            detour: 2.0,
+            attempts: 8,
+        }
+    }
+}
+
+impl ShapeAlgorithm for RibbonAlgorithm {
+    fn grow(
+        &self,
+        grid: &TriangularGrid,
+        rng: &mut dyn RngCore,
+        target_size: usize,
+        used_cells: &BTreeSet<usize>,
+    ) -> Vec<usize> {
+        let free = |id: &usize| !used_cells.contains(id);
+
+        // Ribbons run from edge to edge; fall back to any free cell once the rim is taken
+        let mut starts: Vec<usize> = (0..grid.cell_count())
+            .filter(free)
+            .filter(|&id| grid.neighbors(id).len() < 3)
+            .collect();
+        if starts.is_empty() {
+            starts = (0..grid.cell_count()).filter(free).collect();
+        }
+        if starts.is_empty() {
+            return Vec::new();
+        }
+
+        for _ in 0..self.attempts {
+            let start = starts[rng.gen_range(0..starts.len())];
+            let distances = free_distances(grid, start, used_cells);
+
+            // Ribbons should cross the hexagon, so pick one of the rim cells
+            // farthest from the start, preferring those at least `target_size` steps away
+            let reachable: Vec<usize> = starts
+                .iter()
+                .copied()
+                .filter(|&id| id != start && distances[id] != usize::MAX)
+                .collect();
+            let mut ends: Vec<usize> = reachable
+                .iter()
+                .copied()
+                .filter(|&id| distances[id] >= target_size)
+                .collect();
+            if ends.is_empty() {
+                ends = reachable;
+            }
+            let origin = grid.get_cell_centroid(start);
+            ends.sort_by(|&a, &b| {
+                let distance = |id| {
+                    let centroid = grid.get_cell_centroid(id).zip(origin);
+                    centroid.map_or(f64::NEG_INFINITY, |(p, o)| p.distance(&o))
+                };
+                distance(b).total_cmp(&distance(a))
+            });
+            ends.truncate(3);
+            let end = (!ends.is_empty()).then(|| ends[rng.gen_range(0..ends.len())]);
+
+            if let Some(end) = end {
+                if let Some(path) = self.find_path(grid, rng, start, end, used_cells) {
+                    return path;
+                }
+            }
+        }
+
+        vec![starts[0]]
+    }
+}
+
+impl RibbonAlgorithm {
+    /// A* search from `start` to `goal` through free cells with randomized step costs
+    fn find_path(
+        &self,
+        grid: &TriangularGrid,
+        rng: &mut dyn RngCore,
+        start: usize,
+        goal: usize,
+        used_cells: &BTreeSet<usize>,
+    ) -> Option<Vec<usize>> {
+        let goal_point = grid.get_cell_centroid(goal)?;
+        let edge = grid
+            .get_cell(start)
+            .map(|cell| cell.vertices[0].distance(&cell.vertices[1]))
+            .unwrap_or(1.0);
+
+        // Centroid distance measured in cell edges never overestimates the step count
+        let heuristic = |id: usize| {
+            grid.get_cell_centroid(id)
+                .map(|p| p.distance(&goal_point) / edge)
+                .unwrap_or(0.0)
+        };
+
+        // Scores are kept as fixed point so they can live in a BinaryHeap
+        let to_key = |score: f64| (score * 1000.0) as u64;
+
+        let mut cost = vec![f64::INFINITY; grid.cell_count()];
+        let mut came_from = vec![usize::MAX; grid.cell_count()];
+        let mut open = BinaryHeap::new();
+
+        cost[start] = 0.0;
+        open.push((Reverse(to_key(heuristic(start))), start));
+
+        while let Some((_, current)) = open.pop() {
+            if current == goal {
+                let mut path = vec![goal];
+                let mut node = goal;
+                while node != start {
+                    node = came_from[node];
+                    path.push(node);
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            for neighbor in grid.neighbors(current) {
+                if used_cells.contains(&neighbor) {
+                    continue;
+                }
+
+                // Rim cells cost extra so ribbons cut through the middle instead
+                // of tracing the hexagon's outline
+                let rim_penalty = if grid.neighbors(neighbor).len() < 3 { 2.0 } else { 0.0 };
+                let step = 1.0 + rim_penalty + rng.gen::<f64>() * self.detour;
+                let candidate = cost[current] + step;
+                if candidate < cost[neighbor] {
+                    cost[neighbor] = candidate;
+                    came_from[neighbor] = current;
+                    open.push((Reverse(to_key(candidate + heuristic(neighbor))), neighbor));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Step counts from `start` to every cell reachable through free cells
+fn free_distances(grid: &TriangularGrid, start: usize, used_cells: &BTreeSet<usize>) -> Vec<usize> {
+    let mut distance = vec![usize::MAX; grid.cell_count()];
+    let mut queue = VecDeque::from([start]);
+    distance[start] = 0;
+
+    while let Some(current) = queue.pop_front() {
+        for neighbor in grid.neighbors(current) {
+            if distance[neighbor] == usize::MAX && !used_cells.contains(&neighbor) {
+                distance[neighbor] = distance[current] + 1;
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    distance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_ribbon_is_connected_path() {
+        let grid = TriangularGrid::new(100.0, 5);
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let used: BTreeSet<usize> = grid.cells_in_sector(0).into_iter().collect();
+
+        let path = RibbonAlgorithm::default().grow(&grid, &mut rng, 12, &used);
+
+        assert!(path.len() >= 2);
+        assert!(path.iter().all(|id| !used.contains(id)));
+        for pair in path.windows(2) {
+            assert!(grid.neighbors(pair[0]).contains(&pair[1]));
+        }
+
+        // Both ends sit on the hexagon's rim
+        assert!(grid.neighbors(path[0]).len() < 3);
+        assert!(grid.neighbors(*path.last().unwrap()).len() < 3);
+    }
+
+    #[test]
+    fn test_ribbon_on_full_grid() {
+        let grid = TriangularGrid::new(100.0, 2);
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let used: BTreeSet<usize> = (0..grid.cell_count()).collect();
+
+        assert!(RibbonAlgorithm::default().grow(&grid, &mut rng, 4, &used).is_empty());
+    }
+}
@@ -0,0 +1,180 @@
+use super::Shape;
+use crate::generator::grid::TriangularGrid;
+
+/// Rotational symmetry of a design: shapes grown in one wedge are repeated around the center
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Symmetry {
+    #[default]
+    None,
+    /// Two copies, half a turn apart
+    Two,
+    /// Three copies, a third of a turn apart
+    Three,
+    /// Six copies, one per hexagon sector
+    Six,
+}
+
+/// How the rotated copies of a symmetric shape are colored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymmetryColors {
+    /// Every copy takes its shape's color
+    #[default]
+    Same,
+    /// Each copy shifts one step along the palette, so the colors turn with the shapes
+    Rotate,
+}
+
+/// The symmetric shape a shape was copied from, and which rotated copy it is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SymmetryGroup {
+    /// Index of the shape grown in the wedge, shared by all its copies
+    pub group: usize,
+    /// 0 for the grown shape, then 1, 2, ... going around the center
+    pub copy: usize,
+}
+
+impl Symmetry {
+    /// Names accepted by [`Symmetry::from_str`](std::str::FromStr)
+    pub fn names() -> Vec<&'static str> {
+        vec!["none", "2", "3", "6"]
+    }
+
+    /// Number of copies going around the center; 1 without symmetry
+    pub fn fold(&self) -> usize {
+        match self {
+            Symmetry::None => 1,
+            Symmetry::Two => 2,
+            Symmetry::Three => 3,
+            Symmetry::Six => 6,
+        }
+    }
+
+    /// The cells of the wedge shapes are grown in: the first `6 / fold` sectors
+    pub fn wedge(&self, grid: &TriangularGrid) -> Vec<usize> {
+        (0..6 / self.fold()).flat_map(|sector| grid.cells_in_sector(sector)).collect()
+    }
+
+    /// Repeats each wedge shape around the center, tagging every copy with its [`SymmetryGroup`]
+    ///
+    /// Copies of a shape follow it directly, so shape `i` of `shapes` becomes
+    /// shapes `i * fold` to `i * fold + fold - 1`.
+    pub fn repeat(&self, grid: &TriangularGrid, shapes: Vec<Shape>) -> Vec<Shape> {
+        let fold = self.fold();
+        let mut repeated = Vec::with_capacity(shapes.len() * fold);
+        for (group, shape) in shapes.into_iter().enumerate() {
+            for copy in 0..fold {
+                let mut rotated = shape.clone();
+                rotated.cells = shape.cells.iter().map(|&cell| rotate_cell(grid, cell, copy * 6 / fold)).collect();
+                rotated.symmetry = Some(SymmetryGroup { group, copy });
+                repeated.push(rotated);
+            }
+        }
+        repeated
+    }
+}
+
+/// The cell `sectors` sixths of a turn around the center from `cell_id`
+///
+/// Every sector is subdivided the same way, so rotating moves a cell id by a
+/// whole sector's worth of cells.
+pub fn rotate_cell(grid: &TriangularGrid, cell_id: usize, sectors: usize) -> usize {
+    let count = grid.cell_count();
+    if count == 0 {
+        return cell_id;
+    }
+    (cell_id + sectors % 6 * (count / 6)) % count
+}
+
+impl std::fmt::Display for Symmetry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Symmetry::None => write!(f, "none"),
+            fold => write!(f, "{}", fold.fold()),
+        }
+    }
+}
+
+impl std::str::FromStr for Symmetry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" | "1" => Ok(Symmetry::None),
+            "2" => Ok(Symmetry::Two),
+            "3" => Ok(Symmetry::Three),
+            "6" => Ok(Symmetry::Six),
+            _ => Err(format!(
+                "Unknown symmetry '{}' (expected one of: {})",
+                s,
+                Self::names().join(", ")
+            )),
+        }
+    }
+}
+
+impl SymmetryColors {
+    /// Names accepted by [`SymmetryColors::from_str`](std::str::FromStr)
+    pub fn names() -> Vec<&'static str> {
+        vec!["same", "rotate"]
+    }
+}
+
+impl std::fmt::Display for SymmetryColors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SymmetryColors::Same => write!(f, "same"),
+            SymmetryColors::Rotate => write!(f, "rotate"),
+        }
+    }
+}
+
+impl std::str::FromStr for SymmetryColors {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "same" => Ok(SymmetryColors::Same),
+            "rotate" => Ok(SymmetryColors::Rotate),
+            _ => Err(format!(
+                "Unknown symmetry colors '{}' (expected one of: {})",
+                s,
+                Self::names().join(", ")
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symmetry_repeat() {
+        assert_eq!("3".parse::<Symmetry>(), Ok(Symmetry::Three));
+        assert_eq!("none".parse::<Symmetry>(), Ok(Symmetry::None));
+        assert!("4".parse::<Symmetry>().unwrap_err().contains("none, 2, 3, 6"));
+        assert_eq!("rotate".parse::<SymmetryColors>(), Ok(SymmetryColors::Rotate));
+
+        for density in [2, 4] {
+            let grid = TriangularGrid::new(100.0, density);
+            let per_sector = grid.cell_count() / 6;
+            assert_eq!(Symmetry::Three.wedge(&grid).len(), 2 * per_sector);
+
+            // Rotating by a sector keeps a cell's distance from the center
+            for cell in 0..grid.cell_count() {
+                let rotated = rotate_cell(&grid, cell, 1);
+                assert_eq!(grid.sector_of(rotated), grid.sector_of(cell).map(|sector| (sector + 1) % 6));
+                let distance = |id: usize| grid.cells()[id].centroid.distance(&grid.hex_grid().center);
+                assert!((distance(rotated) - distance(cell)).abs() < 1e-9);
+            }
+        }
+
+        let grid = TriangularGrid::new(100.0, 4);
+        let mut shape = Shape::new(1.0);
+        shape.cells = vec![0, 1];
+        let repeated = Symmetry::Three.repeat(&grid, vec![shape]);
+        assert_eq!(repeated.len(), 3);
+        assert_eq!(repeated[2].cells, vec![64, 65]);
+        assert_eq!(repeated[2].symmetry, Some(SymmetryGroup { group: 0, copy: 2 }));
+    }
+}
@@ -0,0 +1,70 @@
+/// Revision of the generation algorithm
+///
+/// A released version is frozen: the same seed and options always grow the
+/// same design under it. Changes to shape growth go into a new version, so
+/// designs saved by seed keep looking the way they did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlgoVersion {
+    /// The original algorithm
+    V1,
+}
+
+impl AlgoVersion {
+    /// The version new designs use unless told otherwise
+    pub const LATEST: AlgoVersion = AlgoVersion::V1;
+
+    /// Names accepted by [`AlgoVersion::from_str`](std::str::FromStr)
+    pub fn names() -> Vec<&'static str> {
+        vec!["1", "latest"]
+    }
+
+    /// The version's number, as written in specs and design codes
+    pub fn number(&self) -> u8 {
+        match self {
+            AlgoVersion::V1 => 1,
+        }
+    }
+
+    /// The version numbered `number`, if this build has it
+    pub fn from_number(number: u8) -> Option<Self> {
+        match number {
+            1 => Some(AlgoVersion::V1),
+            _ => None,
+        }
+    }
+}
+
+impl Default for AlgoVersion {
+    fn default() -> Self {
+        Self::LATEST
+    }
+}
+
+impl std::fmt::Display for AlgoVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+impl std::str::FromStr for AlgoVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+        if name == "latest" {
+            return Ok(Self::LATEST);
+        }
+        name.strip_prefix('v')
+            .unwrap_or(&name)
+            .parse::<u8>()
+            .ok()
+            .and_then(Self::from_number)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown algorithm version '{}' (expected one of: {})",
+                    s,
+                    Self::names().join(", ")
+                )
+            })
+    }
+}
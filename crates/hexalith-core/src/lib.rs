@@ -0,0 +1,16 @@
+pub mod generator;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+pub use crate::generator::color::{register_theme, unregister_theme};
+pub use crate::generator::{GeneratedDesign, Generator, GeneratorConfig};
+
+/// Computes a 64-bit FNV-1a hash that is stable across platforms and releases
+pub fn stable_hash(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
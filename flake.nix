@@ -108,8 +108,8 @@
               help = "Fix broken test imports";
               command = ''
                 # Fix grid tests
-                sed -i.bak 's/use super::geometry::{HexGrid, Point};/use crate::generator::grid::geometry::{HexGrid, Point};/' ./src/generator/grid/tests.rs
-                sed -i.bak 's/use super::triangular::TriangularGrid;/use crate::generator::grid::triangular::TriangularGrid;/' ./src/generator/grid/tests.rs
+                sed -i.bak 's/use super::geometry::{HexGrid, Point};/use crate::generator::grid::geometry::{HexGrid, Point};/' ./crates/hexalith-core/src/generator/grid/tests.rs
+                sed -i.bak 's/use super::triangular::TriangularGrid;/use crate::generator::grid::triangular::TriangularGrid;/' ./crates/hexalith-core/src/generator/grid/tests.rs
                 
                 # Fix utils tests
                 sed -i.bak 's/use super::\*;/use crate::utils::{uuid_to_seed, default_color_palette};/' ./src/utils/tests.rs
@@ -0,0 +1,20 @@
+#![no_main]
+
+use clap::Parser;
+use hexlogogen::cli::{Cli, DesignSpec};
+use hexlogogen::svg;
+use libfuzzer_sys::fuzz_target;
+
+// A --from-spec file, applied and rendered the way `hexlogogen --from-spec` does
+fuzz_target!(|json: &str| {
+    let Ok(spec) = DesignSpec::parse(json) else {
+        return;
+    };
+    let mut cli = Cli::parse_from(["hexlogogen"]);
+    if spec.apply(&mut cli, None).is_err() {
+        return;
+    }
+    if let (Ok(design), Ok(options)) = (cli.design.design(), cli.design.svg_options()) {
+        let _ = svg::design_to_svg(&design, 64, 64, &options);
+    }
+});
@@ -0,0 +1,20 @@
+#![no_main]
+
+use hexlogogen::web::validation;
+use libfuzzer_sys::fuzz_target;
+
+// A /generate or /api/batch request body, as any client could send it
+fuzz_target!(|data: &[u8]| {
+    let Ok(object) = validation::parse_object(data) else {
+        return;
+    };
+    if let Ok(params) = validation::logo_params(&object) {
+        let canonical = params.canonicalize(42);
+        let _ = canonical.query_string();
+        let _ = canonical.cache_key();
+        let _ = canonical.config().generate();
+    }
+    let _ = validation::recolor(&object);
+    let _ = validation::reroll(&object);
+    let _ = validation::wallpaper(&object);
+});
@@ -0,0 +1,17 @@
+#![no_main]
+
+use hexlogogen::generator::color::{parse_color, parse_hex, ColorManager};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    if let Ok(hex) = parse_color(input) {
+        // Whatever parses must normalize to a color that parses to itself
+        assert_eq!(parse_color(&hex).unwrap(), hex);
+    }
+    if let Ok(rgb) = parse_hex(input) {
+        assert_eq!(ColorManager::hex_to_rgba(input), (rgb.r, rgb.g, rgb.b, rgb.a));
+    }
+    let _ = ColorManager::hex_to_rgb(input);
+    let _ = ColorManager::hex_to_rgba(input);
+    let _ = ColorManager::alpha(input);
+});
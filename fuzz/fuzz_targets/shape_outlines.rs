@@ -0,0 +1,24 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use hexlogogen::generator::grid::TriangularGrid;
+use hexlogogen::svg;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+struct Region {
+    grid_density: u8,
+    /// Cell ids, including out of range and repeated ones
+    cells: Vec<u16>,
+    smoothing: u8,
+    tolerance: f64,
+}
+
+fuzz_target!(|region: Region| {
+    let grid = TriangularGrid::new(100.0, region.grid_density % 12);
+    let cells: Vec<usize> = region.cells.iter().map(|&cell| cell as usize).collect();
+    for outline in svg::shape_outlines(&grid, &cells) {
+        let _ = svg::chaikin(&outline, region.smoothing as usize % 4);
+        let _ = svg::simplify_outline(&outline, region.tolerance);
+    }
+});
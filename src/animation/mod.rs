@@ -0,0 +1,213 @@
+use crate::generator::color::ColorManager;
+use crate::generator::shape::Shape;
+use crate::generator::GeneratedDesign;
+#[cfg(feature = "png")]
+use crate::png;
+use crate::svg;
+use crate::Result;
+use std::fs;
+use std::path::Path;
+
+/// Time each frame is shown, in milliseconds
+pub const FRAME_DELAY_MS: u32 = 40;
+
+/// Upper bound on the number of frames in one animation
+pub const MAX_FRAMES: usize = 240;
+
+/// The color and opacity a cell is painted with
+type Fill = (String, f32);
+
+/// Computes the frames of a transition from one design to another
+///
+/// Cells that both designs cover change color through OKLCH, while cells only
+/// one of them covers fade in or out. The first frame shows `from` and the
+/// last shows `to`; both designs must use the same grid density.
+pub fn morph_frames(
+    from: &GeneratedDesign,
+    to: &GeneratedDesign,
+    frames: usize,
+) -> Result<Vec<Vec<Shape>>> {
+    if from.config().grid_size() != to.config().grid_size() {
+        return Err(format!(
+            "Cannot morph between grid sizes {} and {}",
+            from.config().grid_size(),
+            to.config().grid_size()
+        )
+        .into());
+    }
+
+    let frames = frames.clamp(2, MAX_FRAMES);
+    let before = cell_fills(from);
+    let after = cell_fills(to);
+
+    Ok((0..frames)
+        .map(|index| {
+            // Ease in and out so the transition settles at both ends
+            let t = index as f64 / (frames - 1) as f64;
+            frame_at(&before, &after, t * t * (3.0 - 2.0 * t))
+        })
+        .collect())
+}
+
+/// The fill of every cell, taken from the top-most shape covering it
+fn cell_fills(design: &GeneratedDesign) -> Vec<Option<Fill>> {
+    let mut fills = vec![None; design.grid().cell_count()];
+    for shape in design.shapes() {
+        for &cell in &shape.cells {
+            if let Some(fill) = fills.get_mut(cell) {
+                *fill = Some((shape.fill().to_string(), shape.opacity));
+            }
+        }
+    }
+    fills
+}
+
+/// Interpolates every cell at `t` and groups cells with the same fill into shapes
+fn frame_at(before: &[Option<Fill>], after: &[Option<Fill>], t: f64) -> Vec<Shape> {
+    let mut shapes: Vec<Shape> = Vec::new();
+
+    for (cell, (start, end)) in before.iter().zip(after).enumerate() {
+        let (color, opacity) = match (start, end) {
+            (Some((c1, o1)), Some((c2, o2))) => (
+                ColorManager::interpolate(c1, c2, t),
+                o1 + (o2 - o1) * t as f32,
+            ),
+            (Some((color, opacity)), None) => (color.clone(), opacity * (1.0 - t as f32)),
+            (None, Some((color, opacity))) => (color.clone(), opacity * t as f32),
+            (None, None) => continue,
+        };
+
+        // Quantize so neighboring cells with the same fill merge into one path
+        let opacity = (opacity * 1000.0).round() / 1000.0;
+        if opacity <= 0.0 {
+            continue;
+        }
+
+        match shapes
+            .iter_mut()
+            .find(|shape| shape.color.as_ref() == Some(&color) && shape.opacity == opacity)
+        {
+            Some(shape) => shape.add_cell(cell),
+            None => {
+                let mut shape = Shape::new(opacity).with_color(color);
+                shape.add_cell(cell);
+                shapes.push(shape);
+            }
+        }
+    }
+
+    shapes
+}
+
+/// Renders the transition between two designs as a looping animated SVG
+pub fn morph_to_svg(
+    from: &GeneratedDesign,
+    to: &GeneratedDesign,
+    frames: usize,
+    width: u32,
+    height: u32,
+    options: &svg::SvgOptions,
+) -> Result<String> {
+    let frames = morph_frames(from, to, frames)?;
+    svg::animated_svg(from.grid(), &frames, FRAME_DELAY_MS, width, height, options)
+}
+
+/// Renders the transition between two designs as a looping animated GIF
+///
+/// GIF only supports fully transparent pixels, so anti-aliased edges look
+/// best over an opaque background.
+#[cfg(feature = "png")]
+pub fn morph_to_gif(
+    from: &GeneratedDesign,
+    to: &GeneratedDesign,
+    frames: usize,
+    width: u32,
+    height: u32,
+    options: &svg::SvgOptions,
+) -> Result<Vec<u8>> {
+    let (gif_width, gif_height) = match (u16::try_from(width), u16::try_from(height)) {
+        (Ok(w), Ok(h)) => (w, h),
+        _ => return Err("GIF dimensions must not exceed 65535 pixels".into()),
+    };
+
+    let mut data = Vec::new();
+    {
+        let mut encoder = gif::Encoder::new(&mut data, gif_width, gif_height, &[])?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+
+        for shapes in morph_frames(from, to, frames)? {
+            let svg_data = svg::shapes_to_svg(from.grid(), &shapes, width, height, options)?;
+            let pixmap = png::render_tree_to_pixmap(&png::parse_svg(&svg_data)?, width, height)?;
+
+            let mut rgba: Vec<u8> = pixmap
+                .pixels()
+                .iter()
+                .flat_map(|pixel| {
+                    let color = pixel.demultiply();
+                    [color.red(), color.green(), color.blue(), color.alpha()]
+                })
+                .collect();
+
+            let mut frame = gif::Frame::from_rgba_speed(gif_width, gif_height, &mut rgba, 10);
+            frame.delay = (FRAME_DELAY_MS / 10) as u16;
+            frame.dispose = gif::DisposalMethod::Background;
+            encoder.write_frame(&frame)?;
+        }
+    }
+
+    Ok(data)
+}
+
+/// Saves animation data to a file
+pub fn save_animation<P: AsRef<Path>>(data: &[u8], path: P) -> Result<()> {
+    fs::write(path, data)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::GeneratorConfig;
+
+    #[test]
+    fn test_morph_frames() {
+        let from = GeneratorConfig::new(4, 3, 0.8, Some(1)).generate().unwrap();
+        let to = GeneratorConfig::new(4, 3, 0.8, Some(2)).generate().unwrap();
+
+        let frames = morph_frames(&from, &to, 10).unwrap();
+        assert_eq!(frames.len(), 10);
+
+        // The ends reproduce the fills of each design
+        let fills = |shapes: &[Shape]| {
+            let mut fills = vec![None; from.grid().cell_count()];
+            for shape in shapes {
+                for &cell in &shape.cells {
+                    fills[cell] = Some((shape.fill().to_string(), shape.opacity));
+                }
+            }
+            fills
+        };
+        assert_eq!(fills(&frames[0]), cell_fills(&from));
+        assert_eq!(fills(&frames[9]), cell_fills(&to));
+
+        let other_grid = GeneratorConfig::new(5, 3, 0.8, Some(2)).generate().unwrap();
+        assert!(morph_frames(&from, &other_grid, 10).is_err());
+    }
+
+    #[test]
+    fn test_morph_outputs() {
+        let from = GeneratorConfig::new(3, 2, 0.8, Some(1)).generate().unwrap();
+        let to = GeneratorConfig::new(3, 2, 0.8, Some(2)).generate().unwrap();
+        let options = svg::SvgOptions::default();
+
+        let svg = morph_to_svg(&from, &to, 5, 100, 100, &options).unwrap();
+        assert_eq!(svg.matches("class=\"frame\"").count(), 5);
+        assert!(svg.contains("<animate"));
+
+        #[cfg(feature = "png")]
+        {
+            let gif = morph_to_gif(&from, &to, 5, 64, 64, &options).unwrap();
+            assert_eq!(&gif[0..6], b"GIF89a");
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use crate::generator::color::ColorManager;
+use crate::generator::grid::Point;
+use crate::generator::GeneratedDesign;
+use crate::Result;
+use std::fs;
+use std::path::Path;
+
+/// Ends a run of colored glyphs
+const RESET: &str = "\x1b[0m";
+
+/// Renders a design as text, one ▲ or ▼ per triangle of the grid
+///
+/// Each row of triangles becomes a line and neighboring triangles sit half a
+/// triangle apart, so every character of a line is a cell. The 24 cells of
+/// grid size 2 don't form such rows, so that grid is drawn on the triangles of
+/// grid size 4 instead. Cells no shape covers are drawn hollow (△ ▽). With
+/// `ansi`, filled glyphs take their shape's color through 24-bit ANSI escapes;
+/// without it the text is plain and suits files.
+pub fn design_to_ascii(design: &GeneratedDesign, ansi: bool) -> String {
+    let hex = design.grid().hex_grid();
+    let lattice = match hex.grid_density {
+        2 => 4,
+        density => density as usize,
+    };
+    let half_side = hex.size / lattice as f64 / 2.0;
+    let row_height = hex.size * 3f64.sqrt() / 2.0 / lattice as f64;
+    let top = hex.center.y - hex.size * 3f64.sqrt() / 2.0;
+    let rows = lattice * 2;
+    let columns = rows * 2;
+
+    let mut text = String::new();
+    for row in 0..rows {
+        // Sampling halfway down the row at multiples of half a side lands
+        // inside exactly one triangle per character
+        let y = top + (row as f64 + 0.5) * row_height;
+        let mut line = String::new();
+        let mut current_color: Option<String> = None;
+        for column in 0..=columns {
+            let point = Point::new(hex.center.x + (column as f64 - rows as f64) * half_side, y);
+            if design.cell_at(point).is_none() {
+                line.push(' ');
+                continue;
+            }
+            // The top row starts with an upward triangle at the hexagon's corner,
+            // and each row below starts the other way
+            let up = (column + row + lattice) % 2 == 0;
+            let color = design.shape_at(point).map(|shape| design.shapes()[shape].fill().to_string());
+
+            if ansi && color != current_color {
+                match &color {
+                    Some(color) => {
+                        let (r, g, b, _) = ColorManager::hex_to_rgba(color);
+                        line.push_str(&format!("\x1b[38;2;{};{};{}m", r, g, b));
+                    }
+                    None => line.push_str(RESET),
+                }
+                current_color = color.clone();
+            }
+            line.push(match (color.is_some(), up) {
+                (true, true) => '▲',
+                (true, false) => '▼',
+                (false, true) => '△',
+                (false, false) => '▽',
+            });
+        }
+        text.push_str(line.trim_end());
+        if current_color.is_some() {
+            text.push_str(RESET);
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Saves a text rendering to a file
+pub fn save_ascii<P: AsRef<Path>>(text: &str, path: P) -> Result<()> {
+    fs::write(path, text)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::GeneratorConfig;
+
+    #[test]
+    fn test_every_cell_is_one_character() {
+        for density in 3..=8 {
+            let design = GeneratorConfig::new(density, 3, 0.8, Some(4)).generate().unwrap();
+            let text = design_to_ascii(&design, false);
+            let lines: Vec<&str> = text.lines().collect();
+            assert_eq!(lines.len(), density as usize * 2);
+
+            let glyphs = text.chars().filter(|c| "▲▼△▽".contains(*c)).count();
+            assert_eq!(glyphs, design.grid().cell_count(), "density {}", density);
+            for (row, line) in lines.iter().enumerate() {
+                // Each glyph points the way its cell does
+                for (column, glyph) in line.chars().enumerate().filter(|(_, glyph)| *glyph != ' ') {
+                    let hex = design.grid().hex_grid();
+                    let x = (column as f64 - density as f64 * 2.0) * hex.size / density as f64 / 2.0;
+                    let y = (row as f64 + 0.5 - density as f64) * hex.size * 3f64.sqrt() / 2.0 / density as f64;
+                    let cell = design.cell_at(Point::new(x, y)).unwrap();
+                    assert_eq!(design.grid().cells()[cell].centroid.y > y, "▲△".contains(glyph));
+                }
+            }
+            let mut covered: Vec<usize> = design.shapes().iter().flat_map(|shape| shape.cells.clone()).collect();
+            covered.sort_unstable();
+            covered.dedup();
+            assert_eq!(text.chars().filter(|c| "▲▼".contains(*c)).count(), covered.len());
+
+            // Rows alternate between pointing up and down along their length
+            assert!(lines.iter().all(|line| !line.contains("▲▲") && !line.contains("▼▼")));
+            assert!(!text.contains('\x1b'));
+        }
+
+        // The irregular 24-cell grid is drawn on a finer lattice, still showing every cell
+        let design = GeneratorConfig::new(2, 3, 0.8, Some(4)).generate().unwrap();
+        let text = design_to_ascii(&design, false);
+        assert_eq!(text.lines().count(), 8);
+        let hex = design.grid().hex_grid();
+        let mut drawn: Vec<usize> = (0..8)
+            .flat_map(|row| (0..=16).map(move |column| (row, column)))
+            .filter_map(|(row, column)| {
+                let x = (column as f64 - 8.0) * hex.size / 8.0;
+                let y = (row as f64 - 3.5) * hex.size * 3f64.sqrt() / 8.0;
+                design.cell_at(Point::new(x, y))
+            })
+            .collect();
+        drawn.sort_unstable();
+        drawn.dedup();
+        assert_eq!(drawn, (0..24).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_ansi_colors() {
+        let design = GeneratorConfig::new(4, 3, 0.8, Some(4)).generate().unwrap();
+        let text = design_to_ascii(&design, true);
+        let (r, g, b) = ColorManager::hex_to_rgb(design.shapes()[0].fill());
+        assert!(text.contains(&format!("\x1b[38;2;{};{};{}m", r, g, b)));
+        // No line leaves the terminal colored
+        for line in text.lines().filter(|line| line.contains('\x1b')) {
+            assert!(line.rsplit('\x1b').next().unwrap().starts_with("[0m"));
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use crate::generator::color::{parse_color, ColorManager};
+use crate::generator::{GeneratedDesign, GeneratorConfig};
+use crate::svg;
+use crate::utils;
+use crate::Result;
+use ::svg::node::element::{Definitions, Group, LinearGradient, Mask, Rectangle, Stop, Text, Title};
+use ::svg::node::Text as TextNode;
+use ::svg::Document;
+
+/// Badge height in pixels, as on shields.io
+pub const HEIGHT: u32 = 20;
+
+/// Side of the icon in the label cap
+const ICON_SIZE: f64 = 14.0;
+
+/// Space around the icon
+const ICON_MARGIN: f64 = 3.0;
+
+/// Space on either side of each text
+const PADDING: f64 = 5.0;
+
+/// Background of the label cap
+const LABEL_COLOR: &str = "#555555";
+
+/// Fonts shields.io badges are measured and drawn with
+const FONT_FAMILY: &str = "Verdana,Geneva,DejaVu Sans,sans-serif";
+
+/// A shields.io style badge: a gray label cap with an icon, and a colored message
+#[derive(Debug, Clone)]
+pub struct Badge {
+    pub label: String,
+    pub message: String,
+    /// The message background; taken from the icon's largest shape when not given
+    pub color: Option<String>,
+}
+
+impl Badge {
+    pub fn new(label: &str, message: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            message: message.to_string(),
+            color: None,
+        }
+    }
+
+    /// The icon for this badge: `config`'s design for a seed hashed from the label
+    ///
+    /// Badges with the same label share an icon, whatever their message.
+    pub fn icon(&self, config: &GeneratorConfig) -> Result<GeneratedDesign> {
+        let seed = utils::stable_hash(self.label.as_bytes());
+        config.clone().with_seed(Some(seed)).generate()
+    }
+
+    /// Renders the badge as SVG
+    pub fn to_svg(&self, config: &GeneratorConfig) -> Result<String> {
+        let icon = self.icon(config)?;
+        let color = match &self.color {
+            Some(color) => parse_color(color)?,
+            None => dominant_color(&icon).unwrap_or_else(|| "#44CC11".to_string()),
+        };
+        // White text must stay readable on the message side
+        let color = ColorManager::ensure_contrast(&color, "#FFFFFF", 3.0);
+
+        let label_width = (ICON_MARGIN * 2.0 + ICON_SIZE + text_width(&self.label) + PADDING).round();
+        let message_width = (text_width(&self.message) + PADDING * 2.0).round();
+        let width = label_width + message_width;
+        let height = HEIGHT as f64;
+
+        let gloss = LinearGradient::new()
+            .set("id", "gloss")
+            .set("x2", "0")
+            .set("y2", "100%")
+            .add(Stop::new().set("offset", "0").set("stop-color", "#bbb").set("stop-opacity", ".1"))
+            .add(Stop::new().set("offset", "1").set("stop-opacity", ".1"));
+        let round = Mask::new().set("id", "round").add(
+            Rectangle::new()
+                .set("width", width)
+                .set("height", height)
+                .set("rx", 3)
+                .set("fill", "#fff"),
+        );
+
+        let background = Group::new()
+            .set("mask", "url(#round)")
+            .add(Rectangle::new().set("width", label_width).set("height", height).set("fill", LABEL_COLOR))
+            .add(
+                Rectangle::new()
+                    .set("x", label_width)
+                    .set("width", message_width)
+                    .set("height", height)
+                    .set("fill", color),
+            )
+            .add(Rectangle::new().set("width", width).set("height", height).set("fill", "url(#gloss)"));
+
+        // Fit the filled cells, not the whole hexagon, so small designs stay visible
+        let (min_x, min_y, max_x, max_y) = cell_bounds(&icon).unwrap_or((-100.0, -100.0, 100.0, 100.0));
+        let scale = ICON_SIZE / (max_x - min_x).max(max_y - min_y).max(1.0);
+        let icon_group = svg::design_group(&icon).set(
+            "transform",
+            format!(
+                "translate({} {}) scale({:.4}) translate({:.4} {:.4})",
+                ICON_MARGIN + ICON_SIZE / 2.0,
+                height / 2.0,
+                scale,
+                -(min_x + max_x) / 2.0,
+                -(min_y + max_y) / 2.0
+            ),
+        );
+
+        let label_x = ICON_MARGIN * 2.0 + ICON_SIZE + text_width(&self.label) / 2.0;
+        let message_x = label_width + message_width / 2.0;
+        let text = Group::new()
+            .set("fill", "#fff")
+            .set("text-anchor", "middle")
+            .set("font-family", FONT_FAMILY)
+            .set("font-size", 11)
+            .add(shadowed_text(&self.label, label_x))
+            .add(shadowed_text(&self.message, message_x));
+
+        let title = format!("{}: {}", self.label, self.message);
+        let document = Document::new()
+            .set("width", width)
+            .set("height", height)
+            .set("viewBox", (0.0, 0.0, width, height))
+            .set("role", "img")
+            .set("aria-label", utils::escape_xml(&title))
+            .set(svg::CHECKSUM_ATTRIBUTE, format!("{:016x}", icon.checksum()))
+            .add(Title::new().add(TextNode::new(utils::escape_xml(&title))))
+            .add(Definitions::new().add(gloss).add(round))
+            .add(background)
+            .add(icon_group)
+            .add(text);
+
+        Ok(document.to_string())
+    }
+}
+
+/// Text with the dark offset copy that gives shields.io badges their embossed look
+fn shadowed_text(content: &str, x: f64) -> Group {
+    let content = utils::escape_xml(content);
+    Group::new()
+        .add(
+            Text::new()
+                .set("x", x)
+                .set("y", 15)
+                .set("fill", "#010101")
+                .set("fill-opacity", ".3")
+                .add(TextNode::new(content.clone())),
+        )
+        .add(Text::new().set("x", x).set("y", 14).add(TextNode::new(content)))
+}
+
+/// The color of the shape covering the most cells
+fn dominant_color(design: &GeneratedDesign) -> Option<String> {
+    design
+        .shapes()
+        .iter()
+        .filter(|shape| !shape.cells.is_empty())
+        .max_by_key(|shape| shape.cells.len())
+        .map(|shape| shape.fill().to_string())
+}
+
+/// Bounding box of the cells the design fills, as (min x, min y, max x, max y)
+fn cell_bounds(design: &GeneratedDesign) -> Option<(f64, f64, f64, f64)> {
+    design
+        .shapes()
+        .iter()
+        .flat_map(|shape| shape.cells.iter())
+        .filter_map(|&id| design.grid().get_cell(id))
+        .flat_map(|cell| cell.vertices.iter())
+        .fold(None, |bounds, point| {
+            let (min_x, min_y, max_x, max_y) = bounds.unwrap_or((point.x, point.y, point.x, point.y));
+            Some((min_x.min(point.x), min_y.min(point.y), max_x.max(point.x), max_y.max(point.y)))
+        })
+}
+
+/// Approximate width of `text` in 11px Verdana, the font badges are measured in
+pub fn text_width(text: &str) -> f64 {
+    text.chars()
+        .map(|c| match c {
+            'i' | 'l' | 'j' | '\'' | '.' | ',' | ':' | ';' | '!' | '|' => 3.5,
+            'f' | 't' | 'r' | 'I' | ' ' | '(' | ')' | '[' | ']' | '-' | '/' => 4.5,
+            'm' | 'w' => 9.5,
+            'M' | 'W' | '%' | '@' => 11.0,
+            'A'..='Z' => 7.5,
+            '0'..='9' => 7.0,
+            _ if c.is_ascii() => 6.5,
+            // Wide scripts and emoji take roughly a square
+            _ => 11.0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_badge_svg() {
+        let config = GeneratorConfig::new(2, 3, 1.0, None);
+        let badge = Badge::new("build", "passing");
+        let svg = badge.to_svg(&config).unwrap();
+
+        assert!(svg.contains("aria-label=\"build: passing\""));
+        assert!(svg.contains("<title>\nbuild: passing\n</title>"));
+        assert!(svg.contains("height=\"20\""));
+        assert_eq!(svg, badge.to_svg(&config).unwrap());
+
+        // The icon follows the label, not the message
+        let failing = Badge::new("build", "failing");
+        assert_eq!(
+            badge.icon(&config).unwrap().checksum(),
+            failing.icon(&config).unwrap().checksum()
+        );
+        assert_ne!(
+            badge.icon(&config).unwrap().checksum(),
+            Badge::new("docs", "passing").icon(&config).unwrap().checksum()
+        );
+    }
+
+    #[test]
+    fn test_badge_color_and_escaping() {
+        let config = GeneratorConfig::new(2, 3, 1.0, None);
+        let mut badge = Badge::new("a<b", "x & y");
+        badge.color = Some("#0a7c2f".to_string());
+        let svg = badge.to_svg(&config).unwrap();
+        assert!(svg.contains("#0a7c2f") || svg.contains("#0A7C2F"));
+        assert!(svg.contains("a&lt;b: x &amp; y"));
+
+        badge.color = Some("not-a-color".to_string());
+        assert!(badge.to_svg(&config).is_err());
+    }
+
+    #[test]
+    fn test_text_width() {
+        assert!(text_width("passing") > text_width("ok"));
+        assert!(text_width("WWW") > text_width("iii"));
+        assert_eq!(text_width(""), 0.0);
+    }
+}
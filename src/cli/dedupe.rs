@@ -0,0 +1,113 @@
+use super::Cli;
+use crate::generator::GeneratedDesign;
+use crate::Result;
+use clap::ValueEnum;
+
+/// Fresh seeds tried for a near-duplicate before it is skipped anyway
+const REGENERATE_ATTEMPTS: u64 = 10;
+
+/// What happens to a design too similar to one already in the batch
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, ValueEnum)]
+pub enum DuplicateAction {
+    /// Leave the design out of the batch
+    #[default]
+    Skip,
+    /// Try other seeds until the design is distinct enough
+    Regenerate,
+}
+
+/// How many designs the dedupe pass turned away
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DedupeStats {
+    /// Designs left out of the batch
+    pub skipped: usize,
+    /// Designs replaced by one from another seed
+    pub regenerated: usize,
+}
+
+/// Drops or reseeds designs whose similarity to an earlier design reaches `threshold`
+///
+/// Designs are checked in input order, so the first of a group of look-alikes
+/// is the one kept. Every returned entry has its seed pinned, so rendering it
+/// later reproduces exactly the design that was checked.
+pub fn dedupe(
+    entries: Vec<(usize, Cli)>,
+    threshold: f64,
+    action: DuplicateAction,
+) -> Result<(Vec<(usize, Cli)>, DedupeStats)> {
+    let mut kept: Vec<(usize, Cli)> = Vec::with_capacity(entries.len());
+    let mut designs: Vec<GeneratedDesign> = Vec::with_capacity(entries.len());
+    let mut stats = DedupeStats::default();
+
+    for (number, mut cli) in entries {
+        let base_seed = cli.design.seed()?.unwrap_or_else(rand::random);
+        let attempts = match action {
+            DuplicateAction::Skip => 1,
+            DuplicateAction::Regenerate => 1 + REGENERATE_ATTEMPTS,
+        };
+
+        let config = cli.design.config().map_err(|e| format!("Line {}: {}", number, e))?;
+        let mut accepted = None;
+        for attempt in 0..attempts {
+            // Spread retries far apart so they don't walk into neighboring seeds
+            let seed = base_seed.wrapping_add(attempt.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            let design = config.clone().with_seed(Some(seed)).generate()?;
+            let duplicate = designs.iter().any(|other| design.similarity(other) >= threshold);
+            if !duplicate {
+                accepted = Some((seed, design, attempt > 0));
+                break;
+            }
+        }
+
+        match accepted {
+            Some((seed, design, reseeded)) => {
+                if reseeded {
+                    stats.regenerated += 1;
+                }
+                cli.design.seed = Some(seed);
+                cli.design.uuid = None;
+                designs.push(design);
+                kept.push((number, cli));
+            }
+            None => stats.skipped += 1,
+        }
+    }
+
+    Ok((kept, stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn entry(number: usize, seed: u64) -> (usize, Cli) {
+        let cli = Cli::try_parse_from(["hexlogogen", "--seed", &seed.to_string(), "--grid-size", "2"]).unwrap();
+        (number, cli)
+    }
+
+    #[test]
+    fn test_exact_duplicates_are_skipped() {
+        let entries = vec![entry(1, 3), entry(2, 3), entry(3, 4)];
+        let (kept, stats) = dedupe(entries, 0.99, DuplicateAction::Skip).unwrap();
+        let numbers: Vec<usize> = kept.iter().map(|(number, _)| *number).collect();
+        assert_eq!(numbers, vec![1, 3]);
+        assert_eq!(stats, DedupeStats { skipped: 1, regenerated: 0 });
+    }
+
+    #[test]
+    fn test_duplicates_can_be_regenerated() {
+        let entries = vec![entry(1, 3), entry(2, 3)];
+        let (kept, stats) = dedupe(entries, 0.99, DuplicateAction::Regenerate).unwrap();
+        assert_eq!(kept.len(), 2);
+        assert_eq!(stats.regenerated, 1);
+        assert_ne!(kept[0].1.design.seed, kept[1].1.design.seed);
+    }
+
+    #[test]
+    fn test_random_seeds_are_pinned() {
+        let cli = Cli::try_parse_from(["hexlogogen"]).unwrap();
+        let (kept, _) = dedupe(vec![(1, cli)], 1.0, DuplicateAction::Skip).unwrap();
+        assert!(kept[0].1.design.seed.is_some());
+    }
+}
@@ -0,0 +1,100 @@
+use crate::generator::GeneratedDesign;
+use crate::Result;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Name of the file listing everything stored under a hashed output directory
+pub const INDEX_FILE: &str = "index.ndjson";
+
+/// Serializes index appends from parallel batch workers
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// Where a design is stored under `dir`: `ab/cd/abcd....<extension>`
+///
+/// The name is the design checksum, so identical designs share one file and
+/// two levels of fan-out keep any one directory small.
+pub fn hashed_path(dir: &Path, design: &GeneratedDesign, extension: &str) -> PathBuf {
+    let hash = format!("{:016x}", design.checksum());
+    dir.join(&hash[0..2])
+        .join(&hash[2..4])
+        .join(format!("{}.{}", hash, extension))
+}
+
+/// Reserves `path` for a design, returning whether it wasn't stored yet
+///
+/// The empty file is created under the index lock, so of several workers
+/// rendering the same design only one gets `true` and records it.
+pub fn claim(path: &Path) -> Result<bool> {
+    let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    match OpenOptions::new().write(true).create_new(true).open(path) {
+        Ok(_) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Appends a newly stored file to the directory's index
+///
+/// Call it only for files [`claim`] returned `true` for, so the index stays one line
+/// per file without being read back. Each line of `index.ndjson` is a JSON
+/// object with the hash, the path relative to `dir`, and the parameters that
+/// reproduce the design.
+pub fn record(dir: &Path, path: &Path, design: &GeneratedDesign, format: &str) -> Result<()> {
+    let relative = path.strip_prefix(dir).unwrap_or(path);
+    let relative = relative
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    let config = design.config();
+    let entry = serde_json::json!({
+        "hash": format!("{:016x}", design.checksum()),
+        "path": relative,
+        "format": format,
+        "seed": config.seed(),
+        "theme": config.theme().to_string(),
+        "grid_size": config.grid_size(),
+        "shapes": config.shapes_count(),
+        "opacity": config.opacity(),
+    });
+
+    let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut index = OpenOptions::new().create(true).append(true).open(dir.join(INDEX_FILE))?;
+    writeln!(index, "{}", entry)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::GeneratorConfig;
+
+    #[test]
+    fn test_hashed_layout_and_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let design = GeneratorConfig::new(3, 2, 0.8, Some(77)).generate().unwrap();
+
+        let path = hashed_path(dir.path(), &design, "svg");
+        let hash = format!("{:016x}", design.checksum());
+        assert_eq!(
+            path,
+            dir.path().join(&hash[0..2]).join(&hash[2..4]).join(format!("{}.svg", hash))
+        );
+
+        assert!(claim(&path).unwrap());
+        assert!(!claim(&path).unwrap());
+        record(dir.path(), &path, &design, "svg").unwrap();
+        let index = fs::read_to_string(dir.path().join(INDEX_FILE)).unwrap();
+        assert_eq!(index.lines().count(), 1);
+
+        let entry: serde_json::Value = serde_json::from_str(index.lines().next().unwrap()).unwrap();
+        assert_eq!(entry["hash"], hash);
+        assert_eq!(entry["path"], format!("{}/{}/{}.svg", &hash[0..2], &hash[2..4], hash));
+        assert_eq!(entry["seed"], 77);
+    }
+}
@@ -0,0 +1,251 @@
+use super::spec::DesignSpec;
+use super::{Cli, Format};
+use crate::generator::color::ColorManager;
+use crate::png;
+use crate::svg;
+use crate::Result;
+use base64::Engine;
+use clap::Parser;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+
+/// MCP protocol revision this server speaks
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+// JSON-RPC 2.0 error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Runs the `mcp` subcommand: an MCP server on standard input and output
+///
+/// Messages are newline-delimited JSON-RPC. Standard output carries nothing
+/// else, so diagnostics go to standard error.
+pub fn run() -> Result<()> {
+    eprintln!("hexlogogen MCP server ready on stdio");
+    serve(io::stdin().lock(), io::stdout().lock())
+}
+
+/// Answers each JSON-RPC message read from `input` until it ends
+pub fn serve<R: BufRead, W: Write>(input: R, mut output: W) -> Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => handle(&message),
+            Err(e) => Some(error(Value::Null, PARSE_ERROR, &format!("Parse error: {}", e))),
+        };
+        if let Some(response) = response {
+            writeln!(output, "{}", response)?;
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// The response to one JSON-RPC message; notifications get none
+pub fn handle(message: &Value) -> Option<Value> {
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        return Some(error(
+            message.get("id").cloned().unwrap_or(Value::Null),
+            INVALID_REQUEST,
+            "Invalid request: missing method",
+        ));
+    };
+    let id = message.get("id").cloned()?;
+    let params = message.get("params").cloned().unwrap_or_else(|| json!({}));
+
+    let result = match method {
+        "initialize" => Ok(json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "hexlogogen", "version": env!("CARGO_PKG_VERSION") },
+        })),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tools() })),
+        "tools/call" => call_tool(&params),
+        _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+    };
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => error(id, code, &message),
+    })
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Descriptions and input schemas of the tools offered
+fn tools() -> Value {
+    json!([
+        {
+            "name": "generate_logo",
+            "description": "Generate a hexagonal geometric logo. The same seed and options always give the same logo. Returns SVG markup, or a PNG image.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "seed": { "type": "integer", "minimum": 0, "description": "Seed for a reproducible design; random when omitted" },
+                    "theme": { "type": "string", "description": "Color theme, see list_themes" },
+                    "shapes": { "type": "integer", "minimum": 1, "maximum": 8, "description": "Number of shapes" },
+                    "grid_size": { "type": "integer", "minimum": 2, "maximum": 8, "description": "Grid density" },
+                    "opacity": { "type": "number", "minimum": 0, "maximum": 1 },
+                    "overlap": { "type": "boolean", "description": "Allow shapes to overlap with blended colors" },
+                    "format": { "type": "string", "enum": ["svg", "png"] },
+                    "size": { "type": "integer", "minimum": 16, "maximum": 4096, "description": "Width and height in pixels" }
+                }
+            }
+        },
+        {
+            "name": "list_themes",
+            "description": "List the color themes generate_logo and render_spec accept.",
+            "inputSchema": { "type": "object", "properties": {} }
+        },
+        {
+            "name": "render_spec",
+            "description": "Render a design spec, the JSON accepted by hexlogogen --from-spec, as SVG or PNG. Its output path is ignored.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "spec": { "type": "object", "description": "Design spec, e.g. {\"seed\": 42, \"theme\": \"blues\", \"style\": \"ribbon\"}" }
+                },
+                "required": ["spec"]
+            }
+        }
+    ])
+}
+
+/// Runs a tool; failures of the tool itself are results flagged `isError`
+fn call_tool(params: &Value) -> std::result::Result<Value, (i64, String)> {
+    let name = params.get("name").and_then(Value::as_str).unwrap_or_default();
+    let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+    let content = match name {
+        "generate_logo" => generate_logo(&arguments),
+        "list_themes" => Ok(vec![text(ColorManager::available_themes().join("\n"))]),
+        "render_spec" => match arguments.get("spec") {
+            Some(spec) => serde_json::from_value::<DesignSpec>(spec.clone())
+                .map_err(|e| format!("Invalid design spec: {}", e).into())
+                .and_then(|spec| render(&spec)),
+            None => Err("render_spec needs a spec".into()),
+        },
+        _ => return Err((INVALID_PARAMS, format!("Unknown tool: {}", name))),
+    };
+    Ok(match content {
+        Ok(content) => json!({ "content": content, "isError": false }),
+        Err(e) => json!({ "content": [text(e.to_string())], "isError": true }),
+    })
+}
+
+fn generate_logo(arguments: &Value) -> Result<Vec<Value>> {
+    let number = |field: &str| arguments.get(field).and_then(Value::as_u64);
+    let byte = |field: &str| number(field).map(|n| n.min(u8::MAX as u64) as u8);
+    let size = number("size").map(|n| n.clamp(16, png::MAX_DIMENSION as u64) as u32);
+    let spec = DesignSpec {
+        // The seed is reported back, so a random design can be asked for again
+        seed: Some(number("seed").unwrap_or_else(rand::random)),
+        theme: arguments.get("theme").and_then(Value::as_str).map(str::to_string),
+        shapes: byte("shapes"),
+        grid_size: byte("grid_size"),
+        opacity: arguments.get("opacity").and_then(Value::as_f64).map(|n| n as f32),
+        overlap: arguments.get("overlap").and_then(Value::as_bool),
+        format: arguments.get("format").and_then(Value::as_str).map(str::to_string),
+        width: size,
+        height: size,
+        ..DesignSpec::default()
+    };
+    let mut content = render(&spec)?;
+    content.push(text(format!("Seed: {}", spec.seed.unwrap_or_default())));
+    Ok(content)
+}
+
+/// Renders a spec in memory as MCP content: SVG as text, PNG as an image
+fn render(spec: &DesignSpec) -> Result<Vec<Value>> {
+    let mut cli = Cli::parse_from(["hexlogogen"]);
+    spec.apply(&mut cli, None)?;
+    let design_args = &cli.design;
+    let design = design_args.design()?;
+    let options = design_args.svg_options()?;
+
+    match cli.format {
+        Format::Svg => Ok(vec![text(svg::design_to_svg(
+            &design,
+            design_args.width,
+            design_args.height,
+            &options,
+        )?)]),
+        Format::Png => {
+            let data = png::design_to_png(&design, design_args.width, design_args.height, &options)?;
+            Ok(vec![json!({
+                "type": "image",
+                "data": base64::engine::general_purpose::STANDARD.encode(data),
+                "mimeType": "image/png",
+            })])
+        }
+        format => Err(format!("MCP tools render svg or png, not {}", format).into()),
+    }
+}
+
+fn text(text: impl Into<String>) -> Value {
+    json!({ "type": "text", "text": text.into() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, params: Value) -> Value {
+        handle(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params })).unwrap()
+    }
+
+    #[test]
+    fn test_protocol() {
+        let initialized = request("initialize", json!({ "protocolVersion": PROTOCOL_VERSION }));
+        assert_eq!(initialized["result"]["serverInfo"]["name"], "hexlogogen");
+        assert!(initialized["result"]["capabilities"]["tools"].is_object());
+
+        // Notifications have no id and get no response
+        assert_eq!(handle(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })), None);
+
+        let names: Vec<Value> = request("tools/list", json!({}))["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].clone())
+            .collect();
+        assert_eq!(names, vec!["generate_logo", "list_themes", "render_spec"]);
+
+        assert_eq!(request("resources/list", json!({}))["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(request("tools/call", json!({ "name": "draw" }))["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_tools() {
+        let call = |name: &str, arguments: Value| request("tools/call", json!({ "name": name, "arguments": arguments }))["result"].clone();
+
+        let logo = call("generate_logo", json!({ "seed": 42, "theme": "blues", "shapes": 3 }));
+        assert_eq!(logo["isError"], false);
+        assert!(logo["content"][0]["text"].as_str().unwrap().starts_with("<svg"));
+        assert_eq!(logo["content"][1]["text"], "Seed: 42");
+        // The same seed and options give the same logo
+        assert_eq!(call("generate_logo", json!({ "seed": 42, "theme": "blues", "shapes": 3 })), logo);
+
+        let png = call("generate_logo", json!({ "seed": 42, "format": "png", "size": 64 }));
+        assert_eq!(png["content"][0]["mimeType"], "image/png");
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(png["content"][0]["data"].as_str().unwrap())
+            .unwrap();
+        assert!(data.starts_with(b"\x89PNG"));
+
+        let themes = call("list_themes", json!({}));
+        assert!(themes["content"][0]["text"].as_str().unwrap().contains("mesos"));
+
+        let spec = call("render_spec", json!({ "spec": { "seed": 7, "style": "ribbon" } }));
+        assert_eq!(spec["isError"], false);
+        let bad = call("render_spec", json!({ "spec": { "colour": "red" } }));
+        assert_eq!(bad["isError"], true);
+        assert!(bad["content"][0]["text"].as_str().unwrap().contains("Invalid design spec"));
+    }
+}
@@ -1,10 +1,42 @@
-use crate::generator::Generator;
+use crate::animation;
+use crate::ascii;
+use crate::badge;
+use crate::code::DesignCode;
+use crate::generator::grid::TriangularGrid;
+use crate::generator::{
+    color, Aesthetic, AlgoVersion, Anchor, BlendSpace, Effort, GeneratedDesign, GeneratorConfig, Lod, OverlapOpacity,
+    Params,
+    ShapeStyle, Symmetry, SymmetryColors, Theme,
+};
+use crate::icon;
 use crate::png;
+use crate::print;
+use crate::report;
 use crate::svg;
+use crate::template;
+use crate::trace;
 use crate::utils;
+use crate::wallpaper;
+use crate::wordmark;
 use crate::Result;
-use clap::{Parser, ValueEnum};
-use std::path::PathBuf;
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use std::fs::File;
+use std::io::{BufWriter, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+mod dedupe;
+mod hashed;
+mod mcp;
+mod pool;
+mod presets;
+mod registry;
+mod rerender;
+mod scan;
+mod spec;
+mod verify;
+
+pub use spec::DesignSpec;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -12,12 +44,111 @@ use std::path::PathBuf;
     version,
     about = "Modern geometric logo generator in Rust - creates unique hexagonal designs with minimal configuration",
     long_about = None,
+    args_conflicts_with_subcommands = true,
 )]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Output file path
     #[arg(default_value = "logo.svg")]
     pub output: String,
 
+    #[command(flatten)]
+    pub design: DesignArgs,
+
+    /// Start from a curated set of options (avatar, app-icon, wallpaper, sticker, emoji)
+    #[arg(long)]
+    pub preset: Option<String>,
+
+    /// Shorthand for --preset emoji: a 128x128 PNG tuned for Slack and Discord custom emoji
+    #[arg(long, conflicts_with = "preset")]
+    pub emoji: bool,
+
+    /// Read the design from a JSON spec file, or `-` for standard input; flags given here win
+    #[arg(long, value_name = "FILE")]
+    pub from_spec: Option<PathBuf>,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = Format::Svg)]
+    pub format: Format,
+
+    /// Center the logo on a page for print proofs (A4, A3); use with svg or pdf
+    #[arg(long, value_name = "PAGE")]
+    pub print_layout: Option<String>,
+
+    /// Add crop and registration marks to the print layout
+    #[arg(long)]
+    pub crop_marks: bool,
+
+    /// Add a strip of labelled color swatches to the print layout
+    #[arg(long)]
+    pub swatches: bool,
+
+    /// Set this text next to the logo; an icon-only copy is written alongside
+    #[arg(long, value_name = "TEXT", requires = "font")]
+    pub wordmark: Option<String>,
+
+    /// TrueType or OpenType font for the wordmark
+    #[arg(long, value_name = "FILE")]
+    pub font: Option<PathBuf>,
+
+    /// Where the wordmark goes relative to the logo (right, below)
+    #[arg(long, default_value = "right")]
+    pub lockup: String,
+
+    /// Also write a report of the colors used (.json or .html)
+    #[arg(long, value_name = "FILE")]
+    pub palette_report: Option<PathBuf>,
+
+    /// Store the output under DIR/ab/cd/<hash>.<ext>, named by the design checksum, and list it in DIR/index.ndjson
+    #[arg(long, value_name = "DIR")]
+    pub out_dir_by_hash: Option<PathBuf>,
+
+    /// Upload the output to object storage at s3://bucket/prefix (needs the `cloud` feature)
+    #[arg(long, value_name = "URL")]
+    pub upload: Option<String>,
+
+    /// POST a JSON notification to this URL once the output is written (needs the `webhook` feature)
+    #[arg(long, value_name = "URL")]
+    pub webhook: Option<String>,
+
+    /// Print the design's perceptual hash and the output path, like sha256sum
+    #[arg(long)]
+    pub print_hash: bool,
+
+    /// Print the design code, a short string that --code turns back into this design
+    #[arg(long)]
+    pub print_code: bool,
+
+    /// Also export the colors used as a palette (ase, gpl, tailwind, css) next to the output
+    #[arg(long, value_name = "FORMAT")]
+    pub export_palette: Option<String>,
+
+    /// Write a full PWA manifest icon set (with maskable variants) to this directory
+    #[arg(long, value_name = "DIR")]
+    pub pwa_icons: Option<PathBuf>,
+
+    /// Write one SVG per shape plus composite.svg to this directory, all on the same viewBox
+    #[arg(long, value_name = "DIR", conflicts_with = "pwa_icons")]
+    pub split_layers: Option<PathBuf>,
+
+    /// Frame the logo in an SVG template at its {{logo}} placeholder (svg and png output)
+    #[arg(long, value_name = "FILE")]
+    pub template: Option<PathBuf>,
+
+    /// Set a template variable, overriding seed, title, date, theme or hash (repeatable)
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_template_var, requires = "template")]
+    pub template_vars: Vec<(String, String)>,
+
+    /// Enable verbose output
+    #[arg(short, long)]
+    pub verbose: bool,
+}
+
+/// Parameters that determine a design and its rendered size
+#[derive(Args, Debug)]
+pub struct DesignArgs {
     /// Seed for deterministic generation
     #[arg(short, long)]
     pub seed: Option<u64>,
@@ -26,10 +157,26 @@ pub struct Cli {
     #[arg(short, long)]
     pub uuid: Option<String>,
 
-    /// Color theme (mesos, google, blues, greens, reds, purples, rainbow)
+    /// Design code such as HX1-58208m010m, setting the seed, theme, shapes, grid size, opacity, overlap and algorithm version
+    #[arg(long, value_name = "CODE", conflicts_with_all = ["seed", "uuid", "theme", "shapes", "grid_size", "opacity", "algo_version"])]
+    pub code: Option<String>,
+
+    /// Color theme (mesos, google, blues, greens, reds, purples, rainbow), or a blend such as mesos:blues:0.4
     #[arg(short = 't', long = "theme", default_value = "mesos")]
     pub theme: String,
 
+    /// Use the colors of a GIMP (.gpl), Adobe Swatch Exchange (.ase) or JSON palette instead of the theme
+    #[arg(long, value_name = "FILE", conflicts_with = "palette_url")]
+    pub palette_file: Option<PathBuf>,
+
+    /// Use the colors of a coolors.co palette URL instead of the theme
+    #[arg(long, value_name = "URL")]
+    pub palette_url: Option<String>,
+
+    /// Color space for overlaps and theme blends (oklab, rgb)
+    #[arg(long, default_value = "oklab")]
+    pub blend_space: String,
+
     /// Number of shapes to generate
     #[arg(short = 'n', long, default_value_t = 4)]
     pub shapes: u8,
@@ -42,6 +189,102 @@ pub struct Cli {
     #[arg(short, long, default_value_t = 0.8)]
     pub opacity: f32,
 
+    /// Shape style (balanced, ribbon, organic-ca)
+    #[arg(long, default_value = "balanced")]
+    pub style: String,
+
+    /// Where the first shape starts growing (center, corner, edge, random)
+    #[arg(long, default_value = "center")]
+    pub anchor: String,
+
+    /// Scoring profile shapes are grown and picked by (balanced, compact, spiky, sprawling)
+    #[arg(long, default_value = "balanced")]
+    pub aesthetic: String,
+
+    /// Search effort: candidates grown per shape (low, medium, high, or a count up to 32)
+    #[arg(long, default_value = "medium")]
+    pub effort: String,
+
+    /// Repeat shapes grown in one wedge around the center (none, 2, 3, 6)
+    #[arg(long, default_value = "none")]
+    pub symmetry: String,
+
+    /// Whether rotated copies of a symmetric shape share its color or turn through the palette (same, rotate)
+    #[arg(long, value_name = "MODE", default_value = "same")]
+    pub symmetry_colors: String,
+
+    /// Version of the generation algorithm (1, latest); pin one to keep a seed's design across upgrades
+    #[arg(long, value_name = "VERSION", default_value = "latest")]
+    pub algo_version: String,
+
+    /// Grow shapes toward the dark areas of this SVG or PNG silhouette
+    #[arg(long, value_name = "IMAGE")]
+    pub guide: Option<PathBuf>,
+
+    /// Split every grid cell among the shapes so they tile the whole hexagon
+    #[arg(long)]
+    pub pack: bool,
+
+    /// Cover the grid with dozens of one- to three-cell shapes, no two touching ones alike
+    #[arg(long)]
+    pub mosaic: bool,
+
+    /// Give the largest shape the theme's primary color, the next its secondary, and so on
+    #[arg(long)]
+    pub color_by_size: bool,
+
+    /// Never give two shapes the same color, adding variants of palette colors if it runs out
+    #[arg(long)]
+    pub unique_colors: bool,
+
+    /// Background color as hex, a CSS name, rgb() or hsl() [default: transparent]
+    #[arg(short, long, value_name = "COLOR")]
+    pub background: Option<String>,
+
+    /// Fraction of the canvas left empty on each side (0.0-0.45)
+    #[arg(long, default_value_t = 0.0)]
+    pub padding: f64,
+
+    /// Draw dark leading lines between adjacent shapes, like stained glass
+    #[arg(long)]
+    pub stained_glass: bool,
+
+    /// Title each shape with its color's name and hex and its cell count, for design tools and screen readers
+    #[arg(long)]
+    pub editable: bool,
+
+    /// Outline each shape in a darker shade, this many pixels wide (0 for none)
+    #[arg(long, value_name = "PIXELS", default_value_t = 0.0)]
+    pub outline: f64,
+
+    /// Drop shapes covering fewer cells than this, keeping at least the largest
+    #[arg(long, value_name = "CELLS", default_value_t = 0)]
+    pub min_shape_cells: usize,
+
+    /// Simplify exported outlines, letting them stray this far from the cells (the hexagon has radius 100)
+    #[arg(long, value_name = "UNITS", default_value_t = 0.0)]
+    pub simplify: f64,
+
+    /// Round shape corners off with fillets of this radius (the hexagon has radius 100)
+    #[arg(long, value_name = "UNITS", default_value_t = 0.0)]
+    pub corner_radius: f64,
+
+    /// Smooth shape outlines into soft curves
+    #[arg(long)]
+    pub smooth_boundary: bool,
+
+    /// Draw a hexagonal border ring, e.g. 4 or 3,#333333,6 (units of a radius-100 hexagon; color defaults to the largest shape's)
+    #[arg(long, value_name = "WIDTH[,COLOR[,INSET]]")]
+    pub border: Option<String>,
+
+    /// Merge tiny shapes and smooth outlines when rendering small (auto, off, aggressive)
+    #[arg(long, default_value = "auto")]
+    pub lod: String,
+
+    /// Overlay cell ids, shape outlines, centroids and the grid for debugging
+    #[arg(long)]
+    pub debug_overlay: bool,
+
     /// Output width in pixels (PNG only)
     #[arg(short, long, default_value_t = 512)]
     pub width: u32,
@@ -50,23 +293,360 @@ pub struct Cli {
     #[arg(short = 'H', long, default_value_t = 512)]
     pub height: u32,
 
-    /// Output format
-    #[arg(short, long, value_enum, default_value_t = Format::Svg)]
-    pub format: Format,
-
     /// Allow shapes to overlap with blended colors
     #[arg(long, default_value_t = true)]
     pub overlap: bool,
 
-    /// Enable verbose output
+    /// Fraction of the second shape that should overlap the first (0.0-1.0)
+    #[arg(long, value_name = "RATIO")]
+    pub overlap_ratio: Option<f32>,
+
+    /// Opacity of the region where shapes overlap (inherit, full, multiply, or 0.0-1.0)
+    #[arg(long, value_name = "OPACITY", default_value = "inherit")]
+    pub overlap_opacity: String,
+}
+
+impl DesignArgs {
+    /// The design code given with `--code`, if any
+    pub fn code(&self) -> Result<Option<DesignCode>> {
+        Ok(self.code.as_deref().map(str::parse::<DesignCode>).transpose()?)
+    }
+
+    /// Resolves the seed, preferring a design code and then a UUID when one is given
+    pub fn seed(&self) -> Result<Option<u64>> {
+        if let Some(code) = self.code()? {
+            return Ok(Some(code.seed));
+        }
+        match &self.uuid {
+            Some(uuid) => Ok(Some(utils::uuid_to_seed(uuid)?)),
+            None => Ok(self.seed),
+        }
+    }
+
+    /// The theme to pick colors from, preferring a design code and then an imported palette
+    pub fn theme(&self) -> Result<Theme> {
+        if let Some(code) = self.code()? {
+            return Ok(code.theme.parse::<Theme>()?);
+        }
+        if let Some(path) = &self.palette_file {
+            return Ok(utils::palette_from_file(path)?.into_theme());
+        }
+        if let Some(url) = &self.palette_url {
+            return Ok(color::import::from_coolors_url(url)?.into_theme());
+        }
+        Ok(self.theme.parse::<Theme>()?)
+    }
+
+    /// Rendering options for SVG and raster output
+    pub fn svg_options(&self) -> Result<svg::SvgOptions> {
+        let background = match &self.background {
+            Some(color) => Some(color::parse_color(color)?),
+            None => None,
+        };
+
+        Ok(svg::SvgOptions {
+            padding: self.padding,
+            background,
+            debug_overlay: self.debug_overlay,
+            stained_glass: self.stained_glass,
+            editable: self.editable,
+            outline: self.outline,
+            simplify_tolerance: self.simplify,
+            corner_radius: self.corner_radius,
+            smooth_boundary: self.smooth_boundary,
+            border: self.border.as_deref().map(str::parse::<svg::Border>).transpose()?,
+            ..svg::SvgOptions::default()
+        })
+    }
+
+    /// Builds the generator configuration these arguments describe
+    ///
+    /// Out-of-range numbers are clamped to the supported limits, with a warning
+    /// so the substitution isn't silent.
+    pub fn config(&self) -> Result<GeneratorConfig> {
+        // A design code replaces the values it carries
+        let (grid_size, shapes, opacity, overlap, algo_version) = match self.code()? {
+            Some(code) => (code.grid_size, code.shapes, code.opacity, code.overlap, code.algo_version),
+            None => (
+                self.grid_size,
+                self.shapes,
+                self.opacity,
+                self.overlap,
+                self.algo_version.parse::<AlgoVersion>()?,
+            ),
+        };
+        for (field, message) in Params::new(grid_size, shapes, opacity).problems() {
+            eprintln!("Warning: {} {}; using the nearest supported value", field, message);
+        }
+
+        if let Some(ratio) = self.overlap_ratio {
+            if !(0.0..=1.0).contains(&ratio) {
+                return Err(format!("--overlap-ratio must be between 0.0 and 1.0, got {}", ratio).into());
+            }
+        }
+        let guide = match &self.guide {
+            Some(path) => Some(trace::Raster::load(path)?.guide_mask()),
+            None => None,
+        };
+
+        Ok(
+            GeneratorConfig::new(grid_size, shapes, opacity, self.seed()?)
+                .with_theme(self.theme()?)
+                .with_blend_space(self.blend_space.parse::<BlendSpace>()?)
+                .with_allow_overlap(overlap || self.overlap_ratio.is_some())
+                .with_overlap_ratio(self.overlap_ratio)
+                .with_overlap_opacity(self.overlap_opacity.parse::<OverlapOpacity>()?)
+                .with_style(self.style.parse::<ShapeStyle>()?)
+                .with_anchor(self.anchor.parse::<Anchor>()?)
+                .with_aesthetic(self.aesthetic.parse::<Aesthetic>()?)
+                .with_effort(self.effort.parse::<Effort>()?)
+                .with_symmetry(self.symmetry.parse::<Symmetry>()?)
+                .with_symmetry_colors(self.symmetry_colors.parse::<SymmetryColors>()?)
+                .with_pack(self.pack)
+                .with_mosaic(self.mosaic)
+                .with_color_by_size(self.color_by_size)
+                .with_unique_colors(self.unique_colors)
+                .with_guide(guide)
+                .with_algo_version(algo_version),
+        )
+    }
+
+    /// Generates the design and simplifies it for the output size
+    ///
+    /// Shapes under `--min-shape-cells` are dropped, then `--lod` adapts what's
+    /// left to the width, height and padding it will be rendered at.
+    pub fn design(&self) -> Result<GeneratedDesign> {
+        let lod = self.lod.parse::<Lod>()?;
+        let design = self.config()?.generate()?;
+        let design = match self.min_shape_cells {
+            0 => design,
+            min_cells => design.without_small_shapes(min_cells),
+        };
+        Ok(lod.apply(&design, self.width, self.height, self.padding))
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Regenerate a design and check that an existing SVG or PNG file matches it
+    Verify(VerifyArgs),
+    /// Render the empty triangular grid with every cell labelled by its id
+    Grid(GridArgs),
+    /// Animate the transition between the designs of two seeds
+    Morph(MorphArgs),
+    /// List the curated option presets usable with --preset
+    Presets,
+    /// Scatter a family of related designs over a large wallpaper canvas
+    Wallpaper(WallpaperArgs),
+    /// Generate one file per line of newline-delimited JSON design specs
+    Batch(BatchArgs),
+    /// Render every saved design spec and code in a directory again, at a new size or format
+    Rerender(rerender::RerenderArgs),
+    /// Make a shields.io style SVG badge with an icon derived from the label
+    Badge(BadgeArgs),
+    /// Approximate an existing SVG or PNG mark with cells of the triangular grid
+    Trace(TraceArgs),
+    /// Keep a TOML registry of service logo parameters and render it
+    Registry(registry::RegistryArgs),
+    /// Generate a logo for every service in a directory or docker-compose/k8s manifest
+    Scan(scan::ScanArgs),
+    /// Serve generation as MCP tools over stdio for AI assistants and agents
+    Mcp,
+}
+
+#[derive(Args, Debug)]
+pub struct TraceArgs {
+    /// SVG or PNG image to trace
+    pub input: PathBuf,
+
+    /// Output file path; a .png extension writes a PNG, anything else an SVG
+    #[arg(short, long, default_value = "trace.svg")]
+    pub output: PathBuf,
+
+    /// Grid density (2-8)
+    #[arg(short, long, default_value_t = 6)]
+    pub grid_size: u8,
+
+    /// Most colors, and so shapes, kept from the image
+    #[arg(short, long, default_value_t = 4)]
+    pub colors: usize,
+
+    /// Output width in pixels
+    #[arg(short, long, default_value_t = 512)]
+    pub width: u32,
+
+    /// Output height in pixels
+    #[arg(short = 'H', long, default_value_t = 512)]
+    pub height: u32,
+}
+
+#[derive(Args, Debug)]
+pub struct BadgeArgs {
+    /// Output file path
+    #[arg(default_value = "badge.svg")]
+    pub output: PathBuf,
+
+    /// Text on the gray left side, which also picks the icon
+    #[arg(long)]
+    pub label: String,
+
+    /// Text on the colored right side
+    #[arg(long)]
+    pub message: String,
+
+    /// Color of the message side [default: the icon's main color]
+    #[arg(long)]
+    pub color: Option<String>,
+
+    /// Color theme for the icon
+    #[arg(short, long, default_value = "mesos")]
+    pub theme: String,
+
+    /// Number of shapes in the icon
+    #[arg(short = 'n', long, default_value_t = 2)]
+    pub shapes: u8,
+}
+
+#[derive(Args, Debug)]
+pub struct BatchArgs {
+    /// NDJSON file with one design spec per line
+    #[arg(required_unless_present = "stdin")]
+    pub input: Option<PathBuf>,
+
+    /// Read the specs from standard input instead of a file
+    #[arg(long, conflicts_with = "input")]
+    pub stdin: bool,
+
+    /// Directory for outputs given as relative paths
+    #[arg(long, value_name = "DIR")]
+    pub out_dir: Option<PathBuf>,
+
+    /// Store every output under DIR/ab/cd/<hash>.<ext> with an index.ndjson, ignoring spec output paths
+    #[arg(long, value_name = "DIR", conflicts_with = "out_dir")]
+    pub out_dir_by_hash: Option<PathBuf>,
+
+    /// Upload every output to object storage at s3://bucket/prefix (needs the `cloud` feature)
+    #[arg(long, value_name = "URL")]
+    pub upload: Option<String>,
+
+    /// POST a JSON notification to this URL for every design written (needs the `webhook` feature)
+    #[arg(long, value_name = "URL")]
+    pub webhook: Option<String>,
+
+    /// Number of designs rendered in parallel [default: one per CPU core]
+    #[arg(short, long, value_name = "N")]
+    pub jobs: Option<usize>,
+
+    /// Reject designs at least this similar (0.0-1.0) to an earlier one in the batch
+    #[arg(long, value_name = "THRESHOLD")]
+    pub dedupe_threshold: Option<f64>,
+
+    /// What to do with a rejected near-duplicate
+    #[arg(long, value_enum, default_value_t = dedupe::DuplicateAction::Skip, requires = "dedupe_threshold")]
+    pub on_duplicate: dedupe::DuplicateAction,
+
+    /// Print each file written, in input order, and throughput statistics
     #[arg(short, long)]
     pub verbose: bool,
 }
 
+#[derive(Args, Debug)]
+pub struct WallpaperArgs {
+    /// Output file path
+    #[arg(default_value = "wallpaper.png")]
+    pub output: PathBuf,
+
+    /// Canvas size as WIDTHxHEIGHT
+    #[arg(long, default_value = "3840x2160")]
+    pub resolution: String,
+
+    /// Number of designs to scatter over the canvas
+    #[arg(long, default_value_t = 7)]
+    pub count: usize,
+
+    /// Wallpaper format
+    #[arg(short, long, value_enum, default_value_t = WallpaperFormat::Png)]
+    pub format: WallpaperFormat,
+
+    #[command(flatten)]
+    pub design: DesignArgs,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum WallpaperFormat {
+    Svg,
+    Png,
+}
+
+#[derive(Args, Debug)]
+pub struct MorphArgs {
+    /// Seed of the starting design
+    pub from: u64,
+
+    /// Seed of the final design
+    pub to: u64,
+
+    /// Output file path
+    #[arg(default_value = "morph.svg")]
+    pub output: PathBuf,
+
+    /// Number of frames in the transition
+    #[arg(long, default_value_t = 30)]
+    pub frames: usize,
+
+    /// Animation format
+    #[arg(short, long, value_enum, default_value_t = MorphFormat::Svg)]
+    pub format: MorphFormat,
+
+    #[command(flatten)]
+    pub design: DesignArgs,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum MorphFormat {
+    Svg,
+    Gif,
+}
+
+#[derive(Args, Debug)]
+pub struct GridArgs {
+    /// Grid density (2-8)
+    #[arg(short, long, default_value_t = 4)]
+    pub grid_size: u8,
+
+    /// Output SVG file path
+    #[arg(short, long, default_value = "grid.svg")]
+    pub output: PathBuf,
+
+    /// Background color as hex, a CSS name, rgb() or hsl() [default: transparent]
+    #[arg(short, long, value_name = "COLOR")]
+    pub background: Option<String>,
+
+    /// Output width in pixels
+    #[arg(short, long, default_value_t = 512)]
+    pub width: u32,
+
+    /// Output height in pixels
+    #[arg(short = 'H', long, default_value_t = 512)]
+    pub height: u32,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    /// SVG or PNG file to check
+    pub file: PathBuf,
+
+    #[command(flatten)]
+    pub design: DesignArgs,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
 pub enum Format {
     Svg,
     Png,
+    Icns,
+    Pdf,
+    Ascii,
 }
 
 impl Format {
@@ -74,6 +654,9 @@ impl Format {
         match self {
             Format::Svg => "svg",
             Format::Png => "png",
+            Format::Icns => "icns",
+            Format::Pdf => "pdf",
+            Format::Ascii => "txt",
         }
     }
 }
@@ -83,27 +666,226 @@ impl std::fmt::Display for Format {
         match self {
             Format::Svg => write!(f, "svg"),
             Format::Png => write!(f, "png"),
+            Format::Icns => write!(f, "icns"),
+            Format::Pdf => write!(f, "pdf"),
+            Format::Ascii => write!(f, "ascii"),
         }
     }
 }
 
 pub fn run() -> Result<()> {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches)?;
+
+    match &cli.command {
+        Some(Command::Verify(args)) => return verify::run(args),
+        Some(Command::Grid(args)) => return run_grid(args),
+        Some(Command::Morph(args)) => return run_morph(args),
+        Some(Command::Presets) => return presets::run(),
+        Some(Command::Wallpaper(args)) => return run_wallpaper(args),
+        Some(Command::Batch(args)) => return spec::run_batch(args),
+        Some(Command::Rerender(args)) => return rerender::run(args),
+        Some(Command::Badge(args)) => return run_badge(args),
+        Some(Command::Trace(args)) => return run_trace(args),
+        Some(Command::Registry(args)) => return registry::run(args),
+        Some(Command::Scan(args)) => return scan::run(args),
+        Some(Command::Mcp) => return mcp::run(),
+        None => {}
+    }
+
+    if cli.emoji {
+        cli.preset = Some("emoji".to_string());
+    }
+    if let Some(name) = &cli.preset {
+        let preset = presets::find(name)?;
+        preset.apply(&mut cli, &matches);
+    }
+
+    // A spec overrides the preset but not the flags given alongside it
+    if let Some(source) = &cli.from_spec {
+        let spec = spec::DesignSpec::read(source)?;
+        spec.apply(&mut cli, Some(&matches))?;
+    }
+
+    // A printed code has to name the seed, so a random design gets one picked up front
+    if cli.print_code && cli.design.seed()?.is_none() {
+        cli.design.seed = Some(rand::random());
+    }
+
+    generate_logo(&cli)?;
+    Ok(())
+}
+
+/// Uploads a written file to the `--upload` target under `name`, returning its URL
+#[cfg(feature = "cloud")]
+fn upload_file(url: &str, path: &Path, name: &str, immutable: bool) -> Result<String> {
+    use crate::upload::{S3Uploader, CACHE_DEFAULT, CACHE_IMMUTABLE};
+
+    let uploader = S3Uploader::from_env(url.parse()?)?;
+    uploader.put_file(path, name, if immutable { CACHE_IMMUTABLE } else { CACHE_DEFAULT })
+}
+
+#[cfg(not(feature = "cloud"))]
+fn upload_file(_url: &str, _path: &Path, _name: &str, _immutable: bool) -> Result<String> {
+    Err("--upload needs hexlogogen built with the `cloud` feature".into())
+}
 
-    // Process seed/UUID
-    let seed = match &cli.uuid {
-        Some(uuid) => Some(utils::uuid_to_seed(uuid)?),
-        None => cli.seed,
+/// Splits a `--var KEY=VALUE` argument
+fn parse_template_var(arg: &str) -> std::result::Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => Ok((key.trim().to_string(), value.to_string())),
+        _ => Err(format!("Invalid template variable '{}' (expected KEY=VALUE)", arg)),
+    }
+}
+
+/// Tells the `--webhook` endpoint about a written design
+#[cfg(feature = "webhook")]
+fn notify_webhook(
+    url: &str,
+    cli: &Cli,
+    design: &GeneratedDesign,
+    outputs: &[String],
+    urls: &[String],
+    started: Instant,
+) -> Result<()> {
+    let args = &cli.design;
+    let params = serde_json::json!({
+        "theme": design.config().theme().to_string(),
+        "grid_size": design.config().grid_size(),
+        "shapes": design.config().shapes_count(),
+        "opacity": design.config().opacity(),
+        "overlap": design.config().allow_overlap(),
+        "style": args.style,
+        "format": cli.format.to_string(),
+        "width": args.width,
+        "height": args.height,
+    });
+    let mut payload = crate::webhook::payload(design, params, outputs, started.elapsed());
+    payload["urls"] = serde_json::json!(urls);
+    crate::webhook::notify(url, &payload)
+}
+
+#[cfg(not(feature = "webhook"))]
+fn notify_webhook(
+    _url: &str,
+    _cli: &Cli,
+    _design: &GeneratedDesign,
+    _outputs: &[String],
+    _urls: &[String],
+    _started: Instant,
+) -> Result<()> {
+    Err("--webhook needs hexlogogen built with the `webhook` feature".into())
+}
+
+/// Generates and writes the logo the options describe, returning the output path
+fn generate_logo(cli: &Cli) -> Result<PathBuf> {
+    let started = Instant::now();
+    let design_args = &cli.design;
+    let seed = design_args.seed()?;
+
+    // PDF output is always a print layout, A4 unless told otherwise
+    let print_options = match (&cli.print_layout, cli.format) {
+        (None, Format::Pdf) => Some(print::PageSize::A4),
+        (None, _) => None,
+        (Some(page), Format::Svg | Format::Pdf) => Some(page.parse::<print::PageSize>()?),
+        (Some(_), format) => {
+            return Err(format!("--print-layout supports svg and pdf output, not {}", format).into())
+        }
+    }
+    .map(|page| print::PrintOptions {
+        page,
+        crop_marks: cli.crop_marks,
+        swatches: cli.swatches,
+    });
+
+    let wordmark = match (&cli.wordmark, &cli.font) {
+        (Some(text), Some(font)) => {
+            if !matches!(cli.format, Format::Svg | Format::Png) || print_options.is_some() {
+                return Err("--wordmark supports plain svg and png output".into());
+            }
+            Some((
+                text.as_str(),
+                wordmark::Font::from_file(font)?,
+                cli.lockup.parse::<wordmark::Lockup>()?,
+            ))
+        }
+        _ => None,
+    };
+
+    let export_format = cli
+        .export_palette
+        .as_deref()
+        .map(str::parse::<color::export::PaletteFormat>)
+        .transpose()?;
+
+    if cli.upload.is_some() {
+        if !cfg!(feature = "cloud") {
+            return Err("--upload needs hexlogogen built with the `cloud` feature".into());
+        }
+        if cli.pwa_icons.is_some() {
+            return Err("--upload supports single-file outputs, not --pwa-icons".into());
+        }
+        if cli.split_layers.is_some() {
+            return Err("--upload supports single-file outputs, not --split-layers".into());
+        }
+    }
+
+    let template = match &cli.template {
+        Some(path) => {
+            if !matches!(cli.format, Format::Svg | Format::Png) || print_options.is_some() || wordmark.is_some() {
+                return Err("--template supports plain svg and png output".into());
+            }
+            Some(template::Template::from_file(path)?)
+        }
+        None => None,
     };
 
-    // Set up the generator
-    let mut generator = Generator::new(cli.grid_size, cli.shapes, cli.opacity, seed);
-    generator
-        .set_color_scheme(&cli.theme)
-        .set_allow_overlap(cli.overlap);
+    if cli.webhook.is_some() && !cfg!(feature = "webhook") {
+        return Err("--webhook needs hexlogogen built with the `webhook` feature".into());
+    }
+
+    // Generate the logo, simplified for the size it is rendered at
+    let options = design_args.svg_options()?;
+    let design = design_args.design()?;
 
-    // Generate the logo
-    generator.generate()?;
+    if let Some(path) = &cli.palette_report {
+        report::save_palette_report(&design, path)?;
+        if cli.verbose {
+            println!("Wrote palette report to {}", path.display());
+        }
+    }
+
+    // A PWA icon set replaces the single output file
+    if let Some(dir) = &cli.pwa_icons {
+        let icons = icon::save_pwa_icons(&design, dir)?;
+        if cli.verbose {
+            println!(
+                "Wrote {} PWA icons and manifest.json to {}",
+                icons.len(),
+                dir.display()
+            );
+        }
+        if let Some(url) = &cli.webhook {
+            notify_webhook(url, cli, &design, &[dir.display().to_string()], &[], started)?;
+        }
+        return Ok(dir.clone());
+    }
+
+    // So does a set of layer files
+    if let Some(dir) = &cli.split_layers {
+        let paths = svg::save_split_layers(&design, dir, design_args.width, design_args.height, &options)?;
+        if cli.verbose {
+            println!(
+                "Wrote {} shape layers and composite.svg to {}",
+                paths.len() - 1,
+                dir.display()
+            );
+        }
+        if let Some(url) = &cli.webhook {
+            notify_webhook(url, cli, &design, &[dir.display().to_string()], &[], started)?;
+        }
+        return Ok(dir.clone());
+    }
 
     // Make sure the output path has the correct extension
     let mut output_path = PathBuf::from(&cli.output);
@@ -122,22 +904,150 @@ pub fn run() -> Result<()> {
         output_path.set_extension(cli.format.extension());
     }
 
+    // Content-addressed storage replaces the given path; identical designs share a file
+    let mut newly_stored = false;
+    if let Some(dir) = &cli.out_dir_by_hash {
+        output_path = hashed::hashed_path(dir, &design, cli.format.extension());
+        newly_stored = hashed::claim(&output_path)?;
+    }
+
+    // With a wordmark the output holds the lockup and the logo alone goes next to it
+    let icon_path = match &wordmark {
+        Some(_) => {
+            let stem = output_path.file_stem().and_then(|s| s.to_str()).unwrap_or("logo");
+            output_path.with_file_name(format!("{}-icon.{}", stem, cli.format.extension()))
+        }
+        None => output_path.clone(),
+    };
+
+    // The exported palette is named after the output file, e.g. logo-palette.gpl
+    if let Some(format) = export_format {
+        let stem = output_path.file_stem().and_then(|s| s.to_str()).unwrap_or("logo");
+        let palette_path = output_path.with_file_name(format!("{}-palette.{}", stem, format.extension()));
+        let colors: Vec<String> = report::palette_report(&design)
+            .colors
+            .into_iter()
+            .map(|entry| entry.hex)
+            .collect();
+        utils::save_palette(&color::export::name_colors(&colors), stem, format, &palette_path)?;
+        if cli.verbose {
+            println!("Wrote {} palette to {}", format, palette_path.display());
+        }
+    }
+
+    // The framed document replaces the plain logo; it is rasterized at the template's own size
+    let framed = match &template {
+        Some(template) => {
+            let title = output_path.file_stem().and_then(|s| s.to_str()).unwrap_or("logo");
+            let mut variables = template::variables(&design, title);
+            variables.extend(cli.template_vars.iter().cloned());
+            let logo = svg::design_to_svg(&design, design_args.width, design_args.height, &options)?;
+            Some(template.render(&logo, &variables)?)
+        }
+        None => None,
+    };
+
     // Generate and save the output
     match cli.format {
         Format::Svg => {
-            let svg_data = svg::generate_svg(&generator, cli.width, cli.height)?;
-            svg::save_svg(&svg_data, &output_path)?;
+            let svg_data = match (&print_options, &framed) {
+                (Some(print_options), _) => print::print_svg(&design, print_options)?,
+                (None, Some(framed)) => framed.clone(),
+                (None, None) => {
+                    svg::design_to_svg(&design, design_args.width, design_args.height, &options)?
+                }
+            };
+            svg::save_svg(&svg_data, &icon_path)?;
         }
         Format::Png => {
-            let png_data = png::generate_png(&generator, cli.width, cli.height)?;
-            png::save_png(&png_data, &output_path)?;
+            let png_data = match &framed {
+                Some(framed) => {
+                    let tree = png::parse_svg(framed)?;
+                    let (width, height) = (tree.size.width().ceil() as u32, tree.size.height().ceil() as u32);
+                    png::render_tree_to_png(&tree, width, height)?
+                }
+                None => png::design_to_png(&design, design_args.width, design_args.height, &options)?,
+            };
+            png::save_png(&png_data, &icon_path)?;
+        }
+        Format::Icns => {
+            let icns_data = icon::generate_icns(&design)?;
+            icon::save_icon(&icns_data, &output_path)?;
+        }
+        Format::Pdf => {
+            let pdf_data = print::print_pdf(&design, &print_options.unwrap_or_default())?;
+            print::save_pdf(&pdf_data, &output_path)?;
+        }
+        Format::Ascii if cli.output == "-" => {
+            // Colored glyphs for a terminal, unless NO_COLOR asks otherwise
+            let ansi = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+            print!("{}", ascii::design_to_ascii(&design, ansi));
+        }
+        Format::Ascii => ascii::save_ascii(&ascii::design_to_ascii(&design, false), &output_path)?,
+    }
+
+    if let Some((text, font, lockup)) = &wordmark {
+        let lockup =
+            wordmark::compose(&design, font, text, *lockup, design_args.width, &options)?;
+        match cli.format {
+            Format::Png => {
+                let png_data = png::convert_svg_to_png(&lockup.svg, lockup.width, lockup.height)?;
+                png::save_png(&png_data, &output_path)?;
+            }
+            _ => svg::save_svg(&lockup.svg, &output_path)?,
+        }
+    }
+
+    if let (Some(dir), true) = (&cli.out_dir_by_hash, newly_stored) {
+        hashed::record(dir, &output_path, &design, cli.format.extension())?;
+    }
+
+    let mut urls = Vec::new();
+    if let Some(url) = &cli.upload {
+        // Hashed names change with the content, so caches can keep them forever
+        let (base, immutable) = match &cli.out_dir_by_hash {
+            Some(dir) => (dir.as_path(), true),
+            None => (output_path.parent().unwrap_or(Path::new("")), false),
+        };
+        let mut uploads = vec![output_path.as_path()];
+        if wordmark.is_some() {
+            uploads.push(icon_path.as_path());
+        }
+        for path in uploads {
+            let name = path.strip_prefix(base).unwrap_or(path);
+            let name = name
+                .components()
+                .map(|part| part.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let uploaded = upload_file(url, path, &name, immutable)?;
+            if cli.verbose {
+                println!("Uploaded {} to {}", path.display(), uploaded);
+            }
+            urls.push(uploaded);
         }
     }
 
+    if cli.print_hash {
+        println!("{:016x}  {}", utils::phash(&design)?, output_path.display());
+    }
+    if cli.print_code {
+        println!("{}", DesignCode::from_config(design.config())?);
+    }
+
+    if let Some(url) = &cli.webhook {
+        let mut outputs = vec![output_path.display().to_string()];
+        if wordmark.is_some() {
+            outputs.push(icon_path.display().to_string());
+        }
+        notify_webhook(url, cli, &design, &outputs, &urls, started)?;
+    }
+
     if cli.verbose {
-        let seed_info = match &cli.uuid {
-            Some(uuid) => format!("UUID: {}", uuid),
-            None => match seed {
+        let seed_info = match (&design_args.code, &design_args.uuid) {
+            (Some(code), _) => format!("Code: {}", code),
+            (None, Some(uuid)) => format!("UUID: {}", uuid),
+            (None, None) => match seed {
                 Some(s) => format!("Seed: {}", s),
                 None => "Random generation (no seed)".to_string(),
             },
@@ -145,17 +1055,207 @@ pub fn run() -> Result<()> {
 
         println!("Logo generated successfully:");
         println!("  Output: {}", output_path.display());
+        if wordmark.is_some() {
+            println!("  Icon only: {}", icon_path.display());
+        }
+        if let Some(preset) = &cli.preset {
+            println!("  Preset: {}", preset);
+        }
         println!("  Format: {}", cli.format);
-        println!("  Theme: {}", cli.theme);
-        println!("  Grid size: {}", cli.grid_size);
-        println!("  Shapes: {}", cli.shapes);
-        println!("  Opacity: {}", cli.opacity);
+        if let Some(print_options) = &print_options {
+            println!("  Print layout: {}", print_options.page);
+        }
+        println!("  Theme: {}", design.config().theme());
+        println!("  Grid size: {}", design.config().grid_size());
+        println!("  Shapes: {}", design.config().shapes_count());
+        println!("  Opacity: {}", design.config().opacity());
         println!(
             "  Overlap: {}",
-            if cli.overlap { "enabled" } else { "disabled" }
+            if design.config().allow_overlap() {
+                "enabled"
+            } else {
+                "disabled"
+            }
         );
+        if let Some(achieved) = design.overlap_ratio() {
+            match design_args.overlap_ratio {
+                Some(target) => println!(
+                    "  Overlap ratio: {:.0}% (target {:.0}%)",
+                    achieved * 100.0,
+                    target * 100.0
+                ),
+                None => println!("  Overlap ratio: {:.0}%", achieved * 100.0),
+            }
+        }
+        println!("  Algorithm: v{}", design.config().algo_version());
+        if !design.winning_candidates().is_empty() {
+            let effort = design.config().effort();
+            let picked: Vec<String> = design.winning_candidates().iter().map(|index| index.to_string()).collect();
+            println!(
+                "  Effort: {} ({} candidates per shape; picked {})",
+                effort,
+                effort.candidates(),
+                picked.join(", ")
+            );
+        }
         println!("  {}", seed_info);
     }
 
+    Ok(output_path)
+}
+
+/// Writes the transition animation for the `morph` subcommand
+fn run_morph(args: &MorphArgs) -> Result<()> {
+    let design_args = &args.design;
+    if design_args.seed.is_some() || design_args.uuid.is_some() {
+        return Err("morph takes its two seeds as arguments, not --seed or --uuid".into());
+    }
+
+    let config = design_args.config()?;
+    let options = design_args.svg_options()?;
+    let from = config.clone().with_seed(Some(args.from)).generate()?;
+    let to = config.with_seed(Some(args.to)).generate()?;
+
+    let mut output_path = args.output.clone();
+    let data = match args.format {
+        MorphFormat::Svg => {
+            output_path.set_extension("svg");
+            animation::morph_to_svg(
+                &from,
+                &to,
+                args.frames,
+                design_args.width,
+                design_args.height,
+                &options,
+            )?
+            .into_bytes()
+        }
+        MorphFormat::Gif => {
+            output_path.set_extension("gif");
+            animation::morph_to_gif(
+                &from,
+                &to,
+                args.frames,
+                design_args.width,
+                design_args.height,
+                &options,
+            )?
+        }
+    };
+    animation::save_animation(&data, &output_path)?;
+
+    println!(
+        "Wrote {}-frame morph from seed {} to seed {} to {}",
+        args.frames.clamp(2, animation::MAX_FRAMES),
+        args.from,
+        args.to,
+        output_path.display()
+    );
+    Ok(())
+}
+
+/// Writes the scattered composition for the `wallpaper` subcommand
+///
+/// The design's --width and --height are ignored in favour of --resolution.
+fn run_wallpaper(args: &WallpaperArgs) -> Result<()> {
+    let design_args = &args.design;
+    let (width, height) = wallpaper::parse_resolution(&args.resolution)?;
+    let config = design_args.config()?;
+    let background = design_args.svg_options()?.background;
+
+    let mut output_path = args.output.clone();
+    match args.format {
+        WallpaperFormat::Svg => {
+            output_path.set_extension("svg");
+            // Streamed straight to disk, since a large wallpaper holds many thousands of paths
+            let file = BufWriter::new(File::create(&output_path)?);
+            wallpaper::write_wallpaper_svg(&config, width, height, args.count, background.as_deref(), file)?;
+        }
+        WallpaperFormat::Png => {
+            output_path.set_extension("png");
+            let png_data =
+                wallpaper::wallpaper_png(&config, width, height, args.count, background.as_deref())?;
+            png::save_png(&png_data, &output_path)?;
+        }
+    }
+
+    println!(
+        "Wrote {}x{} wallpaper to {}",
+        width,
+        height,
+        output_path.display()
+    );
+    Ok(())
+}
+
+/// Writes the badge for the `badge` subcommand
+fn run_badge(args: &BadgeArgs) -> Result<()> {
+    // A coarse grid keeps the icon legible at 14 pixels
+    let config = GeneratorConfig::try_new(2, args.shapes, 1.0, None)?.with_theme(args.theme.parse::<Theme>()?);
+    let badge = badge::Badge {
+        label: args.label.clone(),
+        message: args.message.clone(),
+        color: args.color.clone(),
+    };
+
+    let mut output_path = args.output.clone();
+    output_path.set_extension("svg");
+    svg::save_svg(&badge.to_svg(&config)?, &output_path)?;
+    println!("Wrote badge to {}", output_path.display());
+    Ok(())
+}
+
+/// Writes the grid approximation of an image for the `trace` subcommand
+fn run_trace(args: &TraceArgs) -> Result<()> {
+    let raster = trace::Raster::load(&args.input)?;
+    let options = trace::TraceOptions {
+        grid_size: args.grid_size,
+        colors: args.colors,
+        ..trace::TraceOptions::default()
+    };
+    let design = trace::trace(&raster, &options)?;
+
+    let svg_options = svg::SvgOptions::default();
+    let is_png = args.output.extension().is_some_and(|e| e.eq_ignore_ascii_case("png"));
+    if is_png {
+        let png_data = png::design_to_png(&design, args.width, args.height, &svg_options)?;
+        png::save_png(&png_data, &args.output)?;
+    } else {
+        let svg_data = svg::design_to_svg(&design, args.width, args.height, &svg_options)?;
+        svg::save_svg(&svg_data, &args.output)?;
+    }
+
+    let cells: usize = design.shapes().iter().map(|shape| shape.cell_count()).sum();
+    println!(
+        "Traced {} into {} shapes covering {} of {} cells, written to {}",
+        args.input.display(),
+        design.shapes().len(),
+        cells,
+        design.grid().cell_count(),
+        args.output.display()
+    );
+    Ok(())
+}
+
+/// Writes the labelled empty grid for the `grid` subcommand
+fn run_grid(args: &GridArgs) -> Result<()> {
+    // The grid clamps its density to the supported range itself
+    let grid = TriangularGrid::new(100.0, args.grid_size);
+    let options = svg::SvgOptions {
+        background: match &args.background {
+            Some(color) => Some(color::parse_color(color)?),
+            None => None,
+        },
+        ..svg::SvgOptions::default()
+    };
+
+    let svg_data = svg::grid_to_svg(&grid, args.width, args.height, &options)?;
+    svg::save_svg(&svg_data, &args.output)?;
+
+    println!(
+        "Wrote grid with {} cells to {}",
+        grid.cell_count(),
+        args.output.display()
+    );
     Ok(())
 }
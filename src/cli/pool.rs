@@ -0,0 +1,126 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Items waiting in the queue per worker, bounding how far reading runs ahead
+const QUEUE_PER_JOB: usize = 4;
+
+/// Throughput of one pool run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoolStats {
+    pub jobs: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub elapsed: Duration,
+}
+
+impl PoolStats {
+    /// Finished items per second of wall-clock time
+    pub fn per_second(&self) -> f64 {
+        self.completed as f64 / self.elapsed.as_secs_f64().max(1e-9)
+    }
+}
+
+/// The number of workers to use when none is given: one per available core
+pub fn default_jobs() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Runs `work` over `items` on `jobs` threads and returns the results in input order
+///
+/// Items are pulled lazily into a bounded queue, so a batch of any size runs
+/// in flat memory. Each worker takes the next item as soon as it is free, so
+/// slow items don't hold up the rest. Each item is handled independently,
+/// which keeps the results the same for any number of jobs. Once an item
+/// fails no new items are started.
+pub fn run<I, T, F>(items: I, jobs: usize, work: F) -> (Vec<Result<T, String>>, PoolStats)
+where
+    I: IntoIterator,
+    I::Item: Send,
+    T: Send,
+    F: Fn(I::Item) -> Result<T, String> + Sync,
+{
+    let jobs = jobs.max(1);
+    let started = Instant::now();
+    let failed = AtomicBool::new(false);
+    let results = Mutex::new(Vec::new());
+    let (sender, receiver) = mpsc::sync_channel::<(usize, I::Item)>(jobs * QUEUE_PER_JOB);
+    let receiver = Mutex::new(receiver);
+
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| loop {
+                // Hold the lock only while taking an item, not while working on it
+                let next = receiver.lock().unwrap_or_else(|e| e.into_inner()).recv();
+                let Ok((index, item)) = next else { break };
+
+                let result = work(item);
+                if result.is_err() {
+                    failed.store(true, Ordering::Relaxed);
+                }
+                results.lock().unwrap_or_else(|e| e.into_inner()).push((index, result));
+            });
+        }
+
+        for (index, item) in items.into_iter().enumerate() {
+            if failed.load(Ordering::Relaxed) || sender.send((index, item)).is_err() {
+                break;
+            }
+        }
+        drop(sender);
+    });
+
+    let mut results = results.into_inner().unwrap_or_else(|e| e.into_inner());
+    results.sort_by_key(|(index, _)| *index);
+    let results: Vec<Result<T, String>> = results.into_iter().map(|(_, result)| result).collect();
+
+    let stats = PoolStats {
+        jobs,
+        completed: results.iter().filter(|result| result.is_ok()).count(),
+        failed: results.iter().filter(|result| result.is_err()).count(),
+        elapsed: started.elapsed(),
+    };
+    (results, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_results_keep_input_order() {
+        let (results, stats) = run(0..200u64, 8, |n| {
+            // Uneven work so workers finish out of order
+            thread::sleep(Duration::from_micros((n % 7) * 50));
+            Ok(n * 2)
+        });
+        let values: Vec<u64> = results.into_iter().map(Result::unwrap).collect();
+        assert_eq!(values, (0..200).map(|n| n * 2).collect::<Vec<_>>());
+        assert_eq!(stats.completed, 200);
+        assert_eq!(stats.failed, 0);
+        assert_eq!(stats.jobs, 8);
+        assert!(stats.per_second() > 0.0);
+    }
+
+    #[test]
+    fn test_failure_stops_feeding() {
+        let (results, stats) = run(0..10_000u32, 2, |n| {
+            if n == 3 {
+                Err("three".to_string())
+            } else {
+                Ok(n)
+            }
+        });
+        assert_eq!(results[3], Err("three".to_string()));
+        assert_eq!(stats.failed, 1);
+        assert!(results.len() < 10_000);
+    }
+
+    #[test]
+    fn test_single_job() {
+        let (results, _) = run(vec!["a", "b"], 0, |s| Ok(s.to_uppercase()));
+        assert_eq!(results, vec![Ok("A".to_string()), Ok("B".to_string())]);
+    }
+}
@@ -0,0 +1,219 @@
+use super::{Cli, Format};
+use crate::Result;
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+
+/// A curated bundle of parameters for a common use of the logo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub grid_size: u8,
+    pub shapes: u8,
+    pub opacity: f32,
+    pub format: Format,
+    pub width: u32,
+    pub height: u32,
+    pub padding: f64,
+    /// Shape outline width in pixels, 0.0 for none
+    pub outline: f64,
+    /// Shapes smaller than this many cells are dropped
+    pub min_shape_cells: usize,
+}
+
+/// Every preset, in the order `hexlogogen presets` lists them
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "avatar",
+        description: "Bold, simple profile picture that stays readable when cropped to a circle",
+        grid_size: 3,
+        shapes: 3,
+        opacity: 0.9,
+        format: Format::Png,
+        width: 512,
+        height: 512,
+        padding: 0.12,
+        outline: 0.0,
+        min_shape_cells: 0,
+    },
+    Preset {
+        name: "app-icon",
+        description: "Opaque shapes sized for app stores, with room for rounded corners",
+        grid_size: 4,
+        shapes: 4,
+        opacity: 1.0,
+        format: Format::Png,
+        width: 1024,
+        height: 1024,
+        padding: 0.1,
+        outline: 0.0,
+        min_shape_cells: 0,
+    },
+    Preset {
+        name: "wallpaper",
+        description: "Detailed, translucent design on a widescreen desktop canvas",
+        grid_size: 7,
+        shapes: 7,
+        opacity: 0.7,
+        format: Format::Png,
+        width: 2560,
+        height: 1440,
+        padding: 0.05,
+        outline: 0.0,
+        min_shape_cells: 0,
+    },
+    Preset {
+        name: "sticker",
+        description: "Scalable vector with a wide margin for a die-cut border",
+        grid_size: 4,
+        shapes: 3,
+        opacity: 0.85,
+        format: Format::Svg,
+        width: 1024,
+        height: 1024,
+        padding: 0.15,
+        outline: 0.0,
+        min_shape_cells: 0,
+    },
+    Preset {
+        name: "emoji",
+        description: "128px PNG for Slack and Discord emoji: few opaque outlined shapes, inside safe margins",
+        grid_size: 3,
+        shapes: 3,
+        opacity: 1.0,
+        format: Format::Png,
+        width: 128,
+        height: 128,
+        padding: 0.08,
+        outline: 2.0,
+        min_shape_cells: 4,
+    },
+];
+
+/// Looks up a preset by name
+pub fn find(name: &str) -> Result<&'static Preset> {
+    PRESETS
+        .iter()
+        .find(|preset| preset.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            let names: Vec<&str> = PRESETS.iter().map(|preset| preset.name).collect();
+            format!("Unknown preset '{}' (expected one of: {})", name, names.join(", ")).into()
+        })
+}
+
+impl Preset {
+    /// Fills in every option the user left at its default
+    ///
+    /// Anything given explicitly on the command line wins over the preset.
+    pub fn apply(&self, cli: &mut Cli, matches: &ArgMatches) {
+        let defaulted = |id: &str| {
+            matches!(
+                matches.value_source(id),
+                None | Some(ValueSource::DefaultValue)
+            )
+        };
+
+        if defaulted("grid_size") {
+            cli.design.grid_size = self.grid_size;
+        }
+        if defaulted("shapes") {
+            cli.design.shapes = self.shapes;
+        }
+        if defaulted("opacity") {
+            cli.design.opacity = self.opacity;
+        }
+        if defaulted("width") {
+            cli.design.width = self.width;
+        }
+        if defaulted("height") {
+            cli.design.height = self.height;
+        }
+        if defaulted("padding") {
+            cli.design.padding = self.padding;
+        }
+        if defaulted("outline") {
+            cli.design.outline = self.outline;
+        }
+        if defaulted("min_shape_cells") {
+            cli.design.min_shape_cells = self.min_shape_cells;
+        }
+        if defaulted("format") {
+            cli.format = self.format;
+        }
+    }
+}
+
+/// Prints the presets and what each one sets for the `presets` subcommand
+pub fn run() -> Result<()> {
+    for preset in PRESETS {
+        println!("{:<10} {}", preset.name, preset.description);
+        println!(
+            "{:<10} --grid-size {} --shapes {} --opacity {} --format {} --width {} --height {} --padding {}",
+            "",
+            preset.grid_size,
+            preset.shapes,
+            preset.opacity,
+            preset.format,
+            preset.width,
+            preset.height,
+            preset.padding
+        );
+        if preset.outline > 0.0 || preset.min_shape_cells > 0 {
+            println!(
+                "{:<10} --outline {} --min-shape-cells {}",
+                "", preset.outline, preset.min_shape_cells
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+
+    fn parse(args: &[&str]) -> Cli {
+        let matches = Cli::command().get_matches_from(args);
+        let mut cli = Cli::from_arg_matches(&matches).unwrap();
+        let preset = find(cli.preset.as_deref().unwrap()).unwrap();
+        preset.apply(&mut cli, &matches);
+        cli
+    }
+
+    #[test]
+    fn test_preset_fills_defaults() {
+        let cli = parse(&["hexlogogen", "--preset", "wallpaper"]);
+        assert_eq!(cli.design.grid_size, 7);
+        assert_eq!((cli.design.width, cli.design.height), (2560, 1440));
+        assert_eq!(cli.format, Format::Png);
+    }
+
+    #[test]
+    fn test_explicit_options_override_preset() {
+        let cli = parse(&["hexlogogen", "--preset", "avatar", "-g", "5", "--format", "svg"]);
+        assert_eq!(cli.design.grid_size, 5);
+        assert_eq!(cli.format, Format::Svg);
+        assert_eq!(cli.design.shapes, 3);
+        assert_eq!(cli.design.padding, 0.12);
+    }
+
+    #[test]
+    fn test_emoji_preset() {
+        let cli = parse(&["hexlogogen", "--preset", "emoji"]);
+        assert_eq!((cli.design.width, cli.design.height), (128, 128));
+        assert_eq!(cli.design.opacity, 1.0);
+        assert_eq!(cli.design.outline, 2.0);
+        assert_eq!(cli.design.min_shape_cells, 4);
+
+        let cli = parse(&["hexlogogen", "--preset", "emoji", "--outline", "0"]);
+        assert_eq!(cli.design.outline, 0.0);
+    }
+
+    #[test]
+    fn test_unknown_preset() {
+        assert!(find("APP-ICON").is_ok());
+        let error = find("poster").unwrap_err().to_string();
+        assert!(error.contains("avatar, app-icon, wallpaper, sticker, emoji"));
+    }
+}
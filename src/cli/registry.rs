@@ -0,0 +1,243 @@
+use super::spec::DesignSpec;
+use super::{pool, Cli};
+use crate::Result;
+use clap::{Args, Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A versioned mapping of service names to logo parameters
+///
+/// Stored as TOML with one `[logos.<name>]` table per service, holding the
+/// same fields as a batch design spec:
+///
+/// ```toml
+/// [logos.payments-service]
+/// seed = 991
+/// theme = "blues"
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Registry {
+    #[serde(default)]
+    pub logos: BTreeMap<String, DesignSpec>,
+}
+
+impl Registry {
+    pub fn parse(text: &str) -> Result<Self> {
+        let registry: Self = toml::from_str(text).map_err(|e| format!("Invalid registry: {}", e))?;
+        for name in registry.logos.keys() {
+            validate_name(name)?;
+        }
+        Ok(registry)
+    }
+
+    /// Reads a registry file; a missing file is an empty registry
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e).into()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("Cannot read registry {}: {}", path.display(), e).into()),
+        }
+    }
+
+    /// Writes the registry, sorted by name so diffs stay small
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Service names become file names, so they're kept to a safe alphabet
+pub(super) fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid logo name '{}' (use letters, digits, '-', '_' and '.')", name).into())
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct RegistryArgs {
+    #[command(subcommand)]
+    pub action: RegistryAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RegistryAction {
+    /// Add a service's logo parameters to a registry file, creating it if needed
+    Add(RegistryAddArgs),
+    /// Render every logo in a registry file
+    Render(RegistryRenderArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct RegistryAddArgs {
+    /// Registry TOML file
+    pub registry: PathBuf,
+
+    /// Service name, also the rendered file's name
+    #[arg(long)]
+    pub name: String,
+
+    /// Seed for the service's design
+    #[arg(long)]
+    pub seed: u64,
+
+    /// Color theme
+    #[arg(short, long)]
+    pub theme: Option<String>,
+
+    /// Number of shapes
+    #[arg(short = 'n', long)]
+    pub shapes: Option<u8>,
+
+    /// Grid density (2-8)
+    #[arg(short, long)]
+    pub grid_size: Option<u8>,
+
+    /// Shape style (balanced, ribbon, organic-ca)
+    #[arg(long)]
+    pub style: Option<String>,
+
+    /// Output format for this logo [default: svg]
+    #[arg(short, long)]
+    pub format: Option<String>,
+
+    /// Replace the service's entry if it already exists
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct RegistryRenderArgs {
+    /// Registry TOML file
+    pub registry: PathBuf,
+
+    /// Directory the logos are written to, as <name>.<ext>
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    pub out_dir: PathBuf,
+
+    /// Number of logos rendered in parallel [default: one per CPU core]
+    #[arg(short, long, value_name = "N")]
+    pub jobs: Option<usize>,
+}
+
+/// Runs the `registry` subcommand
+pub fn run(args: &RegistryArgs) -> Result<()> {
+    match &args.action {
+        RegistryAction::Add(args) => add(args),
+        RegistryAction::Render(args) => render(args),
+    }
+}
+
+fn add(args: &RegistryAddArgs) -> Result<()> {
+    validate_name(&args.name)?;
+    if i64::try_from(args.seed).is_err() {
+        return Err(format!("Registry seeds must be at most {}, the largest TOML integer", i64::MAX).into());
+    }
+    let mut registry = Registry::load(&args.registry)?;
+    if registry.logos.contains_key(&args.name) && !args.force {
+        return Err(format!(
+            "'{}' is already in {}; use --force to replace it",
+            args.name,
+            args.registry.display()
+        )
+        .into());
+    }
+
+    let spec = DesignSpec {
+        seed: Some(args.seed),
+        theme: args.theme.clone(),
+        shapes: args.shapes,
+        grid_size: args.grid_size,
+        style: args.style.clone(),
+        format: args.format.clone(),
+        ..DesignSpec::default()
+    };
+    // Catch bad values now rather than at render time
+    spec_cli(&args.name, &spec, Path::new("."))?.design.config()?;
+
+    registry.logos.insert(args.name.clone(), spec);
+    registry.save(&args.registry)?;
+    println!("Added {} to {}", args.name, args.registry.display());
+    Ok(())
+}
+
+fn render(args: &RegistryRenderArgs) -> Result<()> {
+    let registry = Registry::load(&args.registry)?;
+    if registry.logos.is_empty() {
+        return Err(format!("{} has no logos", args.registry.display()).into());
+    }
+    let count = render_logos(&registry, &args.out_dir, args.jobs)?;
+    println!("Rendered {} logo(s) to {}", count, args.out_dir.display());
+    Ok(())
+}
+
+/// Renders every logo in a registry in parallel, returning how many were written
+pub(super) fn render_logos(registry: &Registry, out_dir: &Path, jobs: Option<usize>) -> Result<usize> {
+    let jobs = match jobs {
+        Some(0) => return Err("--jobs must be at least 1".into()),
+        Some(jobs) => jobs,
+        None => pool::default_jobs(),
+    };
+    fs::create_dir_all(out_dir)?;
+
+    let (results, _) = pool::run(&registry.logos, jobs, |(name, spec)| {
+        spec_cli(name, spec, out_dir)
+            .and_then(|cli| super::generate_logo(&cli))
+            .map_err(|e| format!("{}: {}", name, e))
+    });
+    for result in &results {
+        if let Err(e) = result {
+            return Err(e.clone().into());
+        }
+    }
+    Ok(results.len())
+}
+
+/// The options for one registry entry, writing to `<out_dir>/<name>.<ext>`
+fn spec_cli(name: &str, spec: &DesignSpec, out_dir: &Path) -> Result<Cli> {
+    let mut cli = Cli::try_parse_from(["hexlogogen"])?;
+    spec.apply(&mut cli, None)?;
+    let output = match &spec.output {
+        Some(output) => PathBuf::from(output),
+        None => PathBuf::from(format!("{}.{}", name, cli.format.extension())),
+    };
+    cli.output = if output.is_relative() {
+        out_dir.join(output).to_string_lossy().into_owned()
+    } else {
+        output.to_string_lossy().into_owned()
+    };
+    Ok(cli)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_round_trip() {
+        let text = "[logos.payments-service]\nseed = 991\ntheme = \"blues\"\n\n[logos.auth]\nseed = 7\nshapes = 2\n";
+        let registry = Registry::parse(text).unwrap();
+        assert_eq!(registry.logos.len(), 2);
+        assert_eq!(registry.logos["payments-service"].seed, Some(991));
+        assert_eq!(registry.logos["auth"].shapes, Some(2));
+
+        let saved = toml::to_string_pretty(&registry).unwrap();
+        assert!(!saved.contains("output"));
+        assert_eq!(Registry::parse(&saved).unwrap(), registry);
+    }
+
+    #[test]
+    fn test_registry_errors() {
+        assert!(Registry::parse("[logos.auth]\nseed = 7\ncolour = \"red\"\n").is_err());
+        assert!(Registry::parse("[logos.\"../escape\"]\nseed = 7\n").is_err());
+        assert!(Registry::parse("[services.auth]\nseed = 7\n").is_err());
+        assert!(Registry::parse("").unwrap().logos.is_empty());
+    }
+}
@@ -0,0 +1,120 @@
+use super::spec::DesignSpec;
+use super::{pool, Cli, Format};
+use crate::Result;
+use clap::{Args, Parser};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Args, Debug)]
+pub struct RerenderArgs {
+    /// Directory of saved design specs (.json) and design codes (.txt), searched recursively
+    pub source: PathBuf,
+
+    /// Directory the renders are written to, mirroring the layout of the source
+    #[arg(long, value_name = "DIR")]
+    pub out_dir: PathBuf,
+
+    /// Output format for every render [default: each spec's own, else svg]
+    #[arg(short, long, value_enum)]
+    pub format: Option<Format>,
+
+    /// Output width in pixels [default: each spec's own, else 512]
+    #[arg(short, long)]
+    pub width: Option<u32>,
+
+    /// Output height in pixels [default: each spec's own, else 512]
+    #[arg(short = 'H', long)]
+    pub height: Option<u32>,
+
+    /// Number of designs rendered in parallel [default: one per CPU core]
+    #[arg(short, long, value_name = "N")]
+    pub jobs: Option<usize>,
+
+    /// Print each file written
+    #[arg(short, long)]
+    pub verbose: bool,
+}
+
+/// Runs the `rerender` subcommand: every saved design in a directory, rendered again
+///
+/// `specs/brand/icon.json` becomes `<out-dir>/brand/icon.<ext>`; the output
+/// paths saved in specs are ignored so the whole archive lands in one place.
+pub fn run(args: &RerenderArgs) -> Result<()> {
+    let jobs = match args.jobs {
+        Some(0) => return Err("--jobs must be at least 1".into()),
+        Some(jobs) => jobs,
+        None => pool::default_jobs(),
+    };
+    let sources = find_designs(&args.source)?;
+    if sources.is_empty() {
+        return Err(format!("Found no design specs or codes in {}", args.source.display()).into());
+    }
+
+    let (results, stats) = pool::run(&sources, jobs, |path| {
+        design_cli(args, path)
+            .and_then(|cli| super::generate_logo(&cli))
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    });
+    for result in results {
+        let output = result?;
+        if args.verbose {
+            println!("{}", output.display());
+        }
+    }
+
+    println!(
+        "Rerendered {} design(s) from {} to {} in {:.2}s",
+        stats.completed,
+        args.source.display(),
+        args.out_dir.display(),
+        stats.elapsed.as_secs_f64()
+    );
+    Ok(())
+}
+
+/// Spec and code files under `dir`, in a stable order
+fn find_designs(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("Cannot read {}: {}", dir.display(), e))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<_>>()?;
+    entries.sort();
+
+    let mut designs = Vec::new();
+    for path in entries {
+        if path.is_dir() {
+            designs.extend(find_designs(&path)?);
+        } else if matches!(path.extension().and_then(|ext| ext.to_str()), Some("json" | "txt")) {
+            designs.push(path);
+        }
+    }
+    Ok(designs)
+}
+
+/// The options for one saved design, with the new format and size and its mirrored output path
+fn design_cli(args: &RerenderArgs, path: &Path) -> Result<Cli> {
+    let mut cli = Cli::try_parse_from(["hexlogogen"])?;
+    if path.extension().is_some_and(|ext| ext == "json") {
+        DesignSpec::read(path)?.apply(&mut cli, None)?;
+    } else {
+        cli.design.code = Some(fs::read_to_string(path)?.trim().to_string());
+    }
+
+    if let Some(format) = args.format {
+        cli.format = format;
+    }
+    if let Some(width) = args.width {
+        cli.design.width = width;
+    }
+    if let Some(height) = args.height {
+        cli.design.height = height;
+    }
+
+    let relative = path.strip_prefix(&args.source).unwrap_or(path);
+    let output = args.out_dir.join(relative).with_extension(cli.format.extension());
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    cli.output = output.to_string_lossy().into_owned();
+    Ok(cli)
+}
@@ -0,0 +1,220 @@
+use super::registry::{self, Registry};
+use super::spec::DesignSpec;
+use super::Format;
+use crate::utils;
+use crate::Result;
+use clap::Args;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Kubernetes kinds whose names are taken as services
+const KUBERNETES_KINDS: [&str; 6] = [
+    "Deployment",
+    "StatefulSet",
+    "DaemonSet",
+    "Job",
+    "CronJob",
+    "Service",
+];
+
+#[derive(Args, Debug)]
+pub struct ScanArgs {
+    /// Directory whose subdirectories are services, or a docker-compose or Kubernetes YAML manifest
+    pub source: PathBuf,
+
+    /// Directory the logos are written to, as <service>.<ext>
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    pub out_dir: PathBuf,
+
+    /// Color theme for every logo [default: mesos]
+    #[arg(short, long)]
+    pub theme: Option<String>,
+
+    /// Output format for every logo
+    #[arg(short, long, value_enum, default_value_t = Format::Svg)]
+    pub format: Format,
+
+    /// Record the services in this registry file; services already in it keep their entry
+    #[arg(long, value_name = "FILE")]
+    pub registry: Option<PathBuf>,
+
+    /// Number of logos rendered in parallel [default: one per CPU core]
+    #[arg(short, long, value_name = "N")]
+    pub jobs: Option<usize>,
+}
+
+/// Runs the `scan` subcommand: one logo per discovered service, seeded from its name
+pub fn run(args: &ScanArgs) -> Result<()> {
+    let services = discover(&args.source)?;
+    if services.is_empty() {
+        return Err(format!("Found no services in {}", args.source.display()).into());
+    }
+
+    let mut known = match &args.registry {
+        Some(path) => Registry::load(path)?,
+        None => Registry::default(),
+    };
+    let mut scanned = Registry::default();
+    for name in &services {
+        registry::validate_name(name)?;
+        let spec = known
+            .logos
+            .entry(name.clone())
+            .or_insert_with(|| DesignSpec {
+                seed: Some(utils::text_to_seed(name)),
+                theme: args.theme.clone(),
+                format: Some(args.format.to_string()),
+                ..DesignSpec::default()
+            });
+        scanned.logos.insert(name.clone(), spec.clone());
+    }
+    if let Some(path) = &args.registry {
+        known.save(path)?;
+    }
+
+    let count = registry::render_logos(&scanned, &args.out_dir, args.jobs)?;
+    println!(
+        "Rendered {} service logo(s) from {} to {}",
+        count,
+        args.source.display(),
+        args.out_dir.display()
+    );
+    Ok(())
+}
+
+/// Service names found in a directory or manifest, in discovery order without repeats
+pub fn discover(source: &Path) -> Result<Vec<String>> {
+    let mut names = if source.is_dir() {
+        let mut names = Vec::new();
+        for entry in
+            fs::read_dir(source).map_err(|e| format!("Cannot read {}: {}", source.display(), e))?
+        {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if entry.file_type()?.is_dir() && !name.starts_with('.') {
+                names.push(name);
+            }
+        }
+        names.sort();
+        names
+    } else {
+        let text = fs::read_to_string(source)
+            .map_err(|e| format!("Cannot read {}: {}", source.display(), e))?;
+        manifest_services(&text).ok_or_else(|| {
+            format!(
+                "{} is neither a docker-compose nor a Kubernetes manifest",
+                source.display()
+            )
+        })?
+    };
+
+    let mut seen = Vec::new();
+    names.retain(|name| {
+        let new = !seen.contains(name);
+        seen.push(name.clone());
+        new
+    });
+    Ok(names)
+}
+
+/// Service names from a docker-compose file or Kubernetes manifest
+///
+/// Only the bits of YAML these files use for names are read: the keys under
+/// a top-level `services:`, or `metadata.name` of each workload document.
+fn manifest_services(text: &str) -> Option<Vec<String>> {
+    if text.lines().any(|line| line.trim_end() == "services:") {
+        return Some(compose_services(text));
+    }
+    if text.lines().any(|line| line.starts_with("kind:")) {
+        return Some(kubernetes_services(text));
+    }
+    None
+}
+
+fn compose_services(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut in_services = false;
+    let mut service_indent = None;
+    for line in text.lines() {
+        let content = line.trim_start();
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - content.len();
+        if indent == 0 {
+            in_services = line.trim_end() == "services:";
+            continue;
+        }
+        if !in_services {
+            continue;
+        }
+
+        // Services are the keys at the first indentation level below `services:`
+        let service_indent = *service_indent.get_or_insert(indent);
+        if indent == service_indent {
+            if let Some(key) = content.trim_end().strip_suffix(':') {
+                names.push(unquote(key).to_string());
+            }
+        }
+    }
+    names
+}
+
+fn kubernetes_services(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for document in text.split("\n---") {
+        let mut kind = None;
+        let mut name = None;
+        let mut in_metadata = false;
+        for line in document.lines() {
+            let content = line.trim_start();
+            if content.is_empty() || content.starts_with('#') {
+                continue;
+            }
+            if line.len() == content.len() {
+                in_metadata = content.trim_end() == "metadata:";
+                if let Some(value) = content.strip_prefix("kind:") {
+                    kind = Some(unquote(value.trim()).to_string());
+                }
+            } else if in_metadata && name.is_none() {
+                if let Some(value) = content.strip_prefix("name:") {
+                    name = Some(unquote(value.trim()).to_string());
+                }
+            }
+        }
+        if let (Some(kind), Some(name)) = (kind, name) {
+            if KUBERNETES_KINDS.contains(&kind.as_str()) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim_matches(|c| c == '"' || c == '\'')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compose_services() {
+        let compose = "version: \"3.8\"\nservices:\n  payments-service:\n    image: payments:1.2\n    ports:\n      - \"8080:8080\"\n  # the login flow\n  \"auth\":\n    build: ./auth\nvolumes:\n  data:\n";
+        assert_eq!(
+            manifest_services(compose),
+            Some(vec!["payments-service".to_string(), "auth".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_kubernetes_services() {
+        let manifest = "apiVersion: apps/v1\nkind: Deployment\nmetadata:\n  name: checkout\n  labels:\n    name: ignored\nspec:\n  template:\n    metadata:\n      name: pod\n---\napiVersion: v1\nkind: ConfigMap\nmetadata:\n  name: settings\n---\napiVersion: v1\nkind: Service\nmetadata:\n  name: 'search'\n";
+        assert_eq!(
+            manifest_services(manifest),
+            Some(vec!["checkout".to_string(), "search".to_string()])
+        );
+        assert_eq!(manifest_services("just: yaml\n"), None);
+    }
+}
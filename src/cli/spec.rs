@@ -0,0 +1,339 @@
+use super::{dedupe, pool, Cli, Format};
+use crate::Result;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Parser, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, BufRead, Read};
+use std::path::Path;
+
+/// One design described as JSON, with the same names as the command line options
+///
+/// Every field is optional; anything left out keeps its usual default.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DesignSpec {
+    pub output: Option<String>,
+    pub format: Option<String>,
+    pub seed: Option<u64>,
+    pub uuid: Option<String>,
+    pub code: Option<String>,
+    pub theme: Option<String>,
+    pub shapes: Option<u8>,
+    pub grid_size: Option<u8>,
+    pub opacity: Option<f32>,
+    pub overlap: Option<bool>,
+    pub overlap_ratio: Option<f32>,
+    pub overlap_opacity: Option<String>,
+    pub style: Option<String>,
+    pub anchor: Option<String>,
+    pub aesthetic: Option<String>,
+    pub effort: Option<String>,
+    pub symmetry: Option<String>,
+    pub symmetry_colors: Option<String>,
+    pub algo_version: Option<String>,
+    pub pack: Option<bool>,
+    pub mosaic: Option<bool>,
+    pub color_by_size: Option<bool>,
+    pub unique_colors: Option<bool>,
+    pub background: Option<String>,
+    pub padding: Option<f64>,
+    pub stained_glass: Option<bool>,
+    pub editable: Option<bool>,
+    pub lod: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+impl DesignSpec {
+    /// Parses a spec from JSON, rejecting unknown fields so typos don't pass silently
+    pub fn parse(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| format!("Invalid design spec: {}", e).into())
+    }
+
+    /// Reads a spec from a file, or from standard input when `source` is `-`
+    pub fn read(source: &Path) -> Result<Self> {
+        let json = if source == Path::new("-") {
+            let mut json = String::new();
+            io::stdin().read_to_string(&mut json)?;
+            json
+        } else {
+            fs::read_to_string(source)
+                .map_err(|e| format!("Cannot read design spec {}: {}", source.display(), e))?
+        };
+        Self::parse(&json)
+    }
+
+    /// Copies the spec's values onto the command line options
+    ///
+    /// With `matches`, anything given explicitly on the command line wins over
+    /// the spec, as with presets; without, every value in the spec is used.
+    pub fn apply(&self, cli: &mut Cli, matches: Option<&ArgMatches>) -> Result<()> {
+        let settable = |id: &str| {
+            matches.is_none_or(|matches| {
+                matches!(
+                    matches.value_source(id),
+                    None | Some(ValueSource::DefaultValue)
+                )
+            })
+        };
+
+        if let (Some(output), true) = (&self.output, settable("output")) {
+            cli.output = output.clone();
+        }
+        if let (Some(format), true) = (&self.format, settable("format")) {
+            cli.format = Format::from_str(format, true).map_err(|_| {
+                let names: Vec<String> = Format::value_variants().iter().map(Format::to_string).collect();
+                format!("Unknown format '{}' (expected one of: {})", format, names.join(", "))
+            })?;
+        }
+
+        let design = &mut cli.design;
+        if let (Some(seed), true) = (self.seed, settable("seed")) {
+            design.seed = Some(seed);
+        }
+        if let (Some(uuid), true) = (&self.uuid, settable("uuid")) {
+            design.uuid = Some(uuid.clone());
+        }
+        if let (Some(code), true) = (&self.code, settable("code")) {
+            design.code = Some(code.clone());
+        }
+        if let (Some(theme), true) = (&self.theme, settable("theme")) {
+            design.theme = theme.clone();
+        }
+        if let (Some(shapes), true) = (self.shapes, settable("shapes")) {
+            design.shapes = shapes;
+        }
+        if let (Some(grid_size), true) = (self.grid_size, settable("grid_size")) {
+            design.grid_size = grid_size;
+        }
+        if let (Some(opacity), true) = (self.opacity, settable("opacity")) {
+            design.opacity = opacity;
+        }
+        if let (Some(overlap), true) = (self.overlap, settable("overlap")) {
+            design.overlap = overlap;
+        }
+        if let (Some(ratio), true) = (self.overlap_ratio, settable("overlap_ratio")) {
+            design.overlap_ratio = Some(ratio);
+        }
+        if let (Some(overlap_opacity), true) = (&self.overlap_opacity, settable("overlap_opacity")) {
+            design.overlap_opacity = overlap_opacity.clone();
+        }
+        if let (Some(style), true) = (&self.style, settable("style")) {
+            design.style = style.clone();
+        }
+        if let (Some(anchor), true) = (&self.anchor, settable("anchor")) {
+            design.anchor = anchor.clone();
+        }
+        if let (Some(aesthetic), true) = (&self.aesthetic, settable("aesthetic")) {
+            design.aesthetic = aesthetic.clone();
+        }
+        if let (Some(effort), true) = (&self.effort, settable("effort")) {
+            design.effort = effort.clone();
+        }
+        if let (Some(symmetry), true) = (&self.symmetry, settable("symmetry")) {
+            design.symmetry = symmetry.clone();
+        }
+        if let (Some(symmetry_colors), true) = (&self.symmetry_colors, settable("symmetry_colors")) {
+            design.symmetry_colors = symmetry_colors.clone();
+        }
+        if let (Some(algo_version), true) = (&self.algo_version, settable("algo_version")) {
+            design.algo_version = algo_version.clone();
+        }
+        if let (Some(pack), true) = (self.pack, settable("pack")) {
+            design.pack = pack;
+        }
+        if let (Some(mosaic), true) = (self.mosaic, settable("mosaic")) {
+            design.mosaic = mosaic;
+        }
+        if let (Some(color_by_size), true) = (self.color_by_size, settable("color_by_size")) {
+            design.color_by_size = color_by_size;
+        }
+        if let (Some(unique_colors), true) = (self.unique_colors, settable("unique_colors")) {
+            design.unique_colors = unique_colors;
+        }
+        if let (Some(background), true) = (&self.background, settable("background")) {
+            design.background = Some(background.clone());
+        }
+        if let (Some(padding), true) = (self.padding, settable("padding")) {
+            design.padding = padding;
+        }
+        if let (Some(stained_glass), true) = (self.stained_glass, settable("stained_glass")) {
+            design.stained_glass = stained_glass;
+        }
+        if let (Some(editable), true) = (self.editable, settable("editable")) {
+            design.editable = editable;
+        }
+        if let (Some(lod), true) = (&self.lod, settable("lod")) {
+            design.lod = lod.clone();
+        }
+        if let (Some(width), true) = (self.width, settable("width")) {
+            design.width = width;
+        }
+        if let (Some(height), true) = (self.height, settable("height")) {
+            design.height = height;
+        }
+        Ok(())
+    }
+}
+
+/// Generates one file per NDJSON spec line for the `batch` subcommand
+///
+/// Blank lines are skipped. Specs without an `output` are written to
+/// `design-<line>.<ext>`, and relative outputs go under `out_dir` when given.
+/// Lines are rendered in parallel on the render pool; the first failing line
+/// stops the batch with its line number. With a dedupe threshold, designs too
+/// similar to an earlier line are skipped or regenerated before rendering.
+pub fn run_batch(args: &super::BatchArgs) -> Result<()> {
+    let reader: Box<dyn BufRead> = match (&args.input, args.stdin) {
+        (Some(path), false) => Box::new(io::BufReader::new(
+            fs::File::open(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?,
+        )),
+        (None, true) => Box::new(io::stdin().lock()),
+        _ => return Err("batch reads specs from a file or, with --stdin, from standard input".into()),
+    };
+    let jobs = match args.jobs {
+        Some(0) => return Err("--jobs must be at least 1".into()),
+        Some(jobs) => jobs,
+        None => pool::default_jobs(),
+    };
+
+    if let Some(dir) = &args.out_dir {
+        fs::create_dir_all(dir)?;
+    }
+
+    let out_dir = args.out_dir.as_deref();
+    let by_hash = args.out_dir_by_hash.as_deref();
+    let upload = args.upload.as_deref();
+    let webhook = args.webhook.as_deref();
+    let lines = reader.lines().enumerate().map(|(index, line)| (index + 1, line));
+    let (results, stats) = match args.dedupe_threshold {
+        None => pool::run(lines, jobs, |(number, line)| {
+            let line = line.map_err(|e| format!("Line {}: {}", number, e))?;
+            if line.trim().is_empty() {
+                return Ok(None);
+            }
+            prepare_line(&line, number, out_dir, by_hash, upload, webhook)
+                .and_then(|cli| super::generate_logo(&cli))
+                .map(Some)
+                .map_err(|e| format!("Line {}: {}", number, e))
+        }),
+        Some(threshold) => {
+            if !(0.0..=1.0).contains(&threshold) {
+                return Err(format!("--dedupe-threshold must be between 0.0 and 1.0, got {}", threshold).into());
+            }
+
+            // Comparing against earlier designs needs them all up front
+            let mut entries = Vec::new();
+            for (number, line) in lines {
+                let line = line.map_err(|e| format!("Line {}: {}", number, e))?;
+                if !line.trim().is_empty() {
+                    let cli = prepare_line(&line, number, out_dir, by_hash, upload, webhook).map_err(|e| format!("Line {}: {}", number, e))?;
+                    entries.push((number, cli));
+                }
+            }
+
+            let (entries, dedupe_stats) = dedupe::dedupe(entries, threshold, args.on_duplicate)?;
+            println!(
+                "Rejected {} near-duplicate design(s): {} skipped, {} regenerated",
+                dedupe_stats.skipped + dedupe_stats.regenerated,
+                dedupe_stats.skipped,
+                dedupe_stats.regenerated
+            );
+
+            pool::run(entries, jobs, |(number, cli)| {
+                super::generate_logo(&cli)
+                    .map(Some)
+                    .map_err(|e| format!("Line {}: {}", number, e))
+            })
+        }
+    };
+
+    let mut count = 0;
+    for result in results {
+        if let Some(output) = result? {
+            count += 1;
+            if args.verbose {
+                println!("{}", output.display());
+            }
+        }
+    }
+
+    if args.verbose {
+        println!(
+            "Generated {} design(s) in {:.2}s with {} job(s) ({:.1} per second)",
+            count,
+            stats.elapsed.as_secs_f64(),
+            stats.jobs,
+            stats.per_second()
+        );
+    }
+    Ok(())
+}
+
+/// The options for the design on one spec line, with its output path resolved
+fn prepare_line(
+    line: &str,
+    number: usize,
+    out_dir: Option<&Path>,
+    by_hash: Option<&Path>,
+    upload: Option<&str>,
+    webhook: Option<&str>,
+) -> Result<Cli> {
+    let spec = DesignSpec::parse(line)?;
+    let mut cli = Cli::try_parse_from(["hexlogogen"])?;
+    spec.apply(&mut cli, None)?;
+
+    if spec.output.is_none() {
+        cli.output = format!("design-{}.{}", number, cli.format.extension());
+    }
+    if let Some(dir) = out_dir {
+        let output = Path::new(&cli.output);
+        if output.is_relative() {
+            cli.output = dir.join(output).to_string_lossy().into_owned();
+        }
+    }
+    cli.out_dir_by_hash = by_hash.map(Path::to_path_buf);
+    cli.upload = upload.map(str::to_string);
+    cli.webhook = webhook.map(str::to_string);
+
+    Ok(cli)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+    use clap::FromArgMatches;
+
+    #[test]
+    fn test_parse_spec() {
+        let spec = DesignSpec::parse(r#"{"seed": 7, "theme": "blues", "grid_size": 3}"#).unwrap();
+        assert_eq!(spec.seed, Some(7));
+        assert_eq!(spec.grid_size, Some(3));
+
+        let error = DesignSpec::parse(r#"{"colour": "red"}"#).unwrap_err();
+        assert!(error.to_string().contains("unknown field `colour`"));
+        assert!(DesignSpec::parse("[1, 2]").is_err());
+    }
+
+    #[test]
+    fn test_command_line_wins_over_spec() {
+        let matches = Cli::command().get_matches_from(["hexlogogen", "--shapes", "2", "out.svg"]);
+        let mut cli = Cli::from_arg_matches(&matches).unwrap();
+        let spec = DesignSpec::parse(r#"{"shapes": 5, "grid_size": 6, "format": "PNG", "output": "spec.png"}"#).unwrap();
+        spec.apply(&mut cli, Some(&matches)).unwrap();
+
+        assert_eq!(cli.design.shapes, 2);
+        assert_eq!(cli.design.grid_size, 6);
+        assert_eq!(cli.format, Format::Png);
+        assert_eq!(cli.output, "out.svg");
+
+        // Without matches the spec sets everything it names
+        spec.apply(&mut cli, None).unwrap();
+        assert_eq!(cli.design.shapes, 5);
+
+        let bad = DesignSpec::parse(r#"{"format": "jpeg"}"#).unwrap();
+        assert!(bad.apply(&mut cli, None).unwrap_err().to_string().contains("Unknown format 'jpeg'"));
+    }
+}
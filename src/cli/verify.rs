@@ -0,0 +1,77 @@
+use super::VerifyArgs;
+use crate::png;
+use crate::svg;
+use crate::utils;
+use crate::Result;
+use std::fs;
+
+/// Regenerates the design described by the arguments and checks the file against it
+///
+/// An embedded checksum identifies files made from different parameters; the
+/// content hash then catches any edit made after generation.
+pub fn run(args: &VerifyArgs) -> Result<()> {
+    let design_args = &args.design;
+    let options = design_args.svg_options()?;
+    if design_args.seed()?.is_none() {
+        return Err("verify requires --seed or --uuid to regenerate the design".into());
+    }
+
+    let design = design_args.design()?;
+    let path = &args.file;
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+
+    let (actual, expected) = match extension.as_deref() {
+        Some("svg") => {
+            let content = fs::read_to_string(path)?;
+            if let Some(embedded) = svg::embedded_checksum(&content) {
+                if embedded != design.checksum() {
+                    return Err(format!(
+                        "{} was not generated from these parameters (checksum {:016x}, expected {:016x})",
+                        path.display(),
+                        embedded,
+                        design.checksum()
+                    )
+                    .into());
+                }
+            }
+
+            let regenerated =
+                svg::design_to_svg(&design, design_args.width, design_args.height, &options)?;
+            (content.into_bytes(), regenerated.into_bytes())
+        }
+        Some("png") => {
+            let regenerated =
+                png::design_to_png(&design, design_args.width, design_args.height, &options)?;
+            (fs::read(path)?, regenerated)
+        }
+        _ => {
+            return Err(format!(
+                "Cannot verify {}: only .svg and .png files are supported",
+                path.display()
+            )
+            .into())
+        }
+    };
+
+    let actual_hash = utils::stable_hash(&actual);
+    let expected_hash = utils::stable_hash(&expected);
+    if actual_hash != expected_hash {
+        return Err(format!(
+            "{} does not match the regenerated design (content hash {:016x}, expected {:016x})",
+            path.display(),
+            actual_hash,
+            expected_hash
+        )
+        .into());
+    }
+
+    println!(
+        "{} matches the design (checksum {:016x})",
+        path.display(),
+        design.checksum()
+    );
+    Ok(())
+}
@@ -0,0 +1,240 @@
+use crate::generator::{AlgoVersion, GeneratorConfig, Theme};
+use crate::render::DesignParams;
+use crate::Result;
+
+/// What every design code starts with, followed by its format version
+pub const CODE_PREFIX: &str = "HX";
+
+/// Format version written into new codes
+pub const CODE_VERSION: u8 = 1;
+
+/// Crockford's base32 digits: no i, l, o or u, so codes survive being read aloud
+const ALPHABET: &[u8; 32] = b"0123456789abcdefghjkmnpqrstvwxyz";
+
+/// A design as one short string such as `HX1-58208m010m`
+///
+/// Codes carry the seed, theme, shape count, grid size, opacity, overlap and
+/// algorithm version: everything that decides the design, nothing about the
+/// size it's rendered at.
+/// A trailing checksum catches typos.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesignCode {
+    pub seed: u64,
+    pub theme: String,
+    pub shapes: u8,
+    pub grid_size: u8,
+    pub opacity: f32,
+    pub overlap: bool,
+    pub algo_version: AlgoVersion,
+}
+
+impl DesignCode {
+    /// The code for the design `params` describe, which needs a seed and valid values
+    pub fn from_params(params: &DesignParams) -> Result<Self> {
+        let config = GeneratorConfig::try_new(params.grid_size, params.shapes, params.opacity, params.seed)?
+            .with_theme(params.theme.parse::<Theme>()?)
+            .with_allow_overlap(params.overlap)
+            .with_algo_version(params.algo_version);
+        Self::from_config(&config)
+    }
+
+    /// The code for a configuration, which needs a seed and a theme known by name
+    ///
+    /// Only the values a code carries are read; style, anchor and the other
+    /// growth options are left behind.
+    pub fn from_config(config: &GeneratorConfig) -> Result<Self> {
+        let seed = config.seed().ok_or("A design code needs a seed")?;
+        let theme = config.theme().to_string();
+        if theme.parse::<Theme>().is_err() {
+            return Err(format!("A design code can't carry the imported palette '{}'", theme).into());
+        }
+        Ok(Self {
+            seed,
+            theme,
+            shapes: config.shapes_count(),
+            grid_size: config.grid_size(),
+            // Opacity travels in whole percent
+            opacity: (config.opacity() * 100.0).round() / 100.0,
+            overlap: config.allow_overlap(),
+            algo_version: config.algo_version(),
+        })
+    }
+
+    /// Render parameters for this design, at the default size and background
+    pub fn params(&self) -> DesignParams {
+        DesignParams {
+            seed: Some(self.seed),
+            theme: self.theme.clone(),
+            shapes: self.shapes,
+            grid_size: self.grid_size,
+            opacity: self.opacity,
+            overlap: self.overlap,
+            algo_version: self.algo_version,
+            ..DesignParams::default()
+        }
+    }
+
+    /// Seed as a little-endian base-128 varint, then grid size, shapes, opacity
+    /// percent, flags, the theme name (empty for mesos) and a checksum
+    ///
+    /// The flags hold overlap in bit 0 and the algorithm version less one above it,
+    /// so codes from before versions existed read as version 1.
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut seed = self.seed;
+        while seed >= 0x80 {
+            bytes.push(seed as u8 | 0x80);
+            seed >>= 7;
+        }
+        bytes.push(seed as u8);
+        bytes.push(self.grid_size);
+        bytes.push(self.shapes);
+        bytes.push((self.opacity * 100.0).round() as u8);
+        bytes.push((self.algo_version.number() - 1) << 1 | self.overlap as u8);
+        if self.theme != "mesos" {
+            bytes.extend_from_slice(self.theme.as_bytes());
+        }
+        bytes.push(checksum(&bytes));
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (&check, bytes) = bytes.split_last()?;
+        if checksum(bytes) != check {
+            return None;
+        }
+
+        let read = bytes.iter().take(10).position(|&byte| byte & 0x80 == 0)? + 1;
+        let seed = bytes[..read].iter().rev().fold(0u64, |seed, &byte| seed << 7 | (byte & 0x7f) as u64);
+        let [grid_size, shapes, opacity, flags, theme @ ..] = &bytes[read..] else {
+            return None;
+        };
+        let theme = match std::str::from_utf8(theme).ok()? {
+            "" => "mesos".to_string(),
+            theme => theme.to_string(),
+        };
+        Some(Self {
+            seed,
+            theme,
+            shapes: *shapes,
+            grid_size: *grid_size,
+            opacity: *opacity as f32 / 100.0,
+            overlap: flags & 1 == 1,
+            algo_version: AlgoVersion::from_number((flags >> 1) + 1)?,
+        })
+    }
+}
+
+/// Rotating XOR over the payload; any single wrong digit changes it
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0x5a, |sum: u8, &byte| sum.rotate_left(3) ^ byte)
+}
+
+fn encode(bytes: &[u8]) -> String {
+    let mut digits = String::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for &byte in bytes {
+        buffer = buffer << 8 | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            digits.push(ALPHABET[(buffer >> bits & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        digits.push(ALPHABET[(buffer << (5 - bits) & 31) as usize] as char);
+    }
+    digits
+}
+
+fn decode(digits: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for digit in digits.chars() {
+        // Letters easily mistaken for digits read as those digits
+        let digit = match digit.to_ascii_lowercase() {
+            'o' => '0',
+            'i' | 'l' => '1',
+            digit => digit,
+        };
+        let value = ALPHABET.iter().position(|&c| c as char == digit)? as u32;
+        buffer = (buffer << 5 | value) & 0xffff;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+impl std::fmt::Display for DesignCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}-{}", CODE_PREFIX, CODE_VERSION, encode(&self.to_bytes()))
+    }
+}
+
+impl std::str::FromStr for DesignCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || format!("Invalid design code '{}'", s);
+        let (version, digits) = s.trim().split_once('-').ok_or_else(invalid)?;
+        match version.to_uppercase().strip_prefix(CODE_PREFIX) {
+            Some(version) if version == CODE_VERSION.to_string() => {}
+            Some(version) if !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()) => {
+                return Err(format!(
+                    "Design code '{}' is version {}; this build reads version {}",
+                    s, version, CODE_VERSION
+                ))
+            }
+            _ => return Err(invalid()),
+        }
+        decode(digits).and_then(|bytes| Self::from_bytes(&bytes)).ok_or_else(invalid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_design_code_round_trip() {
+        let params = DesignParams {
+            seed: Some(42),
+            theme: "Blues".to_string(),
+            opacity: 0.75,
+            overlap: false,
+            ..DesignParams::default()
+        };
+        let code = DesignCode::from_params(&params).unwrap();
+        let text = code.to_string();
+        assert!(text.starts_with("HX1-"));
+        assert_eq!(text.parse::<DesignCode>(), Ok(code.clone()));
+        assert_eq!(text.to_uppercase().parse::<DesignCode>(), Ok(code.clone()));
+        assert_eq!(code.params(), DesignParams { theme: "blues".to_string(), ..params });
+
+        for seed in [0, 127, 128, u64::MAX] {
+            let code = DesignCode { seed, ..code.clone() };
+            assert_eq!(code.to_string().parse::<DesignCode>(), Ok(code));
+        }
+        let blend = DesignCode { theme: "mesos:blues:0.4".to_string(), ..code.clone() };
+        assert_eq!(blend.to_string().parse::<DesignCode>(), Ok(blend));
+
+        // The default theme is left out, keeping common codes short
+        let short = DesignCode::from_params(&DesignParams { seed: Some(42), ..DesignParams::default() }).unwrap();
+        assert_eq!(short.to_string().len(), 4 + 10);
+
+        // A mistyped digit fails the checksum
+        let mut typo = text.clone().into_bytes();
+        let last = typo.len() - 3;
+        typo[last] = if typo[last] == b'0' { b'1' } else { b'0' };
+        assert!(String::from_utf8(typo).unwrap().parse::<DesignCode>().is_err());
+
+        assert!("HX2-abc".parse::<DesignCode>().unwrap_err().contains("version 2"));
+        assert!("XX1-abc".parse::<DesignCode>().is_err());
+        assert!("HX1-u".parse::<DesignCode>().is_err());
+        assert!(DesignCode::from_params(&DesignParams::default()).unwrap_err().to_string().contains("seed"));
+        assert!(DesignCode::from_params(&DesignParams { seed: Some(1), shapes: 0, ..DesignParams::default() }).is_err());
+    }
+}
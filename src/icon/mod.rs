@@ -0,0 +1,203 @@
+use crate::generator::GeneratedDesign;
+use crate::png;
+use crate::svg;
+use crate::Result;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Icon entries written to a macOS .icns bundle as (OSType, pixel size)
+///
+/// The `ic11`-`ic14` types are the @2x retina variants of the smaller sizes,
+/// so several entries share the same pixel data.
+const ICNS_ENTRIES: [(&[u8; 4], u32); 10] = [
+    (b"icp4", 16),
+    (b"icp5", 32),
+    (b"ic07", 128),
+    (b"ic08", 256),
+    (b"ic09", 512),
+    (b"ic10", 1024),
+    (b"ic11", 32),
+    (b"ic12", 64),
+    (b"ic13", 256),
+    (b"ic14", 512),
+];
+
+/// Generates a macOS .icns icon bundle containing every standard resolution
+pub fn generate_icns(design: &GeneratedDesign) -> Result<Vec<u8>> {
+    // Render the design once and rasterize the same tree at each size
+    let svg_data = svg::design_to_svg(design, 1024, 1024, &svg::SvgOptions::default())?;
+    let tree = png::parse_svg(&svg_data)?;
+
+    let mut rendered: HashMap<u32, Vec<u8>> = HashMap::new();
+    let mut body = Vec::new();
+
+    for (os_type, size) in ICNS_ENTRIES {
+        if let Entry::Vacant(entry) = rendered.entry(size) {
+            entry.insert(png::render_tree_to_png(&tree, size, size)?);
+        }
+        let png_data = &rendered[&size];
+
+        // Each entry is its type, its length including the 8-byte header, then the data
+        body.extend_from_slice(os_type);
+        body.extend_from_slice(&(png_data.len() as u32 + 8).to_be_bytes());
+        body.extend_from_slice(png_data);
+    }
+
+    let mut icns = Vec::with_capacity(body.len() + 8);
+    icns.extend_from_slice(b"icns");
+    icns.extend_from_slice(&(body.len() as u32 + 8).to_be_bytes());
+    icns.extend_from_slice(&body);
+
+    Ok(icns)
+}
+
+/// Square icon sizes written for a PWA web app manifest
+const PWA_SIZES: [u32; 9] = [48, 72, 96, 128, 144, 152, 192, 384, 512];
+
+/// Padding for maskable icons, keeping the design inside the 80% safe zone circle
+const MASKABLE_PADDING: f64 = 0.1;
+
+/// A single rendered web app manifest icon
+#[derive(Debug, Clone)]
+pub struct PwaIcon {
+    pub file_name: String,
+    pub size: u32,
+    pub maskable: bool,
+    pub data: Vec<u8>,
+}
+
+/// Generates the full set of PWA manifest icons, including maskable variants
+pub fn generate_pwa_icons(design: &GeneratedDesign) -> Result<Vec<PwaIcon>> {
+    let largest = PWA_SIZES[PWA_SIZES.len() - 1];
+
+    // Parse the regular and padded documents once and rasterize them at every size
+    let any_tree = png::parse_svg(&svg::design_to_svg(
+        design,
+        largest,
+        largest,
+        &svg::SvgOptions::default(),
+    )?)?;
+    let maskable_options = svg::SvgOptions {
+        padding: MASKABLE_PADDING,
+        ..svg::SvgOptions::default()
+    };
+    let maskable_tree = png::parse_svg(&svg::design_to_svg(
+        design,
+        largest,
+        largest,
+        &maskable_options,
+    )?)?;
+
+    let mut icons = Vec::with_capacity(PWA_SIZES.len() * 2);
+
+    for (tree, maskable) in [(&any_tree, false), (&maskable_tree, true)] {
+        for size in PWA_SIZES {
+            let prefix = if maskable { "icon-maskable" } else { "icon" };
+            icons.push(PwaIcon {
+                file_name: format!("{}-{}x{}.png", prefix, size, size),
+                size,
+                maskable,
+                data: png::render_tree_to_png(tree, size, size)?,
+            });
+        }
+    }
+
+    Ok(icons)
+}
+
+/// Builds the `icons` array snippet for a manifest.json referencing the given icons
+pub fn pwa_manifest(icons: &[PwaIcon]) -> serde_json::Value {
+    let entries: Vec<serde_json::Value> = icons
+        .iter()
+        .map(|icon| {
+            serde_json::json!({
+                "src": icon.file_name,
+                "sizes": format!("{}x{}", icon.size, icon.size),
+                "type": "image/png",
+                "purpose": if icon.maskable { "maskable" } else { "any" },
+            })
+        })
+        .collect();
+
+    serde_json::json!({ "icons": entries })
+}
+
+/// Writes the PWA icon set and a manifest.json snippet into a directory
+pub fn save_pwa_icons<P: AsRef<Path>>(design: &GeneratedDesign, dir: P) -> Result<Vec<PwaIcon>> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+
+    let icons = generate_pwa_icons(design)?;
+    for icon in &icons {
+        fs::write(dir.join(&icon.file_name), &icon.data)?;
+    }
+
+    let manifest = serde_json::to_string_pretty(&pwa_manifest(&icons))?;
+    fs::write(dir.join("manifest.json"), manifest)?;
+
+    Ok(icons)
+}
+
+/// Saves icon data to a file
+pub fn save_icon<P: AsRef<Path>>(icon_data: &[u8], path: P) -> Result<()> {
+    fs::write(path, icon_data)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::GeneratorConfig;
+
+    #[test]
+    fn test_icns_generation() {
+        let design = GeneratorConfig::new(4, 2, 0.8, Some(42)).generate().unwrap();
+
+        let icns = generate_icns(&design).unwrap();
+
+        // Header magic and total length
+        assert_eq!(&icns[0..4], b"icns");
+        let total = u32::from_be_bytes([icns[4], icns[5], icns[6], icns[7]]) as usize;
+        assert_eq!(total, icns.len());
+
+        // Walk the entries and make sure each one holds a PNG
+        let mut offset = 8;
+        let mut entries = 0;
+        while offset < icns.len() {
+            let len = u32::from_be_bytes([
+                icns[offset + 4],
+                icns[offset + 5],
+                icns[offset + 6],
+                icns[offset + 7],
+            ]) as usize;
+            assert_eq!(
+                &icns[offset + 8..offset + 16],
+                &[137, 80, 78, 71, 13, 10, 26, 10]
+            );
+            offset += len;
+            entries += 1;
+        }
+
+        assert_eq!(offset, icns.len());
+        assert_eq!(entries, ICNS_ENTRIES.len());
+    }
+
+    #[test]
+    fn test_pwa_icons() {
+        let design = GeneratorConfig::new(4, 2, 0.8, Some(42)).generate().unwrap();
+
+        let icons = generate_pwa_icons(&design).unwrap();
+        assert_eq!(icons.len(), PWA_SIZES.len() * 2);
+        assert_eq!(icons.iter().filter(|icon| icon.maskable).count(), PWA_SIZES.len());
+
+        let manifest = pwa_manifest(&icons);
+        let entries = manifest["icons"].as_array().unwrap();
+        assert_eq!(entries.len(), icons.len());
+        assert_eq!(entries[0]["src"], "icon-48x48.png");
+        assert_eq!(entries[0]["sizes"], "48x48");
+        assert_eq!(entries[0]["purpose"], "any");
+        assert!(entries.iter().any(|entry| entry["purpose"] == "maskable"));
+    }
+}
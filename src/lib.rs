@@ -1,10 +1,44 @@
+pub mod animation;
+pub mod ascii;
+pub mod badge;
+#[cfg(feature = "cli")]
 pub mod cli;
-pub mod generator;
+pub mod code;
+#[cfg(feature = "png")]
+pub mod icon;
+#[cfg(feature = "png")]
 pub mod png;
+#[cfg(feature = "report")]
+pub mod print;
+pub mod render;
+#[cfg(feature = "report")]
+pub mod report;
 pub mod svg;
+pub mod template;
+#[cfg(feature = "png")]
+pub mod trace;
+#[cfg(feature = "cloud")]
+pub mod upload;
 pub mod utils;
+pub mod wallpaper;
+#[cfg(feature = "web")]
 pub mod web;
+#[cfg(feature = "webhook")]
+pub mod webhook;
+#[cfg(feature = "wordmark")]
+pub mod wordmark;
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
-pub use crate::generator::Generator;
+pub use hexalith_core::generator;
+
+pub use crate::code::DesignCode;
+pub use crate::generator::color::{register_theme, unregister_theme};
+pub use crate::generator::{GeneratedDesign, Generator, GeneratorConfig};
+#[cfg(feature = "png")]
+pub use crate::render::render_png;
+pub use crate::render::{render_svg, DesignParams};
+#[cfg(all(feature = "web", feature = "png"))]
+pub use crate::render::render_png_async;
+#[cfg(feature = "web")]
+pub use crate::render::{render_svg_async, set_worker_budget};
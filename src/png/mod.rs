@@ -1,4 +1,4 @@
-use crate::generator::Generator;
+use crate::generator::{GeneratedDesign, Generator};
 use crate::svg;
 use crate::Result;
 use resvg::tiny_skia;
@@ -6,22 +6,55 @@ use resvg::usvg::{self, TreeParsing};
 use std::fs;
 use std::path::Path;
 
+/// Largest width or height that will be rasterized, in pixels
+pub const MAX_DIMENSION: u32 = 16384;
+
 /// Converts an SVG string to PNG data
 pub fn convert_svg_to_png(svg_data: &str, width: u32, height: u32) -> Result<Vec<u8>> {
-    // Parse the SVG string
+    // Parse the SVG string and render it
+    let render_tree = parse_svg(svg_data)?;
+    render_tree_to_png(&render_tree, width, height)
+}
+
+/// Parses an SVG string into a render tree that can be rasterized repeatedly
+pub fn parse_svg(svg_data: &str) -> Result<resvg::Tree> {
     let opt = usvg::Options::default();
     let tree = usvg::Tree::from_str(svg_data, &opt)?;
+    Ok(resvg::Tree::from_usvg(&tree))
+}
+
+/// Rasterizes an already parsed render tree to PNG data, scaling it to fit the given size
+pub fn render_tree_to_png(tree: &resvg::Tree, width: u32, height: u32) -> Result<Vec<u8>> {
+    Ok(render_tree_to_pixmap(tree, width, height)?.encode_png()?)
+}
+
+/// Rasterizes an already parsed render tree to a premultiplied RGBA pixmap
+pub fn render_tree_to_pixmap(
+    tree: &resvg::Tree,
+    width: u32,
+    height: u32,
+) -> Result<tiny_skia::Pixmap> {
+    if width > MAX_DIMENSION || height > MAX_DIMENSION {
+        return Err(format!(
+            "Cannot rasterize {}x{}: width and height are limited to {} pixels",
+            width, height, MAX_DIMENSION
+        )
+        .into());
+    }
 
     // Create a Skia surface to render on
     let pixmap_size = tiny_skia::IntSize::from_wh(width, height).ok_or("Invalid dimensions")?;
     let mut pixmap = tiny_skia::Pixmap::new(pixmap_size.width(), pixmap_size.height())
         .ok_or("Failed to create Pixmap")?;
 
-    // Set up the renderer and render the SVG
-    let render_tree = resvg::Tree::from_usvg(&tree);
-    render_tree.render(tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    // Scale the document so its declared size matches the target pixmap
+    let transform = tiny_skia::Transform::from_scale(
+        width as f32 / tree.size.width(),
+        height as f32 / tree.size.height(),
+    );
+    tree.render(transform, &mut pixmap.as_mut());
 
-    Ok(pixmap.encode_png()?)
+    Ok(pixmap)
 }
 
 /// Generates a PNG from a logo generator
@@ -33,6 +66,28 @@ pub fn generate_png(generator: &Generator, width: u32, height: u32) -> Result<Ve
     convert_svg_to_png(&svg_data, width, height)
 }
 
+/// Generates a PNG from a logo generator using the given SVG rendering options
+pub fn generate_png_with_options(
+    generator: &Generator,
+    width: u32,
+    height: u32,
+    options: &svg::SvgOptions,
+) -> Result<Vec<u8>> {
+    let svg_data = svg::generate_svg_with_options(generator, width, height, options)?;
+    convert_svg_to_png(&svg_data, width, height)
+}
+
+/// Renders an already generated design to PNG data
+pub fn design_to_png(
+    design: &GeneratedDesign,
+    width: u32,
+    height: u32,
+    options: &svg::SvgOptions,
+) -> Result<Vec<u8>> {
+    let svg_data = svg::design_to_svg(design, width, height, options)?;
+    convert_svg_to_png(&svg_data, width, height)
+}
+
 /// Saves PNG data to a file
 pub fn save_png<P: AsRef<Path>>(png_data: &[u8], path: P) -> Result<()> {
     fs::write(path, png_data)?;
@@ -59,4 +114,33 @@ mod tests {
         assert!(!png_data.is_empty());
         assert_eq!(&png_data[0..8], &[137, 80, 78, 71, 13, 10, 26, 10]); // PNG magic number
     }
+
+    #[test]
+    fn test_rasterize_cells_matches_render() {
+        use crate::generator::color::ColorManager;
+        use crate::generator::GeneratorConfig;
+
+        let design = GeneratorConfig::new(5, 4, 1.0, Some(31)).with_allow_overlap(false).generate().unwrap();
+        let options = svg::SvgOptions {
+            background: Some("#FFFFFF".to_string()),
+            ..svg::SvgOptions::default()
+        };
+        let (width, height) = (96, 64);
+        let svg_data = svg::design_to_svg(&design, width, height, &options).unwrap();
+        let pixmap = render_tree_to_pixmap(&parse_svg(&svg_data).unwrap(), width, height).unwrap();
+
+        let owners = design.rasterize_cells(width, height);
+        let matching = owners
+            .iter()
+            .zip(pixmap.pixels())
+            .filter(|(owner, pixel)| {
+                let expected = owner.map_or((255, 255, 255), |shape| {
+                    ColorManager::hex_to_rgb(design.shapes()[shape].fill())
+                });
+                (pixel.red(), pixel.green(), pixel.blue()) == expected
+            })
+            .count();
+        // Only antialiased pixels along the outlines may differ
+        assert!(matching * 10 >= owners.len() * 9, "{} of {} pixels match", matching, owners.len());
+    }
 }
@@ -0,0 +1,344 @@
+use crate::generator::grid::Point;
+use crate::generator::GeneratedDesign;
+use crate::report;
+use crate::svg;
+use crate::Result;
+use std::fs;
+use std::path::Path;
+use ::svg::node::element::path::Data;
+use ::svg::node::element::{Circle, Group, Path as SvgPath, Rectangle, Text as SvgText};
+use ::svg::node::Text as TextNode;
+use ::svg::Document;
+
+mod pdf;
+
+/// Space between the page edge and the trimmed area, where marks are drawn, in mm
+const MARK_MARGIN: f64 = 12.0;
+
+/// Side of one color swatch, in mm
+const SWATCH_SIZE: f64 = 16.0;
+
+/// Height of swatch labels, in mm
+const LABEL_SIZE: f64 = 3.0;
+
+/// Width of crop and registration mark lines, in mm
+const MARK_STROKE: f64 = 0.25;
+
+/// Radius of the registration targets, in mm
+const REGISTRATION_RADIUS: f64 = MARK_MARGIN / 4.0;
+
+/// Standard paper sizes for print proofs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PageSize {
+    #[default]
+    A4,
+    A3,
+}
+
+impl PageSize {
+    /// Names accepted by [`PageSize::from_str`](std::str::FromStr)
+    pub fn names() -> Vec<&'static str> {
+        vec!["A4", "A3"]
+    }
+
+    /// Portrait width and height in millimetres
+    pub fn dimensions(&self) -> (f64, f64) {
+        match self {
+            PageSize::A4 => (210.0, 297.0),
+            PageSize::A3 => (297.0, 420.0),
+        }
+    }
+}
+
+impl std::fmt::Display for PageSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PageSize::A4 => write!(f, "A4"),
+            PageSize::A3 => write!(f, "A3"),
+        }
+    }
+}
+
+impl std::str::FromStr for PageSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "A4" => Ok(PageSize::A4),
+            "A3" => Ok(PageSize::A3),
+            _ => Err(format!(
+                "Unknown page size '{}' (expected one of: {})",
+                s,
+                Self::names().join(", ")
+            )),
+        }
+    }
+}
+
+/// What goes on a print proof besides the logo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PrintOptions {
+    pub page: PageSize,
+    /// Draw crop marks at the corners and registration targets on each side
+    pub crop_marks: bool,
+    /// Draw a strip of the design's colors, labelled with their hex values
+    pub swatches: bool,
+}
+
+/// A labelled square of one design color
+#[derive(Debug, Clone, PartialEq)]
+struct Swatch {
+    x: f64,
+    y: f64,
+    color: String,
+}
+
+/// Positions of everything on the page, in millimetres from the top left
+#[derive(Debug, Clone, PartialEq)]
+struct PageLayout {
+    width: f64,
+    height: f64,
+    /// Center and side of the square the 200-unit design is scaled into
+    logo_center: Point,
+    logo_size: f64,
+    swatches: Vec<Swatch>,
+    crop_lines: Vec<(Point, Point)>,
+    registration: Vec<Point>,
+}
+
+impl PageLayout {
+    fn new(design: &GeneratedDesign, options: &PrintOptions) -> Self {
+        let (width, height) = options.page.dimensions();
+        let trim_width = width - 2.0 * MARK_MARGIN;
+        let trim_height = height - 2.0 * MARK_MARGIN;
+
+        // Sit slightly above the middle to leave room for the swatches
+        let logo_size = trim_width.min(trim_height) * 0.6;
+        let logo_center = Point::new(width / 2.0, MARK_MARGIN + trim_height * 0.42);
+
+        let mut swatches = Vec::new();
+        if options.swatches {
+            let colors: Vec<String> = report::palette_report(design)
+                .colors
+                .into_iter()
+                .map(|entry| entry.hex)
+                .collect();
+            let gap = SWATCH_SIZE * 0.25;
+            let strip = colors.len() as f64 * (SWATCH_SIZE + gap) - gap;
+            let top = logo_center.y + logo_size / 2.0 + SWATCH_SIZE;
+            for (index, color) in colors.into_iter().enumerate() {
+                swatches.push(Swatch {
+                    x: (width - strip) / 2.0 + index as f64 * (SWATCH_SIZE + gap),
+                    y: top,
+                    color,
+                });
+            }
+        }
+
+        let mut crop_lines = Vec::new();
+        let mut registration = Vec::new();
+        if options.crop_marks {
+            // Marks stop short of the trim box so none end up on the final piece
+            let (near, far) = (3.0, MARK_MARGIN - 2.0);
+            for &(x, dx) in &[(MARK_MARGIN, -1.0), (width - MARK_MARGIN, 1.0)] {
+                for &(y, dy) in &[(MARK_MARGIN, -1.0), (height - MARK_MARGIN, 1.0)] {
+                    crop_lines.push((
+                        Point::new(x + dx * near, y),
+                        Point::new(x + dx * far, y),
+                    ));
+                    crop_lines.push((
+                        Point::new(x, y + dy * near),
+                        Point::new(x, y + dy * far),
+                    ));
+                }
+            }
+
+            let middle = MARK_MARGIN / 2.0;
+            registration = vec![
+                Point::new(width / 2.0, middle),
+                Point::new(width / 2.0, height - middle),
+                Point::new(middle, height / 2.0),
+                Point::new(width - middle, height / 2.0),
+            ];
+        }
+
+        Self {
+            width,
+            height,
+            logo_center,
+            logo_size,
+            swatches,
+            crop_lines,
+            registration,
+        }
+    }
+
+    /// Maps a point in design coordinates onto the page
+    fn place(&self, point: &Point) -> Point {
+        let scale = self.logo_size / 200.0;
+        Point::new(
+            self.logo_center.x + point.x * scale,
+            self.logo_center.y + point.y * scale,
+        )
+    }
+}
+
+/// Renders a print proof as an SVG document sized in millimetres
+pub fn print_svg(design: &GeneratedDesign, options: &PrintOptions) -> Result<String> {
+    let layout = PageLayout::new(design, options);
+
+    let logo = svg::design_group(design).set(
+        "transform",
+        format!(
+            "translate({} {}) scale({})",
+            layout.logo_center.x,
+            layout.logo_center.y,
+            layout.logo_size / 200.0
+        ),
+    );
+
+    let mut document = Document::new()
+        .set("viewBox", (0.0, 0.0, layout.width, layout.height))
+        .set("width", format!("{}mm", layout.width))
+        .set("height", format!("{}mm", layout.height))
+        .set(svg::CHECKSUM_ATTRIBUTE, format!("{:016x}", design.checksum()))
+        .add(
+            Rectangle::new()
+                .set("width", layout.width)
+                .set("height", layout.height)
+                .set("fill", "#FFFFFF"),
+        )
+        .add(logo);
+
+    if !layout.swatches.is_empty() {
+        let mut strip = Group::new()
+            .set("class", "swatches")
+            .set("font-family", "Helvetica, Arial, sans-serif")
+            .set("font-size", LABEL_SIZE)
+            .set("text-anchor", "middle");
+        for swatch in &layout.swatches {
+            strip = strip
+                .add(
+                    Rectangle::new()
+                        .set("x", swatch.x)
+                        .set("y", swatch.y)
+                        .set("width", SWATCH_SIZE)
+                        .set("height", SWATCH_SIZE)
+                        .set("fill", swatch.color.as_str()),
+                )
+                .add(
+                    SvgText::new()
+                        .set("x", swatch.x + SWATCH_SIZE / 2.0)
+                        .set("y", swatch.y + SWATCH_SIZE + LABEL_SIZE * 1.5)
+                        .add(TextNode::new(swatch.color.as_str())),
+                );
+        }
+        document = document.add(strip);
+    }
+
+    if !layout.crop_lines.is_empty() || !layout.registration.is_empty() {
+        let radius = REGISTRATION_RADIUS;
+        let mut data = Data::new();
+        for (start, end) in &layout.crop_lines {
+            data = data.move_to((start.x, start.y)).line_to((end.x, end.y));
+        }
+        for center in &layout.registration {
+            data = data
+                .move_to((center.x - radius * 1.5, center.y))
+                .line_to((center.x + radius * 1.5, center.y))
+                .move_to((center.x, center.y - radius * 1.5))
+                .line_to((center.x, center.y + radius * 1.5));
+        }
+
+        let mut marks = Group::new()
+            .set("class", "marks")
+            .set("fill", "none")
+            .set("stroke", "#000000")
+            .set("stroke-width", MARK_STROKE)
+            .add(SvgPath::new().set("d", data));
+        for center in &layout.registration {
+            marks = marks.add(
+                Circle::new()
+                    .set("cx", center.x)
+                    .set("cy", center.y)
+                    .set("r", radius),
+            );
+        }
+        document = document.add(marks);
+    }
+
+    Ok(document.to_string())
+}
+
+/// Renders a print proof as a single-page vector PDF
+pub fn print_pdf(design: &GeneratedDesign, options: &PrintOptions) -> Result<Vec<u8>> {
+    let layout = PageLayout::new(design, options);
+    Ok(pdf::write(design, &layout))
+}
+
+/// Saves PDF data to a file
+pub fn save_pdf<P: AsRef<Path>>(pdf_data: &[u8], path: P) -> Result<()> {
+    fs::write(path, pdf_data)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::GeneratorConfig;
+
+    #[test]
+    fn test_page_size() {
+        assert_eq!("a3".parse::<PageSize>().unwrap(), PageSize::A3);
+        assert_eq!(PageSize::A4.dimensions(), (210.0, 297.0));
+        assert!("letter".parse::<PageSize>().is_err());
+    }
+
+    #[test]
+    fn test_print_svg() {
+        let design = GeneratorConfig::new(4, 3, 0.8, Some(2)).generate().unwrap();
+
+        let plain = print_svg(&design, &PrintOptions::default()).unwrap();
+        assert!(plain.contains("width=\"210mm\""));
+        assert!(!plain.contains("class=\"marks\""));
+
+        let options = PrintOptions {
+            page: PageSize::A3,
+            crop_marks: true,
+            swatches: true,
+        };
+        let proof = print_svg(&design, &options).unwrap();
+        assert!(proof.contains("height=\"420mm\""));
+        assert!(proof.contains("class=\"marks\""));
+        assert_eq!(proof.matches("<circle").count(), 4);
+        // Each color fills a shape and a swatch, and labels the swatch
+        for shape in design.shapes() {
+            assert!(proof.matches(shape.fill()).count() >= 3);
+        }
+    }
+
+    #[test]
+    fn test_print_pdf() {
+        let design = GeneratorConfig::new(4, 3, 0.8, Some(2)).generate().unwrap();
+        let options = PrintOptions {
+            crop_marks: true,
+            swatches: true,
+            ..PrintOptions::default()
+        };
+        let pdf = print_pdf(&design, &options).unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.trim_end().ends_with("%%EOF"));
+        assert!(text.contains("/MediaBox [0 0 595.28 841.89]"));
+
+        // The cross-reference table points at each object
+        let xref = text.rfind("startxref").unwrap();
+        let offset: usize = text[xref + 9..].trim().lines().next().unwrap().parse().unwrap();
+        assert!(text[offset..].starts_with("xref"));
+        for object in 1..=5 {
+            let marker = format!("\n{} 0 obj", object);
+            assert!(text.contains(&marker));
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use super::{PageLayout, LABEL_SIZE, MARK_STROKE, REGISTRATION_RADIUS, SWATCH_SIZE};
+use crate::generator::color::ColorManager;
+use crate::generator::GeneratedDesign;
+use crate::svg;
+use std::fmt::Write;
+
+/// PDF points per millimetre
+const POINTS_PER_MM: f64 = 72.0 / 25.4;
+
+/// Average advance of Helvetica's digits and `#`, as a fraction of the font size
+const LABEL_ADVANCE: f64 = 0.556;
+
+/// Bezier handle length for drawing a quarter circle
+const KAPPA: f64 = 0.552_284_75;
+
+/// Writes the page as a minimal single-page PDF 1.4 document
+///
+/// Everything is vector: shapes are filled polygons with their opacity set
+/// through graphics states, and labels use the built-in Helvetica font.
+pub(super) fn write(design: &GeneratedDesign, layout: &PageLayout) -> Vec<u8> {
+    let (content, alphas) = content_stream(design, layout);
+
+    let graphics_states: String = alphas
+        .iter()
+        .enumerate()
+        .map(|(index, alpha)| format!("/GS{} << /Type /ExtGState /ca {} >> ", index, number(*alpha)))
+        .collect();
+
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] /Contents 4 0 R \
+             /Resources << /Font << /F1 5 0 R >> /ExtGState << {}>> >> >>",
+            layout.width * POINTS_PER_MM,
+            layout.height * POINTS_PER_MM,
+            graphics_states
+        ),
+        format!("<< /Length {} >>\nstream\n{}endstream", content.len(), content),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+    ];
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        let _ = write!(pdf, "{} 0 obj\n{}\nendobj\n", index + 1, object);
+    }
+
+    let xref = pdf.len();
+    let _ = write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(pdf, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        pdf,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    );
+
+    pdf.into_bytes()
+}
+
+/// Drawing operators for the page, plus the distinct fill opacities they use
+fn content_stream(design: &GeneratedDesign, layout: &PageLayout) -> (String, Vec<f32>) {
+    let mut out = String::new();
+    let mut alphas: Vec<f32> = Vec::new();
+
+    // Work in millimetres from the top left, like the SVG output
+    let _ = writeln!(
+        out,
+        "{} 0 0 {} 0 {} cm",
+        number(POINTS_PER_MM as f32),
+        number(-POINTS_PER_MM as f32),
+        number((layout.height * POINTS_PER_MM) as f32)
+    );
+
+    for shape in design.shapes() {
+        let alpha = shape.opacity * ColorManager::alpha(shape.fill());
+        let state = match alphas.iter().position(|&a| a == alpha) {
+            Some(index) => index,
+            None => {
+                alphas.push(alpha);
+                alphas.len() - 1
+            }
+        };
+
+        let _ = writeln!(out, "q /GS{} gs {}", state, fill_color(shape.fill()));
+        for outline in svg::shape_outlines(design.grid(), &shape.cells) {
+            for (index, point) in outline.iter().enumerate() {
+                let point = layout.place(point);
+                let operator = if index == 0 { "m" } else { "l" };
+                let _ = writeln!(out, "{} {} {}", mm(point.x), mm(point.y), operator);
+            }
+            out.push_str("h\n");
+        }
+        out.push_str("f Q\n");
+    }
+
+    for swatch in &layout.swatches {
+        let _ = writeln!(
+            out,
+            "{} {} {} {} {} re f",
+            fill_color(&swatch.color),
+            mm(swatch.x),
+            mm(swatch.y),
+            mm(SWATCH_SIZE),
+            mm(SWATCH_SIZE)
+        );
+
+        // Flip the text matrix back so glyphs aren't drawn upside down
+        let label_width = swatch.color.len() as f64 * LABEL_ADVANCE * LABEL_SIZE;
+        let _ = writeln!(
+            out,
+            "BT /F1 {} Tf 0 g 1 0 0 -1 {} {} Tm ({}) Tj ET",
+            mm(LABEL_SIZE),
+            mm(swatch.x + (SWATCH_SIZE - label_width) / 2.0),
+            mm(swatch.y + SWATCH_SIZE + LABEL_SIZE * 1.5),
+            swatch.color
+        );
+    }
+
+    if !layout.crop_lines.is_empty() || !layout.registration.is_empty() {
+        let _ = writeln!(out, "{} w 0 G", mm(MARK_STROKE));
+        for (start, end) in &layout.crop_lines {
+            let _ = writeln!(out, "{} {} m {} {} l S", mm(start.x), mm(start.y), mm(end.x), mm(end.y));
+        }
+
+        let r = REGISTRATION_RADIUS;
+        let k = r * KAPPA;
+        for c in &layout.registration {
+            let _ = writeln!(
+                out,
+                "{} {} m {} {} l {} {} m {} {} l S",
+                mm(c.x - r * 1.5),
+                mm(c.y),
+                mm(c.x + r * 1.5),
+                mm(c.y),
+                mm(c.x),
+                mm(c.y - r * 1.5),
+                mm(c.x),
+                mm(c.y + r * 1.5)
+            );
+            let _ = writeln!(
+                out,
+                "{} {} m {} {} {} {} {} {} c {} {} {} {} {} {} c {} {} {} {} {} {} c {} {} {} {} {} {} c S",
+                mm(c.x + r), mm(c.y),
+                mm(c.x + r), mm(c.y + k), mm(c.x + k), mm(c.y + r), mm(c.x), mm(c.y + r),
+                mm(c.x - k), mm(c.y + r), mm(c.x - r), mm(c.y + k), mm(c.x - r), mm(c.y),
+                mm(c.x - r), mm(c.y - k), mm(c.x - k), mm(c.y - r), mm(c.x), mm(c.y - r),
+                mm(c.x + k), mm(c.y - r), mm(c.x + r), mm(c.y - k), mm(c.x + r), mm(c.y)
+            );
+        }
+    }
+
+    (out, alphas)
+}
+
+/// The non-stroking RGB operator for a hex color, ignoring its alpha
+fn fill_color(hex: &str) -> String {
+    let (r, g, b) = ColorManager::hex_to_rgb(hex);
+    format!(
+        "{} {} {} rg",
+        number(r as f32 / 255.0),
+        number(g as f32 / 255.0),
+        number(b as f32 / 255.0)
+    )
+}
+
+/// A page coordinate in millimetres, to a hundredth of a millimetre
+fn mm(value: f64) -> String {
+    number(((value * 100.0).round() / 100.0) as f32)
+}
+
+/// Formats a number without trailing zeros, as PDF operands are usually written
+fn number(value: f32) -> String {
+    let text = format!("{:.4}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
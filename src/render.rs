@@ -0,0 +1,174 @@
+use crate::generator::color::parse_color;
+use crate::generator::{AlgoVersion, GeneratedDesign, GeneratorConfig, Theme};
+use crate::svg;
+use crate::Result;
+#[cfg(feature = "web")]
+use std::sync::{Arc, OnceLock};
+#[cfg(feature = "web")]
+use tokio::sync::Semaphore;
+
+/// Renders allowed at once by the async helpers and the built-in server together
+#[cfg(feature = "web")]
+static WORKER_BUDGET: OnceLock<Arc<Semaphore>> = OnceLock::new();
+
+/// Everything a one-shot render needs; the defaults match the CLI's
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesignParams {
+    /// Seed for a reproducible design; random when `None`
+    pub seed: Option<u64>,
+    /// Theme name, or a blend such as `mesos:blues:0.4`
+    pub theme: String,
+    /// Number of shapes (1-25)
+    pub shapes: u8,
+    /// Grid density (2-8)
+    pub grid_size: u8,
+    /// Shape opacity (0.0-1.0)
+    pub opacity: f32,
+    /// Allow shapes to overlap with blended colors
+    pub overlap: bool,
+    /// Output width in pixels
+    pub width: u32,
+    /// Output height in pixels
+    pub height: u32,
+    /// Background color as hex, a CSS name, rgb() or hsl(); transparent when `None`
+    pub background: Option<String>,
+    /// Version of the generation algorithm; pin one to keep a seed's design across upgrades
+    pub algo_version: AlgoVersion,
+}
+
+impl Default for DesignParams {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            theme: "mesos".to_string(),
+            shapes: 4,
+            grid_size: 4,
+            opacity: 0.8,
+            overlap: true,
+            width: 512,
+            height: 512,
+            background: None,
+            algo_version: AlgoVersion::LATEST,
+        }
+    }
+}
+
+impl DesignParams {
+    /// The design these parameters describe, rejecting out-of-range values instead of clamping them
+    pub fn design(&self) -> Result<GeneratedDesign> {
+        let theme = self.theme.parse::<Theme>()?;
+        GeneratorConfig::try_new(self.grid_size, self.shapes, self.opacity, self.seed)?
+            .with_theme(theme)
+            .with_allow_overlap(self.overlap)
+            .with_algo_version(self.algo_version)
+            .generate()
+    }
+
+    /// SVG options carrying the background
+    pub fn svg_options(&self) -> Result<svg::SvgOptions> {
+        let background = match &self.background {
+            Some(color) => Some(parse_color(color)?),
+            None => None,
+        };
+        Ok(svg::SvgOptions {
+            background,
+            ..svg::SvgOptions::default()
+        })
+    }
+}
+
+/// Renders the design `params` describe as an SVG document
+///
+/// The simplest way to embed Hexalith: parameters in, SVG out, with no
+/// [`Generator`](crate::Generator) state to manage.
+pub fn render_svg(params: &DesignParams) -> Result<String> {
+    svg::design_to_svg(&params.design()?, params.width, params.height, &params.svg_options()?)
+}
+
+/// Renders the design `params` describe as PNG data
+#[cfg(feature = "png")]
+pub fn render_png(params: &DesignParams) -> Result<Vec<u8>> {
+    crate::png::design_to_png(&params.design()?, params.width, params.height, &params.svg_options()?)
+}
+
+/// Sets how many renders may run at once, shared by the async helpers and the built-in server
+///
+/// Call it before the first async render or server start; the budget is fixed once in use.
+/// Without it, one render per core may run.
+#[cfg(feature = "web")]
+pub fn set_worker_budget(workers: usize) -> Result<()> {
+    WORKER_BUDGET
+        .set(Arc::new(Semaphore::new(workers.max(1))))
+        .map_err(|_| "The worker budget is already in use".into())
+}
+
+/// The permits the async helpers and the built-in server draw from
+#[cfg(feature = "web")]
+pub fn worker_budget() -> Arc<Semaphore> {
+    WORKER_BUDGET
+        .get_or_init(|| {
+            // One render per core keeps the blocking pool busy without oversubscribing it
+            let cores = std::thread::available_parallelism().map_or(4, |n| n.get());
+            Arc::new(Semaphore::new(cores))
+        })
+        .clone()
+}
+
+/// [`render_svg`] on tokio's blocking thread pool, so it never stalls the async runtime
+#[cfg(feature = "web")]
+pub async fn render_svg_async(params: &DesignParams) -> Result<String> {
+    let params = params.clone();
+    crate::web::render::run_blocking(&worker_budget(), move || render_svg(&params)).await
+}
+
+/// [`render_png`] on tokio's blocking thread pool, so it never stalls the async runtime
+#[cfg(all(feature = "web", feature = "png"))]
+pub async fn render_png_async(params: &DesignParams) -> Result<Vec<u8>> {
+    let params = params.clone();
+    crate::web::render::run_blocking(&worker_budget(), move || render_png(&params)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_svg() {
+        let params = DesignParams {
+            seed: Some(42),
+            theme: "blues".to_string(),
+            background: Some("white".to_string()),
+            ..DesignParams::default()
+        };
+        let svg = render_svg(&params).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("width=\"512\""));
+        // The same parameters always give the same document
+        assert_eq!(render_svg(&params).unwrap(), svg);
+        assert_ne!(render_svg(&DesignParams { seed: Some(43), ..params.clone() }).unwrap(), svg);
+
+        #[cfg(feature = "png")]
+        assert!(render_png(&params).unwrap().starts_with(b"\x89PNG"));
+
+        assert!(render_svg(&DesignParams { shapes: 0, ..params.clone() }).is_err());
+        assert!(render_svg(&DesignParams { theme: "plaid".to_string(), ..params.clone() })
+            .unwrap_err()
+            .to_string()
+            .contains("Unknown theme"));
+        assert!(render_svg(&DesignParams { background: Some("#12".to_string()), ..params }).is_err());
+    }
+
+    #[cfg(feature = "web")]
+    #[tokio::test]
+    async fn test_render_svg_async() {
+        let params = DesignParams { seed: Some(42), ..DesignParams::default() };
+        assert_eq!(render_svg_async(&params).await.unwrap(), render_svg(&params).unwrap());
+        assert!(render_svg_async(&DesignParams { grid_size: 1, ..params }).await.is_err());
+
+        // The built-in server draws from the same budget, which is fixed once in use
+        let budget = worker_budget();
+        assert!(std::ptr::eq(crate::web::state::AppState::default().render_permits(), &*budget));
+        assert!(set_worker_budget(2).is_err());
+        assert_eq!(budget.available_permits(), worker_budget().available_permits());
+    }
+}
@@ -0,0 +1,283 @@
+use crate::generator::color::ColorManager;
+use crate::generator::GeneratedDesign;
+use crate::Result;
+use maud::{html, Markup, PreEscaped};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// Everything a brand guideline needs to know about the colors of a design
+#[derive(Debug, Clone, Serialize)]
+pub struct PaletteReport {
+    pub theme: String,
+    pub seed: Option<u64>,
+    /// Every color the theme can pick from
+    pub theme_palette: Vec<String>,
+    /// Distinct colors used by the design, in order of first use
+    pub colors: Vec<ColorEntry>,
+    pub shapes: Vec<ShapeColor>,
+    pub blends: Vec<BlendEntry>,
+    /// WCAG contrast ratio between each pair of entries in `colors`
+    pub contrast: Vec<Vec<f64>>,
+}
+
+/// A distinct color used by the design
+#[derive(Debug, Clone, Serialize)]
+pub struct ColorEntry {
+    pub hex: String,
+    pub rgb: [u8; 3],
+    pub nearest_named: String,
+    /// Indices of the shapes filled with this color
+    pub shapes: Vec<usize>,
+}
+
+/// The fill of a single shape
+#[derive(Debug, Clone, Serialize)]
+pub struct ShapeColor {
+    pub index: usize,
+    /// The shape's stable id
+    pub id: usize,
+    pub color: String,
+    pub opacity: f32,
+    pub cells: usize,
+    /// The color actually seen when the shape is drawn over white
+    pub on_white: String,
+}
+
+/// A color mixed from two overlapping shapes
+#[derive(Debug, Clone, Serialize)]
+pub struct BlendEntry {
+    pub shape: usize,
+    pub color: String,
+    pub sources: [String; 2],
+    pub source_shapes: [usize; 2],
+}
+
+/// Collects the palette report for a generated design
+pub fn palette_report(design: &GeneratedDesign) -> PaletteReport {
+    let config = design.config();
+    let shapes = design.shapes();
+
+    let mut colors: Vec<ColorEntry> = Vec::new();
+    for (index, shape) in shapes.iter().enumerate() {
+        match colors.iter_mut().find(|entry| entry.hex == shape.fill()) {
+            Some(entry) => entry.shapes.push(index),
+            None => {
+                let (r, g, b) = ColorManager::hex_to_rgb(shape.fill());
+                colors.push(ColorEntry {
+                    hex: shape.fill().to_string(),
+                    rgb: [r, g, b],
+                    nearest_named: ColorManager::nearest_named_color(shape.fill()).to_string(),
+                    shapes: vec![index],
+                });
+            }
+        }
+    }
+
+    let contrast = colors
+        .iter()
+        .map(|a| {
+            colors
+                .iter()
+                .map(|b| (ColorManager::color_contrast(&a.hex, &b.hex) * 100.0).round() / 100.0)
+                .collect()
+        })
+        .collect();
+
+    let shape_colors = shapes
+        .iter()
+        .enumerate()
+        .map(|(index, shape)| ShapeColor {
+            index,
+            id: shape.id,
+            color: shape.fill().to_string(),
+            opacity: shape.opacity,
+            cells: shape.cell_count(),
+            on_white: ColorManager::blend_colors("#FFFFFF", shape.fill(), shape.opacity),
+        })
+        .collect();
+
+    let blends = design
+        .blends()
+        .iter()
+        .map(|blend| BlendEntry {
+            shape: blend.shape,
+            color: shapes[blend.shape].fill().to_string(),
+            sources: blend.sources.map(|source| shapes[source].fill().to_string()),
+            source_shapes: blend.sources,
+        })
+        .collect();
+
+    PaletteReport {
+        theme: config.theme().to_string(),
+        seed: config.seed(),
+        theme_palette: ColorManager::theme_palette(&config.theme()),
+        colors,
+        shapes: shape_colors,
+        blends,
+        contrast,
+    }
+}
+
+impl PaletteReport {
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Renders the report as a standalone HTML page with color swatches
+    pub fn to_html(&self) -> String {
+        let swatch = |hex: &str| -> Markup {
+            html! {
+                span class="swatch" style=(format!("background:{}", hex)) {}
+            }
+        };
+
+        let page = html! {
+            (maud::DOCTYPE)
+            html lang="en" {
+                head {
+                    meta charset="utf-8";
+                    title { "Hexalith Palette Report" }
+                    style { (PreEscaped(REPORT_CSS)) }
+                }
+                body {
+                    h1 { "Palette Report" }
+                    p {
+                        "Theme: " strong { (self.theme) }
+                        @if let Some(seed) = self.seed {
+                            " · Seed: " strong { (seed) }
+                        }
+                    }
+
+                    h2 { "Colors" }
+                    table {
+                        tr { th { "" } th { "Hex" } th { "RGB" } th { "Nearest name" } th { "Shapes" } }
+                        @for color in &self.colors {
+                            tr {
+                                td { (swatch(&color.hex)) }
+                                td { code { (color.hex) } }
+                                td { (format!("{}, {}, {}", color.rgb[0], color.rgb[1], color.rgb[2])) }
+                                td { (color.nearest_named) }
+                                td { (format!("{:?}", color.shapes)) }
+                            }
+                        }
+                    }
+
+                    h2 { "Shapes" }
+                    table {
+                        tr { th { "#" } th { "Fill" } th { "Opacity" } th { "Cells" } th { "On white" } }
+                        @for shape in &self.shapes {
+                            tr {
+                                td { (shape.index) }
+                                td { (swatch(&shape.color)) " " code { (shape.color) } }
+                                td { (shape.opacity) }
+                                td { (shape.cells) }
+                                td { (swatch(&shape.on_white)) " " code { (shape.on_white) } }
+                            }
+                        }
+                    }
+
+                    @if !self.blends.is_empty() {
+                        h2 { "Blends" }
+                        table {
+                            tr { th { "Shape" } th { "Blend" } th { "Sources" } }
+                            @for blend in &self.blends {
+                                tr {
+                                    td { (blend.shape) }
+                                    td { (swatch(&blend.color)) " " code { (blend.color) } }
+                                    td {
+                                        (swatch(&blend.sources[0])) " " code { (blend.sources[0]) }
+                                        " + "
+                                        (swatch(&blend.sources[1])) " " code { (blend.sources[1]) }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    h2 { "Contrast matrix" }
+                    table {
+                        tr {
+                            th { "" }
+                            @for color in &self.colors { th { (swatch(&color.hex)) } }
+                        }
+                        @for (color, row) in self.colors.iter().zip(&self.contrast) {
+                            tr {
+                                th { (swatch(&color.hex)) }
+                                @for ratio in row { td { (format!("{:.2}", ratio)) } }
+                            }
+                        }
+                    }
+
+                    h2 { "Theme palette" }
+                    p {
+                        @for hex in &self.theme_palette { (swatch(hex)) }
+                    }
+                }
+            }
+        };
+
+        page.into_string()
+    }
+}
+
+const REPORT_CSS: &str = "
+    body { font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, Helvetica, Arial, sans-serif; color: #333; margin: 2em; }
+    table { border-collapse: collapse; margin-bottom: 1.5em; }
+    th, td { border: 1px solid #ddd; padding: 4px 10px; text-align: left; }
+    .swatch { display: inline-block; width: 1.2em; height: 1.2em; border: 1px solid #999; vertical-align: middle; }
+";
+
+/// Writes the palette report as JSON or HTML depending on the file extension
+pub fn save_palette_report<P: AsRef<Path>>(design: &GeneratedDesign, path: P) -> Result<()> {
+    let path = path.as_ref();
+    let report = palette_report(design);
+
+    let content = match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => report.to_json()?,
+        Some("html") | Some("htm") => report.to_html(),
+        _ => {
+            return Err(format!(
+                "Unsupported palette report format for {}: use .json or .html",
+                path.display()
+            )
+            .into())
+        }
+    };
+
+    fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::GeneratorConfig;
+
+    #[test]
+    fn test_palette_report() {
+        let design = GeneratorConfig::new(4, 4, 0.8, Some(42))
+            .with_allow_overlap(true)
+            .generate()
+            .unwrap();
+        let report = palette_report(&design);
+
+        assert_eq!(report.shapes.len(), design.shapes().len());
+        assert_eq!(report.contrast.len(), report.colors.len());
+        for (i, row) in report.contrast.iter().enumerate() {
+            assert_eq!(row[i], 1.0);
+        }
+
+        // Every shape belongs to exactly one distinct color
+        let assigned: usize = report.colors.iter().map(|c| c.shapes.len()).sum();
+        assert_eq!(assigned, report.shapes.len());
+
+        for blend in &report.blends {
+            assert_eq!(blend.color, report.shapes[blend.shape].color);
+        }
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["theme"], "mesos");
+        assert!(report.to_html().contains(&report.colors[0].hex));
+    }
+}
@@ -1,49 +1,841 @@
+use crate::generator::color::{self, ColorManager, Theme};
 use crate::generator::grid::Point;
-use crate::generator::{grid::TriangularGrid, Generator};
+use crate::generator::shape::Shape;
+use crate::generator::{grid::TriangularGrid, GeneratedDesign, Generator, GeneratorConfig, Lod};
+use crate::utils;
 use crate::Result;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use svg::node::element::path::Data;
 use svg::node::element::Path as SvgPath;
+use svg::node::element::Rectangle;
+use svg::node::element::Text as SvgText;
+use svg::node::element::{Animate, Circle, Element, Group, Title};
+use svg::node::Text as TextNode;
+use svg::node::Node;
 use svg::Document;
 
+mod stream;
+
+pub use stream::SvgWriter;
+
+/// Optional rendering settings for SVG output
+#[derive(Debug, Clone, Default)]
+pub struct SvgOptions {
+    /// Fraction of the canvas reserved on each side (0.0 to 0.45)
+    pub padding: f64,
+    /// Background fill color, or `None` for a transparent background
+    pub background: Option<String>,
+    /// Draw the grid, cell ids, shape outlines and centroids over the design
+    pub debug_overlay: bool,
+    /// Draw dark "leading" lines along the borders between adjacent shapes
+    pub stained_glass: bool,
+    /// Width in output pixels of a darker outline around each shape, or 0.0 for none
+    pub outline: f64,
+    /// How far, in design units, simplified outlines may stray from the cell edges (0.0 keeps every vertex)
+    pub simplify_tolerance: f64,
+    /// Radius, in design units, of the rounded fillets replacing outline corners (0.0 keeps them sharp)
+    pub corner_radius: f64,
+    /// Round outlines off with Chaikin corner cutting
+    pub smooth_boundary: bool,
+    /// Hexagonal ring drawn over the edge of the design
+    pub border: Option<Border>,
+    /// Canonical parameter string recorded in the document's `<metadata>`
+    pub params: Option<String>,
+    /// Give each shape a `<title>` naming its color and cell count, shown by design tools and screen readers
+    pub editable: bool,
+}
+
+/// A hexagonal band following the edge of the design
+///
+/// Parsed from `WIDTH[,COLOR[,INSET]]`, e.g. `4` or `3,#333333,6`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Border {
+    /// Width of the band in design units, where the hexagon has radius 100
+    pub width: f64,
+    /// Fill color, or `None` for the color of the design's largest shape
+    pub color: Option<String>,
+    /// How far inside the hexagon's edge the band starts; negative draws it outside
+    pub inset: f64,
+}
+
+impl std::str::FromStr for Border {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = |reason: &str| format!("Invalid border '{}' (expected WIDTH[,COLOR[,INSET]]): {}", s, reason);
+        let (width, rest) = match s.split_once(',') {
+            Some((width, rest)) => (width, Some(rest)),
+            None => (s, None),
+        };
+        let width: f64 = width.trim().parse().map_err(|_| invalid("width is not a number"))?;
+        if !width.is_finite() || width <= 0.0 {
+            return Err(invalid("width must be positive"));
+        }
+
+        // Colors such as rgb(1, 2, 3) contain commas, so only a trailing number counts as the inset
+        let (color, inset) = match rest.map(|rest| (rest, rest.rsplit_once(','))) {
+            None => (None, 0.0),
+            Some((_, Some((color, inset)))) if inset.trim().parse::<f64>().is_ok() => {
+                (Some(color), inset.trim().parse::<f64>().unwrap_or(0.0))
+            }
+            Some((rest, _)) => (Some(rest), 0.0),
+        };
+        let color = match color.map(str::trim).filter(|color| !color.is_empty()) {
+            Some(color) => Some(color::parse_color(color).map_err(|e| invalid(&e.to_string()))?),
+            None => None,
+        };
+        if !inset.is_finite() {
+            return Err(invalid("inset must be a number"));
+        }
+
+        Ok(Self { width, color, inset })
+    }
+}
+
+/// Color of the stained-glass leading lines
+const LEADING_COLOR: &str = "#1E1E1E";
+
+/// Root attribute holding the design checksum, used to verify files later
+pub const CHECKSUM_ATTRIBUTE: &str = "data-hexalith-checksum";
+
+/// Attribute carrying each shape path's stable [`Shape::id`]
+pub const SHAPE_ID_ATTRIBUTE: &str = "data-shape-id";
+
+/// Class of the `<metadata>` element holding the parameters a design came from
+pub const PARAMS_METADATA_CLASS: &str = "hexalith-params";
+
 /// Converts the generator output to SVG format
 pub fn generate_svg(generator: &Generator, width: u32, height: u32) -> Result<String> {
-    let grid = match generator.grid() {
-        Some(grid) => grid,
-        None => return Err("Grid not initialized. Call generate() first.".into()),
+    generate_svg_with_options(generator, width, height, &SvgOptions::default())
+}
+
+/// Converts the generator output to SVG format using the given rendering options
+pub fn generate_svg_with_options(
+    generator: &Generator,
+    width: u32,
+    height: u32,
+    options: &SvgOptions,
+) -> Result<String> {
+    match generator.design() {
+        Some(design) => design_to_svg(design, width, height, options),
+        None => Err("Grid not initialized. Call generate() first.".into()),
+    }
+}
+
+/// Renders an already generated design to SVG format
+pub fn design_to_svg(
+    design: &GeneratedDesign,
+    width: u32,
+    height: u32,
+    options: &SvgOptions,
+) -> Result<String> {
+    let mut document = base_document(width, height, options)
+        .set(CHECKSUM_ATTRIBUTE, format!("{:016x}", design.checksum()));
+
+    if let Some(params) = &options.params {
+        let mut metadata = Element::new("metadata");
+        metadata.assign("class", PARAMS_METADATA_CLASS);
+        metadata.append(TextNode::new(params.replace('&', "&amp;")));
+        document = document.add(metadata);
+    }
+
+    // Keep the outline the same number of pixels whatever the output size
+    let extent = 100.0 / (1.0 - 2.0 * options.padding.clamp(0.0, 0.45));
+    let units_per_pixel = extent * 2.0 / width.min(height).max(1) as f64;
+    for layer in design_layers(design, options, options.outline * units_per_pixel) {
+        document = document.add(layer);
+    }
+
+    Ok(document.to_string())
+}
+
+/// One SVG per shape, on the same canvas and viewBox as [`design_to_svg`]
+///
+/// Each layer holds only its shape's fill, in a group with the id
+/// `shape-<id>`, on a transparent background, so the layers stack back into
+/// the design; outlines, leading and the border stay in the composite. Shapes
+/// without cells still get an empty layer, keeping the layers indexed like the
+/// shapes.
+pub fn layer_svgs(design: &GeneratedDesign, width: u32, height: u32, options: &SvgOptions) -> Vec<String> {
+    let transparent = SvgOptions {
+        background: None,
+        ..options.clone()
     };
+    design
+        .shapes()
+        .iter()
+        .map(|shape| {
+            let layer = Group::new()
+                .set("id", format!("shape-{}", shape.id))
+                .add(shape_path(design.grid(), shape, options));
+            base_document(width, height, &transparent).add(layer).to_string()
+        })
+        .collect()
+}
+
+/// Writes `shape-01.svg`, `shape-02.svg`, ... and `composite.svg` to `dir`
+///
+/// See [`layer_svgs`]; the composite is the full [`design_to_svg`] output.
+/// Returns the paths written, composite last.
+pub fn save_split_layers<P: AsRef<Path>>(
+    design: &GeneratedDesign,
+    dir: P,
+    width: u32,
+    height: u32,
+    options: &SvgOptions,
+) -> Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+
+    let mut paths = Vec::new();
+    for (index, layer) in layer_svgs(design, width, height, options).iter().enumerate() {
+        let path = dir.join(format!("shape-{:02}.svg", index + 1));
+        save_svg(layer, &path)?;
+        paths.push(path);
+    }
+
+    let composite = dir.join("composite.svg");
+    save_svg(&design_to_svg(design, width, height, options)?, &composite)?;
+    paths.push(composite);
+    Ok(paths)
+}
+
+/// Everything drawn for a design, in design coordinates and painting order
+///
+/// Shapes come first, then the optional outlines (`outline_width` units
+/// wide), leading, border and debug overlay.
+fn design_layers(design: &GeneratedDesign, options: &SvgOptions, outline_width: f64) -> Vec<Box<dyn Node>> {
+    let mut layers: Vec<Box<dyn Node>> = design
+        .shapes()
+        .iter()
+        .map(|shape| shape_path(design.grid(), shape, options).into())
+        .collect();
+
+    if outline_width > 0.0 {
+        layers.push(outlines(design, outline_width, options).into());
+    }
+
+    if options.stained_glass {
+        layers.push(leading(design).into());
+    }
+
+    if let Some(border) = &options.border {
+        layers.push(border_ring(design, border).into());
+    }
+
+    if options.debug_overlay {
+        layers.push(debug_overlay(design).into());
+    }
+
+    layers
+}
+
+/// Where a design is placed in the coordinates of a host document
+///
+/// The identity transform leaves the hexagon centered on the origin with
+/// radius 100, as in [`design_group`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    /// Where the hexagon's center lands
+    pub x: f64,
+    pub y: f64,
+    /// Host units per design unit
+    pub scale: f64,
+    /// Clockwise rotation in degrees about the hexagon's center
+    pub rotation: f64,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            scale: 1.0,
+            rotation: 0.0,
+        }
+    }
+}
+
+impl Transform {
+    /// Fits the design's 200-unit square into a `size` square with its top-left corner at (x, y)
+    pub fn fit(x: f64, y: f64, size: f64) -> Self {
+        Self {
+            x: x + size / 2.0,
+            y: y + size / 2.0,
+            scale: size / 200.0,
+            rotation: 0.0,
+        }
+    }
+
+    /// The `transform` attribute value that applies this placement
+    pub fn attribute(&self) -> String {
+        if self.rotation == 0.0 {
+            format!("translate({} {}) scale({})", self.x, self.y, self.scale)
+        } else {
+            format!("translate({} {}) rotate({}) scale({})", self.x, self.y, self.rotation, self.scale)
+        }
+    }
+
+    /// Maps a point from design coordinates into host coordinates
+    pub fn apply(&self, point: Point) -> Point {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let (x, y) = (point.x * self.scale, point.y * self.scale);
+        Point::new(self.x + x * cos - y * sin, self.y + x * sin + y * cos)
+    }
+}
+
+/// The design as a single `<g>` element placed by `transform`, without an `<svg>` wrapper
+///
+/// Host applications can paste the markup into their own SVG scenes. The
+/// background and padding of `options` are ignored, and its outline width is
+/// taken in host units.
+pub fn design_to_group(design: &GeneratedDesign, transform: &Transform, options: &SvgOptions) -> String {
+    placed_group(design, transform, options).to_string()
+}
+
+/// The layers of a design in a group carrying `transform`
+fn placed_group(design: &GeneratedDesign, transform: &Transform, options: &SvgOptions) -> Group {
+    let outline_width = options.outline / transform.scale.abs().max(f64::EPSILON);
+    design_layers(design, options, outline_width)
+        .into_iter()
+        .fold(
+            Group::new()
+                .set("class", "hexalith")
+                .set("transform", transform.attribute())
+                .set(CHECKSUM_ATTRIBUTE, format!("{:016x}", design.checksum())),
+            |group, layer| group.add(layer),
+        )
+}
+
+/// Converts the generator output to an SVG document in host coordinates
+///
+/// The design is placed by `transform` and the viewBox is the bounding box of
+/// the placed hexagon, so the document can be positioned by its own numbers.
+pub fn generate_svg_with_transform(generator: &Generator, transform: Transform) -> Result<String> {
+    let design = generator.design().ok_or("Grid not initialized. Call generate() first.")?;
+    let corners: Vec<Point> = design.grid().hex_grid().vertices.iter().map(|&v| transform.apply(v)).collect();
+    let min_x = corners.iter().map(|p| p.x).fold(f64::MAX, f64::min);
+    let min_y = corners.iter().map(|p| p.y).fold(f64::MAX, f64::min);
+    let max_x = corners.iter().map(|p| p.x).fold(f64::MIN, f64::max);
+    let max_y = corners.iter().map(|p| p.y).fold(f64::MIN, f64::max);
+    let (width, height) = (max_x - min_x, max_y - min_y);
+
+    let document = Document::new()
+        .set("viewBox", (min_x, min_y, width, height))
+        .set("width", width)
+        .set("height", height)
+        .add(placed_group(design, &transform, &SvgOptions::default()));
+    Ok(document.to_string())
+}
+
+/// Grid density of identicons, coarse enough to read at favicon sizes
+const IDENTICON_GRID_SIZE: u8 = 3;
+
+/// Shapes in an identicon
+const IDENTICON_SHAPES: u8 = 3;
+
+/// A small square icon that always looks the same for `name`
+///
+/// The name picks both the seed and one of the built-in themes, so different
+/// names, such as server instances or user accounts, are easy to tell apart.
+pub fn identicon(name: &str, size: u32) -> Result<String> {
+    let seed = utils::stable_hash(name.as_bytes());
+    let themes = ColorManager::builtin_themes();
+    let theme = Theme::from(themes[(seed % themes.len() as u64) as usize]);
+    let design = GeneratorConfig::new(IDENTICON_GRID_SIZE, IDENTICON_SHAPES, 1.0, Some(seed))
+        .with_theme(theme)
+        .generate()?;
+    let design = Lod::Auto.apply(&design, size, size, 0.0);
+    design_to_svg(&design, size, size, &SvgOptions::default())
+}
+
+/// Grid density of service icons, the classic 24-cell layout
+const SERVICE_ICON_GRID_SIZE: u8 = 2;
+
+/// An icon for a service or dashboard name, made to stay legible at small sizes
+///
+/// Like [`identicon`] the name picks the seed and theme, here through
+/// [`utils::text_to_seed`] so surrounding whitespace doesn't matter. Icons use
+/// the classic grid with two or three opaque shapes that neither overlap nor
+/// share a color.
+pub fn service_icon(name: &str, size: u32) -> Result<String> {
+    let seed = utils::text_to_seed(name);
+    let themes = ColorManager::builtin_themes();
+    let theme = Theme::from(themes[(seed % themes.len() as u64) as usize]);
+    let shapes = 2 + (seed / themes.len() as u64 % 2) as u8;
+    let design = GeneratorConfig::new(SERVICE_ICON_GRID_SIZE, shapes, 1.0, Some(seed))
+        .with_theme(theme)
+        .with_allow_overlap(false)
+        .with_unique_colors(true)
+        .generate()?;
+    let design = Lod::Auto.apply(&design, size, size, 0.0);
+    design_to_svg(&design, size, size, &SvgOptions::default())
+}
+
+/// The shapes of a design as one group in the design's own coordinates
+///
+/// The hexagon spans -100 to 100 on both axes, so callers can place it on a
+/// larger canvas with a `transform`.
+pub fn design_group(design: &GeneratedDesign) -> Group {
+    design
+        .shapes()
+        .iter()
+        .fold(Group::new(), |group, shape| group.add(shape_path(design.grid(), shape, &SvgOptions::default())))
+}
+
+/// Renders loose shapes on a grid, such as a single frame of an animation
+///
+/// Unlike [`design_to_svg`] no checksum is embedded, since the shapes don't
+/// come from a single design.
+pub fn shapes_to_svg(
+    grid: &TriangularGrid,
+    shapes: &[Shape],
+    width: u32,
+    height: u32,
+    options: &SvgOptions,
+) -> Result<String> {
+    let mut document = base_document(width, height, options);
+    for shape in shapes {
+        document = document.add(shape_path(grid, shape, options));
+    }
+    Ok(document.to_string())
+}
+
+/// Renders a sequence of frames as a looping SMIL-animated SVG
+///
+/// Every frame is a group that is only visible during its own time slot, so
+/// the animation plays in browsers without any script.
+pub fn animated_svg(
+    grid: &TriangularGrid,
+    frames: &[Vec<Shape>],
+    frame_delay_ms: u32,
+    width: u32,
+    height: u32,
+    options: &SvgOptions,
+) -> Result<String> {
+    if frames.is_empty() {
+        return Err("An animation needs at least one frame".into());
+    }
+
+    let count = frames.len() as f64;
+    let duration = format!("{}ms", frame_delay_ms as usize * frames.len());
+    let mut document = base_document(width, height, options);
+
+    for (index, frame) in frames.iter().enumerate() {
+        let start = index as f64 / count;
+        let end = (index + 1) as f64 / count;
+
+        // Discrete keyframes switch the frame on for [start, end) of the loop
+        let (values, key_times) = if index == 0 {
+            ("visible;hidden".to_string(), format!("0;{}", end))
+        } else {
+            ("hidden;visible;hidden".to_string(), format!("0;{};{}", start, end))
+        };
+        let visibility = Animate::new()
+            .set("attributeName", "visibility")
+            .set("values", values)
+            .set("keyTimes", key_times)
+            .set("calcMode", "discrete")
+            .set("dur", duration.as_str())
+            .set("repeatCount", "indefinite");
+
+        let mut group = Group::new()
+            .set("class", "frame")
+            .set("visibility", if index == 0 { "visible" } else { "hidden" })
+            .add(visibility);
+        for shape in frame {
+            group = group.add(shape_path(grid, shape, options));
+        }
+        document = document.add(group);
+    }
+
+    Ok(document.to_string())
+}
+
+/// The filled path for a single shape, its outline traced as `options` describe
+fn shape_path(grid: &TriangularGrid, shape: &Shape, options: &SvgOptions) -> SvgPath {
+    // Per-color alpha multiplies the shape's own opacity
+    let (fill, alpha) = split_alpha(shape.fill());
+    let path = SvgPath::new()
+        .set(SHAPE_ID_ATTRIBUTE, shape.id)
+        .set("d", create_shape_path(grid, shape.cells.as_slice(), options))
+        .set("fill", fill.as_str())
+        .set("fill-opacity", shape.opacity * alpha)
+        .set("stroke", "none");
+    if !options.editable {
+        return path;
+    }
+    path.add(Title::new().add(TextNode::new(utils::escape_xml(&shape_title(shape, &fill)))))
+}
+
+/// A shape described for people, such as `dodgerblue #4285F4, 12 cells`
+fn shape_title(shape: &Shape, fill: &str) -> String {
+    let cells = shape.cell_count();
+    format!(
+        "{} {}, {} {}",
+        ColorManager::nearest_named_color(fill),
+        fill,
+        cells,
+        if cells == 1 { "cell" } else { "cells" }
+    )
+}
+
+/// Strokes every shape's boundary in a darker shade of its own color
+///
+/// At small sizes the outline keeps neighboring shapes of similar colors apart.
+fn outlines(design: &GeneratedDesign, width: f64, options: &SvgOptions) -> Group {
+    design
+        .shapes()
+        .iter()
+        .filter(|shape| !shape.cells.is_empty())
+        .fold(
+            Group::new()
+                .set("class", "outlines")
+                .set("fill", "none")
+                .set("stroke-width", width)
+                .set("stroke-linejoin", "round"),
+            |group, shape| {
+                let (color, _) = split_alpha(shape.fill());
+                group.add(
+                    SvgPath::new()
+                        .set("d", create_shape_path(design.grid(), shape.cells.as_slice(), options))
+                        .set("stroke", ColorManager::darken(&color, 0.2)),
+                )
+            },
+        )
+}
+
+/// The band between two hexagons, `border.inset` and `border.inset + border.width` inside the edge
+fn border_ring(design: &GeneratedDesign, border: &Border) -> SvgPath {
+    let hex = design.grid().hex_grid();
+    let color = border.color.clone().unwrap_or_else(|| {
+        design
+            .shapes()
+            .iter()
+            .max_by_key(|shape| shape.cell_count())
+            .map(|shape| split_alpha(shape.fill()).0)
+            .unwrap_or_else(|| LEADING_COLOR.to_string())
+    });
+    let (fill, alpha) = split_alpha(&color);
+
+    let outer = hex.size - border.inset;
+    let inner = (outer - border.width).max(0.0);
+    let mut data = Data::new();
+    for radius in [outer, inner] {
+        let scale = radius / hex.size;
+        for (index, vertex) in hex.vertices.iter().enumerate() {
+            let x = hex.center.x + (vertex.x - hex.center.x) * scale;
+            let y = hex.center.y + (vertex.y - hex.center.y) * scale;
+            data = if index == 0 { data.move_to((x, y)) } else { data.line_to((x, y)) };
+        }
+        data = data.close();
+    }
+
+    let mut ring = SvgPath::new()
+        .set("class", "border")
+        .set("d", data)
+        .set("fill", fill)
+        .set("fill-rule", "evenodd");
+    if alpha < 1.0 {
+        ring = ring.set("fill-opacity", alpha);
+    }
+    ring
+}
+
+/// Builds the stained-glass leading drawn over the borders between shapes
+///
+/// Edges inside a shape and along the outside of the design are left alone,
+/// so only the seams where two different shapes meet are stroked.
+fn leading(design: &GeneratedDesign) -> SvgPath {
+    let mut data = Data::new();
+    for (start, end) in shared_boundaries(design) {
+        data = data.move_to((start.x, start.y)).line_to((end.x, end.y));
+    }
+
+    SvgPath::new()
+        .set("class", "leading")
+        .set("d", data)
+        .set("fill", "none")
+        .set("stroke", LEADING_COLOR)
+        .set("stroke-width", cell_edge(design.grid()) * 0.12)
+        .set("stroke-linecap", "round")
+        .set("stroke-linejoin", "round")
+}
+
+/// Returns every cell edge shared by two different shapes of a design
+///
+/// Each segment is reported once, as the two grid vertices it connects.
+pub fn shared_boundaries(design: &GeneratedDesign) -> Vec<(Point, Point)> {
+    let grid = design.grid();
+
+    // Later shapes are drawn on top, so they own any cell they share
+    let mut owner = vec![None; grid.cell_count()];
+    for (index, shape) in design.shapes().iter().enumerate() {
+        for &cell in &shape.cells {
+            if let Some(slot) = owner.get_mut(cell) {
+                *slot = Some(index);
+            }
+        }
+    }
+
+    let mut segments = Vec::new();
+    for (a, b) in grid.edges() {
+        let (Some(shape_a), Some(shape_b)) = (owner[a], owner[b]) else {
+            continue;
+        };
+        if shape_a == shape_b {
+            continue;
+        }
+
+        let (Some(cell_a), Some(cell_b)) = (grid.get_cell(a), grid.get_cell(b)) else {
+            continue;
+        };
+        let shared: Vec<Point> = cell_a
+            .vertices
+            .iter()
+            .filter(|v| cell_b.vertices.iter().any(|w| v.distance(w) < 1e-6))
+            .copied()
+            .collect();
+        if let [start, end] = shared[..] {
+            segments.push((start, end));
+        }
+    }
+
+    segments
+}
+
+/// Builds a semi-transparent layer showing how the design maps onto the grid
+///
+/// Draws the triangle skeleton with each cell's id, every shape's outline and
+/// the centroid of each shape labelled with its index.
+fn debug_overlay(design: &GeneratedDesign) -> Group {
+    let grid = design.grid();
+    let edge = cell_edge(grid);
+    let mut overlay = label_group()
+        .set("class", "debug-overlay")
+        .set("opacity", 0.6)
+        .add(grid_layer(grid));
+
+    for (index, shape) in design.shapes().iter().enumerate() {
+        overlay = overlay.add(
+            SvgPath::new()
+                .set("class", "debug-shape-outline")
+                .set("d", create_shape_path(grid, &shape.cells, &SvgOptions::default()))
+                .set("fill", "none")
+                .set("stroke", "#000000")
+                .set("stroke-width", edge * 0.05),
+        );
+
+        let centroids: Vec<Point> = shape
+            .cells
+            .iter()
+            .filter_map(|&id| grid.get_cell_centroid(id))
+            .collect();
+        if centroids.is_empty() {
+            continue;
+        }
+        let count = centroids.len() as f64;
+        let x = centroids.iter().map(|p| p.x).sum::<f64>() / count;
+        let y = centroids.iter().map(|p| p.y).sum::<f64>() / count;
+
+        overlay = overlay
+            .add(
+                Circle::new()
+                    .set("class", "debug-centroid")
+                    .set("cx", x)
+                    .set("cy", y)
+                    .set("r", edge * 0.08)
+                    .set("fill", "#FF0000"),
+            )
+            .add(
+                SvgText::new()
+                    .set("class", "debug-shape-label")
+                    .set("x", x)
+                    .set("y", y - edge * 0.25)
+                    .set("font-size", edge * 0.35)
+                    .set("font-weight", "bold")
+                    .set("fill", "#FF0000")
+                    .add(TextNode::new(format!("#{}", index))),
+            );
+    }
+
+    overlay
+}
+
+/// The triangle skeleton of a grid with each cell's id at its centroid
+fn grid_layer(grid: &TriangularGrid) -> Group {
+    let edge = cell_edge(grid);
+
+    let mut skeleton = Data::new();
+    for cell in grid.cells() {
+        skeleton = skeleton
+            .move_to((cell.vertices[0].x, cell.vertices[0].y))
+            .line_to((cell.vertices[1].x, cell.vertices[1].y))
+            .line_to((cell.vertices[2].x, cell.vertices[2].y))
+            .close();
+    }
+
+    let mut layer = label_group().set("class", "grid-layer").add(
+        SvgPath::new()
+            .set("class", "debug-grid")
+            .set("d", skeleton)
+            .set("fill", "none")
+            .set("stroke", "#888888")
+            .set("stroke-width", edge * 0.02),
+    );
+
+    for cell in grid.cells() {
+        layer = layer.add(
+            SvgText::new()
+                .set("class", "debug-cell-id")
+                .set("x", cell.centroid.x)
+                .set("y", cell.centroid.y)
+                .set("font-size", edge * 0.22)
+                .set("fill", "#444444")
+                .add(TextNode::new(cell.id.to_string())),
+        );
+    }
+
+    layer
+}
+
+/// A group whose text labels are centered on their anchor point
+fn label_group() -> Group {
+    Group::new()
+        .set("font-family", "monospace")
+        .set("text-anchor", "middle")
+        .set("dominant-baseline", "central")
+}
+
+/// Edge length of a grid cell, used to scale labels and strokes with density
+fn cell_edge(grid: &TriangularGrid) -> f64 {
+    grid.cells()
+        .first()
+        .map(|cell| cell.vertices[0].distance(&cell.vertices[1]))
+        .unwrap_or(10.0)
+}
+
+/// Renders an empty grid with every cell labelled by its id
+///
+/// Useful for understanding grid densities and for hand-picking cell ids.
+pub fn grid_to_svg(
+    grid: &TriangularGrid,
+    width: u32,
+    height: u32,
+    options: &SvgOptions,
+) -> Result<String> {
+    let document = base_document(width, height, options).add(grid_layer(grid));
+    Ok(document.to_string())
+}
+
+/// Creates the root document with the padded viewBox and optional background
+fn base_document(width: u32, height: u32, options: &SvgOptions) -> Document {
+    // Grow the viewBox so the 200-unit hexagon only covers the unpadded area
+    let padding = options.padding.clamp(0.0, 0.45);
+    let extent = 100.0 / (1.0 - 2.0 * padding);
 
     // Create an SVG document
     let mut document = Document::new()
-        .set("viewBox", (-100, -100, 200, 200))
+        .set("viewBox", (-extent, -extent, extent * 2.0, extent * 2.0))
         .set("width", width)
         .set("height", height);
 
-    // We don't add the hexagonal boundary anymore to avoid having a border
-
-    // Create a group for each shape
-    for shape in generator.shapes() {
-        let path_data = create_shape_path(grid, shape.cells.as_slice());
+    // Fill the whole canvas when an opaque background is requested. A non-square
+    // canvas shows more than the square viewBox, so stretch the fill to cover it.
+    if let Some(background) = &options.background {
+        let aspect = width.max(1) as f64 / height.max(1) as f64;
+        let extent_x = extent * aspect.max(1.0);
+        let extent_y = extent / aspect.min(1.0);
+        let (fill, alpha) = split_alpha(background);
+        let mut rect = Rectangle::new()
+            .set("x", -extent_x)
+            .set("y", -extent_y)
+            .set("width", extent_x * 2.0)
+            .set("height", extent_y * 2.0)
+            .set("fill", fill);
+        if alpha < 1.0 {
+            rect = rect.set("fill-opacity", alpha);
+        }
+        document = document.add(rect);
+    }
 
-        let shape_path = SvgPath::new()
-            .set("d", path_data)
-            .set("fill", shape.color.clone())
-            .set("fill-opacity", shape.opacity)
-            .set("stroke", "none");
+    document
+}
 
-        document = document.add(shape_path);
+/// Splits an `#RRGGBBAA` or `#RGBA` color into an opaque fill and its alpha
+///
+/// Eight digit hex isn't part of SVG 1.1, so alpha is emitted as opacity instead.
+/// Other colors are passed through unchanged with an alpha of 1.0.
+pub(crate) fn split_alpha(color: &str) -> (String, f32) {
+    let digits = color.trim_start_matches('#').len();
+    if !color.starts_with('#') || (digits != 4 && digits != 8) {
+        return (color.to_string(), 1.0);
     }
 
-    Ok(document.to_string())
+    let (r, g, b, a) = ColorManager::hex_to_rgba(color);
+    (ColorManager::rgb_to_hex(r, g, b), a as f32 / 255.0)
+}
+
+/// Reads the canonical parameter string embedded in an SVG document, if there is one
+pub fn embedded_params(svg_data: &str) -> Option<String> {
+    let marker = format!("class=\"{}\">", PARAMS_METADATA_CLASS);
+    let start = svg_data.find(&marker)? + marker.len();
+    let end = start + svg_data[start..].find("</metadata>")?;
+    Some(svg_data[start..end].trim().replace("&amp;", "&"))
 }
 
-// No hexagon boundary is drawn in the SVG to avoid having a border
+/// Reads the design checksum embedded in an SVG document, if there is one
+pub fn embedded_checksum(svg_data: &str) -> Option<u64> {
+    let marker = format!("{}=\"", CHECKSUM_ATTRIBUTE);
+    let start = svg_data.find(&marker)? + marker.len();
+    let end = start + svg_data[start..].find('"')?;
+    u64::from_str_radix(&svg_data[start..end], 16).ok()
+}
 
 /// Creates an SVG path for a shape made up of triangular cells
-fn create_shape_path(grid: &TriangularGrid, cell_ids: &[usize]) -> Data {
+///
+/// Each outline is simplified with [`simplify_outline`], smoothed with
+/// [`chaikin`] and given rounded corners as `options` ask.
+fn create_shape_path(grid: &TriangularGrid, cell_ids: &[usize], options: &SvgOptions) -> Data {
     let mut data = Data::new();
 
+    for boundary in shape_outlines(grid, cell_ids) {
+        // Rounding only works on real corners, so drop the vertices along straight runs
+        let tolerance = if options.smooth_boundary || options.corner_radius > 0.0 {
+            options.simplify_tolerance.max(COLLINEAR_TOLERANCE)
+        } else {
+            options.simplify_tolerance
+        };
+        let mut boundary = simplify_outline(&boundary, tolerance);
+        if options.smooth_boundary {
+            boundary = chaikin(&boundary, CHAIKIN_ITERATIONS);
+        }
+        if options.corner_radius > 0.0 && boundary.len() >= 3 {
+            data = add_rounded_outline(data, &boundary, options.corner_radius);
+            continue;
+        }
+
+        if let Some(first) = boundary.first() {
+            data = data.move_to((first.x, first.y));
+
+            // Add line segments for the rest of the boundary
+            for point in boundary.iter().skip(1) {
+                data = data.line_to((point.x, point.y));
+            }
+
+            // Close the path
+            data = data.close();
+        }
+    }
+
+    data
+}
+
+/// The outline of every contiguous region of a shape, as closed polygons
+///
+/// Used by every vector output so they all trace shapes the same way.
+pub fn shape_outlines(grid: &TriangularGrid, cell_ids: &[usize]) -> Vec<Vec<Point>> {
     // Group the cells into contiguous regions to create a more efficient path
     let mut regions = Vec::new();
     let mut visited = vec![false; cell_ids.len()];
@@ -77,36 +869,137 @@ fn create_shape_path(grid: &TriangularGrid, cell_ids: &[usize]) -> Data {
         regions.push(region);
     }
 
-    // Create a path for each region
-    for region in regions {
-        data = add_region_to_path(data, grid, &region);
+    regions
+        .iter()
+        .map(|region| compute_region_boundary(grid, region))
+        .filter(|boundary| !boundary.is_empty())
+        .collect()
+}
+
+/// Distance under which a vertex counts as lying on the line through its neighbors
+const COLLINEAR_TOLERANCE: f64 = 1e-6;
+
+/// Corner-cutting passes made by `--smooth-boundary`
+const CHAIKIN_ITERATIONS: usize = 3;
+
+/// Adds a closed outline whose corners are replaced by quadratic Bézier fillets
+///
+/// Each fillet starts and ends `radius` along the two edges meeting at the
+/// corner, with the corner itself as control point. The radius shrinks to half
+/// of a shorter edge so neighboring fillets never cross.
+fn add_rounded_outline(data: Data, outline: &[Point], radius: f64) -> Data {
+    let count = outline.len();
+    let toward = |from: &Point, to: &Point| {
+        let length = from.distance(to);
+        let step = radius.min(length / 2.0) / length.max(f64::EPSILON);
+        Point::new(from.x + (to.x - from.x) * step, from.y + (to.y - from.y) * step)
+    };
+
+    let mut data = data;
+    for (index, corner) in outline.iter().enumerate() {
+        let previous = &outline[(index + count - 1) % count];
+        let next = &outline[(index + 1) % count];
+        let (start, end) = (toward(corner, previous), toward(corner, next));
+        data = if index == 0 {
+            data.move_to((start.x, start.y))
+        } else {
+            data.line_to((start.x, start.y))
+        };
+        data = data.quadratic_curve_to((corner.x, corner.y, end.x, end.y));
     }
+    data.close()
+}
 
-    data
+/// Smooths a closed outline by cutting every corner `iterations` times
+///
+/// Each pass replaces every edge with points a quarter and three quarters of
+/// the way along it, so the outline converges on a smooth curve inside the
+/// original polygon.
+pub fn chaikin(outline: &[Point], iterations: usize) -> Vec<Point> {
+    let mut points = outline.to_vec();
+    for _ in 0..iterations {
+        if points.len() < 3 {
+            break;
+        }
+        points = (0..points.len())
+            .flat_map(|index| {
+                let (a, b) = (points[index], points[(index + 1) % points.len()]);
+                [
+                    Point::new(0.75 * a.x + 0.25 * b.x, 0.75 * a.y + 0.25 * b.y),
+                    Point::new(0.25 * a.x + 0.75 * b.x, 0.25 * a.y + 0.75 * b.y),
+                ]
+            })
+            .collect();
+    }
+    points
 }
 
-/// Adds a region of cells to the SVG path
-fn add_region_to_path(mut data: Data, grid: &TriangularGrid, cell_ids: &[usize]) -> Data {
-    if cell_ids.is_empty() {
-        return data;
+/// Simplifies a closed outline with the Douglas–Peucker algorithm
+///
+/// Every dropped vertex lies within `tolerance` of the simplified outline, and
+/// the kept vertices are a subset of the original ones, so the Hausdorff
+/// distance between the two is at most `tolerance`. Straight runs of cell edges
+/// collapse to their end points even at tiny tolerances. A tolerance of 0.0 or
+/// less returns the outline unchanged.
+pub fn simplify_outline(outline: &[Point], tolerance: f64) -> Vec<Point> {
+    if tolerance <= 0.0 || outline.len() <= 3 {
+        return outline.to_vec();
     }
 
-    let boundary = compute_region_boundary(grid, cell_ids);
+    // Split the ring at the vertex farthest from the first, then simplify both halves as open chains
+    let far = (1..outline.len())
+        .max_by(|&a, &b| outline[0].distance(&outline[a]).total_cmp(&outline[0].distance(&outline[b])))
+        .unwrap_or(0);
+    let mut closed = outline.to_vec();
+    closed.push(outline[0]);
 
-    // Start the path at the first point
-    if let Some(first) = boundary.first() {
-        data = data.move_to((first.x, first.y));
+    let mut keep = vec![false; closed.len()];
+    keep[0] = true;
+    keep[far] = true;
+    keep[closed.len() - 1] = true;
+    douglas_peucker(&closed, 0, far, tolerance, &mut keep);
+    douglas_peucker(&closed, far, closed.len() - 1, tolerance, &mut keep);
 
-        // Add line segments for the rest of the boundary
-        for point in boundary.iter().skip(1) {
-            data = data.line_to((point.x, point.y));
-        }
+    let simplified: Vec<Point> = closed[..closed.len() - 1]
+        .iter()
+        .zip(&keep)
+        .filter(|(_, &kept)| kept)
+        .map(|(point, _)| *point)
+        .collect();
+    // A polygon needs three corners; anything flatter is kept as it was
+    if simplified.len() < 3 {
+        outline.to_vec()
+    } else {
+        simplified
+    }
+}
 
-        // Close the path
-        data = data.close();
+/// Marks the vertices strictly between `start` and `end` that the simplified chain keeps
+fn douglas_peucker(points: &[Point], start: usize, end: usize, tolerance: f64, keep: &mut [bool]) {
+    if end <= start + 1 {
+        return;
     }
 
-    data
+    let (farthest, distance) = (start + 1..end)
+        .map(|index| (index, segment_distance(&points[index], &points[start], &points[end])))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((start, 0.0));
+    if distance > tolerance {
+        keep[farthest] = true;
+        douglas_peucker(points, start, farthest, tolerance, keep);
+        douglas_peucker(points, farthest, end, tolerance, keep);
+    }
+}
+
+/// Distance from `point` to the segment between `a` and `b`
+fn segment_distance(point: &Point, a: &Point, b: &Point) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length = dx * dx + dy * dy;
+    if length == 0.0 {
+        return point.distance(a);
+    }
+    let t = (((point.x - a.x) * dx + (point.y - a.y) * dy) / length).clamp(0.0, 1.0);
+    point.distance(&Point::new(a.x + t * dx, a.y + t * dy))
 }
 
 /// Computes the boundary points of a region of cells
@@ -216,7 +1109,7 @@ pub fn save_svg<P: AsRef<Path>>(svg: &str, path: P) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::generator::Generator;
+    use crate::generator::{Generator, GeneratorConfig};
 
     #[test]
     fn test_svg_generation() {
@@ -237,4 +1130,403 @@ mod tests {
         // Should contain paths for the shapes
         assert!(svg.contains("<path"));
     }
+
+    #[test]
+    fn test_svg_padding() {
+        let mut generator = Generator::new(4, 2, 0.8, Some(42));
+        generator.generate().unwrap();
+
+        // No padding keeps the classic viewBox
+        let svg = generate_svg(&generator, 200, 200).unwrap();
+        assert!(svg.contains("viewBox=\"-100 -100 200 200\""));
+
+        // 10% padding on each side shrinks the design to 80% of the canvas
+        let options = SvgOptions {
+            padding: 0.1,
+            ..SvgOptions::default()
+        };
+        let svg = generate_svg_with_options(&generator, 200, 200, &options).unwrap();
+        assert!(svg.contains("viewBox=\"-125 -125 250 250\""));
+        assert!(!svg.contains("<rect"));
+    }
+
+    #[test]
+    fn test_svg_background() {
+        let mut generator = Generator::new(4, 2, 0.8, Some(42));
+        generator.generate().unwrap();
+
+        let options = SvgOptions {
+            background: Some("#FFFFFF".to_string()),
+            ..SvgOptions::default()
+        };
+        let svg = generate_svg_with_options(&generator, 200, 200, &options).unwrap();
+
+        // The background must be drawn before any shape
+        let rect = svg.find("<rect").unwrap();
+        assert!(svg[rect..].contains("fill=\"#FFFFFF\""));
+        assert!(rect < svg.find("<path").unwrap());
+    }
+
+    #[test]
+    fn test_embedded_checksum() {
+        let design = GeneratorConfig::new(4, 3, 0.8, Some(42)).generate().unwrap();
+        let svg = design_to_svg(&design, 200, 200, &SvgOptions::default()).unwrap();
+
+        assert_eq!(embedded_checksum(&svg), Some(design.checksum()));
+        assert_eq!(embedded_checksum("<svg></svg>"), None);
+    }
+
+    #[test]
+    fn test_embedded_params() {
+        let design = GeneratorConfig::new(4, 3, 0.8, Some(42)).generate().unwrap();
+        let plain = design_to_svg(&design, 200, 200, &SvgOptions::default()).unwrap();
+        assert_eq!(embedded_params(&plain), None);
+
+        let options = SvgOptions {
+            params: Some("seed=42&theme=mesos".to_string()),
+            ..SvgOptions::default()
+        };
+        let svg = design_to_svg(&design, 200, 200, &options).unwrap();
+        assert!(svg.contains("seed=42&amp;theme=mesos"));
+        assert_eq!(embedded_params(&svg).as_deref(), Some("seed=42&theme=mesos"));
+
+        // The escaped document is still valid XML
+        #[cfg(feature = "png")]
+        assert!(crate::png::parse_svg(&svg).is_ok());
+    }
+
+    #[test]
+    fn test_split_alpha() {
+        assert_eq!(split_alpha("#FF000080"), ("#FF0000".to_string(), 128.0 / 255.0));
+        assert_eq!(split_alpha("#F008"), ("#FF0000".to_string(), 136.0 / 255.0));
+        assert_eq!(split_alpha("#FF0000"), ("#FF0000".to_string(), 1.0));
+        assert_eq!(split_alpha("red"), ("red".to_string(), 1.0));
+
+        let options = SvgOptions {
+            background: Some("#0000FF80".to_string()),
+            ..SvgOptions::default()
+        };
+        let design = GeneratorConfig::new(4, 2, 0.8, Some(42)).generate().unwrap();
+        let svg = design_to_svg(&design, 200, 200, &options).unwrap();
+        assert!(svg.contains("fill=\"#0000FF\""));
+        assert!(svg.contains("fill-opacity=\"0.5019608\""));
+    }
+
+    #[test]
+    fn test_editable_titles() {
+        let design = GeneratorConfig::new(4, 3, 0.8, Some(42)).with_theme(Theme::Google).generate().unwrap();
+        let plain = design_to_svg(&design, 200, 200, &SvgOptions::default()).unwrap();
+        assert!(!plain.contains("<title>"));
+
+        let options = SvgOptions {
+            editable: true,
+            ..SvgOptions::default()
+        };
+        let svg = design_to_svg(&design, 200, 200, &options).unwrap();
+        assert_eq!(svg.matches("<title>").count(), design.shapes().len());
+        for shape in design.shapes() {
+            let title = shape_title(shape, shape.fill());
+            assert!(title.starts_with(ColorManager::nearest_named_color(shape.fill())));
+            assert!(svg.contains(&title), "missing {}", title);
+        }
+
+        let mut single = Shape::new(1.0).with_color("#4285F4");
+        single.add_cell(0);
+        assert_eq!(shape_title(&single, single.fill()), "dodgerblue #4285F4, 1 cell");
+    }
+
+    #[test]
+    fn test_debug_overlay() {
+        let design = GeneratorConfig::new(2, 3, 0.8, Some(42)).generate().unwrap();
+
+        let plain = design_to_svg(&design, 200, 200, &SvgOptions::default()).unwrap();
+        assert!(!plain.contains("debug-overlay"));
+
+        let options = SvgOptions {
+            debug_overlay: true,
+            ..SvgOptions::default()
+        };
+        let svg = design_to_svg(&design, 200, 200, &options).unwrap();
+
+        // One id label per cell and one centroid per shape
+        assert_eq!(svg.matches("debug-cell-id").count(), design.grid().cell_count());
+        assert_eq!(svg.matches("debug-centroid").count(), design.shapes().len());
+        let label = svg.find("debug-shape-label").unwrap();
+        assert!(svg[label..].contains("#0"));
+
+        // The overlay is drawn on top of the design
+        assert!(svg.find("debug-overlay").unwrap() > svg.rfind("fill-opacity").unwrap());
+    }
+
+    #[test]
+    fn test_stained_glass() {
+        let design = GeneratorConfig::new(4, 5, 0.8, Some(3))
+            .with_pack(true)
+            .generate()
+            .unwrap();
+
+        // With the grid fully packed, every edge between differently owned
+        // cells is a seam
+        let owner = |cell: usize| design.shapes().iter().position(|s| s.contains_cell(cell));
+        let seams = design
+            .grid()
+            .edges()
+            .into_iter()
+            .filter(|&(a, b)| owner(a) != owner(b))
+            .count();
+        assert!(seams > 0);
+        assert_eq!(shared_boundaries(&design).len(), seams);
+
+        let plain = design_to_svg(&design, 200, 200, &SvgOptions::default()).unwrap();
+        assert!(!plain.contains("class=\"leading\""));
+
+        let options = SvgOptions {
+            stained_glass: true,
+            ..SvgOptions::default()
+        };
+        let svg = design_to_svg(&design, 200, 200, &options).unwrap();
+        let leading = svg.find("class=\"leading\"").unwrap();
+        assert!(leading > svg.rfind("fill-opacity").unwrap());
+    }
+
+    #[test]
+    fn test_outlines() {
+        let design = GeneratorConfig::new(3, 3, 1.0, Some(2)).generate().unwrap();
+        let plain = design_to_svg(&design, 128, 128, &SvgOptions::default()).unwrap();
+        assert!(!plain.contains("class=\"outlines\""));
+
+        let options = SvgOptions {
+            outline: 2.0,
+            ..SvgOptions::default()
+        };
+        let svg = design_to_svg(&design, 128, 128, &options).unwrap();
+        let filled = design.shapes().iter().filter(|shape| !shape.cells.is_empty()).count();
+        assert!(svg.contains("class=\"outlines\""));
+        assert_eq!(svg.matches("stroke=\"#").count(), filled);
+
+        // Two pixels of a 128 pixel canvas showing 200 units
+        assert!(svg.contains("stroke-width=\"3.125\""));
+    }
+
+    /// Largest distance from a vertex of `original` to the closed outline `simplified`
+    fn hausdorff(original: &[Point], simplified: &[Point]) -> f64 {
+        original
+            .iter()
+            .map(|point| {
+                (0..simplified.len())
+                    .map(|i| segment_distance(point, &simplified[i], &simplified[(i + 1) % simplified.len()]))
+                    .fold(f64::MAX, f64::min)
+            })
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn test_simplify_outline_bounds_error() {
+        let design = GeneratorConfig::new(6, 2, 0.8, Some(6)).with_pack(true).generate().unwrap();
+        let (mut before, mut after) = (0, 0);
+
+        for tolerance in [0.5, 4.0] {
+            for shape in design.shapes() {
+                for outline in shape_outlines(design.grid(), &shape.cells) {
+                    let simplified = simplify_outline(&outline, tolerance);
+                    assert!(simplified.len() >= 3);
+                    assert!(simplified.iter().all(|point| outline.contains(point)));
+                    assert!(hausdorff(&outline, &simplified) <= tolerance + 1e-9);
+                    before += outline.len();
+                    after += simplified.len();
+                }
+            }
+        }
+        assert!(after * 3 < before * 2);
+
+        let outline = &shape_outlines(design.grid(), &design.shapes()[0].cells)[0];
+        assert_eq!(&simplify_outline(outline, 0.0), outline);
+    }
+
+    #[test]
+    fn test_svg_simplify_tolerance() {
+        let design = GeneratorConfig::new(6, 2, 0.8, Some(6)).with_pack(true).generate().unwrap();
+        let plain = design_to_svg(&design, 256, 256, &SvgOptions::default()).unwrap();
+        let options = SvgOptions {
+            simplify_tolerance: 1.0,
+            ..SvgOptions::default()
+        };
+        let simplified = design_to_svg(&design, 256, 256, &options).unwrap();
+        assert!(simplified.matches(" L").count() < plain.matches(" L").count());
+        assert_eq!(embedded_checksum(&simplified), embedded_checksum(&plain));
+    }
+
+    #[test]
+    fn test_rounded_corners() {
+        let design = GeneratorConfig::new(4, 3, 0.8, Some(42)).generate().unwrap();
+        let sharp = design_to_svg(&design, 200, 200, &SvgOptions::default()).unwrap();
+        assert!(!sharp.contains('Q'));
+
+        let options = SvgOptions {
+            corner_radius: 5.0,
+            ..SvgOptions::default()
+        };
+        let rounded = design_to_svg(&design, 200, 200, &options).unwrap();
+        assert!(rounded.contains('Q'));
+        assert_eq!(embedded_checksum(&rounded), embedded_checksum(&sharp));
+
+        // Every corner of a triangle gets a fillet
+        let triangle = [Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(0.0, 10.0)];
+        let data = add_rounded_outline(Data::new(), &triangle, 2.0);
+        assert_eq!(data.len(), 7);
+    }
+
+    #[test]
+    fn test_chaikin() {
+        let square = [
+            Point::new(0.0, 0.0),
+            Point::new(4.0, 0.0),
+            Point::new(4.0, 4.0),
+            Point::new(0.0, 4.0),
+        ];
+        let smoothed = chaikin(&square, 2);
+        assert_eq!(smoothed.len(), 16);
+        assert!(smoothed.iter().all(|p| (0.0..=4.0).contains(&p.x) && (0.0..=4.0).contains(&p.y)));
+        assert!(!smoothed.contains(&square[0]));
+        assert_eq!(chaikin(&square, 0), square.to_vec());
+    }
+
+    #[test]
+    fn test_parse_border() {
+        let border: Border = "4".parse().unwrap();
+        assert_eq!((border.width, border.color, border.inset), (4.0, None, 0.0));
+
+        let border: Border = "3,#333333,6".parse().unwrap();
+        assert_eq!(border.color.as_deref(), Some("#333333"));
+        assert_eq!(border.inset, 6.0);
+
+        let border: Border = "2, rgb(255, 0, 0)".parse().unwrap();
+        assert_eq!((border.color.as_deref(), border.inset), (Some("#FF0000"), 0.0));
+
+        assert!("0,red".parse::<Border>().is_err());
+        assert!("wide".parse::<Border>().unwrap_err().contains("expected WIDTH[,COLOR[,INSET]]"));
+        assert!("2,notacolor".parse::<Border>().is_err());
+    }
+
+    #[test]
+    fn test_border_ring() {
+        let design = GeneratorConfig::new(4, 3, 0.8, Some(42)).generate().unwrap();
+        let plain = design_to_svg(&design, 200, 200, &SvgOptions::default()).unwrap();
+        assert!(!plain.contains("class=\"border\""));
+
+        let options = SvgOptions {
+            border: Some("10,#123456,5".parse().unwrap()),
+            ..SvgOptions::default()
+        };
+        let svg = design_to_svg(&design, 200, 200, &options).unwrap();
+        assert!(svg.contains("class=\"border\""));
+        assert!(svg.contains("fill=\"#123456\""));
+        assert!(svg.contains("fill-rule=\"evenodd\""));
+        // The outer hexagon starts 5 units in and the inner one 10 further
+        assert!(svg.contains("M95,0"));
+        assert!(svg.contains("M85,0"));
+
+        // Without a color the ring takes the largest shape's
+        let options = SvgOptions {
+            border: Some("4".parse().unwrap()),
+            ..SvgOptions::default()
+        };
+        let svg = design_to_svg(&design, 200, 200, &options).unwrap();
+        let largest = design.shapes().iter().max_by_key(|shape| shape.cell_count()).unwrap();
+        let ring = &svg[svg.find("class=\"border\"").unwrap()..];
+        assert!(ring.contains(&format!("fill=\"{}\"", largest.fill())));
+    }
+
+    #[test]
+    fn test_transform() {
+        let transform = Transform::fit(10.0, 20.0, 100.0);
+        assert_eq!(transform.attribute(), "translate(60 70) scale(0.5)");
+        assert_eq!(transform.apply(Point::new(100.0, 0.0)), Point::new(110.0, 70.0));
+
+        let turned = Transform {
+            rotation: 90.0,
+            ..Transform::default()
+        };
+        let point = turned.apply(Point::new(100.0, 0.0));
+        assert!(point.x.abs() < 1e-9 && (point.y - 100.0).abs() < 1e-9);
+        assert!(turned.attribute().contains("rotate(90)"));
+    }
+
+    #[test]
+    fn test_design_to_group() {
+        let mut generator = Generator::new(4, 3, 0.8, Some(42));
+        generator.generate().unwrap();
+        let design = generator.design().unwrap();
+
+        let group = design_to_group(design, &Transform::fit(0.0, 0.0, 50.0), &SvgOptions::default());
+        assert!(group.starts_with("<g "));
+        assert!(group.contains("transform=\"translate(25 25) scale(0.25)\""));
+        assert!(!group.contains("<svg"));
+        assert_eq!(group.matches("<path").count(), design.shapes().len());
+
+        // The hexagon is 200 units wide and about 173 tall before scaling
+        let svg = generate_svg_with_transform(&generator, Transform::fit(0.0, 0.0, 50.0)).unwrap();
+        assert!(svg.contains("viewBox=\"0 "));
+        assert!(svg.contains("width=\"50\""));
+        assert!(svg.contains(&group));
+        assert!(generate_svg_with_transform(&Generator::new(4, 3, 0.8, Some(1)), Transform::default()).is_err());
+    }
+
+    #[test]
+    fn test_layer_svgs() {
+        let design = GeneratorConfig::new(4, 3, 0.8, Some(42)).generate().unwrap();
+        let options = SvgOptions {
+            background: Some("#FFFFFF".to_string()),
+            outline: 2.0,
+            ..SvgOptions::default()
+        };
+        let composite = design_to_svg(&design, 256, 256, &options).unwrap();
+        let view_box = &composite[composite.find("viewBox").unwrap()..][..30];
+
+        let layers = layer_svgs(&design, 256, 256, &options);
+        assert_eq!(layers.len(), design.shapes().len());
+        for (index, layer) in layers.iter().enumerate() {
+            assert!(layer.contains(view_box));
+            assert!(layer.contains(&format!("id=\"shape-{}\"", design.shapes()[index].id)));
+            assert!(layer.contains(design.shapes()[index].fill()));
+            assert_eq!(layer.matches("<path").count(), 1);
+            assert!(layer.contains(&format!("{}=\"{}\"", SHAPE_ID_ATTRIBUTE, design.shapes()[index].id)));
+            assert!(!layer.contains("<rect") && !layer.contains("outlines"));
+        }
+    }
+
+    #[test]
+    fn test_identicon() {
+        let icon = identicon("build-agent-7", 32).unwrap();
+        assert_eq!(icon, identicon("build-agent-7", 32).unwrap());
+        assert_ne!(icon, identicon("build-agent-8", 32).unwrap());
+        assert!(icon.contains("width=\"32\""));
+        assert!(icon.contains("<path"));
+    }
+
+    #[test]
+    fn test_service_icon() {
+        let icon = service_icon("payments-service", 48).unwrap();
+        assert_eq!(icon, service_icon("  payments-service\n", 48).unwrap());
+        assert_ne!(icon, service_icon("auth", 48).unwrap());
+        assert!(icon.contains("width=\"48\""));
+
+        // Small, opaque and flat: two or three shapes without blends
+        for name in ["payments-service", "auth", "search", "grafana", "backstage"] {
+            let icon = service_icon(name, 48).unwrap();
+            let shapes = icon.matches("<path").count();
+            assert!((2..=3).contains(&shapes), "{}: {} shapes", name, shapes);
+            assert!(!icon.contains("fill-opacity=\"0."), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_grid_to_svg() {
+        let grid = TriangularGrid::new(100.0, 5);
+        let svg = grid_to_svg(&grid, 300, 300, &SvgOptions::default()).unwrap();
+
+        assert_eq!(svg.matches("debug-cell-id").count(), grid.cell_count());
+        assert!(!svg.contains("fill-opacity"));
+        assert!(!svg.contains(CHECKSUM_ATTRIBUTE));
+    }
 }
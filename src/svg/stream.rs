@@ -0,0 +1,89 @@
+use super::{design_layers, SvgOptions, Transform, CHECKSUM_ATTRIBUTE};
+use crate::generator::GeneratedDesign;
+use std::fmt::Display;
+use std::io::{self, Write};
+
+/// Writes an SVG document element by element instead of building it in memory
+///
+/// Only the element being written is ever held, so documents with hundreds of
+/// thousands of paths, such as large wallpapers, stream straight to a file.
+/// Call [`SvgWriter::finish`] to close the document.
+pub struct SvgWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> SvgWriter<W> {
+    /// Starts a document of `width` x `height` pixels showing `view_box` (x, y, width, height)
+    pub fn new(mut writer: W, width: u32, height: u32, view_box: (f64, f64, f64, f64)) -> io::Result<Self> {
+        let (x, y, view_width, view_height) = view_box;
+        writeln!(
+            writer,
+            "<svg height=\"{}\" viewBox=\"{} {} {} {}\" width=\"{}\" xmlns=\"http://www.w3.org/2000/svg\">",
+            height, x, y, view_width, view_height, width
+        )?;
+        Ok(Self { writer })
+    }
+
+    /// Writes one complete element, such as a background `<rect>` or `<defs>`
+    pub fn write_node(&mut self, node: &impl Display) -> io::Result<()> {
+        writeln!(self.writer, "{}", node)
+    }
+
+    /// Writes a design placed by `transform` as a `<g>`, one layer at a time
+    ///
+    /// The output matches [`super::design_to_group`] for the same arguments.
+    pub fn write_design(
+        &mut self,
+        design: &GeneratedDesign,
+        transform: &Transform,
+        options: &SvgOptions,
+    ) -> io::Result<()> {
+        writeln!(
+            self.writer,
+            "<g class=\"hexalith\" {}=\"{:016x}\" transform=\"{}\">",
+            CHECKSUM_ATTRIBUTE,
+            design.checksum(),
+            transform.attribute()
+        )?;
+        let outline_width = options.outline / transform.scale.abs().max(f64::EPSILON);
+        for layer in design_layers(design, options, outline_width) {
+            writeln!(self.writer, "{}", layer)?;
+        }
+        writeln!(self.writer, "</g>")
+    }
+
+    /// Closes the document and hands back the writer, flushed
+    pub fn finish(mut self) -> io::Result<W> {
+        writeln!(self.writer, "</svg>")?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::GeneratorConfig;
+    use ::svg::node::element::Rectangle;
+
+    #[test]
+    fn test_streamed_document() {
+        let design = GeneratorConfig::new(4, 3, 0.8, Some(42)).generate().unwrap();
+        let transform = Transform::fit(0.0, 0.0, 100.0);
+
+        let mut writer = SvgWriter::new(Vec::new(), 100, 100, (0.0, 0.0, 100.0, 100.0)).unwrap();
+        writer.write_node(&Rectangle::new().set("width", 100).set("height", 100)).unwrap();
+        writer.write_design(&design, &transform, &SvgOptions::default()).unwrap();
+        let svg = String::from_utf8(writer.finish().unwrap()).unwrap();
+
+        assert!(svg.starts_with("<svg height=\"100\" viewBox=\"0 0 100 100\" width=\"100\""));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains("<rect height=\"100\" width=\"100\"/>"));
+
+        // Streaming writes the same group design_to_group builds in memory
+        let group = super::super::design_to_group(&design, &transform, &SvgOptions::default());
+        assert!(svg.contains(group.trim()));
+        #[cfg(feature = "png")]
+        assert!(crate::png::parse_svg(&svg).is_ok());
+    }
+}
@@ -0,0 +1,139 @@
+use crate::generator::GeneratedDesign;
+use crate::utils;
+use crate::Result;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Placeholder replaced by the generated SVG document
+pub const LOGO_PLACEHOLDER: &str = "logo";
+
+/// A user-supplied SVG document that generated logos are framed in
+///
+/// `{{logo}}` marks where the logo goes, as a nested `<svg>` element, and
+/// `{{name}}` anywhere else is replaced by the variable of that name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    source: String,
+}
+
+impl Template {
+    /// Checks that `source` has a `{{logo}}` placeholder and well-formed braces
+    pub fn parse(source: &str) -> Result<Self> {
+        let names = placeholders(source)?;
+        if !names.contains(&LOGO_PLACEHOLDER) {
+            return Err("Template has no {{logo}} placeholder".into());
+        }
+        Ok(Self {
+            source: source.to_string(),
+        })
+    }
+
+    /// Reads and checks a template file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read template {}: {}", path.display(), e))?;
+        Self::parse(&source)
+    }
+
+    /// Injects `logo_svg` and substitutes `variables`, whose values are XML-escaped
+    pub fn render(&self, logo_svg: &str, variables: &BTreeMap<String, String>) -> Result<String> {
+        let logo = strip_prologue(logo_svg);
+        let mut output = String::with_capacity(self.source.len() + logo.len());
+        let mut rest = self.source.as_str();
+
+        while let Some(start) = rest.find("{{") {
+            output.push_str(&rest[..start]);
+            let end = rest[start..].find("}}").map(|end| start + end).ok_or("Unclosed {{ in template")?;
+            let name = rest[start + 2..end].trim();
+            match variables.get(name) {
+                _ if name == LOGO_PLACEHOLDER => output.push_str(logo),
+                Some(value) => output.push_str(&utils::escape_xml(value)),
+                None => {
+                    let mut names: Vec<&str> = variables.keys().map(String::as_str).collect();
+                    names.insert(0, LOGO_PLACEHOLDER);
+                    return Err(format!(
+                        "Unknown template variable '{}' (expected one of: {})",
+                        name,
+                        names.join(", ")
+                    )
+                    .into());
+                }
+            }
+            rest = &rest[end + 2..];
+        }
+        output.push_str(rest);
+        Ok(output)
+    }
+}
+
+/// The variables every template can use: seed, title, date, theme and hash
+///
+/// `date` is today's UTC date as `YYYY-MM-DD`; `seed` is empty for random designs.
+pub fn variables(design: &GeneratedDesign, title: &str) -> BTreeMap<String, String> {
+    let config = design.config();
+    let (year, month, day, _) = utils::utc_date(SystemTime::now());
+
+    let mut variables = BTreeMap::new();
+    variables.insert("seed".to_string(), config.seed().map(|seed| seed.to_string()).unwrap_or_default());
+    variables.insert("title".to_string(), title.to_string());
+    variables.insert("date".to_string(), format!("{:04}-{:02}-{:02}", year, month, day));
+    variables.insert("theme".to_string(), config.theme().to_string());
+    variables.insert("hash".to_string(), format!("{:016x}", design.checksum()));
+    variables
+}
+
+/// Names of the `{{...}}` placeholders in a template
+fn placeholders(source: &str) -> Result<Vec<&str>> {
+    let mut names = Vec::new();
+    let mut rest = source;
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..].find("}}").map(|end| start + end).ok_or("Unclosed {{ in template")?;
+        names.push(rest[start + 2..end].trim());
+        rest = &rest[end + 2..];
+    }
+    Ok(names)
+}
+
+/// The document without its XML declaration, doctype or leading comments, so it can nest
+fn strip_prologue(svg: &str) -> &str {
+    svg.find("<svg").map_or(svg, |start| &svg[start..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::GeneratorConfig;
+
+    const CARD: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" width="400" height="200">
+  <title>{{ title }}</title>
+  <g transform="translate(10 10)">{{logo}}</g>
+  <text x="200" y="100">#{{seed}} {{date}}</text>
+</svg>"#;
+
+    #[test]
+    fn test_render_template() {
+        let design = GeneratorConfig::new(3, 2, 0.8, Some(12)).generate().unwrap();
+        let logo = "<?xml version=\"1.0\"?>\n<svg width=\"180\"><path/></svg>";
+        let variables = variables(&design, "Tom & Jerry");
+
+        let output = Template::parse(CARD).unwrap().render(logo, &variables).unwrap();
+        assert!(output.contains("<title>Tom &amp; Jerry</title>"));
+        assert!(output.contains("<g transform=\"translate(10 10)\"><svg width=\"180\"><path/></svg></g>"));
+        assert!(output.contains(&format!("#12 {}", variables["date"])));
+        assert!(!output.contains("<?xml"));
+        assert_eq!(variables["date"].len(), 10);
+    }
+
+    #[test]
+    fn test_template_errors() {
+        assert!(Template::parse("<svg/>").unwrap_err().to_string().contains("{{logo}}"));
+        assert!(Template::parse("<svg>{{logo}} {{seed</svg>").unwrap_err().to_string().contains("Unclosed"));
+
+        let template = Template::parse("<svg>{{logo}}{{colour}}</svg>").unwrap();
+        let error = template.render("<svg/>", &BTreeMap::new()).unwrap_err();
+        assert!(error.to_string().contains("Unknown template variable 'colour' (expected one of: logo)"));
+    }
+}
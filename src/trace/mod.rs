@@ -0,0 +1,376 @@
+use crate::generator::color::ColorManager;
+use crate::generator::grid::{Point, TriangularGrid};
+use crate::generator::shape::Shape;
+use crate::generator::{GeneratedDesign, GeneratorConfig, GuideMask};
+use crate::png;
+use crate::Result;
+use resvg::tiny_skia::Pixmap;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Longest side, in pixels, that SVG input is rasterized at before sampling
+pub const TRACE_RESOLUTION: u32 = 512;
+
+/// Samples along each side of a cell; a cell is sampled at n(n+1)/2 points
+const SAMPLES_PER_SIDE: usize = 5;
+
+/// RGB distance beyond which a pixel no longer counts as background
+const BACKGROUND_DISTANCE: f64 = 48.0;
+
+/// Side length, in samples, of the mask built from a guide image
+const GUIDE_RESOLUTION: usize = 64;
+
+/// Rounds of k-means refinement when reducing the traced colors
+const QUANTIZE_ITERATIONS: usize = 10;
+
+/// OKLab distance below which two traced colors are treated as one
+const DISTINCT_COLOR_DISTANCE: f64 = 0.05;
+
+/// An image laid over the design's coordinate space
+///
+/// The image is scaled to fit the 200-unit square around the hexagon and
+/// centered on it, keeping its aspect ratio.
+pub struct Raster {
+    pixmap: Pixmap,
+}
+
+impl Raster {
+    /// Reads a PNG or SVG file, choosing by extension
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("png") => Self::from_png(&fs::read(path)?),
+            Some("svg") => Self::from_svg(&fs::read_to_string(path)?),
+            _ => Err(format!("Cannot read {}: only .svg and .png images are supported", path.display()).into()),
+        }
+    }
+
+    pub fn from_png(data: &[u8]) -> Result<Self> {
+        Ok(Self {
+            pixmap: Pixmap::decode_png(data)?,
+        })
+    }
+
+    /// Rasterizes an SVG document with its longest side [`TRACE_RESOLUTION`] pixels
+    pub fn from_svg(svg_data: &str) -> Result<Self> {
+        let tree = png::parse_svg(svg_data)?;
+        let (width, height) = (tree.size.width() as f64, tree.size.height() as f64);
+        let scale = TRACE_RESOLUTION as f64 / width.max(height).max(1.0);
+        let pixmap = png::render_tree_to_pixmap(
+            &tree,
+            ((width * scale).round() as u32).max(1),
+            ((height * scale).round() as u32).max(1),
+        )?;
+        Ok(Self { pixmap })
+    }
+
+    pub fn from_pixmap(pixmap: Pixmap) -> Self {
+        Self { pixmap }
+    }
+
+    /// The unpremultiplied RGBA color under a point in design coordinates
+    ///
+    /// `None` outside the image.
+    pub fn sample(&self, point: Point) -> Option<[u8; 4]> {
+        let (width, height) = (self.pixmap.width() as f64, self.pixmap.height() as f64);
+        let scale = width.max(height) / 200.0;
+        let x = width / 2.0 + point.x * scale;
+        let y = height / 2.0 + point.y * scale;
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let color = self.pixmap.pixel(x as u32, y as u32)?.demultiply();
+        Some([color.red(), color.green(), color.blue(), color.alpha()])
+    }
+
+    /// The most common opaque color around the edge of the image
+    ///
+    /// `None` when the edge is mostly transparent, so that transparency is the background.
+    pub fn background(&self) -> Option<[u8; 3]> {
+        let (width, height) = (self.pixmap.width(), self.pixmap.height());
+        let edge = (0..width)
+            .flat_map(|x| [(x, 0), (x, height - 1)])
+            .chain((0..height).flat_map(|y| [(0, y), (width - 1, y)]));
+
+        let mut transparent = 0;
+        let mut opaque = Vec::new();
+        for (x, y) in edge {
+            let Some(pixel) = self.pixmap.pixel(x, y) else { continue };
+            let color = pixel.demultiply();
+            if color.alpha() < 128 {
+                transparent += 1;
+            } else {
+                opaque.push([color.red(), color.green(), color.blue()]);
+            }
+        }
+
+        let (color, count) = dominant_color(&opaque)?;
+        if count < transparent {
+            return None;
+        }
+        Some(color)
+    }
+
+    /// Darkness of the image for guiding shape growth
+    ///
+    /// Dark opaque pixels are fully dark; light or transparent ones, and the
+    /// area outside the image, are not.
+    pub fn guide_mask(&self) -> GuideMask {
+        GuideMask::from_fn(GUIDE_RESOLUTION, |point| match self.sample(point) {
+            Some([r, g, b, a]) => {
+                let luma = (0.2126 * r as f64 + 0.7152 * g as f64 + 0.0722 * b as f64) / 255.0;
+                (1.0 - luma) * a as f64 / 255.0
+            }
+            None => 0.0,
+        })
+    }
+
+    /// Whether a sampled color is part of the mark rather than the background
+    fn is_filled(color: [u8; 4], background: Option<[u8; 3]>) -> bool {
+        if color[3] < 128 {
+            return false;
+        }
+        background.is_none_or(|bg| rgb_distance([color[0], color[1], color[2]], bg) > BACKGROUND_DISTANCE)
+    }
+}
+
+/// Settings for [`trace`]
+#[derive(Debug, Clone)]
+pub struct TraceOptions {
+    /// Grid density (2-8)
+    pub grid_size: u8,
+    /// Most colors, and so shapes, in the traced design
+    pub colors: usize,
+    pub opacity: f32,
+}
+
+impl Default for TraceOptions {
+    fn default() -> Self {
+        Self {
+            grid_size: 6,
+            colors: 4,
+            opacity: 1.0,
+        }
+    }
+}
+
+/// Approximates an image with cells of the triangular grid
+///
+/// A cell is filled when most of the points sampled inside it belong to the
+/// mark rather than the background, and takes their majority color. The
+/// colors are then reduced to at most `options.colors` by k-means in OKLab,
+/// and each becomes one shape, largest first.
+pub fn trace(raster: &Raster, options: &TraceOptions) -> Result<GeneratedDesign> {
+    let grid = TriangularGrid::new(100.0, options.grid_size);
+    let background = raster.background();
+
+    let mut filled: Vec<(usize, (f64, f64, f64))> = Vec::new();
+    for cell in grid.cells() {
+        let samples = cell_samples(&cell.vertices);
+        let colors: Vec<[u8; 3]> = samples
+            .iter()
+            .filter_map(|&point| raster.sample(point))
+            .filter(|&color| Raster::is_filled(color, background))
+            .map(|color| [color[0], color[1], color[2]])
+            .collect();
+        if colors.len() * 2 <= samples.len() {
+            continue;
+        }
+
+        // The majority color, so cells on an edge between two colors don't mix them
+        let Some(([r, g, b], _)) = dominant_color(&colors) else { continue };
+        filled.push((cell.id, ColorManager::hex_to_oklab(&ColorManager::rgb_to_hex(r, g, b))));
+    }
+    if filled.is_empty() {
+        return Err("Found nothing to trace: the image has no filled regions inside the hexagon".into());
+    }
+
+    let colors: Vec<(f64, f64, f64)> = filled.iter().map(|&(_, color)| color).collect();
+    let (centers, assignment) = quantize(&colors, options.colors.max(1));
+
+    let mut shapes: Vec<Shape> = centers
+        .iter()
+        .map(|&(l, a, b)| Shape::new(options.opacity).with_color(ColorManager::oklab_to_hex(l, a, b)))
+        .collect();
+    for (&(cell, _), &center) in filled.iter().zip(&assignment) {
+        shapes[center].add_cell(cell);
+    }
+    shapes.retain(|shape| !shape.cells.is_empty());
+    shapes.sort_by_key(|shape| std::cmp::Reverse(shape.cell_count()));
+    for (id, shape) in shapes.iter_mut().enumerate() {
+        shape.id = id;
+    }
+
+    let config = GeneratorConfig::new(options.grid_size, shapes.len() as u8, options.opacity, None);
+    Ok(GeneratedDesign::new(config, grid, shapes, Vec::new()))
+}
+
+/// Points spread evenly over the inside of a triangle
+fn cell_samples(vertices: &[Point; 3]) -> Vec<Point> {
+    let n = SAMPLES_PER_SIDE as f64;
+    let mut points = Vec::new();
+    for i in 0..SAMPLES_PER_SIDE {
+        for j in 0..SAMPLES_PER_SIDE - i {
+            // Offsetting by a third keeps every point strictly inside
+            let u = (i as f64 + 1.0 / 3.0) / n;
+            let v = (j as f64 + 1.0 / 3.0) / n;
+            let w = 1.0 - u - v;
+            points.push(Point::new(
+                u * vertices[0].x + v * vertices[1].x + w * vertices[2].x,
+                u * vertices[0].y + v * vertices[1].y + w * vertices[2].y,
+            ));
+        }
+    }
+    points
+}
+
+/// Reduces colors to at most `k` centers, returning the centers and each color's center
+///
+/// Centers start from the most common color and then the color farthest from
+/// those picked so far, so the result is deterministic.
+fn quantize(colors: &[(f64, f64, f64)], k: usize) -> (Vec<(f64, f64, f64)>, Vec<usize>) {
+    let distance = |a: (f64, f64, f64), b: (f64, f64, f64)| {
+        ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2)).sqrt()
+    };
+    let nearest = |centers: &[(f64, f64, f64)], color: (f64, f64, f64)| {
+        (0..centers.len())
+            .min_by(|&x, &y| distance(centers[x], color).total_cmp(&distance(centers[y], color)))
+            .unwrap_or(0)
+    };
+
+    let most_common = (0..colors.len())
+        .max_by_key(|&i| {
+            colors
+                .iter()
+                .filter(|&&other| distance(colors[i], other) < DISTINCT_COLOR_DISTANCE)
+                .count()
+        })
+        .unwrap_or(0);
+    let mut centers = vec![colors[most_common]];
+    while centers.len() < k {
+        let farthest = colors
+            .iter()
+            .copied()
+            .map(|color| (color, distance(centers[nearest(&centers, color)], color)))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        match farthest {
+            Some((color, gap)) if gap > DISTINCT_COLOR_DISTANCE => centers.push(color),
+            _ => break,
+        }
+    }
+
+    let mut assignment = vec![0; colors.len()];
+    for _ in 0..QUANTIZE_ITERATIONS {
+        for (slot, &color) in assignment.iter_mut().zip(colors) {
+            *slot = nearest(&centers, color);
+        }
+        for (index, center) in centers.iter_mut().enumerate() {
+            let members: Vec<(f64, f64, f64)> = colors
+                .iter()
+                .zip(&assignment)
+                .filter(|&(_, &slot)| slot == index)
+                .map(|(&color, _)| color)
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            let count = members.len() as f64;
+            *center = (
+                members.iter().map(|c| c.0).sum::<f64>() / count,
+                members.iter().map(|c| c.1).sum::<f64>() / count,
+                members.iter().map(|c| c.2).sum::<f64>() / count,
+            );
+        }
+    }
+    (centers, assignment)
+}
+
+/// The average of the most common group of similar colors, and the group's size
+fn dominant_color(colors: &[[u8; 3]]) -> Option<([u8; 3], usize)> {
+    // Bucket similar colors so antialiasing and noise don't split the vote
+    let mut buckets: HashMap<[u8; 3], (usize, [u64; 3])> = HashMap::new();
+    for &color in colors {
+        let entry = buckets.entry(color.map(|c| c >> 4)).or_insert((0, [0; 3]));
+        entry.0 += 1;
+        for (sum, c) in entry.1.iter_mut().zip(color) {
+            *sum += c as u64;
+        }
+    }
+
+    // Ties go to the smaller bucket key so the result doesn't depend on hash order
+    let (_, (count, sums)) = buckets
+        .into_iter()
+        .max_by(|a, b| a.1 .0.cmp(&b.1 .0).then(b.0.cmp(&a.0)))?;
+    Some((sums.map(|sum| (sum / count as u64) as u8), count))
+}
+
+fn rgb_distance(a: [u8; 3], b: [u8; 3]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(&x, y)| (x as f64 - y as f64).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use resvg::tiny_skia::{Color, Paint, Rect, Transform};
+
+    #[test]
+    fn test_trace_two_colored_halves() {
+        // Red on the left, blue on the right, over a white background
+        let mut pixmap = Pixmap::new(200, 200).unwrap();
+        pixmap.fill(Color::WHITE);
+        let mut paint = Paint::default();
+        paint.set_color_rgba8(220, 30, 30, 255);
+        pixmap.fill_rect(Rect::from_xywh(20.0, 20.0, 80.0, 160.0).unwrap(), &paint, Transform::identity(), None);
+        paint.set_color_rgba8(30, 60, 220, 255);
+        pixmap.fill_rect(Rect::from_xywh(100.0, 20.0, 80.0, 160.0).unwrap(), &paint, Transform::identity(), None);
+
+        let raster = Raster::from_pixmap(pixmap);
+        assert_eq!(raster.background(), Some([255, 255, 255]));
+
+        let design = trace(&raster, &TraceOptions::default()).unwrap();
+        assert_eq!(design.shapes().len(), 2);
+        for shape in design.shapes() {
+            let (r, _, b) = ColorManager::hex_to_rgb(shape.fill());
+            // Cells straddling the seam take their majority side
+            let side = if r > b { -1.0 } else { 1.0 };
+            for &cell in &shape.cells {
+                let centroid = design.grid().get_cell_centroid(cell).unwrap();
+                assert!(centroid.x * side > -10.0);
+                assert!(centroid.y.abs() < 82.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_trace_svg_on_transparent_background() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+            <circle cx="50" cy="50" r="30" fill="#2E7D32"/>
+        </svg>"##;
+        let raster = Raster::from_svg(svg).unwrap();
+        assert_eq!(raster.background(), None);
+
+        let options = TraceOptions {
+            colors: 3,
+            ..TraceOptions::default()
+        };
+        let design = trace(&raster, &options).unwrap();
+        assert_eq!(design.shapes().len(), 1);
+        for &cell in &design.shapes()[0].cells {
+            let centroid = design.grid().get_cell_centroid(cell).unwrap();
+            assert!((centroid.x.powi(2) + centroid.y.powi(2)).sqrt() < 65.0);
+        }
+
+        let guide = raster.guide_mask();
+        assert!(guide.darkness_at(Point::new(0.0, 0.0)) > 0.5);
+        assert_eq!(guide.darkness_at(Point::new(90.0, 90.0)), 0.0);
+
+        let empty = Raster::from_svg(r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"/>"#).unwrap();
+        assert!(trace(&empty, &options).is_err());
+    }
+}
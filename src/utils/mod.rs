@@ -1,10 +1,23 @@
+use crate::generator::color::export::{self, NamedColor, PaletteFormat};
+use crate::generator::color::import::{self, ImportedPalette};
+#[cfg(feature = "png")]
+use crate::generator::GeneratedDesign;
+#[cfg(feature = "png")]
+use crate::png;
+#[cfg(feature = "png")]
+use crate::svg::{self, SvgOptions};
 use crate::Result;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(feature = "uuid")]
 use uuid::Uuid;
 
 #[cfg(test)]
 mod tests;
 
 /// Converts a UUID to a deterministic seed value
+#[cfg(feature = "uuid")]
 pub fn uuid_to_seed(uuid: &str) -> Result<u64> {
     let uuid = Uuid::parse_str(uuid)?;
     let bytes = uuid.as_bytes();
@@ -18,6 +31,161 @@ pub fn uuid_to_seed(uuid: &str) -> Result<u64> {
     Ok(seed)
 }
 
+pub use hexalith_core::stable_hash;
+
+/// Derives a deterministic seed from a name, such as a service or project name
+///
+/// Names that differ only in case or spacing give the same seed, see
+/// [`normalize_seed_text`]. The seed fits in a signed 64-bit integer, so it
+/// survives TOML files and JSON tooling.
+pub fn text_to_seed(text: &str) -> u64 {
+    stable_hash(normalize_seed_text(text).as_bytes()) & i64::MAX as u64
+}
+
+/// The form of a name that [`text_to_seed`] hashes
+///
+/// Lowercased, trimmed, and with each run of whitespace turned into one space.
+pub fn normalize_seed_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Escapes text for use in XML content and attribute values
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The UTC calendar date and time of day of `time`, as (year, month, day, seconds into the day)
+pub fn utc_date(time: SystemTime) -> (i64, u32, u32, u32) {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rest) = (seconds / 86_400, seconds % 86_400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month as u32, day as u32, rest as u32)
+}
+
+/// Side of the grayscale image a perceptual hash is computed from
+#[cfg(feature = "png")]
+const PHASH_SIZE: usize = 32;
+
+/// Side of the block of low-frequency coefficients that make up the hash
+#[cfg(feature = "png")]
+const PHASH_BLOCK: usize = 8;
+
+/// Computes a 64-bit perceptual hash of how a design looks
+///
+/// The design is rendered on white at 32x32, converted to luminance and
+/// transformed with a DCT; each bit records whether one of the 8x8 lowest
+/// frequencies is above their median. Designs that look alike get hashes a
+/// small [`phash_distance`] apart, whatever their seed or theme names.
+#[cfg(feature = "png")]
+pub fn phash(design: &GeneratedDesign) -> Result<u64> {
+    let options = SvgOptions {
+        background: Some("#FFFFFF".to_string()),
+        ..SvgOptions::default()
+    };
+    let size = PHASH_SIZE as u32;
+    let svg_data = svg::design_to_svg(design, size, size, &options)?;
+    let pixmap = png::render_tree_to_pixmap(&png::parse_svg(&svg_data)?, size, size)?;
+
+    let luminance: Vec<f64> = pixmap
+        .pixels()
+        .iter()
+        .map(|pixel| 0.299 * pixel.red() as f64 + 0.587 * pixel.green() as f64 + 0.114 * pixel.blue() as f64)
+        .collect();
+
+    // Only the low frequencies are needed, so compute just that corner of the DCT
+    let n = PHASH_SIZE as f64;
+    let basis = |frequency: usize, position: usize| {
+        ((2 * position + 1) as f64 * frequency as f64 * std::f64::consts::PI / (2.0 * n)).cos()
+    };
+    let mut coefficients = Vec::with_capacity(PHASH_BLOCK * PHASH_BLOCK);
+    for v in 0..PHASH_BLOCK {
+        for u in 0..PHASH_BLOCK {
+            let mut sum = 0.0;
+            for y in 0..PHASH_SIZE {
+                for x in 0..PHASH_SIZE {
+                    sum += luminance[y * PHASH_SIZE + x] * basis(u, x) * basis(v, y);
+                }
+            }
+            coefficients.push(sum);
+        }
+    }
+
+    // The DC term only measures overall brightness, so it is left out of the median
+    let mut sorted = coefficients[1..].to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let median = sorted[sorted.len() / 2];
+
+    Ok(coefficients
+        .iter()
+        .enumerate()
+        .filter(|(_, &c)| c > median)
+        .fold(0u64, |hash, (bit, _)| hash | 1 << bit))
+}
+
+/// Reads a palette file, choosing the format from its extension (.gpl, .ase or .json)
+pub fn palette_from_file<P: AsRef<Path>>(path: P) -> Result<ImportedPalette> {
+    let path = path.as_ref();
+    let read_error = |e: std::io::Error| format!("Cannot read palette {}: {}", path.display(), e);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+
+    match path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+        Some("gpl") => {
+            let text = fs::read_to_string(path).map_err(read_error)?;
+            let mut palette = import::from_gpl(&text)?;
+            if palette.name == "custom" && !stem.is_empty() {
+                palette.name = stem.to_string();
+            }
+            Ok(palette)
+        }
+        Some("ase") => {
+            let data = fs::read(path).map_err(read_error)?;
+            import::from_ase(&data, stem)
+        }
+        Some("json") => {
+            let text = fs::read_to_string(path).map_err(read_error)?;
+            import::from_json(&text, stem)
+        }
+        _ => Err(format!(
+            "Unsupported palette file {} (expected a .gpl, .ase or .json file)",
+            path.display()
+        )
+        .into()),
+    }
+}
+
+/// Writes an exported palette to a file
+pub fn save_palette<P: AsRef<Path>>(colors: &[NamedColor], name: &str, format: PaletteFormat, path: P) -> Result<()> {
+    fs::write(path, export::export(colors, name, format))?;
+    Ok(())
+}
+
+/// Number of differing bits between two perceptual hashes, from 0 (alike) to 64
+pub fn phash_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
 /// Returns a default color palette
 pub fn default_color_palette() -> Vec<&'static str> {
     vec![
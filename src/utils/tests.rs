@@ -1,9 +1,14 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
-    use crate::utils::{default_color_palette, uuid_to_seed};
+    use crate::utils::{default_color_palette, escape_xml, normalize_seed_text, stable_hash, text_to_seed, utc_date};
+    use std::time::{Duration, UNIX_EPOCH};
 
     #[test]
+    #[cfg(feature = "uuid")]
     fn test_uuid_to_seed() {
+        use crate::utils::uuid_to_seed;
+
         // Test a valid UUID
         let uuid = "f47ac10b-58cc-4372-a567-0e02b2c3d479";
         let seed = uuid_to_seed(uuid).unwrap();
@@ -19,7 +24,10 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "uuid")]
     fn test_invalid_uuid() {
+        use crate::utils::uuid_to_seed;
+
         // Test an invalid UUID
         let result = uuid_to_seed("not-a-uuid");
         assert!(result.is_err());
@@ -35,4 +43,60 @@ mod tests {
         // First color should start with #
         assert!(palette[0].starts_with('#'));
     }
+
+    #[test]
+    fn test_text_to_seed() {
+        assert_eq!(text_to_seed("payments-service"), text_to_seed(" payments-service\n"));
+        assert_ne!(text_to_seed("payments-service"), text_to_seed("auth"));
+        assert!(text_to_seed("payments-service") <= i64::MAX as u64);
+
+        assert_eq!(normalize_seed_text("  Payments \t Service\n"), "payments service");
+        assert_eq!(text_to_seed("Payments  Service"), text_to_seed("payments service"));
+        assert_ne!(text_to_seed("payments service"), text_to_seed("payments-service"));
+    }
+
+    #[test]
+    fn test_stable_hash() {
+        // Reference values for 64-bit FNV-1a
+        assert_eq!(stable_hash(b""), 0xcbf29ce484222325);
+        assert_eq!(stable_hash(b"a"), 0xaf63dc4c8601ec8c);
+        assert_ne!(stable_hash(b"hexalith"), stable_hash(b"hexalitH"));
+    }
+
+    #[test]
+    #[cfg(feature = "png")]
+    fn test_phash() {
+        use crate::generator::{GeneratorConfig, Theme};
+        use crate::utils::{phash, phash_distance};
+
+        let config = GeneratorConfig::new(4, 3, 0.8, Some(21));
+        let design = config.generate().unwrap();
+        let hash = phash(&design).unwrap();
+
+        // The hash depends only on the rendered pixels, so it is stable
+        assert_eq!(hash, phash(&config.generate().unwrap()).unwrap());
+        assert_ne!(hash, 0);
+
+        // A similar design is closer than an unrelated one
+        let faded = GeneratorConfig::new(4, 3, 0.75, Some(21)).generate().unwrap();
+        let other = config.clone().with_seed(Some(900)).with_theme(Theme::Greens).generate().unwrap();
+        let near = phash_distance(hash, phash(&faded).unwrap());
+        let far = phash_distance(hash, phash(&other).unwrap());
+        assert!(near < far, "{} >= {}", near, far);
+        assert_eq!(phash_distance(0, u64::MAX), 64);
+    }
+
+    #[test]
+    fn test_utc_date() {
+        assert_eq!(utc_date(UNIX_EPOCH), (1970, 1, 1, 0));
+        // 2000-02-29T12:34:56Z, a leap day in a century year
+        assert_eq!(utc_date(UNIX_EPOCH + Duration::from_secs(951_827_696)), (2000, 2, 29, 45_296));
+        assert_eq!(utc_date(UNIX_EPOCH + Duration::from_secs(1_735_689_599)), (2024, 12, 31, 86_399));
+    }
+
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(escape_xml("plain"), "plain");
+        assert_eq!(escape_xml(r#"<a href="x">Tom & 'Jerry'</a>"#), "&lt;a href=&quot;x&quot;&gt;Tom &amp; &apos;Jerry&apos;&lt;/a&gt;");
+    }
 }
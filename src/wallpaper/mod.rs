@@ -0,0 +1,245 @@
+use crate::generator::color::ColorManager;
+use crate::generator::GeneratorConfig;
+#[cfg(feature = "png")]
+use crate::png;
+use crate::svg;
+use crate::Result;
+use ::svg::node::element::{Definitions, LinearGradient, Rectangle, Stop};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::io::Write;
+
+/// Resolution used when none is given
+pub const DEFAULT_RESOLUTION: (u32, u32) = (3840, 2160);
+
+/// Longest side a wallpaper may have, the largest PNG that can be rendered
+pub const MAX_SIDE: u32 = 16384;
+
+/// Upper bound on the number of designs scattered over one wallpaper
+pub const MAX_DESIGNS: usize = 32;
+
+/// Placement attempts per design before settling for fewer designs
+const PLACEMENT_ATTEMPTS: usize = 400;
+
+/// Where one design of the family sits on the canvas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    /// Seed the design is generated from
+    pub seed: u64,
+    /// Center of the design in canvas pixels
+    pub x: f64,
+    pub y: f64,
+    /// Distance from the center to the hexagon's corners, in pixels
+    pub radius: f64,
+    /// Rotation in degrees, always a multiple of 30 so edges stay on the grid's angles
+    pub rotation: f64,
+}
+
+/// Parses a `WIDTHxHEIGHT` resolution such as `3840x2160`
+pub fn parse_resolution(resolution: &str) -> Result<(u32, u32)> {
+    let invalid = || format!("Invalid resolution '{}' (expected WIDTHxHEIGHT, e.g. 3840x2160)", resolution);
+    let (width, height) = resolution
+        .trim()
+        .split_once(['x', 'X'])
+        .ok_or_else(invalid)?;
+    let width: u32 = width.trim().parse().map_err(|_| invalid())?;
+    let height: u32 = height.trim().parse().map_err(|_| invalid())?;
+
+    if width == 0 || height == 0 {
+        return Err(invalid().into());
+    }
+    if width > MAX_SIDE || height > MAX_SIDE {
+        return Err(format!(
+            "Resolution {}x{} is too large (at most {} pixels per side)",
+            width,
+            height,
+            MAX_SIDE
+        )
+        .into());
+    }
+    Ok((width, height))
+}
+
+/// Lays out `count` designs of one seed family without overlaps
+///
+/// The first design is the largest and sits near the middle third of the
+/// canvas; the rest shrink progressively and may run off the edges. The same
+/// seed always gives the same layout.
+pub fn layout(seed: u64, width: u32, height: u32, count: usize) -> Vec<Placement> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let (width, height) = (width as f64, height as f64);
+    let short_side = width.min(height);
+    let count = count.clamp(1, MAX_DESIGNS);
+
+    let mut placements: Vec<Placement> = Vec::with_capacity(count);
+    for index in 0..count {
+        let radius = if index == 0 {
+            short_side * 0.3
+        } else {
+            short_side * rng.gen_range(0.07..0.18) * (1.0 - index as f64 / (2 * count) as f64)
+        };
+
+        for _ in 0..PLACEMENT_ATTEMPTS {
+            let (x, y) = if index == 0 {
+                (
+                    width * rng.gen_range(0.33..0.67),
+                    height * rng.gen_range(0.33..0.67),
+                )
+            } else {
+                (rng.gen_range(0.0..width), rng.gen_range(0.0..height))
+            };
+
+            // Leave a small gap between designs so each one reads on its own
+            let fits = placements.iter().all(|other| {
+                let distance = ((other.x - x).powi(2) + (other.y - y).powi(2)).sqrt();
+                distance >= (other.radius + radius) * 1.05
+            });
+            if fits {
+                placements.push(Placement {
+                    seed: rng.gen(),
+                    x,
+                    y,
+                    radius,
+                    rotation: rng.gen_range(0..12) as f64 * 30.0,
+                });
+                break;
+            }
+        }
+    }
+
+    placements
+}
+
+/// Renders a wallpaper of related designs as SVG
+///
+/// Every design shares the config's theme and parameters but has its own seed
+/// from the family of `config`'s seed. Without a `background` the canvas is
+/// filled with a dark diagonal gradient taken from the theme.
+pub fn wallpaper_svg(
+    config: &GeneratorConfig,
+    width: u32,
+    height: u32,
+    count: usize,
+    background: Option<&str>,
+) -> Result<String> {
+    let data = write_wallpaper_svg(config, width, height, count, background, Vec::new())?;
+    Ok(String::from_utf8(data)?)
+}
+
+/// Streams a wallpaper as SVG to `writer`, one design at a time
+///
+/// Produces the same document as [`wallpaper_svg`] without holding it in
+/// memory, and returns the writer once the document is closed.
+pub fn write_wallpaper_svg<W: Write>(
+    config: &GeneratorConfig,
+    width: u32,
+    height: u32,
+    count: usize,
+    background: Option<&str>,
+    writer: W,
+) -> Result<W> {
+    let seed = config.seed().unwrap_or_else(rand::random);
+    let mut writer = svg::SvgWriter::new(writer, width, height, (0.0, 0.0, width as f64, height as f64))?;
+
+    let fill = match background {
+        Some(color) => color.to_string(),
+        None => {
+            writer.write_node(&Definitions::new().add(background_gradient(config)))?;
+            "url(#background)".to_string()
+        }
+    };
+    writer.write_node(
+        &Rectangle::new()
+            .set("width", width)
+            .set("height", height)
+            .set("fill", fill),
+    )?;
+
+    for placement in layout(seed, width, height, count) {
+        let design = config.clone().with_seed(Some(placement.seed)).generate()?;
+        let transform = svg::Transform {
+            x: placement.x,
+            y: placement.y,
+            scale: placement.radius / 100.0,
+            rotation: placement.rotation,
+        };
+        writer.write_design(&design, &transform, &svg::SvgOptions::default())?;
+    }
+
+    Ok(writer.finish()?)
+}
+
+/// Renders a wallpaper of related designs as PNG
+#[cfg(feature = "png")]
+pub fn wallpaper_png(
+    config: &GeneratorConfig,
+    width: u32,
+    height: u32,
+    count: usize,
+    background: Option<&str>,
+) -> Result<Vec<u8>> {
+    let svg_data = wallpaper_svg(config, width, height, count, background)?;
+    png::convert_svg_to_png(&svg_data, width, height)
+}
+
+/// A diagonal gradient between deep shades of the theme's first and last colors
+fn background_gradient(config: &GeneratorConfig) -> LinearGradient {
+    let palette = ColorManager::theme_palette(&config.theme());
+    let shade = |color: &str, lightness: f64| {
+        let (hue, saturation, _) = ColorManager::hex_to_hsl(color);
+        ColorManager::hsl_to_hex(hue, saturation * 0.6, lightness)
+    };
+    let first = palette.first().map(String::as_str).unwrap_or("#333333");
+    let last = palette.last().map(String::as_str).unwrap_or(first);
+
+    LinearGradient::new()
+        .set("id", "background")
+        .set("x1", "0")
+        .set("y1", "0")
+        .set("x2", "1")
+        .set("y2", "1")
+        .add(Stop::new().set("offset", "0").set("stop-color", shade(first, 0.12)))
+        .add(Stop::new().set("offset", "1").set("stop-color", shade(last, 0.24)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_resolution() {
+        assert_eq!(parse_resolution("3840x2160").unwrap(), (3840, 2160));
+        assert_eq!(parse_resolution("1170X2532").unwrap(), (1170, 2532));
+        assert!(parse_resolution("3840").is_err());
+        assert!(parse_resolution("0x100").is_err());
+        assert!(parse_resolution("40000x100").is_err());
+    }
+
+    #[test]
+    fn test_layout_is_deterministic_and_separated() {
+        let placements = layout(9, 1920, 1080, 8);
+        assert_eq!(placements, layout(9, 1920, 1080, 8));
+        assert!(placements.len() > 1);
+
+        for (i, a) in placements.iter().enumerate() {
+            for b in &placements[i + 1..] {
+                let distance = ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt();
+                assert!(distance >= a.radius + b.radius);
+            }
+        }
+    }
+
+    #[test]
+    fn test_wallpaper_outputs() {
+        let config = GeneratorConfig::new(3, 3, 0.8, Some(4));
+        let svg = wallpaper_svg(&config, 400, 300, 5, None).unwrap();
+        assert!(svg.contains("url(#background)"));
+        assert!(svg.contains("rotate("));
+
+        #[cfg(feature = "png")]
+        {
+            let png = wallpaper_png(&config, 400, 300, 5, Some("#101010")).unwrap();
+            assert_eq!(&png[0..8], &[137, 80, 78, 71, 13, 10, 26, 10]);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use super::cache::CacheStats;
+use super::stats::StatsSummary;
+use maud::{html, Markup, PreEscaped};
+use std::time::{Duration, SystemTime};
+
+/// Rows shown in the recent renders and top seeds tables
+pub const ADMIN_ROWS: usize = 20;
+
+const ADMIN_CSS: &str = r#"
+body { font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, Helvetica, Arial, sans-serif; color: #333; max-width: 1000px; margin: 0 auto; padding: 20px; }
+h1 { color: #2b3990; }
+table { border-collapse: collapse; margin-bottom: 24px; }
+th, td { border-bottom: 1px solid #ddd; padding: 4px 12px; text-align: left; }
+td.number { text-align: right; font-variant-numeric: tabular-nums; }
+.failed { color: #c0392b; }
+"#;
+
+/// The operator page: render latency, design cache use, top seeds and recent renders
+pub fn page(instance_name: &str, summary: &StatsSummary, cache: &CacheStats) -> Markup {
+    let lookups = cache.hits + cache.misses;
+    html! {
+        (maud::DOCTYPE)
+        html lang="en" {
+            head {
+                meta charset="utf-8";
+                link rel="icon" href="/favicon.svg" type="image/svg+xml";
+                title { "Hexalith Admin · " (instance_name) }
+                style { (PreEscaped(ADMIN_CSS)) }
+            }
+            body {
+                h1 { "Hexalith Admin" }
+                p { "Instance: " strong { (instance_name) } }
+
+                h2 { "Render latency" }
+                p { "Over the last " (summary.renders) " renders, " (summary.failures) " of which failed." }
+                @if !summary.latency.is_empty() {
+                    table {
+                        tr { @for (p, _) in &summary.latency { th { "p" (p) } } }
+                        tr { @for (_, duration) in &summary.latency { td.number { (millis(*duration)) } } }
+                    }
+                }
+
+                h2 { "Design cache" }
+                table {
+                    tr { th { "Hits" } td.number { (cache.hits) } }
+                    tr { th { "Misses" } td.number { (cache.misses) } }
+                    tr {
+                        th { "Hit rate" }
+                        td.number {
+                            @if lookups == 0 { "–" } @else { (format!("{:.1}%", cache.hits as f64 * 100.0 / lookups as f64)) }
+                        }
+                    }
+                    tr { th { "Designs kept" } td.number { (cache.designs) " / " (cache.capacity) } }
+                    tr { th { "Design tokens kept" } td.number { (cache.tokens) " / " (cache.capacity) } }
+                }
+
+                h2 { "Top seeds" }
+                table {
+                    tr { th { "Seed" } th { "Renders" } }
+                    @for (seed, count) in &summary.top_seeds {
+                        tr {
+                            td { a href=(format!("/svg/{}", seed)) { (seed) } }
+                            td.number { (count) }
+                        }
+                    }
+                }
+
+                h2 { "Recent renders" }
+                table {
+                    tr { th { "When" } th { "Endpoint" } th { "Design" } th { "Time" } }
+                    @for event in &summary.recent {
+                        tr class=[(!event.ok).then_some("failed")] {
+                            td { (ago(event.at)) }
+                            td { code { (event.endpoint) } }
+                            td { code { (event.design) } }
+                            td.number { (millis(event.duration)) }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
+
+fn ago(at: SystemTime) -> String {
+    let seconds = SystemTime::now().duration_since(at).unwrap_or_default().as_secs();
+    match seconds {
+        0..=59 => format!("{}s ago", seconds),
+        60..=3599 => format!("{}m ago", seconds / 60),
+        _ => format!("{}h ago", seconds / 3600),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::web::stats::{RenderEvent, RenderStats};
+
+    #[test]
+    fn test_admin_page() {
+        let stats = RenderStats::default();
+        stats.record(RenderEvent {
+            at: SystemTime::now(),
+            endpoint: "/png/:seed",
+            seed: 4242,
+            design: "/png/4242?theme=<blues>".to_string(),
+            duration: Duration::from_micros(12_500),
+            ok: false,
+        });
+        let cache = CacheStats {
+            hits: 3,
+            misses: 1,
+            designs: 1,
+            tokens: 0,
+            capacity: 64,
+        };
+        let html = page("eu-west-1", &stats.summary(ADMIN_ROWS), &cache).into_string();
+
+        assert!(html.contains("eu-west-1"));
+        assert!(html.contains("12.5 ms"));
+        assert!(html.contains("75.0%"));
+        assert!(html.contains("href=\"/svg/4242\""));
+        assert!(html.contains("class=\"failed\""));
+        // Parameters are escaped
+        assert!(html.contains("theme=&lt;blues&gt;"));
+    }
+}
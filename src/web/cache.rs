@@ -0,0 +1,172 @@
+use super::params::CanonicalParams;
+use crate::generator::GeneratedDesign;
+use crate::Result;
+use axum::http::HeaderMap;
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// How many grown designs are kept for recoloring
+const DESIGN_CACHE_SIZE: usize = 64;
+
+/// A small first-in, first-out cache of designs, newest last
+type DesignCache = Lazy<Mutex<VecDeque<(String, GeneratedDesign)>>>;
+
+/// Recently grown designs, keyed by [`CanonicalParams::shape_key`]
+static DESIGNS: DesignCache = Lazy::new(|| Mutex::new(VecDeque::with_capacity(DESIGN_CACHE_SIZE)));
+
+/// Designs handed out to clients, keyed by their design token
+static TOKENS: DesignCache = Lazy::new(|| Mutex::new(VecDeque::with_capacity(DESIGN_CACHE_SIZE)));
+
+/// Requests [`design_for`] answered from, or had to add to, the design cache
+static HITS: AtomicU64 = AtomicU64::new(0);
+static MISSES: AtomicU64 = AtomicU64::new(0);
+
+/// How the in-memory design caches are doing, for the admin page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Grown designs kept for recoloring, at most [`DESIGN_CACHE_SIZE`]
+    pub designs: usize,
+    /// Designs kept for their design token
+    pub tokens: usize,
+    pub capacity: usize,
+}
+
+pub fn stats() -> CacheStats {
+    let len = |cache: &DesignCache| cache.lock().unwrap_or_else(|e| e.into_inner()).len();
+    CacheStats {
+        hits: HITS.load(Ordering::Relaxed),
+        misses: MISSES.load(Ordering::Relaxed),
+        designs: len(&DESIGNS),
+        tokens: len(&TOKENS),
+        capacity: DESIGN_CACHE_SIZE,
+    }
+}
+
+/// The design `params` describe, reusing recently grown shapes when only colors changed
+///
+/// Live previews send a new request for every theme or opacity change; those
+/// only recolor a cached design instead of growing its shapes again.
+pub fn design_for(params: &CanonicalParams) -> Result<GeneratedDesign> {
+    let key = params.shape_key();
+    if let Some(design) = lookup(&DESIGNS, &key) {
+        HITS.fetch_add(1, Ordering::Relaxed);
+        let design = if design.config().theme() == params.theme {
+            design
+        } else {
+            design.with_theme(params.theme.clone())
+        };
+        return Ok(design.with_opacity(params.opacity));
+    }
+
+    MISSES.fetch_add(1, Ordering::Relaxed);
+    let design = params.config().generate()?;
+    insert(&DESIGNS, key, &design);
+    Ok(design)
+}
+
+/// The token identifying a design in follow-up requests such as recoloring
+///
+/// It is the design checksum, so every edit to a design gets a new token.
+pub fn design_token(design: &GeneratedDesign) -> String {
+    format!("{:016x}", design.checksum())
+}
+
+/// Keeps `design` for later requests and returns its token
+pub fn remember(design: &GeneratedDesign) -> String {
+    let token = design_token(design);
+    if lookup(&TOKENS, &token).is_none() {
+        insert(&TOKENS, token.clone(), design);
+    }
+    token
+}
+
+/// A design previously passed to [`remember`], unless it has since been evicted
+pub fn design_by_token(token: &str) -> Option<GeneratedDesign> {
+    lookup(&TOKENS, token)
+}
+
+fn lookup(cache: &DesignCache, key: &str) -> Option<GeneratedDesign> {
+    let designs = cache.lock().unwrap_or_else(|e| e.into_inner());
+    designs.iter().find(|(k, _)| k == key).map(|(_, design)| design.clone())
+}
+
+fn insert(cache: &DesignCache, key: String, design: &GeneratedDesign) {
+    let mut designs = cache.lock().unwrap_or_else(|e| e.into_inner());
+    if designs.len() >= DESIGN_CACHE_SIZE {
+        designs.pop_front();
+    }
+    designs.push_back((key, design.clone()));
+}
+
+/// Checks whether the request's `If-None-Match` header already matches the tag
+pub fn is_not_modified(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all("if-none-match")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || weak_eq(candidate, etag))
+}
+
+/// Weak comparison: tags match if their opaque parts are equal, ignoring `W/`
+fn weak_eq(a: &str, b: &str) -> bool {
+    a.trim_start_matches("W/") == b.trim_start_matches("W/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_design_for_recolors_cached_shapes() {
+        let params = |query: &str| {
+            let uri: axum::http::Uri = format!("/svg/1?{}", query).parse().unwrap();
+            axum::extract::Query::<super::super::params::LogoParams>::try_from_uri(&uri)
+                .unwrap()
+                .0
+                .canonicalize(90210)
+        };
+
+        let first = params("theme=mesos&opacity=0.8");
+        let recolored = params("theme=rainbow&opacity=0.5");
+        assert_eq!(first.shape_key(), recolored.shape_key());
+
+        design_for(&first).unwrap();
+        let hits = stats().hits;
+        let design = design_for(&recolored).unwrap();
+        assert!(stats().hits > hits);
+        assert_eq!(design.checksum(), recolored.config().generate().unwrap().checksum());
+    }
+
+    #[test]
+    fn test_design_tokens() {
+        let design = crate::generator::GeneratorConfig::new(2, 3, 0.8, Some(77)).generate().unwrap();
+        let token = remember(&design);
+        assert_eq!(token, design_token(&design));
+        assert_eq!(design_by_token(&token).unwrap().checksum(), design.checksum());
+        assert!(design_by_token("0000000000000000").is_none());
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let etag = super::super::cache_policy::CachePolicy::Preview.etag("key");
+        let mut headers = HeaderMap::new();
+        assert!(!is_not_modified(&headers, &etag));
+
+        headers.insert("if-none-match", format!("\"other\", {}", etag).parse().unwrap());
+        assert!(is_not_modified(&headers, &etag));
+
+        // A strong tag with the same opaque value matches under weak comparison
+        let strong = etag.trim_start_matches("W/").to_string();
+        headers.insert("if-none-match", strong.parse().unwrap());
+        assert!(is_not_modified(&headers, &etag));
+
+        headers.insert("if-none-match", "\"other\"".parse().unwrap());
+        assert!(!is_not_modified(&headers, &etag));
+    }
+}
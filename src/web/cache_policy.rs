@@ -0,0 +1,151 @@
+use crate::generator::color::Theme;
+use crate::utils;
+use super::params::LogoParams;
+use axum::extract::Request;
+use axum::http::{header, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response;
+
+/// How long clients and shared caches may keep a response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// Fully determined by the URL, like seed-addressed images; kept for a year
+    Immutable,
+    /// Depends on server state that can change, like configured themes; kept briefly
+    Preview,
+    /// API results, the admin page and errors, never stored
+    NoStore,
+}
+
+impl CachePolicy {
+    /// The `Cache-Control` header value for the policy
+    pub fn cache_control(&self) -> &'static str {
+        match self {
+            CachePolicy::Immutable => "public, max-age=31536000, immutable",
+            // Five minutes, so config changes reach clients soon
+            CachePolicy::Preview => "public, max-age=300",
+            CachePolicy::NoStore => "no-store",
+        }
+    }
+
+    /// The entity tag for a response identified by a canonical key
+    ///
+    /// Immutable responses get a strong tag, since the key and server version
+    /// fix their bytes; others get a weak one.
+    pub fn etag(&self, key: &str) -> String {
+        let hash = utils::stable_hash(format!("{}|{}", env!("CARGO_PKG_VERSION"), key).as_bytes());
+        match self {
+            CachePolicy::Immutable => format!("\"{:016x}\"", hash),
+            _ => format!("W/\"{:016x}\"", hash),
+        }
+    }
+
+    /// Policy of a design image requested with these parameters
+    ///
+    /// Presets and non-built-in themes come from the server's config, which can
+    /// be reloaded, and an unknown theme falls back to the default until it's
+    /// configured, so those images are only previews.
+    pub fn for_design(params: &LogoParams) -> Self {
+        let builtin_theme = match params.theme.as_deref() {
+            Some(theme) => theme.parse::<Theme>().is_ok_and(|theme| is_builtin(&theme)),
+            None => true,
+        };
+        if builtin_theme && params.preset.is_none() {
+            CachePolicy::Immutable
+        } else {
+            CachePolicy::Preview
+        }
+    }
+
+    /// Policy of responses whose handler doesn't choose one
+    fn default_for_path(path: &str) -> Self {
+        if path == "/generate" || path == "/admin" || path.starts_with("/api/") {
+            CachePolicy::NoStore
+        } else {
+            CachePolicy::Preview
+        }
+    }
+}
+
+fn is_builtin(theme: &Theme) -> bool {
+    match theme {
+        Theme::Custom { .. } => false,
+        Theme::Blend(from, to, _) => is_builtin(from) && is_builtin(to),
+        _ => true,
+    }
+}
+
+/// Adds `Cache-Control` to responses without one: errors are never stored,
+/// API results neither, and pages and assets are kept briefly
+pub async fn apply_default(request: Request, next: Next) -> Response {
+    let default = CachePolicy::default_for_path(request.uri().path());
+    let mut response = next.run(request).await;
+    if !response.headers().contains_key(header::CACHE_CONTROL) {
+        let failed = response.status().is_client_error() || response.status().is_server_error();
+        let policy = if failed { CachePolicy::NoStore } else { default };
+        response
+            .headers_mut()
+            .insert(header::CACHE_CONTROL, HeaderValue::from_static(policy.cache_control()));
+    }
+    response
+}
+
+/// Weakens strong entity tags on compressed responses
+///
+/// A strong tag promises identical bytes, which gzip and brotli bodies of one
+/// image are not. `If-None-Match` compares weakly, so revalidation still works.
+pub async fn weaken_compressed_etag(mut response: Response) -> Response {
+    if !response.headers().contains_key(header::CONTENT_ENCODING) {
+        return response;
+    }
+    let weakened = response
+        .headers()
+        .get(header::ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .filter(|etag| !etag.starts_with("W/"))
+        .and_then(|etag| HeaderValue::from_str(&format!("W/{}", etag)).ok());
+    if let Some(etag) = weakened {
+        response.headers_mut().insert(header::ETAG, etag);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(query: &str) -> LogoParams {
+        let uri: axum::http::Uri = format!("/svg/1?{}", query).parse().unwrap();
+        axum::extract::Query::try_from_uri(&uri).unwrap().0
+    }
+
+    #[test]
+    fn test_design_policy() {
+        assert_eq!(CachePolicy::for_design(&params("")), CachePolicy::Immutable);
+        assert_eq!(CachePolicy::for_design(&params("theme=BLUES&shapes=3")), CachePolicy::Immutable);
+        assert_eq!(CachePolicy::for_design(&params("theme=mesos:reds:0.5")), CachePolicy::Immutable);
+
+        // Anything the server's config decides can change under the same URL
+        assert_eq!(CachePolicy::for_design(&params("preset=dashboard")), CachePolicy::Preview);
+        assert_eq!(CachePolicy::for_design(&params("theme=not-yet-configured")), CachePolicy::Preview);
+        crate::register_theme("policy-corp", vec!["#0B3D91"]).unwrap();
+        assert_eq!(CachePolicy::for_design(&params("theme=policy-corp")), CachePolicy::Preview);
+        assert_eq!(CachePolicy::for_design(&params("theme=mesos:policy-corp:0.5")), CachePolicy::Preview);
+    }
+
+    #[test]
+    fn test_policy_headers() {
+        assert!(CachePolicy::Immutable.cache_control().contains("immutable"));
+        assert_eq!(CachePolicy::NoStore.cache_control(), "no-store");
+        assert_eq!(CachePolicy::default_for_path("/api/batch"), CachePolicy::NoStore);
+        assert_eq!(CachePolicy::default_for_path("/generate"), CachePolicy::NoStore);
+        assert_eq!(CachePolicy::default_for_path("/admin"), CachePolicy::NoStore);
+        assert_eq!(CachePolicy::default_for_path("/"), CachePolicy::Preview);
+
+        let strong = CachePolicy::Immutable.etag("seed=1");
+        assert!(strong.starts_with('"'));
+        assert_eq!(strong, CachePolicy::Immutable.etag("seed=1"));
+        assert_ne!(strong, CachePolicy::Immutable.etag("seed=2"));
+        assert_eq!(CachePolicy::Preview.etag("seed=1"), format!("W/{}", strong));
+    }
+}
@@ -0,0 +1,389 @@
+use super::params::LogoParams;
+use super::state::AppState;
+use super::tenant::{self, TenantConfig};
+use crate::generator::color::{self, parse_color, ColorManager, Theme};
+use crate::Result;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// Most seeds one `/api/batch` request may render unless the config says otherwise
+pub const DEFAULT_MAX_BATCH_SEEDS: usize = 32;
+
+/// Most seeds one batch job may render unless the config says otherwise
+pub const DEFAULT_MAX_JOB_SEEDS: usize = 1024;
+
+/// How long file events are collected before reloading, so one save reloads once
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Settings a running server picks up again on reload
+///
+/// Read from the TOML file named by `HEXALITH_CONFIG`:
+///
+/// ```toml
+/// api_tokens = ["token-one"]
+///
+/// [themes]
+/// corporate = ["#0B3D91", "#FC3D21", "#FFFFFF"]
+///
+/// [presets.dashboard]
+/// theme = "corporate"
+/// shapes = 3
+///
+/// [limits]
+/// max_batch_seeds = 16
+///
+/// [tenants.acme]
+/// palette = ["#E4002B", "#1D1D1B"]
+/// silhouette = "acme.svg"
+/// requests_per_minute = 600
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebConfig {
+    /// Bearer tokens accepted on protected endpoints, besides `HEXALITH_API_TOKENS`
+    #[serde(default)]
+    pub api_tokens: Vec<String>,
+    /// Extra palettes, usable by name like the built-in themes
+    #[serde(default)]
+    pub themes: BTreeMap<String, Vec<String>>,
+    /// Named parameter sets requested with `preset`
+    #[serde(default)]
+    pub presets: BTreeMap<String, WebPreset>,
+    #[serde(default)]
+    pub limits: Limits,
+    /// Branding defaults of the products served under `/t/<tenant>/...`
+    #[serde(default)]
+    pub tenants: BTreeMap<String, TenantConfig>,
+}
+
+/// Defaults a request gets by naming the preset; its own parameters still win
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebPreset {
+    pub theme: Option<String>,
+    pub shapes: Option<u8>,
+    pub grid_size: Option<u8>,
+    pub opacity: Option<f32>,
+    pub overlap: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Limits {
+    /// Most seeds one `/api/batch` request may render
+    #[serde(default = "default_max_batch_seeds")]
+    pub max_batch_seeds: usize,
+    /// Most seeds one batch job on `/api/jobs` may render
+    #[serde(default = "default_max_job_seeds")]
+    pub max_job_seeds: usize,
+}
+
+fn default_max_batch_seeds() -> usize {
+    DEFAULT_MAX_BATCH_SEEDS
+}
+
+fn default_max_job_seeds() -> usize {
+    DEFAULT_MAX_JOB_SEEDS
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_batch_seeds: DEFAULT_MAX_BATCH_SEEDS,
+            max_job_seeds: DEFAULT_MAX_JOB_SEEDS,
+        }
+    }
+}
+
+impl WebConfig {
+    /// Parses and checks a config, so a bad edit is rejected before anything changes
+    pub fn parse(text: &str) -> Result<Self> {
+        let config: Self = toml::from_str(text).map_err(|e| format!("Invalid config: {}", e))?;
+
+        for (name, colors) in &config.themes {
+            if ColorManager::builtin_themes().contains(&name.trim().to_lowercase().as_str()) {
+                return Err(format!("Theme '{}' is built in and can't be replaced", name).into());
+            }
+            if colors.is_empty() {
+                return Err(format!("Theme '{}' needs at least one color", name).into());
+            }
+            for color in colors {
+                parse_color(color).map_err(|e| format!("Theme '{}': {}", name, e))?;
+            }
+        }
+        for (name, preset) in &config.presets {
+            if let Some(theme) = &preset.theme {
+                let configured = config.themes.keys().any(|key| key.eq_ignore_ascii_case(theme.trim()));
+                if !configured {
+                    theme
+                        .parse::<Theme>()
+                        .map_err(|e| format!("Preset '{}': {}", name, e))?;
+                }
+            }
+        }
+        if config.limits.max_batch_seeds == 0 {
+            return Err("limits.max_batch_seeds must be at least 1".into());
+        }
+        if config.limits.max_job_seeds == 0 {
+            return Err("limits.max_job_seeds must be at least 1".into());
+        }
+        for (name, tenant) in &config.tenants {
+            if !tenant::is_valid_name(name) {
+                return Err(format!("Invalid tenant name '{}' (use letters, digits, '-' and '_')", name).into());
+            }
+            if tenant.theme.is_some() && !tenant.palette.is_empty() {
+                return Err(format!("Tenant '{}' can have a theme or a palette, not both", name).into());
+            }
+            if let Some(theme) = &tenant.theme {
+                let configured = config.themes.keys().any(|key| key.eq_ignore_ascii_case(theme.trim()));
+                if !configured {
+                    theme
+                        .parse::<Theme>()
+                        .map_err(|e| format!("Tenant '{}': {}", name, e))?;
+                }
+            }
+            if !tenant.palette.is_empty() {
+                // The palette is registered as a theme named after the tenant
+                let taken = ColorManager::builtin_themes().contains(&name.to_lowercase().as_str())
+                    || config.themes.keys().any(|key| key.eq_ignore_ascii_case(name));
+                if taken {
+                    return Err(format!("Tenant '{}' has a palette but its name is already a theme", name).into());
+                }
+                for color in &tenant.palette {
+                    parse_color(color).map_err(|e| format!("Tenant '{}': {}", name, e))?;
+                }
+            }
+            if tenant.requests_per_minute == Some(0) {
+                return Err(format!("Tenant '{}': requests_per_minute must be at least 1", name).into());
+            }
+        }
+        Ok(config)
+    }
+
+    /// Reads a config file, with tenant silhouettes resolved against its directory
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        let mut config = Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let base = path.parent().unwrap_or(Path::new("."));
+        for tenant in config.tenants.values_mut() {
+            tenant.load_silhouette(base)?;
+        }
+        Ok(config)
+    }
+
+    /// Every palette to register by name: the configured themes and the tenants' palettes
+    pub fn palettes(&self) -> impl Iterator<Item = (&str, &Vec<String>)> {
+        let tenant_palettes = self
+            .tenants
+            .iter()
+            .filter(|(_, tenant)| !tenant.palette.is_empty())
+            .map(|(name, tenant)| (name.as_str(), &tenant.palette));
+        self.themes.iter().map(|(name, colors)| (name.as_str(), colors)).chain(tenant_palettes)
+    }
+
+    /// Fills the parameters a request left out from the preset it names
+    ///
+    /// Fails with the available preset names when the preset is unknown.
+    pub fn apply_preset(&self, params: &mut LogoParams) -> std::result::Result<(), String> {
+        let Some(name) = params.preset.as_deref() else {
+            return Ok(());
+        };
+        let preset = self.presets.get(name).ok_or_else(|| {
+            let names: Vec<&str> = self.presets.keys().map(String::as_str).collect();
+            format!("Unknown preset '{}' (expected one of: {})", name, names.join(", "))
+        })?;
+        params.theme = params.theme.take().or_else(|| preset.theme.clone());
+        params.shapes = params.shapes.or(preset.shapes);
+        params.grid_size = params.grid_size.or(preset.grid_size);
+        params.opacity = params.opacity.or(preset.opacity);
+        params.overlap = params.overlap.or(preset.overlap);
+        Ok(())
+    }
+}
+
+/// Replaces the themes registered by the previous config with those of the next one
+///
+/// On failure the previous themes are registered again.
+pub(super) fn swap_themes(previous: &WebConfig, next: &WebConfig) -> Result<()> {
+    for (name, _) in previous.palettes() {
+        color::unregister_theme(name);
+    }
+    for (index, (name, colors)) in next.palettes().enumerate() {
+        if let Err(e) = color::register_theme(name, colors.clone()) {
+            for (name, _) in next.palettes().take(index) {
+                color::unregister_theme(name);
+            }
+            for (name, colors) in previous.palettes() {
+                let _ = color::register_theme(name, colors.clone());
+            }
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// Reloads the state's config file, keeping the current config if the file is bad
+pub fn reload_and_report(state: &AppState, reason: &str) {
+    match state.reload() {
+        Ok(true) => println!("Reloaded config after {}", reason),
+        Ok(false) => {}
+        Err(e) => println!("Keeping the current config after {}: {}", reason, e),
+    }
+}
+
+/// Reloads the config file whenever it changes or the server gets SIGHUP
+///
+/// The file's directory is watched rather than the file, because Kubernetes
+/// updates a mounted ConfigMap by swapping a symlink next to it; other files in
+/// the directory are ignored. The returned
+/// watcher stops watching when dropped.
+pub fn spawn_reloaders(state: AppState) -> Result<RecommendedWatcher> {
+    let path = state.config_path().ok_or("The server has no config file to watch")?;
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => Path::new(".").to_path_buf(),
+    };
+    let file_name = path.file_name().ok_or("The config path has no file name")?.to_owned();
+
+    let (sender, mut changes) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        // Kubernetes swaps the `..data` symlink that the config file points through
+        let relevant = event.paths.iter().any(|changed| {
+            changed.file_name() == Some(file_name.as_os_str())
+                || changed.file_name().is_some_and(|name| name.to_string_lossy().starts_with(".."))
+        });
+        if relevant && !event.kind.is_access() {
+            let _ = sender.send(());
+        }
+    })?;
+    watcher.watch(&directory, RecursiveMode::NonRecursive)?;
+
+    let watched = state.clone();
+    tokio::spawn(async move {
+        while changes.recv().await.is_some() {
+            tokio::time::sleep(RELOAD_DEBOUNCE).await;
+            while changes.try_recv().is_ok() {}
+            reload_and_report(&watched, "a file change");
+        }
+    });
+
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut hangups = signal(SignalKind::hangup())?;
+        tokio::spawn(async move {
+            while hangups.recv().await.is_some() {
+                reload_and_report(&state, "SIGHUP");
+            }
+        });
+    }
+
+    Ok(watcher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let text = "api_tokens = [\"alpha\"]\n\n[themes]\nconfig-parse-corp = [\"#0B3D91\", \"white\"]\n\n[presets.dashboard]\ntheme = \"config-parse-corp\"\nshapes = 3\n\n[presets.calm]\ntheme = \"blues\"\nopacity = 0.6\n";
+        let config = WebConfig::parse(text).unwrap();
+        assert_eq!(config.api_tokens, vec!["alpha"]);
+        assert_eq!(config.presets["dashboard"].shapes, Some(3));
+        assert_eq!(config.limits.max_batch_seeds, DEFAULT_MAX_BATCH_SEEDS);
+        assert_eq!(WebConfig::parse("").unwrap(), WebConfig::default());
+
+        for bad in [
+            "colour = \"red\"\n",
+            "[themes]\nmesos = [\"#000000\"]\n",
+            "[themes]\nempty = []\n",
+            "[themes]\nbad = [\"nope\"]\n",
+            "[presets.lost]\ntheme = \"no-such-theme\"\n",
+            "[presets.odd]\nsize = 3\n",
+            "[limits]\nmax_batch_seeds = 0\n",
+            "[limits]\nmax_job_seeds = 0\n",
+            "[tenants.\"a/b\"]\ntheme = \"blues\"\n",
+            "[tenants.acme]\ntheme = \"blues\"\npalette = [\"#000000\"]\n",
+            "[tenants.acme]\ntheme = \"no-such-theme\"\n",
+            "[tenants.blues]\npalette = [\"#000000\"]\n",
+            "[tenants.acme]\npalette = [\"nope\"]\n",
+            "[tenants.acme]\nrequests_per_minute = 0\n",
+        ] {
+            assert!(WebConfig::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_load_tenants() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("mark.svg"),
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"><rect width="5" height="10"/></svg>"#,
+        )
+        .unwrap();
+        let path = dir.path().join("hexweb.toml");
+        std::fs::write(
+            &path,
+            "[tenants.config-acme]\npalette = [\"#E4002B\"]\nsilhouette = \"mark.svg\"\n\n[tenants.config-globex]\ntheme = \"greens\"\n",
+        )
+        .unwrap();
+
+        let config = WebConfig::load(&path).unwrap();
+        assert!(config.tenants["config-acme"].guide.is_some());
+        assert!(config.tenants["config-globex"].guide.is_none());
+        let palettes: Vec<&str> = config.palettes().map(|(name, _)| name).collect();
+        assert_eq!(palettes, vec!["config-acme"]);
+
+        std::fs::write(&path, "[tenants.config-acme]\nsilhouette = \"missing.svg\"\n").unwrap();
+        assert!(WebConfig::load(&path).is_err());
+    }
+
+    #[test]
+    fn test_apply_preset() {
+        let config = WebConfig::parse("[presets.dashboard]\ntheme = \"blues\"\nshapes = 3\ngrid_size = 2\n").unwrap();
+
+        let mut params = LogoParams {
+            preset: Some("dashboard".to_string()),
+            shapes: Some(5),
+            ..LogoParams::default()
+        };
+        config.apply_preset(&mut params).unwrap();
+        assert_eq!(params.theme.as_deref(), Some("blues"));
+        assert_eq!(params.shapes, Some(5));
+        assert_eq!(params.grid_size, Some(2));
+        assert_eq!(params.opacity, None);
+
+        let mut unknown = LogoParams {
+            preset: Some("wallboard".to_string()),
+            ..LogoParams::default()
+        };
+        let error = config.apply_preset(&mut unknown).unwrap_err();
+        assert!(error.contains("expected one of: dashboard"), "{}", error);
+    }
+
+    #[test]
+    fn test_swap_themes() {
+        let first = WebConfig::parse("[themes]\nconfig-swap-a = [\"#000000\"]\n").unwrap();
+        let second = WebConfig::parse("[themes]\nconfig-swap-b = [\"#FFFFFF\"]\n").unwrap();
+        swap_themes(&WebConfig::default(), &first).unwrap();
+        assert!("config-swap-a".parse::<Theme>().is_ok());
+
+        swap_themes(&first, &second).unwrap();
+        assert!("config-swap-a".parse::<Theme>().is_err());
+        assert!("config-swap-b".parse::<Theme>().is_ok());
+
+        // A theme that can't be registered leaves the previous ones in place
+        color::register_theme("config-swap-taken", vec!["#123456"]).unwrap();
+        let clash = WebConfig::parse("[themes]\nconfig-swap-c = [\"#000000\"]\nconfig-swap-taken = [\"#000000\"]\n").unwrap();
+        assert!(swap_themes(&second, &clash).is_err());
+        assert!("config-swap-b".parse::<Theme>().is_ok());
+        assert!("config-swap-c".parse::<Theme>().is_err());
+    }
+}
@@ -0,0 +1,380 @@
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Environment variable naming a directory where jobs and their results are kept across restarts
+pub const JOBS_DIR_ENV: &str = "HEXALITH_JOBS_DIR";
+
+/// Environment variable with the number of worker threads running jobs
+pub const JOB_WORKERS_ENV: &str = "HEXALITH_JOB_WORKERS";
+
+/// Workers without `HEXALITH_JOB_WORKERS`; jobs are meant to run beside regular requests
+pub const DEFAULT_JOB_WORKERS: usize = 2;
+
+/// Jobs that may wait for a worker before new ones are refused
+pub const MAX_QUEUED_JOBS: usize = 64;
+
+/// Finished jobs remembered in memory; the oldest are forgotten first
+const MAX_FINISHED_JOBS: usize = 256;
+
+/// A file produced by a job
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobOutput {
+    pub content_type: String,
+    /// Suggested name for the download
+    pub file_name: String,
+    pub data: Vec<u8>,
+}
+
+/// The work a job does, run on one of the queue's worker threads
+pub type JobTask = Box<dyn FnOnce() -> Result<JobOutput> + Send>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Queued,
+    Running,
+    Done,
+    Failed,
+}
+
+/// What a client sees of a job on `GET /api/jobs/:id`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobStatus {
+    pub id: String,
+    /// What the job renders, like `batch` or `wallpaper`
+    pub kind: String,
+    pub state: JobState,
+    /// Seconds since the Unix epoch when the job was submitted
+    pub submitted_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+}
+
+struct Job {
+    status: JobStatus,
+    /// The result of a finished job kept in memory; stored jobs keep it on disk
+    data: Option<Arc<Vec<u8>>>,
+}
+
+/// Runs expensive renders in the background on a small pool of worker threads
+///
+/// Jobs are kept in memory, and with a directory also as `<id>.json` status
+/// files and `<id>.result` data, so finished jobs survive restarts. Jobs that
+/// were still queued or running when the server stopped are marked failed.
+pub struct JobQueue {
+    jobs: Mutex<HashMap<String, Job>>,
+    /// Finished job ids, oldest first, for forgetting old results
+    finished: Mutex<VecDeque<String>>,
+    directory: Option<PathBuf>,
+    workers: usize,
+    /// Started with the first submitted job, so idle servers and tests spawn no threads
+    sender: OnceLock<SyncSender<(String, JobTask)>>,
+}
+
+impl JobQueue {
+    /// A queue keeping jobs in memory only, run by `workers` threads
+    pub fn new(workers: usize) -> Self {
+        Self {
+            jobs: Mutex::new(HashMap::new()),
+            finished: Mutex::new(VecDeque::new()),
+            directory: None,
+            workers: workers.max(1),
+            sender: OnceLock::new(),
+        }
+    }
+
+    /// A queue configured from `HEXALITH_JOB_WORKERS` and `HEXALITH_JOBS_DIR`
+    pub fn from_env() -> Result<Self> {
+        let workers = match std::env::var(JOB_WORKERS_ENV) {
+            Ok(value) if !value.trim().is_empty() => value
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|&workers| workers > 0)
+                .ok_or_else(|| format!("Invalid {} '{}' (expected a positive number)", JOB_WORKERS_ENV, value))?,
+            _ => DEFAULT_JOB_WORKERS,
+        };
+        let queue = Self::new(workers);
+        match std::env::var(JOBS_DIR_ENV) {
+            Ok(dir) if !dir.trim().is_empty() => queue.with_directory(Path::new(dir.trim())),
+            _ => Ok(queue),
+        }
+    }
+
+    /// Keeps jobs in `directory`, loading the ones a previous run left there
+    pub fn with_directory(mut self, directory: &Path) -> Result<Self> {
+        std::fs::create_dir_all(directory)
+            .map_err(|e| format!("Cannot create jobs directory {}: {}", directory.display(), e))?;
+        self.directory = Some(directory.to_path_buf());
+
+        let mut loaded = Vec::new();
+        for entry in std::fs::read_dir(directory)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Ok(mut status) = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| serde_json::from_str::<JobStatus>(&text).map_err(|e| e.to_string()))
+            else {
+                println!("Skipping unreadable job file {}", path.display());
+                continue;
+            };
+            if matches!(status.state, JobState::Queued | JobState::Running) {
+                status.state = JobState::Failed;
+                status.error = Some("Interrupted by a server restart".to_string());
+                self.save_status(&status)?;
+            }
+            loaded.push(status);
+        }
+
+        loaded.sort_by_key(|status| status.submitted_at);
+        let jobs = self.jobs.get_mut().unwrap_or_else(|e| e.into_inner());
+        let finished = self.finished.get_mut().unwrap_or_else(|e| e.into_inner());
+        for status in loaded {
+            finished.push_back(status.id.clone());
+            jobs.insert(status.id.clone(), Job { status, data: None });
+        }
+        Ok(self)
+    }
+
+    /// Queues `task` and returns the new job's status
+    ///
+    /// Fails without queueing when [`MAX_QUEUED_JOBS`] jobs are already waiting.
+    pub fn submit(self: &Arc<Self>, kind: &str, task: JobTask) -> Result<JobStatus> {
+        let status = JobStatus {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            kind: kind.to_string(),
+            state: JobState::Queued,
+            submitted_at: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs()),
+            error: None,
+            content_type: None,
+            file_name: None,
+        };
+        self.save_status(&status)?;
+        self.lock_jobs().insert(
+            status.id.clone(),
+            Job {
+                status: status.clone(),
+                data: None,
+            },
+        );
+
+        if self.sender().try_send((status.id.clone(), task)).is_err() {
+            self.lock_jobs().remove(&status.id);
+            self.remove_files(&status.id);
+            return Err(format!("The job queue is full ({} jobs waiting)", MAX_QUEUED_JOBS).into());
+        }
+        Ok(status)
+    }
+
+    /// The status of a job, if it's known
+    pub fn status(&self, id: &str) -> Option<JobStatus> {
+        self.lock_jobs().get(id).map(|job| job.status.clone())
+    }
+
+    /// The result of a finished job, or `None` until it's done
+    pub fn output(&self, id: &str) -> Result<Option<JobOutput>> {
+        let (status, data) = match self.lock_jobs().get(id) {
+            Some(job) if job.status.state == JobState::Done => (job.status.clone(), job.data.clone()),
+            _ => return Ok(None),
+        };
+        let data = match (data, &self.directory) {
+            (Some(data), _) => data.as_ref().clone(),
+            (None, Some(directory)) => std::fs::read(directory.join(format!("{}.result", id)))?,
+            (None, None) => return Ok(None),
+        };
+        Ok(Some(JobOutput {
+            content_type: status.content_type.unwrap_or_default(),
+            file_name: status.file_name.unwrap_or_default(),
+            data,
+        }))
+    }
+
+    fn sender(self: &Arc<Self>) -> &SyncSender<(String, JobTask)> {
+        self.sender.get_or_init(|| {
+            let (sender, receiver) = mpsc::sync_channel(MAX_QUEUED_JOBS);
+            let receiver = Arc::new(Mutex::new(receiver));
+            for index in 0..self.workers {
+                let queue = Arc::downgrade(self);
+                let receiver = Arc::clone(&receiver);
+                std::thread::Builder::new()
+                    .name(format!("hexweb-job-{}", index))
+                    .spawn(move || work(queue, receiver))
+                    .expect("failed to spawn a job worker thread");
+            }
+            sender
+        })
+    }
+
+    fn run(&self, id: &str, task: JobTask) {
+        self.update(id, |job| job.status.state = JobState::Running);
+        println!("Running job {}", id);
+
+        // A panicking render fails its job instead of taking the worker down
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(task))
+            .unwrap_or_else(|_| Err("The render panicked".into()))
+            .and_then(|output| {
+                if let Some(directory) = &self.directory {
+                    std::fs::write(directory.join(format!("{}.result", id)), &output.data)?;
+                }
+                Ok(output)
+            });
+
+        let keep_in_memory = self.directory.is_none();
+        self.update(id, |job| match result {
+            Ok(output) => {
+                job.status.state = JobState::Done;
+                job.status.content_type = Some(output.content_type);
+                job.status.file_name = Some(output.file_name);
+                job.data = keep_in_memory.then(|| Arc::new(output.data));
+            }
+            Err(e) => {
+                println!("Job {} failed: {}", id, e);
+                job.status.state = JobState::Failed;
+                job.status.error = Some(e.to_string());
+            }
+        });
+        self.forget_old_jobs(id);
+    }
+
+    fn update<F: FnOnce(&mut Job)>(&self, id: &str, change: F) {
+        let status = {
+            let mut jobs = self.lock_jobs();
+            let Some(job) = jobs.get_mut(id) else {
+                return;
+            };
+            change(job);
+            job.status.clone()
+        };
+        if let Err(e) = self.save_status(&status) {
+            println!("Cannot save job {}: {}", id, e);
+        }
+    }
+
+    fn forget_old_jobs(&self, finished_id: &str) {
+        let mut finished = self.finished.lock().unwrap_or_else(|e| e.into_inner());
+        finished.push_back(finished_id.to_string());
+        while finished.len() > MAX_FINISHED_JOBS {
+            if let Some(id) = finished.pop_front() {
+                self.lock_jobs().remove(&id);
+                self.remove_files(&id);
+            }
+        }
+    }
+
+    fn save_status(&self, status: &JobStatus) -> Result<()> {
+        if let Some(directory) = &self.directory {
+            std::fs::write(directory.join(format!("{}.json", status.id)), serde_json::to_vec(status)?)?;
+        }
+        Ok(())
+    }
+
+    fn remove_files(&self, id: &str) {
+        if let Some(directory) = &self.directory {
+            let _ = std::fs::remove_file(directory.join(format!("{}.json", id)));
+            let _ = std::fs::remove_file(directory.join(format!("{}.result", id)));
+        }
+    }
+
+    fn lock_jobs(&self) -> std::sync::MutexGuard<'_, HashMap<String, Job>> {
+        self.jobs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for JobQueue {
+    fn default() -> Self {
+        Self::new(DEFAULT_JOB_WORKERS)
+    }
+}
+
+/// A worker thread's loop; it ends once the queue is dropped
+fn work(queue: std::sync::Weak<JobQueue>, receiver: Arc<Mutex<Receiver<(String, JobTask)>>>) {
+    loop {
+        let next = receiver.lock().unwrap_or_else(|e| e.into_inner()).recv();
+        let Ok((id, task)) = next else {
+            return;
+        };
+        match queue.upgrade() {
+            Some(queue) => queue.run(&id, task),
+            None => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn wait_until_finished(queue: &JobQueue, id: &str) -> JobStatus {
+        for _ in 0..200 {
+            let status = queue.status(id).unwrap();
+            if matches!(status.state, JobState::Done | JobState::Failed) {
+                return status;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("job {} did not finish", id);
+    }
+
+    fn text_job(text: &'static str) -> JobTask {
+        Box::new(move || {
+            Ok(JobOutput {
+                content_type: "text/plain".to_string(),
+                file_name: "result.txt".to_string(),
+                data: text.as_bytes().to_vec(),
+            })
+        })
+    }
+
+    #[test]
+    fn test_jobs_run_in_background() {
+        let queue = Arc::new(JobQueue::new(1));
+        let done = queue.submit("test", text_job("rendered")).unwrap();
+        assert_eq!(done.state, JobState::Queued);
+        let failed = queue.submit("test", Box::new(|| Err("no such theme".into()))).unwrap();
+
+        assert_eq!(wait_until_finished(&queue, &done.id).state, JobState::Done);
+        let output = queue.output(&done.id).unwrap().unwrap();
+        assert_eq!(output.data, b"rendered");
+        assert_eq!(output.file_name, "result.txt");
+
+        let status = wait_until_finished(&queue, &failed.id);
+        assert_eq!(status.state, JobState::Failed);
+        assert_eq!(status.error.as_deref(), Some("no such theme"));
+        assert_eq!(queue.output(&failed.id).unwrap(), None);
+        assert_eq!(queue.status("missing"), None);
+    }
+
+    #[test]
+    fn test_jobs_persist_across_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = Arc::new(JobQueue::new(1).with_directory(dir.path()).unwrap());
+        let done = queue.submit("test", text_job("kept")).unwrap();
+        wait_until_finished(&queue, &done.id);
+
+        // A job that was queued when the server stopped
+        let interrupted = JobStatus {
+            id: "interrupted".to_string(),
+            state: JobState::Queued,
+            ..done.clone()
+        };
+        std::fs::write(dir.path().join("interrupted.json"), serde_json::to_vec(&interrupted).unwrap()).unwrap();
+
+        let restarted = JobQueue::new(1).with_directory(dir.path()).unwrap();
+        assert_eq!(restarted.output(&done.id).unwrap().unwrap().data, b"kept");
+        let status = restarted.status("interrupted").unwrap();
+        assert_eq!(status.state, JobState::Failed);
+        assert!(status.error.unwrap().contains("restart"));
+    }
+}
@@ -1,15 +1,62 @@
+pub mod admin;
+pub mod cache;
+pub mod cache_policy;
+pub mod config;
+pub mod jobs;
+pub mod params;
+pub mod render;
 pub mod routes;
+pub mod shutdown;
+pub mod state;
+pub mod stats;
+pub mod tenant;
 pub mod templates;
 pub mod templates_new;
+pub mod validation;
 
 use crate::Result;
 
+/// Runs the web server until SIGTERM or Ctrl-C, then drains in-flight requests
+///
+/// The drain timeout comes from `HEXALITH_DRAIN_TIMEOUT`, in seconds.
 pub async fn start_server(port: u16) -> Result<()> {
-    let app = routes::create_router();
+    let drain_timeout = shutdown::drain_timeout_from_env()?;
+    let state = state::AppState::from_env();
+    #[cfg(feature = "cloud")]
+    let state = match std::env::var(state::UPLOAD_ENV) {
+        Ok(url) if !url.is_empty() => {
+            let uploader = crate::upload::S3Uploader::from_env(url.parse()?)?;
+            println!("Uploading batch renders to {}", uploader.target());
+            state.with_uploader(uploader)
+        }
+        _ => state,
+    };
+    #[cfg(feature = "webhook")]
+    let state = match std::env::var(state::WEBHOOK_ENV) {
+        Ok(url) if !url.is_empty() => {
+            println!("Sending batch notifications to {}", url);
+            state.with_webhook(&url)
+        }
+        _ => state,
+    };
+    let state = state.with_jobs(jobs::JobQueue::from_env()?);
+    let state = match std::env::var(state::CONFIG_ENV) {
+        Ok(path) if !path.is_empty() => state.with_config_file(std::path::Path::new(&path))?,
+        _ => state,
+    };
+    // Dropping the watcher would stop reloads, so it lives as long as the server
+    let _watcher = match state.config_path() {
+        Some(path) => {
+            println!("Reloading config from {} on change or SIGHUP", path.display());
+            Some(config::spawn_reloaders(state.clone())?)
+        }
+        None => None,
+    };
+    println!("Instance name: {}", state.instance_name());
+    let app = routes::create_router_with_state(state);
 
     let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
     println!("Web server running at http://localhost:{}", port);
 
-    axum::serve(listener, app).await?;
-    Ok(())
+    shutdown::serve(listener, app, drain_timeout, shutdown::signal()).await
 }
\ No newline at end of file
@@ -0,0 +1,236 @@
+use crate::generator::params::{GRID_SIZE_RANGE, SHAPES_RANGE};
+use crate::generator::{AlgoVersion, GeneratorConfig, GuideMask, Theme};
+use serde::Deserialize;
+
+/// Smallest and largest raster size the PNG endpoint will render
+pub const MIN_PNG_SIZE: u32 = 256;
+pub const MAX_PNG_SIZE: u32 = 4096;
+
+/// Logo parameters as sent by the web interface, either as JSON or a query string
+#[derive(Debug, Default, Deserialize)]
+pub struct LogoParams {
+    pub theme: Option<String>,
+    pub shapes: Option<u8>,
+    pub grid_size: Option<u8>,
+    pub opacity: Option<f32>,
+    #[serde(default)]
+    pub overlap: Option<bool>, // From JS, it's a boolean
+    #[serde(default, deserialize_with = "deserialize_seed")]
+    pub seed: Option<u64>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub transparent: Option<bool>,
+    /// A preset from the server's config supplying the parameters left out
+    pub preset: Option<String>,
+    /// Generation algorithm version, the latest when left out
+    pub algo_version: Option<u8>,
+}
+
+// Custom deserializer for seed field
+fn deserialize_seed<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    // This type will catch both string values and null/absent values
+    let opt = Option::<String>::deserialize(deserializer)?;
+    
+    match opt {
+        Some(s) if s.is_empty() => Ok(None), // Empty string becomes None
+        Some(s) => {
+            // Try to parse as u64
+            match s.parse::<u64>() {
+                Ok(val) => Ok(Some(val)),
+                Err(_) => {
+                    println!("Failed to parse seed: {}", s);
+                    Ok(None) // If it fails to parse, return None
+                }
+            }
+        },
+        None => Ok(None) // Null/absent value becomes None
+    }
+}
+
+/// Logo parameters with defaults applied and every value normalized to what the
+/// generator actually uses, so equivalent requests share one cache key
+#[derive(Debug, Clone, PartialEq)]
+pub struct CanonicalParams {
+    pub seed: u64,
+    pub theme: Theme,
+    pub grid_size: u8,
+    pub shapes: u8,
+    pub opacity: f32,
+    pub overlap: bool,
+    /// Silhouette the shapes are drawn toward, such as a tenant's
+    pub guide: Option<GuideMask>,
+    pub algo_version: AlgoVersion,
+}
+
+impl LogoParams {
+    /// Resolves defaults and clamps values for a design with the given seed
+    pub fn canonicalize(&self, seed: u64) -> CanonicalParams {
+        // Opacity is quantized so 0.8 and 0.80000001 produce the same design and key
+        let opacity = match self.opacity {
+            Some(opacity) if opacity.is_finite() => (opacity.clamp(0.0, 1.0) * 100.0).round() / 100.0,
+            _ => 0.8,
+        };
+
+        CanonicalParams {
+            seed,
+            theme: Theme::from(self.theme.as_deref().unwrap_or("mesos")),
+            grid_size: self
+                .grid_size
+                .unwrap_or(4)
+                .clamp(*GRID_SIZE_RANGE.start(), *GRID_SIZE_RANGE.end()),
+            shapes: self
+                .shapes
+                .unwrap_or(4)
+                .clamp(*SHAPES_RANGE.start(), *SHAPES_RANGE.end()),
+            opacity,
+            // For the direct HTML version, overlap is now a boolean
+            overlap: self.overlap.unwrap_or(true),
+            guide: None,
+            // Handlers reject unknown versions before getting here
+            algo_version: self.algo_version().unwrap_or(AlgoVersion::LATEST),
+        }
+    }
+
+    /// The requested algorithm version, or an error naming the versions this build has
+    pub fn algo_version(&self) -> Result<AlgoVersion, String> {
+        match self.algo_version {
+            Some(number) => number.to_string().parse(),
+            None => Ok(AlgoVersion::LATEST),
+        }
+    }
+
+    /// PNG dimensions, limited to keep render time and memory bounded
+    pub fn png_size(&self) -> (u32, u32) {
+        (
+            self.width.unwrap_or(512).clamp(MIN_PNG_SIZE, MAX_PNG_SIZE),
+            self.height.unwrap_or(512).clamp(MIN_PNG_SIZE, MAX_PNG_SIZE),
+        )
+    }
+
+    /// Whether PNG output should have a transparent background
+    pub fn transparent(&self) -> bool {
+        self.transparent.unwrap_or(true)
+    }
+}
+
+impl CanonicalParams {
+    /// Query string listing every design parameter in a fixed order
+    pub fn query_string(&self) -> String {
+        format!(
+            "theme={}&grid_size={}&shapes={}&opacity={:.2}&overlap={}{}",
+            self.theme,
+            self.grid_size,
+            self.shapes,
+            self.opacity,
+            self.overlap,
+            self.version_key()
+        )
+    }
+
+    /// Stable key identifying the design, including the seed
+    pub fn cache_key(&self) -> String {
+        format!("seed={}&{}{}", self.seed, self.query_string(), self.guide_key())
+    }
+
+    /// Key of the parameters that decide where shapes grow, leaving out theme and opacity
+    pub fn shape_key(&self) -> String {
+        format!(
+            "seed={}&grid_size={}&shapes={}&overlap={}{}{}",
+            self.seed,
+            self.grid_size,
+            self.shapes,
+            self.overlap,
+            self.version_key(),
+            self.guide_key()
+        )
+    }
+
+    /// The algorithm version's part of the keys; empty for version 1 so its keys stay the same
+    fn version_key(&self) -> String {
+        if self.algo_version == AlgoVersion::V1 {
+            return String::new();
+        }
+        format!("&algo_version={}", self.algo_version)
+    }
+
+    /// The guide's part of the keys; empty without one so existing keys stay the same
+    fn guide_key(&self) -> String {
+        match &self.guide {
+            Some(guide) => format!("&guide={:016x}", guide.checksum()),
+            None => String::new(),
+        }
+    }
+
+    /// Generator configuration producing this design
+    pub fn config(&self) -> GeneratorConfig {
+        GeneratorConfig::new(self.grid_size, self.shapes, self.opacity, Some(self.seed))
+            .with_theme(self.theme.clone())
+            .with_allow_overlap(self.overlap)
+            .with_guide(self.guide.clone())
+            .with_algo_version(self.algo_version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses a query string the same way the `Query` extractor does
+    fn parse(query: &str) -> LogoParams {
+        let uri: axum::http::Uri = format!("/svg/1?{}", query).parse().unwrap();
+        axum::extract::Query::try_from_uri(&uri).unwrap().0
+    }
+
+    #[test]
+    fn test_equivalent_params_share_key() {
+        let defaults = parse("").canonicalize(42);
+        let explicit =
+            parse("overlap=true&opacity=0.8&shapes=4&grid_size=4&theme=MESOS").canonicalize(42);
+        let clamped = parse("grid_size=4&shapes=4&opacity=0.8000001").canonicalize(42);
+
+        assert_eq!(defaults.cache_key(), explicit.cache_key());
+        assert_eq!(defaults.cache_key(), clamped.cache_key());
+        assert_eq!(
+            defaults.cache_key(),
+            "seed=42&theme=mesos&grid_size=4&shapes=4&opacity=0.80&overlap=true"
+        );
+    }
+
+    #[test]
+    fn test_every_param_changes_key() {
+        let base = parse("").canonicalize(42).cache_key();
+
+        for query in [
+            "theme=blues",
+            "grid_size=5",
+            "shapes=2",
+            "opacity=0.5",
+            "overlap=false",
+        ] {
+            assert_ne!(parse(query).canonicalize(42).cache_key(), base, "{}", query);
+        }
+        assert_ne!(parse("").canonicalize(43).cache_key(), base);
+    }
+
+    #[test]
+    fn test_out_of_range_values_are_clamped() {
+        let params = parse("grid_size=20&shapes=0&opacity=3&width=10&height=9000");
+        let canonical = params.canonicalize(1);
+
+        assert_eq!(canonical.grid_size, 8);
+        assert_eq!(canonical.shapes, 1);
+        assert_eq!(canonical.opacity, 1.0);
+        assert_eq!(params.png_size(), (MIN_PNG_SIZE, MAX_PNG_SIZE));
+    }
+
+    #[test]
+    fn test_algo_version() {
+        assert_eq!(parse("").algo_version(), Ok(AlgoVersion::LATEST));
+        assert_eq!(parse("algo_version=1").algo_version(), Ok(AlgoVersion::V1));
+        let error = parse("algo_version=9").algo_version().unwrap_err();
+        assert!(error.contains("Unknown algorithm version '9'"), "{}", error);
+    }
+}
@@ -0,0 +1,48 @@
+use super::state::AppState;
+use crate::Result;
+use tokio::sync::Semaphore;
+
+/// Runs a CPU-heavy generation or render job off the async runtime
+///
+/// The job waits for one of the state's render permits and then runs on
+/// tokio's blocking thread pool, so request handling stays responsive under load.
+pub async fn render_blocking<T, F>(state: &AppState, job: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    run_blocking(state.render_permits(), job).await
+}
+
+/// Runs a job on tokio's blocking thread pool once one of `permits` is free
+pub async fn run_blocking<T, F>(permits: &Semaphore, job: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let _permit = permits.acquire().await?;
+
+    // Boxed errors aren't Send, so carry failures across the thread as strings
+    let result = tokio::task::spawn_blocking(move || job().map_err(|e| e.to_string())).await?;
+    result.map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_render_blocking() {
+        let state = AppState::new(1);
+
+        let value = render_blocking(&state, || Ok(21 * 2)).await.unwrap();
+        assert_eq!(value, 42);
+
+        // Errors from the job are passed back to the caller
+        let result: Result<()> = render_blocking(&state, || Err("render failed".into())).await;
+        assert_eq!(result.unwrap_err().to_string(), "render failed");
+
+        // The permit is released once the job finishes
+        assert_eq!(state.render_permits().available_permits(), 1);
+    }
+}
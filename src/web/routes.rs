@@ -1,25 +1,43 @@
-use crate::generator::Generator;
+use crate::code::DesignCode;
+use crate::generator::grid::Point;
+use crate::generator::{GeneratedDesign, GuideMask};
+use crate::png;
+use crate::utils;
 use crate::svg;
+use super::cache;
+use super::cache_policy::{self, CachePolicy};
+use super::config::WebConfig;
+use super::jobs::{JobOutput, JobState, JobTask};
+use super::params::{CanonicalParams, LogoParams};
+use super::render::render_blocking;
+use super::state::{AppState, FAVICON_SIZE};
+use super::stats::RenderEvent;
+use super::admin;
+use super::validation::{self, ShapeTarget, ValidationErrors};
 use axum::{
-    extract::{Path, Query},
-    response::IntoResponse,
+    extract::{Path, Query, Request, State},
+    http::{header, Extensions, HeaderMap, StatusCode, Version},
+    middleware::{self, Next},
+    response::{Html, IntoResponse, Response},
     routing::{get, post},
-    Router,
+    Json, Router,
 };
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use tower_http::compression::predicate::{Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
 use tower_http::{cors::CorsLayer, services::ServeDir};
 
 // Main web interface handler
 
 async fn direct_handler() -> impl IntoResponse {
-    let html = r#"<!DOCTYPE html>
+    let html = r##"<!DOCTYPE html>
 <html lang="en">
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
     <title>Hexalith Logo Generator</title>
-    <link rel="icon" href="/assets/favicon.svg" type="image/svg+xml">
+    <link rel="icon" href="/favicon.svg" type="image/svg+xml">
     <style>
         body {
             font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, Helvetica, Arial, sans-serif;
@@ -62,6 +80,31 @@ async fn direct_handler() -> impl IntoResponse {
             justify-content: center;
             background-color: #f9f9f9;
         }
+        #logo-preview {
+            cursor: crosshair;
+        }
+        .recolor-group {
+            display: flex;
+            align-items: center;
+            gap: 10px;
+        }
+        .shape-locks {
+            display: flex;
+            flex-wrap: wrap;
+            gap: 10px;
+            margin-top: 10px;
+        }
+        .shape-lock {
+            display: flex;
+            align-items: center;
+            gap: 5px;
+        }
+        .swatch {
+            width: 16px;
+            height: 16px;
+            border: 1px solid #999;
+            border-radius: 3px;
+        }
         form {
             display: flex;
             flex-direction: column;
@@ -163,9 +206,9 @@ async fn direct_handler() -> impl IntoResponse {
                 </div>
                 
                 <div class="form-group">
-                    <label for="shapes">Number of Shapes (1-10)</label>
+                    <label for="shapes">Number of Shapes (1-25)</label>
                     <div class="range-group">
-                        <input type="range" id="shapes" name="shapes" min="1" max="10" value="4" step="1">
+                        <input type="range" id="shapes" name="shapes" min="1" max="25" value="4" step="1">
                         <span id="shapes-value" class="range-value">4</span>
                     </div>
                 </div>
@@ -188,9 +231,34 @@ async fn direct_handler() -> impl IntoResponse {
                     <input type="text" id="seed" name="seed" value="" placeholder="Enter number to reuse a design">
                 </div>
                 
+                <div class="form-group">
+                    <label for="png-width">PNG Size (width × height)</label>
+                    <div class="range-group">
+                        <select id="png-width" name="width">
+                            <option value="256">256</option>
+                            <option value="512" selected>512</option>
+                            <option value="1024">1024</option>
+                            <option value="2048">2048</option>
+                            <option value="4096">4096</option>
+                        </select>
+                        <span>×</span>
+                        <select id="png-height" name="height">
+                            <option value="256">256</option>
+                            <option value="512" selected>512</option>
+                            <option value="1024">1024</option>
+                            <option value="2048">2048</option>
+                            <option value="4096">4096</option>
+                        </select>
+                    </div>
+                </div>
+                <div class="form-group checkbox-group">
+                    <input type="checkbox" id="transparent" name="transparent" checked>
+                    <label for="transparent">Transparent PNG background</label>
+                </div>
                 <div class="button-group">
                     <button type="button" id="generate-btn">Generate Logo</button>
                     <button type="button" id="download-btn" class="button-secondary">Download SVG</button>
+                    <button type="button" id="download-png-btn" class="button-secondary">Download PNG</button>
                 </div>
             </form>
         </div>
@@ -199,6 +267,13 @@ async fn direct_handler() -> impl IntoResponse {
             <div class="logo-container">
                 <img id="logo-preview" src="" alt="Generated logo will appear here" style="max-width: 100%; max-height: 100%;">
             </div>
+            <div class="recolor-group">
+                <label for="recolor-color">Shape color:</label>
+                <input type="color" id="recolor-color" value="#2b3990">
+                <span>Click a shape in the preview to paint it</span>
+            </div>
+            <div id="shape-locks" class="shape-locks"></div>
+            <button type="button" id="reroll-btn" class="button-secondary">Reroll Unlocked Shapes</button>
             <div id="logo-info">
                 <p>Generate a logo to see information about it here.</p>
             </div>
@@ -216,6 +291,11 @@ async fn direct_handler() -> impl IntoResponse {
             document.getElementById(valueId).textContent = value.toFixed(decimals);
         }
         
+        // Token of the previewed design, used to edit it on the server
+        let currentToken = null;
+        // Whether the preview has been recolored or rerolled since it was generated
+        let edited = false;
+        
         // Initialize the page
         document.addEventListener('DOMContentLoaded', function() {
             console.log('Direct HTML page loaded');
@@ -236,6 +316,9 @@ async fn direct_handler() -> impl IntoResponse {
             // Set up button event listeners
             document.getElementById('generate-btn').addEventListener('click', generateLogo);
             document.getElementById('download-btn').addEventListener('click', downloadSvg);
+            document.getElementById('download-png-btn').addEventListener('click', downloadPng);
+            document.getElementById('logo-preview').addEventListener('click', recolorShape);
+            document.getElementById('reroll-btn').addEventListener('click', rerollUnlocked);
             
             // Generate a random logo on page load
             setTimeout(generateLogo, 300);
@@ -250,15 +333,18 @@ async fn direct_handler() -> impl IntoResponse {
             // Convert FormData to a proper object
             const params = {};
             for (const [key, value] of formData.entries()) {
-                if (key === 'grid_size' || key === 'shapes') {
+                if (key === 'grid_size' || key === 'shapes' || key === 'width' || key === 'height') {
                     params[key] = parseInt(value, 10);
                 } else if (key === 'opacity') {
                     params[key] = parseFloat(value);
-                } else if (key !== 'overlap' && key !== 'seed') { // Skip overlap and seed for now
+                } else if (key !== 'overlap' && key !== 'seed' && key !== 'transparent') { // Checkboxes and seed are handled below
                     params[key] = value;
                 }
             }
             
+            // Unchecked checkboxes are missing from FormData, so always send the transparency flag
+            params.transparent = document.getElementById('transparent').checked;
+            
             // Handle overlap separately - this approach works
             if (document.getElementById('overlap').checked) {
                 params.overlap = true;
@@ -298,6 +384,9 @@ async fn direct_handler() -> impl IntoResponse {
                 const data = await response.json();
                 const seed = data.seed;
                 console.log('Received seed:', seed);
+                currentToken = data.token;
+                edited = false;
+                showShapes(data.shapes, false);
                 
                 // Only update the seed field if the user provided a seed
                 // Otherwise, keep it clear to ensure a new random seed next time
@@ -345,6 +434,111 @@ async fn direct_handler() -> impl IntoResponse {
         }
         
         
+        // Paint the clicked shape with the selected color
+        async function recolorShape(event) {
+            const previewImg = event.currentTarget;
+            if (!currentToken || !previewImg.src) {
+                return;
+            }
+            
+            try {
+                // Map the click into design coordinates using the SVG's viewBox
+                const svgText = await (await fetch(previewImg.src)).text();
+                const svgRoot = new DOMParser().parseFromString(svgText, 'image/svg+xml').documentElement;
+                const viewBox = svgRoot.getAttribute('viewBox').split(/[\s,]+/).map(parseFloat);
+                const rect = previewImg.getBoundingClientRect();
+                const x = viewBox[0] + (event.clientX - rect.left) / rect.width * viewBox[2];
+                const y = viewBox[1] + (event.clientY - rect.top) / rect.height * viewBox[3];
+                
+                const response = await fetch(`/api/design/${currentToken}/recolor`, {
+                    method: 'POST',
+                    headers: {
+                        'Content-Type': 'application/json'
+                    },
+                    body: JSON.stringify({
+                        point: [x, y],
+                        color: document.getElementById('recolor-color').value
+                    })
+                });
+                
+                // Clicking the background between shapes leaves the design unchanged
+                if (response.status === 422) {
+                    return;
+                }
+                if (!response.ok) {
+                    throw new Error(await response.text());
+                }
+                
+                showEdited(await response.json());
+            } catch (error) {
+                console.error('Error recoloring shape:', error);
+                alert('Failed to recolor shape: ' + error.message);
+            }
+        }
+        
+        // List the shapes with a lock checkbox each, optionally keeping the current locks
+        function showShapes(shapes, keepLocks) {
+            const container = document.getElementById('shape-locks');
+            const locked = keepLocks ? lockedShapes() : [];
+            container.innerHTML = '';
+            shapes.forEach(shape => {
+                const label = document.createElement('label');
+                label.className = 'shape-lock';
+                const checkbox = document.createElement('input');
+                checkbox.type = 'checkbox';
+                checkbox.value = shape.id;
+                checkbox.checked = locked.includes(shape.id);
+                const swatch = document.createElement('span');
+                swatch.className = 'swatch';
+                swatch.style.backgroundColor = shape.color;
+                label.append(checkbox, swatch, `Lock #${shape.id}`);
+                container.appendChild(label);
+            });
+        }
+        
+        // Ids of the shapes whose lock checkbox is ticked
+        function lockedShapes() {
+            return Array.from(document.querySelectorAll('#shape-locks input:checked'))
+                .map(checkbox => parseInt(checkbox.value, 10));
+        }
+        
+        // Show a design returned by an edit endpoint in the preview
+        function showEdited(data) {
+            currentToken = data.token;
+            edited = true;
+            showShapes(data.shapes, true);
+            document.getElementById('logo-preview').src =
+                URL.createObjectURL(new Blob([data.svg], { type: 'image/svg+xml' }));
+        }
+        
+        // Regrow every shape that isn't locked
+        async function rerollUnlocked() {
+            if (!currentToken) {
+                alert('No logo has been generated yet.');
+                return;
+            }
+            
+            try {
+                const response = await fetch(`/api/design/${currentToken}/reroll`, {
+                    method: 'POST',
+                    headers: {
+                        'Content-Type': 'application/json'
+                    },
+                    body: JSON.stringify({
+                        locked: lockedShapes(),
+                        seed: Math.floor(Math.random() * 4294967296)
+                    })
+                });
+                if (!response.ok) {
+                    throw new Error(await response.text());
+                }
+                showEdited(await response.json());
+            } catch (error) {
+                console.error('Error rerolling shapes:', error);
+                alert('Failed to reroll shapes: ' + error.message);
+            }
+        }
+        
         // Download the current SVG
         function downloadSvg() {
             console.log('Download button clicked');
@@ -382,9 +576,57 @@ async fn direct_handler() -> impl IntoResponse {
                     alert('Failed to download SVG: ' + error.message);
                 });
         }
+        
+        // Download the current design as a PNG at the selected resolution
+        function downloadPng() {
+            console.log('Download PNG button clicked');
+            const previewImg = document.getElementById('logo-preview');
+            const svgUrl = previewImg.src;
+            
+            if (!svgUrl) {
+                alert('No logo has been generated yet.');
+                return;
+            }
+            
+            // The PNG endpoint accepts the same parameters as the SVG endpoint;
+            // an edited design is rasterized from its token instead
+            const url = new URL(edited ? `/api/design/${currentToken}/png` : svgUrl, window.location.href);
+            url.pathname = url.pathname.replace('/svg/', '/png/');
+            url.searchParams.set('width', document.getElementById('png-width').value);
+            url.searchParams.set('height', document.getElementById('png-height').value);
+            url.searchParams.set('transparent', document.getElementById('transparent').checked);
+            
+            fetch(url)
+                .then(response => {
+                    if (!response.ok) {
+                        throw new Error('Failed to render PNG');
+                    }
+                    return response.blob();
+                })
+                .then(blob => {
+                    const blobUrl = URL.createObjectURL(blob);
+                    
+                    // Create a temporary link and trigger download
+                    const a = document.createElement('a');
+                    a.href = blobUrl;
+                    a.download = `hexalith_logo_${edited ? currentToken : url.pathname.split('/').pop()}.png`;
+                    document.body.appendChild(a);
+                    a.click();
+                    
+                    // Clean up
+                    setTimeout(() => {
+                        document.body.removeChild(a);
+                        URL.revokeObjectURL(blobUrl);
+                    }, 100);
+                })
+                .catch(error => {
+                    console.error('Error downloading PNG:', error);
+                    alert('Failed to download PNG: ' + error.message);
+                });
+        }
     </script>
 </body>
-</html>"#;
+</html>"##;
 
     (
         axum::http::StatusCode::OK,
@@ -395,147 +637,1061 @@ async fn direct_handler() -> impl IntoResponse {
 
 
 pub fn create_router() -> Router {
+    create_router_with_state(AppState::default())
+}
+
+/// Creates the router using the given shared state
+pub fn create_router_with_state(state: AppState) -> Router {
     // Get the current directory where the binary is running
     let assets_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/web/assets");
     
+    // Endpoints that do more than read a single image need a token when one is configured
+    let protected = Router::new()
+        .route("/api/batch", post(batch_handler))
+        .route("/api/reload", post(reload_handler))
+        .route("/admin", get(admin_handler))
+        .route("/api/jobs", post(submit_job_handler))
+        .route("/api/jobs/:id", get(job_status_handler))
+        .route("/api/jobs/:id/result", get(job_result_handler))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_api_token));
+
     // Create the router with our routes
     Router::new()
+        .merge(protected)
         .route("/", get(direct_handler)) // Main route with the working interface
         .route("/generate", post(generate_logo_handler))
+        .route("/api/design/:token/recolor", post(recolor_handler))
+        .route("/api/design/:token/reroll", post(reroll_handler))
+        .route("/api/design/:token/png", get(design_png_handler))
         .route("/svg/:seed", get(get_svg_handler))
+        .route("/svg/from/:text", get(svg_from_text_handler))
+        .route("/d/:code", get(design_code_handler))
+        .route("/png/:seed", get(get_png_handler))
+        .route("/t/:tenant/svg/:seed", get(tenant_svg_handler))
+        .route("/t/:tenant/svg/from/:text", get(tenant_svg_from_text_handler))
+        .route("/t/:tenant/png/:seed", get(tenant_png_handler))
         .route("/favicon.ico", get(favicon_handler))
+        .route("/favicon.svg", get(favicon_svg_handler))
+        .route("/icon/:file", get(icon_handler))
         .nest_service("/assets", ServeDir::new(assets_path))
         .layer(CorsLayer::permissive())
+        .layer(compression_layer())
+        .layer(middleware::map_response(cache_policy::weaken_compressed_etag))
+        .layer(middleware::from_fn(cache_policy::apply_default))
+        .with_state(state)
+}
+
+/// Content types that shrink when compressed; PNGs and other images already are
+const COMPRESSIBLE_TYPES: &[&str] = &["image/svg+xml", "application/json", "text/"];
+
+/// Responses smaller than this aren't worth compressing
+const MIN_COMPRESSED_SIZE: u16 = 256;
+
+/// Gzip or brotli for text responses, chosen from the client's `Accept-Encoding`
+///
+/// Compressible responses get `Vary: Accept-Encoding` even when sent
+/// uncompressed, so shared caches keep the encodings apart.
+fn compression_layer() -> CompressionLayer<impl Predicate> {
+    fn compressible(_: StatusCode, _: Version, headers: &HeaderMap, _: &Extensions) -> bool {
+        let content_type = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        COMPRESSIBLE_TYPES.iter().any(|allowed| content_type.starts_with(allowed))
+    }
+
+    CompressionLayer::new().compress_when(SizeAbove::new(MIN_COMPRESSED_SIZE).and(compressible))
 }
 
 
+/// Rejects requests without a valid `Authorization: Bearer <token>` header
+///
+/// Deployments without configured tokens leave the protected endpoints open.
+async fn require_api_token(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    if !state.requires_api_token() {
+        return next.run(request).await;
+    }
+
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match token {
+        Some(token) if state.is_valid_api_token(token.trim()) => next.run(request).await,
+        _ => (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            "A valid API token is required",
+        )
+            .into_response(),
+    }
+}
+
 async fn favicon_handler() -> impl IntoResponse {
     // Redirect to the SVG favicon
     (
         axum::http::StatusCode::TEMPORARY_REDIRECT,
-        [("Location", "/assets/favicon.svg")],
+        [("Location", "/favicon.svg")],
     )
 }
 
+/// Longest name accepted where a name picks the design, as in `/favicon.svg?name=`
+const MAX_NAME_BYTES: usize = 256;
+
 #[derive(Debug, Deserialize)]
-struct LogoParams {
-    theme: Option<String>,
-    shapes: Option<u8>,
-    grid_size: Option<u8>,
-    opacity: Option<f32>,
-    #[serde(default)]
-    overlap: Option<bool>, // From JS, it's a boolean
-    #[serde(default, deserialize_with = "deserialize_seed")]
-    seed: Option<u64>,
-}
-
-// Custom deserializer for seed field
-fn deserialize_seed<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
-where
-    D: serde::Deserializer<'de>,
-{
-    // This type will catch both string values and null/absent values
-    let opt = Option::<String>::deserialize(deserializer)?;
-    
-    match opt {
-        Some(s) if s.is_empty() => Ok(None), // Empty string becomes None
-        Some(s) => {
-            // Try to parse as u64
-            match s.parse::<u64>() {
-                Ok(val) => Ok(Some(val)),
-                Err(_) => {
-                    println!("Failed to parse seed: {}", s);
-                    Ok(None) // If it fails to parse, return None
-                }
-            }
-        },
-        None => Ok(None) // Null/absent value becomes None
+struct FaviconParams {
+    name: Option<String>,
+}
+
+/// Serves this instance's generated favicon, or the identicon for `?name=`
+async fn favicon_svg_handler(
+    State(state): State<AppState>,
+    Query(params): Query<FaviconParams>,
+    headers: HeaderMap,
+) -> Response {
+    let name = params.name.filter(|name| !name.trim().is_empty());
+    let Some(name) = name else {
+        // Another instance, or this one after a restart, may draw a different icon
+        return (
+            [
+                (header::CONTENT_TYPE, "image/svg+xml"),
+                (header::CACHE_CONTROL, CachePolicy::NoStore.cache_control()),
+            ],
+            state.favicon().to_string(),
+        )
+            .into_response();
+    };
+    if name.len() > MAX_NAME_BYTES {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("name must be at most {} bytes", MAX_NAME_BYTES),
+        )
+            .into_response();
+    }
+
+    let etag = CachePolicy::Immutable.etag(&format!("favicon?name={}", name));
+    if cache::is_not_modified(&headers, &etag) {
+        return not_modified(CachePolicy::Immutable, etag, Vec::new());
+    }
+
+    match render_blocking(&state, move || svg::identicon(&name, FAVICON_SIZE)).await {
+        Ok(icon) => (
+            [
+                (header::CONTENT_TYPE, "image/svg+xml".to_string()),
+                (header::CACHE_CONTROL, CachePolicy::Immutable.cache_control().to_string()),
+                (header::ETAG, etag),
+            ],
+            icon,
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Error generating favicon: {}", e),
+        )
+            .into_response(),
+    }
+}
+
+/// Size of `/icon/:name.svg` without `?size=`, suited to portal and dashboard lists
+const DEFAULT_ICON_SIZE: u32 = 64;
+
+/// Smallest and largest `/icon/:name.svg` size
+const MIN_ICON_SIZE: u32 = 16;
+const MAX_ICON_SIZE: u32 = 1024;
+
+#[derive(Debug, Deserialize)]
+struct IconParams {
+    size: Option<u32>,
+}
+
+/// Serves `/icon/:name.svg`, a small deterministic icon for any service or dashboard name
+///
+/// Meant for developer portals and dashboards that template an icon URL from
+/// an entity name; see [`svg::service_icon`] for how the icon is chosen.
+async fn icon_handler(
+    State(state): State<AppState>,
+    Path(file): Path<String>,
+    Query(params): Query<IconParams>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(name) = file.strip_suffix(".svg").filter(|name| !name.trim().is_empty()) else {
+        return (StatusCode::NOT_FOUND, "Icons are served as /icon/<name>.svg").into_response();
+    };
+    if name.len() > MAX_NAME_BYTES {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("name must be at most {} bytes", MAX_NAME_BYTES),
+        )
+            .into_response();
+    }
+    let size = params.size.unwrap_or(DEFAULT_ICON_SIZE).clamp(MIN_ICON_SIZE, MAX_ICON_SIZE);
+
+    let etag = CachePolicy::Immutable.etag(&format!("icon?name={}&size={}", name.trim(), size));
+    if cache::is_not_modified(&headers, &etag) {
+        return not_modified(CachePolicy::Immutable, etag, Vec::new());
+    }
+
+    let name = name.to_string();
+    match render_blocking(&state, move || svg::service_icon(&name, size)).await {
+        Ok(icon) => (
+            [
+                (header::CONTENT_TYPE, "image/svg+xml".to_string()),
+                (header::CACHE_CONTROL, CachePolicy::Immutable.cache_control().to_string()),
+                (header::ETAG, etag),
+            ],
+            icon,
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Error generating icon: {}", e),
+        )
+            .into_response(),
     }
 }
 
 #[derive(Debug, Serialize)]
 struct LogoResponse {
     seed: u64,
+    /// Identifies the generated design in `/api/design/:token/...` requests
+    token: String,
+    shapes: Vec<ShapeSummary>,
+}
+
+/// A shape as listed in JSON responses, in shape order
+#[derive(Debug, Serialize)]
+struct ShapeSummary {
+    /// Stable id, used to name the shape in later requests
+    id: usize,
+    color: String,
+    cells: usize,
+    /// Geometry from the shape's metrics, in grid units
+    area: f64,
+    perimeter: f64,
+    convexity: f64,
+    aspect_ratio: f64,
+}
+
+fn shape_summaries(design: &GeneratedDesign) -> Vec<ShapeSummary> {
+    design
+        .shapes()
+        .iter()
+        .zip(design.shape_metrics())
+        .map(|(shape, metrics)| ShapeSummary {
+            id: shape.id,
+            color: shape.fill().to_string(),
+            cells: shape.cell_count(),
+            area: metrics.area,
+            perimeter: metrics.perimeter,
+            convexity: metrics.convexity,
+            aspect_ratio: metrics.aspect_ratio,
+        })
+        .collect()
+}
+
+/// A seed for requests that don't pick their own
+fn random_seed() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    time ^ 0x12345678 // XOR with a constant for additional randomness
 }
 
 
 // Regular handler with json
 async fn generate_logo_handler(
+    State(state): State<AppState>,
     body: axum::body::Bytes
 ) -> impl IntoResponse {
     println!("Raw request body: {}", String::from_utf8_lossy(&body));
     
-    // Try to parse the request body directly
-    let params: LogoParams = match serde_json::from_slice(&body) {
+    // Invalid fields are reported back instead of being silently replaced
+    let params = match validation::parse_object(&body)
+        .and_then(|object| validation::logo_params(&object))
+        .and_then(|params| with_preset(&state.config(), params))
+    {
         Ok(p) => p,
-        Err(e) => {
-            println!("Failed to parse JSON: {}", e);
-            return (
-                axum::http::StatusCode::UNPROCESSABLE_ENTITY,
-                format!("Failed to parse JSON: {}", e)
-            ).into_response();
+        Err(errors) => {
+            println!("Rejected request: {:?}", errors);
+            return errors.into_response();
         }
     };
     
     println!("Parsed params: {:?}", params);
     
     // Use the provided seed or generate a random one
-    let seed = params.seed.unwrap_or_else(|| {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        time ^ 0x12345678 // XOR with a constant for additional randomness
-    });
+    let seed = params.seed.unwrap_or_else(random_seed);
     
     println!("Generated seed: {}", seed);
 
+    // The design is kept so the preview can be edited before it is downloaded
+    let canonical = params.canonicalize(seed);
+    let design = canonical.cache_key();
+    let rendered = timed_render(&state, "/generate", seed, design, move || generate_from_params(&canonical)).await;
+    let (token, shapes) = match rendered {
+        Ok(design) => (cache::remember(&design), shape_summaries(&design)),
+        Err(e) => {
+            println!("Error generating logo: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Error generating logo: {}", e),
+            )
+                .into_response();
+        }
+    };
+
     (
         axum::http::StatusCode::OK,
-        axum::Json(LogoResponse { seed })
+        axum::Json(LogoResponse { seed, token, shapes })
     ).into_response()
 }
 
-async fn get_svg_handler(Path(seed): Path<u64>, Query(params): Query<LogoParams>) -> impl IntoResponse {
-    // Set up the generator with the parameters from the query string
-    let grid_size = params.grid_size.unwrap_or(4);
-    let shapes = params.shapes.unwrap_or(4);
-    let opacity = params.opacity.unwrap_or(0.8);
-    let theme = params.theme.unwrap_or_else(|| "mesos".to_string());
-    // For the direct HTML version, overlap is now a boolean
-    let overlap = params.overlap.unwrap_or(true);
-    
-    // Debug output to server console
-    println!("Generating logo with: seed={}, grid_size={}, shapes={}, opacity={}, theme={}, overlap={}", 
-        seed, grid_size, shapes, opacity, theme, overlap);
-
-    // Create the generator
-    let mut generator = Generator::new(grid_size, shapes, opacity, Some(seed));
-    generator
-        .set_color_scheme(&theme)
-        .set_allow_overlap(overlap);
-
-    // Generate the logo
-    if let Err(e) = generator.generate() {
-        println!("Error generating logo: {}", e);
+#[derive(Debug, Serialize)]
+struct RecolorResponse {
+    /// Token of the recolored design
+    token: String,
+    shape_index: usize,
+    shapes: Vec<ShapeSummary>,
+    svg: String,
+}
+
+/// Response for a design token that was never issued or has been evicted
+fn unknown_token() -> Response {
+    (StatusCode::NOT_FOUND, "Unknown or expired design token").into_response()
+}
+
+/// Paints one shape of a previously generated design a new color
+///
+/// The shape is named by `shape_index` or picked by hit-testing `point`, so
+/// the web preview can recolor whatever shape was clicked.
+async fn recolor_handler(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    body: axum::body::Bytes,
+) -> Response {
+    let request = match validation::parse_object(&body).and_then(|object| validation::recolor(&object)) {
+        Ok(request) => request,
+        Err(errors) => return errors.into_response(),
+    };
+    let Some(design) = cache::design_by_token(&token) else {
+        return unknown_token();
+    };
+
+    let shape_index = match request.target {
+        ShapeTarget::Index(index) => Some(index).filter(|&index| index < design.shapes().len()),
+        ShapeTarget::Id(id) => design.shape_index(id),
+        ShapeTarget::Point(x, y) => design.shape_at(Point::new(x, y)),
+    };
+    let Some(shape_index) = shape_index else {
+        let mut errors = ValidationErrors::default();
+        match request.target {
+            ShapeTarget::Index(_) => errors.add(
+                "shape_index",
+                format!("must be less than {}", design.shapes().len()),
+            ),
+            ShapeTarget::Id(id) => errors.add("shape_id", format!("the design has no shape {}", id)),
+            ShapeTarget::Point(..) => errors.add("point", "no shape is drawn at this point"),
+        }
+        return errors.into_response();
+    };
+
+    let rendered = render_blocking(&state, move || {
+        let recolored = design.with_shape_color(shape_index, &request.color)?;
+        let svg = svg::design_to_svg(&recolored, 512, 512, &svg::SvgOptions::default())?;
+        Ok(RecolorResponse {
+            token: cache::remember(&recolored),
+            shape_index,
+            shapes: shape_summaries(&recolored),
+            svg,
+        })
+    })
+    .await;
+
+    match rendered {
+        Ok(response) => Json(response).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Error recoloring design: {}", e),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RerollResponse {
+    /// Token of the rerolled design
+    token: String,
+    seed: u64,
+    shapes: Vec<ShapeSummary>,
+    svg: String,
+}
+
+/// Regrows every shape of a previously generated design except the locked ones
+async fn reroll_handler(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    body: axum::body::Bytes,
+) -> Response {
+    let request = match validation::parse_object(&body).and_then(|object| validation::reroll(&object)) {
+        Ok(request) => request,
+        Err(errors) => return errors.into_response(),
+    };
+    let Some(design) = cache::design_by_token(&token) else {
+        return unknown_token();
+    };
+    let locked: Option<Vec<usize>> = request.locked.iter().map(|&id| design.shape_index(id)).collect();
+    let Some(locked) = locked else {
+        let mut errors = ValidationErrors::default();
+        errors.add("locked", "every entry must be the id of a shape in the design");
+        return errors.into_response();
+    };
+
+    let seed = request.seed.unwrap_or_else(random_seed);
+    let rendered = render_blocking(&state, move || {
+        let rerolled = design.with_unlocked_rerolled(&locked, seed)?;
+        let svg = svg::design_to_svg(&rerolled, 512, 512, &svg::SvgOptions::default())?;
+        Ok(RerollResponse {
+            token: cache::remember(&rerolled),
+            seed,
+            shapes: shape_summaries(&rerolled),
+            svg,
+        })
+    })
+    .await;
+
+    match rendered {
+        Ok(response) => Json(response).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Error rerolling design: {}", e),
+        )
+            .into_response(),
+    }
+}
+
+/// Rasterizes a previously generated design, including any edits made to it
+async fn design_png_handler(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    Query(params): Query<LogoParams>,
+) -> Response {
+    let Some(design) = cache::design_by_token(&token) else {
+        return unknown_token();
+    };
+
+    let (width, height) = params.png_size();
+    let options = svg::SvgOptions {
+        background: if params.transparent() {
+            None
+        } else {
+            Some("#FFFFFF".to_string())
+        },
+        ..svg::SvgOptions::default()
+    };
+
+    match render_blocking(&state, move || png::design_to_png(&design, width, height, &options)).await {
+        // The token's design can't change, but this is an editing preview
+        Ok(png_data) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "image/png"),
+                (header::CACHE_CONTROL, CachePolicy::Preview.cache_control()),
+            ],
+            png_data,
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Error generating PNG: {}", e),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct BatchItem {
+    seed: u64,
+    params: String,
+    checksum: String,
+    svg: String,
+    /// Where the SVG was uploaded when the server has an upload target
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+}
+
+/// Reads the seed list and shared parameters of a batch request of at most `max_seeds` seeds
+fn parse_batch(
+    object: &serde_json::Map<String, serde_json::Value>,
+    config: &WebConfig,
+    max_seeds: usize,
+) -> Result<(Vec<u64>, LogoParams), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    let seeds = object
+        .get("seeds")
+        .and_then(|value| value.as_array())
+        .and_then(|values| values.iter().map(|value| value.as_u64()).collect::<Option<Vec<u64>>>())
+        .filter(|seeds| !seeds.is_empty() && seeds.len() <= max_seeds)
+        .unwrap_or_else(|| {
+            errors.add(
+                "seeds",
+                format!("must be a list of 1 to {} non-negative integers", max_seeds),
+            );
+            Vec::new()
+        });
+
+    // Report problems with the seeds and the shared parameters together
+    match validation::logo_params(object).and_then(|params| with_preset(config, params)) {
+        Ok(params) => errors.into_result((seeds, params)),
+        Err(param_errors) => {
+            errors.errors.extend(param_errors.errors);
+            Err(errors)
+        }
+    }
+}
+
+/// Renders the SVG for every requested seed in one call
+async fn batch_handler(State(state): State<AppState>, body: axum::body::Bytes) -> Response {
+    let config = state.config();
+    let request = validation::parse_object(&body)
+        .and_then(|object| parse_batch(&object, &config, config.limits.max_batch_seeds));
+    let (seeds, params) = match request {
+        Ok(request) => request,
+        Err(errors) => return errors.into_response(),
+    };
+
+    let batch = BatchRender::new(&state, &seeds, &params);
+    match render_blocking(&state, move || batch.run()).await {
+        Ok(items) => Json(items).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Error rendering batch: {}", e),
+        )
+            .into_response(),
+    }
+}
+
+/// Everything rendering a batch needs, so it can run off the async runtime
+struct BatchRender {
+    canonical: Vec<CanonicalParams>,
+    #[cfg(feature = "cloud")]
+    uploader: Option<crate::upload::S3Uploader>,
+    #[cfg(feature = "webhook")]
+    webhook: Option<String>,
+}
+
+impl BatchRender {
+    // The state only supplies the upload target and webhook
+    #[cfg_attr(not(any(feature = "cloud", feature = "webhook")), allow(unused_variables))]
+    fn new(state: &AppState, seeds: &[u64], params: &LogoParams) -> Self {
+        Self {
+            canonical: seeds.iter().map(|&seed| params.canonicalize(seed)).collect(),
+            #[cfg(feature = "cloud")]
+            uploader: state.uploader().cloned(),
+            #[cfg(feature = "webhook")]
+            webhook: state.webhook().map(str::to_string),
+        }
+    }
+
+    /// Renders every design, uploading and announcing them when the server is set up to
+    fn run(self) -> crate::Result<Vec<BatchItem>> {
+        self.canonical
+            .iter()
+            .map(|params| {
+                #[cfg(feature = "webhook")]
+                let started = std::time::Instant::now();
+                let design = generate_from_params(params)?;
+                let options = svg::SvgOptions {
+                    params: Some(params.cache_key()),
+                    ..svg::SvgOptions::default()
+                };
+                let checksum = format!("{:016x}", design.checksum());
+                let svg = svg::design_to_svg(&design, 512, 512, &options)?;
+
+                // Named by checksum, an uploaded SVG never changes and can be cached forever
+                #[cfg(feature = "cloud")]
+                let url = match &self.uploader {
+                    Some(uploader) => Some(uploader.put(
+                        &format!("{}.svg", checksum),
+                        svg.clone().into_bytes(),
+                        "image/svg+xml",
+                        crate::upload::CACHE_IMMUTABLE,
+                    )?),
+                    None => None,
+                };
+                #[cfg(not(feature = "cloud"))]
+                let url = None;
+
+                // A failing webhook is logged rather than failing the batch it reports on
+                #[cfg(feature = "webhook")]
+                if let Some(webhook) = &self.webhook {
+                    let mut payload = crate::webhook::payload(
+                        &design,
+                        serde_json::json!(params.cache_key()),
+                        &[],
+                        started.elapsed(),
+                    );
+                    payload["urls"] = serde_json::json!(url.as_slice());
+                    if let Err(e) = crate::webhook::notify(webhook, &payload) {
+                        println!("Webhook notification failed: {}", e);
+                    }
+                }
+
+                Ok(BatchItem {
+                    seed: params.seed,
+                    params: params.cache_key(),
+                    checksum,
+                    svg,
+                    url,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Reads a job request: a batch like `/api/batch` takes, or a wallpaper
+fn parse_job(body: &[u8], state: &AppState) -> Result<(&'static str, JobTask), ValidationErrors> {
+    let object = validation::parse_object(body)?;
+    let config = state.config();
+    match object.get("kind").and_then(|kind| kind.as_str()) {
+        Some("batch") => {
+            let (seeds, params) = parse_batch(&object, &config, config.limits.max_job_seeds)?;
+            let batch = BatchRender::new(state, &seeds, &params);
+            let task: JobTask = Box::new(move || {
+                Ok(JobOutput {
+                    content_type: "application/json".to_string(),
+                    file_name: "batch.json".to_string(),
+                    data: serde_json::to_vec(&batch.run()?)?,
+                })
+            });
+            Ok(("batch", task))
+        }
+        Some("wallpaper") => {
+            let wallpaper = validation::wallpaper(&object)?;
+            let params = with_preset(&config, validation::logo_params(&object)?)?;
+            let seed = params.seed.unwrap_or_else(random_seed);
+            let config = params.canonicalize(seed).config();
+            let task: JobTask = Box::new(move || {
+                let (width, height) = wallpaper.resolution;
+                let background = wallpaper.background.as_deref();
+                let (data, content_type) = if wallpaper.svg {
+                    let svg = crate::wallpaper::wallpaper_svg(&config, width, height, wallpaper.count, background)?;
+                    (svg.into_bytes(), "image/svg+xml")
+                } else {
+                    let png = crate::wallpaper::wallpaper_png(&config, width, height, wallpaper.count, background)?;
+                    (png, "image/png")
+                };
+                let extension = if wallpaper.svg { "svg" } else { "png" };
+                Ok(JobOutput {
+                    content_type: content_type.to_string(),
+                    file_name: format!("wallpaper-{}.{}", seed, extension),
+                    data,
+                })
+            });
+            Ok(("wallpaper", task))
+        }
+        _ => {
+            let mut errors = ValidationErrors::default();
+            errors.add("kind", "must be \"batch\" or \"wallpaper\"");
+            Err(errors)
+        }
+    }
+}
+
+/// Queues a batch or wallpaper render, answering at once with where to follow it
+async fn submit_job_handler(State(state): State<AppState>, body: axum::body::Bytes) -> Response {
+    let (kind, task) = match parse_job(&body, &state) {
+        Ok(job) => job,
+        Err(errors) => return errors.into_response(),
+    };
+    match state.jobs().submit(kind, task) {
+        Ok(status) => (
+            StatusCode::ACCEPTED,
+            [(header::LOCATION, format!("/api/jobs/{}", status.id))],
+            Json(status),
+        )
+            .into_response(),
+        Err(e) => (StatusCode::SERVICE_UNAVAILABLE, e.to_string()).into_response(),
+    }
+}
+
+fn unknown_job() -> Response {
+    (StatusCode::NOT_FOUND, "Unknown job").into_response()
+}
+
+/// The job's state; done jobs can be downloaded from `/api/jobs/:id/result`
+async fn job_status_handler(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match state.jobs().status(&id) {
+        Some(status) => Json(status).into_response(),
+        None => unknown_job(),
+    }
+}
+
+/// Downloads the file a finished job produced
+async fn job_result_handler(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let Some(status) = state.jobs().status(&id) else {
+        return unknown_job();
+    };
+    match state.jobs().output(&id) {
+        Ok(Some(output)) => (
+            [
+                (header::CONTENT_TYPE, output.content_type),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}\"", output.file_name),
+                ),
+            ],
+            output.data,
+        )
+            .into_response(),
+        Ok(None) => {
+            let message = match status.state {
+                JobState::Failed => format!("The job failed: {}", status.error.unwrap_or_default()),
+                _ => "The job hasn't finished yet".to_string(),
+            };
+            (StatusCode::CONFLICT, message).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Cannot read the job's result: {}", e),
+        )
+            .into_response(),
+    }
+}
+
+/// Fills in the parameters from the preset a request names, if any
+fn with_preset(config: &WebConfig, mut params: LogoParams) -> Result<LogoParams, ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    if let Err(message) = config.apply_preset(&mut params) {
+        errors.add("preset", message);
+    }
+    errors.into_result(params)
+}
+
+#[derive(Debug, Serialize)]
+struct ReloadResponse {
+    /// Whether the file differed from the settings in use
+    changed: bool,
+    themes: Vec<String>,
+    presets: Vec<String>,
+    tenants: Vec<String>,
+}
+
+/// Reads the server's config file again, as SIGHUP or editing the file does
+async fn reload_handler(State(state): State<AppState>) -> Response {
+    if state.config_path().is_none() {
         return (
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Error generating logo: {}", e),
-        ).into_response();
+            StatusCode::CONFLICT,
+            format!("The server was started without {}", super::state::CONFIG_ENV),
+        )
+            .into_response();
+    }
+    match state.reload() {
+        Ok(changed) => {
+            let config = state.config();
+            Json(ReloadResponse {
+                changed,
+                themes: config.themes.keys().cloned().collect(),
+                presets: config.presets.keys().cloned().collect(),
+                tenants: config.tenants.keys().cloned().collect(),
+            })
+            .into_response()
+        }
+        Err(e) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Kept the current config: {}", e),
+        )
+            .into_response(),
     }
+}
+
+/// Runs a render with [`render_blocking`] and records how long it took for the admin page
+async fn timed_render<T, F>(state: &AppState, endpoint: &'static str, seed: u64, design: String, job: F) -> crate::Result<T>
+where
+    F: FnOnce() -> crate::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let at = std::time::SystemTime::now();
+    let started = std::time::Instant::now();
+    let result = render_blocking(state, job).await;
+    state.render_stats().record(RenderEvent {
+        at,
+        endpoint,
+        seed,
+        design,
+        duration: started.elapsed(),
+        ok: result.is_ok(),
+    });
+    result
+}
+
+/// Server-rendered stats for operators: latency, cache use, top seeds and recent renders
+async fn admin_handler(State(state): State<AppState>) -> Html<String> {
+    let summary = state.render_stats().summary(admin::ADMIN_ROWS);
+    Html(admin::page(state.instance_name(), &summary, &cache::stats()).into_string())
+}
+
+/// Generates a design from canonicalized request parameters
+fn generate_from_params(params: &CanonicalParams) -> crate::Result<GeneratedDesign> {
+    // Debug output to server console
+    println!("Generating logo with: {}", params.cache_key());
+
+    // Generate the logo, or recolor one whose shapes were grown already
+    let design = cache::design_for(params)?;
     
-    println!("Logo generation successful, generated {} shapes", generator.shapes().len());
+    println!("Logo generation successful, generated {} shapes", design.shapes().len());
+
+    Ok(design)
+}
+
+/// Response header carrying the canonical parameters an image was generated from
+pub const PARAMS_HEADER: &str = "X-Hexalith-Params";
+
+/// The entity tag for a design response identified by `key`
+///
+/// Previews depend on the config, so their tags change with every reload;
+/// a palette edit keeps the theme's name, and with it the canonical key.
+fn design_etag(policy: CachePolicy, key: &str, config_generation: u64) -> String {
+    match policy {
+        CachePolicy::Preview => policy.etag(&format!("{}|config={}", key, config_generation)),
+        _ => policy.etag(key),
+    }
+}
+
+/// Response for a conditional request whose cached copy is still valid
+///
+/// It repeats the caching headers, plus any `extra` ones, of the full response.
+fn not_modified(policy: CachePolicy, etag: String, extra: Vec<(&'static str, String)>) -> Response {
+    let mut response = (
+        StatusCode::NOT_MODIFIED,
+        [
+            (header::ETAG, etag),
+            (header::CACHE_CONTROL, policy.cache_control().to_string()),
+        ],
+    )
+        .into_response();
+    for (name, value) in extra {
+        if let Ok(value) = value.parse() {
+            response.headers_mut().insert(name, value);
+        }
+    }
+    response
+}
+
+/// A design request's parameters with its preset and tenant defaults filled in
+struct DesignRequest {
+    params: LogoParams,
+    policy: CachePolicy,
+    /// `/t/<tenant>` on tenant routes, so URLs and entity tags stay per tenant
+    prefix: String,
+    guide: Option<GuideMask>,
+    /// [`AppState::config_generation`] when the request was resolved
+    config_generation: u64,
+}
+
+impl DesignRequest {
+    fn canonicalize(&self, seed: u64) -> CanonicalParams {
+        CanonicalParams {
+            guide: self.guide.clone(),
+            ..self.params.canonicalize(seed)
+        }
+    }
+}
+
+/// Resolves an image request, made under `/t/<tenant>` when `tenant` is given
+///
+/// Fails with 400 for an unknown algorithm version, 404 for an unknown tenant
+/// and 429 once the tenant's requests for the minute are used up. The error
+/// response is boxed to keep the result small.
+fn design_request(state: &AppState, tenant: Option<&str>, params: LogoParams) -> Result<DesignRequest, Box<Response>> {
+    params
+        .algo_version()
+        .map_err(|message| Box::new((StatusCode::BAD_REQUEST, message).into_response()))?;
+    let config_generation = state.config_generation();
+    let config = state.config();
+    let mut params = with_preset(&config, params).map_err(|errors| Box::new(errors.into_response()))?;
+    let Some(name) = tenant else {
+        return Ok(DesignRequest {
+            policy: CachePolicy::for_design(&params),
+            params,
+            prefix: String::new(),
+            guide: None,
+            config_generation,
+        });
+    };
+
+    let tenant = config
+        .tenants
+        .get(name)
+        .ok_or_else(|| Box::new((StatusCode::NOT_FOUND, format!("Unknown tenant '{}'", name)).into_response()))?;
+    if let Err(retry_after) = state.tenant_limiter().check(name, tenant.requests_per_minute) {
+        return Err(Box::new(
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, retry_after.as_secs().max(1).to_string())],
+                format!(
+                    "Tenant '{}' is limited to {} requests per minute",
+                    name,
+                    tenant.requests_per_minute.unwrap_or_default()
+                ),
+            )
+                .into_response(),
+        ));
+    }
+    tenant.apply(name, &mut params);
+    Ok(DesignRequest {
+        params,
+        // Tenant defaults come from the config, which can be reloaded
+        policy: CachePolicy::Preview,
+        prefix: format!("/t/{}", name),
+        guide: tenant.guide.clone(),
+        config_generation,
+    })
+}
+
+async fn get_svg_handler(
+    State(state): State<AppState>,
+    Path(seed): Path<u64>,
+    Query(params): Query<LogoParams>,
+    headers: HeaderMap,
+) -> Response {
+    svg_response(state, None, seed, params, headers).await
+}
+
+/// Serves `/t/:tenant/svg/:seed`, the design with the tenant's defaults
+async fn tenant_svg_handler(
+    State(state): State<AppState>,
+    Path((tenant, seed)): Path<(String, u64)>,
+    Query(params): Query<LogoParams>,
+    headers: HeaderMap,
+) -> Response {
+    svg_response(state, Some(tenant), seed, params, headers).await
+}
+
+/// Serves `/svg/from/:text`, the design seeded by a readable identifier
+///
+/// The text is hashed with [`utils::text_to_seed`], so `Payments%20Service`
+/// and `payments service` give the same design. The response is the same as
+/// for `/svg/:seed`, whose URL it links as canonical.
+async fn svg_from_text_handler(
+    State(state): State<AppState>,
+    Path(text): Path<String>,
+    Query(params): Query<LogoParams>,
+    headers: HeaderMap,
+) -> Response {
+    svg_from_text_response(state, None, text, params, headers).await
+}
+
+/// Serves `/d/:code`, the design a [`DesignCode`] describes
+///
+/// The code's values replace any given in the query string, so only the
+/// options a code doesn't carry, such as `preset`, still apply. The response
+/// is the same as for `/svg/:seed`, whose URL it links as canonical.
+async fn design_code_handler(
+    State(state): State<AppState>,
+    Path(code): Path<String>,
+    Query(params): Query<LogoParams>,
+    headers: HeaderMap,
+) -> Response {
+    let code = match code.parse::<DesignCode>() {
+        Ok(code) => code,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    let params = LogoParams {
+        theme: Some(code.theme),
+        shapes: Some(code.shapes),
+        grid_size: Some(code.grid_size),
+        opacity: Some(code.opacity),
+        overlap: Some(code.overlap),
+        algo_version: Some(code.algo_version.number()),
+        ..params
+    };
+    svg_response(state, None, code.seed, params, headers).await
+}
+
+/// Serves `/t/:tenant/svg/from/:text`, the text-seeded design with the tenant's defaults
+async fn tenant_svg_from_text_handler(
+    State(state): State<AppState>,
+    Path((tenant, text)): Path<(String, String)>,
+    Query(params): Query<LogoParams>,
+    headers: HeaderMap,
+) -> Response {
+    svg_from_text_response(state, Some(tenant), text, params, headers).await
+}
+
+async fn svg_from_text_response(
+    state: AppState,
+    tenant: Option<String>,
+    text: String,
+    params: LogoParams,
+    headers: HeaderMap,
+) -> Response {
+    if text.trim().is_empty() || text.len() > MAX_NAME_BYTES {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("text must be 1 to {} bytes", MAX_NAME_BYTES),
+        )
+            .into_response();
+    }
+    svg_response(state, tenant, utils::text_to_seed(&text), params, headers).await
+}
 
-    // Generate SVG
-    match svg::generate_svg(&generator, 512, 512) {
+/// The SVG for a seed, answering conditional requests without rendering
+async fn svg_response(
+    state: AppState,
+    tenant: Option<String>,
+    seed: u64,
+    params: LogoParams,
+    headers: HeaderMap,
+) -> Response {
+    let request = match design_request(&state, tenant.as_deref(), params) {
+        Ok(request) => request,
+        Err(response) => return *response,
+    };
+    // Equivalent parameter sets share one key, URL and entity tag
+    let canonical = request.canonicalize(seed);
+    let policy = request.policy;
+    let etag = design_etag(
+        policy,
+        &format!("{}svg?{}", request.prefix, canonical.cache_key()),
+        request.config_generation,
+    );
+    let canonical_url = format!("{}/svg/{}?{}", request.prefix, seed, canonical.query_string());
+    let params_string = canonical.cache_key();
+
+    if cache::is_not_modified(&headers, &etag) {
+        let link = format!("<{}>; rel=\"canonical\"", canonical_url);
+        return not_modified(policy, etag, vec![("Link", link), (PARAMS_HEADER, params_string)]);
+    }
+
+    // The parameters travel with the file so it can be regenerated later
+    let options = svg::SvgOptions {
+        params: Some(params_string.clone()),
+        ..svg::SvgOptions::default()
+    };
+
+    // Generation and rendering both run off the async runtime
+    let endpoint = if tenant.is_some() { "/t/:tenant/svg/:seed" } else { "/svg/:seed" };
+    let rendered = timed_render(&state, endpoint, seed, canonical_url.clone(), move || {
+        let design = generate_from_params(&canonical)?;
+        svg::design_to_svg(&design, 512, 512, &options)
+    })
+    .await;
+
+    match rendered {
         Ok(svg_data) => {
             println!("SVG generation successful, size: {} bytes", svg_data.len());
             (
                 axum::http::StatusCode::OK,
                 [
-                    ("Content-Type", "image/svg+xml"),
-                    ("Cache-Control", "public, max-age=86400"), // Cache for a day
+                    ("Content-Type", "image/svg+xml".to_string()),
+                    ("Cache-Control", policy.cache_control().to_string()),
+                    ("ETag", etag),
+                    ("Link", format!("<{}>; rel=\"canonical\"", canonical_url)),
+                    (PARAMS_HEADER, params_string),
                 ],
                 svg_data,
             ).into_response()
@@ -548,4 +1704,108 @@ async fn get_svg_handler(Path(seed): Path<u64>, Query(params): Query<LogoParams>
             ).into_response()
         }
     }
-}
\ No newline at end of file
+}
+
+async fn get_png_handler(
+    State(state): State<AppState>,
+    Path(seed): Path<u64>,
+    Query(params): Query<LogoParams>,
+    headers: HeaderMap,
+) -> Response {
+    png_response(state, None, seed, params, headers).await
+}
+
+/// Serves `/t/:tenant/png/:seed`, the design with the tenant's defaults
+async fn tenant_png_handler(
+    State(state): State<AppState>,
+    Path((tenant, seed)): Path<(String, u64)>,
+    Query(params): Query<LogoParams>,
+    headers: HeaderMap,
+) -> Response {
+    png_response(state, Some(tenant), seed, params, headers).await
+}
+
+/// The PNG for a seed, answering conditional requests without rendering
+async fn png_response(
+    state: AppState,
+    tenant: Option<String>,
+    seed: u64,
+    params: LogoParams,
+    headers: HeaderMap,
+) -> Response {
+    let request = match design_request(&state, tenant.as_deref(), params) {
+        Ok(request) => request,
+        Err(response) => return *response,
+    };
+    let policy = request.policy;
+    // Raster size is limited to keep render time and memory bounded
+    let (width, height) = request.params.png_size();
+    let transparent = request.params.transparent();
+    let canonical = request.canonicalize(seed);
+
+    // Raster options are part of the cache identity alongside the design
+    let raster_query = format!(
+        "{}&width={}&height={}&transparent={}",
+        canonical.query_string(),
+        width,
+        height,
+        transparent
+    );
+    let params_string = format!("seed={}&{}", seed, raster_query);
+    let key = format!(
+        "{}png?{}&width={}&height={}&transparent={}",
+        request.prefix,
+        canonical.cache_key(),
+        width,
+        height,
+        transparent
+    );
+    let etag = design_etag(policy, &key, request.config_generation);
+    let canonical_url = format!("{}/png/{}?{}", request.prefix, seed, raster_query);
+
+    if cache::is_not_modified(&headers, &etag) {
+        let link = format!("<{}>; rel=\"canonical\"", canonical_url);
+        return not_modified(policy, etag, vec![("Link", link), (PARAMS_HEADER, params_string)]);
+    }
+
+    let options = svg::SvgOptions {
+        background: if transparent {
+            None
+        } else {
+            Some("#FFFFFF".to_string())
+        },
+        ..svg::SvgOptions::default()
+    };
+
+    // Generation and rasterization both run off the async runtime
+    let endpoint = if tenant.is_some() { "/t/:tenant/png/:seed" } else { "/png/:seed" };
+    let rendered = timed_render(&state, endpoint, seed, canonical_url.clone(), move || {
+        let design = generate_from_params(&canonical)?;
+        png::design_to_png(&design, width, height, &options)
+    })
+    .await;
+
+    match rendered {
+        Ok(png_data) => {
+            println!("PNG generation successful, size: {} bytes", png_data.len());
+            (
+                axum::http::StatusCode::OK,
+                [
+                    ("Content-Type", "image/png".to_string()),
+                    ("Cache-Control", policy.cache_control().to_string()),
+                    ("ETag", etag),
+                    ("Link", format!("<{}>; rel=\"canonical\"", canonical_url)),
+                    (PARAMS_HEADER, params_string),
+                ],
+                png_data,
+            ).into_response()
+        }
+        Err(e) => {
+            println!("Error generating PNG: {}", e);
+            (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Error generating PNG: {}", e),
+            ).into_response()
+        }
+    }
+}
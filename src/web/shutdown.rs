@@ -0,0 +1,102 @@
+use crate::Result;
+use axum::Router;
+use std::future::{Future, IntoFuture};
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+/// Environment variable with the seconds in-flight requests get to finish on shutdown
+pub const DRAIN_TIMEOUT_ENV: &str = "HEXALITH_DRAIN_TIMEOUT";
+
+/// Drain time without `HEXALITH_DRAIN_TIMEOUT`, matching Kubernetes' default grace period
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The drain timeout set in the environment, or the default
+pub fn drain_timeout_from_env() -> Result<Duration> {
+    match std::env::var(DRAIN_TIMEOUT_ENV) {
+        Ok(value) if !value.trim().is_empty() => parse_drain_timeout(&value),
+        _ => Ok(DEFAULT_DRAIN_TIMEOUT),
+    }
+}
+
+fn parse_drain_timeout(value: &str) -> Result<Duration> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        // Rejects negative, infinite and NaN seconds, and ones too large for a Duration
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| format!("Invalid {} '{}' (expected a number of seconds)", DRAIN_TIMEOUT_ENV, value).into())
+}
+
+/// Completes when the process is asked to stop with SIGTERM or Ctrl-C
+pub async fn signal() {
+    let interrupt = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => {}
+        _ = terminate => {}
+    }
+}
+
+/// Serves `app` until `shutdown` completes, then drains in-flight requests
+///
+/// Once shutdown starts no new connections are accepted and idle ones are
+/// closed, while requests already running may finish. Connections still busy
+/// after `drain_timeout` are abandoned so a deploy never hangs.
+pub async fn serve<F>(listener: TcpListener, app: Router, drain_timeout: Duration, shutdown: F) -> Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let (draining, drain_started) = tokio::sync::oneshot::channel();
+    let server = axum::serve(listener, app).with_graceful_shutdown(async move {
+        shutdown.await;
+        println!("Shutting down; draining requests for up to {:?}", drain_timeout);
+        let _ = draining.send(());
+    });
+    let deadline = async move {
+        match drain_started.await {
+            Ok(()) => tokio::time::sleep(drain_timeout).await,
+            // The server stopped by itself
+            Err(_) => std::future::pending().await,
+        }
+    };
+
+    tokio::select! {
+        result = server.into_future() => {
+            result?;
+            println!("All requests finished");
+        }
+        _ = deadline => println!("Drain timeout reached; closing the remaining connections"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_drain_timeout() {
+        assert_eq!(parse_drain_timeout("45").unwrap(), Duration::from_secs(45));
+        assert_eq!(parse_drain_timeout(" 0.5 ").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_drain_timeout("0").unwrap(), Duration::ZERO);
+        assert!(parse_drain_timeout("-1").is_err());
+        assert!(parse_drain_timeout("soon").is_err());
+        assert!(parse_drain_timeout("inf").is_err());
+        assert!(parse_drain_timeout("1e300").is_err());
+    }
+}
@@ -0,0 +1,317 @@
+use super::config::{self, WebConfig};
+use super::jobs::JobQueue;
+use super::stats::RenderStats;
+use super::tenant::RateLimiter;
+use crate::Result;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use tokio::sync::Semaphore;
+
+/// Environment variable holding a comma separated list of API tokens
+pub const API_TOKENS_ENV: &str = "HEXALITH_API_TOKENS";
+
+/// Environment variable naming the s3://bucket/prefix that batch renders are uploaded to
+pub const UPLOAD_ENV: &str = "HEXALITH_UPLOAD";
+
+/// Environment variable with a URL notified of every design `/api/batch` renders
+pub const WEBHOOK_ENV: &str = "HEXALITH_WEBHOOK";
+
+/// Environment variable naming this server instance; its favicon is drawn from the name
+pub const INSTANCE_NAME_ENV: &str = "HEXALITH_INSTANCE_NAME";
+
+/// Environment variable naming a TOML [`WebConfig`] file, reloaded while the server runs
+pub const CONFIG_ENV: &str = "HEXALITH_CONFIG";
+
+/// Size in pixels of the generated favicon
+pub const FAVICON_SIZE: u32 = 64;
+
+/// Shared state available to every route handler
+#[derive(Clone)]
+pub struct AppState {
+    render_permits: Arc<Semaphore>,
+    api_tokens: Arc<Vec<String>>,
+    instance_name: Arc<str>,
+    favicon: Arc<str>,
+    config: Arc<RwLock<Arc<WebConfig>>>,
+    config_generation: Arc<AtomicU64>,
+    config_path: Option<Arc<Path>>,
+    tenant_limiter: Arc<RateLimiter>,
+    render_stats: Arc<RenderStats>,
+    jobs: Arc<JobQueue>,
+    #[cfg(feature = "cloud")]
+    uploader: Option<Arc<crate::upload::S3Uploader>>,
+    #[cfg(feature = "webhook")]
+    webhook: Option<Arc<str>>,
+}
+
+impl AppState {
+    /// Creates state allowing at most `max_concurrent_renders` renders at once
+    ///
+    /// The instance is named by a random id until [`AppState::with_instance_name`] is used.
+    pub fn new(max_concurrent_renders: usize) -> Self {
+        let state = Self {
+            render_permits: Arc::new(Semaphore::new(max_concurrent_renders.max(1))),
+            api_tokens: Arc::new(Vec::new()),
+            instance_name: Arc::from(""),
+            favicon: Arc::from(""),
+            config: Arc::new(RwLock::new(Arc::new(WebConfig::default()))),
+            config_generation: Arc::new(AtomicU64::new(0)),
+            config_path: None,
+            tenant_limiter: Arc::new(RateLimiter::default()),
+            render_stats: Arc::new(RenderStats::default()),
+            jobs: Arc::new(JobQueue::default()),
+            #[cfg(feature = "cloud")]
+            uploader: None,
+            #[cfg(feature = "webhook")]
+            webhook: None,
+        };
+        state.with_instance_name(&random_instance_id())
+    }
+
+    /// Default state with API tokens and the instance name read from the environment
+    pub fn from_env() -> Self {
+        let tokens = std::env::var(API_TOKENS_ENV).unwrap_or_default();
+        let state = Self::default().with_api_tokens(tokens.split(',').map(str::to_string));
+        match std::env::var(INSTANCE_NAME_ENV) {
+            Ok(name) if !name.trim().is_empty() => state.with_instance_name(name.trim()),
+            _ => state,
+        }
+    }
+
+    /// Names this server instance and draws its favicon from the name
+    ///
+    /// Instances behind one load balancer get visibly different browser tabs.
+    pub fn with_instance_name(mut self, name: &str) -> Self {
+        // A three-shape design on a small grid always generates
+        let favicon = crate::svg::identicon(name, FAVICON_SIZE).unwrap_or_default();
+        self.instance_name = Arc::from(name);
+        self.favicon = Arc::from(favicon);
+        self
+    }
+
+    pub fn instance_name(&self) -> &str {
+        &self.instance_name
+    }
+
+    /// The SVG favicon drawn for this instance at startup
+    pub fn favicon(&self) -> &str {
+        &self.favicon
+    }
+
+    /// Requires one of these bearer tokens on protected endpoints
+    ///
+    /// Blank tokens are ignored; with no tokens at all the endpoints stay open.
+    pub fn with_api_tokens<I: IntoIterator<Item = String>>(mut self, tokens: I) -> Self {
+        let tokens = tokens
+            .into_iter()
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty())
+            .collect();
+        self.api_tokens = Arc::new(tokens);
+        self
+    }
+
+    /// Whether protected endpoints need a bearer token
+    pub fn requires_api_token(&self) -> bool {
+        !self.api_tokens.is_empty() || self.config().api_tokens.iter().any(|token| !token.trim().is_empty())
+    }
+
+    /// Checks a presented bearer token against the configured ones
+    pub fn is_valid_api_token(&self, token: &str) -> bool {
+        let config = self.config();
+        let from_config = config.api_tokens.iter().map(|token| token.trim()).filter(|token| !token.is_empty());
+        self.api_tokens
+            .iter()
+            .map(String::as_str)
+            .chain(from_config)
+            .any(|expected| constant_time_eq(expected.as_bytes(), token.as_bytes()))
+    }
+
+    /// Reads settings from a config file that [`AppState::reload`] reads again later
+    pub fn with_config_file(mut self, path: &Path) -> Result<Self> {
+        self.config_path = Some(Arc::from(path));
+        self.reload()?;
+        Ok(self)
+    }
+
+    /// The config file given to [`AppState::with_config_file`], if any
+    pub fn config_path(&self) -> Option<&Path> {
+        self.config_path.as_deref()
+    }
+
+    /// The current settings; requests keep the snapshot they started with
+    pub fn config(&self) -> Arc<WebConfig> {
+        self.config.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Swaps in new settings for every clone of this state, registering their themes
+    ///
+    /// In-flight requests are unaffected. If the themes can't be registered the
+    /// current settings stay.
+    pub fn apply_config(&self, next: WebConfig) -> Result<()> {
+        let mut current = self.config.write().unwrap_or_else(|e| e.into_inner());
+        config::swap_themes(&current, &next)?;
+        *current = Arc::new(next);
+        self.config_generation.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Counts the settings applied so far, so responses drawn from them can be told apart
+    ///
+    /// Read it before [`AppState::config`]: a reload in between then only costs
+    /// a client one extra download, instead of tagging new settings with an old count.
+    pub fn config_generation(&self) -> u64 {
+        self.config_generation.load(Ordering::SeqCst)
+    }
+
+    /// Reads the config file again, returning whether anything changed
+    pub fn reload(&self) -> Result<bool> {
+        let path = self
+            .config_path()
+            .ok_or_else(|| format!("No config file; set {} to enable reloading", CONFIG_ENV))?;
+        let next = WebConfig::load(path)?;
+        if *self.config() == next {
+            return Ok(false);
+        }
+        self.apply_config(next)?;
+        Ok(true)
+    }
+
+    /// Counts image requests against each tenant's `requests_per_minute`
+    pub fn tenant_limiter(&self) -> &RateLimiter {
+        &self.tenant_limiter
+    }
+
+    /// Recent renders, shown on the admin page
+    pub fn render_stats(&self) -> &RenderStats {
+        &self.render_stats
+    }
+
+    /// Runs background jobs on this queue instead of the default in-memory one
+    pub fn with_jobs(mut self, jobs: JobQueue) -> Self {
+        self.jobs = Arc::new(jobs);
+        self
+    }
+
+    /// Background renders submitted to `/api/jobs`
+    pub fn jobs(&self) -> &Arc<JobQueue> {
+        &self.jobs
+    }
+
+    /// Uploads rendered batches to object storage
+    #[cfg(feature = "cloud")]
+    pub fn with_uploader(mut self, uploader: crate::upload::S3Uploader) -> Self {
+        self.uploader = Some(Arc::new(uploader));
+        self
+    }
+
+    /// Where rendered batches are uploaded, if anywhere
+    #[cfg(feature = "cloud")]
+    pub fn uploader(&self) -> Option<&crate::upload::S3Uploader> {
+        self.uploader.as_deref()
+    }
+
+    /// Notifies `url` of every design rendered by a batch
+    #[cfg(feature = "webhook")]
+    pub fn with_webhook(mut self, url: &str) -> Self {
+        self.webhook = Some(Arc::from(url));
+        self
+    }
+
+    /// The URL notified of rendered batch designs, if any
+    #[cfg(feature = "webhook")]
+    pub fn webhook(&self) -> Option<&str> {
+        self.webhook.as_deref()
+    }
+
+    /// Draws render permits from `permits`, which other users may share
+    pub fn with_render_permits(mut self, permits: Arc<Semaphore>) -> Self {
+        self.render_permits = permits;
+        self
+    }
+
+    /// Permits bounding how many renders run on the blocking thread pool
+    pub fn render_permits(&self) -> &Semaphore {
+        &self.render_permits
+    }
+}
+
+/// An id for an instance that wasn't given a name, different on every start
+fn random_instance_id() -> String {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    let id = crate::utils::stable_hash(format!("{}:{}", std::process::id(), nanos).as_bytes());
+    format!("hexweb-{:08x}", id as u32)
+}
+
+/// Compares two byte strings without exiting early on the first mismatch
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        // Renders share the library's budget with render_svg_async and render_png_async
+        Self::new(1).with_render_permits(crate::render::worker_budget())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_tokens() {
+        let open = AppState::new(1);
+        assert!(!open.requires_api_token());
+
+        let state = AppState::new(1).with_api_tokens(["alpha".to_string(), " ".to_string()]);
+        assert!(state.requires_api_token());
+        assert!(state.is_valid_api_token("alpha"));
+        assert!(!state.is_valid_api_token("alph"));
+        assert!(!state.is_valid_api_token(""));
+    }
+
+    #[test]
+    fn test_instance_favicon() {
+        let state = AppState::new(1);
+        assert!(state.instance_name().starts_with("hexweb-"));
+        assert!(state.favicon().contains("<svg"));
+
+        let named = AppState::new(1).with_instance_name("eu-west-1");
+        assert_eq!(named.instance_name(), "eu-west-1");
+        assert_eq!(named.favicon(), crate::svg::identicon("eu-west-1", FAVICON_SIZE).unwrap());
+    }
+
+    #[test]
+    fn test_config_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hexweb.toml");
+        std::fs::write(&path, "[themes]\nstate-reload-corp = [\"#0B3D91\"]\n").unwrap();
+
+        let state = AppState::new(1).with_config_file(&path).unwrap();
+        let clone = state.clone();
+        assert!(!state.requires_api_token());
+        assert!(!state.reload().unwrap());
+
+        std::fs::write(&path, "api_tokens = [\"beta\"]\n[themes]\nstate-reload-corp = [\"#FC3D21\"]\n").unwrap();
+        assert!(state.reload().unwrap());
+        assert!(clone.requires_api_token());
+        assert!(clone.is_valid_api_token("beta"));
+        assert_eq!(
+            crate::generator::color::ColorManager::theme_palette(&"state-reload-corp".parse().unwrap()),
+            vec!["#FC3D21"]
+        );
+
+        // A broken file is reported and the working settings stay
+        std::fs::write(&path, "api_tokens = 7\n").unwrap();
+        assert!(state.reload().is_err());
+        assert!(clone.is_valid_api_token("beta"));
+        assert!(AppState::new(1).reload().is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// How many render events are kept; older ones are dropped
+pub const EVENT_CAPACITY: usize = 1024;
+
+/// One design generated by an image endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderEvent {
+    pub at: SystemTime,
+    /// The route that rendered, like `/svg/:seed`
+    pub endpoint: &'static str,
+    pub seed: u64,
+    /// What was rendered: its canonical URL, or its canonical parameters for `/generate`
+    pub design: String,
+    /// Time spent generating and rendering, including waiting for a render permit
+    pub duration: Duration,
+    pub ok: bool,
+}
+
+/// The most recent render events, in a fixed-size ring buffer
+#[derive(Debug, Default)]
+pub struct RenderStats {
+    events: Mutex<VecDeque<RenderEvent>>,
+}
+
+/// Figures about the kept render events, for the admin page
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsSummary {
+    /// Number of events kept
+    pub renders: usize,
+    pub failures: usize,
+    /// Newest first
+    pub recent: Vec<RenderEvent>,
+    /// Most rendered seeds with their render counts, most first
+    pub top_seeds: Vec<(u64, usize)>,
+    /// Render time at the 50th, 90th and 99th percentile; empty without events
+    pub latency: Vec<(u8, Duration)>,
+}
+
+impl RenderStats {
+    /// Keeps `event`, dropping the oldest one when the buffer is full
+    pub fn record(&self, event: RenderEvent) {
+        let mut events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+        if events.len() >= EVENT_CAPACITY {
+            events.pop_front();
+        }
+        events.push_back(event);
+    }
+
+    /// Summarizes the kept events, listing `limit` recent events and top seeds
+    pub fn summary(&self, limit: usize) -> StatsSummary {
+        let events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut counts: HashMap<u64, usize> = HashMap::new();
+        for event in events.iter() {
+            *counts.entry(event.seed).or_default() += 1;
+        }
+        let mut top_seeds: Vec<(u64, usize)> = counts.into_iter().collect();
+        // Ties go to the smaller seed so the table doesn't reshuffle between loads
+        top_seeds.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        top_seeds.truncate(limit);
+
+        let mut durations: Vec<Duration> = events.iter().map(|event| event.duration).collect();
+        durations.sort();
+        let latency = if durations.is_empty() {
+            Vec::new()
+        } else {
+            [50, 90, 99].iter().map(|&p| (p, percentile(&durations, p))).collect()
+        };
+
+        StatsSummary {
+            renders: events.len(),
+            failures: events.iter().filter(|event| !event.ok).count(),
+            recent: events.iter().rev().take(limit).cloned().collect(),
+            top_seeds,
+            latency,
+        }
+    }
+}
+
+/// The nearest-rank percentile of sorted, non-empty durations
+fn percentile(sorted: &[Duration], p: u8) -> Duration {
+    let rank = (sorted.len() * p as usize).div_ceil(100).max(1);
+    sorted[rank.min(sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(seed: u64, millis: u64) -> RenderEvent {
+        RenderEvent {
+            at: SystemTime::now(),
+            endpoint: "/svg/:seed",
+            seed,
+            design: format!("/svg/{}", seed),
+            duration: Duration::from_millis(millis),
+            ok: millis < 100,
+        }
+    }
+
+    #[test]
+    fn test_summary() {
+        let stats = RenderStats::default();
+        assert_eq!(stats.summary(5).latency, Vec::new());
+
+        for (seed, millis) in [(7, 10), (3, 20), (7, 30), (9, 40), (3, 150)] {
+            stats.record(event(seed, millis));
+        }
+        let summary = stats.summary(2);
+        assert_eq!(summary.renders, 5);
+        assert_eq!(summary.failures, 1);
+        let recent: Vec<u64> = summary.recent.iter().map(|event| event.seed).collect();
+        assert_eq!(recent, vec![3, 9]);
+        assert_eq!(summary.top_seeds, vec![(3, 2), (7, 2)]);
+        assert_eq!(
+            summary.latency,
+            vec![
+                (50, Duration::from_millis(30)),
+                (90, Duration::from_millis(150)),
+                (99, Duration::from_millis(150)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ring_buffer_drops_oldest() {
+        let stats = RenderStats::default();
+        for seed in 0..EVENT_CAPACITY as u64 + 10 {
+            stats.record(event(seed, 1));
+        }
+        let summary = stats.summary(1);
+        assert_eq!(summary.renders, EVENT_CAPACITY);
+        assert_eq!(summary.recent[0].seed, EVENT_CAPACITY as u64 + 9);
+        assert_eq!(summary.top_seeds, vec![(10, 1)]);
+    }
+}
@@ -7,7 +7,7 @@ pub fn index_page() -> Markup {
             head {
                 meta charset="utf-8";
                 meta name="viewport" content="width=device-width, initial-scale=1.0";
-                link rel="icon" href="/assets/favicon.svg" type="image/svg+xml";
+                link rel="icon" href="/favicon.svg" type="image/svg+xml";
                 title { "Hexalith Logo Generator" }
                 style { r#"
                     /* Animation for the logo generation */
@@ -190,9 +190,9 @@ pub fn index_page() -> Markup {
                             }
 
                             div class="form-group" {
-                                label for="shapes" { "Number of Shapes (1-10)" }
+                                label for="shapes" { "Number of Shapes (1-25)" }
                                 div class="range-group" {
-                                    input type="range" id="shapes" name="shapes" min="1" max="10" value="4" step="1" {}
+                                    input type="range" id="shapes" name="shapes" min="1" max="25" value="4" step="1" {}
                                     span id="shapes-value" class="range-value" { "4" }
                                 }
                             }
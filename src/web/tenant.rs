@@ -0,0 +1,141 @@
+use super::params::LogoParams;
+use crate::generator::GuideMask;
+use crate::trace::Raster;
+use crate::Result;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Length of the window a tenant's request allowance applies to
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Branding defaults for the designs served under `/t/<tenant>/...`
+///
+/// A request's own parameters and preset still win over these.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TenantConfig {
+    /// Theme used when a request names none
+    pub theme: Option<String>,
+    /// Colors used when a request names no theme, registered as a theme named after the tenant
+    #[serde(default)]
+    pub palette: Vec<String>,
+    /// PNG or SVG image the shapes grow toward, relative to the config file
+    pub silhouette: Option<PathBuf>,
+    /// Most image requests the tenant may make per minute; unlimited when unset
+    pub requests_per_minute: Option<u32>,
+    /// The silhouette sampled into a mask, filled in when the config file is loaded
+    #[serde(skip)]
+    pub guide: Option<GuideMask>,
+}
+
+impl TenantConfig {
+    /// The theme a request to tenant `name` gets when it names none
+    pub fn default_theme(&self, name: &str) -> Option<String> {
+        if self.palette.is_empty() {
+            self.theme.clone()
+        } else {
+            Some(name.to_string())
+        }
+    }
+
+    /// Fills the theme a request left out from tenant `name`'s defaults
+    pub fn apply(&self, name: &str, params: &mut LogoParams) {
+        params.theme = params.theme.take().or_else(|| self.default_theme(name));
+    }
+
+    /// Reads the silhouette image, resolving its path against `base`
+    pub(super) fn load_silhouette(&mut self, base: &Path) -> Result<()> {
+        if let Some(path) = &self.silhouette {
+            let path = base.join(path);
+            let raster = Raster::load(&path).map_err(|e| format!("Silhouette {}: {}", path.display(), e))?;
+            self.guide = Some(raster.guide_mask());
+        }
+        Ok(())
+    }
+}
+
+/// Whether `name` can be used in a `/t/<tenant>` URL and as a theme name
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Counts requests per tenant in fixed one-minute windows
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    windows: Mutex<HashMap<String, (Instant, u32)>>,
+}
+
+impl RateLimiter {
+    /// Counts one request by `tenant`, allowing at most `per_minute` a minute
+    ///
+    /// Fails with the time until the tenant's allowance renews once it's used up.
+    pub fn check(&self, tenant: &str, per_minute: Option<u32>) -> std::result::Result<(), Duration> {
+        let Some(limit) = per_minute else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        let (started, count) = windows.entry(tenant.to_string()).or_insert((now, 0));
+        if now.duration_since(*started) >= RATE_WINDOW {
+            *started = now;
+            *count = 0;
+        }
+        if *count >= limit {
+            return Err(RATE_WINDOW.saturating_sub(now.duration_since(*started)));
+        }
+        *count += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tenant_defaults() {
+        let themed = TenantConfig {
+            theme: Some("blues".to_string()),
+            ..TenantConfig::default()
+        };
+        let mut params = LogoParams::default();
+        themed.apply("acme", &mut params);
+        assert_eq!(params.theme.as_deref(), Some("blues"));
+
+        // A palette is used through the theme named after the tenant
+        let branded = TenantConfig {
+            palette: vec!["#0B3D91".to_string()],
+            ..TenantConfig::default()
+        };
+        assert_eq!(branded.default_theme("acme").as_deref(), Some("acme"));
+
+        let mut chosen = LogoParams {
+            theme: Some("reds".to_string()),
+            ..LogoParams::default()
+        };
+        branded.apply("acme", &mut chosen);
+        assert_eq!(chosen.theme.as_deref(), Some("reds"));
+
+        assert!(is_valid_name("acme-eu_2"));
+        assert!(!is_valid_name("acme/eu"));
+        assert!(!is_valid_name(""));
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::default();
+        assert!(limiter.check("acme", Some(2)).is_ok());
+        assert!(limiter.check("acme", Some(2)).is_ok());
+        let retry_after = limiter.check("acme", Some(2)).unwrap_err();
+        assert!(retry_after > Duration::ZERO && retry_after <= RATE_WINDOW);
+
+        // Tenants have separate allowances, and no limit means none
+        assert!(limiter.check("globex", Some(2)).is_ok());
+        for _ in 0..100 {
+            assert!(limiter.check("initech", None).is_ok());
+        }
+    }
+}
@@ -0,0 +1,456 @@
+use super::params::{LogoParams, MAX_PNG_SIZE, MIN_PNG_SIZE};
+use crate::generator::color::{parse_color, Theme};
+use crate::generator::params::{GRID_SIZE_RANGE, OPACITY_RANGE, SHAPES_RANGE};
+use crate::generator::{AlgoVersion, Params};
+use crate::wallpaper;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// A single invalid field in a request body
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Every problem found in a request body, returned as a 422 JSON response
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ValidationErrors {
+    pub errors: Vec<FieldError>,
+}
+
+impl ValidationErrors {
+    pub fn add(&mut self, field: &str, message: impl Into<String>) {
+        self.errors.push(FieldError {
+            field: field.to_string(),
+            message: message.into(),
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Ok when nothing was reported, otherwise the collected errors
+    pub fn into_result<T>(self, value: T) -> Result<T, ValidationErrors> {
+        if self.is_empty() {
+            Ok(value)
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl IntoResponse for ValidationErrors {
+    fn into_response(self) -> Response {
+        (StatusCode::UNPROCESSABLE_ENTITY, Json(self)).into_response()
+    }
+}
+
+/// Parses a request body that must be a JSON object
+pub fn parse_object(body: &[u8]) -> Result<Map<String, Value>, ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    match serde_json::from_slice::<Value>(body) {
+        Ok(Value::Object(object)) => Ok(object),
+        Ok(_) => {
+            errors.add("body", "expected a JSON object");
+            Err(errors)
+        }
+        Err(e) => {
+            errors.add("body", format!("invalid JSON: {}", e));
+            Err(errors)
+        }
+    }
+}
+
+/// Reads logo parameters from a JSON object, reporting every invalid field
+///
+/// Unlike the lenient query string handling, values outside the supported
+/// ranges are rejected rather than clamped. Unknown fields are ignored.
+pub fn logo_params(object: &Map<String, Value>) -> Result<LogoParams, ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    let mut params = LogoParams::default();
+
+    if let Some(value) = present(object, "theme") {
+        match value.as_str().map(|theme| (theme, theme.parse::<Theme>())) {
+            Some((theme, Ok(_))) => params.theme = Some(theme.to_string()),
+            Some((_, Err(message))) => errors.add("theme", message),
+            None => errors.add("theme", "must be a string"),
+        }
+    }
+
+    params.grid_size = byte(object, "grid_size", &mut errors);
+    params.shapes = byte(object, "shapes", &mut errors);
+    if let Some(value) = present(object, "opacity") {
+        match value.as_f64() {
+            Some(opacity) => params.opacity = Some(opacity as f32),
+            None => errors.add("opacity", "must be a number"),
+        }
+    }
+
+    // Range checks are shared with the CLI and library
+    let limits = Params::new(
+        params.grid_size.unwrap_or(*GRID_SIZE_RANGE.start()),
+        params.shapes.unwrap_or(*SHAPES_RANGE.start()),
+        params.opacity.unwrap_or(*OPACITY_RANGE.end()),
+    );
+    for (field, message) in limits.problems() {
+        errors.add(field, message);
+    }
+
+    params.width = integer(object, "width", MIN_PNG_SIZE as u64, MAX_PNG_SIZE as u64, &mut errors)
+        .map(|v| v as u32);
+    params.height = integer(object, "height", MIN_PNG_SIZE as u64, MAX_PNG_SIZE as u64, &mut errors)
+        .map(|v| v as u32);
+
+    params.overlap = boolean(object, "overlap", &mut errors);
+    params.transparent = boolean(object, "transparent", &mut errors);
+
+    params.seed = seed(object, &mut errors);
+
+    if let Some(value) = present(object, "preset") {
+        match value.as_str() {
+            Some(preset) => params.preset = Some(preset.to_string()),
+            None => errors.add("preset", "must be a string"),
+        }
+    }
+
+    // A version number, or a name such as "latest"
+    if let Some(value) = present(object, "algo_version") {
+        let name = match value {
+            Value::Number(number) => Some(number.to_string()),
+            Value::String(name) => Some(name.clone()),
+            _ => None,
+        };
+        match name.map(|name| name.parse::<AlgoVersion>()) {
+            Some(Ok(version)) => params.algo_version = Some(version.number()),
+            Some(Err(message)) => errors.add("algo_version", message),
+            None => errors.add("algo_version", "must be a version number or \"latest\""),
+        }
+    }
+
+    errors.into_result(params)
+}
+
+/// Which shape a recolor request paints
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShapeTarget {
+    /// A shape by its index in the design
+    Index(usize),
+    /// A shape by its stable id
+    Id(usize),
+    /// Whichever shape is drawn at this point, in design coordinates
+    Point(f64, f64),
+}
+
+/// A request to paint one shape of a design a new color
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recolor {
+    pub target: ShapeTarget,
+    /// The new color, normalized to hex
+    pub color: String,
+}
+
+/// Reads a recolor request, which names the shape by `shape_index`, `shape_id` or `point: [x, y]`
+pub fn recolor(object: &Map<String, Value>) -> Result<Recolor, ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+
+    let named = [
+        ("shape_index", ShapeTarget::Index as fn(usize) -> ShapeTarget),
+        ("shape_id", ShapeTarget::Id),
+    ]
+    .into_iter()
+    .find_map(|(field, target)| present(object, field).map(|value| (field, value, target)));
+    let target = match (named, present(object, "point")) {
+        (Some((field, value, target)), _) => match value.as_u64() {
+            Some(n) => Some(target(n as usize)),
+            None => {
+                errors.add(field, "must be a non-negative integer");
+                None
+            }
+        },
+        (None, Some(point)) => {
+            let coordinates = point
+                .as_array()
+                .filter(|values| values.len() == 2)
+                .and_then(|values| values.iter().map(Value::as_f64).collect::<Option<Vec<f64>>>());
+            match coordinates {
+                Some(xy) => Some(ShapeTarget::Point(xy[0], xy[1])),
+                None => {
+                    errors.add("point", "must be a pair of numbers [x, y]");
+                    None
+                }
+            }
+        }
+        (None, None) => {
+            errors.add("shape_index", "a shape_index, shape_id or point is required");
+            None
+        }
+    };
+
+    let color = match present(object, "color").map(|value| value.as_str().map(parse_color)) {
+        Some(Some(Ok(color))) => Some(color),
+        Some(Some(Err(e))) => {
+            errors.add("color", e.to_string());
+            None
+        }
+        Some(None) => {
+            errors.add("color", "must be a string");
+            None
+        }
+        None => {
+            errors.add("color", "is required");
+            None
+        }
+    };
+
+    match (target, color) {
+        (Some(target), Some(color)) => errors.into_result(Recolor { target, color }),
+        _ => Err(errors),
+    }
+}
+
+/// A request to regrow every shape of a design except the locked ones
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Reroll {
+    /// Stable ids of the shapes to keep
+    pub locked: Vec<usize>,
+    /// Seed for the new shapes; the server picks one when absent
+    pub seed: Option<u64>,
+}
+
+/// Reads a reroll request: an optional `locked` list of shape ids and `seed`
+pub fn reroll(object: &Map<String, Value>) -> Result<Reroll, ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+
+    let locked = match present(object, "locked") {
+        None => Vec::new(),
+        Some(value) => value
+            .as_array()
+            .and_then(|values| values.iter().map(|v| v.as_u64().map(|n| n as usize)).collect())
+            .unwrap_or_else(|| {
+                errors.add("locked", "must be a list of non-negative integers");
+                Vec::new()
+            }),
+    };
+    let seed = seed(object, &mut errors);
+
+    errors.into_result(Reroll { locked, seed })
+}
+
+/// Designs on a wallpaper when a request doesn't say, as for the CLI
+const DEFAULT_WALLPAPER_COUNT: usize = 7;
+
+/// The canvas options of a wallpaper job; the design comes from the usual parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wallpaper {
+    pub resolution: (u32, u32),
+    pub count: usize,
+    /// SVG output instead of PNG
+    pub svg: bool,
+    /// Background color, normalized to hex; a gradient from the theme when absent
+    pub background: Option<String>,
+}
+
+/// Reads a wallpaper request's `resolution`, `count`, `format` and `background`
+pub fn wallpaper(object: &Map<String, Value>) -> Result<Wallpaper, ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+
+    let resolution = match present(object, "resolution").map(|value| value.as_str().map(wallpaper::parse_resolution)) {
+        None => wallpaper::DEFAULT_RESOLUTION,
+        Some(Some(Ok(resolution))) => resolution,
+        Some(Some(Err(e))) => {
+            errors.add("resolution", e.to_string());
+            wallpaper::DEFAULT_RESOLUTION
+        }
+        Some(None) => {
+            errors.add("resolution", "must be a string like \"3840x2160\"");
+            wallpaper::DEFAULT_RESOLUTION
+        }
+    };
+    let count = integer(object, "count", 1, wallpaper::MAX_DESIGNS as u64, &mut errors)
+        .map_or(DEFAULT_WALLPAPER_COUNT, |count| count as usize);
+    let svg = match present(object, "format").map(Value::as_str) {
+        None | Some(Some("png")) => false,
+        Some(Some("svg")) => true,
+        Some(_) => {
+            errors.add("format", "must be \"png\" or \"svg\"");
+            false
+        }
+    };
+    let background = match present(object, "background").map(|value| value.as_str().map(parse_color)) {
+        None => None,
+        Some(Some(Ok(color))) => Some(color),
+        Some(Some(Err(e))) => {
+            errors.add("background", e.to_string());
+            None
+        }
+        Some(None) => {
+            errors.add("background", "must be a string");
+            None
+        }
+    };
+
+    errors.into_result(Wallpaper {
+        resolution,
+        count,
+        svg,
+        background,
+    })
+}
+
+/// The web form sends the seed as a string; an empty one means "pick for me"
+fn seed(object: &Map<String, Value>, errors: &mut ValidationErrors) -> Option<u64> {
+    let parsed = match present(object, "seed")? {
+        Value::String(s) if s.trim().is_empty() => return None,
+        Value::String(s) => s.trim().parse::<u64>().ok(),
+        Value::Number(n) => n.as_u64(),
+        _ => None,
+    };
+    if parsed.is_none() {
+        errors.add("seed", "must be a non-negative integer");
+    }
+    parsed
+}
+
+/// The value of a field unless it is absent or null
+fn present<'a>(object: &'a Map<String, Value>, field: &str) -> Option<&'a Value> {
+    object.get(field).filter(|value| !value.is_null())
+}
+
+/// A small non-negative integer field; its range is checked by [`Params`]
+fn byte(object: &Map<String, Value>, field: &str, errors: &mut ValidationErrors) -> Option<u8> {
+    let value = present(object, field)?;
+    match value.as_u64() {
+        Some(n) => Some(n.min(u8::MAX as u64) as u8),
+        None => {
+            errors.add(field, "must be a non-negative integer");
+            None
+        }
+    }
+}
+
+fn integer(
+    object: &Map<String, Value>,
+    field: &str,
+    min: u64,
+    max: u64,
+    errors: &mut ValidationErrors,
+) -> Option<u64> {
+    let value = present(object, field)?;
+    match value.as_u64() {
+        Some(n) if (min..=max).contains(&n) => Some(n),
+        _ => {
+            errors.add(field, format!("must be an integer from {} to {}", min, max));
+            None
+        }
+    }
+}
+
+fn boolean(object: &Map<String, Value>, field: &str, errors: &mut ValidationErrors) -> Option<bool> {
+    let value = present(object, field)?;
+    match value.as_bool() {
+        Some(flag) => Some(flag),
+        None => {
+            errors.add(field, "must be true or false");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(body: &str) -> Result<LogoParams, ValidationErrors> {
+        logo_params(&parse_object(body.as_bytes())?)
+    }
+
+    #[test]
+    fn test_valid_params() {
+        let params = parse(r#"{"theme": "Blues", "grid_size": 6, "opacity": 0.5, "seed": "42"}"#)
+            .unwrap();
+        assert_eq!(params.theme.as_deref(), Some("Blues"));
+        assert_eq!(params.grid_size, Some(6));
+        assert_eq!(params.seed, Some(42));
+
+        let params = parse(r#"{"seed": "", "overlap": true, "extra": 1}"#).unwrap();
+        assert_eq!(params.seed, None);
+        assert_eq!(params.overlap, Some(true));
+
+        assert_eq!(parse(r#"{"algo_version": 1}"#).unwrap().algo_version, Some(1));
+        let latest = AlgoVersion::LATEST.number();
+        assert_eq!(parse(r#"{"algo_version": "latest"}"#).unwrap().algo_version, Some(latest));
+    }
+
+    #[test]
+    fn test_every_invalid_field_is_reported() {
+        let errors =
+            parse(r#"{"grid_size": 12, "opacity": -0.5, "theme": "plaid", "seed": "abc", "algo_version": 9}"#)
+                .unwrap_err();
+
+        let fields: Vec<&str> = errors.errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, ["theme", "grid_size", "opacity", "seed", "algo_version"]);
+        assert_eq!(parse(r#"{"algo_version": true}"#).unwrap_err().errors[0].field, "algo_version");
+
+        assert_eq!(parse("[1, 2]").unwrap_err().errors[0].field, "body");
+        assert_eq!(parse("{").unwrap_err().errors[0].field, "body");
+    }
+
+    #[test]
+    fn test_recolor() {
+        let parse = |body: &str| recolor(&parse_object(body.as_bytes())?);
+
+        let request = parse(r#"{"shape_index": 2, "color": "teal"}"#).unwrap();
+        assert_eq!(request.target, ShapeTarget::Index(2));
+        assert_eq!(request.color, "#008080");
+
+        let request = parse(r##"{"point": [-12.5, 40], "color": "#f00"}"##).unwrap();
+        assert_eq!(request.target, ShapeTarget::Point(-12.5, 40.0));
+        let request = parse(r#"{"shape_id": 4, "color": "red"}"#).unwrap();
+        assert_eq!(request.target, ShapeTarget::Id(4));
+
+        let fields = |body: &str| -> Vec<String> {
+            parse(body).unwrap_err().errors.into_iter().map(|e| e.field).collect()
+        };
+        assert_eq!(fields(r#"{}"#), ["shape_index", "color"]);
+        assert_eq!(fields(r#"{"point": [1], "color": "plaid"}"#), ["point", "color"]);
+        assert_eq!(fields(r#"{"shape_index": -1, "color": 5}"#), ["shape_index", "color"]);
+        assert_eq!(fields(r#"{"shape_id": "a", "color": "red"}"#), ["shape_id"]);
+    }
+
+    #[test]
+    fn test_reroll() {
+        let parse = |body: &str| reroll(&parse_object(body.as_bytes())?);
+
+        assert_eq!(parse("{}").unwrap(), Reroll::default());
+        let request = parse(r#"{"locked": [0, 2], "seed": "7"}"#).unwrap();
+        assert_eq!(request.locked, [0, 2]);
+        assert_eq!(request.seed, Some(7));
+
+        let errors = parse(r#"{"locked": [0, "a"], "seed": -3}"#).unwrap_err();
+        let fields: Vec<&str> = errors.errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, ["locked", "seed"]);
+    }
+
+    #[test]
+    fn test_wallpaper() {
+        let object = parse_object(br##"{"resolution": "1920x1080", "count": 3, "format": "svg", "background": "#112233"}"##).unwrap();
+        let request = wallpaper(&object).unwrap();
+        assert_eq!(request.resolution, (1920, 1080));
+        assert_eq!(request.count, 3);
+        assert!(request.svg);
+        assert_eq!(request.background.as_deref(), Some("#112233"));
+
+        let defaults = wallpaper(&Map::new()).unwrap();
+        assert_eq!(defaults.resolution, crate::wallpaper::DEFAULT_RESOLUTION);
+        assert!(!defaults.svg);
+
+        let object = parse_object(br#"{"resolution": "huge", "count": 0, "format": "gif", "background": 3}"#).unwrap();
+        let fields: Vec<String> = wallpaper(&object).unwrap_err().errors.into_iter().map(|e| e.field).collect();
+        assert_eq!(fields, vec!["resolution", "count", "format", "background"]);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::generator::GeneratedDesign;
+use crate::Result;
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// How long a webhook endpoint gets to answer before the notification fails
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// One connection pool shared by every notification in the process
+static CLIENT: Lazy<reqwest::blocking::Client> = Lazy::new(|| {
+    reqwest::blocking::Client::builder()
+        .timeout(TIMEOUT)
+        .build()
+        .expect("default HTTP client settings are valid")
+});
+
+/// The JSON body sent when a design has been generated
+///
+/// `params` holds whatever parameters reproduce the design, `outputs` the
+/// paths or URLs written, and `elapsed` the time spent generating and saving.
+pub fn payload(design: &GeneratedDesign, params: Value, outputs: &[String], elapsed: Duration) -> Value {
+    let shapes: Vec<_> = design.shapes().iter().filter(|shape| !shape.cells.is_empty()).collect();
+    let cells: usize = shapes.iter().map(|shape| shape.cell_count()).sum();
+    let total = design.grid().cell_count().max(1);
+    let shape_metrics: Vec<Value> = design
+        .shapes()
+        .iter()
+        .zip(design.shape_metrics())
+        .filter(|(shape, _)| !shape.cells.is_empty())
+        .map(|(shape, metrics)| {
+            json!({
+                "id": shape.id,
+                "area": metrics.area,
+                "perimeter": metrics.perimeter,
+                "convexity": metrics.convexity,
+                "aspect_ratio": metrics.aspect_ratio,
+                "score": metrics.total_score(),
+            })
+        })
+        .collect();
+
+    json!({
+        "event": "design.generated",
+        "seed": design.config().seed(),
+        "params": params,
+        "outputs": outputs,
+        "hash": format!("{:016x}", design.checksum()),
+        "metrics": {
+            "shapes": shapes.len(),
+            "cells": cells,
+            "coverage": cells as f64 / total as f64,
+            "overlap_ratio": design.overlap_ratio(),
+            "elapsed_ms": elapsed.as_millis() as u64,
+            "shape_metrics": shape_metrics,
+        },
+    })
+}
+
+/// POSTs `payload` as JSON to `url`, failing unless the endpoint answers with 2xx
+pub fn notify(url: &str, payload: &Value) -> Result<()> {
+    let response = CLIENT.post(url).json(payload).send()?;
+    if !response.status().is_success() {
+        return Err(format!("Webhook {} answered {}", url, response.status()).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::GeneratorConfig;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Accepts one request, answers with `status` and returns the request body
+    fn serve_once(status: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            write!(reader.get_mut(), "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status).unwrap();
+            String::from_utf8(body).unwrap()
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_payload() {
+        let design = GeneratorConfig::new(3, 2, 0.8, Some(5)).generate().unwrap();
+        let payload = payload(&design, json!({"theme": "mesos"}), &["logo.svg".to_string()], Duration::from_millis(12));
+
+        assert_eq!(payload["seed"], 5);
+        assert_eq!(payload["params"]["theme"], "mesos");
+        assert_eq!(payload["outputs"][0], "logo.svg");
+        assert_eq!(payload["hash"], format!("{:016x}", design.checksum()));
+        assert_eq!(payload["metrics"]["elapsed_ms"], 12);
+        assert!(payload["metrics"]["coverage"].as_f64().unwrap() > 0.0);
+        let shape = &payload["metrics"]["shape_metrics"][0];
+        assert!(shape["area"].as_f64().unwrap() > 0.0);
+        assert!(shape["aspect_ratio"].as_f64().unwrap() >= 1.0);
+    }
+
+    #[test]
+    fn test_notify() {
+        let (url, server) = serve_once("204 No Content");
+        notify(&url, &json!({"event": "design.generated"})).unwrap();
+        let body: Value = serde_json::from_str(&server.join().unwrap()).unwrap();
+        assert_eq!(body["event"], "design.generated");
+
+        let (url, server) = serve_once("500 Internal Server Error");
+        let error = notify(&url, &json!({})).unwrap_err();
+        assert!(error.to_string().contains("500"));
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,344 @@
+use crate::generator::color::ColorManager;
+use crate::generator::GeneratedDesign;
+use crate::svg::{self, SvgOptions};
+use crate::Result;
+use ::svg::node::element::path::Data;
+use ::svg::node::element::{Path as SvgPath, Rectangle};
+use ::svg::Document;
+use std::fs;
+use std::path::Path;
+use ttf_parser::{Face, GlyphId, OutlineBuilder};
+
+/// Half the width of the hexagon in design units
+const LOGO_HALF_WIDTH: f64 = 100.0;
+
+/// Half the height of the flat-topped hexagon in design units
+const LOGO_HALF_HEIGHT: f64 = 86.602_540_378;
+
+/// Empty space around the whole lockup, in design units
+const MARGIN: f64 = 16.0;
+
+/// How the wordmark is placed relative to the logo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lockup {
+    /// Text to the right of the logo, capitals centered on the logo's middle
+    #[default]
+    Right,
+    /// Text centered under the logo
+    Below,
+}
+
+impl Lockup {
+    /// Names accepted by [`Lockup::from_str`](std::str::FromStr)
+    pub fn names() -> Vec<&'static str> {
+        vec!["right", "below"]
+    }
+
+    /// Capital height as a fraction of the logo's height
+    fn cap_ratio(&self) -> f64 {
+        match self {
+            Lockup::Right => 0.42,
+            Lockup::Below => 0.22,
+        }
+    }
+}
+
+impl std::fmt::Display for Lockup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Lockup::Right => write!(f, "right"),
+            Lockup::Below => write!(f, "below"),
+        }
+    }
+}
+
+impl std::str::FromStr for Lockup {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "right" => Ok(Lockup::Right),
+            "below" => Ok(Lockup::Below),
+            _ => Err(format!(
+                "Unknown lockup '{}' (expected one of: {})",
+                s,
+                Self::names().join(", ")
+            )),
+        }
+    }
+}
+
+/// A TrueType or OpenType font whose glyphs are drawn as outlines
+#[derive(Debug, Clone)]
+pub struct Font {
+    data: Vec<u8>,
+}
+
+impl Font {
+    /// Loads a font file, checking that it can be parsed
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let data = fs::read(path)
+            .map_err(|e| format!("Cannot read font {}: {}", path.display(), e))?;
+        Self::from_bytes(data)
+    }
+
+    /// Wraps font data, checking that it can be parsed
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self> {
+        Face::parse(&data, 0).map_err(|e| format!("Invalid font: {}", e))?;
+        Ok(Self { data })
+    }
+
+    fn face(&self) -> Face<'_> {
+        // Parsing was checked when the font was created
+        Face::parse(&self.data, 0).expect("font data was validated")
+    }
+}
+
+/// A finished logo and wordmark composition
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wordmark {
+    pub svg: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Text laid out as outlines, in design units
+struct SetText {
+    data: Data,
+    width: f64,
+    /// Lowest point below the baseline, as a positive distance
+    descent: f64,
+}
+
+/// Lays out `text` so its capitals are `cap_height` design units tall
+///
+/// Glyphs are placed by their advances plus any pair kerning from the font's
+/// `kern` table. Characters the font lacks are drawn with its `.notdef` glyph.
+fn set_text(font: &Font, text: &str, cap_height: f64, left: f64, baseline: f64) -> SetText {
+    let face = font.face();
+    let units_cap = face
+        .capital_height()
+        .filter(|&height| height > 0)
+        .map(f64::from)
+        .unwrap_or(face.ascender() as f64 * 0.7);
+    let scale = cap_height / units_cap;
+
+    let kerning = |left: GlyphId, right: GlyphId| -> f64 {
+        face.tables()
+            .kern
+            .and_then(|kern| {
+                kern.subtables
+                    .into_iter()
+                    .filter(|subtable| subtable.horizontal && !subtable.variable)
+                    .find_map(|subtable| subtable.glyphs_kerning(left, right))
+            })
+            .map(f64::from)
+            .unwrap_or(0.0)
+    };
+
+    let mut builder = GlyphPath {
+        data: Data::new(),
+        scale,
+        x: left,
+        y: baseline,
+    };
+    let mut pen = 0.0;
+    let mut previous: Option<GlyphId> = None;
+    for character in text.chars() {
+        let glyph = face.glyph_index(character).unwrap_or(GlyphId(0));
+        if let Some(previous) = previous {
+            pen += kerning(previous, glyph);
+        }
+        builder.x = left + pen * scale;
+        face.outline_glyph(glyph, &mut builder);
+        pen += face.glyph_hor_advance(glyph).unwrap_or(0) as f64;
+        previous = Some(glyph);
+    }
+
+    SetText {
+        data: builder.data,
+        width: pen * scale,
+        descent: -(face.descender() as f64) * scale,
+    }
+}
+
+/// Collects glyph outlines into one SVG path, flipping font units to SVG's downward y
+struct GlyphPath {
+    data: Data,
+    scale: f64,
+    x: f64,
+    y: f64,
+}
+
+impl GlyphPath {
+    fn point(&self, x: f32, y: f32) -> (f64, f64) {
+        let round = |v: f64| (v * 100.0).round() / 100.0;
+        (
+            round(self.x + x as f64 * self.scale),
+            round(self.y - y as f64 * self.scale),
+        )
+    }
+}
+
+impl OutlineBuilder for GlyphPath {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let point = self.point(x, y);
+        self.data = std::mem::take(&mut self.data).move_to(point);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let point = self.point(x, y);
+        self.data = std::mem::take(&mut self.data).line_to(point);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (c, p) = (self.point(x1, y1), self.point(x, y));
+        self.data = std::mem::take(&mut self.data).quadratic_curve_to((c.0, c.1, p.0, p.1));
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (c1, c2, p) = (self.point(x1, y1), self.point(x2, y2), self.point(x, y));
+        self.data = std::mem::take(&mut self.data)
+            .cubic_curve_to((c1.0, c1.1, c2.0, c2.1, p.0, p.1));
+    }
+
+    fn close(&mut self) {
+        self.data = std::mem::take(&mut self.data).close();
+    }
+}
+
+/// The shape color with the most contrast against white, darkened if needed so
+/// the text stays legible
+fn text_color(design: &GeneratedDesign) -> String {
+    let color = design
+        .shapes()
+        .iter()
+        .map(|shape| shape.fill())
+        .max_by(|a, b| {
+            ColorManager::color_contrast(a, "#FFFFFF")
+                .total_cmp(&ColorManager::color_contrast(b, "#FFFFFF"))
+        })
+        .unwrap_or("#222222")
+        .to_string();
+    ColorManager::ensure_contrast(&color, "#FFFFFF", 4.5)
+}
+
+/// Combines a design with `text` set in `font`, `width` pixels wide
+///
+/// The text is scaled so its capital height is a fixed fraction of the logo
+/// and spaced from it in proportion, so any font lines up the same way. The
+/// height follows from the lockup's proportions.
+pub fn compose(
+    design: &GeneratedDesign,
+    font: &Font,
+    text: &str,
+    lockup: Lockup,
+    width: u32,
+    options: &SvgOptions,
+) -> Result<Wordmark> {
+    if text.trim().is_empty() {
+        return Err("The wordmark text must not be empty".into());
+    }
+
+    let cap_height = LOGO_HALF_HEIGHT * 2.0 * lockup.cap_ratio();
+    let (set, min_x, min_y, max_x, max_y) = match lockup {
+        Lockup::Right => {
+            // Center the capitals, not the whole line, on the logo's middle
+            let left = LOGO_HALF_WIDTH + cap_height * 0.6;
+            let set = set_text(font, text, cap_height, left, cap_height / 2.0);
+            let bottom = (cap_height / 2.0 + set.descent).max(LOGO_HALF_HEIGHT);
+            let right = left + set.width;
+            (set, -LOGO_HALF_WIDTH, -LOGO_HALF_HEIGHT, right, bottom)
+        }
+        Lockup::Below => {
+            let baseline = LOGO_HALF_HEIGHT + cap_height * 0.8 + cap_height;
+            let probe = set_text(font, text, cap_height, 0.0, baseline);
+            let set = set_text(font, text, cap_height, -probe.width / 2.0, baseline);
+            let half = (set.width / 2.0).max(LOGO_HALF_WIDTH);
+            let bottom = baseline + set.descent;
+            (set, -half, -LOGO_HALF_HEIGHT, half, bottom)
+        }
+    };
+
+    let (x, y) = (min_x - MARGIN, min_y - MARGIN);
+    let (view_width, view_height) = (max_x - min_x + 2.0 * MARGIN, max_y - min_y + 2.0 * MARGIN);
+    let height = ((width as f64 * view_height / view_width).round() as u32).max(1);
+
+    let mut document = Document::new()
+        .set("viewBox", (x, y, view_width, view_height))
+        .set("width", width)
+        .set("height", height)
+        .set(svg::CHECKSUM_ATTRIBUTE, format!("{:016x}", design.checksum()));
+
+    if let Some(background) = &options.background {
+        let (fill, alpha) = svg::split_alpha(background);
+        let mut rect = Rectangle::new()
+            .set("x", x)
+            .set("y", y)
+            .set("width", view_width)
+            .set("height", view_height)
+            .set("fill", fill);
+        if alpha < 1.0 {
+            rect = rect.set("fill-opacity", alpha);
+        }
+        document = document.add(rect);
+    }
+
+    document = document.add(svg::design_group(design)).add(
+        SvgPath::new()
+            .set("class", "wordmark")
+            .set("d", set.data)
+            .set("fill", text_color(design)),
+    );
+
+    Ok(Wordmark {
+        svg: document.to_string(),
+        width,
+        height,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::GeneratorConfig;
+
+    #[test]
+    fn test_lockup_names() {
+        assert_eq!("Below".parse::<Lockup>().unwrap(), Lockup::Below);
+        assert_eq!(Lockup::Right.to_string(), "right");
+        assert!("above".parse::<Lockup>().is_err());
+    }
+
+    fn tuffy() -> Font {
+        Font::from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/Tuffy.ttf")).unwrap()
+    }
+
+    #[test]
+    fn test_compose_lockups() {
+        let design = GeneratorConfig::new(4, 3, 0.8, Some(6)).generate().unwrap();
+        let font = tuffy();
+        let options = SvgOptions::default();
+
+        let right = compose(&design, &font, "ACME", Lockup::Right, 800, &options).unwrap();
+        assert_eq!(right.width, 800);
+        assert!(right.height < right.width);
+        assert!(right.svg.contains("class=\"wordmark\""));
+
+        // A longer name needs a wider canvas relative to its height
+        let longer = compose(&design, &font, "ACME TOOLS", Lockup::Right, 800, &options).unwrap();
+        assert!(longer.height < right.height);
+
+        let below = compose(&design, &font, "ACME", Lockup::Below, 800, &options).unwrap();
+        assert!(below.height > right.height);
+
+        assert!(compose(&design, &font, " ", Lockup::Right, 800, &options).is_err());
+    }
+
+    #[test]
+    fn test_invalid_font() {
+        let error = Font::from_bytes(b"not a font".to_vec()).unwrap_err();
+        assert!(error.to_string().starts_with("Invalid font"));
+    }
+}
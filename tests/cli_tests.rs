@@ -52,6 +52,66 @@ fn test_png_output() {
     assert_eq!(&content[0..8], &[137, 80, 78, 71, 13, 10, 26, 10]);
 }
 
+#[test]
+fn test_icns_output() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("logo.icns");
+    
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.arg("--format")
+       .arg("icns")
+       .arg(output_path.to_str().unwrap());
+    cmd.assert().success();
+    
+    // Check the icns header magic
+    let content = fs::read(&output_path).unwrap();
+    assert_eq!(&content[0..4], b"icns");
+}
+
+#[test]
+fn test_pwa_icons_output() {
+    let temp_dir = tempdir().unwrap();
+    let icons_dir = temp_dir.path().join("icons");
+    
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.arg("--pwa-icons")
+       .arg(icons_dir.to_str().unwrap());
+    cmd.assert().success();
+    
+    // Regular and maskable icons plus the manifest snippet
+    assert!(icons_dir.join("icon-48x48.png").exists());
+    assert!(icons_dir.join("icon-maskable-512x512.png").exists());
+    let manifest = fs::read_to_string(icons_dir.join("manifest.json")).unwrap();
+    assert!(manifest.contains("\"purpose\": \"maskable\""));
+}
+
+#[test]
+fn test_split_layers_output() {
+    let temp_dir = tempdir().unwrap();
+    let layers_dir = temp_dir.path().join("layers");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--seed", "42", "--shapes", "3", "--split-layers"])
+       .arg(layers_dir.to_str().unwrap());
+    cmd.assert().success();
+
+    // Every layer shares the composite's viewBox and holds a single shape
+    let composite = fs::read_to_string(layers_dir.join("composite.svg")).unwrap();
+    let view_box = &composite[composite.find("viewBox").unwrap()..][..30];
+    let mut layers = 0;
+    for entry in fs::read_dir(&layers_dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.file_name().unwrap().to_str().unwrap().starts_with("shape-") {
+            let layer = fs::read_to_string(&path).unwrap();
+            assert!(layer.contains(view_box));
+            assert_eq!(layer.matches("<path").count(), 1);
+            layers += 1;
+        }
+    }
+    assert!(layers >= 3);
+    assert!(layers_dir.join("shape-01.svg").exists());
+}
+
 #[test]
 fn test_deterministic_output() {
     let temp_dir = tempdir().unwrap();
@@ -113,23 +173,32 @@ fn test_different_shapes_count() {
 
 #[test]
 fn test_invalid_parameters() {
+    // Without an output path each run writes logo.svg to the working directory
+    let temp_dir = tempdir().unwrap();
+
     // Test invalid grid size (too small)
     let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.current_dir(temp_dir.path());
     cmd.arg("--grid-size").arg("1");
     cmd.assert().success(); // Should clamp to 3, not fail
     
     // Test invalid grid size (too large)
     let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.current_dir(temp_dir.path());
     cmd.arg("--grid-size").arg("10");
-    cmd.assert().success(); // Should clamp to 8, not fail
+    cmd.assert()
+        .success() // Should clamp to 8, not fail
+        .stderr(predicate::str::contains("grid_size must be from 2 to 8"));
     
     // Test invalid opacity (negative)
     let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.current_dir(temp_dir.path());
     cmd.arg("--opacity").arg("0.0"); // Changed from -0.5 to 0.0 as negative values are not handled correctly
     cmd.assert().success(); // Should accept 0.0
     
     // Test invalid opacity (too large)
     let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.current_dir(temp_dir.path());
     cmd.arg("--opacity").arg("2.0");
     cmd.assert().success(); // Should clamp to 1.0, not fail
 }
@@ -234,4 +303,1200 @@ fn test_output_with_uuid() {
     
     // Check that the file was created
     assert!(output_path.exists());
-}
\ No newline at end of file
+}
+#[test]
+fn test_verify_command() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("logo.svg");
+    let output = output_path.to_str().unwrap();
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--seed", "123", "--theme", "blues", output]);
+    cmd.assert().success();
+
+    // The same parameters regenerate an identical file
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["verify", output, "--seed", "123", "--theme", "blues"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("matches the design"));
+
+    // Different parameters are caught by the embedded checksum
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["verify", output, "--seed", "124", "--theme", "blues"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("not generated from these parameters"));
+
+    // A hand edit is caught by the content hash
+    let content = fs::read_to_string(&output_path).unwrap();
+    fs::write(&output_path, content.replacen("fill-opacity=\"0.8\"", "fill-opacity=\"0.7\"", 1)).unwrap();
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["verify", output, "--seed", "123", "--theme", "blues"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("does not match the regenerated design"));
+}
+
+#[test]
+fn test_palette_report_output() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("logo.svg");
+    let json_path = temp_dir.path().join("palette.json");
+    let html_path = temp_dir.path().join("palette.html");
+
+    for report_path in [&json_path, &html_path] {
+        let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+        cmd.args(["--seed", "42", "--palette-report"])
+            .arg(report_path)
+            .arg(&output_path);
+        cmd.assert().success();
+    }
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+    assert!(!report["colors"].as_array().unwrap().is_empty());
+    assert!(report["colors"][0]["nearest_named"].is_string());
+
+    let html = fs::read_to_string(&html_path).unwrap();
+    assert!(html.contains("Contrast matrix"));
+
+    // Unknown report formats are rejected
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--palette-report", "palette.txt"]).arg(&output_path);
+    cmd.assert().failure();
+}
+
+#[test]
+fn test_css_background_color() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("logo.svg");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--background", "hsl(0, 100%, 50%)"]).arg(&output_path);
+    cmd.assert().success();
+    let content = fs::read_to_string(&output_path).unwrap();
+    assert!(content.contains("<rect"));
+    assert!(content.contains("fill=\"#FF0000\""));
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--background", "not-a-color"]).arg(&output_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid color 'not-a-color'"));
+}
+
+#[test]
+fn test_grid_command() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("grid.svg");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["grid", "--grid-size", "5", "-o"]).arg(&output_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("150 cells"));
+
+    let content = fs::read_to_string(&output_path).unwrap();
+    assert_eq!(content.matches("debug-cell-id").count(), 150);
+}
+
+#[test]
+fn test_trace_command() {
+    let temp_dir = tempdir().unwrap();
+    let input_path = temp_dir.path().join("mark.svg");
+    let output_path = temp_dir.path().join("traced.svg");
+    fs::write(
+        &input_path,
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100"><rect width="100" height="100" fill="#ffffff"/><rect x="10" y="20" width="40" height="60" fill="#c62828"/><rect x="50" y="20" width="40" height="60" fill="#1565c0"/></svg>"##,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["trace"]).arg(&input_path).args(["--colors", "4", "-o"]).arg(&output_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("into 2 shapes"));
+    assert_eq!(fs::read_to_string(&output_path).unwrap().matches("data-shape-id").count(), 2);
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["trace", "mark.gif"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("only .svg and .png"));
+}
+
+#[test]
+fn test_registry_commands() {
+    let temp_dir = tempdir().unwrap();
+    let registry = temp_dir.path().join("registry.toml");
+    let out_dir = temp_dir.path().join("logos");
+
+    for (name, seed) in [("payments-service", "991"), ("auth", "7")] {
+        let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+        cmd.args(["registry", "add", "--name", name, "--seed", seed, "--theme", "blues"])
+            .arg(&registry);
+        cmd.assert().success();
+    }
+    let text = fs::read_to_string(&registry).unwrap();
+    assert!(text.contains("[logos.payments-service]"));
+    assert!(text.contains("seed = 991"));
+
+    // Names are unique unless replaced on purpose
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["registry", "add", "--name", "auth", "--seed", "8"]).arg(&registry);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["registry", "render"]).arg(&registry).arg("--out-dir").arg(&out_dir);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Rendered 2 logo(s)"));
+    let first = fs::read_to_string(out_dir.join("payments-service.svg")).unwrap();
+    assert!(out_dir.join("auth.svg").exists());
+
+    // Rendering again reproduces the same files
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["registry", "render"]).arg(&registry).arg("--out-dir").arg(&out_dir);
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string(out_dir.join("payments-service.svg")).unwrap(), first);
+}
+
+#[test]
+fn test_rerender_command() {
+    let temp_dir = tempdir().unwrap();
+    let specs = temp_dir.path().join("specs");
+    fs::create_dir_all(specs.join("brand")).unwrap();
+    fs::write(specs.join("app.json"), r#"{"seed": 3, "theme": "blues", "output": "elsewhere.svg"}"#).unwrap();
+    fs::write(specs.join("notes.md"), "not a design").unwrap();
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--seed", "4", "--print-code"]).arg(temp_dir.path().join("direct.svg"));
+    let code = cmd.assert().success().get_output().stdout.clone();
+    fs::write(specs.join("brand").join("mark.txt"), code).unwrap();
+
+    let out_dir = temp_dir.path().join("renders");
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.arg("rerender").arg(&specs).arg("--out-dir").arg(&out_dir).args(["--format", "png", "--width", "64", "--height", "64"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Rerendered 2 design(s)"));
+    assert!(fs::read(out_dir.join("app.png")).unwrap().starts_with(b"\x89PNG"));
+    assert!(fs::read(out_dir.join("brand").join("mark.png")).unwrap().starts_with(b"\x89PNG"));
+    assert!(!temp_dir.path().join("elsewhere.svg").exists());
+
+    // Without a new format each design keeps its own, and codes render like their flags
+    let svg_dir = temp_dir.path().join("svg");
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.arg("rerender").arg(&specs).arg("--out-dir").arg(&svg_dir);
+    cmd.assert().success();
+    assert_eq!(
+        fs::read_to_string(svg_dir.join("brand").join("mark.svg")).unwrap(),
+        fs::read_to_string(temp_dir.path().join("direct.svg")).unwrap()
+    );
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.arg("rerender").arg(&svg_dir).arg("--out-dir").arg(&out_dir);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Found no design specs or codes"));
+}
+
+#[test]
+fn test_scan_command() {
+    let temp_dir = tempdir().unwrap();
+    let services = temp_dir.path().join("services");
+    for name in ["billing", "search", ".git"] {
+        fs::create_dir_all(services.join(name)).unwrap();
+    }
+    let out_dir = temp_dir.path().join("logos");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.arg("scan").arg(&services).arg("--out-dir").arg(&out_dir);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Rendered 2 service logo(s)"));
+    assert!(!out_dir.join(".git.svg").exists());
+    let billing = fs::read_to_string(out_dir.join("billing.svg")).unwrap();
+    assert_ne!(billing, fs::read_to_string(out_dir.join("search.svg")).unwrap());
+
+    // A compose file naming the same service gives it the same logo
+    let compose = temp_dir.path().join("docker-compose.yml");
+    fs::write(&compose, "services:\n  billing:\n    image: billing\n  auth:\n    build: ./auth\n").unwrap();
+    let registry = temp_dir.path().join("registry.toml");
+    let compose_out = temp_dir.path().join("compose");
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.arg("scan").arg(&compose).arg("--out-dir").arg(&compose_out).arg("--registry").arg(&registry);
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string(compose_out.join("billing.svg")).unwrap(), billing);
+    assert!(compose_out.join("auth.svg").exists());
+    assert!(fs::read_to_string(&registry).unwrap().contains("[logos.auth]"));
+}
+
+#[test]
+fn test_guide_option() {
+    let temp_dir = tempdir().unwrap();
+    let guide_path = temp_dir.path().join("heart.svg");
+    let output_path = temp_dir.path().join("logo.svg");
+    fs::write(
+        &guide_path,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100"><path d="M50 85 L15 45 A18 18 0 0 1 50 25 A18 18 0 0 1 85 45 Z"/></svg>"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--seed", "5", "--guide"]).arg(&guide_path).arg(&output_path);
+    cmd.assert().success();
+    assert!(fs::read_to_string(&output_path).unwrap().contains("<path"));
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--guide", "missing.png"]).arg(&output_path);
+    cmd.assert().failure();
+}
+
+#[test]
+fn test_style_option() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("logo.svg");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--style", "ribbon", "--seed", "3"]).arg(&output_path);
+    cmd.assert().success();
+    assert!(fs::read_to_string(&output_path).unwrap().contains("<path"));
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--style", "zigzag"]).arg(&output_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown shape style 'zigzag'"));
+}
+
+#[test]
+fn test_anchor_option() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("logo.svg");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--anchor", "corner", "--seed", "3"]).arg(&output_path);
+    cmd.assert().success();
+    assert!(fs::read_to_string(&output_path).unwrap().contains("<path"));
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--anchor", "middle"]).arg(&output_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown anchor 'middle'"));
+}
+
+#[test]
+fn test_aesthetic_option() {
+    let temp_dir = tempdir().unwrap();
+    let compact_path = temp_dir.path().join("compact.svg");
+    let spiky_path = temp_dir.path().join("spiky.svg");
+
+    for (aesthetic, path) in [("compact", &compact_path), ("spiky", &spiky_path)] {
+        let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+        cmd.args(["--aesthetic", aesthetic, "--seed", "3", "--grid-size", "6"]).arg(path);
+        cmd.assert().success();
+    }
+    assert_ne!(fs::read_to_string(&compact_path).unwrap(), fs::read_to_string(&spiky_path).unwrap());
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--aesthetic", "pointy"]).arg(&compact_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown aesthetic 'pointy'"));
+}
+
+#[test]
+fn test_effort_option() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("logo.svg");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--effort", "high", "--verbose", "--seed", "3"]).arg(&output_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Effort: high (8 candidates per shape; picked "));
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--effort", "0"]).arg(&output_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown effort '0'"));
+
+    // The most candidates there can be
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--effort", "32", "--seed", "1", "--grid-size", "8", "--shapes", "6", "--overlap"])
+        .arg(&output_path);
+    cmd.assert().success();
+    assert!(fs::read_to_string(&output_path).unwrap().contains("<path"));
+}
+
+#[test]
+fn test_symmetry_option() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("logo.svg");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--symmetry", "6", "--shapes", "2", "--grid-size", "4", "--seed", "5"]).arg(&output_path);
+    cmd.assert().success();
+    let svg = fs::read_to_string(&output_path).unwrap();
+    assert_eq!(svg.matches("<path").count(), 12);
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--symmetry", "6", "--symmetry-colors", "rotate", "--shapes", "2", "--grid-size", "4", "--seed", "5"])
+        .arg(&output_path);
+    cmd.assert().success();
+    assert_ne!(fs::read_to_string(&output_path).unwrap(), svg);
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--symmetry", "4"]).arg(&output_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown symmetry '4'"));
+}
+
+#[test]
+fn test_algo_version_option() {
+    let temp_dir = tempdir().unwrap();
+    let latest_path = temp_dir.path().join("latest.svg");
+    let pinned_path = temp_dir.path().join("pinned.svg");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--seed", "9"]).arg(&latest_path);
+    cmd.assert().success();
+
+    // Version 1 is the latest, so pinning it changes nothing yet
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--seed", "9", "--algo-version", "1"]).arg(&pinned_path);
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string(&pinned_path).unwrap(), fs::read_to_string(&latest_path).unwrap());
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--algo-version", "7"]).arg(&pinned_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown algorithm version '7'"));
+}
+
+#[test]
+fn test_pack_option() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("logo.svg");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--pack", "--shapes", "6", "--seed", "8"]).arg(&output_path);
+    cmd.assert().success();
+
+    let svg = fs::read_to_string(&output_path).unwrap();
+    assert_eq!(svg.matches("<path").count(), 6);
+}
+
+#[test]
+fn test_mosaic_option() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("logo.svg");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--mosaic", "--grid-size", "6", "--shapes", "3", "--seed", "8"]).arg(&output_path);
+    cmd.assert().success();
+
+    // The shape count gives way to dozens of tiles
+    let svg = fs::read_to_string(&output_path).unwrap();
+    assert!(svg.matches("<path").count() >= 24);
+}
+
+#[test]
+fn test_color_by_size_option() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("logo.svg");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--color-by-size", "--pack", "--theme", "google", "--shapes", "4", "--seed", "8"])
+        .arg(&output_path);
+    cmd.assert().success();
+
+    let svg = fs::read_to_string(&output_path).unwrap();
+    for color in ["#4285F4", "#EA4335", "#FBBC05", "#34A853"] {
+        assert!(svg.contains(color), "missing {}", color);
+    }
+}
+
+#[test]
+fn test_stained_glass_option() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("logo.svg");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--pack", "--stained-glass", "--seed", "8"]).arg(&output_path);
+    cmd.assert().success();
+
+    let svg = fs::read_to_string(&output_path).unwrap();
+    assert!(svg.contains("class=\"leading\""));
+}
+
+#[test]
+fn test_editable_option() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("logo.svg");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--editable", "--theme", "google", "--shapes", "3", "--seed", "8"]).arg(&output_path);
+    cmd.assert().success();
+
+    let svg = fs::read_to_string(&output_path).unwrap();
+    // One title per shape path, overlap parts included
+    assert_eq!(svg.matches("<title>").count(), svg.matches("<path").count());
+    assert!(svg.contains(" cells\n</title>"));
+}
+
+#[test]
+fn test_ascii_format() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("logo.txt");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--format", "ascii", "--grid-size", "5", "--seed", "42"]).arg(&output_path);
+    cmd.assert().success();
+    let text = fs::read_to_string(&output_path).unwrap();
+    assert_eq!(text.lines().count(), 10);
+    assert!(text.contains('▲') && text.contains('▼'));
+    assert!(!text.contains('\x1b'));
+
+    // Piped to another program, standard output gets the same plain text
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--format", "ascii", "--grid-size", "5", "--seed", "42", "-"]);
+    cmd.assert().success().stdout(predicate::str::starts_with(text.lines().next().unwrap()));
+}
+
+#[test]
+fn test_morph_command() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("morph.gif");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["morph", "1", "2", "--frames", "4", "--format", "gif", "-w", "64", "-H", "64"])
+        .arg(&output_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("4-frame morph"));
+    assert_eq!(&fs::read(&output_path).unwrap()[0..6], b"GIF89a");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["morph", "1", "2", "--seed", "3"]).arg(&output_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("two seeds as arguments"));
+}
+
+#[test]
+fn test_overlap_ratio_option() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("logo.svg");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--overlap-ratio", "0.3", "--grid-size", "6", "--seed", "5", "--verbose"])
+        .arg(&output_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("(target 30%)"));
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--overlap-ratio", "1.5"]).arg(&output_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--overlap-ratio must be between 0.0 and 1.0"));
+}
+
+#[test]
+fn test_overlap_opacity_option() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("logo.svg");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--overlap-ratio", "0.3", "--overlap-opacity", "full", "--opacity", "0.6"])
+        .args(["--grid-size", "6", "--seed", "5"])
+        .arg(&output_path);
+    cmd.assert().success();
+
+    // The shapes stay translucent while their overlap is drawn solid
+    let svg = fs::read_to_string(&output_path).unwrap();
+    assert!(svg.contains("fill-opacity=\"0.6\""));
+    assert!(svg.contains("fill-opacity=\"1\""));
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--overlap-opacity", "bright"]).arg(&output_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown overlap opacity 'bright'"));
+}
+
+#[test]
+fn test_preset_option() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("avatar");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--preset", "avatar", "--seed", "3", "--verbose"])
+        .arg(&output_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Preset: avatar"))
+        .stdout(predicate::str::contains("Format: png"));
+    assert!(output_path.with_extension("png").exists());
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.arg("presets");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("wallpaper"))
+        .stdout(predicate::str::contains("--width 2560 --height 1440"));
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--preset", "poster"]).arg(&output_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown preset 'poster'"));
+}
+
+#[test]
+fn test_wallpaper_command() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("wallpaper.png");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["wallpaper", "--resolution", "640x360", "--count", "4", "--seed", "8"])
+        .arg(&output_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Wrote 640x360 wallpaper"));
+    let png = fs::read(&output_path).unwrap();
+    assert_eq!(&png[0..8], &[137, 80, 78, 71, 13, 10, 26, 10]);
+
+    // SVG wallpapers are streamed to the file
+    let svg_path = temp_dir.path().join("wallpaper.svg");
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["wallpaper", "--resolution", "640x360", "--count", "4", "--seed", "8", "--format", "svg"])
+        .arg(&svg_path);
+    cmd.assert().success();
+    let svg = fs::read_to_string(&svg_path).unwrap();
+    assert!(svg.starts_with("<svg height=\"360\""));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert!(svg.matches("class=\"hexalith\"").count() >= 2);
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["wallpaper", "--resolution", "wide"]).arg(&output_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid resolution 'wide'"));
+}
+
+#[test]
+fn test_print_layout() {
+    let temp_dir = tempdir().unwrap();
+    let pdf_path = temp_dir.path().join("proof.pdf");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--seed", "42", "--format", "pdf", "--print-layout", "A3", "--crop-marks", "--swatches"])
+        .arg(&pdf_path);
+    cmd.assert().success();
+    let pdf = fs::read(&pdf_path).unwrap();
+    assert!(pdf.starts_with(b"%PDF-1.4"));
+
+    let svg_path = temp_dir.path().join("proof.svg");
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--seed", "42", "--print-layout", "a4", "--swatches"])
+        .arg(&svg_path);
+    cmd.assert().success();
+    let svg = fs::read_to_string(&svg_path).unwrap();
+    assert!(svg.contains("width=\"210mm\""));
+    assert!(svg.contains("class=\"swatches\""));
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--print-layout", "A4", "--format", "png"]).arg(&svg_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--print-layout supports svg and pdf output"));
+}
+
+#[test]
+fn test_wordmark_lockup() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("acme.svg");
+    let font = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/Tuffy.ttf");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--seed", "42", "--wordmark", "ACME", "--font", font, "--lockup", "below"])
+        .arg(&output_path);
+    cmd.assert().success();
+
+    let lockup = fs::read_to_string(&output_path).unwrap();
+    assert!(lockup.contains("class=\"wordmark\""));
+    let icon = fs::read_to_string(temp_dir.path().join("acme-icon.svg")).unwrap();
+    assert!(!icon.contains("class=\"wordmark\""));
+
+    // A wordmark needs a font to set it in
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--wordmark", "ACME"]).arg(&output_path);
+    cmd.assert().failure().stderr(predicate::str::contains("--font"));
+}
+
+#[test]
+fn test_theme_blend() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("blend.svg");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--theme", "mesos:blues:0.4", "--seed", "9", "--verbose"])
+        .arg(&output_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Theme: mesos:blues:0.4"));
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--theme", "mesos:blues:2"]).arg(&output_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid blend amount '2'"));
+}
+
+#[test]
+fn test_palette_import() {
+    let temp_dir = tempdir().unwrap();
+    let palette_path = temp_dir.path().join("brand.gpl");
+    fs::write(
+        &palette_path,
+        "GIMP Palette\nName: Brand\n255 0 0 Red\n0 0 255 Blue\n0 160 0 Green\n",
+    )
+    .unwrap();
+
+    let output_path = temp_dir.path().join("brand.svg");
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.arg("--palette-file")
+        .arg(&palette_path)
+        .args(["--seed", "3", "--verbose"])
+        .arg(&output_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Theme: Brand"));
+
+    let svg = fs::read_to_string(&output_path).unwrap();
+    assert!(["#FF0000", "#0000FF", "#00A000"].iter().any(|color| svg.contains(color)));
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--palette-url", "https://coolors.co/264653-2a9d8f-e9c46a"])
+        .arg(&output_path);
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--palette-url", "https://example.com/264653"])
+        .arg(&output_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid coolors URL"));
+}
+
+#[test]
+fn test_export_palette() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("brand.svg");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--export-palette", "css", "--seed", "5"])
+        .arg(&output_path);
+    cmd.assert().success();
+    let css = fs::read_to_string(temp_dir.path().join("brand-palette.css")).unwrap();
+    assert!(css.contains("--brand-"));
+
+    // An exported GIMP palette can be fed straight back in
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--export-palette", "gpl", "--seed", "5"])
+        .arg(&output_path);
+    cmd.assert().success();
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.arg("--palette-file")
+        .arg(temp_dir.path().join("brand-palette.gpl"))
+        .arg(temp_dir.path().join("again.svg"));
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--export-palette", "sketch"]).arg(&output_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown palette format 'sketch'"));
+}
+
+#[test]
+fn test_from_spec_stdin() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("spec.png");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--from-spec", "-", "--width", "64", "--verbose"])
+        .arg(&output_path)
+        .write_stdin(r#"{"seed": 11, "theme": "greens", "format": "png", "width": 256, "height": 64}"#);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Theme: greens"))
+        .stdout(predicate::str::contains("Seed: 11"));
+
+    // The --width flag wins over the spec, the spec's height is kept
+    let png = fs::read(&output_path).unwrap();
+    assert_eq!(u32::from_be_bytes([png[16], png[17], png[18], png[19]]), 64);
+    assert_eq!(u32::from_be_bytes([png[20], png[21], png[22], png[23]]), 64);
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--from-spec", "-"])
+        .arg(&output_path)
+        .write_stdin(r#"{"colour": "red"}"#);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown field `colour`"));
+}
+
+#[test]
+fn test_batch_stdin() {
+    let temp_dir = tempdir().unwrap();
+    let specs = concat!(
+        "{\"seed\": 1, \"output\": \"one.svg\"}\n",
+        "\n",
+        "{\"seed\": 2, \"theme\": \"reds\", \"format\": \"png\", \"width\": 128, \"height\": 128}\n",
+    );
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["batch", "--stdin", "--verbose", "--out-dir"])
+        .arg(temp_dir.path())
+        .write_stdin(specs);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Generated 2 design(s)"));
+
+    assert!(fs::read_to_string(temp_dir.path().join("one.svg")).unwrap().contains("<svg"));
+    assert!(temp_dir.path().join("design-3.png").exists());
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["batch", "--stdin", "--out-dir"])
+        .arg(temp_dir.path())
+        .write_stdin("{\"seed\": 1}\n{\"shapes\": \"many\"}\n");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Line 2: Invalid design spec"));
+}
+
+#[test]
+fn test_batch_jobs_are_deterministic() {
+    let specs: String = (0..24)
+        .map(|seed| format!("{{\"seed\": {}, \"output\": \"logo-{}.svg\"}}\n", seed, seed))
+        .collect();
+
+    let mut outputs = Vec::new();
+    for jobs in ["1", "4"] {
+        let temp_dir = tempdir().unwrap();
+        let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+        cmd.args(["batch", "--stdin", "--verbose", "--jobs", jobs, "--out-dir"])
+            .arg(temp_dir.path())
+            .write_stdin(specs.clone());
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains(format!("with {} job(s)", jobs)));
+
+        let files: Vec<String> = (0..24)
+            .map(|seed| fs::read_to_string(temp_dir.path().join(format!("logo-{}.svg", seed))).unwrap())
+            .collect();
+        outputs.push(files);
+    }
+    assert_eq!(outputs[0], outputs[1]);
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["batch", "--stdin", "--jobs", "0"]).write_stdin(specs);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--jobs must be at least 1"));
+}
+
+#[test]
+fn test_batch_dedupe() {
+    let temp_dir = tempdir().unwrap();
+    let specs = concat!(
+        "{\"seed\": 8, \"output\": \"a.svg\"}\n",
+        "{\"seed\": 8, \"output\": \"b.svg\"}\n",
+        "{\"seed\": 9, \"output\": \"c.svg\"}\n",
+    );
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["batch", "--stdin", "--dedupe-threshold", "0.95", "--out-dir"])
+        .arg(temp_dir.path())
+        .write_stdin(specs);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Rejected 1 near-duplicate design(s): 1 skipped, 0 regenerated"));
+    assert!(temp_dir.path().join("a.svg").exists());
+    assert!(!temp_dir.path().join("b.svg").exists());
+    assert!(temp_dir.path().join("c.svg").exists());
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["batch", "--stdin", "--dedupe-threshold", "0.95", "--on-duplicate", "regenerate", "--out-dir"])
+        .arg(temp_dir.path())
+        .write_stdin(specs);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("1 regenerated"));
+    assert_ne!(
+        fs::read_to_string(temp_dir.path().join("a.svg")).unwrap(),
+        fs::read_to_string(temp_dir.path().join("b.svg")).unwrap()
+    );
+}
+
+#[test]
+fn test_print_hash() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path().join("hashed.svg");
+
+    let run = || {
+        let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+        cmd.args(["--seed", "42", "--print-hash"]).arg(&output_path);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        String::from_utf8(output).unwrap()
+    };
+
+    let line = run();
+    let (hash, path) = line.trim().split_once("  ").unwrap();
+    assert_eq!(hash.len(), 16);
+    assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+    assert!(path.ends_with("hashed.svg"));
+    assert_eq!(line, run());
+}
+
+#[test]
+fn test_design_code() {
+    let temp_dir = tempdir().unwrap();
+    let flags_path = temp_dir.path().join("flags.svg");
+    let code_path = temp_dir.path().join("code.svg");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--seed", "42", "--theme", "blues", "--shapes", "3", "--print-code"]).arg(&flags_path);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let code = String::from_utf8(output).unwrap().trim().to_string();
+    assert!(code.starts_with("HX1-"));
+
+    // The code alone gives the same design
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--code", &code]).arg(&code_path);
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string(&code_path).unwrap(), fs::read_to_string(&flags_path).unwrap());
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--code", &code, "--seed", "7"]).arg(&code_path);
+    cmd.assert().failure().stderr(predicate::str::contains("cannot be used with"));
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--code", "HX1-zzzz"]).arg(&code_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid design code 'HX1-zzzz'"));
+}
+
+#[test]
+fn test_out_dir_by_hash() {
+    let temp_dir = tempdir().unwrap();
+    let store = temp_dir.path().join("store");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["batch", "--stdin", "--out-dir-by-hash"])
+        .arg(&store)
+        .write_stdin("{\"seed\": 1}\n{\"seed\": 2, \"format\": \"png\", \"width\": 64, \"height\": 64}\n{\"seed\": 1}\n");
+    cmd.assert().success();
+
+    // The repeated design is stored once
+    let index = fs::read_to_string(store.join("index.ndjson")).unwrap();
+    assert_eq!(index.lines().count(), 2);
+    for line in index.lines() {
+        let entry: serde_json::Value = serde_json::from_str(line).unwrap();
+        let hash = entry["hash"].as_str().unwrap();
+        let path = entry["path"].as_str().unwrap();
+        assert!(path.starts_with(&format!("{}/{}/{}", &hash[0..2], &hash[2..4], hash)));
+        assert!(store.join(path).exists());
+    }
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--seed", "3", "--verbose", "--out-dir-by-hash"])
+        .arg(&store)
+        .arg(temp_dir.path().join("ignored.svg"));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("store"));
+    assert!(!temp_dir.path().join("ignored.svg").exists());
+    assert_eq!(fs::read_to_string(store.join("index.ndjson")).unwrap().lines().count(), 3);
+}
+
+#[test]
+fn test_upload_needs_cloud_feature_and_credentials() {
+    let temp_dir = tempdir().unwrap();
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--seed", "1", "--upload", "s3://logos/test"])
+        .arg(temp_dir.path().join("logo.svg"))
+        .env_remove("AWS_ACCESS_KEY_ID")
+        .env_remove("AWS_SECRET_ACCESS_KEY");
+    cmd.assert().failure().stderr(
+        predicate::str::contains("`cloud` feature").or(predicate::str::contains("AWS_ACCESS_KEY_ID")),
+    );
+}
+
+#[cfg(not(feature = "webhook"))]
+#[test]
+fn test_webhook_needs_feature() {
+    let temp_dir = tempdir().unwrap();
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--seed", "1", "--webhook", "http://127.0.0.1:9/hook"])
+        .arg(temp_dir.path().join("logo.svg"));
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("`webhook` feature"));
+    assert!(!temp_dir.path().join("logo.svg").exists());
+}
+
+#[cfg(feature = "webhook")]
+#[test]
+fn test_webhook_receives_payload() {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line.trim().is_empty() {
+                break;
+            }
+            if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                length = value.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        write!(reader.get_mut(), "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").unwrap();
+        body
+    });
+
+    let temp_dir = tempdir().unwrap();
+    let output = temp_dir.path().join("logo.svg");
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--seed", "9", "--webhook", &url]).arg(&output);
+    cmd.assert().success();
+
+    let payload: serde_json::Value = serde_json::from_slice(&server.join().unwrap()).unwrap();
+    assert_eq!(payload["event"], "design.generated");
+    assert_eq!(payload["seed"], 9);
+    assert_eq!(payload["outputs"][0], output.display().to_string());
+    assert_eq!(payload["params"]["format"], "svg");
+}
+
+#[test]
+fn test_template_framing() {
+    let temp_dir = tempdir().unwrap();
+    let template = temp_dir.path().join("card.svg");
+    fs::write(
+        &template,
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="300" height="120">
+  <rect width="300" height="120" fill="#eee"/>
+  <g transform="translate(10 10)">{{logo}}</g>
+  <text x="120" y="60">{{title}} #{{seed}} {{date}}</text>
+</svg>"##,
+    )
+    .unwrap();
+
+    let output = temp_dir.path().join("card-out.svg");
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--seed", "5", "--width", "100", "--height", "100", "--template"])
+        .arg(&template)
+        .args(["--var", "title=Release <1.0>"])
+        .arg(&output);
+    cmd.assert().success();
+
+    let framed = fs::read_to_string(&output).unwrap();
+    assert!(framed.contains("Release &lt;1.0&gt; #5 "));
+    assert!(framed.contains("<g transform=\"translate(10 10)\"><svg"));
+    assert!(!framed.contains("{{"));
+
+    // PNG output takes the template's size
+    let png = temp_dir.path().join("card-out.png");
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--seed", "5", "--format", "png", "--template"]).arg(&template).arg(&png);
+    cmd.assert().success();
+    let data = fs::read(&png).unwrap();
+    assert_eq!(u32::from_be_bytes(data[16..20].try_into().unwrap()), 300);
+    assert_eq!(u32::from_be_bytes(data[20..24].try_into().unwrap()), 120);
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--template"]).arg(&template).args(["--var", "novalue"]).arg(&output);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("expected KEY=VALUE"));
+}
+
+#[test]
+fn test_badge_subcommand() {
+    let temp_dir = tempdir().unwrap();
+    let output = temp_dir.path().join("build.svg");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["badge", "--label", "build", "--message", "passing", "--theme", "blues"])
+        .arg(&output);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Wrote badge"));
+
+    let badge = fs::read_to_string(&output).unwrap();
+    assert!(badge.contains("aria-label=\"build: passing\""));
+    assert!(badge.contains("height=\"20\""));
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["badge", "--label", "build", "--message", "passing", "--color", "nope"])
+        .arg(&output);
+    cmd.assert().failure();
+}
+
+#[test]
+fn test_emoji_export() {
+    let temp_dir = tempdir().unwrap();
+    let output = temp_dir.path().join("party.png");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--emoji", "--seed", "4"]).arg(&output);
+    cmd.assert().success();
+
+    let data = fs::read(&output).unwrap();
+    assert_eq!(&data[0..8], &[137, 80, 78, 71, 13, 10, 26, 10]);
+    assert_eq!(u32::from_be_bytes(data[16..20].try_into().unwrap()), 128);
+    assert_eq!(u32::from_be_bytes(data[20..24].try_into().unwrap()), 128);
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--emoji", "--preset", "avatar"]).arg(&output);
+    cmd.assert().failure();
+
+    let svg = temp_dir.path().join("outlined.svg");
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--seed", "4", "--outline", "1.5", "--min-shape-cells", "3"]).arg(&svg);
+    cmd.assert().success();
+    assert!(fs::read_to_string(&svg).unwrap().contains("class=\"outlines\""));
+}
+
+#[test]
+fn test_lod_simplifies_small_renders() {
+    let temp_dir = tempdir().unwrap();
+    let render = |lod: &str| {
+        let output = temp_dir.path().join(format!("{}.svg", lod));
+        let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+        cmd.args(["--seed", "3", "--grid-size", "8", "--shapes", "6", "-w", "16", "-H", "16", "--lod", lod])
+            .arg(&output);
+        cmd.assert().success();
+        output
+    };
+
+    let off = fs::read_to_string(render("off")).unwrap();
+    let aggressive_path = render("aggressive");
+    let aggressive = fs::read_to_string(&aggressive_path).unwrap();
+    assert_ne!(off, aggressive);
+    assert!(aggressive.len() < off.len());
+
+    // Verify applies the same simplification
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.arg("verify")
+        .arg(&aggressive_path)
+        .args(["--seed", "3", "--grid-size", "8", "--shapes", "6", "-w", "16", "-H", "16", "--lod", "aggressive"]);
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--lod", "tiny"]).arg(temp_dir.path().join("x.svg"));
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown level of detail 'tiny'"));
+}
+
+#[test]
+fn test_simplify_outlines() {
+    let temp_dir = tempdir().unwrap();
+    let render = |name: &str, simplify: &str| {
+        let output = temp_dir.path().join(name);
+        let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+        cmd.args(["--seed", "6", "--grid-size", "8", "--pack", "--simplify", simplify])
+            .arg(&output);
+        cmd.assert().success();
+        fs::read_to_string(output).unwrap()
+    };
+
+    let exact = render("exact.svg", "0");
+    let simplified = render("simplified.svg", "1.5");
+    assert!(simplified.len() < exact.len());
+}
+
+#[test]
+fn test_rounded_and_smooth_boundaries() {
+    let temp_dir = tempdir().unwrap();
+    for flags in [&["--corner-radius", "6"][..], &["--smooth-boundary"][..]] {
+        let output = temp_dir.path().join("soft.png");
+        let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+        cmd.args(["--seed", "42", "--format", "png"]).args(flags).arg(&output);
+        cmd.assert().success();
+        assert!(fs::metadata(&output).unwrap().len() > 0);
+    }
+}
+
+#[test]
+fn test_border_ring() {
+    let temp_dir = tempdir().unwrap();
+    let output = temp_dir.path().join("framed.svg");
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--seed", "42", "--border", "4,#333333"]).arg(&output);
+    cmd.assert().success();
+    let content = fs::read_to_string(&output).unwrap();
+    assert!(content.contains("class=\"border\""));
+    assert!(content.contains("#333333"));
+
+    let mut cmd = Command::cargo_bin("hexlogogen").unwrap();
+    cmd.args(["--border", "thick"]).arg(&output);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid border 'thick'"));
+}
+
+#[test]
+fn test_mcp_command() {
+    let input = [
+        r#"{"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"protocolVersion": "2024-11-05", "capabilities": {}, "clientInfo": {"name": "test", "version": "1"}}}"#,
+        r#"{"jsonrpc": "2.0", "method": "notifications/initialized"}"#,
+        r#"{"jsonrpc": "2.0", "id": 2, "method": "tools/call", "params": {"name": "generate_logo", "arguments": {"seed": 42}}}"#,
+        "not json",
+    ]
+    .join("\n");
+
+    let output = Command::cargo_bin("hexlogogen")
+        .unwrap()
+        .arg("mcp")
+        .write_stdin(input)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // Standard output holds one JSON-RPC response per request and nothing else
+    let responses: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(responses.len(), 3);
+    assert_eq!(responses[0]["result"]["protocolVersion"], "2024-11-05");
+    assert_eq!(responses[1]["id"], 2);
+    assert!(responses[1]["result"]["content"][0]["text"].as_str().unwrap().contains("<svg"));
+    assert_eq!(responses[2]["error"]["code"], -32700);
+}
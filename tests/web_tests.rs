@@ -5,6 +5,7 @@ use axum::{
 use hexlogogen::generator::Generator;
 use hexlogogen::svg;
 use hexlogogen::web::routes;
+use hexlogogen::web::state::AppState;
 use tower::ServiceExt;
 use http_body_util::BodyExt;
 
@@ -57,10 +58,66 @@ async fn test_favicon_handler() {
     // Verify redirect location
     assert_eq!(
         response.headers().get("location").unwrap(),
-        "/assets/favicon.svg"
+        "/favicon.svg"
     );
 }
 
+#[tokio::test]
+async fn test_favicon_svg_handler() {
+    let state = AppState::new(1).with_instance_name("staging");
+    let app = routes::create_router_with_state(state.clone());
+
+    let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(get("/favicon.svg")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "image/svg+xml");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, state.favicon().as_bytes());
+
+    // Any name can be drawn, and the same name always gives the same icon
+    let response = app.clone().oneshot(get("/favicon.svg?name=production")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let production = response.into_body().collect().await.unwrap().to_bytes();
+    assert_ne!(production, body);
+    assert_eq!(production, svg::identicon("production", 64).unwrap().as_bytes());
+
+    let long = format!("/favicon.svg?name={}", "a".repeat(300));
+    let response = app.oneshot(get(&long)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_icon_handler() {
+    let app = routes::create_router();
+    let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    let response = app.clone().oneshot(get("/icon/payments-service.svg")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "image/svg+xml");
+    let etag = response.headers()["etag"].clone();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, svg::service_icon("payments-service", 64).unwrap().as_bytes());
+
+    // Names are URL-decoded and sizes are clamped
+    let response = app.clone().oneshot(get("/icon/Team%20Dashboard.svg?size=4")).await.unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, svg::service_icon("Team Dashboard", 16).unwrap().as_bytes());
+
+    let request = Request::builder()
+        .uri("/icon/payments-service.svg")
+        .header("if-none-match", etag)
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    let response = app.clone().oneshot(get("/icon/payments-service.png")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let long = format!("/icon/{}.svg", "a".repeat(300));
+    let response = app.oneshot(get(&long)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
 #[tokio::test]
 async fn test_svg_handler() {
     // Create router
@@ -131,4 +188,860 @@ async fn test_generate_handler() {
     
     assert!(json.get("seed").is_some());
     assert!(json["seed"].is_u64());
-}
\ No newline at end of file
+    assert!(json["token"].is_string());
+}
+
+/// Sends a JSON POST request and returns the status and parsed body, if any
+async fn post_json(app: &axum::Router, uri: &str, body: &str) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("Content-Type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_recolor_handler() {
+    let app = routes::create_router();
+
+    let (status, generated) = post_json(&app, "/generate", r#"{"grid_size": 3, "shapes": 3, "seed": 42, "overlap": false}"#).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(generated["shapes"][0]["area"].as_f64().unwrap() > 0.0);
+    assert!(generated["shapes"][0]["convexity"].as_f64().unwrap() <= 1.0);
+    let token = generated["token"].as_str().unwrap().to_string();
+    let recolor = format!("/api/design/{}/recolor", token);
+
+    // By index
+    let (status, json) = post_json(&app, &recolor, r#"{"shape_index": 1, "color": "rgb(1, 2, 3)"}"#).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["shape_index"], 1);
+    assert!(json["svg"].as_str().unwrap().contains("#010203"));
+    let edited = json["token"].as_str().unwrap().to_string();
+    assert_ne!(edited, token);
+
+    // By stable id
+    let (status, json) = post_json(&app, &recolor, r#"{"shape_id": 2, "color": "navy"}"#).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["shapes"][2]["color"], "#000080");
+
+    // By hit-testing a point on the first shape, starting from the edited design
+    let design = hexlogogen::generator::GeneratorConfig::new(3, 3, 0.8, Some(42))
+        .with_allow_overlap(false)
+        .generate()
+        .unwrap();
+    let cell = design.shapes()[0].cells[0];
+    let centroid = design.grid().get_cell_centroid(cell).unwrap();
+    let body = format!(r#"{{"point": [{}, {}], "color": "lime"}}"#, centroid.x, centroid.y);
+    let (status, json) = post_json(&app, &format!("/api/design/{}/recolor", edited), &body).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["shape_index"], design.shape_at(centroid).unwrap());
+    let svg = json["svg"].as_str().unwrap();
+    assert!(svg.contains("#00FF00") && svg.contains("#010203"));
+
+    // The edited design can be downloaded as a PNG
+    let png = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/design/{}/png?width=64&height=64", json["token"].as_str().unwrap()))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(png.status(), StatusCode::OK);
+    assert_eq!(png.headers()["content-type"], "image/png");
+
+    let (status, json) = post_json(&app, &recolor, r#"{"shape_index": 3, "color": "plaid"}"#).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(json["errors"][0]["field"], "color");
+    let (status, json) = post_json(&app, &recolor, r#"{"shape_index": 3, "color": "red"}"#).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(json["errors"][0]["field"], "shape_index");
+    let (status, json) = post_json(&app, &recolor, r#"{"point": [0, 150], "color": "red"}"#).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(json["errors"][0]["field"], "point");
+
+    let (status, _) = post_json(&app, "/api/design/0000000000000000/recolor", r#"{"shape_index": 0, "color": "red"}"#).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_reroll_handler() {
+    let app = routes::create_router();
+
+    let (status, generated) = post_json(&app, "/generate", r#"{"grid_size": 4, "shapes": 4, "seed": 8, "overlap": false}"#).await;
+    assert_eq!(status, StatusCode::OK);
+    let shapes = generated["shapes"].as_array().unwrap().clone();
+    assert_eq!(shapes.len(), 4);
+    assert_eq!(shapes[1]["id"], 1);
+    let reroll = format!("/api/design/{}/reroll", generated["token"].as_str().unwrap());
+
+    // Shapes are locked by id; the locked shape keeps its id and color
+    let (status, json) = post_json(&app, &reroll, r#"{"locked": [1], "seed": 3}"#).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["seed"], 3);
+    assert_eq!(json["shapes"][1], shapes[1]);
+    assert_eq!(json["shapes"][0]["id"], 4);
+    assert_ne!(json["token"], generated["token"]);
+    assert!(json["svg"].as_str().unwrap().starts_with("<svg"));
+
+    // Rerolling is repeatable for a given seed
+    let (_, again) = post_json(&app, &reroll, r#"{"locked": [1], "seed": 3}"#).await;
+    assert_eq!(again["token"], json["token"]);
+
+    let (status, json) = post_json(&app, &reroll, r#"{"locked": [9]}"#).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(json["errors"][0]["field"], "locked");
+}
+
+#[tokio::test]
+async fn test_png_handler() {
+    // Create router
+    let app = routes::create_router();
+    
+    // Request an opaque, non-square PNG
+    let request = Request::builder()
+        .uri("/png/12345?theme=mesos&grid_size=2&shapes=3&width=1024&height=256&transparent=false")
+        .body(Body::empty())
+        .unwrap();
+    
+    let response = app.oneshot(request).await.unwrap();
+    
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("content-type").unwrap(), "image/png");
+    
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let pixmap = resvg::tiny_skia::Pixmap::decode_png(&body).unwrap();
+    assert_eq!(pixmap.width(), 1024);
+    assert_eq!(pixmap.height(), 256);
+    
+    // The corner is outside the hexagon, so it only shows the background
+    assert_eq!(pixmap.pixel(0, 0).unwrap().alpha(), 255);
+}
+
+#[tokio::test]
+async fn test_png_handler_clamps_size() {
+    let app = routes::create_router();
+    
+    // Sizes outside 256-4096 are clamped and the background stays transparent by default
+    let request = Request::builder()
+        .uri("/png/12345?width=10&height=10")
+        .body(Body::empty())
+        .unwrap();
+    
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let pixmap = resvg::tiny_skia::Pixmap::decode_png(&body).unwrap();
+    assert_eq!(pixmap.width(), 256);
+    assert_eq!(pixmap.height(), 256);
+    assert_eq!(pixmap.pixel(0, 0).unwrap().alpha(), 0);
+}
+
+#[tokio::test]
+async fn test_svg_etag_is_canonical() {
+    let app = routes::create_router();
+
+    // Defaulted and reordered parameters describe the same design
+    let implicit = app
+        .clone()
+        .oneshot(Request::builder().uri("/svg/42").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let explicit = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/svg/42?overlap=true&theme=mesos&opacity=0.8&shapes=4&grid_size=4")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let other = app
+        .oneshot(
+            Request::builder()
+                .uri("/svg/42?theme=blues")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let etag = implicit.headers().get("etag").unwrap().clone();
+    assert_eq!(explicit.headers().get("etag").unwrap(), &etag);
+    assert_ne!(other.headers().get("etag").unwrap(), &etag);
+    assert_eq!(
+        implicit.headers().get("link").unwrap(),
+        "</svg/42?theme=mesos&grid_size=4&shapes=4&opacity=0.80&overlap=true>; rel=\"canonical\""
+    );
+}
+
+#[tokio::test]
+async fn test_design_code_route() {
+    let app = routes::create_router();
+    let code = hexlogogen::DesignParams {
+        seed: Some(42),
+        theme: "blues".to_string(),
+        ..hexlogogen::DesignParams::default()
+    };
+    let code = hexlogogen::DesignCode::from_params(&code).unwrap();
+
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri(format!("/d/{}", code)).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("link").unwrap(),
+        "</svg/42?theme=blues&grid_size=4&shapes=4&opacity=0.80&overlap=true>; rel=\"canonical\""
+    );
+
+    let response = app
+        .oneshot(Request::builder().uri("/d/HX1-zzzz").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_conditional_request_not_modified() {
+    let app = routes::create_router();
+
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/png/7?width=300").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let etag = response.headers().get("etag").unwrap().clone();
+
+    // A matching validator skips rendering entirely
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/png/7?width=300")
+                .header("if-none-match", etag.clone())
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers().get("etag").unwrap(), &etag);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(body.is_empty());
+
+    // Raster parameters are part of the validator
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/png/7?width=300&transparent=false")
+                .header("if-none-match", etag)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_params_header_and_metadata() {
+    let app = routes::create_router();
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/svg/42?shapes=3&theme=blues")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let expected = "seed=42&theme=blues&grid_size=4&shapes=3&opacity=0.80&overlap=true";
+    assert_eq!(response.headers().get(routes::PARAMS_HEADER).unwrap(), expected);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let svg_data = String::from_utf8(body.to_vec()).unwrap();
+    assert_eq!(svg::embedded_params(&svg_data).as_deref(), Some(expected));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/png/42?width=256&height=256")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        response.headers().get(routes::PARAMS_HEADER).unwrap(),
+        "seed=42&theme=mesos&grid_size=4&shapes=4&opacity=0.80&overlap=true&width=256&height=256&transparent=true"
+    );
+}
+
+fn batch_request(token: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder()
+        .method("POST")
+        .uri("/api/batch")
+        .header("Content-Type", "application/json");
+    if let Some(token) = token {
+        builder = builder.header("Authorization", format!("Bearer {}", token));
+    }
+    builder
+        .body(Body::from(r#"{"seeds": [1, 2], "theme": "blues"}"#))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_batch_without_configured_tokens() {
+    let app = routes::create_router();
+
+    let response = app.oneshot(batch_request(None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let items: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(items.as_array().unwrap().len(), 2);
+    assert_eq!(items[1]["seed"], 2);
+    assert!(items[0]["params"].as_str().unwrap().contains("theme=blues"));
+}
+
+#[tokio::test]
+async fn test_api_token_required() {
+    let state = AppState::new(2).with_api_tokens(["s3cret".to_string()]);
+    let app = routes::create_router_with_state(state);
+
+    let missing = app.clone().oneshot(batch_request(None)).await.unwrap();
+    assert_eq!(missing.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(missing.headers().get("www-authenticate").unwrap(), "Bearer");
+
+    let wrong = app.clone().oneshot(batch_request(Some("guess"))).await.unwrap();
+    assert_eq!(wrong.status(), StatusCode::UNAUTHORIZED);
+
+    let valid = app.clone().oneshot(batch_request(Some("s3cret"))).await.unwrap();
+    assert_eq!(valid.status(), StatusCode::OK);
+
+    // Read endpoints stay public
+    let public = app
+        .oneshot(Request::builder().uri("/svg/42").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(public.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_generate_validation_errors() {
+    let app = routes::create_router();
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/generate")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    r#"{"grid_size": 20, "opacity": -1, "theme": "neon", "seed": "12ab", "algo_version": 9}"#,
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let fields: Vec<&str> = json["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|error| error["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["theme", "grid_size", "opacity", "seed", "algo_version"]);
+
+    // Batch requests share the same validation
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/batch")
+                .header("Content-Type", "application/json")
+                .body(Body::from(r#"{"seeds": [], "shapes": 0, "algo_version": "v9"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["errors"][0]["field"], "seeds");
+    assert_eq!(json["errors"][1]["field"], "shapes");
+    assert_eq!(json["errors"][2]["field"], "algo_version");
+
+    // Query strings are lenient, but an unknown algorithm version is still an error
+    for uri in ["/svg/1?algo_version=9", "/png/1?algo_version=9"] {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", uri);
+    }
+    let request = Request::builder().uri("/svg/1?algo_version=1").body(Body::empty()).unwrap();
+    assert_eq!(app.oneshot(request).await.unwrap().status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_config_reload() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("hexweb.toml");
+    std::fs::write(
+        &path,
+        "api_tokens = [\"ops\"]\n\n[themes]\nweb-reload-corp = [\"#0B3D91\", \"#FC3D21\"]\n\n[presets.dashboard]\ntheme = \"web-reload-corp\"\nshapes = 2\n",
+    )
+    .unwrap();
+    let state = AppState::new(2).with_config_file(&path).unwrap();
+    let app = routes::create_router_with_state(state);
+    let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let reload = |token: &str| {
+        Request::builder()
+            .method("POST")
+            .uri("/api/reload")
+            .header("Authorization", format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    // Presets fill in what the request leaves out
+    let response = app.clone().oneshot(get("/svg/7?preset=dashboard")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()["x-hexalith-params"]
+        .to_str()
+        .unwrap()
+        .contains("theme=web-reload-corp&grid_size=4&shapes=2"));
+    let response = app.clone().oneshot(get("/svg/7?preset=wallboard")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Reloading is a protected endpoint
+    let response = app.clone().oneshot(reload("guess")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    std::fs::write(
+        &path,
+        "api_tokens = [\"ops\"]\n\n[presets.wallboard]\ntheme = \"blues\"\n\n[limits]\nmax_batch_seeds = 1\n",
+    )
+    .unwrap();
+    let response = app.clone().oneshot(reload("ops")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["changed"], true);
+    assert_eq!(json["presets"], serde_json::json!(["wallboard"]));
+
+    let response = app.clone().oneshot(get("/svg/7?preset=wallboard")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let batch = Request::builder()
+        .method("POST")
+        .uri("/api/batch")
+        .header("Authorization", "Bearer ops")
+        .body(Body::from(r#"{"seeds": [1, 2]}"#))
+        .unwrap();
+    let response = app.clone().oneshot(batch).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // A broken edit is refused and the running config stays
+    std::fs::write(&path, "[limits]\nmax_batch_seeds = 0\n").unwrap();
+    let response = app.clone().oneshot(reload("ops")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let response = app.oneshot(get("/svg/7?preset=wallboard")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = routes::create_router().oneshot(reload("ops")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_graceful_shutdown() {
+    use axum::routing::get;
+    use hexlogogen::web::shutdown;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+    use tokio::sync::Notify;
+
+    // The handler signals once the request is in flight, so shutdown starts mid-request
+    let app = |started: Arc<Notify>| {
+        let slow = move || async move {
+            started.notify_one();
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            "done"
+        };
+        axum::Router::new().route("/slow", get(slow))
+    };
+
+    // A request that is running when shutdown starts still gets its response
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/slow", listener.local_addr().unwrap());
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    let started = Arc::new(Notify::new());
+    let router = app(started.clone());
+    let server = tokio::spawn(async move {
+        let shutdown = async {
+            let _ = stopped.await;
+        };
+        let result = shutdown::serve(listener, router, Duration::from_secs(5), shutdown).await;
+        result.map_err(|e| e.to_string())
+    });
+    let request = tokio::spawn(reqwest::get(url.clone()));
+    started.notified().await;
+    stop.send(()).unwrap();
+
+    let response = request.await.unwrap().unwrap();
+    assert_eq!(response.text().await.unwrap(), "done");
+    server.await.unwrap().unwrap();
+    assert!(reqwest::get(url).await.is_err());
+
+    // Requests outlasting the drain timeout don't hold up the shutdown
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/slow", listener.local_addr().unwrap());
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    let started = Arc::new(Notify::new());
+    let router = app(started.clone());
+    let server = tokio::spawn(async move {
+        let shutdown = async {
+            let _ = stopped.await;
+        };
+        let result = shutdown::serve(listener, router, Duration::from_millis(50), shutdown).await;
+        result.map_err(|e| e.to_string())
+    });
+    let _request = tokio::spawn(reqwest::get(url));
+    started.notified().await;
+    let stopping = Instant::now();
+    stop.send(()).unwrap();
+    server.await.unwrap().unwrap();
+    assert!(stopping.elapsed() < Duration::from_millis(250));
+}
+
+#[tokio::test]
+async fn test_compression() {
+    use std::io::Read;
+
+    let app = routes::create_router();
+    let varies_by_encoding = |headers: &axum::http::HeaderMap| {
+        headers.get_all("vary").iter().any(|value| value == "accept-encoding")
+    };
+    let get = |uri: &str, encoding: &str| {
+        Request::builder()
+            .uri(uri)
+            .header("accept-encoding", encoding)
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let response = app.clone().oneshot(get("/svg/12345", "identity")).await.unwrap();
+    assert!(response.headers().get("content-encoding").is_none());
+    assert!(varies_by_encoding(response.headers()));
+    let plain = response.into_body().collect().await.unwrap().to_bytes();
+
+    let response = app.clone().oneshot(get("/svg/12345", "gzip")).await.unwrap();
+    assert_eq!(response.headers()["content-encoding"], "gzip");
+    assert!(varies_by_encoding(response.headers()));
+    let compressed = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(compressed.len() < plain.len());
+    let mut decoded = Vec::new();
+    flate2::read::GzDecoder::new(&compressed[..]).read_to_end(&mut decoded).unwrap();
+    assert_eq!(decoded, plain);
+
+    let response = app.clone().oneshot(get("/svg/12345", "br;q=1.0, gzip;q=0.5")).await.unwrap();
+    assert_eq!(response.headers()["content-encoding"], "br");
+
+    // PNGs are already compressed
+    let response = app.oneshot(get("/png/12345", "gzip, br")).await.unwrap();
+    assert!(response.headers().get("content-encoding").is_none());
+    assert!(!varies_by_encoding(response.headers()));
+}
+
+#[tokio::test]
+async fn test_cache_policy_per_route() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("hexweb.toml");
+    std::fs::write(&path, "[presets.dashboard]\nshapes = 2\n").unwrap();
+    let app = routes::create_router_with_state(AppState::new(2).with_config_file(&path).unwrap());
+    let request = |method: &str, uri: &str, body: &str| {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+    let immutable = "public, max-age=31536000, immutable";
+    let preview = "public, max-age=300";
+
+    for (method, uri, body, cache_control) in [
+        ("GET", "/svg/12345?theme=blues", "", immutable),
+        ("GET", "/png/12345", "", immutable),
+        ("GET", "/icon/auth.svg", "", immutable),
+        ("GET", "/favicon.svg?name=auth", "", immutable),
+        ("GET", "/svg/12345?preset=dashboard", "", preview),
+        ("GET", "/svg/12345?theme=unconfigured", "", preview),
+        ("GET", "/", "", preview),
+        ("GET", "/favicon.svg", "", "no-store"),
+        ("POST", "/generate", r#"{"seed": "5"}"#, "no-store"),
+        ("POST", "/api/batch", r#"{"seeds": [1]}"#, "no-store"),
+        ("GET", "/svg/12345?preset=missing", "", "no-store"),
+    ] {
+        let response = app.clone().oneshot(request(method, uri, body)).await.unwrap();
+        assert_eq!(response.headers()["cache-control"], cache_control, "{} {}", method, uri);
+
+        // Immutable images get strong tags, previews weak ones
+        let etag = response.headers().get("etag").map(|etag| etag.to_str().unwrap().to_string());
+        if cache_control == immutable {
+            assert!(etag.unwrap().starts_with('"'), "{}", uri);
+        } else if cache_control == preview && uri.starts_with("/svg") {
+            assert!(etag.unwrap().starts_with("W/"), "{}", uri);
+        }
+    }
+
+    // Compressed bodies differ byte for byte, so their tags are weak
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/svg/12345?theme=blues")
+                .header("accept-encoding", "gzip")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.headers()["content-encoding"], "gzip");
+    assert!(response.headers()["etag"].to_str().unwrap().starts_with("W/\""));
+}
+
+#[tokio::test]
+async fn test_reloaded_palette_changes_etag() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("hexweb.toml");
+    let write_config = |from: &str, to: &str| {
+        std::fs::write(
+            &path,
+            format!(
+                "[themes]\nweb-etag-corp = [\"{0}\", \"{1}\"]\n\n[tenants.web-etag]\npalette = [\"{0}\", \"{1}\"]\n",
+                from, to
+            ),
+        )
+        .unwrap()
+    };
+    write_config("#0B3D91", "#FC3D21");
+    let state = AppState::new(2).with_config_file(&path).unwrap();
+    let app = routes::create_router_with_state(state.clone());
+    let get = |uri: &str, etag: Option<&axum::http::HeaderValue>| {
+        let request = Request::builder().uri(uri);
+        let request = match etag {
+            Some(etag) => request.header("if-none-match", etag),
+            None => request,
+        };
+        request.body(Body::empty()).unwrap()
+    };
+
+    let uris = ["/svg/7?theme=web-etag-corp", "/png/7?theme=web-etag-corp", "/t/web-etag/svg/7"];
+    let mut etags = Vec::new();
+    for uri in uris {
+        let response = app.clone().oneshot(get(uri, None)).await.unwrap();
+        let etag = response.headers()["etag"].clone();
+        let response = app.clone().oneshot(get(uri, Some(&etag))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED, "{}", uri);
+        etags.push(etag);
+    }
+
+    // The names stay, but the colors behind them change
+    write_config("#2E7D32", "#F9A825");
+    assert!(state.reload().unwrap());
+    for (uri, etag) in uris.iter().zip(&etags) {
+        let response = app.clone().oneshot(get(uri, Some(etag))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+        assert_ne!(&response.headers()["etag"], etag, "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn test_svg_from_text() {
+    let app = routes::create_router();
+    let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    let response = app.clone().oneshot(get("/svg/from/Payments%20Service?theme=blues")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let seed = hexlogogen::utils::text_to_seed("payments service");
+    let link = response.headers()["link"].to_str().unwrap().to_string();
+    assert!(link.starts_with(&format!("</svg/{}?theme=blues&", seed)), "{}", link);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+
+    // Case and spacing don't change the design, and the seed URL serves the same image
+    for uri in [
+        "/svg/from/payments%20%20service?theme=blues".to_string(),
+        format!("/svg/{}?theme=blues", seed),
+    ] {
+        let response = app.clone().oneshot(get(&uri)).await.unwrap();
+        assert_eq!(response.into_body().collect().await.unwrap().to_bytes(), body, "{}", uri);
+    }
+
+    let response = app.clone().oneshot(get("/svg/from/payments-service")).await.unwrap();
+    assert_ne!(response.into_body().collect().await.unwrap().to_bytes(), body);
+
+    let response = app.oneshot(get("/svg/from/%20")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_tenants() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("mark.svg"),
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"><rect width="4" height="10"/></svg>"#,
+    )
+    .unwrap();
+    let path = dir.path().join("hexweb.toml");
+    std::fs::write(
+        &path,
+        "[tenants.web-acme]\npalette = [\"#E4002B\", \"#1D1D1B\"]\nsilhouette = \"mark.svg\"\nrequests_per_minute = 3\n\n[tenants.web-globex]\ntheme = \"greens\"\n",
+    )
+    .unwrap();
+    let state = AppState::new(2).with_config_file(&path).unwrap();
+    let app = routes::create_router_with_state(state);
+    let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    // Each tenant gets its own default theme, under its own canonical URL
+    let response = app.clone().oneshot(get("/t/web-globex/svg/7")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()["link"]
+        .to_str()
+        .unwrap()
+        .starts_with("</t/web-globex/svg/7?theme=greens&"));
+    assert_eq!(response.headers()["cache-control"], "public, max-age=300");
+    let tenant_etag = response.headers()["etag"].clone();
+    let response = app.clone().oneshot(get("/svg/7?theme=greens")).await.unwrap();
+    assert_ne!(response.headers()["etag"], tenant_etag);
+
+    // A palette is the tenant's theme, and its silhouette changes the shapes
+    let response = app.clone().oneshot(get("/t/web-acme/svg/7")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let params = response.headers()["x-hexalith-params"].to_str().unwrap().to_string();
+    assert!(params.contains("theme=web-acme&") && params.contains("&guide="), "{}", params);
+    let response = app.clone().oneshot(get("/t/web-acme/png/7?theme=blues")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()["x-hexalith-params"].to_str().unwrap().contains("theme=blues"));
+    let response = app.clone().oneshot(get("/t/web-acme/svg/from/checkout")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The fourth request in a minute is over web-acme's limit, but not other tenants'
+    let response = app.clone().oneshot(get("/t/web-acme/svg/8")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.headers().contains_key("retry-after"));
+    let response = app.clone().oneshot(get("/t/web-globex/svg/8")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.oneshot(get("/t/initech/svg/7")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_admin_page() {
+    let app = routes::create_router_with_state(AppState::new(2).with_api_tokens(["ops".to_string()]));
+    let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    for uri in ["/svg/31337", "/svg/31337?theme=blues", "/png/31337"] {
+        let response = app.clone().oneshot(get(uri)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = app.clone().oneshot(get("/admin")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let admin = Request::builder()
+        .uri("/admin")
+        .header("Authorization", "Bearer ops")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(admin).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()["content-type"].to_str().unwrap().starts_with("text/html"));
+    assert_eq!(response.headers()["cache-control"], "no-store");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("Over the last 3 renders"), "{}", html);
+    assert!(html.contains("<a href=\"/svg/31337\">31337</a>"));
+    assert!(html.contains("/png/31337?theme=mesos"));
+}
+
+#[tokio::test]
+async fn test_jobs() {
+    let app = routes::create_router_with_state(AppState::new(2).with_api_tokens(["ops".to_string()]));
+    let request = |method: &str, uri: &str, body: &str| {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("Authorization", "Bearer ops")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+    let json = |bytes: &[u8]| serde_json::from_slice::<serde_json::Value>(bytes).unwrap();
+
+    let response = app
+        .clone()
+        .oneshot(request("POST", "/api/jobs", r#"{"kind": "wallpaper", "seed": 7, "resolution": "640x360", "count": 2, "format": "svg"}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let location = response.headers()["location"].to_str().unwrap().to_string();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let id = json(&body)["id"].as_str().unwrap().to_string();
+    assert_eq!(location, format!("/api/jobs/{}", id));
+
+    // The job runs in the background while its status is polled
+    let mut status = serde_json::Value::Null;
+    for _ in 0..500 {
+        let response = app.clone().oneshot(request("GET", &location, "")).await.unwrap();
+        status = json(&response.into_body().collect().await.unwrap().to_bytes());
+        if status["state"] == "done" || status["state"] == "failed" {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(status["state"], "done", "{}", status);
+    assert_eq!(status["kind"], "wallpaper");
+
+    let response = app.clone().oneshot(request("GET", &format!("{}/result", location), "")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "image/svg+xml");
+    assert_eq!(response.headers()["content-disposition"], "attachment; filename=\"wallpaper-7.svg\"");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(String::from_utf8_lossy(&body).contains("width=\"640\""));
+
+    let response = app.clone().oneshot(request("POST", "/api/jobs", r#"{"kind": "video"}"#)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let response = app.clone().oneshot(request("GET", "/api/jobs/missing", "")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let anonymous = Request::builder()
+        .method("POST")
+        .uri("/api/jobs")
+        .body(Body::from(r#"{"kind": "batch", "seeds": [1]}"#))
+        .unwrap();
+    let response = app.oneshot(anonymous).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}